@@ -1,15 +1,21 @@
 use anchor_client::{
-    anchor_lang::{prelude::Pubkey, system_program::System, AccountDeserialize, Id},
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        system_program::System,
+        AccountDeserialize, Id, InstructionData, ToAccountMetas,
+    },
     solana_sdk::{
+        instruction::Instruction,
         rent::Rent,
         signature::{Keypair, Signer},
         sysvar::SysvarId,
     },
 };
 use anchor_spl::token::TokenAccount;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use hyperplane::{
-    ix::{Initialize, UpdatePoolConfig},
+    curve::calculator::AorB,
+    ix::{Initialize, UpdateCurveConfig, UpdatePoolConfig},
     state::SwapPool,
     utils::seeds::{pda, pda::InitPoolPdas},
     InitialSupply,
@@ -60,6 +66,7 @@ where
         admin: Pubkey,
         admin_token_a_ata: Pubkey,
         admin_token_b_ata: Pubkey,
+        pool_token_program: Pubkey,
         Initialize {
             fees,
             curve_parameters,
@@ -68,6 +75,7 @@ where
                     initial_supply_a,
                     initial_supply_b,
                 },
+            pool_token_metadata,
         }: Initialize,
     ) -> Result<Pubkey> {
         let pool_kp = Keypair::new();
@@ -96,6 +104,8 @@ where
             pool_token_mint,
             token_a_fees_vault,
             token_b_fees_vault,
+            token_a_protocol_fees_vault,
+            token_b_protocol_fees_vault,
         } = pda::init_pool_pdas_program_id(
             &self.config.program_id,
             &pool_kp.pubkey(),
@@ -112,13 +122,14 @@ where
                 .await?,
         );
 
-        let pool_token_program = spl_token::id();
+        info!("Pool token program: {}", pool_token_program);
 
         tx = tx.add_anchor_ix(
             &self.config.program_id,
             hyperplane::accounts::InitializePool {
                 admin,
                 pool: pool_kp.pubkey(),
+                global_config: pda::global_config_pda_program_id(&self.config.program_id).0,
                 swap_curve: curve,
                 pool_authority: authority,
                 token_a_mint,
@@ -128,6 +139,8 @@ where
                 pool_token_mint,
                 token_a_fees_vault,
                 token_b_fees_vault,
+                token_a_protocol_fees_vault,
+                token_b_protocol_fees_vault,
                 admin_token_a_ata,
                 admin_token_b_ata,
                 admin_pool_token_ata: admin_pool_token_ata.pubkey(),
@@ -142,6 +155,7 @@ where
                 initial_supply_b,
                 fees,
                 curve_parameters,
+                pool_token_metadata,
             },
         );
 
@@ -177,6 +191,7 @@ where
             source_vault,
             destination_vault,
             source_token_fees_vault,
+            source_token_protocol_fees_vault,
             source_user_ata,
             destination_user_ata,
         ) = if a_to_b {
@@ -186,6 +201,7 @@ where
                 pool.token_a_vault,
                 pool.token_b_vault,
                 pool.token_a_fees_vault,
+                pool.protocol_fees_vault(AorB::A),
                 admin_token_a_ata,
                 admin_token_b_ata,
             )
@@ -196,35 +212,59 @@ where
                 pool.token_b_vault,
                 pool.token_a_vault,
                 pool.token_b_fees_vault,
+                pool.protocol_fees_vault(AorB::B),
                 admin_token_b_ata,
                 admin_token_a_ata,
             )
         };
-        let mut tx = self.client.tx_builder();
+        let remaining_accounts = self
+            .transfer_hooks_accounts(&[
+                (
+                    source_user_ata,
+                    source_mint,
+                    source_vault,
+                    admin.pubkey(),
+                    amount_in,
+                ),
+                (
+                    destination_vault,
+                    destination_mint,
+                    destination_user_ata,
+                    pool.pool_authority,
+                    0,
+                ),
+            ])
+            .await?;
+        let mut accounts = hyperplane::accounts::Swap {
+            signer: admin.pubkey(),
+            pool: pool_pubkey,
+            global_config: pda::global_config_pda_program_id(&self.config.program_id).0,
+            swap_curve: pool.swap_curve,
+            pool_authority: pool.pool_authority,
+            source_mint,
+            destination_mint,
+            source_vault,
+            destination_vault,
+            source_token_fees_vault,
+            source_token_protocol_fees_vault,
+            source_user_ata,
+            destination_user_ata,
+            source_token_host_fees_account: None,
+            source_token_program: token_a_token_program,
+            destination_token_program: token_b_token_program,
+        }
+        .to_account_metas(None);
+        accounts.extend(remaining_accounts);
 
-        tx = tx.add_anchor_ix(
-            &self.config.program_id,
-            hyperplane::accounts::Swap {
-                signer: admin.pubkey(),
-                pool: pool_pubkey,
-                swap_curve: pool.swap_curve,
-                pool_authority: pool.pool_authority,
-                source_mint,
-                destination_mint,
-                source_vault,
-                destination_vault,
-                source_token_fees_vault,
-                source_user_ata,
-                destination_user_ata,
-                source_token_host_fees_account: None,
-                source_token_program: token_a_token_program,
-                destination_token_program: token_b_token_program,
-            },
-            hyperplane::instruction::Swap {
+        let tx = self.client.tx_builder().add_ix(Instruction {
+            program_id: self.config.program_id,
+            accounts,
+            data: hyperplane::instruction::Swap {
                 amount_in,
                 minimum_amount_out: 0,
-            },
-        );
+            }
+            .data(),
+        });
 
         send_tx!(self, tx, []);
 
@@ -240,7 +280,11 @@ where
         // let swap_pool: SwapPool = self.client.get_anchor_account(&pool).await?;
         let tx = self.client.tx_builder().add_anchor_ix(
             &self.config.program_id,
-            hyperplane::accounts::UpdatePoolConfig { admin, pool },
+            hyperplane::accounts::UpdatePoolConfig {
+                admin,
+                pool,
+                global_config: pda::global_config_pda_program_id(&self.config.program_id).0,
+            },
             hyperplane::instruction::UpdatePoolConfig::from(update),
         );
         send_tx!(self, tx, []);
@@ -248,6 +292,194 @@ where
         Ok(())
     }
 
+    pub async fn update_curve_config(
+        &self,
+        admin: Pubkey,
+        pool: Pubkey,
+        swap_curve: Pubkey,
+        update: UpdateCurveConfig,
+    ) -> Result<()> {
+        let tx = self.client.tx_builder().add_anchor_ix(
+            &self.config.program_id,
+            hyperplane::accounts::UpdateCurveConfig {
+                admin,
+                pool,
+                swap_curve,
+            },
+            hyperplane::instruction::UpdateCurveConfig::from(update),
+        );
+        send_tx!(self, tx, []);
+
+        Ok(())
+    }
+
+    pub async fn propose_new_admin(
+        &self,
+        admin: Pubkey,
+        pool: Pubkey,
+        new_admin: Pubkey,
+    ) -> Result<()> {
+        let tx = self.client.tx_builder().add_anchor_ix(
+            &self.config.program_id,
+            hyperplane::accounts::ProposeNewAdmin { admin, pool },
+            hyperplane::instruction::ProposeNewAdmin { new_admin },
+        );
+        send_tx!(self, tx, []);
+
+        Ok(())
+    }
+
+    pub async fn accept_admin(&self, pending_admin: Pubkey, pool: Pubkey) -> Result<()> {
+        let tx = self.client.tx_builder().add_anchor_ix(
+            &self.config.program_id,
+            hyperplane::accounts::AcceptAdmin {
+                pending_admin,
+                pool,
+            },
+            hyperplane::instruction::AcceptAdmin {},
+        );
+        send_tx!(self, tx, []);
+
+        Ok(())
+    }
+
+    pub async fn ramp_amp(
+        &self,
+        admin: Pubkey,
+        pool: Pubkey,
+        swap_curve: Pubkey,
+        target_amp: u64,
+        ramp_end_ts: u64,
+    ) -> Result<()> {
+        let tx = self.client.tx_builder().add_anchor_ix(
+            &self.config.program_id,
+            hyperplane::accounts::RampAmp {
+                admin,
+                pool,
+                swap_curve,
+            },
+            hyperplane::instruction::RampAmp {
+                target_amp,
+                ramp_end_ts,
+            },
+        );
+        send_tx!(self, tx, []);
+
+        Ok(())
+    }
+
+    pub async fn stop_ramp_amp(
+        &self,
+        admin: Pubkey,
+        pool: Pubkey,
+        swap_curve: Pubkey,
+    ) -> Result<()> {
+        let tx = self.client.tx_builder().add_anchor_ix(
+            &self.config.program_id,
+            hyperplane::accounts::StopRampAmp {
+                admin,
+                pool,
+                swap_curve,
+            },
+            hyperplane::instruction::StopRampAmp {},
+        );
+        send_tx!(self, tx, []);
+
+        Ok(())
+    }
+
+    pub async fn close_pool(
+        &self,
+        admin: Pubkey,
+        pool_pubkey: Pubkey,
+        pool: &SwapPool,
+        admin_token_a_ata: Pubkey,
+        admin_token_b_ata: Pubkey,
+    ) -> Result<()> {
+        let pool_token_program = self
+            .client
+            .client
+            .get_account(&pool.pool_token_mint)
+            .await?
+            .owner;
+        let tx = self.client.tx_builder().add_anchor_ix(
+            &self.config.program_id,
+            hyperplane::accounts::ClosePool {
+                admin,
+                pool: pool_pubkey,
+                swap_curve: pool.swap_curve,
+                pool_authority: pool.pool_authority,
+                token_a_mint: pool.token_a_mint,
+                token_b_mint: pool.token_b_mint,
+                token_a_vault: pool.token_a_vault,
+                token_b_vault: pool.token_b_vault,
+                pool_token_mint: pool.pool_token_mint,
+                token_a_fees_vault: pool.token_a_fees_vault,
+                token_b_fees_vault: pool.token_b_fees_vault,
+                token_a_protocol_fees_vault: pool.protocol_fees_vault(AorB::A),
+                token_b_protocol_fees_vault: pool.protocol_fees_vault(AorB::B),
+                admin_token_a_ata,
+                admin_token_b_ata,
+                token_a_token_program: pool.token_a_program,
+                token_b_token_program: pool.token_b_program,
+                pool_token_program,
+            },
+            hyperplane::instruction::ClosePool {},
+        );
+        send_tx!(self, tx, []);
+
+        Ok(())
+    }
+
+    /// Accounts required by the transfer hook of `mint` for a transfer, passed to the pool
+    /// instructions as remaining accounts. Empty if the mint has no transfer hook.
+    pub async fn transfer_hook_accounts(
+        &self,
+        source: Pubkey,
+        mint: Pubkey,
+        destination: Pubkey,
+        authority: Pubkey,
+        amount: u64,
+    ) -> Result<Vec<AccountMeta>> {
+        hyperplane::ix::transfer_hook_accounts(
+            |address| async move {
+                Ok(self
+                    .client
+                    .client
+                    .get_account(&address)
+                    .await
+                    .ok()
+                    .map(|account| account.data))
+            },
+            &source,
+            &mint,
+            &destination,
+            &authority,
+            amount,
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to resolve the transfer hook accounts of {mint}: {e}"))
+    }
+
+    /// Transfer hook accounts of all the `(source, mint, destination, authority, amount)` transfers
+    /// of an instruction
+    async fn transfer_hooks_accounts(
+        &self,
+        transfers: &[(Pubkey, Pubkey, Pubkey, Pubkey, u64)],
+    ) -> Result<Vec<AccountMeta>> {
+        let mut remaining_accounts = vec![];
+        for (source, mint, destination, authority, amount) in transfers {
+            let transfer_hook_accounts = self
+                .transfer_hook_accounts(*source, *mint, *destination, *authority, *amount)
+                .await?;
+            hyperplane::ix::merge_remaining_accounts(
+                &mut remaining_accounts,
+                &transfer_hook_accounts,
+            );
+        }
+        Ok(remaining_accounts)
+    }
+
     /// Get an the rpc instance used by the KLendClient
     pub fn get_rpc(&self) -> &T {
         &self.client.client
@@ -9,13 +9,15 @@ use anchor_client::{
         signature::{Keypair, Signer},
     },
 };
+use anchor_spl::token_2022::spl_token_2022;
 use anyhow::Result;
 use hyperplane::{
     curve::{base::CurveType, calculator::CurveCalculator},
-    ix::Initialize,
+    ix::{Initialize, UpdateCurveConfig},
     state::{
-        ConstantPriceCurve, ConstantProductCurve, OffsetCurve, StableCurve, SwapPool,
-        UpdatePoolConfigMode,
+        ConcentratedCurve, ConstantPriceCurve, ConstantProductCurve, OffsetCurve, OraclePriceCurve,
+        StableCurve, SwapPool, UpdateCurveConfigMode, UpdateCurveConfigValue, UpdatePoolConfigMode,
+        WeightedCurve,
     },
 };
 use orbit_link::async_client::AsyncClient;
@@ -155,16 +157,23 @@ pub async fn initialize_pool<T: AsyncClient, S: Signer>(
         .unwrap_or_else(|| ata::get_associated_token_address(&admin, &token_a_mint));
     let admin_token_b_ata = admin_token_b_ata
         .unwrap_or_else(|| ata::get_associated_token_address(&admin, &token_b_mint));
+    let pool_token_program = if config.pool_token_2022 {
+        spl_token_2022::id()
+    } else {
+        spl_token::id()
+    };
 
     hyperplane
         .initialize_pool(
             admin,
             admin_token_a_ata,
             admin_token_b_ata,
+            pool_token_program,
             Initialize {
                 fees: config.fees,
                 curve_parameters: config.curve,
                 initial_supply: config.initial_supply,
+                pool_token_metadata: config.pool_token_metadata,
             },
         )
         .await?;
@@ -211,6 +220,100 @@ pub async fn update_pool<T: AsyncClient, S: Signer>(
     Ok(())
 }
 
+pub async fn update_curve<T: AsyncClient, S: Signer>(
+    hyperplane: &HyperplaneClient<T, S>,
+    admin: Pubkey,
+    pool_pubkey: Pubkey,
+    mode: UpdateCurveConfigMode,
+    value: u64,
+) -> Result<()> {
+    let pool: SwapPool = hyperplane.client.get_anchor_account(&pool_pubkey).await?;
+    hyperplane
+        .update_curve_config(
+            admin,
+            pool_pubkey,
+            pool.swap_curve,
+            UpdateCurveConfig::new(mode, UpdateCurveConfigValue::U64(value)),
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn propose_new_admin<T: AsyncClient, S: Signer>(
+    hyperplane: &HyperplaneClient<T, S>,
+    admin: Pubkey,
+    pool: Pubkey,
+    new_admin: Pubkey,
+) -> Result<()> {
+    hyperplane.propose_new_admin(admin, pool, new_admin).await?;
+    Ok(())
+}
+
+pub async fn accept_admin<T: AsyncClient, S: Signer>(
+    hyperplane: &HyperplaneClient<T, S>,
+    pending_admin: Pubkey,
+    pool: Pubkey,
+) -> Result<()> {
+    hyperplane.accept_admin(pending_admin, pool).await?;
+    Ok(())
+}
+
+pub async fn ramp_amp<T: AsyncClient, S: Signer>(
+    hyperplane: &HyperplaneClient<T, S>,
+    admin: Pubkey,
+    pool_pubkey: Pubkey,
+    target_amp: u64,
+    ramp_end_ts: u64,
+) -> Result<()> {
+    let pool: SwapPool = hyperplane.client.get_anchor_account(&pool_pubkey).await?;
+    hyperplane
+        .ramp_amp(admin, pool_pubkey, pool.swap_curve, target_amp, ramp_end_ts)
+        .await?;
+    Ok(())
+}
+
+pub async fn stop_ramp_amp<T: AsyncClient, S: Signer>(
+    hyperplane: &HyperplaneClient<T, S>,
+    admin: Pubkey,
+    pool_pubkey: Pubkey,
+) -> Result<()> {
+    let pool: SwapPool = hyperplane.client.get_anchor_account(&pool_pubkey).await?;
+    hyperplane
+        .stop_ramp_amp(admin, pool_pubkey, pool.swap_curve)
+        .await?;
+    Ok(())
+}
+
+pub async fn close_pool<T: AsyncClient, S: Signer>(
+    hyperplane: &HyperplaneClient<T, S>,
+    admin: Pubkey,
+    pool_pubkey: Pubkey,
+) -> Result<()> {
+    let pool: SwapPool = hyperplane.client.get_anchor_account(&pool_pubkey).await?;
+
+    let admin_token_a_ata = ata::get_associated_token_address_with_program_id(
+        &admin,
+        &pool.token_a_mint,
+        &pool.token_a_program,
+    );
+    let admin_token_b_ata = ata::get_associated_token_address_with_program_id(
+        &admin,
+        &pool.token_b_mint,
+        &pool.token_b_program,
+    );
+
+    hyperplane
+        .close_pool(
+            admin,
+            pool_pubkey,
+            &pool,
+            admin_token_a_ata,
+            admin_token_b_ata,
+        )
+        .await?;
+    Ok(())
+}
+
 pub async fn print_pool<T: AsyncClient, S: Signer>(
     hyperplane: &HyperplaneClient<T, S>,
     pool_pubkey: Pubkey,
@@ -241,6 +344,24 @@ pub async fn print_pool<T: AsyncClient, S: Signer>(
                 .get_anchor_account::<OffsetCurve>(&pool.swap_curve)
                 .await?,
         ),
+        CurveType::OraclePrice => Box::new(
+            hyperplane
+                .client
+                .get_anchor_account::<OraclePriceCurve>(&pool.swap_curve)
+                .await?,
+        ),
+        CurveType::Concentrated => Box::new(
+            hyperplane
+                .client
+                .get_anchor_account::<ConcentratedCurve>(&pool.swap_curve)
+                .await?,
+        ),
+        CurveType::Weighted => Box::new(
+            hyperplane
+                .client
+                .get_anchor_account::<WeightedCurve>(&pool.swap_curve)
+                .await?,
+        ),
     };
     info!("\x1b[32mPool {}:\x1b\n\n{:#?}\n\n", pool_pubkey, pool);
     info!("\x1b[32mCurve {}:\x1b\n\n{:#?}\n\n", pool.swap_curve, curve);
@@ -3,54 +3,145 @@ use hyperplane::state::{UpdatePoolConfigMode, UpdatePoolConfigValue};
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum PoolConfigValue {
     WithdrawalsOnly(bool),
+    TradeFee { numerator: u64, denominator: u64 },
+    OwnerTradeFee { numerator: u64, denominator: u64 },
+    OwnerWithdrawFee { numerator: u64, denominator: u64 },
+    HostFee { numerator: u64, denominator: u64 },
 }
 
 impl PoolConfigValue {
     pub fn new(mode: UpdatePoolConfigMode, value: UpdatePoolConfigValue) -> Self {
-        #[allow(unreachable_patterns)] // remove when more modes + values are added
         match (mode, value) {
             (UpdatePoolConfigMode::WithdrawalsOnly, UpdatePoolConfigValue::Bool(val)) => {
                 PoolConfigValue::WithdrawalsOnly(val)
             }
+            (
+                UpdatePoolConfigMode::TradeFee,
+                UpdatePoolConfigValue::Fee {
+                    numerator,
+                    denominator,
+                },
+            ) => PoolConfigValue::TradeFee {
+                numerator,
+                denominator,
+            },
+            (
+                UpdatePoolConfigMode::OwnerTradeFee,
+                UpdatePoolConfigValue::Fee {
+                    numerator,
+                    denominator,
+                },
+            ) => PoolConfigValue::OwnerTradeFee {
+                numerator,
+                denominator,
+            },
+            (
+                UpdatePoolConfigMode::OwnerWithdrawFee,
+                UpdatePoolConfigValue::Fee {
+                    numerator,
+                    denominator,
+                },
+            ) => PoolConfigValue::OwnerWithdrawFee {
+                numerator,
+                denominator,
+            },
+            (
+                UpdatePoolConfigMode::HostFee,
+                UpdatePoolConfigValue::Fee {
+                    numerator,
+                    denominator,
+                },
+            ) => PoolConfigValue::HostFee {
+                numerator,
+                denominator,
+            },
             (
                 // explicitly match all other cases to catch new modes at compile time
-                UpdatePoolConfigMode::WithdrawalsOnly,
+                UpdatePoolConfigMode::WithdrawalsOnly
+                | UpdatePoolConfigMode::TradeFee
+                | UpdatePoolConfigMode::OwnerTradeFee
+                | UpdatePoolConfigMode::OwnerWithdrawFee
+                | UpdatePoolConfigMode::HostFee,
                 _,
             ) => {
-                panic!("Invalid value for update lending market mode: {mode:?}");
+                panic!("Invalid value for update pool mode: {mode:?}");
             }
         }
     }
 
+    /// Bool modes take `true` or `false`, fee modes take `numerator/denominator`, e.g. `25/10000`
     pub fn new_from_str(mode: UpdatePoolConfigMode, value: String) -> PoolConfigValue {
         let parsed_value = match (mode, value) {
             (UpdatePoolConfigMode::WithdrawalsOnly, val) => {
                 UpdatePoolConfigValue::Bool(val.parse::<bool>().unwrap())
             }
+            (
+                UpdatePoolConfigMode::TradeFee
+                | UpdatePoolConfigMode::OwnerTradeFee
+                | UpdatePoolConfigMode::OwnerWithdrawFee
+                | UpdatePoolConfigMode::HostFee,
+                val,
+            ) => {
+                let (numerator, denominator) = val
+                    .split_once('/')
+                    .unwrap_or_else(|| panic!("Expected a fee as numerator/denominator: {val}"));
+                UpdatePoolConfigValue::Fee {
+                    numerator: numerator.trim().parse::<u64>().unwrap(),
+                    denominator: denominator.trim().parse::<u64>().unwrap(),
+                }
+            }
         };
         PoolConfigValue::new(mode, parsed_value)
     }
+
+    pub fn mode(&self) -> UpdatePoolConfigMode {
+        match self {
+            PoolConfigValue::WithdrawalsOnly(_) => UpdatePoolConfigMode::WithdrawalsOnly,
+            PoolConfigValue::TradeFee { .. } => UpdatePoolConfigMode::TradeFee,
+            PoolConfigValue::OwnerTradeFee { .. } => UpdatePoolConfigMode::OwnerTradeFee,
+            PoolConfigValue::OwnerWithdrawFee { .. } => UpdatePoolConfigMode::OwnerWithdrawFee,
+            PoolConfigValue::HostFee { .. } => UpdatePoolConfigMode::HostFee,
+        }
+    }
+
+    pub fn value(&self) -> UpdatePoolConfigValue {
+        match *self {
+            PoolConfigValue::WithdrawalsOnly(val) => UpdatePoolConfigValue::Bool(val),
+            PoolConfigValue::TradeFee {
+                numerator,
+                denominator,
+            }
+            | PoolConfigValue::OwnerTradeFee {
+                numerator,
+                denominator,
+            }
+            | PoolConfigValue::OwnerWithdrawFee {
+                numerator,
+                denominator,
+            }
+            | PoolConfigValue::HostFee {
+                numerator,
+                denominator,
+            } => UpdatePoolConfigValue::Fee {
+                numerator,
+                denominator,
+            },
+        }
+    }
 }
 
 impl From<PoolConfigValue> for hyperplane::instruction::UpdatePoolConfig {
     fn from(value: PoolConfigValue) -> Self {
-        match value {
-            PoolConfigValue::WithdrawalsOnly(val) => hyperplane::instruction::UpdatePoolConfig {
-                mode: UpdatePoolConfigMode::WithdrawalsOnly as u16,
-                value: UpdatePoolConfigValue::Bool(val).to_bytes(),
-            },
+        hyperplane::instruction::UpdatePoolConfig {
+            mode: value.mode() as u16,
+            value: value.value().to_bytes(),
         }
     }
 }
 
 impl From<PoolConfigValue> for hyperplane::ix::UpdatePoolConfig {
     fn from(value: PoolConfigValue) -> Self {
-        match value {
-            PoolConfigValue::WithdrawalsOnly(val) => hyperplane::ix::UpdatePoolConfig::new(
-                UpdatePoolConfigMode::WithdrawalsOnly,
-                UpdatePoolConfigValue::Bool(val),
-            ),
-        }
+        hyperplane::ix::UpdatePoolConfig::new(value.mode(), value.value())
     }
 }
 
@@ -69,6 +160,36 @@ mod test {
         assert_eq!(config_val, PoolConfigValue::WithdrawalsOnly(true));
     }
 
+    #[test]
+    pub fn test_new_pool_config_fee() {
+        let config_val =
+            PoolConfigValue::new_from_str(UpdatePoolConfigMode::TradeFee, "25/10000".to_string());
+        assert_eq!(
+            config_val,
+            PoolConfigValue::TradeFee {
+                numerator: 25,
+                denominator: 10_000
+            }
+        );
+        let ix: hyperplane::ix::UpdatePoolConfig = config_val.into();
+        assert_eq!(
+            ix,
+            hyperplane::ix::UpdatePoolConfig::new(
+                UpdatePoolConfigMode::TradeFee,
+                UpdatePoolConfigValue::Fee {
+                    numerator: 25,
+                    denominator: 10_000
+                }
+            )
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn test_new_pool_config_unparseable_fee() {
+        PoolConfigValue::new_from_str(UpdatePoolConfigMode::HostFee, "25".to_string());
+    }
+
     #[test]
     #[should_panic]
     pub fn test_new_market_config_unparseable_bool() {
@@ -10,7 +10,7 @@ use anchor_client::{
 };
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use hyperplane::state::UpdatePoolConfigMode;
+use hyperplane::state::{UpdateCurveConfigMode, UpdatePoolConfigMode};
 use hyperplane_client::{
     client::{Config, HyperplaneClient},
     command,
@@ -115,9 +115,57 @@ pub enum Actions {
         pool: Pubkey,
         #[clap(short, long)]
         mode: UpdatePoolConfigMode,
+        /// `true`/`false` for bool modes, `numerator/denominator` for fee modes
         #[clap(short, long)]
         value: String,
     },
+    /// Update a curve parameter of a pool, the mode must match the pool's curve type
+    #[clap(arg_required_else_help = true)]
+    UpdateCurve {
+        #[clap(short, long, parse(try_from_str))]
+        pool: Pubkey,
+        #[clap(short, long)]
+        mode: UpdateCurveConfigMode,
+        #[clap(short, long)]
+        value: u64,
+    },
+    /// Propose a new pool admin, the new admin must accept with accept-admin
+    #[clap(arg_required_else_help = true)]
+    ProposeNewAdmin {
+        #[clap(short, long, parse(try_from_str))]
+        pool: Pubkey,
+        #[clap(short, long, parse(try_from_str))]
+        new_admin: Pubkey,
+    },
+    /// Accept the pool admin role, signed by the pending admin
+    #[clap(arg_required_else_help = true)]
+    AcceptAdmin {
+        #[clap(short, long, parse(try_from_str))]
+        pool: Pubkey,
+    },
+    /// Ramp the amplification coefficient of a stable pool to target_amp by ramp_end_ts
+    #[clap(arg_required_else_help = true)]
+    RampAmp {
+        #[clap(short, long, parse(try_from_str))]
+        pool: Pubkey,
+        #[clap(long)]
+        target_amp: u64,
+        /// Unix timestamp at which the ramp completes
+        #[clap(long)]
+        ramp_end_ts: u64,
+    },
+    /// Stop an in-progress amplification coefficient ramp of a stable pool
+    #[clap(arg_required_else_help = true)]
+    StopRampAmp {
+        #[clap(short, long, parse(try_from_str))]
+        pool: Pubkey,
+    },
+    /// Close a drained pool, sweeping the remaining fees and reclaiming the rent of the pool accounts
+    #[clap(arg_required_else_help = true)]
+    ClosePool {
+        #[clap(short, long, parse(try_from_str))]
+        pool: Pubkey,
+    },
     #[clap(arg_required_else_help = true)]
     PrintPool {
         /// Reserve pubkey
@@ -184,6 +232,24 @@ async fn main() -> Result<()> {
         Actions::UpdatePool { pool, mode, value } => {
             command::update_pool(&hyperplane_client, admin, pool, mode, value).await
         }
+        Actions::UpdateCurve { pool, mode, value } => {
+            command::update_curve(&hyperplane_client, admin, pool, mode, value).await
+        }
+        Actions::ProposeNewAdmin { pool, new_admin } => {
+            command::propose_new_admin(&hyperplane_client, admin, pool, new_admin).await
+        }
+        Actions::AcceptAdmin { pool } => {
+            command::accept_admin(&hyperplane_client, admin, pool).await
+        }
+        Actions::RampAmp {
+            pool,
+            target_amp,
+            ramp_end_ts,
+        } => command::ramp_amp(&hyperplane_client, admin, pool, target_amp, ramp_end_ts).await,
+        Actions::StopRampAmp { pool } => {
+            command::stop_ramp_amp(&hyperplane_client, admin, pool).await
+        }
+        Actions::ClosePool { pool } => command::close_pool(&hyperplane_client, admin, pool).await,
         Actions::PrintPool { pool } => command::print_pool(&hyperplane_client, pool).await,
     }
 }
@@ -312,4 +378,145 @@ mod test {
             }
         );
     }
+
+    #[test]
+    pub fn test_parsing_update_pool_fee() {
+        let pool = Pubkey::new_unique();
+        let x = Args::parse_from([
+            "",
+            "-k",
+            "../../test/test/admin.json",
+            "update-pool",
+            "-p",
+            &pool.to_string(),
+            "-m",
+            "OwnerTradeFee",
+            "-v",
+            "5/10000",
+        ]);
+
+        assert_eq!(
+            x.action,
+            Actions::UpdatePool {
+                pool,
+                mode: UpdatePoolConfigMode::OwnerTradeFee,
+                value: "5/10000".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    pub fn test_parsing_propose_new_admin() {
+        let pool = Pubkey::new_unique();
+        let new_admin = Pubkey::new_unique();
+        let x = Args::parse_from([
+            "",
+            "-k",
+            "../../test/test/admin.json",
+            "propose-new-admin",
+            "--pool",
+            &pool.to_string(),
+            "--new-admin",
+            &new_admin.to_string(),
+        ]);
+
+        assert_eq!(x.action, Actions::ProposeNewAdmin { pool, new_admin });
+    }
+
+    #[test]
+    pub fn test_parsing_accept_admin() {
+        let pool = Pubkey::new_unique();
+        let x = Args::parse_from([
+            "",
+            "-k",
+            "../../test/test/admin.json",
+            "accept-admin",
+            "-p",
+            &pool.to_string(),
+        ]);
+
+        assert_eq!(x.action, Actions::AcceptAdmin { pool });
+    }
+
+    #[test]
+    pub fn test_parsing_update_curve() {
+        let pool = Pubkey::new_unique();
+        let x = Args::parse_from([
+            "",
+            "-k",
+            "../../test/test/admin.json",
+            "update-curve",
+            "-p",
+            &pool.to_string(),
+            "-m",
+            "TokenBPrice",
+            "-v",
+            "2",
+        ]);
+
+        assert_eq!(
+            x.action,
+            Actions::UpdateCurve {
+                pool,
+                mode: UpdateCurveConfigMode::TokenBPrice,
+                value: 2,
+            }
+        );
+    }
+
+    #[test]
+    pub fn test_parsing_ramp_amp() {
+        let pool = Pubkey::new_unique();
+        let x = Args::parse_from([
+            "",
+            "-k",
+            "../../test/test/admin.json",
+            "ramp-amp",
+            "-p",
+            &pool.to_string(),
+            "--target-amp",
+            "200",
+            "--ramp-end-ts",
+            "1700000000",
+        ]);
+
+        assert_eq!(
+            x.action,
+            Actions::RampAmp {
+                pool,
+                target_amp: 200,
+                ramp_end_ts: 1_700_000_000,
+            }
+        );
+    }
+
+    #[test]
+    pub fn test_parsing_stop_ramp_amp() {
+        let pool = Pubkey::new_unique();
+        let x = Args::parse_from([
+            "",
+            "-k",
+            "../../test/test/admin.json",
+            "stop-ramp-amp",
+            "-p",
+            &pool.to_string(),
+        ]);
+
+        assert_eq!(x.action, Actions::StopRampAmp { pool });
+    }
+
+    #[test]
+    pub fn test_parsing_close_pool() {
+        let pool = Pubkey::new_unique();
+        let x = Args::parse_from([
+            "",
+            "-k",
+            "../../test/test/admin.json",
+            "close-pool",
+            "-p",
+            &pool.to_string(),
+        ]);
+
+        assert_eq!(x.action, Actions::ClosePool { pool });
+    }
 }
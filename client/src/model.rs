@@ -1,4 +1,4 @@
-use hyperplane::{curve::fees::Fees, CurveUserParameters, InitialSupply};
+use hyperplane::{curve::fees::Fees, CurveUserParameters, InitialSupply, PoolTokenMetadata};
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct InitializePoolConfig {
@@ -7,4 +7,10 @@ pub struct InitializePoolConfig {
     pub curve: CurveUserParameters,
     pub fees: Fees,
     pub initial_supply: InitialSupply,
+    /// Create the pool token mint under token-2022, with token metadata
+    #[serde(default)]
+    pub pool_token_2022: bool,
+    /// Token metadata of a token-2022 pool token mint, derived from the trading mints if unset
+    #[serde(default)]
+    pub pool_token_metadata: Option<PoolTokenMetadata>,
 }
@@ -35,6 +35,10 @@ import {serializeConfigValue} from './util/ser';
 
 export const TOKEN_SWAP_PROGRAM_ID = PROGRAM_ID;
 
+export const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new PublicKey(
+  'BPFLoaderUpgradeab1e11111111111111111111111',
+);
+
 /**
  * Some amount of tokens
  */
@@ -76,6 +80,28 @@ export const CurveType = Object.freeze({
   Offset: 3, // Offset curve, like Uniswap, but with an additional offset on the token B side
 });
 
+/**
+ * Address of the program wide global config
+ */
+export function getGlobalConfigAddress(): PublicKey {
+  const [globalConfig, _globalConfigBump] = PublicKey.findProgramAddressSync(
+    [Buffer.from('global_config')],
+    TOKEN_SWAP_PROGRAM_ID,
+  );
+  return globalConfig;
+}
+
+/**
+ * Address of the program data account, holding the upgrade authority of the program
+ */
+export function getProgramDataAddress(): PublicKey {
+  const [programData, _programDataBump] = PublicKey.findProgramAddressSync(
+    [TOKEN_SWAP_PROGRAM_ID.toBuffer()],
+    BPF_LOADER_UPGRADEABLE_PROGRAM_ID,
+  );
+  return programData;
+}
+
 export function getCurveParams(curveType: number, params?: any) {
   switch (curveType) {
     case CurveType.ConstantProduct:
@@ -209,6 +235,47 @@ export class SwapPool {
     );
   }
 
+  /**
+   * Create the program wide global config, the owner can update it afterwards
+   *
+   * @param connection The connection to use
+   * @param upgradeAuthority The upgrade authority of the program - pays for the transaction
+   * @param owner The global config owner and protocol authority
+   * @param minFees Minimum fees of every pool
+   * @param validCurveTypes Curve types pools can be created with and swapped through
+   * @param blockedTokenExtensions Token-2022 extensions not allowed on trading token mints
+   */
+  static async initializeGlobalConfig(
+    connection: Connection,
+    upgradeAuthority: Keypair,
+    owner: PublicKey,
+    minFees: Fees,
+    validCurveTypes: number[],
+    blockedTokenExtensions: number[],
+    confirmOptions?: ConfirmOptions,
+  ): Promise<TransactionSignature> {
+    const ix = Instructions.initializeGlobalConfig(
+      {
+        owner,
+        minFees,
+        validCurveTypes: validCurveTypes.map(curveType => new BN(curveType)),
+        blockedTokenExtensions,
+      },
+      {
+        upgradeAuthority: upgradeAuthority.publicKey,
+        globalConfig: getGlobalConfigAddress(),
+        programData: getProgramDataAddress(),
+        systemProgram: SystemProgram.programId,
+      },
+    );
+    return await sendAndConfirmTransaction(
+      connection,
+      new Transaction().add(ix),
+      [upgradeAuthority],
+      confirmOptions,
+    );
+  }
+
   /**
    * Create a new Token Swap
    *
@@ -318,6 +385,7 @@ export class SwapPool {
       },
       {
         pool: pool.publicKey,
+        globalConfig: getGlobalConfigAddress(),
         swapCurve: swapCurve,
         admin: admin.publicKey,
         adminPoolTokenAta: adminAuthorityPoolTokenAta.publicKey,
@@ -445,6 +513,7 @@ export class SwapPool {
       {
         signer: userTransferAuthority,
         pool: pool,
+        globalConfig: getGlobalConfigAddress(),
         swapCurve: curve,
         poolAuthority: authority,
         sourceMint,
@@ -769,6 +838,7 @@ export class SwapPool {
       {
         admin,
         pool,
+        globalConfig: getGlobalConfigAddress(),
       },
     );
   }
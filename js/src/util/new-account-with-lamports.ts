@@ -5,9 +5,8 @@ import {sleep} from './sleep';
 export async function newAccountWithLamports(
   connection: Connection,
   lamports: number = 1000000,
+  account: Keypair = new Keypair(),
 ): Promise<Keypair> {
-  const account = new Keypair();
-
   let retries = 30;
   await connection.requestAirdrop(account.publicKey, lamports);
   for (;;) {
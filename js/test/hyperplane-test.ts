@@ -12,11 +12,13 @@ import {
   TOKEN_PROGRAM_ID
 } from '@solana/spl-token';
 
-import {Numberu64, TOKEN_SWAP_PROGRAM_ID, SwapPool} from '../src';
+import {CurveType, getGlobalConfigAddress, Numberu64, TOKEN_SWAP_PROGRAM_ID, SwapPool} from '../src';
 import {newAccountWithLamports} from '../src/util/new-account-with-lamports';
 import {sleep} from '../src/util/sleep';
-import {UpdatePoolConfigMode, UpdatePoolConfigValue} from "../src/_generated/hyperplane-client/types";
+import {Fees, UpdatePoolConfigMode, UpdatePoolConfigValue} from "../src/_generated/hyperplane-client/types";
+import {GlobalConfig} from "../src/_generated/hyperplane-client/accounts";
 import { expect } from 'chai';
+import upgradeAuthority from './keys/upgrade-authority.json';
 
 // The following globals are created by `createTokenSwap` and used by subsequent tests
 // Token swap
@@ -75,6 +77,38 @@ async function getConnection(): Promise<Connection> {
   return connection;
 }
 
+export async function initializeGlobalConfig(): Promise<void> {
+  const connection = await getConnection();
+  // Only the upgrade authority of the program can initialize the global config
+  const globalConfigOwner = await newAccountWithLamports(
+    connection,
+    1_000000000,
+    Keypair.fromSecretKey(Uint8Array.from(upgradeAuthority)),
+  );
+
+  console.log('creating global config');
+  await SwapPool.initializeGlobalConfig(
+    connection,
+    globalConfigOwner,
+    globalConfigOwner.publicKey,
+    new Fees({
+      tradeFeeNumerator: new Numberu64(0),
+      tradeFeeDenominator: new Numberu64(0),
+      ownerTradeFeeNumerator: new Numberu64(0),
+      ownerTradeFeeDenominator: new Numberu64(0),
+      ownerWithdrawFeeNumerator: new Numberu64(0),
+      ownerWithdrawFeeDenominator: new Numberu64(0),
+      hostFeeNumerator: new Numberu64(0),
+      hostFeeDenominator: new Numberu64(0),
+    }),
+    [CurveType.ConstantProduct, CurveType.ConstantPrice, CurveType.Offset],
+    [],
+  );
+
+  const globalConfig = await GlobalConfig.fetch(connection, getGlobalConfigAddress());
+  expect(globalConfig?.owner.toString()).eq(globalConfigOwner.publicKey.toString());
+}
+
 export async function createTokenSwap(
   curveType: number,
   curveParameters?: Numberu64,
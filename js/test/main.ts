@@ -1,6 +1,7 @@
 import {
   createAccountAndSwapAtomic,
   createTokenSwap,
+  initializeGlobalConfig,
   swap,
   deposit,
   withdraw,
@@ -10,6 +11,8 @@ import {CurveType, Numberu64} from '../src';
 
 async function main() {
   // These test cases are designed to run sequentially and in the following order
+  console.log('Run test: initializeGlobalConfig');
+  await initializeGlobalConfig();
   console.log('Run test: createTokenSwap (constant price)');
   await createTokenSwap(CurveType.ConstantPrice, new Numberu64(1));
   console.log(
@@ -81,6 +81,10 @@ fn run_fuzz(fuzz_data: FuzzData) {
         host_fee_numerator,
         host_fee_denominator,
     };
+    // oracle priced pools need a price feed account, which the native harness doesn't provide
+    if fuzz_data.curve_type == CurveType::OraclePrice {
+        return;
+    }
     let curve_params = get_curve_parameters(fuzz_data.curve_type);
     let mut token_swap = NativeTokenSwap::new(
         fees,
@@ -380,5 +384,13 @@ fn get_curve_parameters(curve_type: CurveType) -> CurveParameters {
             token_a_decimals: 6,
             token_b_decimals: 6,
         },
+        CurveType::OraclePrice => unreachable!("oracle priced pools are not fuzzed"),
+        CurveType::Concentrated => CurveParameters::Concentrated {
+            price_lower_x64: 1 << 63,
+            price_upper_x64: 1 << 65,
+        },
+        CurveType::Weighted => CurveParameters::Weighted {
+            token_a_weight_bps: 8_000,
+        },
     }
 }
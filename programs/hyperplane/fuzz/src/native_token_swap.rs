@@ -1,10 +1,13 @@
 //! Helpers for working with swaps in a fuzzing environment
 
 use hyperplane::{
-    curve::{base::SwapCurve, fees::Fees},
+    curve::{
+        base::{CurveType, SwapCurve},
+        fees::Fees,
+    },
     instructions::model::CurveParameters,
-    ix::{self, Deposit, Initialize, Swap, Withdraw, WithdrawFees},
-    state::{Curve, SwapPool},
+    ix::{self, Deposit, Initialize, InitializeGlobalConfig, Swap, Withdraw, WithdrawFees},
+    state::{Curve, GlobalConfig, SwapPool},
     utils::seeds,
     InitialSupply,
 };
@@ -26,10 +29,13 @@ pub struct NativeTokenSwap {
     pub fees: Fees,
     pub swap_curve: SwapCurve,
     pub pool_account: NativeAccountData,
+    pub global_config_account: NativeAccountData,
     pub swap_curve_account: NativeAccountData,
     pub pool_token_mint_account: NativeAccountData,
     pub token_a_fees_vault_account: NativeAccountData,
     pub token_b_fees_vault_account: NativeAccountData,
+    pub token_a_protocol_fees_vault_account: NativeAccountData,
+    pub token_b_protocol_fees_vault_account: NativeAccountData,
     pub admin_token_a_ata: NativeAccountData,
     pub admin_token_b_ata: NativeAccountData,
     pub admin_pool_token_ata: NativeAccountData,
@@ -86,6 +92,41 @@ impl NativeTokenSwap {
         let mut token_b_mint_account =
             native_token::create_mint(&admin_authority.key, token_b_decimals);
 
+        let mut system_program_account = create_program_account(system_program::id());
+        let mut global_config_account = NativeAccountData::new_with_key(
+            seeds::pda::global_config_pda().0,
+            GlobalConfig::LEN,
+            hyperplane::id(),
+        );
+        let init_global_config_instruction = ix::initialize_global_config(
+            &hyperplane::id(),
+            &admin_authority.key,
+            InitializeGlobalConfig {
+                owner: admin_authority.key,
+                min_fees: Fees::default(),
+                valid_curve_types: vec![
+                    CurveType::ConstantProduct.into(),
+                    CurveType::ConstantPrice.into(),
+                    CurveType::Offset.into(),
+                    CurveType::Stable.into(),
+                    CurveType::OraclePrice.into(),
+                    CurveType::Concentrated.into(),
+                    CurveType::Weighted.into(),
+                ],
+                blocked_token_extensions: vec![],
+            },
+        )
+        .unwrap();
+        do_process_instruction(
+            init_global_config_instruction,
+            &[
+                admin_authority.as_account_info(),
+                global_config_account.as_account_info(),
+                system_program_account.as_account_info(),
+            ],
+        )
+        .unwrap();
+
         let mut pool_account = NativeAccountData::new(SwapPool::LEN, hyperplane::id());
         let seeds::pda::InitPoolPdas {
             curve,
@@ -95,6 +136,8 @@ impl NativeTokenSwap {
             pool_token_mint,
             token_a_fees_vault,
             token_b_fees_vault,
+            token_a_protocol_fees_vault,
+            token_b_protocol_fees_vault,
         } = seeds::pda::init_pool_pdas(
             &pool_account.key,
             &token_a_mint_account.key,
@@ -104,7 +147,6 @@ impl NativeTokenSwap {
         let mut swap_curve_account =
             NativeAccountData::new_with_key(curve, Curve::LEN, hyperplane::id());
         let mut pool_authority_account = create_program_account(authority);
-        let mut system_program_account = create_program_account(system_program::id());
         let mut rent = create_sysvar_account(&Rent::default());
         let mut pool_token_program_account = create_program_account(spl_token::id());
         let mut token_b_program_account = create_program_account(spl_token::id());
@@ -138,6 +180,16 @@ impl NativeTokenSwap {
             get_token_account_space(&token_b_program_account.key, &token_b_mint_account),
             token_b_program_account.key,
         );
+        let mut token_a_protocol_fees_vault_account = NativeAccountData::new_with_key(
+            token_a_protocol_fees_vault,
+            get_token_account_space(&token_a_program_account.key, &token_a_mint_account),
+            token_a_program_account.key,
+        );
+        let mut token_b_protocol_fees_vault_account = NativeAccountData::new_with_key(
+            token_b_protocol_fees_vault,
+            get_token_account_space(&token_b_program_account.key, &token_b_mint_account),
+            token_b_program_account.key,
+        );
         let mut admin_authority_token_a_ata_account = native_token::create_token_account(
             &mut token_a_mint_account,
             &token_a_program_account.key,
@@ -164,6 +216,8 @@ impl NativeTokenSwap {
             &pool_token_mint_account.key,
             &token_a_fees_vault_account.key,
             &token_b_fees_vault_account.key,
+            &token_a_protocol_fees_vault_account.key,
+            &token_b_protocol_fees_vault_account.key,
             &admin_authority_token_a_ata_account.key,
             &admin_authority_token_b_ata_account.key,
             &admin_authority_pool_token_ata.key,
@@ -174,6 +228,7 @@ impl NativeTokenSwap {
                 fees,
                 curve_parameters: curve_params.clone().into(),
                 initial_supply: InitialSupply::new(token_a_amount, token_b_amount),
+                pool_token_metadata: None,
             },
         )
         .unwrap();
@@ -183,6 +238,7 @@ impl NativeTokenSwap {
             &[
                 admin_authority.as_account_info(),
                 pool_account.as_account_info(),
+                global_config_account.as_account_info(),
                 swap_curve_account.as_account_info(),
                 pool_authority_account.as_account_info(),
                 token_a_mint_account.as_account_info(),
@@ -192,6 +248,8 @@ impl NativeTokenSwap {
                 pool_token_mint_account.as_account_info(),
                 token_a_fees_vault_account.as_account_info(),
                 token_b_fees_vault_account.as_account_info(),
+                token_a_protocol_fees_vault_account.as_account_info(),
+                token_b_protocol_fees_vault_account.as_account_info(),
                 admin_authority_token_a_ata_account.as_account_info(),
                 admin_authority_token_b_ata_account.as_account_info(),
                 admin_authority_pool_token_ata.as_account_info(),
@@ -209,11 +267,14 @@ impl NativeTokenSwap {
             pool_authority_account,
             fees,
             pool_account,
+            global_config_account,
             swap_curve: SwapCurve::new_from_params(curve_params).unwrap(),
             swap_curve_account,
             pool_token_mint_account,
             token_a_fees_vault_account,
             token_b_fees_vault_account,
+            token_a_protocol_fees_vault_account,
+            token_b_protocol_fees_vault_account,
             admin_token_a_ata: admin_authority_token_a_ata_account,
             admin_token_b_ata: admin_authority_token_b_ata_account,
             admin_pool_token_ata: admin_authority_pool_token_ata,
@@ -290,6 +351,7 @@ impl NativeTokenSwap {
             &self.token_a_vault_account.key,
             &self.token_b_vault_account.key,
             &self.token_a_fees_vault_account.key,
+            &self.token_a_protocol_fees_vault_account.key,
             &user_token_a_account.key,
             &user_token_b_account.key,
             Some(&self.admin_token_a_ata.key),
@@ -304,6 +366,7 @@ impl NativeTokenSwap {
             &[
                 self.admin.as_account_info(),
                 self.pool_account.as_account_info(),
+                self.global_config_account.as_account_info(),
                 self.swap_curve_account.as_account_info(),
                 self.pool_authority_account.as_account_info(),
                 self.token_a_mint_account.as_account_info(),
@@ -311,6 +374,7 @@ impl NativeTokenSwap {
                 self.token_a_vault_account.as_account_info(),
                 self.token_b_vault_account.as_account_info(),
                 self.token_a_fees_vault_account.as_account_info(),
+                self.token_a_protocol_fees_vault_account.as_account_info(),
                 user_token_a_account.as_account_info(),
                 user_token_b_account.as_account_info(),
                 self.admin_token_a_ata.as_account_info(),
@@ -357,6 +421,7 @@ impl NativeTokenSwap {
             &self.token_b_vault_account.key,
             &self.token_a_vault_account.key,
             &self.token_b_fees_vault_account.key,
+            &self.token_b_protocol_fees_vault_account.key,
             &user_token_b_account.key,
             &user_token_a_account.key,
             Some(&self.admin_token_b_ata.key),
@@ -371,6 +436,7 @@ impl NativeTokenSwap {
             &[
                 user_transfer_authority_account.as_account_info(),
                 self.pool_account.as_account_info(),
+                self.global_config_account.as_account_info(),
                 self.swap_curve_account.as_account_info(),
                 self.pool_authority_account.as_account_info(),
                 self.token_b_mint_account.as_account_info(),
@@ -378,6 +444,7 @@ impl NativeTokenSwap {
                 self.token_b_vault_account.as_account_info(),
                 self.token_a_vault_account.as_account_info(),
                 self.token_b_fees_vault_account.as_account_info(),
+                self.token_b_protocol_fees_vault_account.as_account_info(),
                 user_token_b_account.as_account_info(),
                 user_token_a_account.as_account_info(),
                 self.admin_token_b_ata.as_account_info(),
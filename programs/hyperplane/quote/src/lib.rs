@@ -0,0 +1,356 @@
+#![allow(clippy::result_large_err)]
+//! Off-chain quotes for hyperplane pools
+//!
+//! Reproduces the token transfers of the swap, deposit and withdraw instructions from
+//! deserialized account data, so pools can be quoted without simulating transactions.
+//! Token-2022 transfer fees are taken into account from the mints' `TransferFeeConfig`
+//! as of the given epoch.
+
+use std::sync::Arc;
+
+/// Token transfers of a deposit, as made by the `deposit` instruction
+pub use hyperplane::utils::pool_transfers::DepositTransfers as DepositQuote;
+/// Token transfers of a withdrawal, as made by the `withdraw` instruction
+pub use hyperplane::utils::pool_transfers::WithdrawTransfers as WithdrawQuote;
+use hyperplane::{
+    anchor_lang,
+    anchor_lang::{error, prelude::Clock, AccountDeserialize, Result},
+    curve::{
+        base::{CurveType, SwapCurve},
+        calculator::{AorB, CurveCalculator, TradeDirection},
+        fees::Fees,
+        oracle_price::PriceFeed,
+        rate_provider::{check_rate_age, RateProviderType, StakePoolRate},
+    },
+    error::SwapError,
+    require_msg,
+    state::{
+        ConcentratedCurve, ConstantPriceCurve, ConstantProductCurve, GlobalConfig, OffsetCurve,
+        OraclePriceCurve, StableCurve, SwapPool, SwapState, WeightedCurve,
+    },
+    utils::{
+        pool_transfers::{LiquidityParams, SwapParams, SwapTransfers},
+        transfer_fee::MintTransferFee,
+    },
+};
+
+/// Account data of a pool, as read from the chain
+pub struct PoolAccounts<'a> {
+    /// The pool
+    pub pool: &'a SwapPool,
+    /// The global config, for the protocol share of the owner fees
+    pub global_config: &'a GlobalConfig,
+    /// Data of the pool's swap curve account
+    pub swap_curve: &'a [u8],
+    /// Data of the price feed account, required for oracle price pools
+    pub price_feed: Option<&'a [u8]>,
+    /// Data of the stake pool account, required for stable pools with a stake pool rate provider
+    pub rate_provider: Option<&'a [u8]>,
+    /// Data of the token A mint
+    pub token_a_mint: &'a [u8],
+    /// Data of the token B mint
+    pub token_b_mint: &'a [u8],
+    /// Balance of the token A vault
+    pub token_a_vault_amount: u64,
+    /// Balance of the token B vault
+    pub token_b_vault_amount: u64,
+    /// Supply of the pool token mint
+    pub pool_token_supply: u64,
+}
+
+/// Token transfers of a swap
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SwapQuote {
+    /// Total amount debited from the user's source token account (includes: all fees and transfer fees)
+    pub source_amount: u64,
+    /// Amount transferred from the user to the source vault
+    pub source_amount_to_vault: u64,
+    /// Amount transferred from the user to the source token fees vault
+    pub owner_fee: u64,
+    /// Amount transferred from the user to the host fees account
+    pub host_fee: u64,
+    /// Amount transferred from the user to the source token protocol fees vault
+    pub protocol_fee: u64,
+    /// Amount credited to the source vault (excludes: transfer fees)
+    pub source_amount_received: u64,
+    /// Amount credited to the source token fees vault (excludes: transfer fees)
+    pub owner_fee_received: u64,
+    /// Amount credited to the host fees account (excludes: transfer fees)
+    pub host_fee_received: u64,
+    /// Amount credited to the source token protocol fees vault (excludes: transfer fees)
+    pub protocol_fee_received: u64,
+    /// Amount transferred from the destination vault to the user
+    pub destination_amount_from_vault: u64,
+    /// Amount credited to the user's destination token account (excludes: transfer fees)
+    pub destination_amount: u64,
+    /// Total trading fees paid in source tokens, as emitted in the swap event
+    pub total_fees: u64,
+    /// Effective trade fee fraction, as emitted in the swap event
+    pub trade_fee_numerator: u64,
+    pub trade_fee_denominator: u64,
+}
+
+/// Quotes the instructions of a pool as of a clock
+pub struct PoolQuoter {
+    pool: SwapPool,
+    global_config: GlobalConfig,
+    swap_curve: SwapCurve,
+    fees: Fees,
+    token_a_transfer_fee: MintTransferFee,
+    token_b_transfer_fee: MintTransferFee,
+    token_a_vault_amount: u64,
+    token_b_vault_amount: u64,
+    pool_token_supply: u64,
+}
+
+impl PoolQuoter {
+    /// Load the pool's curve and mints as of `clock`, the clock sets the epoch of transfer fees,
+    /// the amplification coefficient of ramping stable curves, the staleness of price feeds and
+    /// the interest accrued by interest-bearing mints, and the volatility of dynamic fees
+    pub fn new(accounts: PoolAccounts, clock: &Clock) -> Result<Self> {
+        let pool = accounts.pool;
+        let swap_curve = swap_curve(
+            pool,
+            accounts.swap_curve,
+            accounts.price_feed,
+            accounts.rate_provider,
+            clock,
+        )?
+        .with_interest_bearing_mints(
+            pool.is_interest_bearing(AorB::A)
+                .then_some(accounts.token_a_mint),
+            pool.is_interest_bearing(AorB::B)
+                .then_some(accounts.token_b_mint),
+            clock.unix_timestamp,
+        )?;
+        // accumulate volatility as the next swap would, before it trades
+        let mut dynamic_fee = pool.dynamic_fee;
+        dynamic_fee.update_at(
+            swap_curve.calculator.as_ref(),
+            accounts.token_a_vault_amount,
+            accounts.token_b_vault_amount,
+            u64::try_from(clock.unix_timestamp)
+                .map_err(|_| error!(SwapError::ConversionFailure))?,
+        )?;
+        Ok(Self {
+            pool: *pool,
+            global_config: *accounts.global_config,
+            swap_curve,
+            fees: dynamic_fee.fees(&pool.fees)?,
+            token_a_transfer_fee: MintTransferFee::new(accounts.token_a_mint, clock.epoch)?,
+            token_b_transfer_fee: MintTransferFee::new(accounts.token_b_mint, clock.epoch)?,
+            token_a_vault_amount: accounts.token_a_vault_amount,
+            token_b_vault_amount: accounts.token_b_vault_amount,
+            pool_token_supply: accounts.pool_token_supply,
+        })
+    }
+
+    /// The swap curve of the pool
+    pub fn swap_curve(&self) -> &SwapCurve {
+        &self.swap_curve
+    }
+
+    /// The fees of the next swap, including the dynamic trade fee of the pool if enabled
+    pub fn fees(&self) -> &Fees {
+        &self.fees
+    }
+
+    /// Quote the `swap` instruction for `amount_in` of the source token, with or without a host
+    /// fees account
+    pub fn swap(
+        &self,
+        trade_direction: TradeDirection,
+        amount_in: u64,
+        with_host_fee: bool,
+    ) -> Result<SwapQuote> {
+        let transfers = self
+            .swap_params(trade_direction, with_host_fee)?
+            .swap(amount_in)?;
+        self.swap_quote(trade_direction, transfers)
+    }
+
+    /// Quote the `swap_exact_out` instruction for `amount_out` of the destination token received,
+    /// with or without a host fees account
+    pub fn swap_exact_out(
+        &self,
+        trade_direction: TradeDirection,
+        amount_out: u64,
+        with_host_fee: bool,
+    ) -> Result<SwapQuote> {
+        let transfers = self
+            .swap_params(trade_direction, with_host_fee)?
+            .swap_exact_out(amount_out)?;
+        self.swap_quote(trade_direction, transfers)
+    }
+
+    /// Quote the `deposit` instruction for minting `pool_token_amount` pool tokens
+    pub fn deposit(&self, pool_token_amount: u64) -> Result<DepositQuote> {
+        require_msg!(
+            !self.pool.withdrawals_only(),
+            SwapError::WithdrawalsOnlyMode,
+            "The pool is in withdrawals only mode"
+        );
+        self.liquidity_params().deposit(pool_token_amount)
+    }
+
+    /// Quote the `withdraw` instruction for burning `pool_token_amount` pool tokens
+    pub fn withdraw(&self, pool_token_amount: u64) -> Result<WithdrawQuote> {
+        self.liquidity_params().withdraw(pool_token_amount)
+    }
+
+    fn swap_params(
+        &self,
+        trade_direction: TradeDirection,
+        with_host_fee: bool,
+    ) -> Result<SwapParams> {
+        require_msg!(
+            !self.pool.withdrawals_only(),
+            SwapError::WithdrawalsOnlyMode,
+            "The pool is in withdrawals only mode"
+        );
+        let (
+            source,
+            source_transfer_fee,
+            destination_transfer_fee,
+            source_vault_amount,
+            destination_vault_amount,
+        ) = match trade_direction {
+            TradeDirection::AtoB => (
+                AorB::A,
+                self.token_a_transfer_fee,
+                self.token_b_transfer_fee,
+                self.token_a_vault_amount,
+                self.token_b_vault_amount,
+            ),
+            TradeDirection::BtoA => (
+                AorB::B,
+                self.token_b_transfer_fee,
+                self.token_a_transfer_fee,
+                self.token_b_vault_amount,
+                self.token_a_vault_amount,
+            ),
+        };
+        Ok(SwapParams {
+            swap_curve: &self.swap_curve,
+            fees: &self.fees,
+            trade_direction,
+            source_vault_amount,
+            destination_vault_amount,
+            source_transfer_fee,
+            destination_transfer_fee,
+            host_fee: with_host_fee,
+            // pools without protocol fees vaults pay no protocol fees
+            protocol_fee: self
+                .pool
+                .protocol_fees_vault(source)
+                .is_some()
+                .then_some(&self.global_config),
+        })
+    }
+
+    fn swap_quote(
+        &self,
+        trade_direction: TradeDirection,
+        transfers: SwapTransfers,
+    ) -> Result<SwapQuote> {
+        let source_transfer_fee = match trade_direction {
+            TradeDirection::AtoB => &self.token_a_transfer_fee,
+            TradeDirection::BtoA => &self.token_b_transfer_fee,
+        };
+        Ok(SwapQuote {
+            source_amount: transfers.source_amount()?,
+            source_amount_to_vault: transfers.source_amount_to_vault,
+            owner_fee: transfers.owner_fee_to_vault,
+            host_fee: transfers.host_fee_to_account,
+            protocol_fee: transfers.protocol_fee_to_vault,
+            source_amount_received: source_transfer_fee
+                .sub_transfer_fee(transfers.source_amount_to_vault)?,
+            owner_fee_received: source_transfer_fee
+                .sub_transfer_fee(transfers.owner_fee_to_vault)?,
+            host_fee_received: source_transfer_fee
+                .sub_transfer_fee(transfers.host_fee_to_account)?,
+            protocol_fee_received: source_transfer_fee
+                .sub_transfer_fee(transfers.protocol_fee_to_vault)?,
+            destination_amount_from_vault: transfers.destination_amount_from_vault,
+            destination_amount: transfers.destination_amount,
+            total_fees: transfers.total_fees,
+            trade_fee_numerator: transfers.trade_fee_numerator,
+            trade_fee_denominator: transfers.trade_fee_denominator,
+        })
+    }
+
+    fn liquidity_params(&self) -> LiquidityParams {
+        LiquidityParams {
+            calculator: self.swap_curve.calculator.as_ref(),
+            fees: self.pool.fees(),
+            token_a_vault_amount: self.token_a_vault_amount,
+            token_b_vault_amount: self.token_b_vault_amount,
+            pool_token_supply: self.pool_token_supply,
+            token_a_transfer_fee: self.token_a_transfer_fee,
+            token_b_transfer_fee: self.token_b_transfer_fee,
+        }
+    }
+}
+
+/// Deserialize the swap curve of a pool as of `clock`
+///
+/// Ramping stable curves are fixed at their current amplification coefficient and oracle price
+/// curves are priced from the given price feed data, subject to the same staleness and
+/// confidence checks as on-chain. Stable curves with a stake pool rate provider are rated from
+/// the given stake pool data, as of the clock's epoch.
+pub fn swap_curve(
+    pool: &SwapPool,
+    swap_curve_data: &[u8],
+    price_feed_data: Option<&[u8]>,
+    rate_provider_data: Option<&[u8]>,
+    clock: &Clock,
+) -> Result<SwapCurve> {
+    let data = &mut &swap_curve_data[..];
+    let calculator: Arc<dyn CurveCalculator + Sync + Send> = match pool.curve_type() {
+        CurveType::ConstantProduct => Arc::new(ConstantProductCurve::try_deserialize(data)?),
+        CurveType::ConstantPrice => Arc::new(ConstantPriceCurve::try_deserialize(data)?),
+        CurveType::Offset => Arc::new(OffsetCurve::try_deserialize(data)?),
+        CurveType::Stable => {
+            let mut curve = StableCurve::try_deserialize(data)?;
+            let now = u64::try_from(clock.unix_timestamp)
+                .map_err(|_| error!(SwapError::ConversionFailure))?;
+            match RateProviderType::try_from(curve.rate_provider_type)
+                .map_err(|_| error!(SwapError::InvalidRateProvider))?
+            {
+                RateProviderType::None => {}
+                RateProviderType::StakePool => {
+                    let stake_pool = StakePoolRate::try_from_bytes(
+                        rate_provider_data.ok_or_else(|| error!(SwapError::InvalidRateProvider))?,
+                    )?;
+                    curve.token_b_rate = stake_pool.token_b_rate(clock.epoch)?;
+                }
+                RateProviderType::Authority => {
+                    check_rate_age(curve.rate_updated_ts, curve.max_rate_age_sec, now)?;
+                }
+            }
+            Arc::new(StableCurve {
+                amp: curve.current_amp_at(now)?,
+                ramp_end_ts: 0,
+                ..curve
+            })
+        }
+        CurveType::OraclePrice => {
+            let mut curve = OraclePriceCurve::try_deserialize(data)?;
+            let price_feed = PriceFeed::try_from_bytes(
+                price_feed_data.ok_or_else(|| error!(SwapError::InvalidPriceFeed))?,
+            )?;
+            curve.token_b_price_x64 = price_feed.token_b_price_x64(
+                clock.unix_timestamp,
+                curve.max_price_age_sec,
+                curve.max_price_confidence_bps,
+            )?;
+            Arc::new(curve)
+        }
+        CurveType::Concentrated => Arc::new(ConcentratedCurve::try_deserialize(data)?),
+        CurveType::Weighted => Arc::new(WeightedCurve::try_deserialize(data)?),
+    };
+    Ok(SwapCurve {
+        curve_type: pool.curve_type(),
+        calculator,
+    })
+}
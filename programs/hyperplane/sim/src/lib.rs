@@ -54,10 +54,7 @@ impl StableSwapModel {
             balances: balances.iter().map(|x| BigInt::from(*x)).collect(),
             n_coins: BigInt::from(n_coins),
             fee: BigInt::zero(),
-            target_prices: vec![
-                BigInt::from(DEFAULT_TARGET_PRICE),
-                BigInt::from(DEFAULT_TARGET_PRICE),
-            ],
+            target_prices: vec![BigInt::from(DEFAULT_TARGET_PRICE); n_coins as usize],
             pool_tokens: BigInt::from(pool_token_amount),
         }
     }
@@ -191,6 +188,33 @@ impl StableSwapModel {
         y.to_u128().unwrap()
     }
 
+    pub fn sim_add_liquidity(&mut self, amounts: Vec<u128>) -> u128 {
+        let fee = &self.fee * &self.n_coins / (4 * (&self.n_coins - 1));
+        let old_balances = self.balances.clone();
+        let mut new_balances = self.balances.clone();
+        let d0 = self.sim_d();
+        for i in 0..self.n_coins.to_usize().unwrap() {
+            new_balances[i] += amounts[i];
+        }
+        self.balances = new_balances.clone();
+        let d1 = self.sim_d();
+        self.balances = old_balances.clone();
+        let mut fees: Vec<BigInt> = new_balances.iter().map(|_| BigInt::zero()).collect();
+        for i in 0..self.n_coins.to_usize().unwrap() {
+            let ideal_balance = d1 * &old_balances[i] / d0;
+            let difference = ideal_balance.abs_diff(&new_balances[i]);
+            fees[i] = &fee * difference / BigInt::from(10).pow(10);
+            new_balances[i] -= fees[i].clone();
+        }
+        self.balances = new_balances.clone();
+        let d2 = self.sim_d();
+        self.balances = old_balances;
+
+        let token_amount = (d2 - d0) * &self.pool_tokens / d0;
+
+        token_amount.to_u128().unwrap()
+    }
+
     pub fn sim_remove_liquidity_imbalance(&mut self, amounts: Vec<u128>) -> u128 {
         let fee = &self.fee * &self.n_coins / (4 * (&self.n_coins - 1));
         let old_balances = self.balances.clone();
@@ -228,7 +252,7 @@ impl StableSwapModel {
         };
 
         let d0 = self.sim_d();
-        let d1 = d0 - token_amount * d0 / &self.pool_tokens;
+        let d1 = d0 - BigInt::from(token_amount) * d0 / &self.pool_tokens;
         let dy = &xp[i as usize] - self.sim_y_d(i, d1.to_u128().unwrap());
 
         (&dy - &dy * &fee / BigInt::from(10).pow(10))
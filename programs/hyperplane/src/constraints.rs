@@ -1,98 +1,73 @@
-//! Various constraints as required for production environments
+//! Validation of pools against the program wide constraints held in the global config
 
-#[cfg(feature = "production")]
-use std::env;
-
-use anchor_lang::{
-    err,
-    prelude::{AccountInfo, Pubkey},
-    Result,
-};
-use anchor_spl::token_2022::spl_token_2022::extension::{
-    BaseStateWithExtensions, ExtensionType, StateWithExtensions,
-};
+use anchor_lang::{err, prelude::AccountInfo, Result};
 
 use crate::{
-    curve::{
-        base::{CurveType, SwapCurve},
-        fees::Fees,
-    },
+    curve::{base::CurveType, fees::Fees},
     error::SwapError,
+    state::GlobalConfig,
+    utils::token_extensions::{mint_extension_types, MintExtension},
 };
 
-/// Encodes fee constraints, used in multihost environments where the program
-/// may be used by multiple frontends, to ensure that proper fees are being
-/// assessed.
-/// Since this struct needs to be created at compile-time, we only have access
-/// to const functions and constructors. Since SwapCurve contains a Arc, it
-/// cannot be used, so we have to split the curves based on their types.
-pub struct SwapConstraints<'a> {
-    /// Owner of the program
-    pub owner_key: &'a str,
-    /// Valid curve types
-    pub valid_curve_types: &'a [CurveType],
-    /// Valid fees
-    pub fees: &'a Fees,
-    /// token_2022 trading token blocked extensions
-    pub blocked_trading_token_extensions: &'a [ExtensionType],
-}
-
-impl<'a> SwapConstraints<'a> {
-    /// Checks that the provided admin is valid for the given constraints
-    pub fn validate_admin(&self, admin: &Pubkey) -> Result<()> {
-        let owner_key = self
-            .owner_key
-            .parse::<Pubkey>()
-            .map_err(|_| SwapError::InvaliPoolAdmin)?;
-        if &owner_key == admin {
-            Ok(())
-        } else {
-            err!(SwapError::InvaliPoolAdmin)
-        }
-    }
-
-    /// Checks that the provided curve is valid for the given constraints
-    pub fn validate_curve(&self, swap_curve: &SwapCurve) -> Result<()> {
-        if self
-            .valid_curve_types
-            .iter()
-            .any(|x| *x == swap_curve.curve_type)
-        {
+impl GlobalConfig {
+    /// Checks that the provided curve type is valid for the given constraints
+    pub fn validate_curve(&self, curve_type: CurveType) -> Result<()> {
+        if self.valid_curve_types().any(|x| x == u64::from(curve_type)) {
             Ok(())
         } else {
             err!(SwapError::UnsupportedCurveType)
         }
     }
 
-    /// Checks that the provided curve is valid for the given constraints
+    /// Checks that the provided fees are at least the minimum fees of the given constraints
+    ///
+    /// The host fee is the portion of the owner fees paid to hosts, so once it is set pools must
+    /// charge exactly that host fee, as a higher one would take owner fees from the pool admin.
     pub fn validate_fees(&self, fees: &Fees) -> Result<()> {
-        if fees.trade_fee_numerator >= self.fees.trade_fee_numerator
-            && fees.trade_fee_denominator == self.fees.trade_fee_denominator
-            && fees.owner_trade_fee_numerator >= self.fees.owner_trade_fee_numerator
-            && fees.owner_trade_fee_denominator == self.fees.owner_trade_fee_denominator
-            && fees.owner_withdraw_fee_numerator >= self.fees.owner_withdraw_fee_numerator
-            && fees.owner_withdraw_fee_denominator == self.fees.owner_withdraw_fee_denominator
-            && fees.host_fee_numerator == self.fees.host_fee_numerator
-            && fees.host_fee_denominator == self.fees.host_fee_denominator
-        {
+        let min_fees = &self.min_fees;
+        if fee_at_least(
+            fees.trade_fee_numerator,
+            fees.trade_fee_denominator,
+            min_fees.trade_fee_numerator,
+            min_fees.trade_fee_denominator,
+        ) && fee_at_least(
+            fees.owner_trade_fee_numerator,
+            fees.owner_trade_fee_denominator,
+            min_fees.owner_trade_fee_numerator,
+            min_fees.owner_trade_fee_denominator,
+        ) && fee_at_least(
+            fees.owner_withdraw_fee_numerator,
+            fees.owner_withdraw_fee_denominator,
+            min_fees.owner_withdraw_fee_numerator,
+            min_fees.owner_withdraw_fee_denominator,
+        ) && fee_matches(
+            fees.host_fee_numerator,
+            fees.host_fee_denominator,
+            min_fees.host_fee_numerator,
+            min_fees.host_fee_denominator,
+        ) {
             Ok(())
         } else {
             err!(SwapError::InvalidFee)
         }
     }
 
-    /// Checks that the provided admin is valid for the given constraints
+    /// Checks that the provided mint only has token 2022 extensions supported by pools or allowed
+    /// by the given constraints, and none of the extensions blocked by the given constraints
     pub fn validate_token_2022_trading_token_extensions(
         &self,
         mint_acc_info: &AccountInfo,
     ) -> Result<()> {
         let mint_data = mint_acc_info.data.borrow();
-        let mint =
-            StateWithExtensions::<anchor_spl::token_2022::spl_token_2022::state::Mint>::unpack(
-                &mint_data,
-            )?;
-        for mint_ext in mint.get_extension_types()? {
-            if self.blocked_trading_token_extensions.contains(&mint_ext) {
+        for mint_ext in mint_extension_types(&mint_data)? {
+            let supported = MintExtension::try_from(mint_ext)
+                .map(|ext| {
+                    ext.is_supported()
+                        || (ext.is_opt_in()
+                            && self.allowed_token_extensions().any(|x| x == mint_ext))
+                })
+                .unwrap_or(false);
+            if !supported || self.blocked_token_extensions().any(|x| x == mint_ext) {
                 return err!(SwapError::InvalidTokenExtension);
             }
         }
@@ -100,70 +75,80 @@ impl<'a> SwapConstraints<'a> {
     }
 }
 
-#[cfg(feature = "production")]
-const OWNER_KEY: &str = env!("SWAP_PROGRAM_OWNER_FEE_ADDRESS");
-#[cfg(feature = "production")]
-const FEES: &Fees = &Fees {
-    trade_fee_numerator: 0,
-    trade_fee_denominator: 10000,
-    owner_trade_fee_numerator: 0,
-    owner_trade_fee_denominator: 10000,
-    owner_withdraw_fee_numerator: 0,
-    owner_withdraw_fee_denominator: 10000,
-    host_fee_numerator: 2000,
-    host_fee_denominator: 10000,
-};
-#[cfg(feature = "production")]
-const VALID_CURVE_TYPES: &[CurveType] = &[CurveType::ConstantPrice, CurveType::ConstantProduct];
-#[cfg(feature = "production")]
-const INVALID_TOKEN_2022_EXTENSIONS: &[ExtensionType] = &[ExtensionType::TransferFeeConfig];
-
-/// Fee structure defined by program creator in order to enforce certain
-/// fees when others use the program.  Adds checks on pool creation and
-/// swapping to ensure the correct fees and account owners are passed.
-/// Fees provided during production build currently are considered min
-/// fees that creator of the pool can specify. Host fee is a fixed
-/// percentage that host receives as a portion of owner fees
-pub const SWAP_CONSTRAINTS: Option<SwapConstraints> = {
-    #[cfg(feature = "production")]
-    {
-        Some(SwapConstraints {
-            owner_key: OWNER_KEY,
-            valid_curve_types: VALID_CURVE_TYPES,
-            fees: FEES,
-            blocked_trading_token_extensions: INVALID_TOKEN_2022_EXTENSIONS,
-        })
+/// Checks a fee is exactly the required fee, any fee matches an unset (zero) required fee
+fn fee_matches(
+    numerator: u64,
+    denominator: u64,
+    required_numerator: u64,
+    required_denominator: u64,
+) -> bool {
+    if required_numerator == 0 || required_denominator == 0 {
+        return true;
+    }
+    numerator == required_numerator && denominator == required_denominator
+}
+
+/// Compares fee fractions, a zero denominator is a zero fee
+fn fee_at_least(
+    numerator: u64,
+    denominator: u64,
+    min_numerator: u64,
+    min_denominator: u64,
+) -> bool {
+    if min_numerator == 0 || min_denominator == 0 {
+        return true;
     }
-    #[cfg(not(feature = "production"))]
-    {
-        None
+    if denominator == 0 {
+        return false;
     }
-};
+    u128::from(numerator) * u128::from(min_denominator)
+        >= u128::from(min_numerator) * u128::from(denominator)
+}
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
-
     use anchor_lang::{
-        prelude::{Clock, SolanaSysvar},
-        solana_program::{clock::Epoch, program_option::COption},
+        prelude::{Clock, Pubkey, SolanaSysvar},
+        solana_program::{clock::Epoch, program_option::COption, program_pack::Pack},
     };
     use anchor_spl::{
         token_2022::{
             spl_token_2022,
-            spl_token_2022::extension::{
-                transfer_fee::{TransferFee, TransferFeeConfig},
-                StateWithExtensionsMut,
+            spl_token_2022::{
+                extension::{
+                    default_account_state::DefaultAccountState,
+                    interest_bearing_mint::InterestBearingConfig,
+                    mint_close_authority::MintCloseAuthority,
+                    non_transferable::NonTransferable,
+                    permanent_delegate::PermanentDelegate,
+                    transfer_fee::{TransferFee, TransferFeeConfig},
+                    Extension, ExtensionType, StateWithExtensionsMut,
+                },
+                state::{Account, Mint},
             },
         },
         token_interface::spl_token_2022::pod::OptionalNonZeroPubkey,
     };
 
     use super::*;
-    use crate::{
-        curve::base::CurveType, instructions::test::runner::syscall_stubs::test_syscall_stubs,
-        state::ConstantProductCurve,
-    };
+    use crate::instructions::test::runner::syscall_stubs::test_syscall_stubs;
+
+    fn global_config(min_fees: Fees, valid_curve_types: &[CurveType]) -> GlobalConfig {
+        let mut global_config = GlobalConfig {
+            owner: Pubkey::new_unique(),
+            min_fees,
+            ..Default::default()
+        };
+        global_config
+            .set_valid_curve_types(
+                &valid_curve_types
+                    .iter()
+                    .map(|c| u64::from(*c))
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap();
+        global_config
+    }
 
     #[test]
     fn test_validate_fees() {
@@ -175,8 +160,6 @@ mod tests {
         let owner_withdraw_fee_denominator = 10;
         let host_fee_numerator = 10;
         let host_fee_denominator = 100;
-        let owner_key = "";
-        let curve_type = CurveType::ConstantProduct;
         let valid_fees = Fees {
             trade_fee_numerator,
             trade_fee_denominator,
@@ -187,21 +170,11 @@ mod tests {
             host_fee_numerator,
             host_fee_denominator,
         };
-        let calculator = ConstantProductCurve {
-            ..Default::default()
-        };
-        let swap_curve = SwapCurve {
-            curve_type,
-            calculator: Arc::new(calculator.clone()),
-        };
-        let constraints = SwapConstraints {
-            owner_key,
-            valid_curve_types: &[curve_type],
-            fees: &valid_fees,
-            blocked_trading_token_extensions: &[],
-        };
+        let constraints = global_config(valid_fees, &[CurveType::ConstantProduct]);
 
-        constraints.validate_curve(&swap_curve).unwrap();
+        constraints
+            .validate_curve(CurveType::ConstantProduct)
+            .unwrap();
         constraints.validate_fees(&valid_fees).unwrap();
 
         let mut fees = valid_fees;
@@ -213,18 +186,27 @@ mod tests {
         fees.trade_fee_numerator = trade_fee_numerator;
 
         // passing higher fee is ok
-        fees.trade_fee_numerator = trade_fee_numerator - 1;
-        assert_eq!(constraints.validate_fees(&valid_fees), Ok(()));
+        fees.trade_fee_numerator = trade_fee_numerator + 1;
+        assert_eq!(constraints.validate_fees(&fees), Ok(()));
         fees.trade_fee_numerator = trade_fee_numerator;
 
-        fees.trade_fee_denominator = trade_fee_denominator - 1;
+        // the same fee with a different denominator is ok
+        fees.trade_fee_numerator = trade_fee_numerator * 2;
+        fees.trade_fee_denominator = trade_fee_denominator * 2;
+        assert_eq!(constraints.validate_fees(&fees), Ok(()));
+        fees.trade_fee_numerator = trade_fee_numerator;
+        fees.trade_fee_denominator = trade_fee_denominator;
+
+        // lower fee through a higher denominator
+        fees.trade_fee_denominator = trade_fee_denominator + 1;
         assert_eq!(
             Err(SwapError::InvalidFee.into()),
             constraints.validate_fees(&fees),
         );
         fees.trade_fee_denominator = trade_fee_denominator;
 
-        fees.trade_fee_denominator = trade_fee_denominator + 1;
+        // zero denominator is a zero fee
+        fees.trade_fee_denominator = 0;
         assert_eq!(
             Err(SwapError::InvalidFee.into()),
             constraints.validate_fees(&fees),
@@ -238,57 +220,68 @@ mod tests {
         );
         fees.owner_trade_fee_numerator = owner_trade_fee_numerator;
 
-        // passing higher fee is ok
-        fees.owner_trade_fee_numerator = owner_trade_fee_numerator - 1;
-        assert_eq!(constraints.validate_fees(&valid_fees), Ok(()));
-        fees.owner_trade_fee_numerator = owner_trade_fee_numerator;
+        fees.owner_withdraw_fee_numerator = owner_withdraw_fee_numerator - 1;
+        assert_eq!(
+            Err(SwapError::InvalidFee.into()),
+            constraints.validate_fees(&fees),
+        );
+        fees.owner_withdraw_fee_numerator = owner_withdraw_fee_numerator;
 
-        fees.owner_trade_fee_denominator = owner_trade_fee_denominator - 1;
+        fees.host_fee_numerator = host_fee_numerator - 1;
         assert_eq!(
             Err(SwapError::InvalidFee.into()),
             constraints.validate_fees(&fees),
         );
-        fees.owner_trade_fee_denominator = owner_trade_fee_denominator;
+        fees.host_fee_numerator = host_fee_numerator;
+
+        // the host fee must match exactly, a higher host fee is rejected too
+        fees.host_fee_numerator = host_fee_numerator + 1;
+        assert_eq!(
+            Err(SwapError::InvalidFee.into()),
+            constraints.validate_fees(&fees),
+        );
+        fees.host_fee_numerator = host_fee_numerator;
+
+        // as is the same host fee with a different denominator
+        fees.host_fee_numerator = host_fee_numerator * 2;
+        fees.host_fee_denominator = host_fee_denominator * 2;
+        assert_eq!(
+            Err(SwapError::InvalidFee.into()),
+            constraints.validate_fees(&fees),
+        );
+        fees.host_fee_numerator = host_fee_numerator;
+        fees.host_fee_denominator = host_fee_denominator;
 
-        let swap_curve = SwapCurve {
-            curve_type: CurveType::ConstantPrice,
-            calculator: Arc::new(calculator),
-        };
         assert_eq!(
             Err(SwapError::UnsupportedCurveType.into()),
-            constraints.validate_curve(&swap_curve),
+            constraints.validate_curve(CurveType::ConstantPrice),
         );
     }
 
     #[test]
-    fn test_validate_admin() {
-        let key = Pubkey::new_unique();
-        let owner_key = &key.to_string();
-        let fees = Fees::default();
-        let constraints = SwapConstraints {
-            owner_key,
-            valid_curve_types: &[],
-            fees: &fees,
-            blocked_trading_token_extensions: &[],
-        };
+    fn test_validate_fees_without_min_fees() {
+        let constraints = global_config(Fees::default(), &[]);
 
-        constraints.validate_admin(&key).unwrap();
+        constraints.validate_fees(&Fees::default()).unwrap();
+        constraints
+            .validate_fees(&Fees {
+                trade_fee_numerator: 1,
+                trade_fee_denominator: 100,
+                host_fee_numerator: 1,
+                host_fee_denominator: 10,
+                ..Default::default()
+            })
+            .unwrap();
     }
 
     #[test]
-    fn test_validate_admin_fail_when_invalid_admin() {
-        let key = Pubkey::new_unique();
-        let owner_key = &key.to_string();
-        let fees = Fees::default();
-        let constraints = SwapConstraints {
-            owner_key,
-            valid_curve_types: &[],
-            fees: &fees,
-            blocked_trading_token_extensions: &[],
-        };
+    fn test_validate_curve_fails_without_valid_curve_types() {
+        let constraints = global_config(Fees::default(), &[]);
 
-        let res = constraints.validate_admin(&Pubkey::new_unique());
-        assert_eq!(res.err(), Some(SwapError::InvaliPoolAdmin.into()));
+        assert_eq!(
+            Err(SwapError::UnsupportedCurveType.into()),
+            constraints.validate_curve(CurveType::ConstantProduct),
+        );
     }
 
     #[test]
@@ -312,14 +305,7 @@ mod tests {
             Epoch::default(),
         );
 
-        let owner_key = "";
-        let fees = Fees::default();
-        let constraints = SwapConstraints {
-            owner_key,
-            valid_curve_types: &[],
-            fees: &fees,
-            blocked_trading_token_extensions: &[],
-        };
+        let constraints = global_config(Fees::default(), &[]);
 
         constraints
             .validate_token_2022_trading_token_extensions(&mint_info)
@@ -347,19 +333,145 @@ mod tests {
             Epoch::default(),
         );
 
-        let owner_key = "";
-        let fees = Fees::default();
-        let constraints = SwapConstraints {
-            owner_key,
-            valid_curve_types: &[],
-            fees: &fees,
-            blocked_trading_token_extensions: &[ExtensionType::TransferFeeConfig],
-        };
+        let mut constraints = global_config(Fees::default(), &[]);
+        constraints
+            .set_blocked_token_extensions(&[ExtensionType::TransferFeeConfig.into()])
+            .unwrap();
 
         let res = constraints.validate_token_2022_trading_token_extensions(&mint_info);
         assert_eq!(res.err(), Some(SwapError::InvalidTokenExtension.into()));
     }
 
+    #[test]
+    fn test_validate_trading_token_extensions_when_extension_supported() {
+        test_syscall_stubs();
+        let constraints = global_config(Fees::default(), &[]);
+
+        for mut mint_data in [
+            mint_with_extension::<InterestBearingConfig>(),
+            mint_with_extension::<MintCloseAuthority>(),
+        ] {
+            with_mint_info(&mut mint_data, |mint_info| {
+                constraints
+                    .validate_token_2022_trading_token_extensions(mint_info)
+                    .unwrap();
+            });
+        }
+    }
+
+    #[test]
+    fn test_validate_trading_token_extensions_fail_when_extension_unsupported() {
+        test_syscall_stubs();
+        let constraints = global_config(Fees::default(), &[]);
+
+        for mut mint_data in [
+            mint_with_extension::<PermanentDelegate>(),
+            mint_with_extension::<NonTransferable>(),
+            mint_with_extension::<DefaultAccountState>(),
+        ] {
+            with_mint_info(&mut mint_data, |mint_info| {
+                let res = constraints.validate_token_2022_trading_token_extensions(mint_info);
+                assert_eq!(res.err(), Some(SwapError::InvalidTokenExtension.into()));
+            });
+        }
+    }
+
+    #[test]
+    fn test_validate_trading_token_extensions_fail_when_extension_unknown() {
+        test_syscall_stubs();
+        let constraints = global_config(Fees::default(), &[]);
+
+        let mut mint_data = mint_with_extension::<MintCloseAuthority>();
+        // overwrite the type of the first tlv entry, right after the mint account type
+        let tlv_start = Account::LEN + 1;
+        mint_data[tlv_start..tlv_start + 2].copy_from_slice(&999u16.to_le_bytes());
+        with_mint_info(&mut mint_data, |mint_info| {
+            let res = constraints.validate_token_2022_trading_token_extensions(mint_info);
+            assert_eq!(res.err(), Some(SwapError::InvalidTokenExtension.into()));
+        });
+    }
+
+    #[test]
+    fn test_validate_trading_token_extensions_fail_when_supported_extension_blocked() {
+        test_syscall_stubs();
+        let mut constraints = global_config(Fees::default(), &[]);
+        constraints
+            .set_blocked_token_extensions(&[ExtensionType::InterestBearingConfig.into()])
+            .unwrap();
+
+        let mut mint_data = mint_with_extension::<InterestBearingConfig>();
+        with_mint_info(&mut mint_data, |mint_info| {
+            let res = constraints.validate_token_2022_trading_token_extensions(mint_info);
+            assert_eq!(res.err(), Some(SwapError::InvalidTokenExtension.into()));
+        });
+    }
+
+    #[test]
+    fn test_validate_trading_token_extensions_when_opt_in_extension_allowed() {
+        test_syscall_stubs();
+        let mut constraints = global_config(Fees::default(), &[]);
+
+        // the spl-token-2022 version built against cannot write a transfer hook extension, so
+        // overwrite the type of the first tlv entry, right after the mint account type
+        let mut mint_data = mint_with_extension::<MintCloseAuthority>();
+        let tlv_start = Account::LEN + 1;
+        mint_data[tlv_start..tlv_start + 2]
+            .copy_from_slice(&u16::from(MintExtension::TransferHook).to_le_bytes());
+
+        with_mint_info(&mut mint_data, |mint_info| {
+            let res = constraints.validate_token_2022_trading_token_extensions(mint_info);
+            assert_eq!(res.err(), Some(SwapError::InvalidTokenExtension.into()));
+        });
+
+        constraints
+            .set_allowed_token_extensions(&[MintExtension::TransferHook.into()])
+            .unwrap();
+        with_mint_info(&mut mint_data, |mint_info| {
+            constraints
+                .validate_token_2022_trading_token_extensions(mint_info)
+                .unwrap();
+        });
+
+        // blocking takes precedence over allowing
+        constraints
+            .set_blocked_token_extensions(&[MintExtension::TransferHook.into()])
+            .unwrap();
+        with_mint_info(&mut mint_data, |mint_info| {
+            let res = constraints.validate_token_2022_trading_token_extensions(mint_info);
+            assert_eq!(res.err(), Some(SwapError::InvalidTokenExtension.into()));
+        });
+    }
+
+    fn with_mint_info(mint_data: &mut [u8], f: impl FnOnce(&AccountInfo)) {
+        let key = Pubkey::new_unique();
+        let mut lamports = u64::MAX;
+        let token_program = spl_token_2022::id();
+        let mint_info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            mint_data,
+            &token_program,
+            false,
+            Epoch::default(),
+        );
+        f(&mint_info);
+    }
+
+    fn mint_with_extension<V: Extension>() -> Vec<u8> {
+        let mut mint_data = vec![0; ExtensionType::get_account_len::<Mint>(&[V::TYPE])];
+        let mut mint =
+            StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut mint_data).unwrap();
+        mint.init_extension::<V>(true).unwrap();
+        mint.base.decimals = 6;
+        mint.base.is_initialized = true;
+        mint.base.mint_authority = COption::Some(Pubkey::new_unique());
+        mint.pack_base();
+        mint.init_account_type().unwrap();
+        mint_data
+    }
+
     fn mint_with_transfer_fee(mint_data: &mut [u8], transfer_fee_bps: u16) {
         let mut mint =
             StateWithExtensionsMut::<anchor_spl::token_2022::spl_token_2022::state::Mint>::unpack_uninitialized(mint_data)
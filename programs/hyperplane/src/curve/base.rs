@@ -2,22 +2,32 @@
 
 use std::{fmt::Debug, sync::Arc};
 
-use anchor_lang::Result;
+use anchor_lang::{
+    prelude::{borsh, Pubkey},
+    AnchorDeserialize, AnchorSerialize, Result,
+};
 #[cfg(feature = "fuzz")]
 use arbitrary::Arbitrary;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
+use spl_math::precise_number::PreciseNumber;
 
 use crate::{
     curve::{
-        calculator::{CurveCalculator, SwapWithoutFeesResult, TradeDirection},
+        calculator::{CurveCalculator, RoundDirection, SwapWithoutFeesResult, TradeDirection},
         fees::Fees,
     },
-    model::CurveParameters,
-    state::{ConstantPriceCurve, ConstantProductCurve, OffsetCurve, StableCurve},
-    try_math,
-    utils::math::TryMath,
+    error::SwapError,
+    require_msg,
+    state::{
+        ConcentratedCurve, ConstantPriceCurve, ConstantProductCurve, OffsetCurve, OraclePriceCurve,
+        StableCurve, WeightedCurve,
+    },
+    to_u64, try_math,
+    utils::math::{TryMath, TryMathRef, TryNew},
 };
 
+const BPS_DENOMINATOR: u128 = 10_000;
+
 /// Curve types supported by the hyperplane program.
 #[cfg_attr(feature = "fuzz", derive(Arbitrary))]
 #[repr(u64)]
@@ -31,6 +41,43 @@ pub enum CurveType {
     Offset = 3,
     /// Stable curve, like constant product with less slippage around a fixed price
     Stable = 4,
+    /// Flat line like the constant price curve, with the token B price read from a price feed
+    OraclePrice = 5,
+    /// Constant product on virtual reserves, only trading within a fixed price range
+    Concentrated = 6,
+    /// Balancer-style curve, invariant = token_a_amount^weight_a * token_b_amount^weight_b
+    Weighted = 7,
+}
+
+/// Parameters of each curve type, as resolved at pool initialization
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum CurveParameters {
+    ConstantProduct,
+    ConstantPrice {
+        token_b_price: u64,
+    },
+    Offset {
+        token_b_offset: u64,
+    },
+    Stable {
+        amp: u64,
+        token_a_decimals: u8,
+        token_b_decimals: u8,
+    },
+    OraclePrice {
+        price_feed: Pubkey,
+        price_feed_program: Pubkey,
+        max_price_age_sec: u64,
+        max_price_confidence_bps: u64,
+        token_b_price_x64: u128,
+    },
+    Concentrated {
+        price_lower_x64: u128,
+        price_upper_x64: u128,
+    },
+    Weighted {
+        token_a_weight_bps: u64,
+    },
 }
 
 /// Encodes all results of swapping from a source token to a destination token
@@ -56,6 +103,57 @@ pub struct SwapResult {
     pub owner_fee: u128,
 }
 
+/// Expected outcome of swapping from a source token to a destination token,
+/// for quoting trades off-chain
+#[derive(Debug, PartialEq)]
+pub struct SwapQuote {
+    /// Amount of destination token sent to the user
+    pub destination_amount: u128,
+    /// Amount of source tokens going to pool holders
+    pub trade_fee: u128,
+    /// Amount of source tokens going to owner (excludes: host fee)
+    pub owner_fee: u128,
+    /// Amount of source tokens going to the host, taken out of the owner fee
+    pub host_fee: u128,
+    /// Price of the source token in the destination token before the swap
+    pub spot_price: PreciseNumber,
+    /// Destination tokens received per source token debited from the user (includes: all fees)
+    pub effective_price: PreciseNumber,
+    /// Shortfall of the price the swap executes at (excludes: all fees) from the spot price,
+    /// in basis points
+    pub price_impact_bps: u64,
+}
+
+/// Encodes all results of depositing a single trading token into the pool
+#[derive(Debug, PartialEq)]
+pub struct DepositSingleTokenTypeResult {
+    /// Amount of pool tokens to mint for the deposit
+    pub pool_token_amount: u128,
+    /// Amount of source token to transfer to the vault (excludes: owner fees)
+    pub source_amount_to_vault: u128,
+    /// Total fees paid in source tokens (includes: owner + trading fees)
+    pub total_fees: u128,
+    /// Amount of source tokens going to pool holders
+    pub trade_fee: u128,
+    /// Amount of source tokens going to owner
+    pub owner_fee: u128,
+}
+
+/// Encodes all results of withdrawing a single trading token from the pool
+#[derive(Debug, PartialEq)]
+pub struct WithdrawSingleTokenTypeResult {
+    /// Amount of pool tokens to burn for the withdrawal
+    pub pool_token_amount: u128,
+    /// Amount of destination token to transfer to the user (excludes: all fees)
+    pub destination_amount: u128,
+    /// Total fees paid in destination tokens (includes: owner + trading fees)
+    pub total_fees: u128,
+    /// Amount of destination tokens going to pool holders
+    pub trade_fee: u128,
+    /// Amount of destination tokens going to owner (includes: owner withdraw + owner trading fees)
+    pub owner_fee: u128,
+}
+
 /// Concrete struct to wrap around the trait object which performs calculation.
 #[repr(C)]
 #[derive(Debug, Clone)]
@@ -99,6 +197,35 @@ impl SwapCurve {
                 curve_type: CurveType::Stable,
                 calculator: Arc::new(StableCurve::new(amp, token_a_decimals, token_b_decimals)?),
             },
+            CurveParameters::OraclePrice {
+                price_feed,
+                price_feed_program,
+                max_price_age_sec,
+                max_price_confidence_bps,
+                token_b_price_x64,
+            } => SwapCurve {
+                curve_type: CurveType::OraclePrice,
+                calculator: Arc::new(OraclePriceCurve {
+                    token_b_price_x64,
+                    ..OraclePriceCurve::new(
+                        price_feed,
+                        price_feed_program,
+                        max_price_age_sec,
+                        max_price_confidence_bps,
+                    )
+                }),
+            },
+            CurveParameters::Concentrated {
+                price_lower_x64,
+                price_upper_x64,
+            } => SwapCurve {
+                curve_type: CurveType::Concentrated,
+                calculator: Arc::new(ConcentratedCurve::new(price_lower_x64, price_upper_x64)?),
+            },
+            CurveParameters::Weighted { token_a_weight_bps } => SwapCurve {
+                curve_type: CurveType::Weighted,
+                calculator: Arc::new(WeightedCurve::new(token_a_weight_bps)),
+            },
         };
         Ok(curve)
     }
@@ -146,6 +273,294 @@ impl SwapCurve {
             owner_fee,
         })
     }
+
+    /// Quote a swap of an amount of source token, splitting the fees the same
+    /// way as the swap instruction with or without a host fees account.
+    pub fn quote(
+        &self,
+        source_amount: u128,
+        pool_source_amount: u128,
+        pool_destination_amount: u128,
+        trade_direction: TradeDirection,
+        fees: &Fees,
+        with_host_fee: bool,
+    ) -> Result<SwapQuote> {
+        let (pool_token_a_amount, pool_token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (pool_source_amount, pool_destination_amount),
+            TradeDirection::BtoA => (pool_destination_amount, pool_source_amount),
+        };
+        let spot_price = self.calculator.spot_price(
+            pool_token_a_amount,
+            pool_token_b_amount,
+            trade_direction,
+        )?;
+
+        let SwapResult {
+            total_source_amount_swapped,
+            source_amount_swapped,
+            destination_amount_swapped,
+            trade_fee,
+            owner_fee,
+            ..
+        } = self.swap(
+            source_amount,
+            pool_source_amount,
+            pool_destination_amount,
+            trade_direction,
+            fees,
+        )?;
+        let host_fee = if with_host_fee {
+            try_math!(fees.host_fee(owner_fee))?
+        } else {
+            0
+        };
+
+        let destination_amount = PreciseNumber::try_new(destination_amount_swapped)?;
+        let effective_price =
+            destination_amount.try_div(&PreciseNumber::try_new(total_source_amount_swapped)?)?;
+        let execution_price =
+            destination_amount.try_div(&PreciseNumber::try_new(source_amount_swapped)?)?;
+        let price_impact_bps = if execution_price.less_than(&spot_price) {
+            let price_impact_bps = spot_price
+                .try_sub(&execution_price)?
+                .try_mul(&PreciseNumber::try_new(BPS_DENOMINATOR)?)?
+                .try_div(&spot_price)?
+                .try_floor()?
+                .try_to_imprecise()?;
+            to_u64!(price_impact_bps)?
+        } else {
+            0
+        };
+
+        Ok(SwapQuote {
+            destination_amount: destination_amount_swapped,
+            trade_fee,
+            owner_fee: try_math!(owner_fee.try_sub(host_fee))?,
+            host_fee,
+            spot_price,
+            effective_price,
+            price_impact_bps,
+        })
+    }
+
+    /// Calculate how much source token, including fees, is required to
+    /// provide exactly the given amount of destination token.
+    pub fn swap_exact_out(
+        &self,
+        destination_amount: u128,
+        pool_source_amount: u128,
+        pool_destination_amount: u128,
+        trade_direction: TradeDirection,
+        fees: &Fees,
+    ) -> Result<SwapResult> {
+        let SwapWithoutFeesResult {
+            source_amount_swapped,
+            destination_amount_swapped,
+        } = self.calculator.swap_without_fees_exact_out(
+            destination_amount,
+            pool_source_amount,
+            pool_destination_amount,
+            trade_direction,
+        )?;
+
+        // gross up the source amount by the fees, the trade and owner fees are
+        // calculated separately and each rounded up to at least 1 token, so the
+        // combined inverse can fall short by a few tokens
+        let mut total_source_amount_swapped =
+            try_math!(fees.pre_trading_fee_amount(source_amount_swapped))?;
+        let (trade_fee, owner_fee) = loop {
+            let trade_fee = try_math!(fees.trading_fee(total_source_amount_swapped))?;
+            let owner_fee = try_math!(fees.owner_trading_fee(total_source_amount_swapped))?;
+            let required = try_math!(source_amount_swapped.try_add(trade_fee)?.try_add(owner_fee))?;
+            if required <= total_source_amount_swapped {
+                break (trade_fee, owner_fee);
+            }
+            total_source_amount_swapped = required;
+        };
+
+        let total_fees = try_math!(trade_fee.try_add(owner_fee))?;
+        // any excess from rounding the fees is swapped into the pool
+        let source_amount_swapped = try_math!(total_source_amount_swapped.try_sub(total_fees))?;
+        let source_amount_to_vault = try_math!(source_amount_swapped.try_add(trade_fee))?;
+        Ok(SwapResult {
+            new_pool_source_amount: try_math!(pool_source_amount.try_add(source_amount_to_vault))?,
+            new_pool_destination_amount: try_math!(
+                pool_destination_amount.try_sub(destination_amount_swapped)
+            )?,
+            total_source_amount_swapped,
+            source_amount_swapped,
+            destination_amount_swapped,
+            source_amount_to_vault,
+            total_fees,
+            trade_fee,
+            owner_fee,
+        })
+    }
+
+    /// Subtract fees and calculate how many pool tokens will be minted for
+    /// depositing an amount of token A or B.
+    ///
+    /// A single sided deposit is equivalent to swapping half of the source
+    /// amount for the other token and depositing both sides, so the trading
+    /// and owner fees are charged on half of the source amount only.
+    pub fn deposit_single_token_type(
+        &self,
+        source_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        fees: &Fees,
+    ) -> Result<DepositSingleTokenTypeResult> {
+        let half_source_amount = std::cmp::max(1, try_math!(source_amount.try_div(2))?);
+        let trade_fee = try_math!(fees.trading_fee(half_source_amount))?;
+        let owner_fee = try_math!(fees.owner_trading_fee(half_source_amount))?;
+
+        let total_fees = try_math!(trade_fee.try_add(owner_fee))?;
+        let source_amount_less_fees = try_math!(source_amount.try_sub(total_fees))?;
+
+        let pool_token_amount = self.calculator.trading_tokens_to_pool_tokens(
+            source_amount_less_fees,
+            pool_token_a_amount,
+            pool_token_b_amount,
+            pool_token_supply,
+            trade_direction,
+            RoundDirection::Floor,
+        )?;
+
+        Ok(DepositSingleTokenTypeResult {
+            pool_token_amount,
+            source_amount_to_vault: try_math!(source_amount.try_sub(owner_fee))?,
+            total_fees,
+            trade_fee,
+            owner_fee,
+        })
+    }
+
+    /// Add fees and calculate how many pool tokens must be burned to withdraw
+    /// exactly an amount of token A or B.
+    ///
+    /// The owner withdraw fee is charged on the amount withdrawn, as with a
+    /// regular withdrawal. A single sided withdrawal is also equivalent to
+    /// withdrawing both sides and swapping the other token for half of the
+    /// amount, so the trading and owner fees are charged on half of the amount.
+    pub fn withdraw_single_token_type_exact_out(
+        &self,
+        destination_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        fees: &Fees,
+    ) -> Result<WithdrawSingleTokenTypeResult> {
+        let pre_withdraw_fee_amount =
+            try_math!(fees.pre_owner_withdraw_fee_amount(destination_amount))?;
+        let owner_withdraw_fee = try_math!(fees.owner_withdraw_fee(pre_withdraw_fee_amount))?;
+        let withdraw_amount = try_math!(destination_amount.try_add(owner_withdraw_fee))?;
+
+        let half_withdraw_amount = std::cmp::max(1, try_math!(withdraw_amount.try_div(2))?);
+        let trade_fee = try_math!(fees.trading_fee(half_withdraw_amount))?;
+        let owner_trade_fee = try_math!(fees.owner_trading_fee(half_withdraw_amount))?;
+        let owner_fee = try_math!(owner_withdraw_fee.try_add(owner_trade_fee))?;
+        let total_fees = try_math!(owner_fee.try_add(trade_fee))?;
+
+        let withdraw_amount_with_fees = try_math!(destination_amount.try_add(total_fees))?;
+        let pool_destination_amount = match trade_direction {
+            TradeDirection::AtoB => pool_token_a_amount,
+            TradeDirection::BtoA => pool_token_b_amount,
+        };
+        require_msg!(
+            withdraw_amount_with_fees < pool_destination_amount,
+            SwapError::ZeroTradingTokens,
+            &format!(
+                "Withdraw amount with fees {} must be less than the pool destination amount {}",
+                withdraw_amount_with_fees, pool_destination_amount
+            )
+        );
+
+        let pool_token_amount = self.calculator.withdraw_single_token_type_exact_out(
+            withdraw_amount_with_fees,
+            pool_token_a_amount,
+            pool_token_b_amount,
+            pool_token_supply,
+            trade_direction,
+            RoundDirection::Ceiling,
+        )?;
+
+        Ok(WithdrawSingleTokenTypeResult {
+            pool_token_amount,
+            destination_amount,
+            total_fees,
+            trade_fee,
+            owner_fee,
+        })
+    }
+
+    /// Calculate how much of token A or B is withdrawn for burning an amount
+    /// of pool tokens, and subtract fees.
+    ///
+    /// The trading and owner fees are charged on half of the amount withdrawn,
+    /// and the owner withdraw fee on the remainder, as in
+    /// `withdraw_single_token_type_exact_out`.
+    pub fn withdraw_one_token(
+        &self,
+        pool_token_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        fees: &Fees,
+    ) -> Result<WithdrawSingleTokenTypeResult> {
+        require_msg!(
+            pool_token_amount < pool_token_supply,
+            SwapError::ZeroTradingTokens,
+            &format!(
+                "Pool token amount {} must be less than the pool token supply {}",
+                pool_token_amount, pool_token_supply
+            )
+        );
+        let withdraw_amount_with_fees = self.calculator.withdraw_one_token(
+            pool_token_amount,
+            pool_token_a_amount,
+            pool_token_b_amount,
+            pool_token_supply,
+            trade_direction,
+            RoundDirection::Floor,
+        )?;
+        let pool_destination_amount = match trade_direction {
+            TradeDirection::AtoB => pool_token_a_amount,
+            TradeDirection::BtoA => pool_token_b_amount,
+        };
+        require_msg!(
+            withdraw_amount_with_fees < pool_destination_amount,
+            SwapError::ZeroTradingTokens,
+            &format!(
+                "Withdraw amount {} must be less than the pool destination amount {}",
+                withdraw_amount_with_fees, pool_destination_amount
+            )
+        );
+
+        let half_withdraw_amount =
+            std::cmp::max(1, try_math!(withdraw_amount_with_fees.try_div(2))?);
+        let trade_fee = try_math!(fees.trading_fee(half_withdraw_amount))?;
+        let owner_trade_fee = try_math!(fees.owner_trading_fee(half_withdraw_amount))?;
+        let withdraw_amount = try_math!(withdraw_amount_with_fees
+            .try_sub(trade_fee)?
+            .try_sub(owner_trade_fee))?;
+        let owner_withdraw_fee = try_math!(fees.owner_withdraw_fee(withdraw_amount))?;
+        let destination_amount = try_math!(withdraw_amount.try_sub(owner_withdraw_fee))?;
+
+        let owner_fee = try_math!(owner_withdraw_fee.try_add(owner_trade_fee))?;
+        let total_fees = try_math!(owner_fee.try_add(trade_fee))?;
+
+        Ok(WithdrawSingleTokenTypeResult {
+            pool_token_amount,
+            destination_amount,
+            total_fees,
+            trade_fee,
+            owner_fee,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -273,4 +688,421 @@ mod test {
         assert_eq!(result.destination_amount_swapped, 4545);
         assert_eq!(result.new_pool_destination_amount, 45455);
     }
+
+    #[test]
+    fn constant_product_swap_exact_out_no_fee() {
+        let swap_source_amount: u128 = 1_000;
+        let swap_destination_amount: u128 = 50_000;
+        let destination_amount: u128 = 4545;
+        let curve = ConstantProductCurve::default();
+        let fees = Fees::default();
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: Arc::new(curve),
+        };
+        let result = swap_curve
+            .swap_exact_out(
+                destination_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                TradeDirection::AtoB,
+                &fees,
+            )
+            .unwrap();
+        assert_eq!(result.new_pool_source_amount, 1100);
+        assert_eq!(result.total_source_amount_swapped, 100);
+        assert_eq!(result.destination_amount_swapped, 4545);
+        assert_eq!(result.new_pool_destination_amount, 45455);
+        assert_eq!(result.total_fees, 0);
+    }
+
+    #[test]
+    fn constant_product_swap_exact_out_trade_and_owner_fee() {
+        let swap_source_amount: u128 = 1_000;
+        let swap_destination_amount: u128 = 50_000;
+        let fees = Fees {
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            owner_trade_fee_numerator: 1,
+            owner_trade_fee_denominator: 100,
+            ..Default::default()
+        };
+        let curve = ConstantProductCurve::default();
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: Arc::new(curve),
+        };
+        for destination_amount in [1, 10, 4_545, 4_713, 25_000, 40_000] {
+            let result = swap_curve
+                .swap_exact_out(
+                    destination_amount,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    TradeDirection::AtoB,
+                    &fees,
+                )
+                .unwrap();
+            assert_eq!(result.destination_amount_swapped, destination_amount);
+            assert_eq!(
+                result.total_source_amount_swapped,
+                result.source_amount_swapped + result.total_fees
+            );
+            assert_eq!(result.total_fees, result.trade_fee + result.owner_fee);
+            assert_eq!(
+                result.trade_fee,
+                fees.trading_fee(result.total_source_amount_swapped)
+                    .unwrap()
+            );
+            assert_eq!(
+                result.owner_fee,
+                fees.owner_trading_fee(result.total_source_amount_swapped)
+                    .unwrap()
+            );
+
+            // swapping the total source amount in gives at least the destination amount out
+            let exact_in_result = swap_curve
+                .swap(
+                    result.total_source_amount_swapped,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    TradeDirection::AtoB,
+                    &fees,
+                )
+                .unwrap();
+            assert!(exact_in_result.destination_amount_swapped >= destination_amount);
+        }
+    }
+
+    #[test]
+    fn constant_price_swap_exact_out_fee_rounding() {
+        // 1 source token is required, the inverse fee amount is 2 but the
+        // minimum fee of 1 on each of the trade and owner fees means that 3
+        // tokens are needed
+        let fees = Fees {
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            owner_trade_fee_numerator: 1,
+            owner_trade_fee_denominator: 100,
+            ..Default::default()
+        };
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::ConstantPrice,
+            calculator: Arc::new(ConstantPriceCurve {
+                token_b_price: 1,
+                ..Default::default()
+            }),
+        };
+        let result = swap_curve
+            .swap_exact_out(1, 1_000, 1_000, TradeDirection::AtoB, &fees)
+            .unwrap();
+        assert_eq!(fees.pre_trading_fee_amount(1).unwrap(), 2);
+        assert_eq!(result.total_source_amount_swapped, 3);
+        assert_eq!(result.source_amount_swapped, 1);
+        assert_eq!(result.trade_fee, 1);
+        assert_eq!(result.owner_fee, 1);
+        assert_eq!(result.source_amount_to_vault, 2);
+        assert_eq!(result.new_pool_source_amount, 1_002);
+        assert_eq!(result.new_pool_destination_amount, 999);
+    }
+
+    #[test]
+    fn constant_product_deposit_single_token_type_no_fee() {
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: Arc::new(ConstantProductCurve::default()),
+        };
+        let result = swap_curve
+            .deposit_single_token_type(
+                3_000,
+                1_000,
+                1_000,
+                1_000,
+                TradeDirection::AtoB,
+                &Fees::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            result,
+            DepositSingleTokenTypeResult {
+                pool_token_amount: 1_000,
+                source_amount_to_vault: 3_000,
+                total_fees: 0,
+                trade_fee: 0,
+                owner_fee: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn constant_product_deposit_single_token_type_fees_on_half() {
+        let fees = Fees {
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            owner_trade_fee_numerator: 1,
+            owner_trade_fee_denominator: 100,
+            ..Default::default()
+        };
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: Arc::new(ConstantProductCurve::default()),
+        };
+        // fees are charged on 1_500, and the remaining 2_970 is deposited:
+        // 1_000 * (sqrt(1 + 2_970 / 1_000) - 1) = 992.48
+        let result = swap_curve
+            .deposit_single_token_type(3_000, 1_000, 1_000, 1_000, TradeDirection::BtoA, &fees)
+            .unwrap();
+        assert_eq!(
+            result,
+            DepositSingleTokenTypeResult {
+                pool_token_amount: 992,
+                source_amount_to_vault: 2_985,
+                total_fees: 30,
+                trade_fee: 15,
+                owner_fee: 15,
+            }
+        );
+
+        // the minimum fees are more than the deposit
+        assert!(swap_curve
+            .deposit_single_token_type(1, 1_000, 1_000, 1_000, TradeDirection::AtoB, &fees)
+            .is_err());
+    }
+
+    #[test]
+    fn constant_product_withdraw_single_token_type_no_fee() {
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: Arc::new(ConstantProductCurve::default()),
+        };
+        // 1_000 * (1 - sqrt(1 - 750 / 1_000)) = 500
+        let result = swap_curve
+            .withdraw_single_token_type_exact_out(
+                750,
+                1_000,
+                1_000,
+                1_000,
+                TradeDirection::AtoB,
+                &Fees::default(),
+            )
+            .unwrap();
+        let expected = WithdrawSingleTokenTypeResult {
+            pool_token_amount: 500,
+            destination_amount: 750,
+            total_fees: 0,
+            trade_fee: 0,
+            owner_fee: 0,
+        };
+        assert_eq!(result, expected);
+
+        let result = swap_curve
+            .withdraw_one_token(
+                500,
+                1_000,
+                1_000,
+                1_000,
+                TradeDirection::AtoB,
+                &Fees::default(),
+            )
+            .unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn constant_product_withdraw_single_token_type_fees() {
+        let fees = Fees {
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            owner_trade_fee_numerator: 1,
+            owner_trade_fee_denominator: 100,
+            owner_withdraw_fee_numerator: 1,
+            owner_withdraw_fee_denominator: 100,
+            ..Default::default()
+        };
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: Arc::new(ConstantProductCurve::default()),
+        };
+        // owner withdraw fee of 10 on 1_011, then fees are charged on half of 1_010,
+        // so 1_020 is withdrawn from the curve:
+        // 10_000 * (1 - sqrt(1 - 1_020 / 10_000)) = 523.7
+        let result = swap_curve
+            .withdraw_single_token_type_exact_out(
+                1_000,
+                10_000,
+                10_000,
+                10_000,
+                TradeDirection::BtoA,
+                &fees,
+            )
+            .unwrap();
+        let expected = WithdrawSingleTokenTypeResult {
+            pool_token_amount: 524,
+            destination_amount: 1_000,
+            total_fees: 20,
+            trade_fee: 5,
+            owner_fee: 15,
+        };
+        assert_eq!(result, expected);
+
+        // 10_000 * (1 - (1 - 524 / 10_000)^2) = 1_020.5
+        let result = swap_curve
+            .withdraw_one_token(524, 10_000, 10_000, 10_000, TradeDirection::BtoA, &fees)
+            .unwrap();
+        assert_eq!(result, expected);
+
+        // can't withdraw the whole pool side
+        assert!(swap_curve
+            .withdraw_single_token_type_exact_out(
+                9_900,
+                10_000,
+                10_000,
+                10_000,
+                TradeDirection::BtoA,
+                &fees
+            )
+            .is_err());
+        assert!(swap_curve
+            .withdraw_one_token(10_000, 10_000, 10_000, 10_000, TradeDirection::BtoA, &fees)
+            .is_err());
+    }
+
+    #[test]
+    fn quote_constant_product() {
+        let fees = Fees {
+            trade_fee_numerator: 25,
+            trade_fee_denominator: 10_000,
+            owner_trade_fee_numerator: 5,
+            owner_trade_fee_denominator: 10_000,
+            host_fee_numerator: 20,
+            host_fee_denominator: 100,
+            ..Default::default()
+        };
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: Arc::new(ConstantProductCurve::default()),
+        };
+
+        let quote = swap_curve
+            .quote(
+                100_000,
+                1_000_000,
+                2_000_000,
+                TradeDirection::AtoB,
+                &fees,
+                true,
+            )
+            .unwrap();
+        let result = swap_curve
+            .swap(100_000, 1_000_000, 2_000_000, TradeDirection::AtoB, &fees)
+            .unwrap();
+        assert_eq!(quote.destination_amount, result.destination_amount_swapped);
+        assert_eq!(quote.destination_amount, 181_322);
+        assert_eq!(quote.trade_fee, 250);
+        assert_eq!(quote.owner_fee, 40);
+        assert_eq!(quote.host_fee, 10);
+        assert_eq!(quote.spot_price, PreciseNumber::new(2).unwrap());
+        assert_eq!(
+            quote.effective_price,
+            PreciseNumber::new(181_322)
+                .unwrap()
+                .checked_div(&PreciseNumber::new(100_000).unwrap())
+                .unwrap()
+        );
+        // 10_000 * (1 - 181_322 / 99_700 / 2) = 906.6
+        assert_eq!(quote.price_impact_bps, 906);
+
+        // without a host fees account the owner keeps the whole owner fee
+        let quote = swap_curve
+            .quote(
+                100_000,
+                1_000_000,
+                2_000_000,
+                TradeDirection::AtoB,
+                &fees,
+                false,
+            )
+            .unwrap();
+        assert_eq!(quote.owner_fee, 50);
+        assert_eq!(quote.host_fee, 0);
+
+        // the opposite direction is priced in token A
+        let quote = swap_curve
+            .quote(
+                100_000,
+                2_000_000,
+                1_000_000,
+                TradeDirection::BtoA,
+                &Fees::default(),
+                false,
+            )
+            .unwrap();
+        assert_eq!(
+            quote.spot_price,
+            PreciseNumber::new(1)
+                .unwrap()
+                .checked_div(&PreciseNumber::new(2).unwrap())
+                .unwrap()
+        );
+        assert_eq!(quote.destination_amount, 47_619);
+        // 10_000 * (1 - 47_619 / 100_000 * 2) = 476.2
+        assert_eq!(quote.price_impact_bps, 476);
+    }
+
+    #[test]
+    fn quote_constant_price_has_no_price_impact() {
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::ConstantPrice,
+            calculator: Arc::new(ConstantPriceCurve {
+                token_b_price: 4,
+                ..Default::default()
+            }),
+        };
+
+        let quote = swap_curve
+            .quote(
+                1_000,
+                1_000_000,
+                1_000_000,
+                TradeDirection::BtoA,
+                &Fees::default(),
+                false,
+            )
+            .unwrap();
+        assert_eq!(quote.destination_amount, 4_000);
+        assert_eq!(quote.spot_price, PreciseNumber::new(4).unwrap());
+        assert_eq!(quote.effective_price, PreciseNumber::new(4).unwrap());
+        assert_eq!(quote.price_impact_bps, 0);
+    }
+
+    #[test]
+    fn quote_small_trades_have_no_price_impact() {
+        let swap_curve = SwapCurve {
+            curve_type: CurveType::Stable,
+            calculator: Arc::new(StableCurve::new(100, 6, 6).unwrap()),
+        };
+
+        let quote = swap_curve
+            .quote(
+                1_000_000,
+                1_000_000_000_000,
+                1_000_000_000_000,
+                TradeDirection::AtoB,
+                &Fees::default(),
+                false,
+            )
+            .unwrap();
+        assert_eq!(quote.price_impact_bps, 0);
+
+        // the pool cannot be drained to quote the swap
+        assert!(swap_curve
+            .quote(
+                1_000_000,
+                0,
+                1_000_000,
+                TradeDirection::AtoB,
+                &Fees::default(),
+                false,
+            )
+            .is_err());
+    }
 }
@@ -4,7 +4,7 @@
 
 use std::fmt::Debug;
 
-use anchor_lang::Result;
+use anchor_lang::{prelude::borsh, AnchorDeserialize, AnchorSerialize, Result};
 #[cfg(feature = "fuzz")]
 use arbitrary::Arbitrary;
 use spl_math::precise_number::PreciseNumber;
@@ -34,7 +34,8 @@ pub enum TradeDirection {
 }
 
 /// Utility to represent either token A or token B
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[derive(Clone, Copy, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
 pub enum AorB {
     A,
     B,
@@ -62,6 +63,17 @@ impl TradeDirection {
     }
 }
 
+impl RoundDirection {
+    /// Given a round direction, gives the opposite direction, so floor
+    /// becomes ceiling, and vice versa
+    pub fn opposite(&self) -> RoundDirection {
+        match self {
+            RoundDirection::Floor => RoundDirection::Ceiling,
+            RoundDirection::Ceiling => RoundDirection::Floor,
+        }
+    }
+}
+
 /// Encodes all results of swapping from a source token to a destination token
 #[derive(Debug, PartialEq)]
 pub struct SwapWithoutFeesResult {
@@ -99,6 +111,28 @@ pub trait CurveCalculator: Debug + DynAccountSerialize {
         trade_direction: TradeDirection,
     ) -> Result<SwapWithoutFeesResult>;
 
+    /// Calculate how much source token is required to receive exactly the given
+    /// amount of destination token. This is the inverse of `swap_without_fees`,
+    /// rounding in favour of the pool.
+    fn swap_without_fees_exact_out(
+        &self,
+        destination_amount: u128,
+        pool_source_amount: u128,
+        pool_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<SwapWithoutFeesResult>;
+
+    /// Calculate the marginal price of the source token in destination tokens,
+    /// ie. the amount of destination token received per source token for an
+    /// infinitesimally small trade, without fees.
+    /// `AtoB` gives the price of token A in token B, `BtoA` the price of token B in token A.
+    fn spot_price(
+        &self,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<PreciseNumber>;
+
     /// Get the supply for a new pool
     /// The default implementation is a Balancer-style fixed initial supply
     fn new_pool_supply(&self) -> u128 {
@@ -122,6 +156,64 @@ pub trait CurveCalculator: Debug + DynAccountSerialize {
         round_direction: RoundDirection,
     ) -> Result<TradingTokenResult>;
 
+    /// Get the amount of pool tokens for the given amount of token A or B,
+    /// provided the total trading tokens and supply of pool tokens.
+    /// Used for single sided deposits, fees are not included.
+    /// * `source_amount` - the amount of token A or B to deposit
+    /// * `pool_token_a_amount` - the amount of token A in the pool
+    /// * `pool_token_b_amount` - the amount of token B in the pool
+    /// * `pool_token_supply` - the total supply of pool tokens
+    /// * `trade_direction` - `AtoB` when depositing token A, `BtoA` when depositing token B
+    /// * `round_direction` - the direction to round the output pool token amount
+    fn trading_tokens_to_pool_tokens(
+        &self,
+        source_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128>;
+
+    /// Get the amount of pool tokens to burn for the given amount of token A
+    /// or B, provided the total trading tokens and supply of pool tokens.
+    /// Used for single sided withdrawals of an exact amount, fees are not included.
+    /// * `destination_amount` - the amount of token A or B to withdraw
+    /// * `pool_token_a_amount` - the amount of token A in the pool
+    /// * `pool_token_b_amount` - the amount of token B in the pool
+    /// * `pool_token_supply` - the total supply of pool tokens
+    /// * `trade_direction` - `AtoB` when withdrawing token A, `BtoA` when withdrawing token B
+    /// * `round_direction` - the direction to round the pool token amount to burn
+    fn withdraw_single_token_type_exact_out(
+        &self,
+        destination_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128>;
+
+    /// Get the amount of token A or B redeemed for the given amount of pool
+    /// tokens, provided the total trading tokens and supply of pool tokens.
+    /// Used for single sided withdrawals of an exact amount of pool tokens,
+    /// fees are not included.
+    /// * `pool_tokens` - the amount of pool tokens to burn
+    /// * `pool_token_a_amount` - the amount of token A in the pool
+    /// * `pool_token_b_amount` - the amount of token B in the pool
+    /// * `pool_token_supply` - the total supply of pool tokens
+    /// * `trade_direction` - `AtoB` when withdrawing token A, `BtoA` when withdrawing token B
+    /// * `round_direction` - the direction to round the output trading token amount
+    fn withdraw_one_token(
+        &self,
+        pool_tokens: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128>;
+
     /// Validate that the given curve has no invalid parameters
     fn validate(&self) -> Result<()>;
 
@@ -234,6 +326,116 @@ pub mod test {
         assert!(difference <= epsilon);
     }
 
+    /// Test function checking that the spot price matches the price of a
+    /// small trade, i.e. the finite difference of the swap output over the
+    /// swap input.
+    ///
+    /// Curves are convex, so the trade never executes at a better price than
+    /// the spot price, and a trade that is small relative to the pool executes
+    /// within `epsilon_bps` of it, give or take the rounding of the output.
+    pub fn check_spot_price_from_small_swap(
+        curve: &dyn CurveCalculator,
+        source_token_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+        epsilon_bps: u128,
+    ) {
+        let (swap_token_a_amount, swap_token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (swap_source_amount, swap_destination_amount),
+            TradeDirection::BtoA => (swap_destination_amount, swap_source_amount),
+        };
+        let spot_price = curve
+            .spot_price(swap_token_a_amount, swap_token_b_amount, trade_direction)
+            .unwrap();
+
+        let results = curve
+            .swap_without_fees(
+                source_token_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+            )
+            .unwrap();
+        let execution_price = PreciseNumber::new(results.destination_amount_swapped)
+            .unwrap()
+            .checked_div(&PreciseNumber::new(results.source_amount_swapped).unwrap())
+            .unwrap();
+
+        // allow for the rounding of the fixed point spot price and of curves
+        // solved by iteration
+        let rounding = spot_price
+            .checked_div(&PreciseNumber::new(100_000_000).unwrap())
+            .unwrap();
+        assert!(execution_price.less_than_or_equal(&spot_price.checked_add(&rounding).unwrap()));
+
+        // the destination amount is rounded down by up to 1 token
+        let rounded_up_execution_price = PreciseNumber::new(results.destination_amount_swapped + 1)
+            .unwrap()
+            .checked_div(&PreciseNumber::new(results.source_amount_swapped).unwrap())
+            .unwrap();
+        let epsilon = spot_price
+            .checked_mul(&PreciseNumber::new(epsilon_bps).unwrap())
+            .unwrap()
+            .checked_div(&PreciseNumber::new(10_000).unwrap())
+            .unwrap();
+        assert!(rounded_up_execution_price
+            .checked_add(&epsilon)
+            .unwrap()
+            .checked_add(&rounding)
+            .unwrap()
+            .greater_than_or_equal(&spot_price));
+    }
+
+    /// Test function checking that an exact out swap never reduces the overall
+    /// value of the pool, and that exactly the requested destination amount
+    /// is provided.
+    ///
+    /// The source amount is always rounded up, so unlike
+    /// `check_curve_value_from_swap` the value gained by the pool is not bounded
+    /// by a fixed epsilon.
+    pub fn check_curve_value_from_swap_exact_out(
+        curve: &dyn CurveCalculator,
+        destination_token_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) {
+        let results = curve
+            .swap_without_fees_exact_out(
+                destination_token_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+            )
+            .unwrap();
+        assert_eq!(results.destination_amount_swapped, destination_token_amount);
+
+        let (swap_token_a_amount, swap_token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (swap_source_amount, swap_destination_amount),
+            TradeDirection::BtoA => (swap_destination_amount, swap_source_amount),
+        };
+        let previous_value = curve
+            .normalized_value(swap_token_a_amount, swap_token_b_amount)
+            .unwrap();
+
+        let new_swap_source_amount = swap_source_amount
+            .checked_add(results.source_amount_swapped)
+            .unwrap();
+        let new_swap_destination_amount = swap_destination_amount
+            .checked_sub(results.destination_amount_swapped)
+            .unwrap();
+        let (swap_token_a_amount, swap_token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (new_swap_source_amount, new_swap_destination_amount),
+            TradeDirection::BtoA => (new_swap_destination_amount, new_swap_source_amount),
+        };
+
+        let new_value = curve
+            .normalized_value(swap_token_a_amount, swap_token_b_amount)
+            .unwrap();
+        assert!(new_value.greater_than_or_equal(&previous_value));
+    }
+
     /// Test function checking that a deposit never reduces the value of pool
     /// tokens.
     ///
@@ -334,6 +536,170 @@ pub mod test {
             .greater_than_or_equal(&value.checked_mul(&new_pool_token_supply).unwrap()));
     }
 
+    /// Test function checking that a single sided deposit never reduces the
+    /// value of pool tokens.
+    ///
+    /// The pool tokens minted are rounded down, so the value per pool token
+    /// after the deposit must be at least the value per pool token before.
+    pub fn check_pool_value_from_deposit_single_token_type(
+        curve: &dyn CurveCalculator,
+        source_token_amount: u128,
+        pool_token_supply: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        trade_direction: TradeDirection,
+    ) {
+        let pool_token_amount = curve
+            .trading_tokens_to_pool_tokens(
+                source_token_amount,
+                swap_token_a_amount,
+                swap_token_b_amount,
+                pool_token_supply,
+                trade_direction,
+                RoundDirection::Floor,
+            )
+            .unwrap();
+        let (new_swap_token_a_amount, new_swap_token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (
+                swap_token_a_amount + source_token_amount,
+                swap_token_b_amount,
+            ),
+            TradeDirection::BtoA => (
+                swap_token_a_amount,
+                swap_token_b_amount + source_token_amount,
+            ),
+        };
+        let new_pool_token_supply = pool_token_supply + pool_token_amount;
+
+        let value = curve
+            .normalized_value(swap_token_a_amount, swap_token_b_amount)
+            .unwrap();
+        let new_value = curve
+            .normalized_value(new_swap_token_a_amount, new_swap_token_b_amount)
+            .unwrap();
+
+        // the following inequality must hold:
+        // new_pool_value / new_pool_token_supply >= pool_value / pool_token_supply
+        // which can also be written:
+        // new_pool_value * pool_token_supply >= pool_value * new_pool_token_supply
+
+        let pool_token_supply = PreciseNumber::new(pool_token_supply).unwrap();
+        let new_pool_token_supply = PreciseNumber::new(new_pool_token_supply).unwrap();
+        assert!(new_value
+            .checked_mul(&pool_token_supply)
+            .unwrap()
+            .greater_than_or_equal(&value.checked_mul(&new_pool_token_supply).unwrap()));
+    }
+
+    /// Test function checking that a single sided withdrawal of an exact
+    /// amount of trading tokens never reduces the value of pool tokens.
+    ///
+    /// The pool tokens burned are rounded up, so the value per pool token
+    /// after the withdrawal must be at least the value per pool token before.
+    pub fn check_pool_value_from_withdraw_single_token_type_exact_out(
+        curve: &dyn CurveCalculator,
+        destination_token_amount: u128,
+        pool_token_supply: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        trade_direction: TradeDirection,
+    ) {
+        let pool_token_amount = curve
+            .withdraw_single_token_type_exact_out(
+                destination_token_amount,
+                swap_token_a_amount,
+                swap_token_b_amount,
+                pool_token_supply,
+                trade_direction,
+                RoundDirection::Ceiling,
+            )
+            .unwrap();
+        check_pool_value_from_withdraw_single_token_type(
+            curve,
+            destination_token_amount,
+            pool_token_amount,
+            pool_token_supply,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            trade_direction,
+        );
+    }
+
+    /// Test function checking that a single sided withdrawal of an exact
+    /// amount of pool tokens never reduces the value of pool tokens.
+    ///
+    /// The trading tokens withdrawn are rounded down, so the value per pool token
+    /// after the withdrawal must be at least the value per pool token before.
+    pub fn check_pool_value_from_withdraw_one_token(
+        curve: &dyn CurveCalculator,
+        pool_token_amount: u128,
+        pool_token_supply: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        trade_direction: TradeDirection,
+    ) {
+        let destination_token_amount = curve
+            .withdraw_one_token(
+                pool_token_amount,
+                swap_token_a_amount,
+                swap_token_b_amount,
+                pool_token_supply,
+                trade_direction,
+                RoundDirection::Floor,
+            )
+            .unwrap();
+        check_pool_value_from_withdraw_single_token_type(
+            curve,
+            destination_token_amount,
+            pool_token_amount,
+            pool_token_supply,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            trade_direction,
+        );
+    }
+
+    fn check_pool_value_from_withdraw_single_token_type(
+        curve: &dyn CurveCalculator,
+        destination_token_amount: u128,
+        pool_token_amount: u128,
+        pool_token_supply: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        trade_direction: TradeDirection,
+    ) {
+        let (new_swap_token_a_amount, new_swap_token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (
+                swap_token_a_amount - destination_token_amount,
+                swap_token_b_amount,
+            ),
+            TradeDirection::BtoA => (
+                swap_token_a_amount,
+                swap_token_b_amount - destination_token_amount,
+            ),
+        };
+        let new_pool_token_supply = pool_token_supply - pool_token_amount;
+
+        let value = curve
+            .normalized_value(swap_token_a_amount, swap_token_b_amount)
+            .unwrap();
+        let new_value = curve
+            .normalized_value(new_swap_token_a_amount, new_swap_token_b_amount)
+            .unwrap();
+
+        // the following inequality must hold:
+        // new_pool_value / new_pool_token_supply >= pool_value / pool_token_supply
+        // which can also be written:
+        // new_pool_value * pool_token_supply >= pool_value * new_pool_token_supply
+
+        let pool_token_supply = PreciseNumber::new(pool_token_supply).unwrap();
+        let new_pool_token_supply = PreciseNumber::new(new_pool_token_supply).unwrap();
+        assert!(new_value
+            .checked_mul(&pool_token_supply)
+            .unwrap()
+            .greater_than_or_equal(&value.checked_mul(&new_pool_token_supply).unwrap()));
+    }
+
     prop_compose! {
         pub fn total_and_intermediate(max_value: u64)(total in 1..max_value)
                         (intermediate in 1..total, total in Just(total))
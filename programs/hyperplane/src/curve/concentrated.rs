@@ -0,0 +1,866 @@
+#![allow(clippy::arithmetic_side_effects)]
+
+//! Concentrated liquidity curve, a constant product on virtual reserves that
+//! only trades within a fixed price range, like a single Uniswap v3 position
+
+use anchor_lang::{error, require, Result};
+use spl_math::{precise_number::PreciseNumber, uint::U256};
+
+use crate::{
+    curve::{
+        calculator::{
+            CurveCalculator, DynAccountSerialize, RoundDirection, SwapWithoutFeesResult,
+            TradeDirection, TradingTokenResult,
+        },
+        constant_product::{div_rounded, sqrt_rounded},
+        math,
+    },
+    error::SwapError,
+    require_msg,
+    state::ConcentratedCurve,
+    try_math,
+    utils::math::{TryMath, TryNew},
+};
+
+/// Number of fractional bits of the Q64.64 prices and square root prices
+const Q64_FRACTIONAL_BITS: usize = 64;
+
+/// Extra fractional bits carried through the liquidity calculation, as many as
+/// fit in a U256 for pool amounts up to u64::MAX
+const LIQUIDITY_PRECISION_BITS: usize = 29;
+
+/// Square root of a Q64.64 price as a Q64.64 number, rounded in the given direction
+fn sqrt_price_x64(price_x64: u128, round_direction: RoundDirection) -> Result<u128> {
+    // sqrt(price_x64 / 2^64) * 2^64 = sqrt(price_x64 * 2^64)
+    let sqrt_price_x64 = sqrt_rounded(
+        scale_up(U256::from(price_x64), Q64_FRACTIONAL_BITS)?,
+        U256::one(),
+        round_direction,
+    )?;
+    to_u128(sqrt_price_x64)
+}
+
+/// Multiply the value by 2^bits, failing on overflow
+fn scale_up(value: U256, bits: usize) -> Result<U256> {
+    try_math!(value.try_mul(U256::one() << bits))
+}
+
+fn to_u128(value: U256) -> Result<u128> {
+    u128::try_from(value).map_err(|_| error!(SwapError::ConversionFailure))
+}
+
+impl ConcentratedCurve {
+    /// The square root of the lower price is rounded down and the square root of the
+    /// upper price is rounded up, so the range of the position is never narrowed
+    pub fn new(price_lower_x64: u128, price_upper_x64: u128) -> Result<Self> {
+        Ok(Self {
+            price_lower_x64,
+            price_upper_x64,
+            sqrt_price_lower_x64: sqrt_price_x64(price_lower_x64, RoundDirection::Floor)?,
+            sqrt_price_upper_x64: sqrt_price_x64(price_upper_x64, RoundDirection::Ceiling)?,
+            _padding: [0; 8],
+        })
+    }
+
+    /// Liquidity `L` of the position holding the given pool amounts, the positive root of
+    /// `(token_a + L * sqrt(price_lower)) * (token_b + L / sqrt(price_upper)) = L^2`
+    ///
+    /// `L = (b + sqrt(b^2 + 4 * a * c)) / (2 * a)` where
+    /// - `a = 1 - sqrt(price_lower) / sqrt(price_upper)`
+    /// - `b = token_a / sqrt(price_upper) + token_b * sqrt(price_lower)`
+    /// - `c = token_a * token_b`
+    ///
+    /// Every step is rounded in the given direction, so the result is a bound on the
+    /// exact liquidity. This is guaranteed to work for pool amounts up to u64::MAX.
+    fn liquidity(
+        &self,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Result<U256> {
+        let sqrt_price_lower = U256::from(self.sqrt_price_lower_x64);
+        let sqrt_price_upper = U256::from(self.sqrt_price_upper_x64);
+        let sqrt_price_diff = try_math!(sqrt_price_upper.try_sub(sqrt_price_lower))?;
+        let token_a = U256::from(pool_token_a_amount);
+        let token_b = U256::from(pool_token_b_amount);
+
+        // b, scaled up by 2^LIQUIDITY_PRECISION_BITS
+        let b = try_math!(div_rounded(
+            scale_up(token_a, Q64_FRACTIONAL_BITS + LIQUIDITY_PRECISION_BITS)?,
+            sqrt_price_upper,
+            round_direction,
+        )?
+        .try_add(div_rounded(
+            scale_up(token_b.try_mul(sqrt_price_lower)?, LIQUIDITY_PRECISION_BITS)?,
+            U256::one() << Q64_FRACTIONAL_BITS,
+            round_direction,
+        )?))?;
+
+        // 4 * a * c, scaled up by 2^(2 * LIQUIDITY_PRECISION_BITS)
+        let a_times_token_a = div_rounded(
+            scale_up(
+                try_math!(token_a.try_mul(sqrt_price_diff))?,
+                Q64_FRACTIONAL_BITS + LIQUIDITY_PRECISION_BITS,
+            )?,
+            sqrt_price_upper,
+            round_direction,
+        )?;
+        let four_a_c = try_math!(div_rounded(
+            scale_up(a_times_token_a.try_mul(token_b)?, LIQUIDITY_PRECISION_BITS)?,
+            U256::one() << Q64_FRACTIONAL_BITS,
+            round_direction,
+        )?
+        .try_mul(U256::from(4)))?;
+
+        let root = sqrt_rounded(
+            try_math!(b.try_mul(b)?.try_add(four_a_c))?,
+            U256::one(),
+            round_direction,
+        )?;
+
+        // 1 / (2 * a) = sqrt(price_upper) / (2 * (sqrt(price_upper) - sqrt(price_lower))),
+        // also removing the scaling of b and the root
+        div_rounded(
+            try_math!(sqrt_price_upper.try_mul(b.try_add(root)?))?,
+            scale_up(sqrt_price_diff, LIQUIDITY_PRECISION_BITS + 1)?,
+            round_direction,
+        )
+    }
+
+    /// Virtual amounts of token A and B added to the pool amounts for the given
+    /// liquidity, `L * sqrt(price_lower)` and `L / sqrt(price_upper)`
+    fn virtual_offsets(
+        &self,
+        liquidity: U256,
+        round_direction: RoundDirection,
+    ) -> Result<(U256, U256)> {
+        let token_a_offset = div_rounded(
+            try_math!(liquidity.try_mul(U256::from(self.sqrt_price_lower_x64)))?,
+            U256::one() << Q64_FRACTIONAL_BITS,
+            round_direction,
+        )?;
+        let token_b_offset = div_rounded(
+            scale_up(liquidity, Q64_FRACTIONAL_BITS)?,
+            U256::from(self.sqrt_price_upper_x64),
+            round_direction,
+        )?;
+        Ok((token_a_offset, token_b_offset))
+    }
+
+    /// Liquidity and virtual offsets of the source and destination side of a swap,
+    /// with the liquidity rounded up and the offsets rounded down so that the
+    /// pool never ends up with less liquidity than it started with
+    fn swap_state(
+        &self,
+        pool_source_amount: u128,
+        pool_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<(U256, U256, U256)> {
+        let (pool_token_a_amount, pool_token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (pool_source_amount, pool_destination_amount),
+            TradeDirection::BtoA => (pool_destination_amount, pool_source_amount),
+        };
+        let liquidity = self.liquidity(
+            pool_token_a_amount,
+            pool_token_b_amount,
+            RoundDirection::Ceiling,
+        )?;
+        let (token_a_offset, token_b_offset) =
+            self.virtual_offsets(liquidity, RoundDirection::Floor)?;
+        let (source_offset, destination_offset) = match trade_direction {
+            TradeDirection::AtoB => (token_a_offset, token_b_offset),
+            TradeDirection::BtoA => (token_b_offset, token_a_offset),
+        };
+        Ok((liquidity, source_offset, destination_offset))
+    }
+}
+
+/// Virtual amount of one token that keeps the liquidity of the position given the
+/// virtual amount of the other token, `L^2 / other_virtual_amount`
+fn virtual_amount(
+    liquidity: U256,
+    other_virtual_amount: U256,
+    round_direction: RoundDirection,
+) -> Result<U256> {
+    div_rounded(
+        try_math!(liquidity.try_mul(liquidity))?,
+        other_virtual_amount,
+        round_direction,
+    )
+}
+
+impl CurveCalculator for ConcentratedCurve {
+    /// Constant product swap on the virtual reserves, which ensures
+    /// `(token_a + L * sqrt(price_lower)) * (token_b + L / sqrt(price_upper)) = L^2`
+    ///
+    /// Fails if the swap would take the price outside of the position's range, ie.
+    /// would need more destination tokens than the pool has.
+    fn swap_without_fees(
+        &self,
+        source_amount: u128,
+        pool_source_amount: u128,
+        pool_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<SwapWithoutFeesResult> {
+        require!(source_amount > 0, SwapError::ZeroTradingTokens);
+        let (liquidity, source_offset, destination_offset) =
+            self.swap_state(pool_source_amount, pool_destination_amount, trade_direction)?;
+
+        let new_virtual_source_amount = try_math!(U256::from(pool_source_amount)
+            .try_add(source_amount.into())?
+            .try_add(source_offset))?;
+        let new_virtual_destination_amount = virtual_amount(
+            liquidity,
+            new_virtual_source_amount,
+            RoundDirection::Ceiling,
+        )?;
+        let new_pool_destination_amount = new_virtual_destination_amount
+            .checked_sub(destination_offset)
+            .ok_or_else(|| error!(SwapError::PriceOutOfRange))?;
+
+        let destination_amount_swapped = to_u128(
+            U256::from(pool_destination_amount).saturating_sub(new_pool_destination_amount),
+        )?;
+        require!(destination_amount_swapped > 0, SwapError::ZeroTradingTokens);
+
+        Ok(SwapWithoutFeesResult {
+            source_amount_swapped: source_amount,
+            destination_amount_swapped,
+        })
+    }
+
+    /// Inverse of the virtual reserve swap, the source amount is rounded up
+    fn swap_without_fees_exact_out(
+        &self,
+        destination_amount: u128,
+        pool_source_amount: u128,
+        pool_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<SwapWithoutFeesResult> {
+        require_msg!(
+            destination_amount > 0 && destination_amount < pool_destination_amount,
+            SwapError::ZeroTradingTokens,
+            &format!(
+                "Destination amount {} must be non-zero and less than the pool destination amount {}",
+                destination_amount, pool_destination_amount
+            )
+        );
+        let (liquidity, source_offset, destination_offset) =
+            self.swap_state(pool_source_amount, pool_destination_amount, trade_direction)?;
+
+        let new_virtual_destination_amount = try_math!(U256::from(pool_destination_amount)
+            .try_sub(destination_amount.into())?
+            .try_add(destination_offset))?;
+        let new_virtual_source_amount = virtual_amount(
+            liquidity,
+            new_virtual_destination_amount,
+            RoundDirection::Ceiling,
+        )?;
+        let new_pool_source_amount = new_virtual_source_amount.saturating_sub(source_offset);
+
+        let source_amount_swapped =
+            to_u128(new_pool_source_amount.saturating_sub(U256::from(pool_source_amount)))?;
+        require!(source_amount_swapped > 0, SwapError::ZeroTradingTokens);
+
+        Ok(SwapWithoutFeesResult {
+            source_amount_swapped,
+            destination_amount_swapped: destination_amount,
+        })
+    }
+
+    /// The spot price is the ratio of the virtual reserves
+    fn spot_price(
+        &self,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<PreciseNumber> {
+        let liquidity = self.liquidity(
+            pool_token_a_amount,
+            pool_token_b_amount,
+            RoundDirection::Floor,
+        )?;
+        let (token_a_offset, token_b_offset) =
+            self.virtual_offsets(liquidity, RoundDirection::Floor)?;
+        let virtual_token_a_amount =
+            try_math!(U256::from(pool_token_a_amount).try_add(token_a_offset))?;
+        let virtual_token_b_amount =
+            try_math!(U256::from(pool_token_b_amount).try_add(token_b_offset))?;
+        match trade_direction {
+            TradeDirection::AtoB => {
+                math::precise_ratio(virtual_token_b_amount, virtual_token_a_amount)
+            }
+            TradeDirection::BtoA => {
+                math::precise_ratio(virtual_token_a_amount, virtual_token_b_amount)
+            }
+        }
+    }
+
+    /// The position is proportional to its real reserves, so pool tokens are
+    /// converted like the constant product curve
+    fn pool_tokens_to_trading_tokens(
+        &self,
+        pool_tokens: u128,
+        pool_token_supply: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Result<TradingTokenResult> {
+        math::pool_tokens_to_trading_tokens(
+            pool_tokens,
+            pool_token_supply,
+            pool_token_a_amount,
+            pool_token_b_amount,
+            round_direction,
+        )
+    }
+
+    /// Pool tokens are minted in proportion to the liquidity added to the position
+    fn trading_tokens_to_pool_tokens(
+        &self,
+        source_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let (new_pool_token_a_amount, new_pool_token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (
+                try_math!(pool_token_a_amount.try_add(source_amount))?,
+                pool_token_b_amount,
+            ),
+            TradeDirection::BtoA => (
+                pool_token_a_amount,
+                try_math!(pool_token_b_amount.try_add(source_amount))?,
+            ),
+        };
+        let liquidity = self.liquidity(
+            pool_token_a_amount,
+            pool_token_b_amount,
+            round_direction.opposite(),
+        )?;
+        let new_liquidity = self.liquidity(
+            new_pool_token_a_amount,
+            new_pool_token_b_amount,
+            round_direction,
+        )?;
+
+        let pool_tokens = div_rounded(
+            try_math!(U256::from(pool_supply).try_mul(new_liquidity.saturating_sub(liquidity)))?,
+            liquidity,
+            round_direction,
+        )?;
+        to_u128(pool_tokens)
+    }
+
+    /// Pool tokens are burned in proportion to the liquidity removed from the position
+    fn withdraw_single_token_type_exact_out(
+        &self,
+        source_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let (new_pool_token_a_amount, new_pool_token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (
+                try_math!(pool_token_a_amount.try_sub(source_amount))?,
+                pool_token_b_amount,
+            ),
+            TradeDirection::BtoA => (
+                pool_token_a_amount,
+                try_math!(pool_token_b_amount.try_sub(source_amount))?,
+            ),
+        };
+        let liquidity =
+            self.liquidity(pool_token_a_amount, pool_token_b_amount, round_direction)?;
+        let new_liquidity = self.liquidity(
+            new_pool_token_a_amount,
+            new_pool_token_b_amount,
+            round_direction.opposite(),
+        )?;
+
+        let pool_tokens = div_rounded(
+            try_math!(U256::from(pool_supply).try_mul(liquidity.saturating_sub(new_liquidity)))?,
+            liquidity,
+            round_direction,
+        )?;
+        to_u128(pool_tokens)
+    }
+
+    /// Removes the liquidity of the pool tokens from the position, and gives the
+    /// amount of the destination token that brings the pool down to that liquidity
+    fn withdraw_one_token(
+        &self,
+        pool_tokens: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let liquidity = self.liquidity(
+            pool_token_a_amount,
+            pool_token_b_amount,
+            round_direction.opposite(),
+        )?;
+        let new_liquidity = div_rounded(
+            try_math!(liquidity.try_mul(pool_supply.try_sub(pool_tokens)?.into()))?,
+            U256::from(pool_supply),
+            round_direction.opposite(),
+        )?;
+        let (token_a_offset, token_b_offset) =
+            self.virtual_offsets(new_liquidity, round_direction)?;
+
+        let (pool_destination_amount, pool_other_amount, destination_offset, other_offset) =
+            match trade_direction {
+                TradeDirection::AtoB => (
+                    pool_token_a_amount,
+                    pool_token_b_amount,
+                    token_a_offset,
+                    token_b_offset,
+                ),
+                TradeDirection::BtoA => (
+                    pool_token_b_amount,
+                    pool_token_a_amount,
+                    token_b_offset,
+                    token_a_offset,
+                ),
+            };
+        let new_virtual_destination_amount = virtual_amount(
+            new_liquidity,
+            try_math!(U256::from(pool_other_amount).try_add(other_offset))?,
+            round_direction.opposite(),
+        )?;
+        let new_pool_destination_amount =
+            new_virtual_destination_amount.saturating_sub(destination_offset);
+
+        to_u128(U256::from(pool_destination_amount).saturating_sub(new_pool_destination_amount))
+    }
+
+    fn validate(&self) -> Result<()> {
+        require_msg!(
+            self.price_lower_x64 > 0,
+            SwapError::InvalidCurve,
+            "Lower price must be greater than 0 for concentrated curve"
+        );
+        require_msg!(
+            self.price_lower_x64 < self.price_upper_x64,
+            SwapError::InvalidCurve,
+            &format!(
+                "price_lower_x64={} >= price_upper_x64={}",
+                self.price_lower_x64, self.price_upper_x64
+            )
+        );
+        Ok(())
+    }
+
+    fn validate_supply(&self, token_a_amount: u64, token_b_amount: u64) -> Result<()> {
+        require_msg!(
+            token_a_amount > 0 || token_b_amount > 0,
+            SwapError::EmptySupply,
+            "Token A or token B amount must be greater than 0 for concentrated curve"
+        );
+        Ok(())
+    }
+
+    /// The normalized value of the concentrated curve is the liquidity of the
+    /// position, which is the geometric mean of the virtual reserves
+    fn normalized_value(
+        &self,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+    ) -> Result<PreciseNumber> {
+        let liquidity = self.liquidity(
+            pool_token_a_amount,
+            pool_token_b_amount,
+            RoundDirection::Floor,
+        )?;
+        PreciseNumber::try_new(to_u128(liquidity)?)
+    }
+}
+
+impl DynAccountSerialize for ConcentratedCurve {
+    fn try_dyn_serialize(&self, mut dst: std::cell::RefMut<&mut [u8]>) -> Result<()> {
+        let dst: &mut [u8] = &mut dst;
+        let mut cursor = std::io::Cursor::new(dst);
+        anchor_lang::AccountSerialize::try_serialize(self, &mut cursor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::BorrowMut;
+
+    use anchor_lang::AccountDeserialize;
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::{
+        curve::calculator::test::{
+            check_curve_value_from_swap_exact_out, check_pool_value_from_deposit,
+            check_pool_value_from_deposit_single_token_type, check_pool_value_from_withdraw,
+            check_pool_value_from_withdraw_one_token,
+            check_pool_value_from_withdraw_single_token_type_exact_out,
+            check_spot_price_from_small_swap, total_and_intermediate,
+        },
+        state::Curve,
+    };
+
+    /// Price range of 1 to 4 token A per token B, with exact square roots of 1 and 2
+    fn one_to_four_curve() -> ConcentratedCurve {
+        ConcentratedCurve::new(1 << 64, 4 << 64).unwrap()
+    }
+
+    /// Price ranges from 1% to 4x wide, at prices from 2^-16 to 2^16
+    fn price_range() -> impl Strategy<Value = (u128, u128)> {
+        ((1_u128 << 48)..(1_u128 << 80), 100..40_000_u128).prop_map(
+            |(price_lower_x64, width_bps)| {
+                (
+                    price_lower_x64,
+                    price_lower_x64 + price_lower_x64 * width_bps / 10_000,
+                )
+            },
+        )
+    }
+
+    /// Like `check_curve_value_from_swap`, but the liquidity gained from the
+    /// rounding of a swap is not bounded by a fixed epsilon at every price
+    fn check_liquidity_from_swap(
+        curve: &ConcentratedCurve,
+        source_token_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) {
+        let results = curve
+            .swap_without_fees(
+                source_token_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+            )
+            .unwrap();
+
+        let (swap_token_a_amount, swap_token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (swap_source_amount, swap_destination_amount),
+            TradeDirection::BtoA => (swap_destination_amount, swap_source_amount),
+        };
+        let previous_value = curve
+            .normalized_value(swap_token_a_amount, swap_token_b_amount)
+            .unwrap();
+
+        let new_swap_source_amount = swap_source_amount + results.source_amount_swapped;
+        let new_swap_destination_amount =
+            swap_destination_amount - results.destination_amount_swapped;
+        let (swap_token_a_amount, swap_token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (new_swap_source_amount, new_swap_destination_amount),
+            TradeDirection::BtoA => (new_swap_destination_amount, new_swap_source_amount),
+        };
+        let new_value = curve
+            .normalized_value(swap_token_a_amount, swap_token_b_amount)
+            .unwrap();
+        assert!(new_value.greater_than_or_equal(&previous_value));
+    }
+
+    #[test]
+    fn serialize_concentrated_curve() {
+        let curve = ConcentratedCurve::new(u128::MAX - 1, u128::MAX).unwrap();
+
+        let mut arr = [0u8; Curve::LEN];
+        let packed = arr.borrow_mut();
+        let ref_mut = std::cell::RefCell::new(packed);
+
+        curve.try_dyn_serialize(ref_mut.borrow_mut()).unwrap();
+        let unpacked = ConcentratedCurve::try_deserialize(&mut arr.as_ref()).unwrap();
+        assert_eq!(curve, unpacked);
+    }
+
+    #[test]
+    fn new_rounds_sqrt_prices_outwards() {
+        let curve = one_to_four_curve();
+        assert_eq!(curve.sqrt_price_lower_x64, 1 << 64);
+        assert_eq!(curve.sqrt_price_upper_x64, 2 << 64);
+
+        // sqrt(2 / 2^64) * 2^64 = 6074000999.95...
+        let curve = ConcentratedCurve::new(2, 2).unwrap();
+        assert_eq!(curve.sqrt_price_lower_x64, 6_074_000_999);
+        assert_eq!(curve.sqrt_price_upper_x64, 6_074_001_000);
+    }
+
+    #[test]
+    fn liquidity_and_spot_price() {
+        let curve = one_to_four_curve();
+        // (3_000 + 6_000 * 1) * (1_000 + 6_000 / 2) = 6_000^2
+        assert_eq!(
+            curve.normalized_value(3_000, 1_000).unwrap(),
+            PreciseNumber::new(6_000).unwrap()
+        );
+        // 9_000 / 4_000 token A per token B, within the range
+        assert_eq!(
+            curve
+                .spot_price(3_000, 1_000, TradeDirection::BtoA)
+                .unwrap(),
+            PreciseNumber::new(9)
+                .unwrap()
+                .checked_div(&PreciseNumber::new(4).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn swap_to_the_edge_of_the_range() {
+        let curve = one_to_four_curve();
+        // 12_000 * 3_000 = 6_000^2 leaves no token B at the upper price
+        let result = curve
+            .swap_without_fees(3_000, 3_000, 1_000, TradeDirection::AtoB)
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 3_000);
+        assert_eq!(result.destination_amount_swapped, 1_000);
+
+        assert_eq!(
+            curve.swap_without_fees(3_100, 3_000, 1_000, TradeDirection::AtoB),
+            Err(SwapError::PriceOutOfRange.into())
+        );
+    }
+
+    #[test]
+    fn swap_exact_out() {
+        let curve = one_to_four_curve();
+        // 36_000_000 / 3_500 = 10_285.71..., rounded up
+        let result = curve
+            .swap_without_fees_exact_out(500, 3_000, 1_000, TradeDirection::AtoB)
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 1_286);
+        assert_eq!(result.destination_amount_swapped, 500);
+
+        assert_eq!(
+            curve.swap_without_fees_exact_out(1_000, 3_000, 1_000, TradeDirection::AtoB),
+            Err(SwapError::ZeroTradingTokens.into())
+        );
+    }
+
+    #[test]
+    fn validate_price_range() {
+        assert!(one_to_four_curve().validate().is_ok());
+        assert_eq!(
+            ConcentratedCurve::new(0, 4 << 64).unwrap().validate(),
+            Err(SwapError::InvalidCurve.into())
+        );
+        assert_eq!(
+            ConcentratedCurve::new(4 << 64, 4 << 64).unwrap().validate(),
+            Err(SwapError::InvalidCurve.into())
+        );
+        assert_eq!(
+            ConcentratedCurve::new(4 << 64, 1 << 64).unwrap().validate(),
+            Err(SwapError::InvalidCurve.into())
+        );
+    }
+
+    #[test]
+    fn validate_supply_on_one_side() {
+        let curve = one_to_four_curve();
+        assert!(curve.validate_supply(1_000, 0).is_ok());
+        assert!(curve.validate_supply(0, 1_000).is_ok());
+        assert_eq!(
+            curve.validate_supply(0, 0),
+            Err(SwapError::EmptySupply.into())
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap(
+            source_token_amount in 1..u64::MAX as u128,
+            swap_source_amount in 1..u64::MAX as u128,
+            swap_destination_amount in 1..u64::MAX as u128,
+            (price_lower_x64, price_upper_x64) in price_range(),
+        ) {
+            let curve = ConcentratedCurve::new(price_lower_x64, price_upper_x64).unwrap();
+            for trade_direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+                // swaps that leave the price range or are too small for any output fail
+                if curve
+                    .swap_without_fees(
+                        source_token_amount,
+                        swap_source_amount,
+                        swap_destination_amount,
+                        trade_direction,
+                    )
+                    .is_err()
+                {
+                    continue;
+                }
+                check_liquidity_from_swap(
+                    &curve,
+                    source_token_amount,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    trade_direction,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap_exact_out(
+            destination_token_amount in 1..u64::MAX as u128,
+            swap_source_amount in 1..u64::MAX as u128,
+            swap_destination_amount in 1..u64::MAX as u128,
+            (price_lower_x64, price_upper_x64) in price_range(),
+        ) {
+            prop_assume!(destination_token_amount < swap_destination_amount);
+            let curve = ConcentratedCurve::new(price_lower_x64, price_upper_x64).unwrap();
+            for trade_direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+                check_curve_value_from_swap_exact_out(
+                    &curve,
+                    destination_token_amount,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    trade_direction,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn spot_price_matches_small_swap(
+            swap_token_a_amount in 10_000_000_000..1_000_000_000_000_u64,
+            swap_token_b_amount in 10_000_000_000..1_000_000_000_000_u64,
+            price_lower_x64 in (1_u128 << 62)..(1_u128 << 63),
+            price_upper_x64 in (1_u128 << 65)..(1_u128 << 66),
+        ) {
+            let curve = ConcentratedCurve::new(price_lower_x64, price_upper_x64).unwrap();
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            check_spot_price_from_small_swap(
+                &curve,
+                swap_token_a_amount / 100_000,
+                swap_token_a_amount,
+                swap_token_b_amount,
+                TradeDirection::AtoB,
+                1,
+            );
+            check_spot_price_from_small_swap(
+                &curve,
+                swap_token_b_amount / 100_000,
+                swap_token_b_amount,
+                swap_token_a_amount,
+                TradeDirection::BtoA,
+                1,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_deposit(
+            pool_token_amount in 1..u64::MAX as u128,
+            pool_token_supply in 1..u64::MAX as u128,
+            swap_token_a_amount in 1..u64::MAX as u128,
+            swap_token_b_amount in 1..u64::MAX as u128,
+        ) {
+            check_pool_value_from_deposit(
+                &one_to_four_curve(),
+                pool_token_amount,
+                pool_token_supply,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw(
+            (pool_token_supply, pool_token_amount) in total_and_intermediate(u64::MAX),
+            swap_token_a_amount in 1..u64::MAX as u128,
+            swap_token_b_amount in 1..u64::MAX as u128,
+        ) {
+            check_pool_value_from_withdraw(
+                &one_to_four_curve(),
+                pool_token_amount as u128,
+                pool_token_supply as u128,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_deposit_single_token_type(
+            source_token_amount in 1..u64::MAX as u128,
+            pool_token_supply in 1..u64::MAX as u128,
+            swap_token_a_amount in 1..u64::MAX as u128,
+            swap_token_b_amount in 1..u64::MAX as u128,
+            (price_lower_x64, price_upper_x64) in price_range(),
+        ) {
+            let curve = ConcentratedCurve::new(price_lower_x64, price_upper_x64).unwrap();
+            for trade_direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+                check_pool_value_from_deposit_single_token_type(
+                    &curve,
+                    source_token_amount,
+                    pool_token_supply,
+                    swap_token_a_amount,
+                    swap_token_b_amount,
+                    trade_direction,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw_single_token_type_exact_out(
+            destination_token_amount in 1..u64::MAX as u128,
+            pool_token_supply in 1..u64::MAX as u128,
+            swap_token_a_amount in 1..u64::MAX as u128,
+            swap_token_b_amount in 1..u64::MAX as u128,
+            (price_lower_x64, price_upper_x64) in price_range(),
+        ) {
+            let curve = ConcentratedCurve::new(price_lower_x64, price_upper_x64).unwrap();
+            for (trade_direction, swap_destination_amount) in [
+                (TradeDirection::AtoB, swap_token_a_amount),
+                (TradeDirection::BtoA, swap_token_b_amount),
+            ] {
+                if destination_token_amount >= swap_destination_amount {
+                    continue;
+                }
+                check_pool_value_from_withdraw_single_token_type_exact_out(
+                    &curve,
+                    destination_token_amount,
+                    pool_token_supply,
+                    swap_token_a_amount,
+                    swap_token_b_amount,
+                    trade_direction,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw_one_token(
+            (pool_token_supply, pool_token_amount) in total_and_intermediate(u64::MAX),
+            swap_token_a_amount in 1..u64::MAX as u128,
+            swap_token_b_amount in 1..u64::MAX as u128,
+            (price_lower_x64, price_upper_x64) in price_range(),
+        ) {
+            let curve = ConcentratedCurve::new(price_lower_x64, price_upper_x64).unwrap();
+            for trade_direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+                check_pool_value_from_withdraw_one_token(
+                    &curve,
+                    pool_token_amount as u128,
+                    pool_token_supply as u128,
+                    swap_token_a_amount,
+                    swap_token_b_amount,
+                    trade_direction,
+                );
+            }
+        }
+    }
+}
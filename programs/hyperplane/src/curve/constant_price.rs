@@ -2,7 +2,7 @@
 
 //! Simple constant price swap curve, set at init
 
-use anchor_lang::{require, Result};
+use anchor_lang::{error, require, Result};
 use spl_math::{precise_number::PreciseNumber, uint::U256};
 
 use crate::{
@@ -94,6 +94,47 @@ impl CurveCalculator for ConstantPriceCurve {
         })
     }
 
+    /// Constant price curve inverse, rounding the source amount up when buying
+    /// token A with a remainder
+    fn swap_without_fees_exact_out(
+        &self,
+        destination_amount: u128,
+        _swap_source_amount: u128,
+        _swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<SwapWithoutFeesResult> {
+        let token_b_price = self.token_b_price as u128;
+
+        let source_amount_swapped = match trade_direction {
+            TradeDirection::BtoA => try_math!(destination_amount
+                .try_add(token_b_price.try_sub(1)?)?
+                .try_div(token_b_price))?,
+            TradeDirection::AtoB => try_math!(destination_amount.try_mul(token_b_price))?,
+        };
+        require!(
+            source_amount_swapped > 0 && destination_amount > 0,
+            SwapError::ZeroTradingTokens
+        );
+        Ok(SwapWithoutFeesResult {
+            source_amount_swapped,
+            destination_amount_swapped: destination_amount,
+        })
+    }
+
+    /// The constant price does not depend on the pool balances
+    fn spot_price(
+        &self,
+        _pool_token_a_amount: u128,
+        _pool_token_b_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<PreciseNumber> {
+        let token_b_price = PreciseNumber::try_new(u128::from(self.token_b_price))?;
+        match trade_direction {
+            TradeDirection::AtoB => PreciseNumber::try_new(1)?.try_div(&token_b_price),
+            TradeDirection::BtoA => Ok(token_b_price),
+        }
+    }
+
     /// Get the amount of trading tokens for the given amount of pool tokens,
     /// provided the total trading tokens and supply of pool tokens.
     /// For the constant price curve, the total value of the pool is weighted
@@ -139,6 +180,84 @@ impl CurveCalculator for ConstantPriceCurve {
         })
     }
 
+    /// Get the amount of pool tokens for the given amount of token A or B,
+    /// valuing token B at the constant price
+    fn trading_tokens_to_pool_tokens(
+        &self,
+        source_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        trading_tokens_to_pool_tokens(
+            self.token_b_price,
+            source_amount,
+            pool_token_a_amount,
+            pool_token_b_amount,
+            pool_token_supply,
+            trade_direction,
+            round_direction,
+        )
+    }
+
+    /// Get the amount of pool tokens to burn for the given amount of token A
+    /// or B, valuing token B at the constant price
+    fn withdraw_single_token_type_exact_out(
+        &self,
+        destination_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        trading_tokens_to_pool_tokens(
+            self.token_b_price,
+            destination_amount,
+            pool_token_a_amount,
+            pool_token_b_amount,
+            pool_token_supply,
+            trade_direction,
+            round_direction,
+        )
+    }
+
+    /// Get the amount of token A or B redeemed for the given amount of pool
+    /// tokens, valuing token B at the constant price
+    fn withdraw_one_token(
+        &self,
+        pool_tokens: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let token_b_price = U256::from(self.token_b_price);
+        let total_value = try_math!(U256::from(pool_token_b_amount)
+            .try_mul(token_b_price)?
+            .try_add(U256::from(pool_token_a_amount)))?;
+        let pool_tokens_value = try_math!(U256::from(pool_tokens).try_mul(total_value))?;
+        let denominator = match trade_direction {
+            TradeDirection::AtoB => U256::from(pool_token_supply),
+            TradeDirection::BtoA => {
+                try_math!(U256::from(pool_token_supply).try_mul(token_b_price))?
+            }
+        };
+        let destination_amount = try_math!(pool_tokens_value.try_div(denominator))?;
+        let destination_amount = match round_direction {
+            RoundDirection::Ceiling
+                if try_math!(destination_amount.try_mul(denominator))? < pool_tokens_value =>
+            {
+                try_math!(destination_amount.try_add(U256::one()))?
+            }
+            _ => destination_amount,
+        };
+        u128::try_from(destination_amount).map_err(|_| error!(SwapError::ConversionFailure))
+    }
+
     fn validate(&self) -> Result<()> {
         require_msg!(
             self.token_b_price > 0,
@@ -204,7 +323,12 @@ mod tests {
     use super::*;
     use crate::{
         curve::calculator::{
-            test::{check_curve_value_from_swap, total_and_intermediate},
+            test::{
+                check_curve_value_from_swap, check_curve_value_from_swap_exact_out,
+                check_pool_value_from_deposit_single_token_type,
+                check_pool_value_from_withdraw_single_token_type_exact_out,
+                check_spot_price_from_small_swap, total_and_intermediate,
+            },
             INITIAL_SWAP_POOL_AMOUNT,
         },
         state::Curve,
@@ -329,6 +453,117 @@ mod tests {
         assert_eq!(result.destination_amount_swapped, 1u128);
     }
 
+    #[test]
+    fn swap_exact_out_calculation_large_price() {
+        let token_b_price = 1123513u128;
+        let curve = ConstantPriceCurve {
+            token_b_price: token_b_price as u64,
+            ..Default::default()
+        };
+        let token_b_amount = 500u128;
+        let token_a_amount = token_b_amount * token_b_price;
+        let bad_result = curve.swap_without_fees_exact_out(
+            0,
+            token_a_amount,
+            token_b_amount,
+            TradeDirection::AtoB,
+        );
+        assert!(bad_result.is_err());
+
+        let result = curve
+            .swap_without_fees_exact_out(1, token_a_amount, token_b_amount, TradeDirection::AtoB)
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, token_b_price);
+        assert_eq!(result.destination_amount_swapped, 1);
+
+        // buying less than one token B worth of token A still costs a whole token B
+        let result = curve
+            .swap_without_fees_exact_out(1, token_a_amount, token_b_amount, TradeDirection::BtoA)
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 1);
+        assert_eq!(result.destination_amount_swapped, 1);
+
+        let result = curve
+            .swap_without_fees_exact_out(
+                token_b_price + 1,
+                token_a_amount,
+                token_b_amount,
+                TradeDirection::BtoA,
+            )
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 2);
+        assert_eq!(result.destination_amount_swapped, token_b_price + 1);
+    }
+
+    proptest! {
+        #[test]
+        fn spot_price_matches_small_swap(
+            source_token_amount in 1..u32::MAX,
+            swap_source_amount in 1..u64::MAX,
+            swap_destination_amount in 1..u64::MAX,
+            token_b_price in 1..u32::MAX,
+        ) {
+            let curve = ConstantPriceCurve { token_b_price: token_b_price as u64, ..Default::default() };
+            // the price is constant, so any trade executes exactly at the spot price
+            check_spot_price_from_small_swap(
+                &curve,
+                source_token_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::BtoA,
+                0,
+            );
+            check_spot_price_from_small_swap(
+                &curve,
+                source_token_amount as u128 * token_b_price as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::AtoB,
+                0,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap_exact_out_a_to_b(
+            destination_token_amount in 1..u32::MAX, // kept small to avoid proptest rejections
+            swap_source_amount in 1..u64::MAX,
+            swap_destination_amount in 1..u64::MAX,
+            token_b_price in 1..u32::MAX, // kept small to avoid proptest rejections
+        ) {
+            prop_assume!((destination_token_amount as u64) <= swap_destination_amount);
+            let curve = ConstantPriceCurve { token_b_price: token_b_price as u64, ..Default::default() };
+            check_curve_value_from_swap_exact_out(
+                &curve,
+                destination_token_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::AtoB
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap_exact_out_b_to_a(
+            destination_token_amount in 1..u64::MAX,
+            swap_source_amount in 1..u64::MAX,
+            swap_destination_amount in 1..u64::MAX,
+            token_b_price in 1..u64::MAX,
+        ) {
+            prop_assume!(destination_token_amount <= swap_destination_amount);
+            let curve = ConstantPriceCurve { token_b_price, ..Default::default() };
+            check_curve_value_from_swap_exact_out(
+                &curve,
+                destination_token_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::BtoA
+            );
+        }
+    }
+
     proptest! {
         #[test]
         fn curve_value_does_not_decrease_from_swap_a_to_b(
@@ -380,6 +615,186 @@ mod tests {
         }
     }
 
+    #[test]
+    fn single_trading_token_conversion() {
+        let token_b_price = 10;
+        let curve = ConstantPriceCurve {
+            token_b_price,
+            ..Default::default()
+        };
+        // pool value is 1_000 + 100 * 10 = 2_000 token A
+        for (source_amount, trade_direction, round_direction, expected) in [
+            (500, TradeDirection::AtoB, RoundDirection::Floor, 250),
+            (50, TradeDirection::BtoA, RoundDirection::Floor, 250),
+            (3, TradeDirection::AtoB, RoundDirection::Floor, 1),
+            (3, TradeDirection::AtoB, RoundDirection::Ceiling, 2),
+            (1, TradeDirection::BtoA, RoundDirection::Floor, 5),
+        ] {
+            let pool_tokens = curve
+                .trading_tokens_to_pool_tokens(
+                    source_amount,
+                    1_000,
+                    100,
+                    1_000,
+                    trade_direction,
+                    round_direction,
+                )
+                .unwrap();
+            assert_eq!(pool_tokens, expected);
+        }
+    }
+
+    #[test]
+    fn single_trading_token_withdrawal() {
+        let token_b_price = 10;
+        let curve = ConstantPriceCurve {
+            token_b_price,
+            ..Default::default()
+        };
+        // pool value is 1_000 + 100 * 10 = 2_000 token A
+        for (destination_amount, trade_direction, round_direction, expected) in [
+            (500, TradeDirection::AtoB, RoundDirection::Ceiling, 250),
+            (50, TradeDirection::BtoA, RoundDirection::Ceiling, 250),
+            (3, TradeDirection::AtoB, RoundDirection::Floor, 1),
+            (3, TradeDirection::AtoB, RoundDirection::Ceiling, 2),
+        ] {
+            let pool_tokens = curve
+                .withdraw_single_token_type_exact_out(
+                    destination_amount,
+                    1_000,
+                    100,
+                    1_000,
+                    trade_direction,
+                    round_direction,
+                )
+                .unwrap();
+            assert_eq!(pool_tokens, expected);
+        }
+        for (pool_tokens, trade_direction, round_direction, expected) in [
+            (250, TradeDirection::AtoB, RoundDirection::Floor, 500),
+            (250, TradeDirection::BtoA, RoundDirection::Floor, 50),
+            (3, TradeDirection::AtoB, RoundDirection::Floor, 6),
+            (3, TradeDirection::BtoA, RoundDirection::Floor, 0),
+            (3, TradeDirection::BtoA, RoundDirection::Ceiling, 1),
+        ] {
+            let destination_amount = curve
+                .withdraw_one_token(
+                    pool_tokens,
+                    1_000,
+                    100,
+                    1_000,
+                    trade_direction,
+                    round_direction,
+                )
+                .unwrap();
+            assert_eq!(destination_amount, expected);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_deposit_single_token_type(
+            source_token_amount in 1..u64::MAX,
+            pool_token_supply in 1..u64::MAX,
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u32::MAX, // kept small to avoid overflow
+            token_b_price in 1..u32::MAX, // kept small to avoid overflow
+        ) {
+            let curve = ConstantPriceCurve { token_b_price: token_b_price as u64, ..Default::default() };
+            for trade_direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+                // amounts are doubled so that halving the value in
+                // `normalized_value` does not truncate
+                check_pool_value_from_deposit_single_token_type(
+                    &curve,
+                    source_token_amount as u128 * 2,
+                    pool_token_supply as u128,
+                    swap_token_a_amount as u128 * 2,
+                    swap_token_b_amount as u128 * 2,
+                    trade_direction,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw_single_token_type_exact_out(
+            destination_token_amount in 1..u64::MAX,
+            pool_token_supply in 1..u64::MAX,
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u32::MAX, // kept small to avoid overflow
+            token_b_price in 1..u32::MAX, // kept small to avoid overflow
+        ) {
+            let curve = ConstantPriceCurve { token_b_price: token_b_price as u64, ..Default::default() };
+            for (trade_direction, swap_destination_amount) in [
+                (TradeDirection::AtoB, swap_token_a_amount),
+                (TradeDirection::BtoA, swap_token_b_amount as u64),
+            ] {
+                if destination_token_amount >= swap_destination_amount {
+                    continue;
+                }
+                // amounts are doubled so that halving the value in
+                // `normalized_value` does not truncate
+                check_pool_value_from_withdraw_single_token_type_exact_out(
+                    &curve,
+                    destination_token_amount as u128 * 2,
+                    pool_token_supply as u128,
+                    swap_token_a_amount as u128 * 2,
+                    swap_token_b_amount as u128 * 2,
+                    trade_direction,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw_one_token(
+            (pool_token_supply, pool_token_amount) in total_and_intermediate(u64::MAX),
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u32::MAX, // kept small to avoid overflow
+            token_b_price in 1..u32::MAX, // kept small to avoid overflow
+        ) {
+            let curve = ConstantPriceCurve { token_b_price: token_b_price as u64, ..Default::default() };
+            let value = |token_a_amount: u64, token_b_amount: u64| {
+                U256::from(token_a_amount) + U256::from(token_b_amount) * U256::from(token_b_price)
+            };
+            for trade_direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+                let destination_token_amount = curve
+                    .withdraw_one_token(
+                        pool_token_amount as u128,
+                        swap_token_a_amount as u128,
+                        swap_token_b_amount as u128,
+                        pool_token_supply as u128,
+                        trade_direction,
+                        RoundDirection::Floor,
+                    )
+                    .unwrap() as u64;
+                // the value of the pool tokens may be more than the pool amount
+                // of the destination token
+                let (new_swap_token_a_amount, new_swap_token_b_amount) = match trade_direction {
+                    TradeDirection::AtoB => match swap_token_a_amount.checked_sub(destination_token_amount) {
+                        Some(amount) => (amount, swap_token_b_amount as u64),
+                        None => continue,
+                    },
+                    TradeDirection::BtoA => match (swap_token_b_amount as u64).checked_sub(destination_token_amount) {
+                        Some(amount) => (swap_token_a_amount, amount),
+                        None => continue,
+                    },
+                };
+
+                // the value is checked exactly, since halving the value in
+                // `normalized_value` truncates odd withdrawal amounts
+                // new_pool_value * pool_token_supply >= pool_value * new_pool_token_supply
+                let new_pool_token_supply = pool_token_supply - pool_token_amount;
+                prop_assert!(
+                    value(new_swap_token_a_amount, new_swap_token_b_amount) * U256::from(pool_token_supply)
+                        >= value(swap_token_a_amount, swap_token_b_amount as u64) * U256::from(new_pool_token_supply)
+                );
+            }
+        }
+    }
+
     proptest! {
         #[test]
         fn curve_value_does_not_decrease_from_deposit(
@@ -2,8 +2,8 @@
 
 //! invariant calculator.
 
-use anchor_lang::{require, Result};
-use spl_math::precise_number::PreciseNumber;
+use anchor_lang::{error, require, Result};
+use spl_math::{precise_number::PreciseNumber, uint::U256};
 
 use crate::{
     curve::{
@@ -14,6 +14,7 @@ use crate::{
         math,
     },
     error::SwapError,
+    require_msg,
     state::ConstantProductCurve,
     try_math,
     utils::math::{TryCeilDiv, TryMath, TryMathRef, TryNew},
@@ -49,6 +50,160 @@ pub fn swap(
     })
 }
 
+/// The inverse of the constant product swap calculation, giving the amount of
+/// source token required to take exactly `destination_amount` out of the pool.
+///
+/// The source amount is rounded up so that the invariant never decreases.
+/// This is guaranteed to work for all values such that:
+///  - 1 <= destination_amount < swap_destination_amount
+///  - 1 <= swap_source_amount * swap_destination_amount <= u128::MAX
+pub fn swap_exact_out(
+    destination_amount: u128,
+    pool_source_amount: u128,
+    pool_destination_amount: u128,
+) -> Result<SwapWithoutFeesResult> {
+    require_msg!(
+        destination_amount > 0 && destination_amount < pool_destination_amount,
+        SwapError::ZeroTradingTokens,
+        &format!(
+            "Destination amount {} must be non-zero and less than the pool destination amount {}",
+            destination_amount, pool_destination_amount
+        )
+    );
+    let invariant = try_math!(pool_source_amount.try_mul(pool_destination_amount))?;
+
+    let new_pool_destination_amount =
+        try_math!(pool_destination_amount.try_sub(destination_amount))?;
+    let (new_pool_source_amount, _) =
+        try_math!(invariant.try_ceil_div(new_pool_destination_amount))?;
+
+    let source_amount_swapped = try_math!(new_pool_source_amount.try_sub(pool_source_amount))?;
+
+    require!(source_amount_swapped > 0, SwapError::ZeroTradingTokens);
+    Ok(SwapWithoutFeesResult {
+        source_amount_swapped,
+        destination_amount_swapped: destination_amount,
+    })
+}
+
+/// Divide two numbers, rounding the quotient in the given direction
+pub fn div_rounded(
+    numerator: U256,
+    denominator: U256,
+    round_direction: RoundDirection,
+) -> Result<U256> {
+    let quotient = try_math!(numerator.try_div(denominator))?;
+    match round_direction {
+        RoundDirection::Ceiling if try_math!(quotient.try_mul(denominator))? < numerator => {
+            try_math!(quotient.try_add(U256::one()))
+        }
+        _ => Ok(quotient),
+    }
+}
+
+/// Take the square root of `numerator / denominator` on integers, rounding the
+/// root exactly in the given direction
+pub fn sqrt_rounded(
+    numerator: U256,
+    denominator: U256,
+    round_direction: RoundDirection,
+) -> Result<U256> {
+    let squared = div_rounded(numerator, denominator, round_direction)?;
+    let root = squared.integer_sqrt();
+    match round_direction {
+        RoundDirection::Ceiling if try_math!(root.try_mul(root))? < squared => {
+            try_math!(root.try_add(U256::one()))
+        }
+        _ => Ok(root),
+    }
+}
+
+/// Get the amount of pool tokens for the given amount of token A or B.
+///
+/// The constant product implementation uses the Balancer formulas found at
+/// <https://balancer.finance/whitepaper/#single-asset-deposit>, specifically
+/// in the case for 2 tokens, each weighted at 1/2. This is the same as
+/// swapping part of the source amount for the other token and depositing both
+/// sides proportionally, without fees.
+///
+/// pool_tokens = pool_supply * (sqrt(1 + source_amount / swap_source_amount) - 1)
+///
+/// The square root is taken on integers, as
+/// sqrt(pool_supply^2 * (swap_source_amount + source_amount) / swap_source_amount),
+/// so that the result is rounded exactly in the given direction.
+pub fn trading_tokens_to_pool_tokens(
+    source_amount: u128,
+    swap_source_amount: u128,
+    pool_supply: u128,
+    round_direction: RoundDirection,
+) -> Result<u128> {
+    let pool_supply = U256::from(pool_supply);
+    let swap_source_amount = U256::from(swap_source_amount);
+    let numerator = try_math!(pool_supply
+        .try_mul(pool_supply)?
+        .try_mul(swap_source_amount.try_add(U256::from(source_amount))?))?;
+    let new_pool_supply = sqrt_rounded(numerator, swap_source_amount, round_direction)?;
+    let pool_tokens = try_math!(new_pool_supply.try_sub(pool_supply))?;
+    u128::try_from(pool_tokens).map_err(|_| error!(SwapError::ConversionFailure))
+}
+
+/// Get the amount of pool tokens to burn for the given amount of token A or B.
+///
+/// The constant product implementation uses the Balancer formulas found at
+/// <https://balancer.finance/whitepaper/#single-asset-withdrawal>, specifically
+/// in the case for 2 tokens, each weighted at 1/2.
+///
+/// pool_tokens = pool_supply * (1 - sqrt(1 - destination_amount / swap_destination_amount))
+///
+/// The square root is taken on integers, as
+/// sqrt(pool_supply^2 * (swap_destination_amount - destination_amount) / swap_destination_amount),
+/// and rounded in the opposite direction, since it is subtracted from the supply.
+pub fn withdraw_single_token_type_exact_out(
+    destination_amount: u128,
+    swap_destination_amount: u128,
+    pool_supply: u128,
+    round_direction: RoundDirection,
+) -> Result<u128> {
+    let pool_supply = U256::from(pool_supply);
+    let swap_destination_amount = U256::from(swap_destination_amount);
+    let numerator = try_math!(pool_supply
+        .try_mul(pool_supply)?
+        .try_mul(swap_destination_amount.try_sub(U256::from(destination_amount))?))?;
+    let remaining_pool_supply = sqrt_rounded(
+        numerator,
+        swap_destination_amount,
+        round_direction.opposite(),
+    )?;
+    let pool_tokens = try_math!(pool_supply.try_sub(remaining_pool_supply))?;
+    u128::try_from(pool_tokens).map_err(|_| error!(SwapError::ConversionFailure))
+}
+
+/// Get the amount of token A or B redeemed for the given amount of pool tokens.
+///
+/// This is the inverse of `withdraw_single_token_type_exact_out`:
+///
+/// destination_amount = swap_destination_amount * (1 - ((pool_supply - pool_tokens) / pool_supply)^2)
+pub fn withdraw_one_token(
+    pool_tokens: u128,
+    swap_destination_amount: u128,
+    pool_supply: u128,
+    round_direction: RoundDirection,
+) -> Result<u128> {
+    let swap_destination_amount = U256::from(swap_destination_amount);
+    let pool_supply = U256::from(pool_supply);
+    let remaining_pool_supply = try_math!(pool_supply.try_sub(U256::from(pool_tokens)))?;
+    let remaining_destination_amount = div_rounded(
+        try_math!(swap_destination_amount
+            .try_mul(remaining_pool_supply)?
+            .try_mul(remaining_pool_supply))?,
+        try_math!(pool_supply.try_mul(pool_supply))?,
+        round_direction.opposite(),
+    )?;
+    let destination_amount =
+        try_math!(swap_destination_amount.try_sub(remaining_destination_amount))?;
+    u128::try_from(destination_amount).map_err(|_| error!(SwapError::ConversionFailure))
+}
+
 /// Calculates the total normalized value of the curve given the liquidity
 /// parameters.
 ///
@@ -65,6 +220,15 @@ pub fn normalized_value(
         .try_sqrt())
 }
 
+/// The constant product spot price, the amount of destination token per source
+/// token for an infinitesimally small trade: `pool_destination_amount / pool_source_amount`
+pub fn spot_price(
+    pool_source_amount: u128,
+    pool_destination_amount: u128,
+) -> Result<PreciseNumber> {
+    math::precise_ratio(pool_destination_amount.into(), pool_source_amount.into())
+}
+
 impl CurveCalculator for ConstantProductCurve {
     /// Constant product swap ensures x * y = constant
     fn swap_without_fees(
@@ -77,6 +241,33 @@ impl CurveCalculator for ConstantProductCurve {
         swap(source_amount, pool_source_amount, pool_destination_amount)
     }
 
+    /// Inverse of the constant product swap, rounding the source amount up
+    fn swap_without_fees_exact_out(
+        &self,
+        destination_amount: u128,
+        pool_source_amount: u128,
+        pool_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Result<SwapWithoutFeesResult> {
+        swap_exact_out(
+            destination_amount,
+            pool_source_amount,
+            pool_destination_amount,
+        )
+    }
+
+    fn spot_price(
+        &self,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<PreciseNumber> {
+        match trade_direction {
+            TradeDirection::AtoB => spot_price(pool_token_a_amount, pool_token_b_amount),
+            TradeDirection::BtoA => spot_price(pool_token_b_amount, pool_token_a_amount),
+        }
+    }
+
     /// The constant product implementation is a simple ratio calculation for how many
     /// trading tokens correspond to a certain number of pool tokens
     fn pool_tokens_to_trading_tokens(
@@ -96,6 +287,69 @@ impl CurveCalculator for ConstantProductCurve {
         )
     }
 
+    fn trading_tokens_to_pool_tokens(
+        &self,
+        source_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let swap_source_amount = match trade_direction {
+            TradeDirection::AtoB => pool_token_a_amount,
+            TradeDirection::BtoA => pool_token_b_amount,
+        };
+        trading_tokens_to_pool_tokens(
+            source_amount,
+            swap_source_amount,
+            pool_token_supply,
+            round_direction,
+        )
+    }
+
+    fn withdraw_single_token_type_exact_out(
+        &self,
+        destination_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let swap_destination_amount = match trade_direction {
+            TradeDirection::AtoB => pool_token_a_amount,
+            TradeDirection::BtoA => pool_token_b_amount,
+        };
+        withdraw_single_token_type_exact_out(
+            destination_amount,
+            swap_destination_amount,
+            pool_token_supply,
+            round_direction,
+        )
+    }
+
+    fn withdraw_one_token(
+        &self,
+        pool_tokens: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let swap_destination_amount = match trade_direction {
+            TradeDirection::AtoB => pool_token_a_amount,
+            TradeDirection::BtoA => pool_token_b_amount,
+        };
+        withdraw_one_token(
+            pool_tokens,
+            swap_destination_amount,
+            pool_token_supply,
+            round_direction,
+        )
+    }
+
     fn validate(&self) -> Result<()> {
         Ok(())
     }
@@ -128,8 +382,11 @@ mod tests {
     use crate::{
         curve::calculator::{
             test::{
-                check_curve_value_from_swap, check_pool_value_from_deposit,
-                check_pool_value_from_withdraw, total_and_intermediate,
+                check_curve_value_from_swap, check_curve_value_from_swap_exact_out,
+                check_pool_value_from_deposit, check_pool_value_from_deposit_single_token_type,
+                check_pool_value_from_withdraw, check_pool_value_from_withdraw_one_token,
+                check_pool_value_from_withdraw_single_token_type_exact_out,
+                check_spot_price_from_small_swap, total_and_intermediate,
             },
             RoundDirection, INITIAL_SWAP_POOL_AMOUNT,
         },
@@ -188,6 +445,93 @@ mod tests {
         assert_eq!(results, Err(SwapError::CalculationFailure.into()));
     }
 
+    #[test]
+    fn single_trading_token_conversion() {
+        let calculator = ConstantProductCurve {
+            ..Default::default()
+        };
+        // 3_000 / 1_000 = 3, sqrt(1 + 3) - 1 = 1
+        let pool_tokens = calculator
+            .trading_tokens_to_pool_tokens(
+                3_000,
+                1_000,
+                50,
+                1_000,
+                TradeDirection::AtoB,
+                RoundDirection::Floor,
+            )
+            .unwrap();
+        assert_eq!(pool_tokens, 1_000);
+        // sqrt(1 + 1) - 1 = 0.41421...
+        for (trade_direction, round_direction, expected) in [
+            (TradeDirection::AtoB, RoundDirection::Floor, 414),
+            (TradeDirection::AtoB, RoundDirection::Ceiling, 415),
+            (TradeDirection::BtoA, RoundDirection::Floor, 414),
+            (TradeDirection::BtoA, RoundDirection::Ceiling, 415),
+        ] {
+            let pool_tokens = calculator
+                .trading_tokens_to_pool_tokens(
+                    1_000,
+                    1_000,
+                    1_000,
+                    1_000,
+                    trade_direction,
+                    round_direction,
+                )
+                .unwrap();
+            assert_eq!(pool_tokens, expected);
+        }
+    }
+
+    #[test]
+    fn single_trading_token_withdrawal() {
+        let calculator = ConstantProductCurve {
+            ..Default::default()
+        };
+        // 1 - sqrt(1 - 750 / 1_000) = 0.5
+        for round_direction in [RoundDirection::Floor, RoundDirection::Ceiling] {
+            let pool_tokens = calculator
+                .withdraw_single_token_type_exact_out(
+                    750,
+                    1_000,
+                    50,
+                    1_000,
+                    TradeDirection::AtoB,
+                    round_direction,
+                )
+                .unwrap();
+            assert_eq!(pool_tokens, 500);
+            let destination_amount = calculator
+                .withdraw_one_token(500, 1_000, 50, 1_000, TradeDirection::AtoB, round_direction)
+                .unwrap();
+            assert_eq!(destination_amount, 750);
+        }
+        // 1 - sqrt(1 - 500 / 1_000) = 0.29289...
+        // 1 - (1 - 293 / 1_000)^2 = 0.50015...
+        for (trade_direction, round_direction, expected_pool_tokens, expected_destination) in [
+            (TradeDirection::AtoB, RoundDirection::Floor, 292, 500),
+            (TradeDirection::AtoB, RoundDirection::Ceiling, 293, 501),
+            (TradeDirection::BtoA, RoundDirection::Floor, 292, 500),
+            (TradeDirection::BtoA, RoundDirection::Ceiling, 293, 501),
+        ] {
+            let pool_tokens = calculator
+                .withdraw_single_token_type_exact_out(
+                    500,
+                    1_000,
+                    1_000,
+                    1_000,
+                    trade_direction,
+                    round_direction,
+                )
+                .unwrap();
+            assert_eq!(pool_tokens, expected_pool_tokens);
+            let destination_amount = calculator
+                .withdraw_one_token(293, 1_000, 1_000, 1_000, trade_direction, round_direction)
+                .unwrap();
+            assert_eq!(destination_amount, expected_destination);
+        }
+    }
+
     #[test]
     fn serialize_constant_product_curve() {
         let curve = ConstantProductCurve {
@@ -288,6 +632,96 @@ mod tests {
         }
     }
 
+    #[test]
+    fn constant_product_swap_exact_out_rounding() {
+        let curve = ConstantProductCurve::default();
+
+        // cannot take the whole pool
+        assert!(curve
+            .swap_without_fees_exact_out(30_000, 20_000, 30_000, TradeDirection::AtoB)
+            .is_err());
+        assert!(curve
+            .swap_without_fees_exact_out(0, 20_000, 30_000, TradeDirection::AtoB)
+            .is_err());
+
+        let tests: &[(u128, u128, u128, u128)] = &[
+            (15, 20_000 - 10, 30_000, 10), // 19_990 * 30_000 / 29_985 = 20_000
+            (14, 20_000, 30_000, 10),      // 20_000 * 30_000 / 29_986 = 20_009.33
+            (49, 60_000, 30_000, 99),      // 60_000 * 30_000 / 29_951 = 60_098.16
+            (6, 30_000 - 20, 10_000, 18),  // 29_980 * 10_000 / 9_994 = 29_997.99
+        ];
+        for (
+            destination_amount,
+            swap_source_amount,
+            swap_destination_amount,
+            expected_source_amount,
+        ) in tests.iter()
+        {
+            let invariant = swap_source_amount * swap_destination_amount;
+            let result = curve
+                .swap_without_fees_exact_out(
+                    *destination_amount,
+                    *swap_source_amount,
+                    *swap_destination_amount,
+                    TradeDirection::AtoB,
+                )
+                .unwrap();
+            assert_eq!(result.source_amount_swapped, *expected_source_amount);
+            assert_eq!(result.destination_amount_swapped, *destination_amount);
+            let new_invariant = (swap_source_amount + result.source_amount_swapped)
+                * (swap_destination_amount - result.destination_amount_swapped);
+            assert!(new_invariant >= invariant);
+
+            // swapping the required source amount gives at least the destination amount
+            let forward = curve
+                .swap_without_fees(
+                    result.source_amount_swapped,
+                    *swap_source_amount,
+                    *swap_destination_amount,
+                    TradeDirection::AtoB,
+                )
+                .unwrap();
+            assert!(forward.destination_amount_swapped >= *destination_amount);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn spot_price_matches_small_swap(
+            swap_source_amount in 10_000_000_000..1_000_000_000_000_000_u64,
+            swap_destination_amount in 10_000_000_000..1_000_000_000_000_000_u64,
+        ) {
+            let curve = ConstantProductCurve { ..Default::default() };
+            check_spot_price_from_small_swap(
+                &curve,
+                swap_source_amount as u128 / 100_000,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::AtoB,
+                1,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap_exact_out(
+            destination_token_amount in 1..u64::MAX,
+            swap_source_amount in 1..u64::MAX,
+            swap_destination_amount in 1..u64::MAX,
+        ) {
+            prop_assume!(destination_token_amount < swap_destination_amount);
+            let curve = ConstantProductCurve { ..Default::default() };
+            check_curve_value_from_swap_exact_out(
+                &curve,
+                destination_token_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::AtoB
+            );
+        }
+    }
+
     proptest! {
         #[test]
         fn curve_value_does_not_decrease_from_deposit(
@@ -340,4 +774,79 @@ mod tests {
             );
         }
     }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_deposit_single_token_type(
+            source_token_amount in 1..u64::MAX,
+            pool_token_supply in 1..u64::MAX,
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u64::MAX,
+        ) {
+            // The new invariant needs to fit in a u128 to calculate the value
+            let source_token_amount = source_token_amount as u128;
+            prop_assume!((source_token_amount + swap_token_a_amount as u128).checked_mul(swap_token_b_amount as u128).is_some());
+            prop_assume!((source_token_amount + swap_token_b_amount as u128).checked_mul(swap_token_a_amount as u128).is_some());
+            let curve = ConstantProductCurve { ..Default::default() };
+            for trade_direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+                check_pool_value_from_deposit_single_token_type(
+                    &curve,
+                    source_token_amount,
+                    pool_token_supply as u128,
+                    swap_token_a_amount as u128,
+                    swap_token_b_amount as u128,
+                    trade_direction,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw_single_token_type_exact_out(
+            destination_token_amount in 1..u64::MAX,
+            pool_token_supply in 1..u64::MAX,
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u64::MAX,
+        ) {
+            let curve = ConstantProductCurve { ..Default::default() };
+            for (trade_direction, swap_destination_amount) in [
+                (TradeDirection::AtoB, swap_token_a_amount),
+                (TradeDirection::BtoA, swap_token_b_amount),
+            ] {
+                if destination_token_amount >= swap_destination_amount {
+                    continue;
+                }
+                check_pool_value_from_withdraw_single_token_type_exact_out(
+                    &curve,
+                    destination_token_amount as u128,
+                    pool_token_supply as u128,
+                    swap_token_a_amount as u128,
+                    swap_token_b_amount as u128,
+                    trade_direction,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw_one_token(
+            (pool_token_supply, pool_token_amount) in total_and_intermediate(u64::MAX),
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u64::MAX,
+        ) {
+            let curve = ConstantProductCurve { ..Default::default() };
+            for trade_direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+                check_pool_value_from_withdraw_one_token(
+                    &curve,
+                    pool_token_amount as u128,
+                    pool_token_supply as u128,
+                    swap_token_a_amount as u128,
+                    swap_token_b_amount as u128,
+                    trade_direction,
+                );
+            }
+        }
+    }
 }
@@ -92,7 +92,8 @@ fn pre_fee_amount(
     }
 }
 
-fn validate_fraction(numerator: u64, denominator: u64) -> Result<()> {
+/// Validate that a fee fraction is below one, a zero denominator is only valid for a zero fee
+pub fn validate_fraction(numerator: u64, denominator: u64) -> Result<()> {
     if denominator == 0 && numerator == 0 {
         Ok(())
     } else if numerator >= denominator {
@@ -113,6 +114,16 @@ impl Fees {
         )
     }
 
+    /// Calculate the inverse withdraw amount, how much must be withdrawn for
+    /// the provided amount to remain after the owner withdraw fee
+    pub fn pre_owner_withdraw_fee_amount(&self, post_fee_amount: u128) -> Result<u128> {
+        pre_fee_amount(
+            post_fee_amount,
+            u128::from(self.owner_withdraw_fee_numerator),
+            u128::from(self.owner_withdraw_fee_denominator),
+        )
+    }
+
     /// Calculate the trading fee in trading tokens
     pub fn trading_fee(&self, trading_tokens: u128) -> Result<u128> {
         calculate_fee(
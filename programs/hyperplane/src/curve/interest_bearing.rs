@@ -0,0 +1,742 @@
+//! Token-2022 interest-bearing trading tokens
+//!
+//! The raw amounts of an interest-bearing token do not change as interest accrues, only their UI
+//! amount does. Curves pegged to a price (stable and constant price) trade the UI amounts, so the
+//! peg tracks the accrued interest instead of drifting away from it.
+
+use std::sync::Arc;
+
+use anchor_lang::{
+    err,
+    prelude::{msg, AccountInfo, Clock, SolanaSysvar},
+    Key, Result,
+};
+use anchor_spl::token_2022::spl_token_2022::extension::interest_bearing_mint::InterestBearingConfig;
+use spl_math::precise_number::PreciseNumber;
+
+use crate::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        calculator::{
+            AorB, CurveCalculator, DynAccountSerialize, RoundDirection, SwapWithoutFeesResult,
+            TradeDirection, TradingTokenResult,
+        },
+    },
+    error::SwapError,
+    require_msg,
+    state::{SwapPool, INTEREST_BEARING_TOKEN_A, INTEREST_BEARING_TOKEN_B},
+    try_math,
+    utils::{
+        math::{TryMath, TryMathRef, TryNew},
+        token_extensions,
+    },
+};
+
+/// Denominator of the fixed-point interest scales, a scale of `INTEREST_SCALE_DENOMINATOR` is 1
+pub const INTEREST_SCALE_DENOMINATOR: u128 = 1_000_000_000_000;
+
+const ONE_IN_BASIS_POINTS: f64 = 10_000.;
+const SECONDS_PER_YEAR: f64 = 60. * 60. * 24. * 365.24;
+
+/// The factor from raw to UI amounts of an interest-bearing token at the given time, excluding
+/// the mint decimals, over `INTEREST_SCALE_DENOMINATOR`
+///
+/// Interest is continuously compounded at the average rate up to the last rate update, then at
+/// the current rate, as done by the token-2022 program for `AmountToUiAmount`.
+pub fn interest_scale(config: &InterestBearingConfig, unix_timestamp: i64) -> Result<u128> {
+    let exp = |rate: i16, from: i64, to: i64| -> f64 {
+        let timespan = i128::from(to) - i128::from(from);
+        (i128::from(rate) * timespan) as f64 / SECONDS_PER_YEAR / ONE_IN_BASIS_POINTS
+    };
+    let initialization_timestamp = i64::from(config.initialization_timestamp);
+    let last_update_timestamp = i64::from(config.last_update_timestamp);
+    let exponent = exp(
+        config.pre_update_average_rate.into(),
+        initialization_timestamp,
+        last_update_timestamp,
+    ) + exp(
+        config.current_rate.into(),
+        last_update_timestamp,
+        unix_timestamp,
+    );
+    let scale = (exponent.exp() * INTEREST_SCALE_DENOMINATOR as f64).round();
+    require_msg!(
+        scale.is_finite() && scale >= 1. && scale <= u64::MAX as f64,
+        SwapError::CalculationFailure,
+        &format!("CalculationFailure: interest scale out of range, exponent={exponent}")
+    );
+    Ok(scale as u128)
+}
+
+/// The interest scale of a mint at the given time, `None` if the mint is not interest-bearing
+pub fn mint_interest_scale(mint_data: &[u8], unix_timestamp: i64) -> Result<Option<u128>> {
+    token_extensions::mint_extension::<InterestBearingConfig>(mint_data)?
+        .map(|config| interest_scale(&config, unix_timestamp))
+        .transpose()
+}
+
+/// The `SwapPool::interest_bearing_mints` flags of a pair of trading token mints
+pub fn interest_bearing_mints(token_a_mint: &[u8], token_b_mint: &[u8]) -> Result<u64> {
+    let mut flags = 0;
+    if token_extensions::mint_extension::<InterestBearingConfig>(token_a_mint)?.is_some() {
+        flags |= INTEREST_BEARING_TOKEN_A;
+    }
+    if token_extensions::mint_extension::<InterestBearingConfig>(token_b_mint)?.is_some() {
+        flags |= INTEREST_BEARING_TOKEN_B;
+    }
+    Ok(flags)
+}
+
+impl SwapCurve {
+    /// Trade the UI amounts of interest-bearing trading tokens, for the curves pegged to a price
+    ///
+    /// Any other curve, or a pair without interest-bearing tokens, is returned as is.
+    pub fn with_interest_bearing_mints(
+        self,
+        token_a_mint: Option<&[u8]>,
+        token_b_mint: Option<&[u8]>,
+        unix_timestamp: i64,
+    ) -> Result<Self> {
+        let scale = |mint: Option<&[u8]>| -> Result<Option<u128>> {
+            match mint {
+                Some(mint_data) => mint_interest_scale(mint_data, unix_timestamp),
+                None => Ok(None),
+            }
+        };
+        Ok(self.with_interest_scales(scale(token_a_mint)?, scale(token_b_mint)?))
+    }
+
+    /// Trade the UI amounts of the interest-bearing trading tokens of the pool at the current time
+    ///
+    /// The mints of the pool flagged as interest-bearing are taken from the instruction accounts,
+    /// or looked up by key in the remaining accounts when the instruction only has one of them.
+    pub fn with_pool_interest_bearing_mints(
+        self,
+        pool: &SwapPool,
+        mints: &[&AccountInfo],
+        remaining_accounts: &[AccountInfo],
+    ) -> Result<Self> {
+        if pool.interest_bearing_mints == 0 {
+            return Ok(self);
+        }
+        let unix_timestamp = Clock::get()?.unix_timestamp;
+        let scale = |a_or_b: AorB| -> Result<Option<u128>> {
+            if !pool.is_interest_bearing(a_or_b) {
+                return Ok(None);
+            }
+            let key = match a_or_b {
+                AorB::A => pool.token_a_mint,
+                AorB::B => pool.token_b_mint,
+            };
+            let mint_data = match mints.iter().find(|mint| mint.key() == key) {
+                Some(mint) => mint.data.borrow(),
+                None => {
+                    match remaining_accounts.iter().find(|mint| mint.key() == key) {
+                        Some(mint) => mint.data.borrow(),
+                        None => {
+                            msg!("MissingInterestBearingMint: token {a_or_b:?} mint {key} not provided");
+                            return err!(SwapError::MissingInterestBearingMint);
+                        }
+                    }
+                }
+            };
+            mint_interest_scale(&mint_data, unix_timestamp)
+        };
+        Ok(self.with_interest_scales(scale(AorB::A)?, scale(AorB::B)?))
+    }
+
+    fn with_interest_scales(
+        self,
+        token_a_scale: Option<u128>,
+        token_b_scale: Option<u128>,
+    ) -> Self {
+        if !matches!(
+            self.curve_type,
+            CurveType::Stable | CurveType::ConstantPrice
+        ) || (token_a_scale.is_none() && token_b_scale.is_none())
+        {
+            return self;
+        }
+        SwapCurve {
+            curve_type: self.curve_type,
+            calculator: Arc::new(InterestBearingCurve {
+                calculator: self.calculator,
+                token_a_scale: token_a_scale.unwrap_or(INTEREST_SCALE_DENOMINATOR),
+                token_b_scale: token_b_scale.unwrap_or(INTEREST_SCALE_DENOMINATOR),
+            }),
+        }
+    }
+}
+
+/// Wraps a curve to trade the UI amounts of its interest-bearing tokens
+///
+/// Amounts are scaled to UI amounts on the way into the wrapped curve, and back to raw amounts on
+/// the way out, rounding in favour of the pool.
+#[derive(Debug)]
+pub struct InterestBearingCurve {
+    /// The curve trading the UI amounts
+    pub calculator: Arc<dyn CurveCalculator + Sync + Send>,
+    /// Token A interest scale, over `INTEREST_SCALE_DENOMINATOR`
+    pub token_a_scale: u128,
+    /// Token B interest scale, over `INTEREST_SCALE_DENOMINATOR`
+    pub token_b_scale: u128,
+}
+
+impl InterestBearingCurve {
+    /// The (source, destination) scales of a trade, the source is token A when trading A to B
+    fn scales(&self, trade_direction: TradeDirection) -> (u128, u128) {
+        match trade_direction {
+            TradeDirection::AtoB => (self.token_a_scale, self.token_b_scale),
+            TradeDirection::BtoA => (self.token_b_scale, self.token_a_scale),
+        }
+    }
+
+    fn pool_to_ui(
+        &self,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+    ) -> Result<(u128, u128)> {
+        Ok((
+            to_ui(
+                pool_token_a_amount,
+                self.token_a_scale,
+                RoundDirection::Floor,
+            )?,
+            to_ui(
+                pool_token_b_amount,
+                self.token_b_scale,
+                RoundDirection::Floor,
+            )?,
+        ))
+    }
+}
+
+fn mul_div(
+    amount: u128,
+    numerator: u128,
+    denominator: u128,
+    round_direction: RoundDirection,
+) -> Result<u128> {
+    let product = try_math!(amount.try_mul(numerator))?;
+    match round_direction {
+        RoundDirection::Floor => try_math!(product.try_div(denominator)),
+        RoundDirection::Ceiling => {
+            try_math!(product
+                .try_add(denominator)?
+                .try_sub(1)?
+                .try_div(denominator))
+        }
+    }
+}
+
+fn to_ui(amount: u128, scale: u128, round_direction: RoundDirection) -> Result<u128> {
+    mul_div(amount, scale, INTEREST_SCALE_DENOMINATOR, round_direction)
+}
+
+fn from_ui(ui_amount: u128, scale: u128, round_direction: RoundDirection) -> Result<u128> {
+    mul_div(
+        ui_amount,
+        INTEREST_SCALE_DENOMINATOR,
+        scale,
+        round_direction,
+    )
+}
+
+impl CurveCalculator for InterestBearingCurve {
+    fn swap_without_fees(
+        &self,
+        source_amount: u128,
+        pool_source_amount: u128,
+        pool_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<SwapWithoutFeesResult> {
+        let (source_scale, destination_scale) = self.scales(trade_direction);
+        let result = self.calculator.swap_without_fees(
+            to_ui(source_amount, source_scale, RoundDirection::Floor)?,
+            to_ui(pool_source_amount, source_scale, RoundDirection::Floor)?,
+            to_ui(
+                pool_destination_amount,
+                destination_scale,
+                RoundDirection::Floor,
+            )?,
+            trade_direction,
+        )?;
+        let source_amount_swapped = from_ui(
+            result.source_amount_swapped,
+            source_scale,
+            RoundDirection::Ceiling,
+        )?;
+        Ok(SwapWithoutFeesResult {
+            source_amount_swapped: source_amount_swapped.min(source_amount),
+            destination_amount_swapped: from_ui(
+                result.destination_amount_swapped,
+                destination_scale,
+                RoundDirection::Floor,
+            )?,
+        })
+    }
+
+    fn swap_without_fees_exact_out(
+        &self,
+        destination_amount: u128,
+        pool_source_amount: u128,
+        pool_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<SwapWithoutFeesResult> {
+        let (source_scale, destination_scale) = self.scales(trade_direction);
+        let result = self.calculator.swap_without_fees_exact_out(
+            to_ui(
+                destination_amount,
+                destination_scale,
+                RoundDirection::Ceiling,
+            )?,
+            to_ui(pool_source_amount, source_scale, RoundDirection::Floor)?,
+            to_ui(
+                pool_destination_amount,
+                destination_scale,
+                RoundDirection::Floor,
+            )?,
+            trade_direction,
+        )?;
+        Ok(SwapWithoutFeesResult {
+            source_amount_swapped: from_ui(
+                result.source_amount_swapped,
+                source_scale,
+                RoundDirection::Ceiling,
+            )?,
+            destination_amount_swapped: from_ui(
+                result.destination_amount_swapped,
+                destination_scale,
+                RoundDirection::Floor,
+            )?,
+        })
+    }
+
+    fn spot_price(
+        &self,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<PreciseNumber> {
+        let (pool_token_a_amount, pool_token_b_amount) =
+            self.pool_to_ui(pool_token_a_amount, pool_token_b_amount)?;
+        let ui_spot_price = self.calculator.spot_price(
+            pool_token_a_amount,
+            pool_token_b_amount,
+            trade_direction,
+        )?;
+        // destination per source UI amount -> destination per source raw amount
+        let (source_scale, destination_scale) = self.scales(trade_direction);
+        ui_spot_price
+            .try_mul(&PreciseNumber::try_new(source_scale)?)?
+            .try_div(&PreciseNumber::try_new(destination_scale)?)
+    }
+
+    fn new_pool_supply(&self) -> u128 {
+        self.calculator.new_pool_supply()
+    }
+
+    fn pool_tokens_to_trading_tokens(
+        &self,
+        pool_tokens: u128,
+        pool_token_supply: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Result<TradingTokenResult> {
+        let (pool_token_a_amount, pool_token_b_amount) =
+            self.pool_to_ui(pool_token_a_amount, pool_token_b_amount)?;
+        let result = self.calculator.pool_tokens_to_trading_tokens(
+            pool_tokens,
+            pool_token_supply,
+            pool_token_a_amount,
+            pool_token_b_amount,
+            round_direction,
+        )?;
+        Ok(TradingTokenResult {
+            token_a_amount: from_ui(result.token_a_amount, self.token_a_scale, round_direction)?,
+            token_b_amount: from_ui(result.token_b_amount, self.token_b_scale, round_direction)?,
+        })
+    }
+
+    fn trading_tokens_to_pool_tokens(
+        &self,
+        source_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let (pool_token_a_amount, pool_token_b_amount) =
+            self.pool_to_ui(pool_token_a_amount, pool_token_b_amount)?;
+        let (source_scale, _) = self.scales(trade_direction);
+        self.calculator.trading_tokens_to_pool_tokens(
+            to_ui(source_amount, source_scale, round_direction)?,
+            pool_token_a_amount,
+            pool_token_b_amount,
+            pool_token_supply,
+            trade_direction,
+            round_direction,
+        )
+    }
+
+    fn withdraw_single_token_type_exact_out(
+        &self,
+        destination_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let (pool_token_a_amount, pool_token_b_amount) =
+            self.pool_to_ui(pool_token_a_amount, pool_token_b_amount)?;
+        let (destination_scale, _) = self.scales(trade_direction);
+        self.calculator.withdraw_single_token_type_exact_out(
+            to_ui(destination_amount, destination_scale, round_direction)?,
+            pool_token_a_amount,
+            pool_token_b_amount,
+            pool_token_supply,
+            trade_direction,
+            round_direction,
+        )
+    }
+
+    fn withdraw_one_token(
+        &self,
+        pool_tokens: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let (pool_token_a_amount, pool_token_b_amount) =
+            self.pool_to_ui(pool_token_a_amount, pool_token_b_amount)?;
+        let (destination_scale, _) = self.scales(trade_direction);
+        let ui_amount = self.calculator.withdraw_one_token(
+            pool_tokens,
+            pool_token_a_amount,
+            pool_token_b_amount,
+            pool_token_supply,
+            trade_direction,
+            round_direction,
+        )?;
+        from_ui(ui_amount, destination_scale, round_direction)
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.calculator.validate()
+    }
+
+    fn validate_supply(&self, token_a_amount: u64, token_b_amount: u64) -> Result<()> {
+        self.calculator
+            .validate_supply(token_a_amount, token_b_amount)
+    }
+
+    fn allows_deposits(&self) -> bool {
+        self.calculator.allows_deposits()
+    }
+
+    fn normalized_value(
+        &self,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+    ) -> Result<PreciseNumber> {
+        let (pool_token_a_amount, pool_token_b_amount) =
+            self.pool_to_ui(pool_token_a_amount, pool_token_b_amount)?;
+        self.calculator
+            .normalized_value(pool_token_a_amount, pool_token_b_amount)
+    }
+}
+
+impl DynAccountSerialize for InterestBearingCurve {
+    fn try_dyn_serialize(&self, dst: std::cell::RefMut<&mut [u8]>) -> Result<()> {
+        self.calculator.try_dyn_serialize(dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{
+        prelude::Pubkey,
+        solana_program::{clock::Epoch, program_option::COption, program_pack::Pack},
+    };
+    use anchor_spl::token_2022::{
+        spl_token_2022,
+        spl_token_2022::{
+            extension::{ExtensionType, StateWithExtensionsMut},
+            state::Mint,
+        },
+    };
+
+    use super::*;
+    use crate::{
+        instructions::test::runner::syscall_stubs::test_syscall_stubs,
+        state::{ConstantPriceCurve, ConstantProductCurve, StableCurve},
+    };
+
+    const ONE_YEAR: i64 = 31_556_736;
+
+    fn config(rate_bps: i16) -> InterestBearingConfig {
+        InterestBearingConfig {
+            current_rate: rate_bps.into(),
+            ..Default::default()
+        }
+    }
+
+    fn interest_bearing_mint(rate_bps: i16) -> Vec<u8> {
+        let mut mint_data =
+            vec![
+                0;
+                ExtensionType::get_account_len::<Mint>(&[ExtensionType::InterestBearingConfig])
+            ];
+        let mut mint =
+            StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut mint_data).unwrap();
+        *mint.init_extension::<InterestBearingConfig>(true).unwrap() = config(rate_bps);
+        mint.base.decimals = 6;
+        mint.base.is_initialized = true;
+        mint.base.mint_authority = COption::Some(Pubkey::new_unique());
+        mint.pack_base();
+        mint.init_account_type().unwrap();
+        mint_data
+    }
+
+    fn is_interest_bearing(curve: &SwapCurve) -> bool {
+        format!("{:?}", curve.calculator).starts_with("InterestBearingCurve")
+    }
+
+    fn constant_price_curve(token_b_price: u64) -> SwapCurve {
+        SwapCurve {
+            curve_type: CurveType::ConstantPrice,
+            calculator: Arc::new(ConstantPriceCurve {
+                token_b_price,
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn test_interest_scale_matches_token_2022_ui_amount() {
+        for (rate_bps, unix_timestamp) in [
+            (0, ONE_YEAR),
+            (500, ONE_YEAR),
+            (500, 3 * ONE_YEAR),
+            (-300, ONE_YEAR),
+            (i16::MAX, ONE_YEAR),
+        ] {
+            let config = config(rate_bps);
+            let scale = interest_scale(&config, unix_timestamp).unwrap();
+            let amount = 1_000_000_000;
+            let ui_amount: f64 = config
+                .amount_to_ui_amount(amount, 0, unix_timestamp)
+                .unwrap()
+                .parse()
+                .unwrap();
+            let scaled_amount = to_ui(amount.into(), scale, RoundDirection::Floor).unwrap();
+            assert!(
+                (scaled_amount as f64 - ui_amount).abs() <= 1.,
+                "{scaled_amount} != {ui_amount}"
+            );
+        }
+        assert_eq!(
+            interest_scale(&config(0), ONE_YEAR).unwrap(),
+            INTEREST_SCALE_DENOMINATOR
+        );
+    }
+
+    #[test]
+    fn test_constant_price_trades_ui_amounts() {
+        // token B has doubled in value, in UI terms 1 raw token B is worth 2 token A
+        let curve = constant_price_curve(1);
+        let interest_bearing = InterestBearingCurve {
+            calculator: curve.calculator,
+            token_a_scale: INTEREST_SCALE_DENOMINATOR,
+            token_b_scale: 2 * INTEREST_SCALE_DENOMINATOR,
+        };
+
+        let result = interest_bearing
+            .swap_without_fees(100, 1_000, 1_000, TradeDirection::AtoB)
+            .unwrap();
+        assert_eq!(
+            result,
+            SwapWithoutFeesResult {
+                source_amount_swapped: 100,
+                destination_amount_swapped: 50,
+            }
+        );
+        let result = interest_bearing
+            .swap_without_fees(100, 1_000, 1_000, TradeDirection::BtoA)
+            .unwrap();
+        assert_eq!(
+            result,
+            SwapWithoutFeesResult {
+                source_amount_swapped: 100,
+                destination_amount_swapped: 200,
+            }
+        );
+        let result = interest_bearing
+            .swap_without_fees_exact_out(50, 1_000, 1_000, TradeDirection::AtoB)
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 100);
+
+        let spot_price = interest_bearing
+            .spot_price(1_000, 1_000, TradeDirection::BtoA)
+            .unwrap();
+        assert_eq!(spot_price.to_imprecise().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_stable_trades_around_the_ui_peg() {
+        let token_b_scale = interest_scale(&config(500), 2 * ONE_YEAR).unwrap();
+        let stable = SwapCurve {
+            curve_type: CurveType::Stable,
+            calculator: Arc::new(StableCurve::new(100, 6, 6).unwrap()),
+        };
+        let interest_bearing = InterestBearingCurve {
+            calculator: stable.calculator.clone(),
+            token_a_scale: INTEREST_SCALE_DENOMINATOR,
+            token_b_scale,
+        };
+
+        // balanced in UI terms, the raw B balance is lower by the accrued interest
+        let pool_token_a_amount = 1_000_000_000_000;
+        let pool_token_b_amount =
+            from_ui(pool_token_a_amount, token_b_scale, RoundDirection::Floor).unwrap();
+        let source_amount = 1_000_000;
+        let result = interest_bearing
+            .swap_without_fees(
+                source_amount,
+                pool_token_a_amount,
+                pool_token_b_amount,
+                TradeDirection::AtoB,
+            )
+            .unwrap();
+        let expected = from_ui(source_amount, token_b_scale, RoundDirection::Floor).unwrap();
+        assert!(result.destination_amount_swapped <= expected);
+        assert!(result.destination_amount_swapped >= expected - 2);
+
+        // unscaled, the same pool would overpay in token B, ignoring its accrued interest
+        let result = stable
+            .calculator
+            .swap_without_fees(
+                source_amount,
+                pool_token_a_amount,
+                pool_token_b_amount,
+                TradeDirection::AtoB,
+            )
+            .unwrap();
+        assert!(result.destination_amount_swapped > expected * 105 / 100);
+    }
+
+    #[test]
+    fn test_scaling_rounds_in_favour_of_the_pool() {
+        let interest_bearing = InterestBearingCurve {
+            calculator: constant_price_curve(1).calculator,
+            token_a_scale: INTEREST_SCALE_DENOMINATOR * 3 / 2,
+            token_b_scale: INTEREST_SCALE_DENOMINATOR * 7 / 5,
+        };
+        for source_amount in 1..100 {
+            let result = interest_bearing
+                .swap_without_fees(source_amount, 1_000_000, 1_000_000, TradeDirection::AtoB)
+                .unwrap();
+            // the value received never exceeds the value paid
+            assert!(result.destination_amount_swapped * 14 <= result.source_amount_swapped * 15);
+            assert!(result.source_amount_swapped <= source_amount);
+        }
+    }
+
+    #[test]
+    fn test_with_interest_bearing_mints() {
+        let mint = interest_bearing_mint(500);
+
+        let curve = constant_price_curve(1)
+            .with_interest_bearing_mints(None, Some(&mint), ONE_YEAR)
+            .unwrap();
+        assert!(is_interest_bearing(&curve));
+
+        let curve = constant_price_curve(1)
+            .with_interest_bearing_mints(None, None, ONE_YEAR)
+            .unwrap();
+        assert!(!is_interest_bearing(&curve));
+
+        // only the curves pegged to a price trade UI amounts
+        let curve = SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: Arc::new(ConstantProductCurve::default()),
+        }
+        .with_interest_bearing_mints(Some(&mint), Some(&mint), ONE_YEAR)
+        .unwrap();
+        assert!(!is_interest_bearing(&curve));
+    }
+
+    #[test]
+    fn test_with_pool_interest_bearing_mints() {
+        test_syscall_stubs();
+
+        let key = Pubkey::new_unique();
+        let pool = SwapPool {
+            token_b_mint: key,
+            interest_bearing_mints: INTEREST_BEARING_TOKEN_B,
+            ..Default::default()
+        };
+        let mut mint_data = interest_bearing_mint(500);
+        let mut lamports = u64::MAX;
+        let token_program = spl_token_2022::id();
+        let mint_info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut mint_data,
+            &token_program,
+            false,
+            Epoch::default(),
+        );
+
+        let curve = constant_price_curve(1)
+            .with_pool_interest_bearing_mints(&pool, &[&mint_info], &[])
+            .unwrap();
+        assert!(is_interest_bearing(&curve));
+        let curve = constant_price_curve(1)
+            .with_pool_interest_bearing_mints(&pool, &[], &[mint_info.clone()])
+            .unwrap();
+        assert!(is_interest_bearing(&curve));
+
+        assert_eq!(
+            constant_price_curve(1)
+                .with_pool_interest_bearing_mints(&pool, &[], &[])
+                .err(),
+            Some(SwapError::MissingInterestBearingMint.into())
+        );
+    }
+
+    #[test]
+    fn test_interest_bearing_mints_flags() {
+        let mint = interest_bearing_mint(500);
+        let mut plain_mint = vec![0; Mint::LEN];
+        Mint::pack(
+            Mint {
+                is_initialized: true,
+                ..Default::default()
+            },
+            &mut plain_mint,
+        )
+        .unwrap();
+
+        assert_eq!(
+            interest_bearing_mints(&mint, &plain_mint).unwrap(),
+            INTEREST_BEARING_TOKEN_A
+        );
+        assert_eq!(
+            interest_bearing_mints(&plain_mint, &mint).unwrap(),
+            INTEREST_BEARING_TOKEN_B
+        );
+        assert_eq!(
+            interest_bearing_mints(&mint, &mint).unwrap(),
+            INTEREST_BEARING_TOKEN_A | INTEREST_BEARING_TOKEN_B
+        );
+        assert_eq!(interest_bearing_mints(&plain_mint, &plain_mint).unwrap(), 0);
+    }
+}
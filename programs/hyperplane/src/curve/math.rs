@@ -1,11 +1,13 @@
 #![allow(clippy::arithmetic_side_effects)]
 
 use anchor_lang::prelude::*;
+use spl_math::{precise_number::PreciseNumber, uint::U256};
 
 use crate::{
     curve::calculator::{RoundDirection, TradingTokenResult},
+    error::SwapError,
     try_math,
-    utils::math::TryMath,
+    utils::math::{TryMath, TryMathRef, TryNew},
 };
 
 /// Get the amount of trading tokens for the given amount of pool tokens,
@@ -55,8 +57,184 @@ pub fn pool_tokens_to_trading_tokens(
     })
 }
 
+/// Ratio of two unsigned integers as a precise number.
+///
+/// Both sides are shifted right until they fit into a u128, which only drops
+/// precision beyond that of the precise number for ratios that can be represented.
+pub fn precise_ratio(numerator: U256, denominator: U256) -> Result<PreciseNumber> {
+    let shift = numerator.bits().max(denominator.bits()).saturating_sub(128);
+    let numerator = PreciseNumber::try_new((numerator >> shift).as_u128())?;
+    let denominator = PreciseNumber::try_new((denominator >> shift).as_u128())?;
+    numerator.try_div(&denominator)
+}
+
+/// ln(2) with 18 decimals, so that multiples of it keep the precision of a precise number
+const LN_2_E18: u128 = 693_147_180_559_945_309;
+
+/// Ratio of the 18 decimals of `LN_2_E18` to the 12 decimals of a precise number
+const LN_2_SCALE: u128 = 1_000_000;
+
+/// Upper bound on the number of terms of the `ln` and `exp` series, which converge
+/// to the precision of a precise number in fewer than 20 terms
+const MAX_SERIES_TERMS: u128 = 64;
+
+/// Largest power of 2 in the results of `exp`, beyond which `e^x` overflows and
+/// `e^-x` rounds to zero
+const MAX_EXP_SHIFT: u128 = 255;
+
+/// Relative error bound of `pow_ratio` per unit of the exponent, 1e-10 in units of
+/// the precision of a precise number, a few times the error of the `ln` and `exp`
+/// approximations
+const POW_RELATIVE_ERROR_PER_EXPONENT: u128 = 100;
+
+/// `k * ln(2)` as the value of a precise number
+fn ln_2_times(k: u128) -> Result<U256> {
+    try_math!(U256::from(k)
+        .try_mul(U256::from(LN_2_E18))?
+        .try_add(U256::from(LN_2_SCALE / 2))?
+        .try_div(U256::from(LN_2_SCALE)))
+}
+
+/// Natural logarithm of `numerator / denominator`, which must be at least 1.
+///
+/// The ratio is split into `2^k * m` with `1 <= m < 2`, and
+/// `ln(m) = 2 * atanh(t) = 2 * (t + t^3 / 3 + t^5 / 5 + ...)` with
+/// `t = (m - 1) / (m + 1) < 1 / 3`.
+pub fn ln_ratio(numerator: U256, denominator: U256) -> Result<PreciseNumber> {
+    require!(
+        !denominator.is_zero() && numerator >= denominator,
+        SwapError::CalculationFailure
+    );
+    let one = PreciseNumber::one().value;
+
+    // largest k such that denominator * 2^k <= numerator
+    let mut k = numerator.bits() - denominator.bits();
+    let mut scaled_denominator = denominator << k;
+    if scaled_denominator > numerator {
+        k -= 1;
+        scaled_denominator = scaled_denominator >> 1;
+    }
+
+    let t = precise_ratio(
+        try_math!(numerator.try_sub(scaled_denominator))?,
+        try_math!(numerator.try_add(scaled_denominator))?,
+    )?
+    .value;
+    let t_squared = t * t / one;
+    let mut term = t;
+    let mut series = t;
+    for n in 1..MAX_SERIES_TERMS {
+        term = term * t_squared / one;
+        if term.is_zero() {
+            break;
+        }
+        series = series + term / U256::from(2 * n + 1);
+    }
+
+    Ok(PreciseNumber {
+        value: try_math!(series
+            .try_mul(U256::from(2))?
+            .try_add(ln_2_times(k as u128)?))?,
+    })
+}
+
+/// `e^x` split into `k` and `e^r` with `x = k * ln(2) + r` and `0 <= r < ln(2)`,
+/// summing the series `e^r = 1 + r + r^2 / 2! + r^3 / 3! + ...`
+fn exp_parts(exponent: &PreciseNumber) -> Result<(u128, U256)> {
+    let one = PreciseNumber::one().value;
+    let k = try_math!(exponent
+        .value
+        .try_mul(U256::from(LN_2_SCALE))?
+        .try_div(U256::from(LN_2_E18)))?;
+    let k = u128::try_from(k).map_err(|_| error!(SwapError::ConversionFailure))?;
+    require!(k <= MAX_EXP_SHIFT, SwapError::CalculationFailure);
+    let r = exponent.value.saturating_sub(ln_2_times(k)?);
+
+    let mut term = one;
+    let mut series = one;
+    for n in 1..MAX_SERIES_TERMS {
+        term = term * r / (one * U256::from(n));
+        if term.is_zero() {
+            break;
+        }
+        series = series + term;
+    }
+    Ok((k, series))
+}
+
+/// `e^x` for a non-negative precise number `x`
+pub fn exp(exponent: &PreciseNumber) -> Result<PreciseNumber> {
+    let (k, exp_r) = exp_parts(exponent)?;
+    Ok(PreciseNumber {
+        value: try_math!(exp_r.try_mul(U256::one() << k as usize))?,
+    })
+}
+
+/// `(base_numerator / base_denominator)^(exponent_numerator / exponent_denominator)`,
+/// approximated as `e^(exponent * ln(base))` and bounded in the given direction.
+///
+/// The approximation is within a relative error of `(exponent + 1) * 1e-10` plus the
+/// precision of a precise number, which is added to the result when rounding up, and
+/// subtracted from it when rounding down.
+pub fn pow_ratio(
+    base_numerator: u128,
+    base_denominator: u128,
+    exponent_numerator: u64,
+    exponent_denominator: u64,
+    round_direction: RoundDirection,
+) -> Result<PreciseNumber> {
+    require!(
+        base_denominator > 0 && exponent_denominator > 0,
+        SwapError::CalculationFailure
+    );
+    let one = PreciseNumber::one().value;
+    let exponent_numerator = U256::from(exponent_numerator);
+    let exponent_denominator = U256::from(exponent_denominator);
+    let scale_ln = |ln: PreciseNumber| -> Result<PreciseNumber> {
+        Ok(PreciseNumber {
+            value: try_math!(ln
+                .value
+                .try_mul(exponent_numerator)?
+                .try_div(exponent_denominator))?,
+        })
+    };
+
+    let power = if base_numerator == 0 {
+        U256::zero()
+    } else if base_numerator >= base_denominator {
+        exp(&scale_ln(ln_ratio(
+            base_numerator.into(),
+            base_denominator.into(),
+        )?)?)?
+        .value
+    } else {
+        // base^exponent = 1 / (1 / base)^exponent
+        let exponent = scale_ln(ln_ratio(base_denominator.into(), base_numerator.into())?)?;
+        if exponent.value >= ln_2_times(MAX_EXP_SHIFT)? {
+            U256::zero()
+        } else {
+            let (k, exp_r) = exp_parts(&exponent)?;
+            (one * one / exp_r) >> k as usize
+        }
+    };
+
+    let error = try_math!(power
+        .try_mul(exponent_numerator.try_add(exponent_denominator)?)?
+        .try_mul(U256::from(POW_RELATIVE_ERROR_PER_EXPONENT))?
+        .try_div(exponent_denominator.try_mul(one)?)?
+        .try_add(U256::one()))?;
+    let value = match round_direction {
+        RoundDirection::Floor => power.saturating_sub(error),
+        RoundDirection::Ceiling => try_math!(power.try_add(error))?,
+    };
+    Ok(PreciseNumber { value })
+}
+
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+    use spl_math::{precise_number::PreciseNumber, uint::U256};
+
     use crate::curve::calculator::RoundDirection;
 
     #[test]
@@ -178,4 +356,135 @@ mod tests {
         assert_eq!(result.token_a_amount, 1);
         assert_eq!(result.token_b_amount, 0);
     }
+
+    #[test]
+    pub fn test_precise_ratio() {
+        let ratio = super::precise_ratio(U256::from(3), U256::from(2)).unwrap();
+        assert_eq!(
+            ratio,
+            PreciseNumber::new(3)
+                .unwrap()
+                .checked_div(&PreciseNumber::new(2).unwrap())
+                .unwrap()
+        );
+
+        // both sides beyond u128 keep the ratio
+        let ratio =
+            super::precise_ratio(U256::from(u128::MAX) * 6, U256::from(u128::MAX) * 4).unwrap();
+        assert!(ratio.almost_eq(
+            &PreciseNumber::new(3)
+                .unwrap()
+                .checked_div(&PreciseNumber::new(2).unwrap())
+                .unwrap(),
+            U256::from(1_000)
+        ));
+
+        assert!(super::precise_ratio(U256::from(1), U256::zero()).is_err());
+    }
+
+    fn precise(value: u128) -> PreciseNumber {
+        PreciseNumber {
+            value: U256::from(value),
+        }
+    }
+
+    #[test]
+    pub fn test_ln_ratio() {
+        assert_eq!(
+            super::ln_ratio(U256::from(7), U256::from(7)).unwrap(),
+            PreciseNumber::new(0).unwrap()
+        );
+        // ln(2) = 0.693147180559945...
+        assert!(super::ln_ratio(U256::from(2), U256::one())
+            .unwrap()
+            .almost_eq(&precise(693_147_180_560), U256::from(10)));
+        // ln(10) = 2.302585092994045...
+        assert!(super::ln_ratio(U256::from(10), U256::one())
+            .unwrap()
+            .almost_eq(&precise(2_302_585_092_994), U256::from(10)));
+        // ln(u64::MAX) = 44.361419555836499...
+        assert!(super::ln_ratio(U256::from(u64::MAX), U256::one())
+            .unwrap()
+            .almost_eq(&precise(44_361_419_555_836), U256::from(10)));
+
+        assert!(super::ln_ratio(U256::one(), U256::from(2)).is_err());
+        assert!(super::ln_ratio(U256::one(), U256::zero()).is_err());
+    }
+
+    #[test]
+    pub fn test_exp() {
+        assert_eq!(
+            super::exp(&PreciseNumber::new(0).unwrap()).unwrap(),
+            PreciseNumber::new(1).unwrap()
+        );
+        // e = 2.718281828459045...
+        assert!(super::exp(&PreciseNumber::new(1).unwrap())
+            .unwrap()
+            .almost_eq(&precise(2_718_281_828_459), U256::from(10)));
+        // e^10 = 22026.465794806716...
+        assert!(super::exp(&PreciseNumber::new(10).unwrap())
+            .unwrap()
+            .almost_eq(&precise(22_026_465_794_806_716), U256::from(1_000_000)));
+
+        assert!(super::exp(&PreciseNumber::new(1_000).unwrap()).is_err());
+    }
+
+    #[test]
+    pub fn test_pow_ratio() {
+        // (8 / 9)^4 = 4096 / 6561 = 0.624295076969974...
+        let floor = super::pow_ratio(8, 9, 8_000, 2_000, RoundDirection::Floor).unwrap();
+        let ceiling = super::pow_ratio(8, 9, 8_000, 2_000, RoundDirection::Ceiling).unwrap();
+        assert!(floor.less_than(&precise(624_295_076_970)));
+        assert!(ceiling.greater_than(&precise(624_295_076_969)));
+        assert!(floor.almost_eq(&ceiling, U256::from(1_000)));
+
+        // a zero base has a zero power, rounded up to the precision of a precise number
+        assert_eq!(
+            super::pow_ratio(0, 9, 1, 2, RoundDirection::Floor).unwrap(),
+            PreciseNumber::new(0).unwrap()
+        );
+        assert_eq!(
+            super::pow_ratio(0, 9, 1, 2, RoundDirection::Ceiling).unwrap(),
+            precise(1)
+        );
+
+        assert!(super::pow_ratio(u64::MAX as u128, 1, 50, 1, RoundDirection::Floor).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn pow_ratio_bounds_the_power(
+            base_numerator in 1..u64::MAX as u128,
+            base_denominator in 1..u64::MAX as u128,
+            exponent_numerator in 200..10_000_u64,
+            exponent_denominator in 200..10_000_u64,
+        ) {
+            let exponent = exponent_numerator as f64 / exponent_denominator as f64;
+            let expected = (base_numerator as f64 / base_denominator as f64).powf(exponent);
+            prop_assume!(expected < 1e20);
+
+            let to_f64 = |power: PreciseNumber| power.value.as_u128() as f64 / 1e12;
+            let floor = to_f64(super::pow_ratio(
+                base_numerator,
+                base_denominator,
+                exponent_numerator,
+                exponent_denominator,
+                RoundDirection::Floor,
+            ).unwrap());
+            let ceiling = to_f64(super::pow_ratio(
+                base_numerator,
+                base_denominator,
+                exponent_numerator,
+                exponent_denominator,
+                RoundDirection::Ceiling,
+            ).unwrap());
+
+            // allow for the precision of the expected power in floating point
+            prop_assert!(floor <= expected * (1.0 + 1e-14));
+            prop_assert!(ceiling >= expected * (1.0 - 1e-14));
+            // the bounds are no wider than the error allowed on the approximation
+            let error = expected * (exponent + 1.0) * 1e-10 + 1e-12;
+            prop_assert!(ceiling - floor <= 2.0 * error + 2e-12);
+        }
+    }
 }
@@ -2,9 +2,15 @@
 
 pub mod base;
 pub mod calculator;
+pub mod concentrated;
 pub mod constant_price;
 pub mod constant_product;
 pub mod fees;
+pub mod interest_bearing;
 pub mod math;
+pub mod multi_stable;
 pub mod offset;
+pub mod oracle_price;
+pub mod rate_provider;
 pub mod stable;
+pub mod weighted;
@@ -0,0 +1,818 @@
+#![allow(clippy::arithmetic_side_effects)]
+
+//! The stableswap invariant of a pool of 3 to `MAX_TOKENS` tokens, the N-dimensional
+//! generalization of the two token [`crate::curve::stable`] curve.
+use std::convert::TryFrom;
+
+use anchor_lang::{error, Result};
+use spl_math::{checked_ceil_div::CheckedCeilDiv, uint::U256};
+
+use crate::{
+    curve::{
+        base::SwapResult,
+        calculator::{RoundDirection, INITIAL_SWAP_POOL_AMOUNT},
+        fees::{calculate_fee, Fees},
+        stable::{scale_down, scale_up, MAX_AMP, MIN_AMP},
+    },
+    error::SwapError,
+    require_msg, try_math,
+    utils::math::{decimals_to_factor, TryCeilDiv, TryMath},
+};
+
+/// Minimum number of tokens in a multi-token pool, two token pools use the stable curve
+pub const MIN_TOKENS: usize = 3;
+
+/// Maximum number of tokens in a multi-token pool
+pub const MAX_TOKENS: usize = 8;
+
+const ITERATIONS: u16 = 256;
+
+/// Calculates An**n for deriving D
+///
+/// As for the two token curve, A * n is used rather than A * n**n
+fn compute_ann(amp: u64, n_tokens: usize) -> Result<u64> {
+    amp.try_mul(n_tokens as u64)
+}
+
+/// Compute the stable swap invariant (D) of any number of balances
+///
+/// Solves the invariant for D with Newton's method, as the two token curve:
+///
+/// ```md
+/// A * sum(x_i) * n**n + D = A * D * n**n + D**(n+1) / (n**n * prod(x_i))
+///
+/// D = (AnnS + D_P * n) * D / ((Ann - 1) * D + (n + 1) * D_P)
+/// ```
+///
+/// Where D_P = D**(n+1) / n**n * prod(x_i) is built up one balance at a time to stay within U256
+///
+/// * `ann` - A * n - The invariant of A - the amplification coefficient times n**(n-1)
+/// * `balances` - The scaled number of tokens of each type in the pool
+pub fn compute_d(ann: u64, balances: &[u128]) -> Result<u128> {
+    let sum_x = try_math!(balances.iter().try_fold(0_u128, |sum, x| sum.try_add(*x)))?; // sum(x_i), a.k.a S
+    if sum_x == 0 {
+        return Ok(0);
+    }
+    let n_coins = U256::from(balances.len());
+    let balances_times_coins = balances
+        .iter()
+        .map(|x| U256::from(*x).try_mul(n_coins))
+        .collect::<Result<Vec<U256>>>()?;
+
+    // An**n * sum(x)
+    let anns = try_math!(U256::from(ann).try_mul(sum_x.into()))?;
+    let ann_minus_one = U256::from(try_math!(ann.try_sub(1))?);
+
+    let mut d_previous: U256;
+    // start by guessing D with the sum(x_i)
+    let mut d: U256 = sum_x.into();
+
+    // Iteratively approximate D
+    for _ in 0..ITERATIONS {
+        // D_P = D**(n+1) / n**n * prod(x_i)
+        let mut d_product = d;
+        for x_times_coins in balances_times_coins.iter() {
+            d_product = try_math!(d_product.try_mul(d)?.try_div(*x_times_coins))?;
+        }
+        d_previous = d;
+        // D = (AnnS + D_P * n) * D / ((Ann - 1) * D + (n + 1) * D_P)
+        let numerator = try_math!(anns.try_add(d_product.try_mul(n_coins)?)?.try_mul(d))?;
+        let denominator = try_math!(d
+            .try_mul(ann_minus_one)?
+            .try_add(d_product.try_mul(n_coins.try_add(U256::one())?)?))?;
+        d = try_math!(numerator.try_div(denominator))?;
+
+        // Equality with the precision of 1
+        if d.abs_diff(d_previous) <= 1.into() {
+            break;
+        }
+    }
+    u128::try_from(d).map_err(|_| error!(SwapError::ConversionFailure))
+}
+
+/// Compute the balance of token `j` which keeps the invariant at D, given the balances of all
+/// other tokens
+///
+/// Solves the quadratic equation in y with Newton's method, as the two token curve:
+///
+/// ```md
+/// f(y) = y**2 + (b - D)y - c = 0
+///
+/// y = y**2 + c / 2y + b - D
+///
+/// Where:
+/// - b = S + D / Ann
+/// - c = D**n+1 / n**n * P * Ann
+/// - S = sum(x_i) where i != j
+/// - P = prod(x_i) where i != j
+/// ```
+///
+/// * `ann` - A * n - The invariant of A - the amplification coefficient times n**(n-1)
+/// * `balances` - The scaled balances after depositing the swap amount, the balance of `j` is ignored
+/// * `j` - The index of the token to solve for
+/// * `d` - D - The stable swap invariant to hold
+pub fn compute_y(ann: u64, balances: &[u128], j: usize, d: u128) -> Result<u128> {
+    // Upscale to U256
+    let n_coins = U256::from(balances.len());
+    let ann: U256 = ann.into();
+    let d: U256 = d.into();
+    let zero = U256::zero();
+    let one = U256::one();
+
+    // c = D**n+1 / n**n * P * Ann
+    // Rewrite this to avoid overflows from D**n+1:
+    // c = D * (D / x_0 * n) * ... * (D / Ann * n)
+    let mut sum_x = U256::zero();
+    let mut c = d;
+    for (i, x) in balances.iter().enumerate() {
+        if i == j {
+            continue;
+        }
+        sum_x = try_math!(sum_x.try_add((*x).into()))?;
+        c = try_math!(c.try_mul(d)?.try_div(U256::from(*x).try_mul(n_coins)?))?;
+    }
+    c = try_math!(c.try_mul(d)?.try_div(ann.try_mul(n_coins)?))?;
+
+    // b = S + D / Ann
+    let b = try_math!(sum_x.try_add(d.try_div(ann)?))?;
+
+    // Solve for y:
+    let mut y = d;
+    for _ in 0..ITERATIONS {
+        // y = y**2 + c / 2y + b - D
+        let numerator = try_math!(y.try_mul(y)?.try_add(c))?;
+        let denominator = try_math!(y.try_mul(2.into())?.try_add(b)?.try_sub(d))?;
+        // ceiling to 1 token rather than failing, see the two token curve
+        let (y_new, _) = numerator.checked_ceil_div(denominator).unwrap_or_else(|| {
+            if numerator == zero {
+                (zero, zero)
+            } else {
+                (one, zero)
+            }
+        });
+        if y_new == y {
+            break;
+        } else {
+            y = y_new;
+        }
+    }
+    u128::try_from(y).map_err(|_| error!(SwapError::CalculationFailure))
+}
+
+/// Stableswap curve of a pool of `MIN_TOKENS` to `MAX_TOKENS` tokens
+///
+/// Balances are scaled up to the largest number of decimals in the pool to solve the invariant
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultiStableCurve {
+    /// Amplifier constant
+    pub amp: u64,
+    /// Amount to multiply each token by to scale it to the largest number of decimals
+    pub factors: Vec<u64>,
+}
+
+impl MultiStableCurve {
+    pub fn new(amp: u64, decimals: &[u8]) -> Result<Self> {
+        let max_decimals = decimals.iter().copied().max().unwrap_or_default();
+        let factors = decimals
+            .iter()
+            .map(|decimals| decimals_to_factor(*decimals, max_decimals))
+            .collect::<Result<Vec<u64>>>()?;
+        Ok(Self { amp, factors })
+    }
+
+    pub fn n_tokens(&self) -> usize {
+        self.factors.len()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        require_msg!(
+            (MIN_TOKENS..=MAX_TOKENS).contains(&self.n_tokens()),
+            SwapError::InvalidTokenCount,
+            &format!(
+                "n_tokens={} is not within MIN_TOKENS={} and MAX_TOKENS={}",
+                self.n_tokens(),
+                MIN_TOKENS,
+                MAX_TOKENS
+            )
+        );
+        require_msg!(
+            self.amp > MIN_AMP,
+            SwapError::InvalidCurve,
+            &format!("amp={} <= MIN_AMP={}", self.amp, MIN_AMP)
+        );
+        require_msg!(
+            self.amp < MAX_AMP,
+            SwapError::InvalidCurve,
+            &format!("amp={} >= MAX_AMP={}", self.amp, MAX_AMP)
+        );
+        Ok(())
+    }
+
+    fn scale_balances(&self, balances: &[u128]) -> Result<Vec<u128>> {
+        require_msg!(
+            balances.len() == self.n_tokens(),
+            SwapError::InvalidTokenCount,
+            &format!(
+                "InvalidTokenCount: {} balances for a pool of {} tokens",
+                balances.len(),
+                self.n_tokens()
+            )
+        );
+        balances
+            .iter()
+            .zip(self.factors.iter())
+            .map(|(amount, factor)| scale_up(*amount, *factor))
+            .collect()
+    }
+
+    /// The stable swap invariant of the unscaled pool balances
+    pub fn compute_d(&self, balances: &[u128]) -> Result<u128> {
+        let ann = compute_ann(self.amp, self.n_tokens())?;
+        compute_d(ann, &self.scale_balances(balances)?)
+    }
+
+    /// Amount of token `j` received for `source_amount` of token `i`, rounded down
+    pub fn swap_without_fees(
+        &self,
+        i: usize,
+        j: usize,
+        source_amount: u128,
+        balances: &[u128],
+    ) -> Result<u128> {
+        require_msg!(
+            i != j && i < self.n_tokens() && j < self.n_tokens(),
+            SwapError::IncorrectTradingMint,
+            &format!(
+                "IncorrectTradingMint: cannot swap token {} for token {} in a pool of {} tokens",
+                i,
+                j,
+                self.n_tokens()
+            )
+        );
+        if source_amount == 0 {
+            return Ok(0);
+        }
+        let ann = compute_ann(self.amp, self.n_tokens())?;
+
+        let mut scaled_balances = self.scale_balances(balances)?;
+        let d = try_math!(compute_d(ann, &scaled_balances))?;
+        scaled_balances[i] =
+            try_math!(scaled_balances[i].try_add(scale_up(source_amount, self.factors[i])?))?;
+        let new_destination_amount = try_math!(compute_y(ann, &scaled_balances, j, d))?;
+
+        let new_destination_amount = try_math!(scale_down(
+            new_destination_amount,
+            self.factors[j],
+            true // round up to ensure the pool is favoured
+        ))?;
+        try_math!(balances[j].try_sub(new_destination_amount))
+    }
+
+    /// Swap `source_amount` of token `i` for token `j`, taking the trade and owner fees from the
+    /// source amount as `SwapCurve::swap`
+    pub fn swap(
+        &self,
+        i: usize,
+        j: usize,
+        source_amount: u128,
+        balances: &[u128],
+        fees: &Fees,
+    ) -> Result<SwapResult> {
+        // debit the fee to calculate the amount swapped
+        let trade_fee = try_math!(fees.trading_fee(source_amount))?;
+        let owner_fee = try_math!(fees.owner_trading_fee(source_amount))?;
+
+        let total_fees = try_math!(trade_fee.try_add(owner_fee))?;
+        let source_amount_swapped = try_math!(source_amount.try_sub(total_fees))?;
+
+        let destination_amount_swapped =
+            self.swap_without_fees(i, j, source_amount_swapped, balances)?;
+
+        let source_amount_to_vault = try_math!(source_amount_swapped.try_add(trade_fee))?;
+        let total_source_amount_swapped = try_math!(source_amount_swapped.try_add(total_fees))?;
+        Ok(SwapResult {
+            new_pool_source_amount: try_math!(balances[i].try_add(source_amount_to_vault))?,
+            new_pool_destination_amount: try_math!(balances[j].try_sub(destination_amount_swapped))?,
+            total_source_amount_swapped,
+            source_amount_swapped,
+            destination_amount_swapped,
+            source_amount_to_vault,
+            total_fees,
+            trade_fee,
+            owner_fee,
+        })
+    }
+
+    /// Fee on the imbalance of a deposit or withdrawal, the trade fee scaled by n / (4 * (n - 1))
+    /// as a swap of every token into the others
+    fn imbalance_fee(&self, amount: u128, fees: &Fees) -> Result<u128> {
+        let n_tokens = self.n_tokens() as u128;
+        calculate_fee(
+            amount,
+            try_math!(u128::from(fees.trade_fee_numerator).try_mul(n_tokens))?,
+            try_math!(u128::from(fees.trade_fee_denominator)
+                .try_mul(4)?
+                .try_mul(n_tokens.try_sub(1)?))?,
+            RoundDirection::Ceiling,
+        )
+    }
+
+    /// The invariant of `old_balances`, and of `new_balances` less the imbalance fees
+    ///
+    /// The imbalance of each token is the difference between its new balance and its balance
+    /// had the invariant changed by the same amount proportionally to the old balances. The
+    /// imbalance fees stay in the pool.
+    fn imbalanced_d(
+        &self,
+        old_balances: &[u128],
+        new_balances: &[u128],
+        fees: &Fees,
+    ) -> Result<(u128, u128)> {
+        let d0 = self.compute_d(old_balances)?;
+        let d1 = self.compute_d(new_balances)?;
+        let mut new_balances_less_fees = Vec::with_capacity(new_balances.len());
+        for (old_balance, new_balance) in old_balances.iter().zip(new_balances.iter()) {
+            let ideal_balance = try_math!(U256::from(d1)
+                .try_mul((*old_balance).into())?
+                .try_div(d0.into()))?;
+            let ideal_balance =
+                u128::try_from(ideal_balance).map_err(|_| error!(SwapError::ConversionFailure))?;
+            let fee = self.imbalance_fee(ideal_balance.abs_diff(*new_balance), fees)?;
+            new_balances_less_fees.push(try_math!(new_balance.try_sub(fee))?);
+        }
+        let d2 = self.compute_d(&new_balances_less_fees)?;
+        Ok((d0, d2))
+    }
+
+    /// Pool tokens minted for depositing `amounts` of each token, rounded down
+    ///
+    /// The first deposit into a pool without pool tokens mints `INITIAL_SWAP_POOL_AMOUNT`, and
+    /// must include every token.
+    pub fn deposit(
+        &self,
+        amounts: &[u128],
+        balances: &[u128],
+        pool_token_supply: u128,
+        fees: &Fees,
+    ) -> Result<u128> {
+        require_msg!(
+            amounts.len() == self.n_tokens(),
+            SwapError::InvalidTokenCount,
+            &format!(
+                "InvalidTokenCount: {} amounts for a pool of {} tokens",
+                amounts.len(),
+                self.n_tokens()
+            )
+        );
+        if pool_token_supply == 0 {
+            require_msg!(
+                amounts.iter().all(|amount| *amount > 0),
+                SwapError::ZeroTradingTokens,
+                "The first deposit must include every token"
+            );
+            return Ok(INITIAL_SWAP_POOL_AMOUNT);
+        }
+        let new_balances = balances
+            .iter()
+            .zip(amounts.iter())
+            .map(|(balance, amount)| balance.try_add(*amount))
+            .collect::<Result<Vec<u128>>>()?;
+
+        let (d0, d2) = self.imbalanced_d(balances, &new_balances, fees)?;
+        if d2 <= d0 {
+            return Ok(0);
+        }
+        // pool_token_supply * (D2 - D0) / D0
+        let pool_tokens = try_math!(U256::from(pool_token_supply)
+            .try_mul(d2.try_sub(d0)?.into())?
+            .try_div(d0.into()))?;
+        u128::try_from(pool_tokens).map_err(|_| error!(SwapError::ConversionFailure))
+    }
+
+    /// Amounts of each token received for burning `pool_token_amount`, rounded down
+    pub fn withdraw(
+        &self,
+        pool_token_amount: u128,
+        balances: &[u128],
+        pool_token_supply: u128,
+    ) -> Result<Vec<u128>> {
+        require_msg!(
+            pool_token_amount <= pool_token_supply,
+            SwapError::InsufficientPoolTokenFunds,
+            &format!(
+                "InsufficientPoolTokenFunds: pool_token_amount={} > pool_token_supply={}",
+                pool_token_amount, pool_token_supply
+            )
+        );
+        balances
+            .iter()
+            .map(|balance| {
+                try_math!(balance
+                    .try_mul(pool_token_amount)?
+                    .try_div(pool_token_supply))
+            })
+            .collect()
+    }
+
+    /// Pool tokens burnt for withdrawing `amounts` of each token, rounded up
+    pub fn withdraw_imbalanced(
+        &self,
+        amounts: &[u128],
+        balances: &[u128],
+        pool_token_supply: u128,
+        fees: &Fees,
+    ) -> Result<u128> {
+        require_msg!(
+            amounts.len() == self.n_tokens(),
+            SwapError::InvalidTokenCount,
+            &format!(
+                "InvalidTokenCount: {} amounts for a pool of {} tokens",
+                amounts.len(),
+                self.n_tokens()
+            )
+        );
+        let new_balances = balances
+            .iter()
+            .zip(amounts.iter())
+            .map(|(balance, amount)| balance.try_sub(*amount))
+            .collect::<Result<Vec<u128>>>()?;
+
+        let (d0, d2) = self.imbalanced_d(balances, &new_balances, fees)?;
+        // pool_token_supply * (D0 - D2) / D0
+        let (pool_tokens, _) = try_math!(U256::from(pool_token_supply)
+            .try_mul(d0.try_sub(d2)?.into())?
+            .try_ceil_div(d0.into()))?;
+        u128::try_from(pool_tokens).map_err(|_| error!(SwapError::ConversionFailure))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyperplane_sim::StableSwapModel;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// Trade fee numerator over a denominator of 1e10, the fee unit of the sim, divisible by
+    /// 4 * (n - 1) for every n so the imbalance fee rates of the sim are exact
+    const SIM_FEE_STEP: u64 = 1_680;
+    const SIM_FEE_DENOMINATOR: u64 = 10_000_000_000;
+
+    /// Balances and decimals of `MIN_TOKENS` to `MAX_TOKENS` tokens, within a range that keeps
+    /// D_P of the most imbalanced pool within U256
+    fn pool_tokens() -> impl Strategy<Value = Vec<(u128, u8)>> {
+        prop::collection::vec(
+            (1_000_000_000..100_000_000_000_u128, 6..=9_u8),
+            MIN_TOKENS..=MAX_TOKENS,
+        )
+    }
+
+    fn curve_and_model(
+        amp: u64,
+        pool_tokens: &[(u128, u8)],
+        pool_token_supply: u128,
+        trade_fee_numerator: u64,
+    ) -> (MultiStableCurve, StableSwapModel, Vec<u128>) {
+        let decimals: Vec<u8> = pool_tokens.iter().map(|(_, decimals)| *decimals).collect();
+        let balances: Vec<u128> = pool_tokens.iter().map(|(balance, _)| *balance).collect();
+        let curve = MultiStableCurve::new(amp, &decimals).unwrap();
+        let mut model = StableSwapModel::new_with_pool_tokens(
+            amp.into(),
+            balances.clone(),
+            curve
+                .factors
+                .iter()
+                .map(|factor| u128::from(*factor))
+                .collect(),
+            curve.n_tokens() as u8,
+            pool_token_supply,
+        );
+        model.fee = trade_fee_numerator.into();
+        (curve, model, balances)
+    }
+
+    fn sim_fees(trade_fee_numerator: u64) -> Fees {
+        Fees {
+            trade_fee_numerator,
+            trade_fee_denominator: SIM_FEE_DENOMINATOR,
+            ..Fees::default()
+        }
+    }
+
+    #[test]
+    fn balanced_pool_d_is_sum() {
+        for n_tokens in MIN_TOKENS..=MAX_TOKENS {
+            let ann = compute_ann(100, n_tokens).unwrap();
+            let balances = vec![1_000_000_u128; n_tokens];
+            assert_eq!(
+                compute_d(ann, &balances).unwrap(),
+                1_000_000 * n_tokens as u128
+            );
+        }
+    }
+
+    #[test]
+    fn empty_pool_d_is_zero() {
+        assert_eq!(compute_d(300, &[0, 0, 0]).unwrap(), 0);
+    }
+
+    #[test]
+    fn new_scales_to_max_decimals() {
+        let curve = MultiStableCurve::new(100, &[6, 9, 8]).unwrap();
+        assert_eq!(curve.factors, vec![1_000, 1, 10]);
+    }
+
+    #[test]
+    fn validate_token_count_and_amp() {
+        assert!(MultiStableCurve::new(100, &[6, 6, 6])
+            .unwrap()
+            .validate()
+            .is_ok());
+        assert_eq!(
+            MultiStableCurve::new(100, &[6, 6]).unwrap().validate(),
+            Err(SwapError::InvalidTokenCount.into())
+        );
+        assert_eq!(
+            MultiStableCurve::new(100, &[6; MAX_TOKENS + 1])
+                .unwrap()
+                .validate(),
+            Err(SwapError::InvalidTokenCount.into())
+        );
+        assert_eq!(
+            MultiStableCurve::new(MIN_AMP, &[6, 6, 6])
+                .unwrap()
+                .validate(),
+            Err(SwapError::InvalidCurve.into())
+        );
+        assert_eq!(
+            MultiStableCurve::new(MAX_AMP, &[6, 6, 6])
+                .unwrap()
+                .validate(),
+            Err(SwapError::InvalidCurve.into())
+        );
+    }
+
+    #[test]
+    fn swap_balanced_pool() {
+        let curve = MultiStableCurve::new(100, &[6, 6, 6]).unwrap();
+        let balances = [1_000_000_000, 1_000_000_000, 1_000_000_000];
+        // close to 1:1 with a high amp
+        assert_eq!(
+            curve.swap_without_fees(0, 2, 1_000, &balances).unwrap(),
+            999
+        );
+        assert_eq!(curve.swap_without_fees(0, 2, 0, &balances).unwrap(), 0);
+        assert_eq!(
+            curve.swap_without_fees(1, 1, 1_000, &balances),
+            Err(SwapError::IncorrectTradingMint.into())
+        );
+        assert_eq!(
+            curve.swap_without_fees(0, 3, 1_000, &balances),
+            Err(SwapError::IncorrectTradingMint.into())
+        );
+    }
+
+    #[test]
+    fn swap_fees() {
+        let curve = MultiStableCurve::new(100, &[6, 6, 6]).unwrap();
+        let balances = [1_000_000_000, 1_000_000_000, 1_000_000_000];
+        let fees = Fees {
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            owner_trade_fee_numerator: 1,
+            owner_trade_fee_denominator: 200,
+            ..Fees::default()
+        };
+        let result = curve.swap(1, 0, 10_000, &balances, &fees).unwrap();
+        assert_eq!(result.trade_fee, 100);
+        assert_eq!(result.owner_fee, 50);
+        assert_eq!(result.source_amount_swapped, 9_850);
+        assert_eq!(result.source_amount_to_vault, 9_950);
+        assert_eq!(
+            result.destination_amount_swapped,
+            curve.swap_without_fees(1, 0, 9_850, &balances).unwrap()
+        );
+        assert_eq!(result.new_pool_source_amount, 1_000_009_950);
+    }
+
+    #[test]
+    fn proportional_deposit_and_withdraw() {
+        let curve = MultiStableCurve::new(100, &[6, 6, 6]).unwrap();
+        let balances = [1_000_000, 1_000_000, 1_000_000];
+        let fees = sim_fees(SIM_FEE_STEP * 1_000);
+        // a proportional deposit has no imbalance fee
+        assert_eq!(
+            curve
+                .deposit(&[100_000, 100_000, 100_000], &balances, 1_000, &fees)
+                .unwrap(),
+            100
+        );
+        // an imbalanced deposit of the same value pays the fee
+        assert_eq!(
+            curve
+                .deposit(&[300_000, 0, 0], &balances, 1_000, &fees)
+                .unwrap(),
+            99
+        );
+        assert_eq!(
+            curve.withdraw(100, &balances, 1_000).unwrap(),
+            vec![100_000, 100_000, 100_000]
+        );
+        assert_eq!(
+            curve.withdraw(1_001, &balances, 1_000),
+            Err(SwapError::InsufficientPoolTokenFunds.into())
+        );
+    }
+
+    #[test]
+    fn first_deposit() {
+        let curve = MultiStableCurve::new(100, &[6, 6, 6]).unwrap();
+        let fees = Fees::default();
+        assert_eq!(
+            curve.deposit(&[1, 2, 3], &[0, 0, 0], 0, &fees).unwrap(),
+            INITIAL_SWAP_POOL_AMOUNT
+        );
+        assert_eq!(
+            curve.deposit(&[1, 0, 3], &[0, 0, 0], 0, &fees),
+            Err(SwapError::ZeroTradingTokens.into())
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn compare_sim_d(
+            pool_tokens in pool_tokens(),
+            amp in MIN_AMP..MAX_AMP,
+        ) {
+            let (curve, model, balances) = curve_and_model(amp, &pool_tokens, 0, 0);
+
+            let result = curve.compute_d(&balances).unwrap();
+            let sim_result = model.sim_d();
+
+            // D is only calculated to a precision of 1
+            assert!(
+                result.abs_diff(sim_result) <= 1,
+                "result={}, sim_result={}, amp={}, pool_tokens={:?}",
+                result,
+                sim_result,
+                amp,
+                pool_tokens,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn compare_sim_swap_no_fee(
+            pool_tokens in pool_tokens(),
+            source_amount in 100..1_000_000_000_u128,
+            amp in MIN_AMP..MAX_AMP,
+            i in 0..MAX_TOKENS,
+            j in 0..MAX_TOKENS,
+        ) {
+            let n_tokens = pool_tokens.len();
+            let (i, j) = (i % n_tokens, j % n_tokens);
+            prop_assume!(i != j);
+            let (curve, mut model, balances) = curve_and_model(amp, &pool_tokens, 0, 0);
+
+            let result = curve.swap_without_fees(i, j, source_amount, &balances).unwrap();
+            let sim_result = model.sim_exchange(i as u128, j as u128, source_amount);
+
+            let diff = sim_result.abs_diff(result);
+
+            // tolerate a difference of 2 because of the ceiling during calculation
+            let tolerance = std::cmp::max(2, sim_result / 1_000_000_000);
+
+            assert!(
+                diff <= tolerance,
+                "result={}, sim_result={}, diff={}, amp={}, source_amount={}, i={}, j={}, pool_tokens={:?}",
+                result,
+                sim_result,
+                diff,
+                amp,
+                source_amount,
+                i,
+                j,
+                pool_tokens,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn compare_sim_deposit(
+            pool_tokens in pool_tokens(),
+            amounts in prop::collection::vec(0..1_000_000_000_u128, MAX_TOKENS),
+            pool_token_supply in 1_000_000..1_000_000_000_000_000_u128,
+            amp in MIN_AMP..MAX_AMP,
+            trade_fee_steps in 0..1_000_u64,
+        ) {
+            let trade_fee_numerator = trade_fee_steps * SIM_FEE_STEP;
+            let (curve, mut model, balances) =
+                curve_and_model(amp, &pool_tokens, pool_token_supply, trade_fee_numerator);
+            let amounts = &amounts[..curve.n_tokens()];
+
+            let result = curve
+                .deposit(amounts, &balances, pool_token_supply, &sim_fees(trade_fee_numerator))
+                .unwrap();
+            let sim_result = model.sim_add_liquidity(amounts.to_vec());
+
+            let diff = sim_result.abs_diff(result);
+
+            // D is only calculated to a precision of 1, and each imbalance fee rounds to within
+            // one token, which is up to its factor in D
+            let d0 = curve.compute_d(&balances).unwrap();
+            let max_d_diff = 2 + curve.factors.iter().map(|factor| u128::from(*factor)).sum::<u128>();
+            let tolerance = 2 * pool_token_supply * max_d_diff / d0 + 2;
+
+            assert!(
+                diff <= tolerance,
+                "result={}, sim_result={}, diff={}, amp={}, amounts={:?}, pool_token_supply={}, trade_fee_numerator={}, pool_tokens={:?}",
+                result,
+                sim_result,
+                diff,
+                amp,
+                amounts,
+                pool_token_supply,
+                trade_fee_numerator,
+                pool_tokens,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn compare_sim_withdraw_imbalanced(
+            pool_tokens in pool_tokens(),
+            amounts in prop::collection::vec(0..500_000_000_u128, MAX_TOKENS),
+            pool_token_supply in 1_000_000..1_000_000_000_000_000_u128,
+            amp in MIN_AMP..MAX_AMP,
+            trade_fee_steps in 0..1_000_u64,
+        ) {
+            let trade_fee_numerator = trade_fee_steps * SIM_FEE_STEP;
+            let (curve, mut model, balances) =
+                curve_and_model(amp, &pool_tokens, pool_token_supply, trade_fee_numerator);
+            let amounts = &amounts[..curve.n_tokens()];
+
+            let result = curve
+                .withdraw_imbalanced(amounts, &balances, pool_token_supply, &sim_fees(trade_fee_numerator))
+                .unwrap();
+            let sim_result = model.sim_remove_liquidity_imbalance(amounts.to_vec());
+
+            let diff = sim_result.abs_diff(result);
+
+            // as the deposit, with the pool tokens burnt rounded up rather than down
+            let d0 = curve.compute_d(&balances).unwrap();
+            let max_d_diff = 2 + curve.factors.iter().map(|factor| u128::from(*factor)).sum::<u128>();
+            let tolerance = 2 * pool_token_supply * max_d_diff / d0 + 3;
+
+            assert!(
+                diff <= tolerance,
+                "result={}, sim_result={}, diff={}, amp={}, amounts={:?}, pool_token_supply={}, trade_fee_numerator={}, pool_tokens={:?}",
+                result,
+                sim_result,
+                diff,
+                amp,
+                amounts,
+                pool_token_supply,
+                trade_fee_numerator,
+                pool_tokens,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn deposit_then_withdraw_imbalanced_does_not_profit(
+            pool_tokens in pool_tokens(),
+            amounts in prop::collection::vec(0..1_000_000_000_u128, MAX_TOKENS),
+            pool_token_supply in 1_000_000..1_000_000_000_000_000_u128,
+            amp in MIN_AMP..MAX_AMP,
+            trade_fee_steps in 0..1_000_u64,
+        ) {
+            let fees = sim_fees(trade_fee_steps * SIM_FEE_STEP);
+            let (curve, _, balances) = curve_and_model(amp, &pool_tokens, pool_token_supply, 0);
+            let amounts = &amounts[..curve.n_tokens()];
+
+            let minted = curve.deposit(amounts, &balances, pool_token_supply, &fees).unwrap();
+            let new_balances: Vec<u128> = balances
+                .iter()
+                .zip(amounts.iter())
+                .map(|(balance, amount)| balance + amount)
+                .collect();
+            let burnt = curve
+                .withdraw_imbalanced(amounts, &new_balances, pool_token_supply + minted, &fees)
+                .unwrap();
+
+            assert!(
+                burnt >= minted,
+                "burnt={}, minted={}, amp={}, amounts={:?}, pool_tokens={:?}",
+                burnt,
+                minted,
+                amp,
+                amounts,
+                pool_tokens,
+            );
+        }
+    }
+}
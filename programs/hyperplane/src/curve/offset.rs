@@ -11,7 +11,10 @@ use crate::{
             CurveCalculator, DynAccountSerialize, RoundDirection, SwapWithoutFeesResult,
             TradeDirection, TradingTokenResult,
         },
-        constant_product::{normalized_value, swap},
+        constant_product::{
+            normalized_value, spot_price, swap, swap_exact_out, trading_tokens_to_pool_tokens,
+            withdraw_one_token, withdraw_single_token_type_exact_out,
+        },
         math,
     },
     error::SwapError,
@@ -49,6 +52,54 @@ impl CurveCalculator for OffsetCurve {
         swap(source_amount, pool_source_amount, pool_destination_amount)
     }
 
+    /// Inverse of the offset swap, the offset is only applied to the curve
+    /// calculation so the destination amount must still be available in the pool
+    fn swap_without_fees_exact_out(
+        &self,
+        destination_amount: u128,
+        pool_source_amount: u128,
+        pool_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<SwapWithoutFeesResult> {
+        require_msg!(
+            destination_amount < pool_destination_amount,
+            SwapError::ZeroTradingTokens,
+            &format!(
+                "Destination amount {} must be less than the pool destination amount {}",
+                destination_amount, pool_destination_amount
+            )
+        );
+        let token_b_offset = self.token_b_offset as u128;
+        let pool_source_amount = match trade_direction {
+            TradeDirection::AtoB => pool_source_amount,
+            TradeDirection::BtoA => try_math!(pool_source_amount.try_add(token_b_offset))?,
+        };
+        let pool_destination_amount = match trade_direction {
+            TradeDirection::AtoB => try_math!(pool_destination_amount.try_add(token_b_offset))?,
+            TradeDirection::BtoA => pool_destination_amount,
+        };
+        swap_exact_out(
+            destination_amount,
+            pool_source_amount,
+            pool_destination_amount,
+        )
+    }
+
+    /// The constant product spot price with the offset added to token B
+    fn spot_price(
+        &self,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<PreciseNumber> {
+        let pool_token_b_amount =
+            try_math!(pool_token_b_amount.try_add(self.token_b_offset as u128))?;
+        match trade_direction {
+            TradeDirection::AtoB => spot_price(pool_token_a_amount, pool_token_b_amount),
+            TradeDirection::BtoA => spot_price(pool_token_b_amount, pool_token_a_amount),
+        }
+    }
+
     /// The conversion for the offset curve needs to take into account the
     /// offset
     fn pool_tokens_to_trading_tokens(
@@ -69,6 +120,74 @@ impl CurveCalculator for OffsetCurve {
         )
     }
 
+    /// The conversion for the offset curve needs to take into account the
+    /// offset on the token B side
+    fn trading_tokens_to_pool_tokens(
+        &self,
+        source_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let token_b_offset = self.token_b_offset as u128;
+        let swap_source_amount = match trade_direction {
+            TradeDirection::AtoB => pool_token_a_amount,
+            TradeDirection::BtoA => try_math!(pool_token_b_amount.try_add(token_b_offset))?,
+        };
+        trading_tokens_to_pool_tokens(
+            source_amount,
+            swap_source_amount,
+            pool_token_supply,
+            round_direction,
+        )
+    }
+
+    fn withdraw_single_token_type_exact_out(
+        &self,
+        destination_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let token_b_offset = self.token_b_offset as u128;
+        let swap_destination_amount = match trade_direction {
+            TradeDirection::AtoB => pool_token_a_amount,
+            TradeDirection::BtoA => try_math!(pool_token_b_amount.try_add(token_b_offset))?,
+        };
+        withdraw_single_token_type_exact_out(
+            destination_amount,
+            swap_destination_amount,
+            pool_token_supply,
+            round_direction,
+        )
+    }
+
+    fn withdraw_one_token(
+        &self,
+        pool_tokens: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let token_b_offset = self.token_b_offset as u128;
+        let swap_destination_amount = match trade_direction {
+            TradeDirection::AtoB => pool_token_a_amount,
+            TradeDirection::BtoA => try_math!(pool_token_b_amount.try_add(token_b_offset))?,
+        };
+        withdraw_one_token(
+            pool_tokens,
+            swap_destination_amount,
+            pool_token_supply,
+            round_direction,
+        )
+    }
+
     fn validate(&self) -> Result<()> {
         require_msg!(
             self.token_b_offset > 0,
@@ -129,8 +248,11 @@ mod tests {
     use super::*;
     use crate::{
         curve::calculator::test::{
-            check_curve_value_from_swap, check_pool_value_from_deposit,
-            check_pool_value_from_withdraw, total_and_intermediate,
+            check_curve_value_from_swap, check_curve_value_from_swap_exact_out,
+            check_pool_value_from_deposit, check_pool_value_from_deposit_single_token_type,
+            check_pool_value_from_withdraw, check_pool_value_from_withdraw_one_token,
+            check_pool_value_from_withdraw_single_token_type_exact_out,
+            check_spot_price_from_small_swap, total_and_intermediate,
         },
         state::Curve,
     };
@@ -254,6 +376,37 @@ mod tests {
         assert_eq!(result.destination_amount_swapped, 499);
     }
 
+    #[test]
+    fn swap_exact_out_offset() {
+        let swap_source_amount: u128 = 1_000_000;
+        let swap_destination_amount: u128 = 1_000;
+        let destination_amount: u128 = 99;
+        let token_b_offset = 1_000_000;
+        let curve = OffsetCurve {
+            token_b_offset,
+            ..Default::default()
+        };
+        let result = curve
+            .swap_without_fees_exact_out(
+                destination_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                TradeDirection::AtoB,
+            )
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 99);
+        assert_eq!(result.destination_amount_swapped, destination_amount);
+
+        // the offset cannot be withdrawn from the pool
+        let bad_result = curve.swap_without_fees_exact_out(
+            swap_destination_amount,
+            swap_source_amount,
+            swap_destination_amount,
+            TradeDirection::AtoB,
+        );
+        assert!(bad_result.is_err());
+    }
+
     prop_compose! {
         pub fn values_sum_within_u64()(total in 1..u64::MAX)
                         (amount in 1..total, total in Just(total))
@@ -327,6 +480,93 @@ mod tests {
         }
     }
 
+    proptest! {
+        #[test]
+        fn spot_price_matches_small_swap(
+            swap_token_a_amount in 10_000_000_000..1_000_000_000_000_000_u64,
+            swap_token_b_amount in 10_000_000_000..1_000_000_000_000_000_u64,
+            token_b_offset in 1..100_000_000_000_000_u64,
+        ) {
+            let curve = OffsetCurve { token_b_offset, ..Default::default() };
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            check_spot_price_from_small_swap(
+                &curve,
+                swap_token_a_amount / 100_000,
+                swap_token_a_amount,
+                swap_token_b_amount,
+                TradeDirection::AtoB,
+                1,
+            );
+            check_spot_price_from_small_swap(
+                &curve,
+                swap_token_b_amount / 100_000,
+                swap_token_b_amount,
+                swap_token_a_amount,
+                TradeDirection::BtoA,
+                1,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap_exact_out_a_to_b(
+            destination_token_amount in 1..u64::MAX,
+            swap_source_amount in 1..u64::MAX,
+            swap_destination_amount in 1..u64::MAX,
+            token_b_offset in 1..u64::MAX,
+        ) {
+            let curve = OffsetCurve { token_b_offset, ..Default::default() };
+
+            let destination_token_amount = destination_token_amount as u128;
+            let swap_source_amount = swap_source_amount as u128;
+            let swap_destination_amount = swap_destination_amount as u128;
+            let token_b_offset = token_b_offset as u128;
+
+            // The invariant needs to fit in a u128
+            // invariant = swap_source_amount * (swap_destination_amount + token_b_offset)
+            prop_assume!(!(swap_destination_amount + token_b_offset).overflowing_mul(swap_source_amount).1);
+            prop_assume!(destination_token_amount < swap_destination_amount);
+            check_curve_value_from_swap_exact_out(
+                &curve,
+                destination_token_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                TradeDirection::AtoB
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap_exact_out_b_to_a(
+            destination_token_amount in 1..u64::MAX,
+            swap_source_amount in 1..u64::MAX,
+            swap_destination_amount in 1..u64::MAX,
+            token_b_offset in 1..u64::MAX,
+        ) {
+            let curve = OffsetCurve { token_b_offset, ..Default::default() };
+
+            let destination_token_amount = destination_token_amount as u128;
+            let swap_source_amount = swap_source_amount as u128;
+            let swap_destination_amount = swap_destination_amount as u128;
+            let token_b_offset = token_b_offset as u128;
+
+            // The invariant needs to fit in a u128
+            // invariant = swap_destination_amount * (swap_source_amount + token_b_offset)
+            prop_assume!(!(swap_source_amount + token_b_offset).overflowing_mul(swap_destination_amount).1);
+            prop_assume!(destination_token_amount < swap_destination_amount);
+            check_curve_value_from_swap_exact_out(
+                &curve,
+                destination_token_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                TradeDirection::BtoA
+            );
+        }
+    }
+
     proptest! {
         #[test]
         fn curve_value_does_not_decrease_from_deposit(
@@ -396,4 +636,98 @@ mod tests {
             );
         }
     }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_deposit_single_token_type(
+            source_token_amount in 1..u64::MAX,
+            pool_token_supply in 1..u64::MAX,
+            swap_token_a_amount in 1..u64::MAX,
+            (swap_token_b_amount, token_b_offset) in values_sum_within_u64(),
+        ) {
+            let curve = OffsetCurve { token_b_offset, ..Default::default() };
+            let source_token_amount = source_token_amount as u128;
+            let swap_token_b_with_offset = swap_token_b_amount as u128 + token_b_offset as u128;
+
+            // The new invariant needs to fit in a u128 to calculate the value
+            prop_assume!((source_token_amount + swap_token_a_amount as u128).checked_mul(swap_token_b_with_offset).is_some());
+            prop_assume!((source_token_amount + swap_token_b_with_offset).checked_mul(swap_token_a_amount as u128).is_some());
+            for trade_direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+                check_pool_value_from_deposit_single_token_type(
+                    &curve,
+                    source_token_amount,
+                    pool_token_supply as u128,
+                    swap_token_a_amount as u128,
+                    swap_token_b_amount as u128,
+                    trade_direction,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw_single_token_type_exact_out(
+            destination_token_amount in 1..u64::MAX,
+            pool_token_supply in 1..u64::MAX,
+            swap_token_a_amount in 1..u64::MAX,
+            (swap_token_b_amount, token_b_offset) in values_sum_within_u64(),
+        ) {
+            let curve = OffsetCurve { token_b_offset, ..Default::default() };
+            for (trade_direction, swap_destination_amount) in [
+                (TradeDirection::AtoB, swap_token_a_amount),
+                (TradeDirection::BtoA, swap_token_b_amount),
+            ] {
+                if destination_token_amount >= swap_destination_amount {
+                    continue;
+                }
+                check_pool_value_from_withdraw_single_token_type_exact_out(
+                    &curve,
+                    destination_token_amount as u128,
+                    pool_token_supply as u128,
+                    swap_token_a_amount as u128,
+                    swap_token_b_amount as u128,
+                    trade_direction,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw_one_token(
+            (pool_token_supply, pool_token_amount) in total_and_intermediate(u64::MAX),
+            swap_token_a_amount in 1..u64::MAX,
+            (swap_token_b_amount, token_b_offset) in values_sum_within_u64(),
+        ) {
+            let curve = OffsetCurve { token_b_offset, ..Default::default() };
+            for (trade_direction, swap_destination_amount) in [
+                (TradeDirection::AtoB, swap_token_a_amount),
+                (TradeDirection::BtoA, swap_token_b_amount),
+            ] {
+                // the offset may allow withdrawing more than the real pool amount
+                let destination_token_amount = curve
+                    .withdraw_one_token(
+                        pool_token_amount as u128,
+                        swap_token_a_amount as u128,
+                        swap_token_b_amount as u128,
+                        pool_token_supply as u128,
+                        trade_direction,
+                        RoundDirection::Floor,
+                    )
+                    .unwrap();
+                if destination_token_amount >= swap_destination_amount as u128 {
+                    continue;
+                }
+                check_pool_value_from_withdraw_one_token(
+                    &curve,
+                    pool_token_amount as u128,
+                    pool_token_supply as u128,
+                    swap_token_a_amount as u128,
+                    swap_token_b_amount as u128,
+                    trade_direction,
+                );
+            }
+        }
+    }
 }
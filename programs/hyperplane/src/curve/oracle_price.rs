@@ -0,0 +1,893 @@
+#![allow(clippy::arithmetic_side_effects)]
+
+//! Constant price swap curve, with the token B price read from a price feed account
+//! and kept as a Q64.64 number, so prices below 1 token A per token B are supported
+
+use anchor_lang::{
+    error,
+    prelude::{msg, AccountInfo, Clock, Pubkey, SolanaSysvar},
+    require, Key, Result,
+};
+use spl_math::{precise_number::PreciseNumber, uint::U256};
+
+use crate::{
+    curve::{
+        calculator::{
+            CurveCalculator, DynAccountSerialize, RoundDirection, SwapWithoutFeesResult,
+            TradeDirection, TradingTokenResult,
+        },
+        math,
+    },
+    error::SwapError,
+    require_msg,
+    state::OraclePriceCurve,
+    try_math,
+    utils::math::TryMath,
+};
+
+/// Confidence intervals are configured in basis points of the price
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Prices published ahead of the on-chain clock by more than this are rejected, rather than
+/// treated as fresh, allowing for a small skew between the price feed and the validators
+pub const MAX_PRICE_CLOCK_SKEW_SEC: i64 = 5;
+
+/// Number of fractional bits of the Q64.64 token B price
+const Q64_FRACTIONAL_BITS: usize = 64;
+
+/// Price read from a price feed account.
+///
+/// Price feeds use a generic Pyth-style layout, little-endian from the start of
+/// the account data:
+///
+/// | offset | field          | type  |
+/// |--------|----------------|-------|
+/// | 0      | `price`        | `i64` |
+/// | 8      | `conf`         | `u64` |
+/// | 16     | `expo`         | `i32` |
+/// | 20     | `publish_time` | `i64` |
+///
+/// The price is the amount of token A (in base units) per token B (in base
+/// units), scaled by `10^expo`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PriceFeed {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+impl PriceFeed {
+    pub const LEN: usize = 28;
+
+    pub fn try_from_bytes(data: &[u8]) -> Result<Self> {
+        require_msg!(
+            data.len() >= Self::LEN,
+            SwapError::InvalidPriceFeed,
+            &format!(
+                "InvalidPriceFeed: price feed data length {} < {}",
+                data.len(),
+                Self::LEN
+            )
+        );
+        Ok(Self {
+            price: i64::from_le_bytes(data[0..8].try_into().unwrap()),
+            conf: u64::from_le_bytes(data[8..16].try_into().unwrap()),
+            expo: i32::from_le_bytes(data[16..20].try_into().unwrap()),
+            publish_time: i64::from_le_bytes(data[20..28].try_into().unwrap()),
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut data = [0; Self::LEN];
+        data[0..8].copy_from_slice(&self.price.to_le_bytes());
+        data[8..16].copy_from_slice(&self.conf.to_le_bytes());
+        data[16..20].copy_from_slice(&self.expo.to_le_bytes());
+        data[20..28].copy_from_slice(&self.publish_time.to_le_bytes());
+        data
+    }
+
+    /// The price as a Q64.64 amount of token A per token B, after checking that
+    /// the price is fresh, not published in the future, and its confidence interval is narrow enough
+    pub fn token_b_price_x64(
+        &self,
+        now: i64,
+        max_price_age_sec: u64,
+        max_price_confidence_bps: u64,
+    ) -> Result<u128> {
+        require_msg!(
+            self.publish_time <= now.saturating_add(MAX_PRICE_CLOCK_SKEW_SEC),
+            SwapError::InvalidPriceFeed,
+            &format!(
+                "InvalidPriceFeed: price published at {} is ahead of the clock at {}",
+                self.publish_time, now
+            )
+        );
+        let price_age_sec = u64::try_from(now.saturating_sub(self.publish_time)).unwrap_or(0);
+        require_msg!(
+            price_age_sec <= max_price_age_sec,
+            SwapError::StalePrice,
+            &format!(
+                "StalePrice: price published at {} is {}s old, max_price_age_sec={}",
+                self.publish_time, price_age_sec, max_price_age_sec
+            )
+        );
+
+        let price = u128::try_from(self.price).unwrap_or(0);
+        require_msg!(
+            price > 0,
+            SwapError::InvalidPriceFeed,
+            &format!("InvalidPriceFeed: price={} must be positive", self.price)
+        );
+        require_msg!(
+            try_math!(u128::from(self.conf).try_mul(BPS_DENOMINATOR))?
+                <= try_math!(price.try_mul(u128::from(max_price_confidence_bps)))?,
+            SwapError::PriceConfidenceTooWide,
+            &format!(
+                "PriceConfidenceTooWide: conf={} is more than {}bps of price={}",
+                self.conf, max_price_confidence_bps, self.price
+            )
+        );
+
+        let scale = 10u128
+            .checked_pow(self.expo.unsigned_abs())
+            .ok_or_else(|| error!(SwapError::CalculationFailure))?;
+        let price_x64 = U256::from(price) << Q64_FRACTIONAL_BITS;
+        let token_b_price_x64 = if self.expo >= 0 {
+            try_math!(price_x64.try_mul(U256::from(scale)))?
+        } else {
+            try_math!(price_x64.try_div(U256::from(scale)))?
+        };
+        let token_b_price_x64 =
+            u128::try_from(token_b_price_x64).map_err(|_| error!(SwapError::ConversionFailure))?;
+        require_msg!(
+            token_b_price_x64 > 0,
+            SwapError::InvalidPriceFeed,
+            &format!(
+                "InvalidPriceFeed: price={} with expo={} rounds to 0 token A per token B",
+                self.price, self.expo
+            )
+        );
+        Ok(token_b_price_x64)
+    }
+}
+
+impl OraclePriceCurve {
+    pub fn new(
+        price_feed: Pubkey,
+        price_feed_program: Pubkey,
+        max_price_age_sec: u64,
+        max_price_confidence_bps: u64,
+    ) -> Self {
+        Self {
+            price_feed,
+            price_feed_program,
+            max_price_age_sec,
+            max_price_confidence_bps,
+            token_b_price_x64: 0,
+            _padding: [0; 4],
+        }
+    }
+
+    /// Read the token B price from the configured price feed, which must be one of `accounts`
+    pub fn load_price(&mut self, accounts: &[AccountInfo]) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        self.load_price_at(accounts, now)
+    }
+
+    /// Read the token B price from the configured price feed as of `now`
+    pub fn load_price_at(&mut self, accounts: &[AccountInfo], now: i64) -> Result<()> {
+        let price_feed = accounts
+            .iter()
+            .find(|account| account.key() == self.price_feed)
+            .ok_or_else(|| {
+                msg!(
+                    "InvalidPriceFeed: price feed {} was not provided",
+                    self.price_feed
+                );
+                error!(SwapError::InvalidPriceFeed)
+            })?;
+        require_msg!(
+            *price_feed.owner == self.price_feed_program,
+            SwapError::InvalidPriceFeed,
+            &format!(
+                "InvalidPriceFeed: price feed owner {} != price_feed_program {}",
+                price_feed.owner, self.price_feed_program
+            )
+        );
+
+        let price = PriceFeed::try_from_bytes(&price_feed.try_borrow_data()?)?;
+        self.token_b_price_x64 =
+            price.token_b_price_x64(now, self.max_price_age_sec, self.max_price_confidence_bps)?;
+        Ok(())
+    }
+
+    /// Value of the token A and token B amounts in token A, as a Q64.64 number
+    fn value_x64(&self, token_a_amount: u128, token_b_amount: u128) -> Result<U256> {
+        try_math!((U256::from(token_a_amount) << Q64_FRACTIONAL_BITS)
+            .try_add(self.token_b_value_x64(token_b_amount)?))
+    }
+
+    /// Value of the token B amount in token A, as a Q64.64 number
+    fn token_b_value_x64(&self, token_b_amount: u128) -> Result<U256> {
+        try_math!(U256::from(token_b_amount).try_mul(U256::from(self.token_b_price_x64)))
+    }
+
+    /// Amount of the destination token worth `source_amount` of the source token,
+    /// rounded in the given direction
+    fn convert(
+        &self,
+        source_amount: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let (numerator, denominator) = match trade_direction {
+            TradeDirection::AtoB => (
+                U256::from(source_amount) << Q64_FRACTIONAL_BITS,
+                U256::from(self.token_b_price_x64),
+            ),
+            TradeDirection::BtoA => (
+                self.token_b_value_x64(source_amount)?,
+                U256::one() << Q64_FRACTIONAL_BITS,
+            ),
+        };
+        to_u128(div(numerator, denominator, round_direction)?)
+    }
+
+    /// Value of a token A amount, or of a token B amount, as a Q64.64 amount of token A
+    fn given_value_x64(&self, amount: u128, trade_direction: TradeDirection) -> Result<U256> {
+        match trade_direction {
+            TradeDirection::AtoB => Ok(U256::from(amount) << Q64_FRACTIONAL_BITS),
+            TradeDirection::BtoA => self.token_b_value_x64(amount),
+        }
+    }
+
+    /// Pool tokens worth the given amount of token A or B, shared by deposits and withdrawals
+    fn value_to_pool_tokens(
+        &self,
+        amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let given_value = self.given_value_x64(amount, trade_direction)?;
+        let total_value = self.value_x64(pool_token_a_amount, pool_token_b_amount)?;
+        to_u128(div(
+            try_math!(U256::from(pool_token_supply).try_mul(given_value))?,
+            total_value,
+            round_direction,
+        )?)
+    }
+}
+
+/// Divide, rounding in the given direction
+fn div(numerator: U256, denominator: U256, round_direction: RoundDirection) -> Result<U256> {
+    let quotient = try_math!(numerator.try_div(denominator))?;
+    match round_direction {
+        RoundDirection::Ceiling if try_math!(quotient.try_mul(denominator))? < numerator => {
+            try_math!(quotient.try_add(U256::one()))
+        }
+        _ => Ok(quotient),
+    }
+}
+
+fn to_u128(value: U256) -> Result<u128> {
+    u128::try_from(value).map_err(|_| error!(SwapError::ConversionFailure))
+}
+
+/// OraclePriceCurve struct implementing CurveCalculator, priced at the loaded
+/// price feed price kept as a Q64.64 number
+impl CurveCalculator for OraclePriceCurve {
+    /// Pays out the source amount at the price, rounded down, and only takes
+    /// the source tokens needed for it, rounded up
+    fn swap_without_fees(
+        &self,
+        source_amount: u128,
+        _pool_source_amount: u128,
+        _pool_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<SwapWithoutFeesResult> {
+        let destination_amount_swapped =
+            self.convert(source_amount, trade_direction, RoundDirection::Floor)?;
+        let source_amount_swapped = self.convert(
+            destination_amount_swapped,
+            trade_direction.opposite(),
+            RoundDirection::Ceiling,
+        )?;
+        require!(
+            source_amount_swapped > 0 && destination_amount_swapped > 0,
+            SwapError::ZeroTradingTokens
+        );
+        Ok(SwapWithoutFeesResult {
+            source_amount_swapped,
+            destination_amount_swapped,
+        })
+    }
+
+    /// Takes the source tokens worth the destination amount at the price, rounded up
+    fn swap_without_fees_exact_out(
+        &self,
+        destination_amount: u128,
+        _pool_source_amount: u128,
+        _pool_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<SwapWithoutFeesResult> {
+        let source_amount_swapped = self.convert(
+            destination_amount,
+            trade_direction.opposite(),
+            RoundDirection::Ceiling,
+        )?;
+        require!(
+            source_amount_swapped > 0 && destination_amount > 0,
+            SwapError::ZeroTradingTokens
+        );
+        Ok(SwapWithoutFeesResult {
+            source_amount_swapped,
+            destination_amount_swapped: destination_amount,
+        })
+    }
+
+    /// The price does not depend on the pool balances
+    fn spot_price(
+        &self,
+        _pool_token_a_amount: u128,
+        _pool_token_b_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<PreciseNumber> {
+        let price_x64 = U256::from(self.token_b_price_x64);
+        let one_x64 = U256::one() << Q64_FRACTIONAL_BITS;
+        match trade_direction {
+            TradeDirection::AtoB => math::precise_ratio(one_x64, price_x64),
+            TradeDirection::BtoA => math::precise_ratio(price_x64, one_x64),
+        }
+    }
+
+    /// A share of the pool balances, which does not depend on the price so that proportional
+    /// withdrawals do not need a fresh price feed
+    fn pool_tokens_to_trading_tokens(
+        &self,
+        pool_tokens: u128,
+        pool_token_supply: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Result<TradingTokenResult> {
+        math::pool_tokens_to_trading_tokens(
+            pool_tokens,
+            pool_token_supply,
+            pool_token_a_amount,
+            pool_token_b_amount,
+            round_direction,
+        )
+    }
+
+    fn trading_tokens_to_pool_tokens(
+        &self,
+        source_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        self.value_to_pool_tokens(
+            source_amount,
+            pool_token_a_amount,
+            pool_token_b_amount,
+            pool_token_supply,
+            trade_direction,
+            round_direction,
+        )
+    }
+
+    fn withdraw_single_token_type_exact_out(
+        &self,
+        destination_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        self.value_to_pool_tokens(
+            destination_amount,
+            pool_token_a_amount,
+            pool_token_b_amount,
+            pool_token_supply,
+            trade_direction,
+            round_direction,
+        )
+    }
+
+    fn withdraw_one_token(
+        &self,
+        pool_tokens: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_token_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let pool_tokens_value = try_math!(U256::from(pool_tokens)
+            .try_mul(self.value_x64(pool_token_a_amount, pool_token_b_amount)?))?;
+        let destination_value = div(
+            pool_tokens_value,
+            U256::from(pool_token_supply),
+            round_direction,
+        )?;
+        let price_x64 = match trade_direction {
+            TradeDirection::AtoB => U256::one() << Q64_FRACTIONAL_BITS,
+            TradeDirection::BtoA => U256::from(self.token_b_price_x64),
+        };
+        to_u128(div(destination_value, price_x64, round_direction)?)
+    }
+
+    fn validate(&self) -> Result<()> {
+        require_msg!(
+            self.price_feed != Pubkey::default(),
+            SwapError::InvalidCurve,
+            "Price feed must be set for oracle price curve"
+        );
+        require_msg!(
+            self.max_price_age_sec > 0,
+            SwapError::InvalidCurve,
+            "Max price age must be greater than 0 for oracle price curve"
+        );
+        require_msg!(
+            u128::from(self.max_price_confidence_bps) <= BPS_DENOMINATOR,
+            SwapError::InvalidCurve,
+            "Max price confidence must be at most 10000 bps for oracle price curve"
+        );
+        require_msg!(
+            self.token_b_price_x64 > 0,
+            SwapError::InvalidCurve,
+            "Token B price must be greater than 0 for oracle price curve"
+        );
+        Ok(())
+    }
+
+    fn validate_supply(&self, token_a_amount: u64, _token_b_amount: u64) -> Result<()> {
+        require_msg!(
+            token_a_amount > 0,
+            SwapError::EmptySupply,
+            "Token A amount must be greater than 0 for oracle price curve"
+        );
+        Ok(())
+    }
+
+    /// Half of the value of the pool in token A, like the constant price curve
+    fn normalized_value(
+        &self,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+    ) -> Result<PreciseNumber> {
+        let value_x64 = self.value_x64(pool_token_a_amount, pool_token_b_amount)?;
+        Ok(PreciseNumber {
+            value: try_math!(value_x64
+                .try_mul(PreciseNumber::one().value)?
+                .try_div(U256::from(2) << Q64_FRACTIONAL_BITS))?,
+        })
+    }
+}
+
+impl DynAccountSerialize for OraclePriceCurve {
+    fn try_dyn_serialize(&self, mut dst: std::cell::RefMut<&mut [u8]>) -> anchor_lang::Result<()> {
+        let dst: &mut [u8] = &mut dst;
+        let mut cursor = std::io::Cursor::new(dst);
+        anchor_lang::AccountSerialize::try_serialize(self, &mut cursor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::BorrowMut;
+
+    use anchor_lang::AccountDeserialize;
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::{
+        curve::calculator::test::{
+            check_curve_value_from_swap, check_curve_value_from_swap_exact_out,
+            check_pool_value_from_deposit_single_token_type,
+            check_pool_value_from_withdraw_single_token_type_exact_out,
+        },
+        state::Curve,
+    };
+
+    const PRICE_FEED_PROGRAM: Pubkey = Pubkey::new_from_array([7; 32]);
+
+    const ONE_X64: u128 = 1 << 64;
+
+    fn price_feed(price: i64, conf: u64, expo: i32, publish_time: i64) -> PriceFeed {
+        PriceFeed {
+            price,
+            conf,
+            expo,
+            publish_time,
+        }
+    }
+
+    fn oracle_curve(token_b_price_x64: u128) -> OraclePriceCurve {
+        OraclePriceCurve {
+            token_b_price_x64,
+            ..OraclePriceCurve::new(Pubkey::new_unique(), PRICE_FEED_PROGRAM, 60, 100)
+        }
+    }
+
+    #[test]
+    fn price_feed_bytes_roundtrip() {
+        let feed = price_feed(-12_345, 67, -8, 1_700_000_000);
+        assert_eq!(PriceFeed::try_from_bytes(&feed.to_bytes()).unwrap(), feed);
+        assert!(PriceFeed::try_from_bytes(&feed.to_bytes()[..PriceFeed::LEN - 1]).is_err());
+    }
+
+    #[test]
+    fn token_b_price_scales_by_exponent() {
+        let now = 1_000;
+        assert_eq!(
+            price_feed(2_500, 0, 0, now)
+                .token_b_price_x64(now, 60, 0)
+                .unwrap(),
+            2_500 * ONE_X64
+        );
+        assert_eq!(
+            price_feed(25, 0, 2, now)
+                .token_b_price_x64(now, 60, 0)
+                .unwrap(),
+            2_500 * ONE_X64
+        );
+        // the fractional part of the price is kept
+        assert_eq!(
+            price_feed(2_500_500, 0, -3, now)
+                .token_b_price_x64(now, 60, 0)
+                .unwrap(),
+            2_500 * ONE_X64 + ONE_X64 / 2
+        );
+        // as are prices below 1 token A per token B
+        assert_eq!(
+            price_feed(25, 0, -2, now)
+                .token_b_price_x64(now, 60, 0)
+                .unwrap(),
+            ONE_X64 / 4
+        );
+        // but not prices rounding to 0
+        assert!(price_feed(1, 0, -30, now)
+            .token_b_price_x64(now, 60, 0)
+            .is_err());
+        assert!(price_feed(0, 0, 0, now)
+            .token_b_price_x64(now, 60, 0)
+            .is_err());
+        assert!(price_feed(-1, 0, 0, now)
+            .token_b_price_x64(now, 60, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn token_b_price_staleness() {
+        let feed = price_feed(100, 0, 0, 1_000);
+        assert_eq!(feed.token_b_price_x64(1_060, 60, 0).unwrap(), 100 * ONE_X64);
+        assert_eq!(
+            feed.token_b_price_x64(1_061, 60, 0).unwrap_err(),
+            error!(SwapError::StalePrice)
+        );
+        // prices published slightly ahead of the clock are within the skew tolerance
+        assert_eq!(
+            feed.token_b_price_x64(1_000 - MAX_PRICE_CLOCK_SKEW_SEC, 60, 0)
+                .unwrap(),
+            100 * ONE_X64
+        );
+        // prices further in the future are rejected rather than treated as fresh
+        assert_eq!(
+            feed.token_b_price_x64(1_000 - MAX_PRICE_CLOCK_SKEW_SEC - 1, 60, 0)
+                .unwrap_err(),
+            error!(SwapError::InvalidPriceFeed)
+        );
+        assert_eq!(
+            feed.token_b_price_x64(900, 60, 0).unwrap_err(),
+            error!(SwapError::InvalidPriceFeed)
+        );
+    }
+
+    #[test]
+    fn token_b_price_confidence() {
+        // 1% confidence interval
+        let feed = price_feed(10_000, 100, 0, 1_000);
+        assert_eq!(
+            feed.token_b_price_x64(1_000, 60, 100).unwrap(),
+            10_000 * ONE_X64
+        );
+        assert_eq!(
+            feed.token_b_price_x64(1_000, 60, 99).unwrap_err(),
+            error!(SwapError::PriceConfidenceTooWide)
+        );
+    }
+
+    #[test]
+    fn load_price_from_feed_account() {
+        let price_feed_key = Pubkey::new_unique();
+        let mut curve = OraclePriceCurve::new(price_feed_key, PRICE_FEED_PROGRAM, 60, 100);
+
+        let mut lamports = 0;
+        let mut data = price_feed(2_000, 0, 0, 1_000).to_bytes();
+        let feed_account = AccountInfo::new(
+            &price_feed_key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &PRICE_FEED_PROGRAM,
+            false,
+            0,
+        );
+
+        // the feed is missing
+        assert_eq!(
+            curve.load_price_at(&[], 1_000).unwrap_err(),
+            error!(SwapError::InvalidPriceFeed)
+        );
+
+        curve.load_price_at(&[feed_account.clone()], 1_000).unwrap();
+        assert_eq!(curve.token_b_price_x64, 2_000 * ONE_X64);
+
+        // the feed is owned by the wrong program
+        let wrong_program = Pubkey::new_unique();
+        let mut wrong_owner_feed = feed_account.clone();
+        wrong_owner_feed.owner = &wrong_program;
+        assert_eq!(
+            curve.load_price_at(&[wrong_owner_feed], 1_000).unwrap_err(),
+            error!(SwapError::InvalidPriceFeed)
+        );
+
+        // the feed is stale
+        assert_eq!(
+            curve.load_price_at(&[feed_account], 1_061).unwrap_err(),
+            error!(SwapError::StalePrice)
+        );
+    }
+
+    #[test]
+    fn swap_at_loaded_price() {
+        let curve = oracle_curve(10 * ONE_X64);
+
+        let result = curve
+            .swap_without_fees(100, 1_000, 1_000, TradeDirection::AtoB)
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 100);
+        assert_eq!(result.destination_amount_swapped, 10);
+
+        let result = curve
+            .swap_without_fees(10, 1_000, 1_000, TradeDirection::BtoA)
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 10);
+        assert_eq!(result.destination_amount_swapped, 100);
+    }
+
+    #[test]
+    fn swap_at_fractional_price() {
+        // 2.5 token A per token B
+        let curve = oracle_curve(5 * ONE_X64 / 2);
+
+        let result = curve
+            .swap_without_fees(11, 1_000, 1_000, TradeDirection::AtoB)
+            .unwrap();
+        // only the 10 token A needed for 4 token B are taken
+        assert_eq!(result.source_amount_swapped, 10);
+        assert_eq!(result.destination_amount_swapped, 4);
+
+        let result = curve
+            .swap_without_fees(3, 1_000, 1_000, TradeDirection::BtoA)
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 3);
+        assert_eq!(result.destination_amount_swapped, 7);
+
+        let result = curve
+            .swap_without_fees_exact_out(3, 1_000, 1_000, TradeDirection::AtoB)
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 8);
+        assert_eq!(result.destination_amount_swapped, 3);
+
+        let result = curve
+            .swap_without_fees_exact_out(3, 1_000, 1_000, TradeDirection::BtoA)
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 2);
+        assert_eq!(result.destination_amount_swapped, 3);
+
+        // less than 1 token A per token B
+        let curve = oracle_curve(ONE_X64 / 4);
+
+        let result = curve
+            .swap_without_fees(10, 1_000, 1_000, TradeDirection::AtoB)
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 10);
+        assert_eq!(result.destination_amount_swapped, 40);
+
+        let result = curve
+            .swap_without_fees(43, 1_000, 1_000, TradeDirection::BtoA)
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 40);
+        assert_eq!(result.destination_amount_swapped, 10);
+
+        // not enough token B for 1 token A
+        assert_eq!(
+            curve
+                .swap_without_fees(3, 1_000, 1_000, TradeDirection::BtoA)
+                .unwrap_err(),
+            error!(SwapError::ZeroTradingTokens)
+        );
+    }
+
+    #[test]
+    fn value_at_fractional_price() {
+        let curve = oracle_curve(ONE_X64 / 4);
+        assert_eq!(
+            curve.normalized_value(1_000, 1_000).unwrap().to_imprecise(),
+            Some(625)
+        );
+        assert_eq!(
+            curve
+                .spot_price(1_000, 1_000, TradeDirection::AtoB)
+                .unwrap()
+                .to_imprecise(),
+            Some(4)
+        );
+
+        // pool tokens are a share of the pool balances, whatever the price
+        let result = curve
+            .pool_tokens_to_trading_tokens(10, 100, 1_000, 1_005, RoundDirection::Floor)
+            .unwrap();
+        assert_eq!(result.token_a_amount, 100);
+        assert_eq!(result.token_b_amount, 100);
+        let result = curve
+            .pool_tokens_to_trading_tokens(10, 100, 1_000, 1_005, RoundDirection::Ceiling)
+            .unwrap();
+        assert_eq!(result.token_a_amount, 100);
+        assert_eq!(result.token_b_amount, 101);
+
+        assert_eq!(
+            curve
+                .trading_tokens_to_pool_tokens(
+                    500,
+                    1_000,
+                    1_000,
+                    100,
+                    TradeDirection::BtoA,
+                    RoundDirection::Floor
+                )
+                .unwrap(),
+            10
+        );
+        assert_eq!(
+            curve
+                .withdraw_one_token(
+                    10,
+                    1_000,
+                    1_000,
+                    100,
+                    TradeDirection::BtoA,
+                    RoundDirection::Floor
+                )
+                .unwrap(),
+            500
+        );
+    }
+
+    #[test]
+    fn validate_oracle_price_curve() {
+        let mut curve = OraclePriceCurve::new(Pubkey::new_unique(), PRICE_FEED_PROGRAM, 60, 100);
+        // price not loaded
+        assert!(curve.validate().is_err());
+        curve.token_b_price_x64 = 1;
+        curve.validate().unwrap();
+
+        curve.max_price_confidence_bps = 10_001;
+        assert!(curve.validate().is_err());
+        curve.max_price_confidence_bps = 100;
+        curve.max_price_age_sec = 0;
+        assert!(curve.validate().is_err());
+        curve.max_price_age_sec = 60;
+        curve.price_feed = Pubkey::default();
+        assert!(curve.validate().is_err());
+    }
+
+    #[test]
+    fn serialize_oracle_price_curve() {
+        let curve = oracle_curve(1_251_258 * ONE_X64 + 12_345);
+
+        let mut arr = [0u8; Curve::LEN];
+        let packed = arr.borrow_mut();
+        let ref_mut = std::cell::RefCell::new(packed);
+
+        curve.try_dyn_serialize(ref_mut.borrow_mut()).unwrap();
+        let unpacked = OraclePriceCurve::try_deserialize(&mut arr.as_ref()).unwrap();
+        assert_eq!(curve, unpacked);
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap(
+            source_token_amount in 1..u32::MAX,
+            swap_source_amount in 1..u32::MAX,
+            // enough to pay out any source amount at any price
+            swap_destination_amount in 1u64 << 53..u64::MAX,
+            token_b_price_x64 in ONE_X64 / 1_000_000..ONE_X64 * 1_000_000,
+        ) {
+            let curve = oracle_curve(token_b_price_x64);
+            for trade_direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+                // skip trades too small to get any tokens out
+                if curve
+                    .swap_without_fees(
+                        source_token_amount as u128,
+                        swap_source_amount as u128,
+                        swap_destination_amount as u128,
+                        trade_direction,
+                    )
+                    .is_err()
+                {
+                    continue;
+                }
+                check_curve_value_from_swap(
+                    &curve,
+                    source_token_amount as u128,
+                    swap_source_amount as u128,
+                    swap_destination_amount as u128,
+                    trade_direction,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap_exact_out(
+            destination_token_amount in 1..u32::MAX,
+            swap_source_amount in 1..u32::MAX,
+            swap_destination_amount in u32::MAX as u64..u64::MAX,
+            token_b_price_x64 in ONE_X64 / 1_000_000..ONE_X64 * 1_000_000,
+        ) {
+            let curve = oracle_curve(token_b_price_x64);
+            for trade_direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+                check_curve_value_from_swap_exact_out(
+                    &curve,
+                    destination_token_amount as u128,
+                    swap_source_amount as u128,
+                    swap_destination_amount as u128,
+                    trade_direction,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn pool_value_does_not_decrease_from_single_token(
+            token_amount in 1..u32::MAX,
+            pool_token_supply in u32::MAX as u64..u64::MAX,
+            swap_token_a_amount in u32::MAX as u64..u64::MAX,
+            swap_token_b_amount in u32::MAX as u64..u64::MAX,
+            token_b_price_x64 in ONE_X64 / 1_000_000..ONE_X64 * 1_000_000,
+        ) {
+            let curve = oracle_curve(token_b_price_x64);
+            for trade_direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+                check_pool_value_from_deposit_single_token_type(
+                    &curve,
+                    token_amount as u128,
+                    pool_token_supply as u128,
+                    swap_token_a_amount as u128,
+                    swap_token_b_amount as u128,
+                    trade_direction,
+                );
+                check_pool_value_from_withdraw_single_token_type_exact_out(
+                    &curve,
+                    token_amount as u128,
+                    pool_token_supply as u128,
+                    swap_token_a_amount as u128,
+                    swap_token_b_amount as u128,
+                    trade_direction,
+                );
+            }
+        }
+    }
+}
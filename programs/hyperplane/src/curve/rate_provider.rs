@@ -0,0 +1,227 @@
+//! Rate providers of stable curves pairing a liquid staking token with its underlying token
+//!
+//! The value of a liquid staking token grows against its underlying token as staking rewards
+//! accrue. A stable curve with a rate provider values token B at the provided rate, so the peg
+//! tracks the rate instead of drifting away from it.
+
+use anchor_lang::{
+    error,
+    prelude::{borsh, Pubkey},
+    solana_program::pubkey,
+    AnchorDeserialize, AnchorSerialize, Result,
+};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+use crate::{error::SwapError, require_msg, try_math, utils::math::TryMath};
+
+/// Denominator of the fixed-point token B rates, a rate of `RATE_PRECISION` is 1
+pub const RATE_PRECISION: u128 = 1_000_000_000_000;
+
+/// Upper bound of the `max_rate_change_bps` of an authority rate provider, 10%
+pub const MAX_RATE_CHANGE_BPS: u64 = 1_000;
+
+/// The SPL stake pool program, owner of `StakePool` rate provider accounts
+pub const SPL_STAKE_POOL_PROGRAM_ID: Pubkey =
+    pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
+
+/// Source of the token B rate of a stable curve
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
+#[repr(u64)]
+pub enum RateProviderType {
+    /// Token B is valued 1:1 with token A, after scaling by decimals
+    None = 0,
+    /// The rate is read from an SPL stake pool account whenever the curve is loaded
+    StakePool = 1,
+    /// The rate is set by a rate updater authority
+    Authority = 2,
+}
+
+/// Rate provider of a stable curve, as configured by the pool admin
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum RateProvider {
+    None,
+    StakePool {
+        /// SPL stake pool account of the token B mint
+        stake_pool: Pubkey,
+    },
+    Authority {
+        /// Authority allowed to update the rate
+        rate_updater: Pubkey,
+        /// Initial amount of token A per token B, over `RATE_PRECISION`
+        token_b_rate: u64,
+        /// Maximum age of the rate before it is considered stale
+        max_rate_age_sec: u64,
+        /// Maximum change of the rate per `max_rate_age_sec` elapsed since the previous update, in
+        /// basis points of the previous rate, at most `MAX_RATE_CHANGE_BPS`
+        max_rate_change_bps: u64,
+    },
+}
+
+/// Rate read from an SPL stake pool `StakePool` account.
+///
+/// Only the fields needed for the rate are read, little-endian from the start of the account
+/// data:
+///
+/// | offset | field               | type     |
+/// |--------|---------------------|----------|
+/// | 0      | `account_type`      | `u8`     |
+/// | 162    | `pool_mint`         | `Pubkey` |
+/// | 258    | `total_lamports`    | `u64`    |
+/// | 266    | `pool_token_supply` | `u64`    |
+/// | 274    | `last_update_epoch` | `u64`    |
+///
+/// The rate is the amount of lamports (token A) per pool token (token B).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StakePoolRate {
+    pub account_type: u8,
+    pub pool_mint: Pubkey,
+    pub total_lamports: u64,
+    pub pool_token_supply: u64,
+    pub last_update_epoch: u64,
+}
+
+impl StakePoolRate {
+    pub const LEN: usize = 282;
+
+    /// `AccountType::StakePool` of the SPL stake pool program
+    pub const ACCOUNT_TYPE_STAKE_POOL: u8 = 1;
+
+    pub fn try_from_bytes(data: &[u8]) -> Result<Self> {
+        require_msg!(
+            data.len() >= Self::LEN,
+            SwapError::InvalidRateProvider,
+            &format!(
+                "InvalidRateProvider: stake pool data length {} < {}",
+                data.len(),
+                Self::LEN
+            )
+        );
+        let stake_pool = Self {
+            account_type: data[0],
+            pool_mint: Pubkey::new_from_array(data[162..194].try_into().unwrap()),
+            total_lamports: u64::from_le_bytes(data[258..266].try_into().unwrap()),
+            pool_token_supply: u64::from_le_bytes(data[266..274].try_into().unwrap()),
+            last_update_epoch: u64::from_le_bytes(data[274..282].try_into().unwrap()),
+        };
+        require_msg!(
+            stake_pool.account_type == Self::ACCOUNT_TYPE_STAKE_POOL,
+            SwapError::InvalidRateProvider,
+            &format!(
+                "InvalidRateProvider: account type {} is not a stake pool",
+                stake_pool.account_type
+            )
+        );
+        Ok(stake_pool)
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut data = [0; Self::LEN];
+        data[0] = self.account_type;
+        data[162..194].copy_from_slice(self.pool_mint.as_ref());
+        data[258..266].copy_from_slice(&self.total_lamports.to_le_bytes());
+        data[266..274].copy_from_slice(&self.pool_token_supply.to_le_bytes());
+        data[274..282].copy_from_slice(&self.last_update_epoch.to_le_bytes());
+        data
+    }
+
+    /// The amount of token A per token B over `RATE_PRECISION`, after checking that the stake
+    /// pool has been updated in the current epoch
+    pub fn token_b_rate(&self, epoch: u64) -> Result<u64> {
+        require_msg!(
+            self.last_update_epoch == epoch,
+            SwapError::StaleRate,
+            &format!(
+                "StaleRate: stake pool last_update_epoch={} != epoch={}",
+                self.last_update_epoch, epoch
+            )
+        );
+        require_msg!(
+            self.pool_token_supply > 0 && self.total_lamports > 0,
+            SwapError::InvalidRateProvider,
+            &format!(
+                "InvalidRateProvider: total_lamports={}, pool_token_supply={}",
+                self.total_lamports, self.pool_token_supply
+            )
+        );
+        let rate = try_math!(u128::from(self.total_lamports)
+            .try_mul(RATE_PRECISION)?
+            .try_div(u128::from(self.pool_token_supply)))?;
+        u64::try_from(rate).map_err(|_| error!(SwapError::ConversionFailure))
+    }
+}
+
+/// Check that an authority-set rate is no older than `max_rate_age_sec` at `now`
+pub fn check_rate_age(rate_updated_ts: u64, max_rate_age_sec: u64, now: u64) -> Result<()> {
+    let age = now.saturating_sub(rate_updated_ts);
+    require_msg!(
+        age <= max_rate_age_sec,
+        SwapError::StaleRate,
+        &format!(
+            "StaleRate: rate updated at {} is {}s old, max_rate_age_sec={}",
+            rate_updated_ts, age, max_rate_age_sec
+        )
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stake_pool(total_lamports: u64, pool_token_supply: u64, epoch: u64) -> StakePoolRate {
+        StakePoolRate {
+            account_type: StakePoolRate::ACCOUNT_TYPE_STAKE_POOL,
+            pool_mint: Pubkey::new_unique(),
+            total_lamports,
+            pool_token_supply,
+            last_update_epoch: epoch,
+        }
+    }
+
+    #[test]
+    fn stake_pool_bytes_round_trip() {
+        let stake_pool = stake_pool(1_100, 1_000, 5);
+        assert_eq!(
+            StakePoolRate::try_from_bytes(&stake_pool.to_bytes()).unwrap(),
+            stake_pool
+        );
+        assert_eq!(
+            StakePoolRate::try_from_bytes(&stake_pool.to_bytes()[..StakePoolRate::LEN - 1])
+                .unwrap_err(),
+            error!(SwapError::InvalidRateProvider)
+        );
+
+        let mut validator_list = stake_pool.to_bytes();
+        validator_list[0] = 2;
+        assert_eq!(
+            StakePoolRate::try_from_bytes(&validator_list).unwrap_err(),
+            error!(SwapError::InvalidRateProvider)
+        );
+    }
+
+    #[test]
+    fn stake_pool_rate() {
+        assert_eq!(
+            stake_pool(1_100, 1_000, 5).token_b_rate(5).unwrap(),
+            1_100_000_000_000
+        );
+        assert_eq!(
+            stake_pool(1_100, 1_000, 4).token_b_rate(5).unwrap_err(),
+            error!(SwapError::StaleRate)
+        );
+        assert_eq!(
+            stake_pool(1_100, 0, 5).token_b_rate(5).unwrap_err(),
+            error!(SwapError::InvalidRateProvider)
+        );
+    }
+
+    #[test]
+    fn authority_rate_age() {
+        check_rate_age(1_000, 60, 1_060).unwrap();
+        check_rate_age(1_000, 60, 999).unwrap();
+        assert_eq!(
+            check_rate_age(1_000, 60, 1_061).unwrap_err(),
+            error!(SwapError::StaleRate)
+        );
+    }
+}
@@ -0,0 +1,733 @@
+#![allow(clippy::arithmetic_side_effects)]
+
+//! Weighted curve, the Balancer invariant `token_a^w_a * token_b^w_b` with
+//! configurable weights, eg. 80/20
+
+use anchor_lang::{error, require, Result};
+use spl_math::{precise_number::PreciseNumber, uint::U256};
+
+use crate::{
+    curve::{
+        calculator::{
+            CurveCalculator, DynAccountSerialize, RoundDirection, SwapWithoutFeesResult,
+            TradeDirection, TradingTokenResult,
+        },
+        constant_product::div_rounded,
+        math,
+    },
+    error::SwapError,
+    require_msg,
+    state::WeightedCurve,
+    try_math,
+    utils::math::{TryMath, TryNew},
+};
+
+/// Sum of the weights of both tokens, in basis points
+pub const TOTAL_WEIGHT_BPS: u64 = 10_000;
+
+/// Minimum weight of either token, which bounds the exponents of the swap
+/// math to `(TOTAL_WEIGHT_BPS - MIN_WEIGHT_BPS) / MIN_WEIGHT_BPS = 49`
+pub const MIN_WEIGHT_BPS: u64 = 200;
+
+impl WeightedCurve {
+    pub fn new(token_a_weight_bps: u64) -> Self {
+        Self {
+            token_a_weight_bps,
+            token_b_weight_bps: TOTAL_WEIGHT_BPS.saturating_sub(token_a_weight_bps),
+            _padding: [0; 14],
+        }
+    }
+
+    /// Weights of the source and destination tokens of a trade, where the source
+    /// is the token deposited or withdrawn for single token operations
+    fn weights(&self, trade_direction: TradeDirection) -> (u64, u64) {
+        match trade_direction {
+            TradeDirection::AtoB => (self.token_a_weight_bps, self.token_b_weight_bps),
+            TradeDirection::BtoA => (self.token_b_weight_bps, self.token_a_weight_bps),
+        }
+    }
+}
+
+/// `amount * ratio`, rounded in the given direction
+fn mul_ratio(amount: u128, ratio: &PreciseNumber, round_direction: RoundDirection) -> Result<u128> {
+    let product = div_rounded(
+        try_math!(U256::from(amount).try_mul(ratio.value))?,
+        PreciseNumber::one().value,
+        round_direction,
+    )?;
+    u128::try_from(product).map_err(|_| error!(SwapError::ConversionFailure))
+}
+
+impl CurveCalculator for WeightedCurve {
+    /// Weighted swap, which ensures the invariant does not decrease:
+    ///
+    /// `destination_amount = pool_destination * (1 - (pool_source / (pool_source + source_amount))^(w_source / w_destination))`
+    ///
+    /// The power is rounded up, so that the destination amount is rounded down
+    /// by at least the error of the approximation.
+    fn swap_without_fees(
+        &self,
+        source_amount: u128,
+        pool_source_amount: u128,
+        pool_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<SwapWithoutFeesResult> {
+        require!(source_amount > 0, SwapError::ZeroTradingTokens);
+        let (source_weight, destination_weight) = self.weights(trade_direction);
+
+        let new_pool_source_amount = try_math!(pool_source_amount.try_add(source_amount))?;
+        let ratio = math::pow_ratio(
+            pool_source_amount,
+            new_pool_source_amount,
+            source_weight,
+            destination_weight,
+            RoundDirection::Ceiling,
+        )?;
+        let new_pool_destination_amount =
+            mul_ratio(pool_destination_amount, &ratio, RoundDirection::Ceiling)?;
+
+        let destination_amount_swapped =
+            pool_destination_amount.saturating_sub(new_pool_destination_amount);
+        require!(destination_amount_swapped > 0, SwapError::ZeroTradingTokens);
+
+        Ok(SwapWithoutFeesResult {
+            source_amount_swapped: source_amount,
+            destination_amount_swapped,
+        })
+    }
+
+    /// Inverse of the weighted swap:
+    ///
+    /// `source_amount = pool_source * ((pool_destination / (pool_destination - destination_amount))^(w_destination / w_source) - 1)`
+    ///
+    /// The power and the source amount are rounded up.
+    fn swap_without_fees_exact_out(
+        &self,
+        destination_amount: u128,
+        pool_source_amount: u128,
+        pool_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<SwapWithoutFeesResult> {
+        require_msg!(
+            destination_amount > 0 && destination_amount < pool_destination_amount,
+            SwapError::ZeroTradingTokens,
+            &format!(
+                "Destination amount {} must be non-zero and less than the pool destination amount {}",
+                destination_amount, pool_destination_amount
+            )
+        );
+        let (source_weight, destination_weight) = self.weights(trade_direction);
+
+        let new_pool_destination_amount =
+            try_math!(pool_destination_amount.try_sub(destination_amount))?;
+        let ratio = math::pow_ratio(
+            pool_destination_amount,
+            new_pool_destination_amount,
+            destination_weight,
+            source_weight,
+            RoundDirection::Ceiling,
+        )?;
+        let new_pool_source_amount =
+            mul_ratio(pool_source_amount, &ratio, RoundDirection::Ceiling)?;
+
+        let source_amount_swapped = new_pool_source_amount.saturating_sub(pool_source_amount);
+        require!(source_amount_swapped > 0, SwapError::ZeroTradingTokens);
+
+        Ok(SwapWithoutFeesResult {
+            source_amount_swapped,
+            destination_amount_swapped: destination_amount,
+        })
+    }
+
+    /// The spot price is the ratio of the pool amounts divided by their weights,
+    /// `(pool_destination / w_destination) / (pool_source / w_source)`
+    fn spot_price(
+        &self,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<PreciseNumber> {
+        let weighted_token_a_amount = try_math!(
+            U256::from(pool_token_a_amount).try_mul(U256::from(self.token_b_weight_bps))
+        )?;
+        let weighted_token_b_amount = try_math!(
+            U256::from(pool_token_b_amount).try_mul(U256::from(self.token_a_weight_bps))
+        )?;
+        match trade_direction {
+            TradeDirection::AtoB => {
+                math::precise_ratio(weighted_token_b_amount, weighted_token_a_amount)
+            }
+            TradeDirection::BtoA => {
+                math::precise_ratio(weighted_token_a_amount, weighted_token_b_amount)
+            }
+        }
+    }
+
+    /// Proportional deposits and withdrawals keep the weights of the pool
+    fn pool_tokens_to_trading_tokens(
+        &self,
+        pool_tokens: u128,
+        pool_token_supply: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Result<TradingTokenResult> {
+        math::pool_tokens_to_trading_tokens(
+            pool_tokens,
+            pool_token_supply,
+            pool_token_a_amount,
+            pool_token_b_amount,
+            round_direction,
+        )
+    }
+
+    /// Get the amount of pool tokens for the given amount of token A or B,
+    /// `pool_supply * ((1 + source_amount / pool_source)^w_source - 1)`
+    fn trading_tokens_to_pool_tokens(
+        &self,
+        source_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let (source_weight, _) = self.weights(trade_direction);
+        let pool_source_amount = match trade_direction {
+            TradeDirection::AtoB => pool_token_a_amount,
+            TradeDirection::BtoA => pool_token_b_amount,
+        };
+        let new_pool_source_amount = try_math!(pool_source_amount.try_add(source_amount))?;
+
+        let ratio = math::pow_ratio(
+            new_pool_source_amount,
+            pool_source_amount,
+            source_weight,
+            TOTAL_WEIGHT_BPS,
+            round_direction,
+        )?;
+        let new_pool_supply = mul_ratio(pool_supply, &ratio, round_direction)?;
+        Ok(new_pool_supply.saturating_sub(pool_supply))
+    }
+
+    /// Get the amount of pool tokens to burn for the given amount of token A or B,
+    /// `pool_supply * (1 - (1 - destination_amount / pool_destination)^w_destination)`
+    fn withdraw_single_token_type_exact_out(
+        &self,
+        destination_amount: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let (destination_weight, _) = self.weights(trade_direction);
+        let pool_destination_amount = match trade_direction {
+            TradeDirection::AtoB => pool_token_a_amount,
+            TradeDirection::BtoA => pool_token_b_amount,
+        };
+        let new_pool_destination_amount =
+            try_math!(pool_destination_amount.try_sub(destination_amount))?;
+
+        let ratio = math::pow_ratio(
+            new_pool_destination_amount,
+            pool_destination_amount,
+            destination_weight,
+            TOTAL_WEIGHT_BPS,
+            round_direction.opposite(),
+        )?;
+        let new_pool_supply = mul_ratio(pool_supply, &ratio, round_direction.opposite())?;
+        Ok(pool_supply.saturating_sub(new_pool_supply))
+    }
+
+    /// Get the amount of token A or B redeemed for the given amount of pool tokens,
+    /// `pool_destination * (1 - (1 - pool_tokens / pool_supply)^(1 / w_destination))`
+    fn withdraw_one_token(
+        &self,
+        pool_tokens: u128,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Result<u128> {
+        let (destination_weight, _) = self.weights(trade_direction);
+        let pool_destination_amount = match trade_direction {
+            TradeDirection::AtoB => pool_token_a_amount,
+            TradeDirection::BtoA => pool_token_b_amount,
+        };
+        let new_pool_supply = try_math!(pool_supply.try_sub(pool_tokens))?;
+
+        let ratio = math::pow_ratio(
+            new_pool_supply,
+            pool_supply,
+            TOTAL_WEIGHT_BPS,
+            destination_weight,
+            round_direction.opposite(),
+        )?;
+        let new_pool_destination_amount =
+            mul_ratio(pool_destination_amount, &ratio, round_direction.opposite())?;
+        Ok(pool_destination_amount.saturating_sub(new_pool_destination_amount))
+    }
+
+    fn validate(&self) -> Result<()> {
+        require_msg!(
+            self.token_a_weight_bps >= MIN_WEIGHT_BPS && self.token_b_weight_bps >= MIN_WEIGHT_BPS,
+            SwapError::InvalidCurve,
+            &format!(
+                "token_a_weight_bps={} or token_b_weight_bps={} < MIN_WEIGHT_BPS={}",
+                self.token_a_weight_bps, self.token_b_weight_bps, MIN_WEIGHT_BPS
+            )
+        );
+        require_msg!(
+            self.token_a_weight_bps.checked_add(self.token_b_weight_bps) == Some(TOTAL_WEIGHT_BPS),
+            SwapError::InvalidCurve,
+            &format!(
+                "token_a_weight_bps={} + token_b_weight_bps={} != TOTAL_WEIGHT_BPS={}",
+                self.token_a_weight_bps, self.token_b_weight_bps, TOTAL_WEIGHT_BPS
+            )
+        );
+        Ok(())
+    }
+
+    /// The normalized value of the weighted curve is the weighted geometric
+    /// mean of the pool amounts, `token_a^w_a * token_b^w_b` with weights
+    /// summing to 1
+    fn normalized_value(
+        &self,
+        pool_token_a_amount: u128,
+        pool_token_b_amount: u128,
+    ) -> Result<PreciseNumber> {
+        if pool_token_a_amount == 0 || pool_token_b_amount == 0 {
+            return PreciseNumber::try_new(0);
+        }
+        let ln_token_a = math::ln_ratio(pool_token_a_amount.into(), U256::one())?;
+        let ln_token_b = math::ln_ratio(pool_token_b_amount.into(), U256::one())?;
+        let exponent = try_math!(ln_token_a
+            .value
+            .try_mul(U256::from(self.token_a_weight_bps))?
+            .try_add(
+                ln_token_b
+                    .value
+                    .try_mul(U256::from(self.token_b_weight_bps))?
+            )?
+            .try_div(U256::from(TOTAL_WEIGHT_BPS)))?;
+        math::exp(&PreciseNumber { value: exponent })
+    }
+}
+
+impl DynAccountSerialize for WeightedCurve {
+    fn try_dyn_serialize(&self, mut dst: std::cell::RefMut<&mut [u8]>) -> Result<()> {
+        let dst: &mut [u8] = &mut dst;
+        let mut cursor = std::io::Cursor::new(dst);
+        anchor_lang::AccountSerialize::try_serialize(self, &mut cursor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::BorrowMut;
+
+    use anchor_lang::AccountDeserialize;
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::{
+        curve::calculator::test::{
+            check_pool_value_from_deposit, check_spot_price_from_small_swap, total_and_intermediate,
+        },
+        state::Curve,
+    };
+
+    /// Natural log of `new_amount / amount`, without losing the precision of
+    /// small changes to large amounts
+    fn ln_change(new_amount: u128, amount: u128) -> f64 {
+        if new_amount >= amount {
+            ((new_amount - amount) as f64 / amount as f64).ln_1p()
+        } else {
+            (-((amount - new_amount) as f64) / amount as f64).ln_1p()
+        }
+    }
+
+    /// The logs of the power approximations are only checked to f64 precision
+    fn assert_not_less(lhs: f64, rhs: f64) {
+        let tolerance = 1e-12 * (lhs.abs() + rhs.abs()) + 1e-18;
+        assert!(lhs >= rhs - tolerance, "{} < {}", lhs, rhs);
+    }
+
+    /// Like `check_curve_value_from_swap`, comparing the logs of the invariant
+    /// `w_source * ln(pool_source) + w_destination * ln(pool_destination)`
+    fn check_invariant_from_swap(
+        curve: &WeightedCurve,
+        source_token_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) {
+        let results = curve
+            .swap_without_fees(
+                source_token_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+            )
+            .unwrap();
+        let (source_weight, destination_weight) = curve.weights(trade_direction);
+
+        let source_change = source_weight as f64
+            * ln_change(
+                swap_source_amount + results.source_amount_swapped,
+                swap_source_amount,
+            );
+        let destination_change = destination_weight as f64
+            * ln_change(
+                swap_destination_amount - results.destination_amount_swapped,
+                swap_destination_amount,
+            );
+        assert_not_less(source_change, -destination_change);
+    }
+
+    /// Like the single token checks of the calculator tests, comparing the
+    /// logs of the value per pool token `pool_amount^w / pool_token_supply`
+    fn check_value_per_pool_token(
+        weight_bps: u64,
+        pool_amount: u128,
+        new_pool_amount: u128,
+        pool_token_supply: u128,
+        new_pool_token_supply: u128,
+    ) {
+        let value_change =
+            weight_bps as f64 / TOTAL_WEIGHT_BPS as f64 * ln_change(new_pool_amount, pool_amount);
+        let supply_change = ln_change(new_pool_token_supply, pool_token_supply);
+        assert_not_less(value_change, supply_change);
+    }
+
+    #[test]
+    fn serialize_weighted_curve() {
+        let curve = WeightedCurve::new(8_000);
+
+        let mut arr = [0u8; Curve::LEN];
+        let packed = arr.borrow_mut();
+        let ref_mut = std::cell::RefCell::new(packed);
+
+        curve.try_dyn_serialize(ref_mut.borrow_mut()).unwrap();
+        let unpacked = WeightedCurve::try_deserialize(&mut arr.as_ref()).unwrap();
+        assert_eq!(curve, unpacked);
+    }
+
+    #[test]
+    fn swap_eighty_twenty() {
+        let curve = WeightedCurve::new(8_000);
+        // (800 / 0.8) / (200 / 0.2), the pool is balanced by value
+        assert_eq!(
+            curve.spot_price(800, 200, TradeDirection::AtoB).unwrap(),
+            PreciseNumber::new(1).unwrap()
+        );
+
+        // 200 * (1 - (800 / 900)^4) = 75.14...
+        let result = curve
+            .swap_without_fees(100, 800, 200, TradeDirection::AtoB)
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 100);
+        assert_eq!(result.destination_amount_swapped, 75);
+
+        // 800 * (1 - (200 / 300)^(1 / 4)) = 77.06...
+        let result = curve
+            .swap_without_fees(100, 200, 800, TradeDirection::BtoA)
+            .unwrap();
+        assert_eq!(result.destination_amount_swapped, 77);
+    }
+
+    #[test]
+    fn swap_fifty_fifty_matches_constant_product() {
+        let curve = WeightedCurve::new(5_000);
+        // 1_000 - 1_000_000 / 1_100, rounded down
+        let result = curve
+            .swap_without_fees(100, 1_000, 1_000, TradeDirection::AtoB)
+            .unwrap();
+        assert_eq!(result.destination_amount_swapped, 90);
+
+        // 1_000_000 / 910 - 1_000, rounded up
+        let result = curve
+            .swap_without_fees_exact_out(90, 1_000, 1_000, TradeDirection::AtoB)
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 99);
+        assert_eq!(result.destination_amount_swapped, 90);
+
+        assert_eq!(
+            curve.swap_without_fees_exact_out(1_000, 1_000, 1_000, TradeDirection::AtoB),
+            Err(SwapError::ZeroTradingTokens.into())
+        );
+    }
+
+    #[test]
+    fn normalized_value_is_weighted_geometric_mean() {
+        // sqrt(4 * 9)
+        assert!(WeightedCurve::new(5_000)
+            .normalized_value(4, 9)
+            .unwrap()
+            .almost_eq(&PreciseNumber::new(6).unwrap(), U256::from(1_000_000)));
+        // 1024^0.8
+        assert!(WeightedCurve::new(8_000)
+            .normalized_value(1_024, 1)
+            .unwrap()
+            .almost_eq(&PreciseNumber::new(256).unwrap(), U256::from(1_000_000)));
+        assert_eq!(
+            WeightedCurve::new(8_000).normalized_value(0, 1).unwrap(),
+            PreciseNumber::new(0).unwrap()
+        );
+    }
+
+    #[test]
+    fn validate_weights() {
+        assert!(WeightedCurve::new(8_000).validate().is_ok());
+        assert!(WeightedCurve::new(MIN_WEIGHT_BPS).validate().is_ok());
+        assert_eq!(
+            WeightedCurve::new(MIN_WEIGHT_BPS - 1).validate(),
+            Err(SwapError::InvalidCurve.into())
+        );
+        assert_eq!(
+            WeightedCurve::new(TOTAL_WEIGHT_BPS - MIN_WEIGHT_BPS + 1).validate(),
+            Err(SwapError::InvalidCurve.into())
+        );
+        assert_eq!(
+            WeightedCurve::new(20_000).validate(),
+            Err(SwapError::InvalidCurve.into())
+        );
+        assert_eq!(
+            WeightedCurve {
+                token_a_weight_bps: 5_000,
+                token_b_weight_bps: 6_000,
+                ..Default::default()
+            }
+            .validate(),
+            Err(SwapError::InvalidCurve.into())
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap(
+            source_token_amount in 1..u64::MAX as u128,
+            swap_source_amount in 1..u64::MAX as u128,
+            swap_destination_amount in 1..u64::MAX as u128,
+            token_a_weight_bps in MIN_WEIGHT_BPS..=TOTAL_WEIGHT_BPS - MIN_WEIGHT_BPS,
+        ) {
+            let curve = WeightedCurve::new(token_a_weight_bps);
+            for trade_direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+                // swaps too small for any output fail
+                if curve
+                    .swap_without_fees(
+                        source_token_amount,
+                        swap_source_amount,
+                        swap_destination_amount,
+                        trade_direction,
+                    )
+                    .is_err()
+                {
+                    continue;
+                }
+                check_invariant_from_swap(
+                    &curve,
+                    source_token_amount,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    trade_direction,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap_exact_out(
+            destination_token_amount in 1..u64::MAX as u128,
+            swap_source_amount in 1..u64::MAX as u128,
+            swap_destination_amount in 1..u64::MAX as u128,
+            token_a_weight_bps in MIN_WEIGHT_BPS..=TOTAL_WEIGHT_BPS - MIN_WEIGHT_BPS,
+        ) {
+            prop_assume!(destination_token_amount < swap_destination_amount);
+            let curve = WeightedCurve::new(token_a_weight_bps);
+            for trade_direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+                // draining most of a light token overflows the source amount
+                let results = match curve.swap_without_fees_exact_out(
+                    destination_token_amount,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    trade_direction,
+                ) {
+                    Ok(results) => results,
+                    Err(_) => continue,
+                };
+                assert_eq!(results.destination_amount_swapped, destination_token_amount);
+
+                let (source_weight, destination_weight) = curve.weights(trade_direction);
+                let source_change = source_weight as f64
+                    * ln_change(
+                        swap_source_amount + results.source_amount_swapped,
+                        swap_source_amount,
+                    );
+                let destination_change = destination_weight as f64
+                    * ln_change(
+                        swap_destination_amount - destination_token_amount,
+                        swap_destination_amount,
+                    );
+                assert_not_less(source_change, -destination_change);
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn spot_price_matches_small_swap(
+            swap_token_a_amount in 10_000_000_000..1_000_000_000_000_u64,
+            swap_token_b_amount in 10_000_000_000..1_000_000_000_000_u64,
+            token_a_weight_bps in 2_000..=8_000_u64,
+        ) {
+            let curve = WeightedCurve::new(token_a_weight_bps);
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            check_spot_price_from_small_swap(
+                &curve,
+                swap_token_a_amount / 100_000,
+                swap_token_a_amount,
+                swap_token_b_amount,
+                TradeDirection::AtoB,
+                1,
+            );
+            check_spot_price_from_small_swap(
+                &curve,
+                swap_token_b_amount / 100_000,
+                swap_token_b_amount,
+                swap_token_a_amount,
+                TradeDirection::BtoA,
+                1,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_deposit(
+            pool_token_amount in 1..u64::MAX as u128,
+            pool_token_supply in 1..u64::MAX as u128,
+            swap_token_a_amount in 1..u64::MAX as u128,
+            swap_token_b_amount in 1..u64::MAX as u128,
+        ) {
+            check_pool_value_from_deposit(
+                &WeightedCurve::new(8_000),
+                pool_token_amount,
+                pool_token_supply,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_deposit_single_token_type(
+            source_token_amount in 1..u64::MAX as u128,
+            pool_token_supply in 1..u64::MAX as u128,
+            swap_token_a_amount in 1..u64::MAX as u128,
+            swap_token_b_amount in 1..u64::MAX as u128,
+            token_a_weight_bps in MIN_WEIGHT_BPS..=TOTAL_WEIGHT_BPS - MIN_WEIGHT_BPS,
+        ) {
+            let curve = WeightedCurve::new(token_a_weight_bps);
+            for (trade_direction, swap_source_amount) in [
+                (TradeDirection::AtoB, swap_token_a_amount),
+                (TradeDirection::BtoA, swap_token_b_amount),
+            ] {
+                let pool_token_amount = curve
+                    .trading_tokens_to_pool_tokens(
+                        source_token_amount,
+                        swap_token_a_amount,
+                        swap_token_b_amount,
+                        pool_token_supply,
+                        trade_direction,
+                        RoundDirection::Floor,
+                    )
+                    .unwrap();
+                check_value_per_pool_token(
+                    curve.weights(trade_direction).0,
+                    swap_source_amount,
+                    swap_source_amount + source_token_amount,
+                    pool_token_supply,
+                    pool_token_supply + pool_token_amount,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw_single_token_type_exact_out(
+            destination_token_amount in 1..u64::MAX as u128,
+            pool_token_supply in 1..u64::MAX as u128,
+            swap_token_a_amount in 1..u64::MAX as u128,
+            swap_token_b_amount in 1..u64::MAX as u128,
+            token_a_weight_bps in MIN_WEIGHT_BPS..=TOTAL_WEIGHT_BPS - MIN_WEIGHT_BPS,
+        ) {
+            let curve = WeightedCurve::new(token_a_weight_bps);
+            for (trade_direction, swap_destination_amount) in [
+                (TradeDirection::AtoB, swap_token_a_amount),
+                (TradeDirection::BtoA, swap_token_b_amount),
+            ] {
+                if destination_token_amount >= swap_destination_amount {
+                    continue;
+                }
+                let pool_token_amount = curve
+                    .withdraw_single_token_type_exact_out(
+                        destination_token_amount,
+                        swap_token_a_amount,
+                        swap_token_b_amount,
+                        pool_token_supply,
+                        trade_direction,
+                        RoundDirection::Ceiling,
+                    )
+                    .unwrap();
+                check_value_per_pool_token(
+                    curve.weights(trade_direction).0,
+                    swap_destination_amount,
+                    swap_destination_amount - destination_token_amount,
+                    pool_token_supply,
+                    pool_token_supply - pool_token_amount,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw_one_token(
+            (pool_token_supply, pool_token_amount) in total_and_intermediate(u64::MAX),
+            swap_token_a_amount in 1..u64::MAX as u128,
+            swap_token_b_amount in 1..u64::MAX as u128,
+            token_a_weight_bps in MIN_WEIGHT_BPS..=TOTAL_WEIGHT_BPS - MIN_WEIGHT_BPS,
+        ) {
+            let curve = WeightedCurve::new(token_a_weight_bps);
+            for (trade_direction, swap_destination_amount) in [
+                (TradeDirection::AtoB, swap_token_a_amount),
+                (TradeDirection::BtoA, swap_token_b_amount),
+            ] {
+                let destination_token_amount = curve
+                    .withdraw_one_token(
+                        pool_token_amount as u128,
+                        swap_token_a_amount,
+                        swap_token_b_amount,
+                        pool_token_supply as u128,
+                        trade_direction,
+                        RoundDirection::Floor,
+                    )
+                    .unwrap();
+                check_value_per_pool_token(
+                    curve.weights(trade_direction).0,
+                    swap_destination_amount,
+                    swap_destination_amount - destination_token_amount,
+                    pool_token_supply as u128,
+                    (pool_token_supply - pool_token_amount) as u128,
+                );
+            }
+        }
+    }
+}
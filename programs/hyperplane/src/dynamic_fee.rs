@@ -0,0 +1,319 @@
+//! Volatility-based trade fees, in the style of the Liquidity Book variable fee
+//!
+//! Before every swap the spot price of the pool is compared to a reference price. The price
+//! movement since the reference, plus the volatility carried over from before it, is the
+//! volatility accumulator, and the trade fee of the swap scales linearly from the minimum fee
+//! without volatility to the maximum fee at `max_volatility_bps`.
+//!
+//! Swaps less than `filter_period_sec` apart keep measuring against the same reference price, so
+//! bursts of swaps accumulate volatility. After a quieter period the reference moves to the
+//! current price, carrying over `reduction_factor_bps` of the accumulated volatility, or none
+//! once `decay_period_sec` has passed without swaps.
+
+use anchor_lang::{
+    err, error,
+    prelude::{borsh, Clock, SolanaSysvar},
+    AnchorDeserialize, AnchorSerialize, Result,
+};
+
+use crate::{
+    curve::{
+        calculator::{CurveCalculator, TradeDirection},
+        fees::Fees,
+    },
+    error::SwapError,
+    require_msg,
+    state::DynamicFee,
+    try_math,
+    twap::{from_words, to_fixed_point, to_words},
+    utils::math::TryMath,
+};
+
+/// Denominator of the dynamic trade fees, a fee of 1_000_000 is 100%
+pub const DYNAMIC_FEE_DENOMINATOR: u64 = 1_000_000;
+
+/// Volatility and the reduction factor are measured in basis points
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Configuration of the dynamic trade fee of a pool
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct DynamicFeeParameters {
+    /// Trade fee without volatility, over `DYNAMIC_FEE_DENOMINATOR`
+    pub min_fee: u64,
+    /// Trade fee at `max_volatility_bps` of volatility, over `DYNAMIC_FEE_DENOMINATOR`
+    pub max_fee: u64,
+    /// Swaps less than this many seconds apart keep accumulating volatility
+    pub filter_period_sec: u64,
+    /// Seconds without swaps after which the accumulated volatility is forgotten
+    pub decay_period_sec: u64,
+    /// Share of the volatility carried over when the reference price moves, in bps
+    pub reduction_factor_bps: u64,
+    /// Volatility at which the trade fee reaches `max_fee`, in bps of price movement
+    pub max_volatility_bps: u64,
+}
+
+impl DynamicFeeParameters {
+    pub fn validate(&self) -> Result<()> {
+        require_msg!(
+            self.min_fee <= self.max_fee && self.max_fee < DYNAMIC_FEE_DENOMINATOR,
+            SwapError::InvalidFee,
+            &format!(
+                "InvalidFee: min_fee={} must be at most max_fee={}, which must be less than {}",
+                self.min_fee, self.max_fee, DYNAMIC_FEE_DENOMINATOR
+            )
+        );
+        require_msg!(
+            self.filter_period_sec <= self.decay_period_sec,
+            SwapError::InvalidFee,
+            &format!(
+                "InvalidFee: filter_period_sec={} > decay_period_sec={}",
+                self.filter_period_sec, self.decay_period_sec
+            )
+        );
+        require_msg!(
+            self.reduction_factor_bps <= BPS_DENOMINATOR,
+            SwapError::InvalidFee,
+            &format!(
+                "InvalidFee: reduction_factor_bps={} > {}",
+                self.reduction_factor_bps, BPS_DENOMINATOR
+            )
+        );
+        if self.max_volatility_bps == 0 {
+            return err!(SwapError::InvalidFee);
+        }
+        Ok(())
+    }
+}
+
+impl DynamicFee {
+    /// Dynamic fee with the given parameters and no accumulated volatility
+    pub fn new(parameters: &DynamicFeeParameters) -> Self {
+        Self {
+            enabled: 1,
+            min_fee: parameters.min_fee,
+            max_fee: parameters.max_fee,
+            filter_period_sec: parameters.filter_period_sec,
+            decay_period_sec: parameters.decay_period_sec,
+            reduction_factor_bps: parameters.reduction_factor_bps,
+            max_volatility_bps: parameters.max_volatility_bps,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled != 0
+    }
+
+    /// The configuration of an enabled dynamic fee
+    pub fn parameters(&self) -> Option<DynamicFeeParameters> {
+        self.is_enabled().then_some(DynamicFeeParameters {
+            min_fee: self.min_fee,
+            max_fee: self.max_fee,
+            filter_period_sec: self.filter_period_sec,
+            decay_period_sec: self.decay_period_sec,
+            reduction_factor_bps: self.reduction_factor_bps,
+            max_volatility_bps: self.max_volatility_bps,
+        })
+    }
+
+    /// Spot price of token A in token B the volatility is measured against, scaled by
+    /// `twap::PRICE_SCALE`
+    pub fn price_reference(&self) -> u128 {
+        from_words(self.price_reference)
+    }
+
+    /// Accumulate the volatility of the pool balances before a swap, as of the on-chain clock
+    pub fn update(
+        &mut self,
+        calculator: &dyn CurveCalculator,
+        pool_token_a_amount: u64,
+        pool_token_b_amount: u64,
+    ) -> Result<()> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        let now = u64::try_from(Clock::get()?.unix_timestamp)
+            .map_err(|_| error!(SwapError::ConversionFailure))?;
+        self.update_at(calculator, pool_token_a_amount, pool_token_b_amount, now)
+    }
+
+    /// Accumulate the volatility of the pool balances before a swap, as of `now`
+    ///
+    /// Nothing is accumulated while either side of the pool is empty, since it has no price.
+    pub fn update_at(
+        &mut self,
+        calculator: &dyn CurveCalculator,
+        pool_token_a_amount: u64,
+        pool_token_b_amount: u64,
+        now: u64,
+    ) -> Result<()> {
+        if !self.is_enabled() || pool_token_a_amount == 0 || pool_token_b_amount == 0 {
+            return Ok(());
+        }
+        let price = to_fixed_point(calculator.spot_price(
+            u128::from(pool_token_a_amount),
+            u128::from(pool_token_b_amount),
+            TradeDirection::AtoB,
+        )?)?;
+
+        let elapsed = now.saturating_sub(self.last_update_ts);
+        if self.price_reference() == 0 || elapsed >= self.filter_period_sec {
+            self.volatility_reference_bps = if elapsed < self.decay_period_sec {
+                try_math!(self
+                    .volatility_accumulator_bps
+                    .try_mul(self.reduction_factor_bps)?
+                    .try_div(BPS_DENOMINATOR))?
+            } else {
+                0
+            };
+            self.price_reference = to_words(price);
+        }
+
+        let price_reference = self.price_reference();
+        if price_reference > 0 {
+            let price_change_bps = try_math!(price
+                .abs_diff(price_reference)
+                .try_mul(u128::from(BPS_DENOMINATOR))?
+                .try_div(price_reference))?;
+            let volatility_bps = u128::from(self.volatility_reference_bps)
+                .saturating_add(price_change_bps)
+                .min(u128::from(self.max_volatility_bps));
+            self.volatility_accumulator_bps =
+                u64::try_from(volatility_bps).map_err(|_| error!(SwapError::ConversionFailure))?;
+        }
+        self.last_update_ts = self.last_update_ts.max(now);
+        Ok(())
+    }
+
+    /// The trade fee at the accumulated volatility, over `DYNAMIC_FEE_DENOMINATOR`
+    pub fn trade_fee_numerator(&self) -> Result<u64> {
+        if self.max_volatility_bps == 0 {
+            return Ok(self.min_fee);
+        }
+        let volatility_bps = self.volatility_accumulator_bps.min(self.max_volatility_bps);
+        let variable_fee = try_math!(u128::from(self.max_fee.saturating_sub(self.min_fee))
+            .try_mul(u128::from(volatility_bps))?
+            .try_div(u128::from(self.max_volatility_bps)))?;
+        let fee = try_math!(u128::from(self.min_fee).try_add(variable_fee))?;
+        u64::try_from(fee).map_err(|_| error!(SwapError::ConversionFailure))
+    }
+
+    /// The pool fees with the trade fee replaced by the dynamic trade fee, when enabled
+    pub fn fees(&self, fees: &Fees) -> Result<Fees> {
+        if !self.is_enabled() {
+            return Ok(*fees);
+        }
+        Ok(Fees {
+            trade_fee_numerator: self.trade_fee_numerator()?,
+            trade_fee_denominator: DYNAMIC_FEE_DENOMINATOR,
+            ..*fees
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::ConstantProductCurve;
+
+    fn parameters() -> DynamicFeeParameters {
+        DynamicFeeParameters {
+            min_fee: 1_000,
+            max_fee: 11_000,
+            filter_period_sec: 30,
+            decay_period_sec: 600,
+            reduction_factor_bps: 5_000,
+            max_volatility_bps: 1_000,
+        }
+    }
+
+    #[test]
+    fn validate_parameters() {
+        parameters().validate().unwrap();
+        for invalid in [
+            DynamicFeeParameters {
+                min_fee: 12_000,
+                ..parameters()
+            },
+            DynamicFeeParameters {
+                max_fee: DYNAMIC_FEE_DENOMINATOR,
+                ..parameters()
+            },
+            DynamicFeeParameters {
+                filter_period_sec: 601,
+                ..parameters()
+            },
+            DynamicFeeParameters {
+                reduction_factor_bps: BPS_DENOMINATOR + 1,
+                ..parameters()
+            },
+            DynamicFeeParameters {
+                max_volatility_bps: 0,
+                ..parameters()
+            },
+        ] {
+            assert_eq!(invalid.validate(), Err(error!(SwapError::InvalidFee)));
+        }
+    }
+
+    #[test]
+    fn disabled_dynamic_fee_keeps_the_pool_fees() {
+        let fees = Fees {
+            trade_fee_numerator: 25,
+            trade_fee_denominator: 10_000,
+            ..Default::default()
+        };
+        let mut dynamic_fee = DynamicFee::default();
+        dynamic_fee
+            .update_at(&ConstantProductCurve::default(), 1_000, 2_000, 1_000)
+            .unwrap();
+        assert_eq!(dynamic_fee, DynamicFee::default());
+        assert_eq!(dynamic_fee.fees(&fees).unwrap(), fees);
+    }
+
+    #[test]
+    fn fee_scales_with_volatility() {
+        let curve = ConstantProductCurve::default();
+        let mut dynamic_fee = DynamicFee::new(&parameters());
+
+        // the first update sets the reference price
+        dynamic_fee
+            .update_at(&curve, 1_000_000, 1_000_000, 1_000)
+            .unwrap();
+        assert_eq!(dynamic_fee.volatility_accumulator_bps, 0);
+        assert_eq!(dynamic_fee.trade_fee_numerator().unwrap(), 1_000);
+
+        // a 2% move within the filter period accumulates 200 bps of volatility
+        dynamic_fee
+            .update_at(&curve, 1_000_000, 1_020_000, 1_010)
+            .unwrap();
+        assert_eq!(dynamic_fee.volatility_accumulator_bps, 200);
+        assert_eq!(dynamic_fee.trade_fee_numerator().unwrap(), 3_000);
+        let fees = dynamic_fee.fees(&Fees::default()).unwrap();
+        assert_eq!(fees.trade_fee_numerator, 3_000);
+        assert_eq!(fees.trade_fee_denominator, DYNAMIC_FEE_DENOMINATOR);
+
+        // the volatility is capped
+        dynamic_fee
+            .update_at(&curve, 1_000_000, 2_000_000, 1_020)
+            .unwrap();
+        assert_eq!(dynamic_fee.volatility_accumulator_bps, 1_000);
+        assert_eq!(dynamic_fee.trade_fee_numerator().unwrap(), 11_000);
+
+        // after the filter period half of the volatility is carried over to the new reference
+        dynamic_fee
+            .update_at(&curve, 1_000_000, 2_000_000, 1_060)
+            .unwrap();
+        assert_eq!(dynamic_fee.volatility_reference_bps, 500);
+        assert_eq!(dynamic_fee.volatility_accumulator_bps, 500);
+        assert_eq!(dynamic_fee.trade_fee_numerator().unwrap(), 6_000);
+
+        // and after the decay period it is forgotten
+        dynamic_fee
+            .update_at(&curve, 1_000_000, 2_000_000, 1_660)
+            .unwrap();
+        assert_eq!(dynamic_fee.volatility_reference_bps, 0);
+        assert_eq!(dynamic_fee.volatility_accumulator_bps, 0);
+        assert_eq!(dynamic_fee.trade_fee_numerator().unwrap(), 1_000);
+    }
+}
@@ -77,6 +77,46 @@ pub enum SwapError {
     InvaliPoolAdmin,
     #[msg("Token 2022 extension is not supported")]
     InvalidTokenExtension,
+    #[msg("Pending pool admin is not correct")]
+    InvalidPendingAdmin,
+    #[msg("The amplification coefficient ramp parameters are invalid")]
+    InvalidAmpRamp,
+    #[msg("The price feed account is missing or invalid")]
+    InvalidPriceFeed,
+    #[msg("The price feed price is stale")]
+    StalePrice,
+    #[msg("The price feed confidence interval is too wide")]
+    PriceConfidenceTooWide,
+    #[msg("The pool still has outstanding pool tokens or protocol fees")]
+    PoolNotEmpty,
+    #[msg("The route of pools to swap through is invalid")]
+    InvalidRoute,
+    #[msg("The global config is invalid")]
+    InvalidGlobalConfig,
+    #[msg("The interest-bearing trading token mint account is missing")]
+    MissingInterestBearingMint,
+    #[msg("The transfer hook program or its extra accounts are missing")]
+    MissingTransferHookAccounts,
+    #[msg("The trading token mints have no token metadata to derive the pool token metadata from")]
+    MissingPoolTokenMetadata,
+    #[msg("Pool token metadata requires a token-2022 pool token mint")]
+    PoolTokenMetadataNotSupported,
+    #[msg("The protocol authority is invalid")]
+    InvalidProtocolAuthority,
+    #[msg("The swap would move the price outside of the concentrated liquidity range")]
+    PriceOutOfRange,
+    #[msg("The number of tokens of the multi-token pool is invalid")]
+    InvalidTokenCount,
+    #[msg("The rate provider of the stable curve is missing or invalid")]
+    InvalidRateProvider,
+    #[msg("The rate provider rate is stale")]
+    StaleRate,
+    #[msg("The protocol fees vaults of the pool are already initialized")]
+    ProtocolFeesVaultsAlreadyInitialized,
+    #[msg("The signer is not the upgrade authority of the program")]
+    InvalidUpgradeAuthority,
+    #[msg("The rate changes more than the maximum rate change per update")]
+    RateChangeTooLarge,
 }
 
 impl From<SwapError> for ProgramError {
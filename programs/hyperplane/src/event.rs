@@ -1,25 +1,75 @@
-use anchor_lang::{event, prelude::borsh, AnchorDeserialize, AnchorSerialize};
+use anchor_lang::{
+    event,
+    prelude::{borsh, Pubkey},
+    AnchorDeserialize, AnchorSerialize,
+};
 
-use crate::state::{UpdatePoolConfigMode, UpdatePoolConfigValue};
+use crate::{
+    dynamic_fee::DynamicFeeParameters,
+    state::{
+        UpdateCurveConfigMode, UpdateCurveConfigValue, UpdateGlobalConfigMode,
+        UpdateGlobalConfigValue, UpdatePoolConfigMode, UpdatePoolConfigValue,
+    },
+};
 
 #[event]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Deposit {
+    /// Amount of token A paid by the user (includes: transfer fees)
     pub token_a_amount: u64,
+    /// Amount of token B paid by the user (includes: transfer fees)
     pub token_b_amount: u64,
     pub pool_token_amount: u64,
 }
 
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DepositSingleTokenType {
+    pub token_amount: u64,
+    pub pool_token_amount: u64,
+    /// The total fees collected (includes owner + trading fees)
+    pub total_fees: u64,
+}
+
 #[event]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Withdraw {
+    /// Amount of token A received by the user (excludes: transfer fees)
     pub token_a_amount: u64,
+    /// Amount of token B received by the user (excludes: transfer fees)
     pub token_b_amount: u64,
     pub pool_token_amount: u64,
+    /// Owner withdraw fee received by the token A fees vault (excludes: transfer fees)
     pub token_a_fees: u64,
+    /// Owner withdraw fee received by the token B fees vault (excludes: transfer fees)
     pub token_b_fees: u64,
 }
 
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawSingleTokenType {
+    pub token_amount: u64,
+    pub pool_token_amount: u64,
+    /// The total fees collected (includes owner + trading fees)
+    pub total_fees: u64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiStableDeposit {
+    /// Amount of each token paid by the user, in pool order
+    pub token_amounts: Vec<u64>,
+    pub pool_token_amount: u64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiStableWithdraw {
+    /// Amount of each token received by the user, in pool order
+    pub token_amounts: Vec<u64>,
+    pub pool_token_amount: u64,
+}
+
 #[event]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Swap {
@@ -27,6 +77,18 @@ pub struct Swap {
     pub token_out_amount: u64,
     /// The total fees collected (includes owner, trading, + host fees)
     pub total_fees: u64,
+    /// Trading fee kept in the pool for liquidity providers
+    pub trade_fee: u64,
+    /// Owner fee received by the pool admin's fees vault (excludes: host and protocol fees)
+    pub owner_fee: u64,
+    /// Portion of the owner fee paid to the host fees account
+    pub host_fee: u64,
+    /// Portion of the owner fee paid to the protocol fees vault
+    pub protocol_fee: u64,
+    /// Effective trade fee fraction of the swap, which varies with volatility when the pool
+    /// has a dynamic fee
+    pub trade_fee_numerator: u64,
+    pub trade_fee_denominator: u64,
 }
 
 #[event]
@@ -35,9 +97,108 @@ pub struct WithdrawFees {
     pub withdraw_amount: u64,
 }
 
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawProtocolFees {
+    pub withdraw_amount: u64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InitializeProtocolFeesVaults {
+    pub token_a_protocol_fees_vault: Pubkey,
+    pub token_b_protocol_fees_vault: Pubkey,
+}
+
 #[event]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct UpdatePoolConfig {
     pub mode: UpdatePoolConfigMode,
+    pub previous_value: UpdatePoolConfigValue,
     pub value: UpdatePoolConfigValue,
 }
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdateCurveConfig {
+    pub mode: UpdateCurveConfigMode,
+    pub previous_value: UpdateCurveConfigValue,
+    pub value: UpdateCurveConfigValue,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdateGlobalConfig {
+    pub mode: UpdateGlobalConfigMode,
+    pub previous_value: UpdateGlobalConfigValue,
+    pub value: UpdateGlobalConfigValue,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProposeNewAdmin {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AcceptAdmin {
+    pub previous_admin: Pubkey,
+    pub admin: Pubkey,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RampAmp {
+    pub initial_amp: u64,
+    pub target_amp: u64,
+    pub ramp_start_ts: u64,
+    pub ramp_end_ts: u64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StopRampAmp {
+    pub amp: u64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SetRateProvider {
+    pub rate_provider_type: u64,
+    pub rate_provider: Pubkey,
+    pub token_b_rate: u64,
+    pub max_rate_age_sec: u64,
+    pub max_rate_change_bps: u64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdateRate {
+    pub previous_rate: u64,
+    pub token_b_rate: u64,
+    pub rate_updated_ts: u64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdateDynamicFee {
+    /// Previous configuration, `None` if the dynamic fee was disabled
+    pub previous_parameters: Option<DynamicFeeParameters>,
+    /// New configuration, `None` disables the dynamic fee
+    pub parameters: Option<DynamicFeeParameters>,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClosePool {
+    /// Token A left in the trading vault swept to the admin
+    pub token_a_amount: u64,
+    /// Token B left in the trading vault swept to the admin
+    pub token_b_amount: u64,
+    /// Token A fees swept from the fees vault to the admin
+    pub token_a_fees: u64,
+    /// Token B fees swept from the fees vault to the admin
+    pub token_b_fees: u64,
+}
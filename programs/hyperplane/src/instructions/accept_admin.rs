@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::{emitted, error::SwapError, event, state::SwapPool};
+
+pub fn handler_accept_admin(ctx: Context<AcceptAdmin>) -> Result<event::AcceptAdmin> {
+    let pool = &mut ctx.accounts.pool.load_mut()?;
+
+    let previous_admin = pool.admin;
+    msg!(
+        "Accepting pool admin: previous_admin={}, admin={}",
+        previous_admin,
+        pool.pending_admin
+    );
+    pool.admin = pool.pending_admin;
+    pool.pending_admin = Pubkey::default();
+
+    emitted!(event::AcceptAdmin {
+        previous_admin,
+        admin: pool.admin,
+    });
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(mut)]
+    pub pending_admin: Signer<'info>,
+
+    #[account(mut,
+        has_one = pending_admin @ SwapError::InvalidPendingAdmin,
+    )]
+    pub pool: AccountLoader<'info, SwapPool>,
+}
@@ -0,0 +1,338 @@
+use anchor_lang::{
+    accounts::{interface::Interface, interface_account::InterfaceAccount},
+    prelude::*,
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    emitted,
+    error::SwapError,
+    event, require_msg,
+    state::{SwapPool, SwapState},
+    utils::{instructions::close_program_account, pool_token, swap_token},
+};
+
+/// Close a pool without outstanding pool tokens, sweeping any remaining fees to the admin and
+/// returning the rent of the pool, curve and vault accounts to the admin.
+///
+/// Once the pool token supply is zero, whatever is left in the trading vaults (rounding dust,
+/// tokens sent directly to the vaults) is swept to the admin along with the fees.
+///
+/// The protocol fees belong to the protocol authority, so they must be withdrawn with
+/// `withdraw_protocol_fees` before the pool can be closed. Pools whose protocol fees vaults were
+/// never initialized are closed without them.
+///
+/// Token-2022 pool token mints are closed by the pool authority, their mint close authority.
+/// Spl token pool token mints cannot be closed, so they are left behind with a supply of zero,
+/// as are token-2022 pool token mints created without a mint close authority.
+pub fn handler_close_pool<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClosePool<'info>>,
+) -> Result<event::ClosePool> {
+    let pool = ctx.accounts.pool.load()?;
+    utils::validate_protocol_fees_vaults(&ctx, &pool)?;
+
+    msg!(
+        "Close pool inputs: pool_token_supply={}, token_a_balance={}, token_b_balance={}, token_a_fees={}, token_b_fees={}",
+        ctx.accounts.pool_token_mint.supply,
+        ctx.accounts.token_a_vault.amount,
+        ctx.accounts.token_b_vault.amount,
+        ctx.accounts.token_a_fees_vault.amount,
+        ctx.accounts.token_b_fees_vault.amount,
+    );
+    require_msg!(
+        ctx.accounts.pool_token_mint.supply == 0,
+        SwapError::PoolNotEmpty,
+        &format!(
+            "PoolNotEmpty: pool_token_supply={}",
+            ctx.accounts.pool_token_mint.supply
+        )
+    );
+    let token_a_protocol_fees = ctx
+        .accounts
+        .token_a_protocol_fees_vault
+        .as_ref()
+        .map_or(0, |vault| vault.amount);
+    let token_b_protocol_fees = ctx
+        .accounts
+        .token_b_protocol_fees_vault
+        .as_ref()
+        .map_or(0, |vault| vault.amount);
+    require_msg!(
+        token_a_protocol_fees == 0 && token_b_protocol_fees == 0,
+        SwapError::PoolNotEmpty,
+        &format!(
+            "PoolNotEmpty: token_a_protocol_fees={}, token_b_protocol_fees={}",
+            token_a_protocol_fees, token_b_protocol_fees
+        )
+    );
+
+    let token_a_amount = ctx.accounts.token_a_vault.amount;
+    let token_b_amount = ctx.accounts.token_b_vault.amount;
+    let token_a_fees = ctx.accounts.token_a_fees_vault.amount;
+    let token_b_fees = ctx.accounts.token_b_fees_vault.amount;
+    for (vault, amount, mint, admin_ata, token_program) in [
+        (
+            &ctx.accounts.token_a_vault,
+            token_a_amount,
+            &ctx.accounts.token_a_mint,
+            &ctx.accounts.admin_token_a_ata,
+            &ctx.accounts.token_a_token_program,
+        ),
+        (
+            &ctx.accounts.token_b_vault,
+            token_b_amount,
+            &ctx.accounts.token_b_mint,
+            &ctx.accounts.admin_token_b_ata,
+            &ctx.accounts.token_b_token_program,
+        ),
+        (
+            &ctx.accounts.token_a_fees_vault,
+            token_a_fees,
+            &ctx.accounts.token_a_mint,
+            &ctx.accounts.admin_token_a_ata,
+            &ctx.accounts.token_a_token_program,
+        ),
+        (
+            &ctx.accounts.token_b_fees_vault,
+            token_b_fees,
+            &ctx.accounts.token_b_mint,
+            &ctx.accounts.admin_token_b_ata,
+            &ctx.accounts.token_b_token_program,
+        ),
+    ] {
+        if amount > 0 {
+            swap_token::transfer_from_vault(
+                token_program.to_account_info(),
+                ctx.accounts.pool.to_account_info(),
+                vault.to_account_info(),
+                mint.to_account_info(),
+                admin_ata.to_account_info(),
+                ctx.accounts.pool_authority.to_account_info(),
+                ctx.remaining_accounts,
+                pool.bump_seed(),
+                amount,
+                mint.decimals,
+            )?;
+        }
+    }
+
+    let mut vaults = vec![
+        (
+            ctx.accounts.token_a_vault.to_account_info(),
+            ctx.accounts.token_a_mint.to_account_info(),
+            ctx.accounts.token_a_token_program.to_account_info(),
+        ),
+        (
+            ctx.accounts.token_b_vault.to_account_info(),
+            ctx.accounts.token_b_mint.to_account_info(),
+            ctx.accounts.token_b_token_program.to_account_info(),
+        ),
+        (
+            ctx.accounts.token_a_fees_vault.to_account_info(),
+            ctx.accounts.token_a_mint.to_account_info(),
+            ctx.accounts.token_a_token_program.to_account_info(),
+        ),
+        (
+            ctx.accounts.token_b_fees_vault.to_account_info(),
+            ctx.accounts.token_b_mint.to_account_info(),
+            ctx.accounts.token_b_token_program.to_account_info(),
+        ),
+    ];
+    if let Some(vault) = &ctx.accounts.token_a_protocol_fees_vault {
+        vaults.push((
+            vault.to_account_info(),
+            ctx.accounts.token_a_mint.to_account_info(),
+            ctx.accounts.token_a_token_program.to_account_info(),
+        ));
+    }
+    if let Some(vault) = &ctx.accounts.token_b_protocol_fees_vault {
+        vaults.push((
+            vault.to_account_info(),
+            ctx.accounts.token_b_mint.to_account_info(),
+            ctx.accounts.token_b_token_program.to_account_info(),
+        ));
+    }
+    for (vault, mint, token_program) in vaults {
+        swap_token::close_vault(
+            token_program,
+            ctx.accounts.pool.to_account_info(),
+            vault,
+            mint,
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.pool_authority.to_account_info(),
+            pool.bump_seed(),
+        )?;
+    }
+
+    if utils::is_pool_token_mint_closable(&ctx)? {
+        pool_token::close_mint(
+            ctx.accounts.pool_token_program.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.pool_token_mint.to_account_info(),
+            ctx.accounts.admin.to_account_info(),
+            ctx.accounts.pool_authority.to_account_info(),
+            pool.bump_seed(),
+        )?;
+    }
+
+    close_program_account(
+        &ctx.accounts.swap_curve.to_account_info(),
+        &ctx.accounts.admin.to_account_info(),
+    )?;
+
+    // the pool account itself is closed by the anchor `close` constraint on exit
+    emitted!(event::ClosePool {
+        token_a_amount,
+        token_b_amount,
+        token_a_fees,
+        token_b_fees,
+    });
+}
+
+#[derive(Accounts)]
+pub struct ClosePool<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut,
+        close = admin,
+        has_one = admin,
+        has_one = swap_curve,
+        has_one = pool_authority @ SwapError::InvalidProgramAddress,
+        has_one = token_a_mint,
+        has_one = token_b_mint,
+        has_one = token_a_vault @ SwapError::IncorrectSwapAccount,
+        has_one = token_b_vault @ SwapError::IncorrectSwapAccount,
+        has_one = pool_token_mint @ SwapError::IncorrectPoolMint,
+        has_one = token_a_fees_vault @ SwapError::IncorrectFeeAccount,
+        has_one = token_b_fees_vault @ SwapError::IncorrectFeeAccount,
+    )]
+    pub pool: AccountLoader<'info, SwapPool>,
+
+    /// CHECK: has_one constraint on the pool
+    #[account(mut)]
+    pub swap_curve: UncheckedAccount<'info>,
+
+    /// CHECK: has_one constraint on the pool
+    pub pool_authority: AccountInfo<'info>,
+
+    /// Mutable to harvest any token 2022 transfer fees withheld in the vaults
+    /// CHECK: has_one constraint on the pool
+    #[account(mut)]
+    pub token_a_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Mutable to harvest any token 2022 transfer fees withheld in the vaults
+    /// CHECK: has_one constraint on the pool
+    #[account(mut)]
+    pub token_b_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: has_one constraint on the pool
+    #[account(mut,
+        token::token_program = token_a_token_program,
+    )]
+    pub token_a_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: has_one constraint on the pool
+    #[account(mut,
+        token::token_program = token_b_token_program,
+    )]
+    pub token_b_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Mutable to close token-2022 pool token mints
+    /// CHECK: has_one constraint on the pool
+    #[account(mut,
+        mint::token_program = pool_token_program,
+    )]
+    pub pool_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: has_one constraint on the pool
+    #[account(mut,
+        token::token_program = token_a_token_program,
+    )]
+    pub token_a_fees_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: has_one constraint on the pool
+    #[account(mut,
+        token::token_program = token_b_token_program,
+    )]
+    pub token_b_fees_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// None if the pool's protocol fees vaults were never initialized
+    /// CHECK: checked in the handler
+    #[account(mut,
+        token::token_program = token_a_token_program,
+    )]
+    pub token_a_protocol_fees_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// None if the pool's protocol fees vaults were never initialized
+    /// CHECK: checked in the handler
+    #[account(mut,
+        token::token_program = token_b_token_program,
+    )]
+    pub token_b_protocol_fees_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Admin's token A account to sweep the remaining token A balance and fees to
+    #[account(mut,
+        token::mint = token_a_mint,
+        token::authority = admin,
+        token::token_program = token_a_token_program,
+    )]
+    pub admin_token_a_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Admin's token B account to sweep the remaining token B balance and fees to
+    #[account(mut,
+        token::mint = token_b_mint,
+        token::authority = admin,
+        token::token_program = token_b_token_program,
+    )]
+    pub admin_token_b_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token program for the token A mint
+    pub token_a_token_program: Interface<'info, TokenInterface>,
+    /// Token program for the token B mint
+    pub token_b_token_program: Interface<'info, TokenInterface>,
+    /// Token program for the pool token mint
+    pub pool_token_program: Interface<'info, TokenInterface>,
+}
+
+mod utils {
+    use anchor_spl::token_2022::spl_token_2022::{
+        self, extension::mint_close_authority::MintCloseAuthority,
+    };
+
+    use super::*;
+    use crate::{curve::calculator::AorB, utils::token_extensions};
+
+    /// Token-2022 pool token mints created with the pool authority as mint close authority
+    pub fn is_pool_token_mint_closable(ctx: &Context<ClosePool>) -> Result<bool> {
+        if ctx.accounts.pool_token_program.key() != spl_token_2022::id() {
+            return Ok(false);
+        }
+        let mint_data = ctx.accounts.pool_token_mint.to_account_info();
+        let close_authority =
+            token_extensions::mint_extension::<MintCloseAuthority>(&mint_data.data.borrow())?
+                .and_then(|extension| Option::<Pubkey>::from(extension.close_authority));
+        Ok(close_authority == Some(ctx.accounts.pool_authority.key()))
+    }
+
+    /// The protocol fees vaults must be given exactly when the pool has them
+    pub fn validate_protocol_fees_vaults(ctx: &Context<ClosePool>, pool: &SwapPool) -> Result<()> {
+        for (a_or_b, vault) in [
+            (AorB::A, &ctx.accounts.token_a_protocol_fees_vault),
+            (AorB::B, &ctx.accounts.token_b_protocol_fees_vault),
+        ] {
+            let vault = vault.as_ref().map(|vault| vault.key());
+            require_msg!(
+                vault == pool.protocol_fees_vault(a_or_b),
+                SwapError::IncorrectFeeAccount,
+                &format!(
+                    "IncorrectFeeAccount: protocol_fees_vault.key ({:?}) != pool protocol_fees_vault.key ({:?}), a_or_b={:?}",
+                    vault,
+                    pool.protocol_fees_vault(a_or_b),
+                    a_or_b,
+                )
+            );
+        }
+        Ok(())
+    }
+}
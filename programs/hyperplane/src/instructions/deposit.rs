@@ -6,23 +6,23 @@ use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 use crate::{
     curve,
-    curve::{base::SwapCurve, calculator::RoundDirection},
+    curve::base::SwapCurve,
     deposit::utils::validate_inputs,
     emitted,
     error::SwapError,
     event, require_msg,
     state::{SwapPool, SwapState},
-    to_u64,
-    utils::{pool_token, swap_token},
+    swap::utils as swap_utils,
+    utils::{pool_token, pool_transfers::LiquidityParams, swap_token},
 };
 
-pub fn handler_deposit(
-    ctx: Context<Deposit>,
+pub fn handler_deposit<'info>(
+    ctx: Context<'_, '_, '_, 'info, Deposit<'info>>,
     pool_token_amount: u64,
     maximum_token_a_amount: u64,
     maximum_token_b_amount: u64,
 ) -> Result<event::Deposit> {
-    let pool = ctx.accounts.pool.load()?;
+    let mut pool = ctx.accounts.pool.load_mut()?;
     validate_inputs(&ctx, &pool)?;
     msg!(
         "Deposit inputs: maximum_token_a_amount={}, maximum_token_b_amount={}, pool_token_amount={}",
@@ -30,12 +30,19 @@ pub fn handler_deposit(
         maximum_token_b_amount,
         pool_token_amount,
     );
-    let swap_curve = curve!(ctx.accounts.swap_curve, pool);
-
-    let calculator = &swap_curve.calculator;
-    require!(
-        calculator.allows_deposits(),
-        SwapError::UnsupportedCurveOperation
+    let swap_curve = curve!(ctx.accounts.swap_curve, pool, ctx.remaining_accounts)
+        .with_pool_interest_bearing_mints(
+            &pool,
+            &[
+                &ctx.accounts.token_a_mint.to_account_info(),
+                &ctx.accounts.token_b_mint.to_account_info(),
+            ],
+            ctx.remaining_accounts,
+        )?;
+    pool.price_accumulator.update_best_effort(
+        swap_curve.calculator.as_ref(),
+        ctx.accounts.token_a_vault.amount,
+        ctx.accounts.token_b_vault.amount,
     );
 
     msg!(
@@ -45,66 +52,47 @@ pub fn handler_deposit(
         ctx.accounts.token_b_vault.amount,
         ctx.accounts.pool_token_mint.supply,
     );
-
-    let current_pool_mint_supply = u128::from(ctx.accounts.pool_token_mint.supply);
-    let (pool_token_amount, pool_mint_supply) = if current_pool_mint_supply > 0 {
-        (u128::from(pool_token_amount), current_pool_mint_supply)
-    } else {
-        (calculator.new_pool_supply(), calculator.new_pool_supply())
-    };
-
-    let results = calculator
-        .pool_tokens_to_trading_tokens(
-            pool_token_amount,
-            pool_mint_supply,
-            u128::from(ctx.accounts.token_a_vault.amount),
-            u128::from(ctx.accounts.token_b_vault.amount),
-            RoundDirection::Ceiling,
-        )
-        .map_err(|_| error!(SwapError::ZeroTradingTokens))?;
-
-    let token_a_amount = to_u64!(results.token_a_amount)?;
-    let token_b_amount = to_u64!(results.token_b_amount)?;
-    let pool_token_amount = to_u64!(pool_token_amount)?;
+    let transfers = LiquidityParams {
+        calculator: swap_curve.calculator.as_ref(),
+        fees: pool.fees(),
+        token_a_vault_amount: ctx.accounts.token_a_vault.amount,
+        token_b_vault_amount: ctx.accounts.token_b_vault.amount,
+        pool_token_supply: ctx.accounts.pool_token_mint.supply,
+        token_a_transfer_fee: swap_utils::mint_transfer_fee(
+            &ctx.accounts.token_a_mint.to_account_info(),
+        )?,
+        token_b_transfer_fee: swap_utils::mint_transfer_fee(
+            &ctx.accounts.token_b_mint.to_account_info(),
+        )?,
+    }
+    .deposit(pool_token_amount)?;
+    let token_a_amount_to_vault = transfers.token_a_amount;
+    let token_b_amount_to_vault = transfers.token_b_amount;
+    let pool_token_amount = transfers.pool_token_amount;
 
     msg!(
-        "Deposit outputs: token_a_to_deposit={}, token_b_to_deposit={}, pool_tokens_to_mint={}",
-        token_a_amount,
-        token_b_amount,
+        "Deposit outputs: token_a_to_vault={}, token_b_to_vault={}, token_a_received={}, token_b_received={}, pool_tokens_to_mint={}",
+        token_a_amount_to_vault,
+        token_b_amount_to_vault,
+        transfers.token_a_amount_received,
+        transfers.token_b_amount_received,
         pool_token_amount,
     );
 
     require_msg!(
-        token_a_amount <= maximum_token_a_amount,
+        token_a_amount_to_vault <= maximum_token_a_amount,
         SwapError::ExceededSlippage,
         &format!(
-            "ExceededSlippage: token_a_amount={} > maximum_token_a_amount={}",
-            token_a_amount, maximum_token_a_amount
+            "ExceededSlippage: token_a_amount_to_vault={} > maximum_token_a_amount={}",
+            token_a_amount_to_vault, maximum_token_a_amount
         )
     );
     require_msg!(
-        token_a_amount > 0,
-        SwapError::ZeroTradingTokens,
-        &format!(
-            "Amount of pool tokens being minted is less 0 token a in value: token_a_amount={}",
-            token_a_amount
-        )
-    );
-
-    require_msg!(
-        token_b_amount <= maximum_token_b_amount,
+        token_b_amount_to_vault <= maximum_token_b_amount,
         SwapError::ExceededSlippage,
         &format!(
-            "ExceededSlippage: token_b_amount={} > maximum_token_b_amount={}",
-            token_b_amount, maximum_token_b_amount
-        )
-    );
-    require_msg!(
-        token_b_amount > 0,
-        SwapError::ZeroTradingTokens,
-        &format!(
-            "Amount of pool tokens being minted is less 0 token b in value: token_b_amount={}",
-            token_b_amount
+            "ExceededSlippage: token_b_amount_to_vault={} > maximum_token_b_amount={}",
+            token_b_amount_to_vault, maximum_token_b_amount
         )
     );
 
@@ -114,7 +102,8 @@ pub fn handler_deposit(
         ctx.accounts.token_a_mint.to_account_info(),
         ctx.accounts.token_a_vault.to_account_info(),
         ctx.accounts.signer.to_account_info(),
-        token_a_amount,
+        ctx.remaining_accounts,
+        token_a_amount_to_vault,
         ctx.accounts.token_a_mint.decimals,
     )?;
     swap_token::transfer_from_user(
@@ -123,7 +112,8 @@ pub fn handler_deposit(
         ctx.accounts.token_b_mint.to_account_info(),
         ctx.accounts.token_b_vault.to_account_info(),
         ctx.accounts.signer.to_account_info(),
-        token_b_amount,
+        ctx.remaining_accounts,
+        token_b_amount_to_vault,
         ctx.accounts.token_b_mint.decimals,
     )?;
 
@@ -138,8 +128,8 @@ pub fn handler_deposit(
     )?;
 
     emitted!(event::Deposit {
-        token_a_amount,
-        token_b_amount,
+        token_a_amount: token_a_amount_to_vault,
+        token_b_amount: token_b_amount_to_vault,
         pool_token_amount,
     });
 }
@@ -216,11 +206,9 @@ pub struct Deposit<'info> {
 }
 
 mod utils {
-    use std::cell::Ref;
-
     use super::*;
 
-    pub fn validate_inputs(ctx: &Context<Deposit>, pool: &Ref<SwapPool>) -> Result<()> {
+    pub fn validate_inputs(ctx: &Context<Deposit>, pool: &SwapPool) -> Result<()> {
         require_msg!(
             !pool.withdrawals_only(),
             SwapError::WithdrawalsOnlyMode,
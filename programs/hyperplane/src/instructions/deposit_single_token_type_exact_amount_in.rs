@@ -0,0 +1,294 @@
+use anchor_lang::{
+    accounts::{interface::Interface, interface_account::InterfaceAccount},
+    prelude::*,
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    curve,
+    curve::{base::SwapCurve, calculator::TradeDirection},
+    deposit_single_token_type_exact_amount_in::utils::validate_inputs,
+    emitted,
+    error::SwapError,
+    event, require_msg,
+    state::{SwapPool, SwapState},
+    swap::utils as swap_utils,
+    to_u64,
+    utils::{pool_token, swap_token},
+};
+
+pub fn handler_deposit_single_token_type_exact_amount_in<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositSingleTokenType<'info>>,
+    source_token_amount: u64,
+    minimum_pool_token_amount: u64,
+) -> Result<event::DepositSingleTokenType> {
+    let mut pool = ctx.accounts.pool.load_mut()?;
+    let trade_direction = validate_inputs(&ctx, &pool)?;
+    let swap_curve = curve!(ctx.accounts.swap_curve, pool, ctx.remaining_accounts)
+        .with_pool_interest_bearing_mints(
+            &pool,
+            &[&ctx.accounts.source_mint.to_account_info()],
+            ctx.remaining_accounts,
+        )?;
+    pool.price_accumulator.update(
+        swap_curve.calculator.as_ref(),
+        ctx.accounts.token_a_vault.amount,
+        ctx.accounts.token_b_vault.amount,
+    )?;
+    pool.dynamic_fee.update(
+        swap_curve.calculator.as_ref(),
+        ctx.accounts.token_a_vault.amount,
+        ctx.accounts.token_b_vault.amount,
+    )?;
+    let fees = pool.dynamic_fee.fees(&pool.fees)?;
+
+    let calculator = &swap_curve.calculator;
+    require!(
+        calculator.allows_deposits(),
+        SwapError::UnsupportedCurveOperation
+    );
+
+    let pool_token_supply = u128::from(ctx.accounts.pool_token_mint.supply);
+    require_msg!(
+        pool_token_supply > 0,
+        SwapError::EmptySupply,
+        "Single sided deposits require a pool with a non-zero supply"
+    );
+
+    // Take transfer fees into account for actual amount transferred in
+    let actual_source_token_amount = swap_utils::sub_input_transfer_fees(
+        &ctx.accounts.source_mint.to_account_info(),
+        &fees,
+        source_token_amount,
+        false,
+        None,
+    )?;
+
+    msg!(
+        "Deposit single token type inputs: trade_direction={:?}, source_token_amount={}, actual_source_token_amount={}, minimum_pool_token_amount={}",
+        trade_direction,
+        source_token_amount,
+        actual_source_token_amount,
+        minimum_pool_token_amount,
+    );
+    msg!(
+        "Swap pool inputs: swap_type={:?}, token_a_balance={}, token_b_balance={}, pool_token_supply={}",
+        swap_curve.curve_type,
+        ctx.accounts.token_a_vault.amount,
+        ctx.accounts.token_b_vault.amount,
+        pool_token_supply,
+    );
+
+    let result = swap_curve
+        .deposit_single_token_type(
+            u128::from(actual_source_token_amount),
+            u128::from(ctx.accounts.token_a_vault.amount),
+            u128::from(ctx.accounts.token_b_vault.amount),
+            pool_token_supply,
+            trade_direction,
+            &fees,
+        )
+        .map_err(|_| error!(SwapError::ZeroTradingTokens))?;
+
+    let pool_token_amount = to_u64!(result.pool_token_amount)?;
+    // Add transfer fees to each of the input transfers, so the vault and fee accounts receive the curve amounts
+    let source_amount_to_vault = swap_utils::add_inverse_transfer_fee(
+        &ctx.accounts.source_mint.to_account_info(),
+        to_u64!(result.source_amount_to_vault)?,
+    )?;
+    let owner_fee = if result.owner_fee > 0 {
+        swap_utils::add_inverse_transfer_fee(
+            &ctx.accounts.source_mint.to_account_info(),
+            to_u64!(result.owner_fee)?,
+        )?
+    } else {
+        0
+    };
+
+    msg!(
+        "Deposit single token type result: source_amount_to_vault={}, trade_fee={}, owner_fee={}, pool_token_amount={}",
+        source_amount_to_vault,
+        result.trade_fee,
+        owner_fee,
+        pool_token_amount,
+    );
+    require_msg!(
+        pool_token_amount >= minimum_pool_token_amount,
+        SwapError::ExceededSlippage,
+        &format!(
+            "ExceededSlippage: pool_token_amount={} < minimum_pool_token_amount={}",
+            pool_token_amount, minimum_pool_token_amount
+        )
+    );
+    require_msg!(
+        pool_token_amount > 0,
+        SwapError::ZeroTradingTokens,
+        "Amount of pool tokens being minted is 0"
+    );
+
+    let source_vault = match trade_direction {
+        TradeDirection::AtoB => &ctx.accounts.token_a_vault,
+        TradeDirection::BtoA => &ctx.accounts.token_b_vault,
+    };
+    swap_token::transfer_from_user(
+        ctx.accounts.source_token_program.to_account_info(),
+        ctx.accounts.source_user_ata.to_account_info(),
+        ctx.accounts.source_mint.to_account_info(),
+        source_vault.to_account_info(),
+        ctx.accounts.signer.to_account_info(),
+        ctx.remaining_accounts,
+        source_amount_to_vault,
+        ctx.accounts.source_mint.decimals,
+    )?;
+
+    if owner_fee > 0 {
+        swap_token::transfer_from_user(
+            ctx.accounts.source_token_program.to_account_info(),
+            ctx.accounts.source_user_ata.to_account_info(),
+            ctx.accounts.source_mint.to_account_info(),
+            ctx.accounts.source_token_fees_vault.to_account_info(),
+            ctx.accounts.signer.to_account_info(),
+            ctx.remaining_accounts,
+            owner_fee,
+            ctx.accounts.source_mint.decimals,
+        )?;
+    }
+
+    pool_token::mint(
+        ctx.accounts.pool_token_program.to_account_info(),
+        ctx.accounts.pool.to_account_info(),
+        ctx.accounts.pool_token_mint.to_account_info(),
+        ctx.accounts.pool_authority.to_account_info(),
+        pool.bump_seed(),
+        ctx.accounts.pool_token_user_ata.to_account_info(),
+        pool_token_amount,
+    )?;
+
+    let total_fees = to_u64!(result.total_fees)?;
+
+    msg!(
+        "Deposit single token type outputs: token_amount={}, pool_token_amount={}, total_fees={}",
+        source_amount_to_vault,
+        pool_token_amount,
+        total_fees,
+    );
+    emitted!(event::DepositSingleTokenType {
+        token_amount: source_amount_to_vault,
+        pool_token_amount,
+        total_fees,
+    });
+}
+
+#[derive(Accounts)]
+pub struct DepositSingleTokenType<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(mut,
+        has_one = swap_curve,
+        has_one = pool_authority @ SwapError::InvalidProgramAddress,
+        has_one = token_a_vault @ SwapError::IncorrectSwapAccount,
+        has_one = token_b_vault @ SwapError::IncorrectSwapAccount,
+        has_one = pool_token_mint @ SwapError::IncorrectPoolMint,
+    )]
+    pub pool: AccountLoader<'info, SwapPool>,
+
+    /// CHECK: has_one constraint on the pool
+    pub swap_curve: UncheckedAccount<'info>,
+
+    /// CHECK: has_one constraint on the pool
+    pub pool_authority: AccountInfo<'info>,
+
+    /// CHECK: checked in the handler
+    pub source_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: has_one constraint on the pool
+    #[account(mut)]
+    pub token_a_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: has_one constraint on the pool
+    #[account(mut)]
+    pub token_b_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Account to collect fees into
+    /// CHECK: checked in the handler
+    #[account(mut)]
+    pub source_token_fees_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: has_one constraint on the pool
+    #[account(mut)]
+    pub pool_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Signer's source token account
+    #[account(mut,
+        token::mint = source_mint,
+        token::token_program = source_token_program,
+    )]
+    pub source_user_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Signer's pool token account
+    // note - authority constraint repeated for clarity
+    #[account(mut,
+        token::mint = pool_token_mint,
+        token::authority = source_user_ata.owner,
+        token::token_program = pool_token_program,
+    )]
+    pub pool_token_user_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token program for the pool token mint
+    pub pool_token_program: Interface<'info, TokenInterface>,
+    /// Token program for the source mint
+    pub source_token_program: Interface<'info, TokenInterface>,
+}
+
+mod utils {
+    use super::*;
+
+    pub fn validate_inputs(
+        ctx: &Context<DepositSingleTokenType>,
+        pool: &SwapPool,
+    ) -> Result<TradeDirection> {
+        require_msg!(
+            !pool.withdrawals_only(),
+            SwapError::WithdrawalsOnlyMode,
+            "The pool is in withdrawals only mode"
+        );
+        let (trade_direction, source_vault, source_token_fees_vault) =
+            if ctx.accounts.source_mint.key() == pool.token_a_mint {
+                (
+                    TradeDirection::AtoB,
+                    pool.token_a_vault,
+                    pool.token_a_fees_vault,
+                )
+            } else if ctx.accounts.source_mint.key() == pool.token_b_mint {
+                (
+                    TradeDirection::BtoA,
+                    pool.token_b_vault,
+                    pool.token_b_fees_vault,
+                )
+            } else {
+                return err!(SwapError::IncorrectSwapAccount);
+            };
+
+        require_msg!(
+            ctx.accounts.source_token_fees_vault.key() == source_token_fees_vault,
+            SwapError::IncorrectSwapAccount,
+            &format!(
+                "IncorrectSwapAccount: source_token_fees_vault.key ({}) != fees_vault.key ({})",
+                ctx.accounts.source_token_fees_vault.key(),
+                source_token_fees_vault
+            )
+        );
+        require_msg!(
+            ctx.accounts.source_user_ata.key() != source_vault,
+            SwapError::IncorrectSwapAccount,
+            &format!(
+                "IncorrectSwapAccount: source_user_ata.key ({}) == source_vault.key ({})",
+                ctx.accounts.source_user_ata.key(),
+                source_vault
+            )
+        );
+
+        Ok(trade_direction)
+    }
+}
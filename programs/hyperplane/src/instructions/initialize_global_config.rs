@@ -0,0 +1,60 @@
+use anchor_lang::{prelude::*, solana_program::bpf_loader_upgradeable};
+
+use crate::{curve::fees::Fees, error::SwapError, state::GlobalConfig, utils::seeds};
+
+/// Create the program wide global config, `owner` becomes its owner and protocol authority.
+///
+/// There is a single global config per program and pools cannot be created until it
+/// exists, so it is initialized by the upgrade authority as part of deploying the program. The
+/// owner is separate so the deployment key does not have to own the program wide constraints.
+pub fn handler_initialize_global_config(
+    ctx: Context<InitializeGlobalConfig>,
+    owner: Pubkey,
+    min_fees: Fees,
+    valid_curve_types: Vec<u64>,
+    blocked_token_extensions: Vec<u16>,
+) -> Result<()> {
+    msg!(
+        "Initialize global config: owner={}, min_fees={:?}, valid_curve_types={:?}, blocked_token_extensions={:?}",
+        owner,
+        min_fees,
+        valid_curve_types,
+        blocked_token_extensions
+    );
+    min_fees.validate()?;
+
+    let global_config = &mut ctx.accounts.global_config.load_init()?;
+    global_config.owner = owner;
+    global_config.protocol_authority = owner;
+    global_config.min_fees = min_fees;
+    global_config.set_valid_curve_types(&valid_curve_types)?;
+    global_config.set_blocked_token_extensions(&blocked_token_extensions)?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeGlobalConfig<'info> {
+    #[account(mut)]
+    pub upgrade_authority: Signer<'info>,
+
+    #[account(init,
+        seeds = [seeds::GLOBAL_CONFIG],
+        bump,
+        payer = upgrade_authority,
+        space = GlobalConfig::LEN,
+    )]
+    pub global_config: AccountLoader<'info, GlobalConfig>,
+
+    /// Program data account of the program, only its upgrade authority can initialize the global
+    /// config so it cannot be taken over by front-running the deployment
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key()) @ SwapError::InvalidUpgradeAuthority,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,335 @@
+use anchor_lang::{
+    accounts::{interface::Interface, interface_account::InterfaceAccount},
+    prelude::*,
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    curve::{
+        base::CurveType,
+        calculator::INITIAL_SWAP_POOL_AMOUNT,
+        fees::Fees,
+        multi_stable::{MultiStableCurve, MAX_TOKENS, MIN_TOKENS},
+    },
+    error::SwapError,
+    initialize_multi_stable_pool::utils::parse_tokens,
+    require_msg,
+    state::{GlobalConfig, MultiStablePool, MultiStableToken},
+    to_u64,
+    utils::{pool_pda::create_pool_token_account, pool_token, seeds, swap_token, transfer_fee},
+};
+
+/// Remaining accounts of each trading token: mint, vault, fees vault, protocol fees vault, admin
+/// token account and token program
+pub const INIT_MULTI_STABLE_TOKEN_ACCOUNTS: usize = 6;
+
+pub fn handler_initialize_multi_stable_pool<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitializeMultiStablePool<'info>>,
+    amp: u64,
+    fees: Fees,
+    initial_supplies: Vec<u64>,
+) -> Result<()> {
+    let tokens = parse_tokens(&ctx, initial_supplies.len())?;
+
+    let decimals: Vec<u8> = tokens.iter().map(|token| token.mint.decimals).collect();
+    let curve = MultiStableCurve::new(amp, &decimals)?;
+    msg!(
+        "Initialize parameters: curve={:?}, initial_supplies={:?}",
+        curve,
+        initial_supplies
+    );
+
+    {
+        let global_config = ctx.accounts.global_config.load()?;
+        global_config.validate_curve(CurveType::Stable)?;
+        global_config.validate_fees(&fees)?;
+        for token in tokens.iter() {
+            let mint = token.mint.to_account_info();
+            global_config.validate_token_2022_trading_token_extensions(&mint)?;
+            // the vault balances are the curve balances, which transfer fees would not match
+            require_msg!(
+                transfer_fee::transfer_fee_config(&mint.data.borrow())?.is_none(),
+                SwapError::InvalidTokenExtension,
+                &format!(
+                    "InvalidTokenExtension: mint {} has a transfer fee",
+                    mint.key()
+                )
+            );
+        }
+    }
+    fees.validate()?;
+    curve.validate()?;
+    require_msg!(
+        initial_supplies.iter().all(|supply| *supply > 0),
+        SwapError::EmptySupply,
+        "The initial supply of every token must be non-zero"
+    );
+
+    let pool_authority_bump = *ctx.bumps.get("pool_authority").unwrap();
+
+    pool_token::create_mint(
+        &ctx.accounts.pool_token_program.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.admin.to_account_info(),
+        &ctx.accounts.pool.to_account_info(),
+        &ctx.accounts.pool_token_mint,
+        *ctx.bumps.get("pool_token_mint").unwrap(),
+        &ctx.accounts.pool_authority,
+        pool_authority_bump,
+        None,
+    )?;
+    pool_token::create_token_account(
+        &ctx.accounts.pool_token_program.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.admin.to_account_info(),
+        &ctx.accounts.pool_token_mint,
+        &ctx.accounts.admin_pool_token_ata.to_account_info(),
+    )?;
+
+    let pool = &mut ctx.accounts.pool.load_init()?;
+    pool.admin = ctx.accounts.admin.key();
+    pool.pool_authority_bump_seed = u64::from(pool_authority_bump);
+    pool.pool_authority = ctx.accounts.pool_authority.key();
+    pool.pool_token_mint = ctx.accounts.pool_token_mint.key();
+    pool.pool_token_program = ctx.accounts.pool_token_program.key();
+    pool.fees = fees;
+    pool.amp = amp;
+    pool.n_tokens = u64::try_from(tokens.len()).unwrap();
+
+    for (i, (token, initial_supply)) in tokens.iter().zip(initial_supplies.iter()).enumerate() {
+        create_pool_token_account(
+            &token.token_program.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.pool.to_account_info(),
+            &token.mint.to_account_info(),
+            &token.vault,
+            seeds::pda::token_vault_pda_program_id,
+            seeds::TOKEN_VAULT,
+            &ctx.accounts.pool_authority,
+        )?;
+        create_pool_token_account(
+            &token.token_program.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.pool.to_account_info(),
+            &token.mint.to_account_info(),
+            &token.fees_vault,
+            seeds::pda::token_fees_vault_pda_program_id,
+            seeds::TOKEN_FEES_VAULT,
+            &ctx.accounts.pool_authority,
+        )?;
+        create_pool_token_account(
+            &token.token_program.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.pool.to_account_info(),
+            &token.mint.to_account_info(),
+            &token.protocol_fees_vault,
+            seeds::pda::token_protocol_fees_vault_pda_program_id,
+            seeds::TOKEN_PROTOCOL_FEES_VAULT,
+            &ctx.accounts.pool_authority,
+        )?;
+
+        pool.tokens[i] = MultiStableToken {
+            mint: token.mint.key(),
+            vault: token.vault.key(),
+            fees_vault: token.fees_vault.key(),
+            protocol_fees_vault: token.protocol_fees_vault.key(),
+            token_program: token.token_program.key(),
+            factor: curve.factors[i],
+            decimals: u64::from(token.mint.decimals),
+        };
+
+        swap_token::transfer_from_user(
+            token.token_program.to_account_info(),
+            token.admin_ata.to_account_info(),
+            token.mint.to_account_info(),
+            token.vault.clone(),
+            ctx.accounts.admin.to_account_info(),
+            ctx.remaining_accounts,
+            *initial_supply,
+            token.mint.decimals,
+        )?;
+    }
+
+    pool_token::mint(
+        ctx.accounts.pool_token_program.to_account_info(),
+        ctx.accounts.pool.to_account_info(),
+        ctx.accounts.pool_token_mint.to_account_info(),
+        ctx.accounts.pool_authority.to_account_info(),
+        pool_authority_bump,
+        ctx.accounts.admin_pool_token_ata.to_account_info(),
+        to_u64!(INITIAL_SWAP_POOL_AMOUNT)?,
+    )?;
+
+    Ok(())
+}
+
+/// Initialize a stable pool of `initial_supplies.len()` tokens, each given by
+/// `INIT_MULTI_STABLE_TOKEN_ACCOUNTS` remaining accounts
+#[derive(Accounts)]
+pub struct InitializeMultiStablePool<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(zero)]
+    pub pool: AccountLoader<'info, MultiStablePool>,
+
+    /// Program wide constraints the pool is validated against
+    #[account(
+        seeds = [seeds::GLOBAL_CONFIG],
+        bump,
+    )]
+    pub global_config: AccountLoader<'info, GlobalConfig>,
+
+    /// CHECK: PDA owned by the program
+    #[account(mut,
+        seeds = [seeds::POOL_AUTHORITY, pool.key().as_ref()],
+        bump
+    )]
+    pub pool_authority: AccountInfo<'info>,
+
+    /// Created in the handler
+    /// CHECK: seeds check this
+    #[account(mut,
+        seeds=[seeds::POOL_TOKEN_MINT, pool.key().as_ref()],
+        bump
+    )]
+    pub pool_token_mint: AccountInfo<'info>,
+
+    /// Admin authority's pool token account to deposit the initially minted pool tokens into
+    /// Created in the handler once the pool token mint is initialized
+    #[account(mut)]
+    pub admin_pool_token_ata: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    /// The token program for the pool token mint
+    pub pool_token_program: Interface<'info, TokenInterface>,
+}
+
+mod utils {
+    use super::*;
+
+    /// Trading token accounts of the pool, parsed from the remaining accounts
+    pub struct InitToken<'info> {
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        pub vault: AccountInfo<'info>,
+        pub fees_vault: AccountInfo<'info>,
+        pub protocol_fees_vault: AccountInfo<'info>,
+        pub admin_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn parse_tokens<'info>(
+        ctx: &Context<'_, '_, '_, 'info, InitializeMultiStablePool<'info>>,
+        n_tokens: usize,
+    ) -> Result<Vec<InitToken<'info>>> {
+        require_msg!(
+            (MIN_TOKENS..=MAX_TOKENS).contains(&n_tokens)
+                && ctx.remaining_accounts.len() >= n_tokens * INIT_MULTI_STABLE_TOKEN_ACCOUNTS,
+            SwapError::InvalidTokenCount,
+            &format!(
+                "InvalidTokenCount: {} tokens with {} remaining accounts, expected between {} and {} tokens of {} accounts",
+                n_tokens,
+                ctx.remaining_accounts.len(),
+                MIN_TOKENS,
+                MAX_TOKENS,
+                INIT_MULTI_STABLE_TOKEN_ACCOUNTS
+            )
+        );
+
+        let mut tokens: Vec<InitToken> = Vec::with_capacity(n_tokens);
+        for accounts in ctx
+            .remaining_accounts
+            .chunks_exact(INIT_MULTI_STABLE_TOKEN_ACCOUNTS)
+            .take(n_tokens)
+        {
+            let token = parse_token(ctx, accounts)?;
+            require_msg!(
+                tokens
+                    .iter()
+                    .all(|other| other.mint.key() != token.mint.key()),
+                SwapError::RepeatedMint,
+                &format!("RepeatedMint: mint {} is repeated", token.mint.key())
+            );
+            tokens.push(token);
+        }
+        Ok(tokens)
+    }
+
+    fn parse_token<'info>(
+        ctx: &Context<'_, '_, '_, 'info, InitializeMultiStablePool<'info>>,
+        accounts: &[AccountInfo<'info>],
+    ) -> Result<InitToken<'info>> {
+        let [mint, vault, fees_vault, protocol_fees_vault, admin_ata, token_program] = accounts
+        else {
+            return err!(SwapError::InvalidTokenCount);
+        };
+
+        let mint = Box::new(InterfaceAccount::<Mint>::try_from(mint)?);
+        let admin_ata = Box::new(InterfaceAccount::<TokenAccount>::try_from(admin_ata)?);
+        let token_program = Interface::<TokenInterface>::try_from(token_program)?;
+        require_msg!(
+            mint.to_account_info().owner == token_program.key,
+            ErrorCode::ConstraintMintTokenProgram,
+            &format!(
+                "ConstraintMintTokenProgram: mint.owner ({}) != token_program.key ({})",
+                mint.to_account_info().owner,
+                token_program.key()
+            )
+        );
+
+        let pool = ctx.accounts.pool.key();
+        let (expected_vault, _) = seeds::pda::token_vault_pda(&pool, &mint.key());
+        require_msg!(
+            vault.key() == expected_vault,
+            SwapError::InvalidProgramAddress,
+            &format!(
+                "InvalidProgramAddress: vault.key ({}) != token vault PDA ({})",
+                vault.key(),
+                expected_vault
+            )
+        );
+        let (expected_fees_vault, _) = seeds::pda::token_fees_vault_pda(&pool, &mint.key());
+        require_msg!(
+            fees_vault.key() == expected_fees_vault,
+            SwapError::InvalidProgramAddress,
+            &format!(
+                "InvalidProgramAddress: fees_vault.key ({}) != token fees vault PDA ({})",
+                fees_vault.key(),
+                expected_fees_vault
+            )
+        );
+        let (expected_protocol_fees_vault, _) =
+            seeds::pda::token_protocol_fees_vault_pda(&pool, &mint.key());
+        require_msg!(
+            protocol_fees_vault.key() == expected_protocol_fees_vault,
+            SwapError::InvalidProgramAddress,
+            &format!(
+                "InvalidProgramAddress: protocol_fees_vault.key ({}) != token protocol fees vault PDA ({})",
+                protocol_fees_vault.key(),
+                expected_protocol_fees_vault
+            )
+        );
+        require_msg!(
+            admin_ata.mint == mint.key() && admin_ata.owner == ctx.accounts.admin.key(),
+            SwapError::IncorrectSwapAccount,
+            &format!(
+                "IncorrectSwapAccount: admin_ata ({}) is not a {} token account of the admin",
+                admin_ata.key(),
+                mint.key()
+            )
+        );
+
+        Ok(InitToken {
+            mint,
+            vault: vault.clone(),
+            fees_vault: fees_vault.clone(),
+            protocol_fees_vault: protocol_fees_vault.clone(),
+            admin_ata,
+            token_program,
+        })
+    }
+}
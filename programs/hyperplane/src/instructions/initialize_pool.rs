@@ -6,7 +6,7 @@ use anchor_lang::{
     },
 };
 use anchor_spl::{
-    token::Token,
+    token_2022::spl_token_2022,
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 use derive_more::Constructor;
@@ -14,21 +14,39 @@ use derive_more::Constructor;
 use serde;
 
 use crate::{
-    constraints::SWAP_CONSTRAINTS,
-    curve::{base::SwapCurve, fees::Fees},
+    curve::{base::SwapCurve, fees::Fees, interest_bearing},
     error::SwapError,
-    state::{Curve, SwapPool},
+    state::{Curve, GlobalConfig, PriceAccumulator, SwapPool},
     to_u64,
-    utils::{pool_pda::create_pool_token_account, pool_token, seeds, swap_token},
+    utils::{pool_pda::create_pool_token_account, pool_token, seeds, swap_token, token_extensions},
 };
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
 pub enum CurveUserParameters {
     ConstantProduct,
-    ConstantPrice { token_b_price: u64 },
-    Offset { token_b_offset: u64 },
-    Stable { amp: u64 },
+    ConstantPrice {
+        token_b_price: u64,
+    },
+    Offset {
+        token_b_offset: u64,
+    },
+    Stable {
+        amp: u64,
+    },
+    OraclePrice {
+        price_feed: Pubkey,
+        price_feed_program: Pubkey,
+        max_price_age_sec: u64,
+        max_price_confidence_bps: u64,
+    },
+    Concentrated {
+        price_lower_x64: u128,
+        price_upper_x64: u128,
+    },
+    Weighted {
+        token_a_weight_bps: u64,
+    },
 }
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -38,6 +56,36 @@ pub struct InitialSupply {
     pub initial_supply_b: u64,
 }
 
+/// Token metadata of a token-2022 pool token mint
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct PoolTokenMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+impl PoolTokenMetadata {
+    /// Derive the pool token metadata from the token metadata of the trading token mints,
+    /// e.g. "HYP-USDC-USDT LP" for a USDC/USDT pool
+    pub fn from_trading_mints(token_a_mint_data: &[u8], token_b_mint_data: &[u8]) -> Result<Self> {
+        match (
+            token_extensions::token_metadata(token_a_mint_data)?,
+            token_extensions::token_metadata(token_b_mint_data)?,
+        ) {
+            (Some(token_a), Some(token_b)) => {
+                let symbol = format!("HYP-{}-{}", token_a.symbol, token_b.symbol);
+                Ok(PoolTokenMetadata {
+                    name: format!("{} LP", symbol),
+                    symbol,
+                    uri: String::new(),
+                })
+            }
+            _ => err!(SwapError::MissingPoolTokenMetadata),
+        }
+    }
+}
+
 pub fn initialize_pool_token_accounts(ctx: &Context<InitializePool>) -> Result<()> {
     create_pool_token_account(
         &ctx.accounts.token_a_token_program.to_account_info(),
@@ -61,6 +109,17 @@ pub fn initialize_pool_token_accounts(ctx: &Context<InitializePool>) -> Result<(
         seeds::TOKEN_A_FEES_VAULT,
         &ctx.accounts.pool_authority,
     )?;
+    create_pool_token_account(
+        &ctx.accounts.token_a_token_program.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.admin.to_account_info(),
+        &ctx.accounts.pool.to_account_info(),
+        &ctx.accounts.token_a_mint.to_account_info(),
+        &ctx.accounts.token_a_protocol_fees_vault,
+        seeds::pda::token_a_protocol_fees_vault_pda_program_id,
+        seeds::TOKEN_A_PROTOCOL_FEES_VAULT,
+        &ctx.accounts.pool_authority,
+    )?;
 
     create_pool_token_account(
         &ctx.accounts.token_b_token_program.to_account_info(),
@@ -84,24 +143,37 @@ pub fn initialize_pool_token_accounts(ctx: &Context<InitializePool>) -> Result<(
         seeds::TOKEN_B_FEES_VAULT,
         &ctx.accounts.pool_authority,
     )?;
+    create_pool_token_account(
+        &ctx.accounts.token_b_token_program.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.admin.to_account_info(),
+        &ctx.accounts.pool.to_account_info(),
+        &ctx.accounts.token_b_mint.to_account_info(),
+        &ctx.accounts.token_b_protocol_fees_vault,
+        seeds::pda::token_b_protocol_fees_vault_pda_program_id,
+        seeds::TOKEN_B_PROTOCOL_FEES_VAULT,
+        &ctx.accounts.pool_authority,
+    )?;
     Ok(())
 }
 
-pub fn handler_initialize_pool(
-    ctx: Context<InitializePool>,
+pub fn handler_initialize_pool<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitializePool<'info>>,
     curve_parameters: CurveUserParameters,
     fees: Fees,
     initial_supply: InitialSupply,
+    pool_token_metadata: Option<PoolTokenMetadata>,
 ) -> Result<()> {
     let InitialSupply {
         initial_supply_a,
         initial_supply_b,
     } = initial_supply;
 
-    let curve_parameters = Box::new(curve_parameters.to_curve_params(
+    let mut curve_parameters = Box::new(curve_parameters.to_curve_params(
         ctx.accounts.token_a_mint.decimals,
         ctx.accounts.token_b_mint.decimals,
     ));
+    curve_parameters.load_price(ctx.remaining_accounts)?;
     msg!("Curve params {:?}", curve_parameters);
 
     let swap_curve = Box::new(SwapCurve::new_from_params(*curve_parameters)?);
@@ -117,26 +189,62 @@ pub fn handler_initialize_pool(
         .calculator
         .validate_supply(initial_supply_a, initial_supply_b)?;
 
-    let swap_constraints = &SWAP_CONSTRAINTS;
-
-    if let Some(swap_constraints) = swap_constraints {
-        // swap_constraints.validate_admin(ctx.accounts.admin.key)?;
-        swap_constraints.validate_curve(&swap_curve)?;
-        swap_constraints.validate_fees(&fees)?;
-        swap_constraints.validate_token_2022_trading_token_extensions(
+    {
+        let global_config = ctx.accounts.global_config.load()?;
+        global_config.validate_curve(swap_curve.curve_type)?;
+        global_config.validate_fees(&fees)?;
+        global_config.validate_token_2022_trading_token_extensions(
             &ctx.accounts.token_a_mint.to_account_info(),
         )?;
-        swap_constraints.validate_token_2022_trading_token_extensions(
+        global_config.validate_token_2022_trading_token_extensions(
             &ctx.accounts.token_b_mint.to_account_info(),
         )?;
     }
     fees.validate()?;
     swap_curve.calculator.validate()?;
+
+    // token-2022 pool token mints always carry metadata, derived from the trading mints if not given
+    let pool_token_metadata = if ctx.accounts.pool_token_program.key() == spl_token_2022::id() {
+        match pool_token_metadata {
+            Some(metadata) => Some(metadata),
+            None => Some(PoolTokenMetadata::from_trading_mints(
+                &ctx.accounts.token_a_mint.to_account_info().data.borrow(),
+                &ctx.accounts.token_b_mint.to_account_info().data.borrow(),
+            )?),
+        }
+    } else {
+        require!(
+            pool_token_metadata.is_none(),
+            SwapError::PoolTokenMetadataNotSupported
+        );
+        None
+    };
+    msg!("Pool token metadata {:?}", pool_token_metadata);
+
     initialize_pool_token_accounts(&ctx)?;
 
     let initial_amount = swap_curve.calculator.new_pool_supply();
     let pool_authority_bump = *ctx.bumps.get("pool_authority").unwrap();
 
+    pool_token::create_mint(
+        &ctx.accounts.pool_token_program.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.admin.to_account_info(),
+        &ctx.accounts.pool.to_account_info(),
+        &ctx.accounts.pool_token_mint,
+        *ctx.bumps.get("pool_token_mint").unwrap(),
+        &ctx.accounts.pool_authority,
+        pool_authority_bump,
+        pool_token_metadata,
+    )?;
+    pool_token::create_token_account(
+        &ctx.accounts.pool_token_program.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.admin.to_account_info(),
+        &ctx.accounts.pool_token_mint,
+        &ctx.accounts.admin_pool_token_ata.to_account_info(),
+    )?;
+
     let pool = &mut ctx.accounts.pool.load_init()?;
     pool.admin = ctx.accounts.admin.key();
     pool.pool_authority_bump_seed = u64::try_from(pool_authority_bump).unwrap();
@@ -148,11 +256,21 @@ pub fn handler_initialize_pool(
     pool.token_b_mint = ctx.accounts.token_b_mint.key();
     pool.token_a_fees_vault = ctx.accounts.token_a_fees_vault.key();
     pool.token_b_fees_vault = ctx.accounts.token_b_fees_vault.key();
+    pool.token_a_protocol_fees_vault = ctx.accounts.token_a_protocol_fees_vault.key();
+    pool.token_b_protocol_fees_vault = ctx.accounts.token_b_protocol_fees_vault.key();
     pool.token_a_program = ctx.accounts.token_a_token_program.key();
     pool.token_b_program = ctx.accounts.token_b_token_program.key();
     pool.fees = fees;
     pool.curve_type = swap_curve.curve_type.into();
     pool.swap_curve = ctx.accounts.swap_curve.key();
+    pool.price_accumulator = PriceAccumulator::new(
+        u64::try_from(Clock::get()?.unix_timestamp)
+            .map_err(|_| error!(SwapError::ConversionFailure))?,
+    );
+    pool.interest_bearing_mints = interest_bearing::interest_bearing_mints(
+        &ctx.accounts.token_a_mint.to_account_info().data.borrow(),
+        &ctx.accounts.token_b_mint.to_account_info().data.borrow(),
+    )?;
 
     swap_token::transfer_from_user(
         ctx.accounts.token_a_token_program.to_account_info(),
@@ -160,6 +278,7 @@ pub fn handler_initialize_pool(
         ctx.accounts.token_a_mint.to_account_info(),
         ctx.accounts.token_a_vault.to_account_info(),
         ctx.accounts.admin.to_account_info(),
+        ctx.remaining_accounts,
         initial_supply_a,
         ctx.accounts.token_a_mint.decimals,
     )?;
@@ -169,6 +288,7 @@ pub fn handler_initialize_pool(
         ctx.accounts.token_b_mint.to_account_info(),
         ctx.accounts.token_b_vault.to_account_info(),
         ctx.accounts.admin.to_account_info(),
+        ctx.remaining_accounts,
         initial_supply_b,
         ctx.accounts.token_b_mint.decimals,
     )?;
@@ -199,6 +319,13 @@ pub struct InitializePool<'info> {
     #[account(zero)]
     pub pool: AccountLoader<'info, SwapPool>,
 
+    /// Program wide constraints the pool is validated against
+    #[account(
+        seeds = [seeds::GLOBAL_CONFIG],
+        bump,
+    )]
+    pub global_config: AccountLoader<'info, GlobalConfig>,
+
     /// CHECK: This is checked in the handler -- TODO elliot - test checks better
     #[account(init,
         seeds = [seeds::SWAP_CURVE, pool.key().as_ref()],
@@ -249,16 +376,13 @@ pub struct InitializePool<'info> {
     )]
     pub token_b_vault: AccountInfo<'info>,
 
-    // todo - elliot - set no close authority, immutable? Should be default?
-    #[account(init,
+    /// Created in the handler, with token metadata for token-2022 pool token mints
+    /// CHECK: seeds check this
+    #[account(mut,
         seeds=[seeds::POOL_TOKEN_MINT, pool.key().as_ref()],
-        bump,
-        payer = admin,
-        mint::decimals = 6,
-        mint::authority = pool_authority,
-        mint::token_program = pool_token_program,
+        bump
     )]
-    pub pool_token_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub pool_token_mint: AccountInfo<'info>,
 
     /// Token account to collect trading token a fees into - designated to the pool admin authority
     /// CHECK: seeds check this
@@ -276,6 +400,22 @@ pub struct InitializePool<'info> {
     )]
     pub token_b_fees_vault: AccountInfo<'info>,
 
+    /// Token account to collect the protocol share of trading token a fees into - designated to the protocol authority
+    /// CHECK: seeds check this
+    #[account(mut,
+        seeds=[seeds::TOKEN_A_PROTOCOL_FEES_VAULT, pool.key().as_ref(), token_a_mint.key().as_ref()],
+        bump
+    )]
+    pub token_a_protocol_fees_vault: AccountInfo<'info>,
+
+    /// Token account to collect the protocol share of trading token b fees into - designated to the protocol authority
+    /// CHECK: seeds check this
+    #[account(mut,
+        seeds=[seeds::TOKEN_B_PROTOCOL_FEES_VAULT, pool.key().as_ref(), token_b_mint.key().as_ref()],
+        bump
+    )]
+    pub token_b_protocol_fees_vault: AccountInfo<'info>,
+
     /// Admin authority's token A account to deposit initial liquidity from
     #[account(mut,
         token::mint = token_a_mint,
@@ -293,19 +433,14 @@ pub struct InitializePool<'info> {
     pub admin_token_b_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// Admin authority's pool token account to deposit the initially minted pool tokens into
-    #[account(
-        init,
-        payer = admin,
-        token::mint = pool_token_mint,
-        token::authority = admin,
-        token::token_program = pool_token_program
-    )]
-    pub admin_pool_token_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// Created in the handler once the pool token mint is initialized
+    #[account(mut)]
+    pub admin_pool_token_ata: Signer<'info>,
 
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
-    /// The token program for the pool token mint
-    pub pool_token_program: Program<'info, Token>,
+    /// The token program for the pool token mint, token-2022 to create the mint with token metadata
+    pub pool_token_program: Interface<'info, TokenInterface>,
     /// The token program for the token A mint
     pub token_a_token_program: Interface<'info, TokenInterface>,
     /// The token program for the token B mint
@@ -315,21 +450,31 @@ pub struct InitializePool<'info> {
 pub mod model {
 
     use super::*;
-
-    #[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
-    pub enum CurveParameters {
-        ConstantProduct,
-        ConstantPrice {
-            token_b_price: u64,
-        },
-        Offset {
-            token_b_offset: u64,
-        },
-        Stable {
-            amp: u64,
-            token_a_decimals: u8,
-            token_b_decimals: u8,
-        },
+    pub use crate::curve::base::CurveParameters;
+    use crate::state::OraclePriceCurve;
+
+    impl CurveParameters {
+        /// Load the current token B price of oracle priced curves from the price feed in `accounts`
+        pub fn load_price(&mut self, accounts: &[AccountInfo]) -> Result<()> {
+            if let CurveParameters::OraclePrice {
+                price_feed,
+                price_feed_program,
+                max_price_age_sec,
+                max_price_confidence_bps,
+                token_b_price_x64,
+            } = self
+            {
+                let mut curve = OraclePriceCurve::new(
+                    *price_feed,
+                    *price_feed_program,
+                    *max_price_age_sec,
+                    *max_price_confidence_bps,
+                );
+                curve.load_price(accounts)?;
+                *token_b_price_x64 = curve.token_b_price_x64;
+            }
+            Ok(())
+        }
     }
 
     impl CurveUserParameters {
@@ -353,6 +498,28 @@ pub mod model {
                     token_a_decimals,
                     token_b_decimals,
                 },
+                CurveUserParameters::OraclePrice {
+                    price_feed,
+                    price_feed_program,
+                    max_price_age_sec,
+                    max_price_confidence_bps,
+                } => CurveParameters::OraclePrice {
+                    price_feed: *price_feed,
+                    price_feed_program: *price_feed_program,
+                    max_price_age_sec: *max_price_age_sec,
+                    max_price_confidence_bps: *max_price_confidence_bps,
+                    token_b_price_x64: 0,
+                },
+                CurveUserParameters::Concentrated {
+                    price_lower_x64,
+                    price_upper_x64,
+                } => CurveParameters::Concentrated {
+                    price_lower_x64: *price_lower_x64,
+                    price_upper_x64: *price_upper_x64,
+                },
+                CurveUserParameters::Weighted { token_a_weight_bps } => CurveParameters::Weighted {
+                    token_a_weight_bps: *token_a_weight_bps,
+                },
             }
         }
     }
@@ -372,6 +539,28 @@ pub mod model {
                     token_a_decimals: _,
                     token_b_decimals: _,
                 } => CurveUserParameters::Stable { amp },
+                CurveParameters::OraclePrice {
+                    price_feed,
+                    price_feed_program,
+                    max_price_age_sec,
+                    max_price_confidence_bps,
+                    token_b_price_x64: _,
+                } => CurveUserParameters::OraclePrice {
+                    price_feed,
+                    price_feed_program,
+                    max_price_age_sec,
+                    max_price_confidence_bps,
+                },
+                CurveParameters::Concentrated {
+                    price_lower_x64,
+                    price_upper_x64,
+                } => CurveUserParameters::Concentrated {
+                    price_lower_x64,
+                    price_upper_x64,
+                },
+                CurveParameters::Weighted { token_a_weight_bps } => {
+                    CurveUserParameters::Weighted { token_a_weight_bps }
+                }
             }
         }
     }
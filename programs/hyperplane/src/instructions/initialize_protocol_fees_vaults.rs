@@ -0,0 +1,130 @@
+use anchor_lang::{accounts::interface::Interface, prelude::*};
+use anchor_spl::token_interface::{Mint, TokenInterface};
+
+use crate::{
+    emitted,
+    error::SwapError,
+    event, require_msg,
+    state::{GlobalConfig, SwapPool},
+    utils::{pool_pda::create_pool_token_account, seeds},
+};
+
+/// Create the protocol fees vaults of a pool created before protocol fees were introduced.
+///
+/// Until the vaults are created, swaps through the pool pay no protocol fees.
+pub fn handler_initialize_protocol_fees_vaults(
+    ctx: Context<InitializeProtocolFeesVaults>,
+) -> Result<event::InitializeProtocolFeesVaults> {
+    {
+        let pool = ctx.accounts.pool.load()?;
+        let protocol_authority = ctx.accounts.global_config.load()?.protocol_authority;
+        require_msg!(
+            ctx.accounts.payer.key() == pool.admin || ctx.accounts.payer.key() == protocol_authority,
+            SwapError::InvalidProtocolAuthority,
+            &format!(
+                "InvalidProtocolAuthority: payer ({}) is neither the pool admin ({}) nor the protocol authority ({})",
+                ctx.accounts.payer.key(),
+                pool.admin,
+                protocol_authority
+            )
+        );
+        require_msg!(
+            pool.token_a_protocol_fees_vault == Pubkey::default()
+                && pool.token_b_protocol_fees_vault == Pubkey::default(),
+            SwapError::ProtocolFeesVaultsAlreadyInitialized,
+            &format!(
+                "ProtocolFeesVaultsAlreadyInitialized: token_a_protocol_fees_vault={}, token_b_protocol_fees_vault={}",
+                pool.token_a_protocol_fees_vault, pool.token_b_protocol_fees_vault
+            )
+        );
+    }
+
+    create_pool_token_account(
+        &ctx.accounts.token_a_token_program.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.pool.to_account_info(),
+        &ctx.accounts.token_a_mint.to_account_info(),
+        &ctx.accounts.token_a_protocol_fees_vault,
+        seeds::pda::token_a_protocol_fees_vault_pda_program_id,
+        seeds::TOKEN_A_PROTOCOL_FEES_VAULT,
+        &ctx.accounts.pool_authority,
+    )?;
+    create_pool_token_account(
+        &ctx.accounts.token_b_token_program.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.pool.to_account_info(),
+        &ctx.accounts.token_b_mint.to_account_info(),
+        &ctx.accounts.token_b_protocol_fees_vault,
+        seeds::pda::token_b_protocol_fees_vault_pda_program_id,
+        seeds::TOKEN_B_PROTOCOL_FEES_VAULT,
+        &ctx.accounts.pool_authority,
+    )?;
+
+    let pool = &mut ctx.accounts.pool.load_mut()?;
+    pool.token_a_protocol_fees_vault = ctx.accounts.token_a_protocol_fees_vault.key();
+    pool.token_b_protocol_fees_vault = ctx.accounts.token_b_protocol_fees_vault.key();
+
+    emitted!(event::InitializeProtocolFeesVaults {
+        token_a_protocol_fees_vault: pool.token_a_protocol_fees_vault,
+        token_b_protocol_fees_vault: pool.token_b_protocol_fees_vault,
+    });
+}
+
+#[derive(Accounts)]
+pub struct InitializeProtocolFeesVaults<'info> {
+    /// The pool admin or the protocol authority, pays the rent of the vaults
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::GLOBAL_CONFIG],
+        bump,
+    )]
+    pub global_config: AccountLoader<'info, GlobalConfig>,
+
+    #[account(mut,
+        has_one = pool_authority @ SwapError::InvalidProgramAddress,
+        has_one = token_a_mint,
+        has_one = token_b_mint,
+    )]
+    pub pool: AccountLoader<'info, SwapPool>,
+
+    /// CHECK: has_one constraint on the pool
+    pub pool_authority: AccountInfo<'info>,
+
+    /// CHECK: has_one constraint on the pool
+    #[account(
+        mint::token_program = token_a_token_program,
+    )]
+    pub token_a_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: has_one constraint on the pool
+    #[account(
+        mint::token_program = token_b_token_program,
+    )]
+    pub token_b_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Token account to collect the protocol share of trading token a fees into - designated to the protocol authority
+    /// CHECK: seeds check this
+    #[account(mut,
+        seeds=[seeds::TOKEN_A_PROTOCOL_FEES_VAULT, pool.key().as_ref(), token_a_mint.key().as_ref()],
+        bump
+    )]
+    pub token_a_protocol_fees_vault: AccountInfo<'info>,
+
+    /// Token account to collect the protocol share of trading token b fees into - designated to the protocol authority
+    /// CHECK: seeds check this
+    #[account(mut,
+        seeds=[seeds::TOKEN_B_PROTOCOL_FEES_VAULT, pool.key().as_ref(), token_b_mint.key().as_ref()],
+        bump
+    )]
+    pub token_b_protocol_fees_vault: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+    /// The token program for the token A mint
+    pub token_a_token_program: Interface<'info, TokenInterface>,
+    /// The token program for the token B mint
+    pub token_b_token_program: Interface<'info, TokenInterface>,
+}
@@ -1,16 +1,66 @@
+pub mod accept_admin;
+pub mod close_pool;
 pub mod deposit;
+pub mod deposit_single_token_type_exact_amount_in;
+pub mod initialize_global_config;
+pub mod initialize_multi_stable_pool;
 pub mod initialize_pool;
+pub mod initialize_protocol_fees_vaults;
+pub mod multi_stable_deposit;
+pub mod multi_stable_swap;
+pub mod multi_stable_withdraw;
+pub mod multi_stable_withdraw_fees;
+pub mod multi_stable_withdraw_imbalanced;
+pub mod multi_stable_withdraw_protocol_fees;
+pub mod propose_new_admin;
+pub mod ramp_amp;
+pub mod route_swap;
+pub mod set_rate_provider;
+pub mod stop_ramp_amp;
 pub mod swap;
+pub mod swap_exact_out;
+pub mod update_curve_config;
+pub mod update_dynamic_fee;
+pub mod update_global_config;
 pub mod update_pool_config;
+pub mod update_rate;
 pub mod withdraw;
 pub mod withdraw_fees;
+pub mod withdraw_one_token;
+pub mod withdraw_protocol_fees;
+pub mod withdraw_single_token_type_exact_amount_out;
 
 #[cfg(test)]
 pub mod test;
 
+pub use accept_admin::*;
+pub use close_pool::*;
 pub use deposit::*;
+pub use deposit_single_token_type_exact_amount_in::*;
+pub use initialize_global_config::*;
+pub use initialize_multi_stable_pool::*;
 pub use initialize_pool::*;
+pub use initialize_protocol_fees_vaults::*;
+pub use multi_stable_deposit::*;
+pub use multi_stable_swap::*;
+pub use multi_stable_withdraw::*;
+pub use multi_stable_withdraw_fees::*;
+pub use multi_stable_withdraw_imbalanced::*;
+pub use multi_stable_withdraw_protocol_fees::*;
+pub use propose_new_admin::*;
+pub use ramp_amp::*;
+pub use route_swap::*;
+pub use set_rate_provider::*;
+pub use stop_ramp_amp::*;
 pub use swap::*;
+pub use swap_exact_out::*;
+pub use update_curve_config::*;
+pub use update_dynamic_fee::*;
+pub use update_global_config::*;
 pub use update_pool_config::*;
+pub use update_rate::*;
 pub use withdraw::*;
 pub use withdraw_fees::*;
+pub use withdraw_one_token::*;
+pub use withdraw_protocol_fees::*;
+pub use withdraw_single_token_type_exact_amount_out::*;
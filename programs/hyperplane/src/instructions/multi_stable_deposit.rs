@@ -0,0 +1,232 @@
+use anchor_lang::{
+    accounts::{interface::Interface, interface_account::InterfaceAccount},
+    prelude::*,
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    emitted,
+    error::SwapError,
+    event,
+    multi_stable_deposit::utils::parse_tokens,
+    require_msg,
+    state::MultiStablePool,
+    to_u64,
+    utils::{pool_token, swap_token},
+};
+
+/// Remaining accounts of each trading token: mint, vault, user token account and token program
+pub const MULTI_STABLE_LIQUIDITY_TOKEN_ACCOUNTS: usize = 4;
+
+pub fn handler_multi_stable_deposit<'info>(
+    ctx: Context<'_, '_, '_, 'info, MultiStableLiquidity<'info>>,
+    token_amounts: Vec<u64>,
+    minimum_pool_token_amount: u64,
+) -> Result<event::MultiStableDeposit> {
+    let pool = ctx.accounts.pool.load()?;
+    require_msg!(
+        !pool.withdrawals_only(),
+        SwapError::WithdrawalsOnlyMode,
+        "The pool is in withdrawals only mode"
+    );
+    let tokens = parse_tokens(&ctx, &pool)?;
+    require_msg!(
+        token_amounts.len() == tokens.len(),
+        SwapError::InvalidTokenCount,
+        &format!(
+            "InvalidTokenCount: {} token amounts for a pool of {} tokens",
+            token_amounts.len(),
+            tokens.len()
+        )
+    );
+    require_msg!(
+        token_amounts.iter().any(|amount| *amount > 0),
+        SwapError::ZeroTradingTokens,
+        "Cannot deposit zero trading tokens"
+    );
+
+    let amounts: Vec<u128> = token_amounts.iter().copied().map(u128::from).collect();
+    let balances: Vec<u128> = tokens
+        .iter()
+        .map(|token| u128::from(token.vault.amount))
+        .collect();
+    msg!(
+        "Deposit inputs: token_amounts={:?}, minimum_pool_token_amount={}, balances={:?}, pool_token_supply={}",
+        token_amounts,
+        minimum_pool_token_amount,
+        balances,
+        ctx.accounts.pool_token_mint.supply,
+    );
+
+    let pool_token_amount = pool.curve().deposit(
+        &amounts,
+        &balances,
+        u128::from(ctx.accounts.pool_token_mint.supply),
+        &pool.fees,
+    )?;
+    let pool_token_amount = to_u64!(pool_token_amount)?;
+    require_msg!(
+        pool_token_amount > 0,
+        SwapError::ZeroTradingTokens,
+        "Deposit would mint zero pool tokens"
+    );
+    require_msg!(
+        pool_token_amount >= minimum_pool_token_amount,
+        SwapError::ExceededSlippage,
+        &format!(
+            "ExceededSlippage: pool_token_amount={} < minimum_pool_token_amount={}",
+            pool_token_amount, minimum_pool_token_amount
+        )
+    );
+
+    for (token, amount) in tokens.iter().zip(token_amounts.iter()) {
+        if *amount > 0 {
+            swap_token::transfer_from_user(
+                token.token_program.to_account_info(),
+                token.user_ata.to_account_info(),
+                token.mint.to_account_info(),
+                token.vault.to_account_info(),
+                ctx.accounts.signer.to_account_info(),
+                ctx.remaining_accounts,
+                *amount,
+                token.mint.decimals,
+            )?;
+        }
+    }
+
+    pool_token::mint(
+        ctx.accounts.pool_token_program.to_account_info(),
+        ctx.accounts.pool.to_account_info(),
+        ctx.accounts.pool_token_mint.to_account_info(),
+        ctx.accounts.pool_authority.to_account_info(),
+        pool.bump_seed(),
+        ctx.accounts.pool_token_user_ata.to_account_info(),
+        pool_token_amount,
+    )?;
+
+    emitted!(event::MultiStableDeposit {
+        token_amounts,
+        pool_token_amount,
+    });
+}
+
+/// Deposit into or withdraw from a multi-token stable pool
+///
+/// Every token of the pool is passed in pool order as `MULTI_STABLE_LIQUIDITY_TOKEN_ACCOUNTS`
+/// remaining accounts
+#[derive(Accounts)]
+pub struct MultiStableLiquidity<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        has_one = pool_authority @ SwapError::InvalidProgramAddress,
+        has_one = pool_token_mint @ SwapError::IncorrectPoolMint,
+    )]
+    pub pool: AccountLoader<'info, MultiStablePool>,
+
+    /// CHECK: has_one constraint on the pool
+    pub pool_authority: AccountInfo<'info>,
+
+    /// CHECK: has_one constraint on the pool
+    #[account(mut)]
+    pub pool_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Signer's pool token account
+    #[account(mut,
+        token::mint = pool_token_mint,
+        token::authority = signer,
+        token::token_program = pool_token_program,
+    )]
+    pub pool_token_user_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token program for the pool token mint
+    pub pool_token_program: Interface<'info, TokenInterface>,
+}
+
+pub(crate) mod utils {
+    use super::*;
+
+    /// Trading token accounts of the pool, parsed from the remaining accounts
+    pub struct LiquidityToken<'info> {
+        pub mint: Box<InterfaceAccount<'info, Mint>>,
+        pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub user_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    pub fn parse_tokens<'info>(
+        ctx: &Context<'_, '_, '_, 'info, MultiStableLiquidity<'info>>,
+        pool: &MultiStablePool,
+    ) -> Result<Vec<LiquidityToken<'info>>> {
+        let n_tokens = pool.tokens().len();
+        require_msg!(
+            ctx.remaining_accounts.len() >= n_tokens * MULTI_STABLE_LIQUIDITY_TOKEN_ACCOUNTS,
+            SwapError::InvalidTokenCount,
+            &format!(
+                "InvalidTokenCount: {} remaining accounts for {} tokens of {} accounts",
+                ctx.remaining_accounts.len(),
+                n_tokens,
+                MULTI_STABLE_LIQUIDITY_TOKEN_ACCOUNTS
+            )
+        );
+
+        pool.tokens()
+            .iter()
+            .zip(
+                ctx.remaining_accounts
+                    .chunks_exact(MULTI_STABLE_LIQUIDITY_TOKEN_ACCOUNTS),
+            )
+            .map(|(token, accounts)| {
+                let [mint, vault, user_ata, token_program] = accounts else {
+                    return err!(SwapError::InvalidTokenCount);
+                };
+                require_msg!(
+                    mint.key() == token.mint,
+                    SwapError::IncorrectTradingMint,
+                    &format!(
+                        "IncorrectTradingMint: mint.key ({}) != token mint ({})",
+                        mint.key(),
+                        token.mint
+                    )
+                );
+                require_msg!(
+                    vault.key() == token.vault,
+                    SwapError::IncorrectSwapAccount,
+                    &format!(
+                        "IncorrectSwapAccount: vault.key ({}) != token vault ({})",
+                        vault.key(),
+                        token.vault
+                    )
+                );
+                require_msg!(
+                    token_program.key() == token.token_program,
+                    ErrorCode::ConstraintMintTokenProgram,
+                    &format!(
+                        "ConstraintMintTokenProgram: token_program.key ({}) != token program ({})",
+                        token_program.key(),
+                        token.token_program
+                    )
+                );
+                let user_ata = Box::new(InterfaceAccount::<TokenAccount>::try_from(user_ata)?);
+                require_msg!(
+                    user_ata.mint == token.mint
+                        && user_ata.owner == ctx.accounts.signer.key()
+                        && user_ata.key() != token.vault,
+                    SwapError::IncorrectSwapAccount,
+                    &format!(
+                        "IncorrectSwapAccount: user_ata ({}) is not a {} token account of the signer",
+                        user_ata.key(),
+                        token.mint
+                    )
+                );
+                Ok(LiquidityToken {
+                    mint: Box::new(InterfaceAccount::<Mint>::try_from(mint)?),
+                    vault: Box::new(InterfaceAccount::<TokenAccount>::try_from(vault)?),
+                    user_ata,
+                    token_program: Interface::<TokenInterface>::try_from(token_program)?,
+                })
+            })
+            .collect()
+    }
+}
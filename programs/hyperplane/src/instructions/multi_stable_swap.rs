@@ -0,0 +1,322 @@
+use anchor_lang::{
+    accounts::{interface::Interface, interface_account::InterfaceAccount},
+    prelude::*,
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    curve::base::CurveType,
+    emitted,
+    error::SwapError,
+    event,
+    multi_stable_swap::utils::{validate_inputs, vault_balances},
+    require_msg,
+    state::{GlobalConfig, MultiStablePool},
+    to_u64, try_math,
+    utils::{math::TryMath, seeds, swap_token},
+};
+
+pub fn handler_multi_stable_swap<'info>(
+    ctx: Context<'_, '_, '_, 'info, MultiStableSwap<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Result<event::Swap> {
+    let pool = ctx.accounts.pool.load()?;
+    let (source_index, destination_index) = validate_inputs(&ctx, &pool)?;
+    let balances = vault_balances(&pool, ctx.remaining_accounts)?;
+
+    msg!(
+        "Swap inputs: source_index={}, destination_index={}, amount_in={}, minimum_amount_out={}",
+        source_index,
+        destination_index,
+        amount_in,
+        minimum_amount_out
+    );
+    msg!("Swap pool inputs: balances={:?}", balances);
+    let result = pool.curve().swap(
+        source_index,
+        destination_index,
+        u128::from(amount_in),
+        &balances,
+        &pool.fees,
+    )?;
+
+    let source_amount_to_vault = to_u64!(result.source_amount_to_vault)?;
+    let protocol_fee = ctx
+        .accounts
+        .global_config
+        .load()?
+        .protocol_fee(result.owner_fee)
+        .map_err(|_| error!(SwapError::FeeCalculationFailure))?;
+    let owner_fee = to_u64!(try_math!(result.owner_fee.try_sub(protocol_fee))?)?;
+    let protocol_fee = to_u64!(protocol_fee)?;
+    let destination_amount = to_u64!(result.destination_amount_swapped)?;
+
+    msg!(
+        "Swap result: total_source_debit_amount={}, source_amount_swapped={}, source_amount_to_vault={}, trade_fee={}, owner_fee={}, protocol_fee={}, destination_amount={}",
+        result.total_source_amount_swapped,
+        result.source_amount_swapped,
+        source_amount_to_vault,
+        result.trade_fee,
+        owner_fee,
+        protocol_fee,
+        destination_amount,
+    );
+    require_msg!(
+        destination_amount >= minimum_amount_out,
+        SwapError::ExceededSlippage,
+        &format!(
+            "ExceededSlippage: amount_received={} < minimum_amount_out={}",
+            destination_amount, minimum_amount_out
+        )
+    );
+
+    swap_token::transfer_from_user(
+        ctx.accounts.source_token_program.to_account_info(),
+        ctx.accounts.source_user_ata.to_account_info(),
+        ctx.accounts.source_mint.to_account_info(),
+        ctx.accounts.source_vault.to_account_info(),
+        ctx.accounts.signer.to_account_info(),
+        ctx.remaining_accounts,
+        source_amount_to_vault,
+        ctx.accounts.source_mint.decimals,
+    )?;
+    if owner_fee > 0 {
+        swap_token::transfer_from_user(
+            ctx.accounts.source_token_program.to_account_info(),
+            ctx.accounts.source_user_ata.to_account_info(),
+            ctx.accounts.source_mint.to_account_info(),
+            ctx.accounts.source_token_fees_vault.to_account_info(),
+            ctx.accounts.signer.to_account_info(),
+            ctx.remaining_accounts,
+            owner_fee,
+            ctx.accounts.source_mint.decimals,
+        )?;
+    }
+    if protocol_fee > 0 {
+        swap_token::transfer_from_user(
+            ctx.accounts.source_token_program.to_account_info(),
+            ctx.accounts.source_user_ata.to_account_info(),
+            ctx.accounts.source_mint.to_account_info(),
+            ctx.accounts
+                .source_token_protocol_fees_vault
+                .to_account_info(),
+            ctx.accounts.signer.to_account_info(),
+            ctx.remaining_accounts,
+            protocol_fee,
+            ctx.accounts.source_mint.decimals,
+        )?;
+    }
+    swap_token::transfer_from_vault(
+        ctx.accounts.destination_token_program.to_account_info(),
+        ctx.accounts.pool.to_account_info(),
+        ctx.accounts.destination_vault.to_account_info(),
+        ctx.accounts.destination_mint.to_account_info(),
+        ctx.accounts.destination_user_ata.to_account_info(),
+        ctx.accounts.pool_authority.to_account_info(),
+        ctx.remaining_accounts,
+        pool.bump_seed(),
+        destination_amount,
+        ctx.accounts.destination_mint.decimals,
+    )?;
+
+    let total_fees = to_u64!(result.total_fees)?;
+    let trade_fee = to_u64!(result.trade_fee)?;
+
+    msg!(
+        "Swap outputs: token_in_amount={}, token_out_amount={}, total_fees={}, trade_fee={}, owner_fee={}, protocol_fee={}",
+        source_amount_to_vault,
+        destination_amount,
+        total_fees,
+        trade_fee,
+        owner_fee,
+        protocol_fee,
+    );
+    emitted!(event::Swap {
+        token_in_amount: source_amount_to_vault,
+        token_out_amount: destination_amount,
+        total_fees,
+        trade_fee,
+        owner_fee,
+        host_fee: 0,
+        protocol_fee,
+        trade_fee_numerator: pool.fees.trade_fee_numerator,
+        trade_fee_denominator: pool.fees.trade_fee_denominator,
+    });
+}
+
+/// Swap between two tokens of a multi-token stable pool
+///
+/// The vaults of every token of the pool are passed in pool order as the first remaining accounts
+#[derive(Accounts)]
+pub struct MultiStableSwap<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        has_one = pool_authority @ SwapError::InvalidProgramAddress,
+    )]
+    pub pool: AccountLoader<'info, MultiStablePool>,
+
+    /// Program wide constraints the pool is validated against
+    #[account(
+        seeds = [seeds::GLOBAL_CONFIG],
+        bump,
+    )]
+    pub global_config: AccountLoader<'info, GlobalConfig>,
+
+    /// CHECK: has_one constraint on the pool
+    pub pool_authority: AccountInfo<'info>,
+
+    /// CHECK: checked in the handler
+    // note - constraint repeated for clarity
+    #[account(
+        constraint = source_mint.key() != destination_mint.key() @ SwapError::RepeatedMint,
+    )]
+    pub source_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: checked in the handler
+    // note - constraint repeated for clarity
+    #[account(
+        constraint = source_mint.key() != destination_mint.key() @ SwapError::RepeatedMint,
+    )]
+    pub destination_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: checked in the handler
+    #[account(mut)]
+    pub source_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: checked in the handler
+    #[account(mut)]
+    pub destination_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Account to collect fees into
+    /// CHECK: checked in the handler
+    #[account(mut)]
+    pub source_token_fees_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Account to collect the protocol share of the fees into
+    /// CHECK: checked in the handler
+    #[account(mut)]
+    pub source_token_protocol_fees_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Signer's source token account
+    // note - authority constraint repeated for clarity
+    #[account(mut,
+        token::mint = source_mint,
+        token::authority = destination_user_ata.owner,
+        token::token_program = source_token_program,
+    )]
+    pub source_user_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Signer's destination token account
+    // note - authority constraint repeated for clarity
+    #[account(mut,
+        token::mint = destination_mint,
+        token::authority = source_user_ata.owner,
+        token::token_program = destination_token_program,
+    )]
+    pub destination_user_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token program for the source mint
+    pub source_token_program: Interface<'info, TokenInterface>,
+    /// Token program for the destination mint
+    pub destination_token_program: Interface<'info, TokenInterface>,
+}
+
+pub(crate) mod utils {
+    use super::*;
+
+    /// Validate the swap accounts against the pool, returning the source and destination token indices
+    pub fn validate_inputs(
+        ctx: &Context<MultiStableSwap>,
+        pool: &MultiStablePool,
+    ) -> Result<(usize, usize)> {
+        require_msg!(
+            !pool.withdrawals_only(),
+            SwapError::WithdrawalsOnlyMode,
+            "The pool is in withdrawals only mode"
+        );
+        // curve types can be disallowed after pools were created with them
+        ctx.accounts
+            .global_config
+            .load()?
+            .validate_curve(CurveType::Stable)?;
+
+        let source_index = pool.token_index(&ctx.accounts.source_mint.key())?;
+        let destination_index = pool.token_index(&ctx.accounts.destination_mint.key())?;
+        let source = &pool.tokens[source_index];
+        let destination = &pool.tokens[destination_index];
+        require_msg!(
+            ctx.accounts.source_vault.key() == source.vault,
+            SwapError::IncorrectSwapAccount,
+            &format!(
+                "IncorrectSwapAccount: source_vault.key ({}) != token vault ({})",
+                ctx.accounts.source_vault.key(),
+                source.vault
+            )
+        );
+        require_msg!(
+            ctx.accounts.destination_vault.key() == destination.vault,
+            SwapError::IncorrectSwapAccount,
+            &format!(
+                "IncorrectSwapAccount: destination_vault.key ({}) != token vault ({})",
+                ctx.accounts.destination_vault.key(),
+                destination.vault
+            )
+        );
+        require_msg!(
+            ctx.accounts.source_token_fees_vault.key() == source.fees_vault,
+            SwapError::IncorrectFeeAccount,
+            &format!(
+                "IncorrectFeeAccount: source_token_fees_vault.key ({}) != token fees vault ({})",
+                ctx.accounts.source_token_fees_vault.key(),
+                source.fees_vault
+            )
+        );
+        require_msg!(
+            ctx.accounts.source_token_protocol_fees_vault.key() == source.protocol_fees_vault,
+            SwapError::IncorrectFeeAccount,
+            &format!(
+                "IncorrectFeeAccount: source_token_protocol_fees_vault.key ({}) != token protocol fees vault ({})",
+                ctx.accounts.source_token_protocol_fees_vault.key(),
+                source.protocol_fees_vault
+            )
+        );
+        Ok((source_index, destination_index))
+    }
+
+    /// Balances of the pool vaults, passed in pool order at the start of the remaining accounts
+    pub fn vault_balances(
+        pool: &MultiStablePool,
+        remaining_accounts: &[AccountInfo],
+    ) -> Result<Vec<u128>> {
+        let tokens = pool.tokens();
+        require_msg!(
+            remaining_accounts.len() >= tokens.len(),
+            SwapError::InvalidTokenCount,
+            &format!(
+                "InvalidTokenCount: {} remaining accounts for the vaults of {} tokens",
+                remaining_accounts.len(),
+                tokens.len()
+            )
+        );
+        tokens
+            .iter()
+            .zip(remaining_accounts.iter())
+            .map(|(token, vault)| {
+                require_msg!(
+                    vault.key() == token.vault,
+                    SwapError::IncorrectSwapAccount,
+                    &format!(
+                        "IncorrectSwapAccount: vault.key ({}) != token vault ({})",
+                        vault.key(),
+                        token.vault
+                    )
+                );
+                let vault = InterfaceAccount::<TokenAccount>::try_from(vault)?;
+                Ok(u128::from(vault.amount))
+            })
+            .collect()
+    }
+}
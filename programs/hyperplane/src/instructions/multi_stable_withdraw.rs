@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    emitted,
+    error::SwapError,
+    event,
+    multi_stable_deposit::{utils::parse_tokens, MultiStableLiquidity},
+    require_msg,
+    utils::{pool_token, swap_token},
+};
+
+pub fn handler_multi_stable_withdraw<'info>(
+    ctx: Context<'_, '_, '_, 'info, MultiStableLiquidity<'info>>,
+    pool_token_amount: u64,
+    minimum_token_amounts: Vec<u64>,
+) -> Result<event::MultiStableWithdraw> {
+    let pool = ctx.accounts.pool.load()?;
+    let tokens = parse_tokens(&ctx, &pool)?;
+    require_msg!(
+        minimum_token_amounts.len() == tokens.len(),
+        SwapError::InvalidTokenCount,
+        &format!(
+            "InvalidTokenCount: {} minimum token amounts for a pool of {} tokens",
+            minimum_token_amounts.len(),
+            tokens.len()
+        )
+    );
+    require_msg!(
+        pool_token_amount > 0,
+        SwapError::ZeroTradingTokens,
+        "Cannot withdraw zero pool tokens"
+    );
+    require_msg!(
+        ctx.accounts.pool_token_user_ata.amount >= pool_token_amount,
+        SwapError::InsufficientPoolTokenFunds,
+        &format!(
+            "InsufficientPoolTokenFunds: pool_token_user_ata.amount={} < pool_token_amount={}",
+            ctx.accounts.pool_token_user_ata.amount, pool_token_amount
+        )
+    );
+
+    let balances: Vec<u128> = tokens
+        .iter()
+        .map(|token| u128::from(token.vault.amount))
+        .collect();
+    msg!(
+        "Withdraw inputs: pool_token_amount={}, minimum_token_amounts={:?}, balances={:?}, pool_token_supply={}",
+        pool_token_amount,
+        minimum_token_amounts,
+        balances,
+        ctx.accounts.pool_token_mint.supply,
+    );
+
+    let token_amounts = pool
+        .curve()
+        .withdraw(
+            u128::from(pool_token_amount),
+            &balances,
+            u128::from(ctx.accounts.pool_token_mint.supply),
+        )?
+        .into_iter()
+        .map(|amount| u64::try_from(amount).map_err(|_| error!(SwapError::ConversionFailure)))
+        .collect::<Result<Vec<u64>>>()?;
+    for (amount, minimum_amount) in token_amounts.iter().zip(minimum_token_amounts.iter()) {
+        require_msg!(
+            amount >= minimum_amount,
+            SwapError::ExceededSlippage,
+            &format!(
+                "ExceededSlippage: token_amounts={:?} < minimum_token_amounts={:?}",
+                token_amounts, minimum_token_amounts
+            )
+        );
+    }
+    require_msg!(
+        token_amounts.iter().any(|amount| *amount > 0),
+        SwapError::ZeroTradingTokens,
+        "Withdraw would receive zero trading tokens"
+    );
+
+    pool_token::burn(
+        ctx.accounts.pool_token_mint.to_account_info(),
+        ctx.accounts.pool_token_user_ata.to_account_info(),
+        ctx.accounts.signer.to_account_info(),
+        ctx.accounts.pool_token_program.to_account_info(),
+        pool_token_amount,
+    )?;
+
+    for (token, amount) in tokens.iter().zip(token_amounts.iter()) {
+        if *amount > 0 {
+            swap_token::transfer_from_vault(
+                token.token_program.to_account_info(),
+                ctx.accounts.pool.to_account_info(),
+                token.vault.to_account_info(),
+                token.mint.to_account_info(),
+                token.user_ata.to_account_info(),
+                ctx.accounts.pool_authority.to_account_info(),
+                ctx.remaining_accounts,
+                pool.bump_seed(),
+                *amount,
+                token.mint.decimals,
+            )?;
+        }
+    }
+
+    emitted!(event::MultiStableWithdraw {
+        token_amounts,
+        pool_token_amount,
+    });
+}
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    emitted,
+    error::SwapError,
+    event,
+    multi_stable_deposit::{utils::parse_tokens, MultiStableLiquidity},
+    require_msg, to_u64,
+    utils::{pool_token, swap_token},
+};
+
+pub fn handler_multi_stable_withdraw_imbalanced<'info>(
+    ctx: Context<'_, '_, '_, 'info, MultiStableLiquidity<'info>>,
+    token_amounts: Vec<u64>,
+    maximum_pool_token_amount: u64,
+) -> Result<event::MultiStableWithdraw> {
+    let pool = ctx.accounts.pool.load()?;
+    let tokens = parse_tokens(&ctx, &pool)?;
+    require_msg!(
+        token_amounts.len() == tokens.len(),
+        SwapError::InvalidTokenCount,
+        &format!(
+            "InvalidTokenCount: {} token amounts for a pool of {} tokens",
+            token_amounts.len(),
+            tokens.len()
+        )
+    );
+    require_msg!(
+        token_amounts.iter().any(|amount| *amount > 0),
+        SwapError::ZeroTradingTokens,
+        "Cannot withdraw zero trading tokens"
+    );
+
+    let amounts: Vec<u128> = token_amounts.iter().copied().map(u128::from).collect();
+    let balances: Vec<u128> = tokens
+        .iter()
+        .map(|token| u128::from(token.vault.amount))
+        .collect();
+    msg!(
+        "Withdraw inputs: token_amounts={:?}, maximum_pool_token_amount={}, balances={:?}, pool_token_supply={}",
+        token_amounts,
+        maximum_pool_token_amount,
+        balances,
+        ctx.accounts.pool_token_mint.supply,
+    );
+
+    let pool_token_amount = pool.curve().withdraw_imbalanced(
+        &amounts,
+        &balances,
+        u128::from(ctx.accounts.pool_token_mint.supply),
+        &pool.fees,
+    )?;
+    let pool_token_amount = to_u64!(pool_token_amount)?;
+    require_msg!(
+        pool_token_amount <= maximum_pool_token_amount,
+        SwapError::ExceededSlippage,
+        &format!(
+            "ExceededSlippage: pool_token_amount={} > maximum_pool_token_amount={}",
+            pool_token_amount, maximum_pool_token_amount
+        )
+    );
+    require_msg!(
+        ctx.accounts.pool_token_user_ata.amount >= pool_token_amount,
+        SwapError::InsufficientPoolTokenFunds,
+        &format!(
+            "InsufficientPoolTokenFunds: pool_token_user_ata.amount={} < pool_token_amount={}",
+            ctx.accounts.pool_token_user_ata.amount, pool_token_amount
+        )
+    );
+
+    pool_token::burn(
+        ctx.accounts.pool_token_mint.to_account_info(),
+        ctx.accounts.pool_token_user_ata.to_account_info(),
+        ctx.accounts.signer.to_account_info(),
+        ctx.accounts.pool_token_program.to_account_info(),
+        pool_token_amount,
+    )?;
+
+    for (token, amount) in tokens.iter().zip(token_amounts.iter()) {
+        if *amount > 0 {
+            swap_token::transfer_from_vault(
+                token.token_program.to_account_info(),
+                ctx.accounts.pool.to_account_info(),
+                token.vault.to_account_info(),
+                token.mint.to_account_info(),
+                token.user_ata.to_account_info(),
+                ctx.accounts.pool_authority.to_account_info(),
+                ctx.remaining_accounts,
+                pool.bump_seed(),
+                *amount,
+                token.mint.decimals,
+            )?;
+        }
+    }
+
+    emitted!(event::MultiStableWithdraw {
+        token_amounts,
+        pool_token_amount,
+    });
+}
@@ -0,0 +1,112 @@
+use std::cmp;
+
+use anchor_lang::{
+    accounts::{interface::Interface, interface_account::InterfaceAccount},
+    prelude::*,
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    emitted,
+    error::SwapError,
+    event, require_msg,
+    state::{GlobalConfig, MultiStablePool},
+    utils::{seeds, swap_token},
+};
+
+/// Withdraw the protocol share of the owner trading fees of a multi-token stable pool to the
+/// protocol authority
+pub fn handler_multi_stable_withdraw_protocol_fees<'info>(
+    ctx: Context<'_, '_, '_, 'info, MultiStableWithdrawProtocolFees<'info>>,
+    requested_withdraw_amount: u64,
+) -> Result<event::WithdrawProtocolFees> {
+    let pool = ctx.accounts.pool.load()?;
+    let token = &pool.tokens[pool.token_index(&ctx.accounts.fees_mint.key())?];
+    require_msg!(
+        token.protocol_fees_vault == ctx.accounts.protocol_fees_vault.key(),
+        SwapError::IncorrectFeeAccount,
+        &format!(
+            "IncorrectFeeAccount: token protocol fees vault ({}) != protocol_fees_vault.key ({})",
+            token.protocol_fees_vault,
+            ctx.accounts.protocol_fees_vault.key(),
+        )
+    );
+
+    require_msg!(
+        requested_withdraw_amount > 0,
+        SwapError::ZeroTradingTokens,
+        "Cannot withdraw zero protocol fees"
+    );
+
+    let withdraw_amount = cmp::min(
+        requested_withdraw_amount,
+        ctx.accounts.protocol_fees_vault.amount,
+    );
+
+    msg!(
+        "Withdrawing from protocol fees vault: withdraw_amount={}, requested_withdraw_amount={}",
+        withdraw_amount,
+        requested_withdraw_amount,
+    );
+
+    swap_token::transfer_from_vault(
+        ctx.accounts.fees_token_program.to_account_info(),
+        ctx.accounts.pool.to_account_info(),
+        ctx.accounts.protocol_fees_vault.to_account_info(),
+        ctx.accounts.fees_mint.to_account_info(),
+        ctx.accounts.protocol_authority_fees_ata.to_account_info(),
+        ctx.accounts.pool_authority.to_account_info(),
+        ctx.remaining_accounts,
+        pool.bump_seed(),
+        withdraw_amount,
+        ctx.accounts.fees_mint.decimals,
+    )?;
+
+    emitted!(event::WithdrawProtocolFees { withdraw_amount });
+}
+
+#[derive(Accounts)]
+pub struct MultiStableWithdrawProtocolFees<'info> {
+    #[account(mut)]
+    pub protocol_authority: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::GLOBAL_CONFIG],
+        bump,
+        has_one = protocol_authority @ SwapError::InvalidProtocolAuthority,
+    )]
+    pub global_config: AccountLoader<'info, GlobalConfig>,
+
+    #[account(
+        has_one = pool_authority @ SwapError::InvalidProgramAddress,
+    )]
+    pub pool: AccountLoader<'info, MultiStablePool>,
+
+    /// CHECK: has_one constraint on the pool
+    pub pool_authority: AccountInfo<'info>,
+
+    /// CHECK: checked in the handler
+    #[account(
+        token::token_program = fees_token_program,
+    )]
+    pub fees_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Protocol fee vault to withdraw from
+    /// CHECK: checked in the handler
+    #[account(mut,
+        constraint = protocol_fees_vault.amount > 0 @ SwapError::ZeroTradingTokens,
+        token::token_program = fees_token_program,
+    )]
+    pub protocol_fees_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Protocol authority's token account to withdraw fees to
+    #[account(mut,
+        token::mint = fees_mint,
+        token::authority = protocol_authority,
+        token::token_program = fees_token_program,
+    )]
+    pub protocol_authority_fees_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token program for the fee token mint
+    pub fees_token_program: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::{emitted, event, state::SwapPool};
+
+pub fn handler_propose_new_admin(
+    ctx: Context<ProposeNewAdmin>,
+    new_admin: Pubkey,
+) -> Result<event::ProposeNewAdmin> {
+    let pool = &mut ctx.accounts.pool.load_mut()?;
+
+    msg!(
+        "Proposing new pool admin: admin={}, pending_admin={}",
+        pool.admin,
+        new_admin
+    );
+    pool.pending_admin = new_admin;
+
+    emitted!(event::ProposeNewAdmin {
+        admin: pool.admin,
+        pending_admin: new_admin,
+    });
+}
+
+#[derive(Accounts)]
+pub struct ProposeNewAdmin<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut,
+        has_one = admin,
+    )]
+    pub pool: AccountLoader<'info, SwapPool>,
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    emitted,
+    error::SwapError,
+    event,
+    state::{StableCurve, SwapPool},
+};
+
+pub fn handler_ramp_amp(
+    ctx: Context<RampAmp>,
+    target_amp: u64,
+    ramp_end_ts: u64,
+) -> Result<event::RampAmp> {
+    let now = u64::try_from(Clock::get()?.unix_timestamp)
+        .map_err(|_| error!(SwapError::ConversionFailure))?;
+    let swap_curve = &mut ctx.accounts.swap_curve;
+
+    msg!(
+        "Ramping amp: target_amp={}, ramp_end_ts={}, now={}",
+        target_amp,
+        ramp_end_ts,
+        now
+    );
+    swap_curve.ramp_amp(target_amp, ramp_end_ts, now)?;
+
+    emitted!(event::RampAmp {
+        initial_amp: swap_curve.initial_amp,
+        target_amp: swap_curve.target_amp,
+        ramp_start_ts: swap_curve.ramp_start_ts,
+        ramp_end_ts: swap_curve.ramp_end_ts,
+    });
+}
+
+#[derive(Accounts)]
+pub struct RampAmp<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin,
+        has_one = swap_curve,
+    )]
+    pub pool: AccountLoader<'info, SwapPool>,
+
+    #[account(mut)]
+    pub swap_curve: Account<'info, StableCurve>,
+}
@@ -0,0 +1,462 @@
+use anchor_lang::{
+    accounts::{interface::Interface, interface_account::InterfaceAccount},
+    prelude::*,
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    curve,
+    curve::{base::SwapCurve, calculator::TradeDirection},
+    error::SwapError,
+    event, require_msg,
+    route_swap::utils::{parse_route, RouteHop},
+    state::{GlobalConfig, SwapPool, SwapState},
+    swap::utils::{mint_transfer_fee, update_dynamic_fee, update_price_accumulator},
+    utils::{pool_transfers::SwapParams, seeds, swap_token},
+};
+
+/// Maximum number of pools a route can swap through
+pub const MAX_ROUTE_HOPS: usize = 4;
+
+/// Number of remaining accounts required for each hop of a route:
+/// 0. `[writable]` pool
+/// 1. `[]` swap curve
+/// 2. `[]` pool authority
+/// 3. `[writable]` source vault
+/// 4. `[writable]` destination vault
+/// 5. `[writable]` source token fees vault
+/// 6. `[writable]` source token protocol fees vault, or the hyperplane program id for pools whose
+///    protocol fees vaults have not been initialized
+/// 7. `[]` destination mint
+/// 8. `[]` destination token program
+///
+/// Any remaining accounts after the hops are made available to the curves, e.g. oracle price feeds
+pub const ROUTE_HOP_ACCOUNTS: usize = 9;
+
+/// Swap `amount_in` of the source token through a route of pools, the output of each hop is
+/// transferred from the vault of its pool directly to the vaults of the next pool.
+///
+/// The intermediate hops send exactly what the next pool takes in, any dust left over after
+/// rounding stays in the vault of the pool it came from.
+pub fn handler_route_swap<'info>(
+    ctx: Context<'_, '_, '_, 'info, RouteSwap<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Result<Vec<event::Swap>> {
+    let route = parse_route(&ctx)?;
+    let curve_accounts = &ctx.remaining_accounts[route.len() * ROUTE_HOP_ACCOUNTS..];
+    msg!(
+        "Route swap inputs: hops={}, amount_in={}, minimum_amount_out={}",
+        route.len(),
+        amount_in,
+        minimum_amount_out
+    );
+
+    let global_config = ctx.accounts.global_config.load()?;
+
+    // calculate every hop up front, so intermediate amounts can be sent vault to vault
+    let mut swaps = Vec::with_capacity(route.len());
+    let mut hop_amount_in = amount_in;
+    for (i, hop) in route.iter().enumerate() {
+        let source_mint = route_source_mint(&ctx, &route, i);
+        let mut pool = hop.pool.load_mut()?;
+        let swap_curve = curve!(hop.swap_curve, pool, curve_accounts)
+            .with_pool_interest_bearing_mints(
+                &pool,
+                &[&source_mint, &hop.destination_mint.to_account_info()],
+                curve_accounts,
+            )?;
+        update_price_accumulator(
+            &mut pool,
+            &swap_curve,
+            hop.trade_direction,
+            hop.source_vault.amount,
+            hop.destination_vault.amount,
+        )?;
+        let fees = update_dynamic_fee(
+            &mut pool,
+            &swap_curve,
+            hop.trade_direction,
+            hop.source_vault.amount,
+            hop.destination_vault.amount,
+        )?;
+
+        msg!(
+            "Route swap hop {}: pool={}, trade_direction={:?}, swap_type={:?}, amount_in={}, source_token_balance={}, destination_token_balance={}",
+            i,
+            hop.pool.key(),
+            hop.trade_direction,
+            swap_curve.curve_type,
+            hop_amount_in,
+            hop.source_vault.amount,
+            hop.destination_vault.amount,
+        );
+        let hop_swap = SwapParams {
+            swap_curve: &swap_curve,
+            fees: &fees,
+            trade_direction: hop.trade_direction,
+            source_vault_amount: hop.source_vault.amount,
+            destination_vault_amount: hop.destination_vault.amount,
+            source_transfer_fee: mint_transfer_fee(&source_mint)?,
+            destination_transfer_fee: mint_transfer_fee(&hop.destination_mint.to_account_info())?,
+            host_fee: false,
+            // pools without protocol fees vaults pay no protocol fees
+            protocol_fee: hop
+                .source_token_protocol_fees_vault
+                .is_some()
+                .then_some(&*global_config),
+        }
+        .swap(hop_amount_in)?;
+
+        // the user may pay a little more than `amount_in` to cover transfer fee rounding on the
+        // first hop, as with `swap`, but intermediate hops can only use what the previous pool sends
+        let total_source_amount = hop_swap.source_amount()?;
+        require_msg!(
+            i == 0 || total_source_amount <= hop_amount_in,
+            SwapError::ExceededSlippage,
+            &format!(
+                "ExceededSlippage: hop {} requires {} > {} received from the previous hop",
+                i, total_source_amount, hop_amount_in
+            )
+        );
+        hop_amount_in = hop_swap.destination_amount_from_vault;
+        swaps.push(hop_swap);
+    }
+
+    let destination_amount_post_transfer_fees = swaps.last().unwrap().destination_amount;
+    require_msg!(
+        destination_amount_post_transfer_fees >= minimum_amount_out,
+        SwapError::ExceededSlippage,
+        &format!(
+            "ExceededSlippage: amount_received={} < minimum_amount_out={}",
+            destination_amount_post_transfer_fees, minimum_amount_out
+        )
+    );
+
+    for (i, (hop, hop_swap)) in route.iter().zip(swaps.iter()).enumerate() {
+        let source_mint = route_source_mint(&ctx, &route, i);
+        let decimals = route_source_decimals(&ctx, &route, i);
+        // the protocol fee is sent straight from the source like the owner fee, so the protocol
+        // receives the whole protocol fee after transfer fees. It is only non-zero when the
+        // protocol fees vault is present
+        let protocol_fees_vault = hop
+            .source_token_protocol_fees_vault
+            .as_ref()
+            .map(|vault| vault.to_account_info())
+            .unwrap_or_else(|| hop.source_token_fees_vault.to_account_info());
+        if i == 0 {
+            for (destination, amount) in [
+                (
+                    hop.source_vault.to_account_info(),
+                    hop_swap.source_amount_to_vault,
+                ),
+                (
+                    hop.source_token_fees_vault.to_account_info(),
+                    hop_swap.owner_fee_to_vault,
+                ),
+                (protocol_fees_vault.clone(), hop_swap.protocol_fee_to_vault),
+            ] {
+                if amount > 0 {
+                    swap_token::transfer_from_user(
+                        ctx.accounts.source_token_program.to_account_info(),
+                        ctx.accounts.source_user_ata.to_account_info(),
+                        source_mint.clone(),
+                        destination,
+                        ctx.accounts.signer.to_account_info(),
+                        curve_accounts,
+                        amount,
+                        decimals,
+                    )?;
+                }
+            }
+        } else {
+            let previous_hop = &route[i - 1];
+            let previous_bump_seed = previous_hop.pool.load()?.bump_seed();
+            for (destination, amount) in [
+                (
+                    hop.source_vault.to_account_info(),
+                    hop_swap.source_amount_to_vault,
+                ),
+                (
+                    hop.source_token_fees_vault.to_account_info(),
+                    hop_swap.owner_fee_to_vault,
+                ),
+                (protocol_fees_vault.clone(), hop_swap.protocol_fee_to_vault),
+            ] {
+                if amount > 0 {
+                    swap_token::transfer_from_vault(
+                        previous_hop.destination_token_program.to_account_info(),
+                        previous_hop.pool.to_account_info(),
+                        previous_hop.destination_vault.to_account_info(),
+                        source_mint.clone(),
+                        destination,
+                        previous_hop.pool_authority.clone(),
+                        curve_accounts,
+                        previous_bump_seed,
+                        amount,
+                        decimals,
+                    )?;
+                }
+            }
+        }
+    }
+
+    let last_hop = route.last().unwrap();
+    let last_swap = swaps.last().unwrap();
+    swap_token::transfer_from_vault(
+        ctx.accounts.destination_token_program.to_account_info(),
+        last_hop.pool.to_account_info(),
+        last_hop.destination_vault.to_account_info(),
+        ctx.accounts.destination_mint.to_account_info(),
+        ctx.accounts.destination_user_ata.to_account_info(),
+        last_hop.pool_authority.clone(),
+        curve_accounts,
+        last_hop.pool.load()?.bump_seed(),
+        last_swap.destination_amount_from_vault,
+        ctx.accounts.destination_mint.decimals,
+    )?;
+
+    // each intermediate pool pays out exactly what the next pool takes in
+    let mut events = Vec::with_capacity(route.len());
+    for (i, hop_swap) in swaps.iter().enumerate() {
+        let token_out_amount = match swaps.get(i + 1) {
+            Some(next_swap) => next_swap.source_amount()?,
+            None => hop_swap.destination_amount_from_vault,
+        };
+        let event = event::Swap {
+            token_out_amount,
+            ..hop_swap.event()
+        };
+        msg!(
+            "Route swap hop {} outputs: token_in_amount={}, token_out_amount={}, total_fees={}, trade_fee={}, owner_fee={}, protocol_fee={}",
+            i,
+            event.token_in_amount,
+            event.token_out_amount,
+            event.total_fees,
+            event.trade_fee,
+            event.owner_fee,
+            event.protocol_fee
+        );
+        emit!(event.clone());
+        events.push(event);
+    }
+
+    Ok(events)
+}
+
+/// Source mint of the `i`th hop - the route source mint, or the destination mint of the previous hop
+fn route_source_mint<'info>(
+    ctx: &Context<'_, '_, '_, 'info, RouteSwap<'info>>,
+    route: &[RouteHop<'info>],
+    i: usize,
+) -> AccountInfo<'info> {
+    match i {
+        0 => ctx.accounts.source_mint.to_account_info(),
+        _ => route[i - 1].destination_mint.to_account_info(),
+    }
+}
+
+fn route_source_decimals<'info>(
+    ctx: &Context<'_, '_, '_, 'info, RouteSwap<'info>>,
+    route: &[RouteHop<'info>],
+    i: usize,
+) -> u8 {
+    match i {
+        0 => ctx.accounts.source_mint.decimals,
+        _ => route[i - 1].destination_mint.decimals,
+    }
+}
+
+#[derive(Accounts)]
+pub struct RouteSwap<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// Program wide constraints each pool of the route is validated against
+    #[account(
+        seeds = [seeds::GLOBAL_CONFIG],
+        bump,
+    )]
+    pub global_config: AccountLoader<'info, GlobalConfig>,
+
+    /// Mint of the token swapped into the first pool of the route
+    /// CHECK: checked against the first hop in the handler
+    #[account(
+        mint::token_program = source_token_program,
+    )]
+    pub source_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Mint of the token received from the last pool of the route
+    /// CHECK: checked against the last hop in the handler
+    #[account(
+        constraint = source_mint.key() != destination_mint.key() @ SwapError::RepeatedMint,
+        mint::token_program = destination_token_program,
+    )]
+    pub destination_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Signer's source token account
+    #[account(mut,
+        token::mint = source_mint,
+        token::authority = signer,
+        token::token_program = source_token_program,
+    )]
+    pub source_user_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Signer's destination token account
+    #[account(mut,
+        token::mint = destination_mint,
+        token::authority = signer,
+        token::token_program = destination_token_program,
+    )]
+    pub destination_user_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token program for the source mint
+    pub source_token_program: Interface<'info, TokenInterface>,
+    /// Token program for the destination mint
+    pub destination_token_program: Interface<'info, TokenInterface>,
+}
+
+pub(crate) mod utils {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::swap::utils::validate_swap_accounts;
+
+    /// Accounts of a single hop of a route, parsed from the remaining accounts
+    pub struct RouteHop<'info> {
+        pub pool: AccountLoader<'info, SwapPool>,
+        pub swap_curve: AccountInfo<'info>,
+        pub pool_authority: AccountInfo<'info>,
+        pub source_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub destination_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub source_token_fees_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+        /// None for pools whose protocol fees vaults have not been initialized
+        pub source_token_protocol_fees_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+        pub destination_mint: Box<InterfaceAccount<'info, Mint>>,
+        pub destination_token_program: Interface<'info, TokenInterface>,
+        pub trade_direction: TradeDirection,
+    }
+
+    pub fn parse_route<'info>(
+        ctx: &Context<'_, '_, '_, 'info, RouteSwap<'info>>,
+    ) -> Result<Vec<RouteHop<'info>>> {
+        let hops = ctx.remaining_accounts.len() / ROUTE_HOP_ACCOUNTS;
+        require_msg!(
+            (1..=MAX_ROUTE_HOPS).contains(&hops),
+            SwapError::InvalidRoute,
+            &format!(
+                "InvalidRoute: {} remaining accounts is {} hops, expected between 1 and {} hops of {} accounts",
+                ctx.remaining_accounts.len(),
+                hops,
+                MAX_ROUTE_HOPS,
+                ROUTE_HOP_ACCOUNTS
+            )
+        );
+
+        let global_config = ctx.accounts.global_config.load()?;
+        let mut pools = BTreeSet::new();
+        let mut source_mint = ctx.accounts.source_mint.key();
+        let mut route = Vec::with_capacity(hops);
+        for accounts in ctx
+            .remaining_accounts
+            .chunks_exact(ROUTE_HOP_ACCOUNTS)
+            .take(hops)
+        {
+            let hop = parse_hop(accounts, &global_config, &source_mint)?;
+            // vault balances are read once for the whole route, so each pool can only be used once
+            require_msg!(
+                pools.insert(hop.pool.key()),
+                SwapError::InvalidRoute,
+                &format!("InvalidRoute: pool {} is repeated", hop.pool.key())
+            );
+            source_mint = hop.destination_mint.key();
+            route.push(hop);
+        }
+        require_msg!(
+            source_mint == ctx.accounts.destination_mint.key(),
+            SwapError::InvalidRoute,
+            &format!(
+                "InvalidRoute: route destination mint {} != destination_mint {}",
+                source_mint,
+                ctx.accounts.destination_mint.key()
+            )
+        );
+        Ok(route)
+    }
+
+    fn parse_hop<'info>(
+        accounts: &[AccountInfo<'info>],
+        global_config: &GlobalConfig,
+        source_mint: &Pubkey,
+    ) -> Result<RouteHop<'info>> {
+        let [pool, swap_curve, pool_authority, source_vault, destination_vault, source_token_fees_vault, source_token_protocol_fees_vault, destination_mint, destination_token_program] =
+            accounts
+        else {
+            return err!(SwapError::InvalidRoute);
+        };
+
+        let pool = AccountLoader::<SwapPool>::try_from(pool)?;
+        // the program id stands in for the protocol fees vault of pools without one
+        let source_token_protocol_fees_vault = (source_token_protocol_fees_vault.key()
+            != crate::ID)
+            .then_some(source_token_protocol_fees_vault);
+        let destination_mint = Box::new(InterfaceAccount::<Mint>::try_from(destination_mint)?);
+        let destination_token_program =
+            Interface::<TokenInterface>::try_from(destination_token_program)?;
+        let trade_direction = {
+            let pool_state = pool.load()?;
+            require_msg!(
+                swap_curve.key() == pool_state.swap_curve,
+                ErrorCode::ConstraintHasOne,
+                &format!(
+                    "ConstraintHasOne: swap_curve.key ({}) != pool.swap_curve ({})",
+                    swap_curve.key(),
+                    pool_state.swap_curve
+                )
+            );
+            require_msg!(
+                pool_authority.key() == pool_state.pool_authority,
+                SwapError::InvalidProgramAddress,
+                &format!(
+                    "InvalidProgramAddress: pool_authority.key ({}) != pool.pool_authority ({})",
+                    pool_authority.key(),
+                    pool_state.pool_authority
+                )
+            );
+            require_msg!(
+                destination_mint.to_account_info().owner == destination_token_program.key,
+                ErrorCode::ConstraintMintTokenProgram,
+                &format!(
+                    "ConstraintMintTokenProgram: destination_mint.owner ({}) != destination_token_program.key ({})",
+                    destination_mint.to_account_info().owner,
+                    destination_token_program.key()
+                )
+            );
+            global_config.validate_curve(pool_state.curve_type())?;
+            validate_swap_accounts(
+                &pool_state,
+                source_mint,
+                &destination_mint.key(),
+                source_vault.key,
+                destination_vault.key,
+                source_token_fees_vault.key,
+                source_token_protocol_fees_vault.map(|vault| vault.key),
+            )?
+        };
+
+        Ok(RouteHop {
+            pool,
+            swap_curve: swap_curve.clone(),
+            pool_authority: pool_authority.clone(),
+            source_vault: Box::new(InterfaceAccount::try_from(source_vault)?),
+            destination_vault: Box::new(InterfaceAccount::try_from(destination_vault)?),
+            source_token_fees_vault: Box::new(InterfaceAccount::try_from(source_token_fees_vault)?),
+            source_token_protocol_fees_vault: source_token_protocol_fees_vault
+                .map(|vault| InterfaceAccount::try_from(vault).map(Box::new))
+                .transpose()?,
+            destination_mint,
+            destination_token_program,
+            trade_direction,
+        })
+    }
+}
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    curve::rate_provider::RateProvider,
+    emitted,
+    error::SwapError,
+    event,
+    state::{StableCurve, SwapPool},
+};
+
+pub fn handler_set_rate_provider(
+    ctx: Context<SetRateProvider>,
+    rate_provider: RateProvider,
+) -> Result<event::SetRateProvider> {
+    let clock = Clock::get()?;
+    let now =
+        u64::try_from(clock.unix_timestamp).map_err(|_| error!(SwapError::ConversionFailure))?;
+    let token_b_mint = ctx.accounts.pool.load()?.token_b_mint;
+    let swap_curve = &mut ctx.accounts.swap_curve;
+
+    msg!("Setting rate provider: rate_provider={:?}", rate_provider);
+    swap_curve.set_rate_provider(
+        rate_provider,
+        &token_b_mint,
+        ctx.remaining_accounts,
+        now,
+        clock.epoch,
+    )?;
+
+    emitted!(event::SetRateProvider {
+        rate_provider_type: swap_curve.rate_provider_type,
+        rate_provider: swap_curve.rate_provider,
+        token_b_rate: swap_curve.token_b_rate,
+        max_rate_age_sec: swap_curve.max_rate_age_sec,
+        max_rate_change_bps: swap_curve.max_rate_change_bps,
+    });
+}
+
+/// Configure the token B rate provider of a stable curve
+///
+/// A stake pool rate provider must also be passed as a remaining account
+#[derive(Accounts)]
+pub struct SetRateProvider<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin,
+        has_one = swap_curve,
+    )]
+    pub pool: AccountLoader<'info, SwapPool>,
+
+    #[account(mut)]
+    pub swap_curve: Account<'info, StableCurve>,
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    emitted,
+    error::SwapError,
+    event,
+    state::{StableCurve, SwapPool},
+};
+
+pub fn handler_stop_ramp_amp(ctx: Context<StopRampAmp>) -> Result<event::StopRampAmp> {
+    let now = u64::try_from(Clock::get()?.unix_timestamp)
+        .map_err(|_| error!(SwapError::ConversionFailure))?;
+    let swap_curve = &mut ctx.accounts.swap_curve;
+
+    swap_curve.stop_ramp_amp(now)?;
+    msg!("Stopped amp ramp: amp={}, now={}", swap_curve.amp, now);
+
+    emitted!(event::StopRampAmp {
+        amp: swap_curve.amp,
+    });
+}
+
+#[derive(Accounts)]
+pub struct StopRampAmp<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin,
+        has_one = swap_curve,
+    )]
+    pub pool: AccountLoader<'info, SwapPool>,
+
+    #[account(mut)]
+    pub swap_curve: Account<'info, StableCurve>,
+}
@@ -4,47 +4,57 @@ use anchor_lang::{
     accounts::{interface::Interface, interface_account::InterfaceAccount},
     prelude::*,
 };
-use anchor_spl::{
-    token_2022::spl_token_2022::extension::{
-        transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions,
-    },
-    token_interface::{Mint, TokenAccount, TokenInterface},
-};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 use crate::{
     curve,
-    curve::{base::SwapCurve, calculator::TradeDirection},
+    curve::{
+        base::SwapCurve,
+        calculator::{AorB, TradeDirection},
+    },
     emitted,
     error::SwapError,
     event, require_msg,
-    state::{SwapPool, SwapState},
+    state::{GlobalConfig, SwapPool, SwapState},
     swap::utils::validate_inputs,
-    to_u64, try_math,
-    utils::{math::TryMath, swap_token},
+    utils::{pool_transfers::SwapParams, seeds, swap_token},
 };
 
-pub fn handler_swap(
-    ctx: Context<Swap>,
+pub fn handler_swap<'info>(
+    ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
     amount_in: u64,
     minimum_amount_out: u64,
 ) -> Result<event::Swap> {
-    let pool = ctx.accounts.pool.load()?;
+    let mut pool = ctx.accounts.pool.load_mut()?;
     let trade_direction = validate_inputs(&ctx, &pool)?;
-    let swap_curve = curve!(ctx.accounts.swap_curve, pool);
-
-    // Take transfer fees into account for actual amount transferred in
-    let actual_amount_in = utils::sub_input_transfer_fees(
-        &ctx.accounts.source_mint.to_account_info(),
-        &pool.fees,
-        amount_in,
-        ctx.accounts.source_token_host_fees_account.is_some(),
+    let swap_curve = curve!(ctx.accounts.swap_curve, pool, ctx.remaining_accounts)
+        .with_pool_interest_bearing_mints(
+            &pool,
+            &[
+                &ctx.accounts.source_mint.to_account_info(),
+                &ctx.accounts.destination_mint.to_account_info(),
+            ],
+            ctx.remaining_accounts,
+        )?;
+    utils::update_price_accumulator(
+        &mut pool,
+        &swap_curve,
+        trade_direction,
+        ctx.accounts.source_vault.amount,
+        ctx.accounts.destination_vault.amount,
+    )?;
+    let fees = utils::update_dynamic_fee(
+        &mut pool,
+        &swap_curve,
+        trade_direction,
+        ctx.accounts.source_vault.amount,
+        ctx.accounts.destination_vault.amount,
     )?;
 
     msg!(
-        "Swap inputs: trade_direction={:?}, amount_in={}, actual_amount_in={}, minimum_amount_out={}",
+        "Swap inputs: trade_direction={:?}, amount_in={}, minimum_amount_out={}",
         trade_direction,
         amount_in,
-        actual_amount_in,
         minimum_amount_out
     );
     msg!(
@@ -53,124 +63,50 @@ pub fn handler_swap(
         ctx.accounts.source_vault.amount,
         ctx.accounts.destination_vault.amount,
     );
-    let result = swap_curve
-        .swap(
-            u128::from(actual_amount_in),
-            u128::from(ctx.accounts.source_vault.amount),
-            u128::from(ctx.accounts.destination_vault.amount),
-            trade_direction,
-            pool.fees(),
-        )
-        .map_err(|_| error!(SwapError::ZeroTradingTokens))?;
-
-    // Re-calculate the source amount swapped based on what the curve says
-    let source_amount_to_vault = to_u64!(result.source_amount_to_vault)?;
-    let source_amount_to_vault = utils::add_inverse_transfer_fee(
-        &ctx.accounts.source_mint.to_account_info(),
-        source_amount_to_vault,
-    )?;
-
-    let destination_amount_from_vault = to_u64!(result.destination_amount_swapped)?;
-    let destination_amount_post_transfer_fees = utils::sub_transfer_fee(
-        &ctx.accounts.destination_mint.to_account_info(),
-        destination_amount_from_vault,
-    )?;
+    let global_config = ctx.accounts.global_config.load()?;
+    let transfers = SwapParams {
+        swap_curve: &swap_curve,
+        fees: &fees,
+        trade_direction,
+        source_vault_amount: ctx.accounts.source_vault.amount,
+        destination_vault_amount: ctx.accounts.destination_vault.amount,
+        source_transfer_fee: utils::mint_transfer_fee(&ctx.accounts.source_mint.to_account_info())?,
+        destination_transfer_fee: utils::mint_transfer_fee(
+            &ctx.accounts.destination_mint.to_account_info(),
+        )?,
+        host_fee: ctx.accounts.source_token_host_fees_account.is_some(),
+        // pools without protocol fees vaults pay no protocol fees
+        protocol_fee: ctx
+            .accounts
+            .source_token_protocol_fees_vault
+            .is_some()
+            .then_some(&*global_config),
+    }
+    .swap(amount_in)?;
 
-    msg!(
-        "Swap result: total_source_debit_amount={}, source_amount_swapped={}, source_amount_to_vault={}, trade_fee={}, owner_fee={}, destination_amount_from_vault={}, destination_amount_post_transfer_fees={}",
-        result.total_source_amount_swapped,
-        result.source_amount_swapped,
-        source_amount_to_vault,
-        result.trade_fee,
-        result.owner_fee,
-        destination_amount_from_vault,
-        destination_amount_post_transfer_fees
-    );
     require_msg!(
-        destination_amount_post_transfer_fees >= minimum_amount_out,
+        transfers.destination_amount >= minimum_amount_out,
         SwapError::ExceededSlippage,
         &format!(
             "ExceededSlippage: amount_received={} < minimum_amount_out={}",
-            destination_amount_post_transfer_fees, minimum_amount_out
+            transfers.destination_amount, minimum_amount_out
         )
     );
 
-    swap_token::transfer_from_user(
-        ctx.accounts.source_token_program.to_account_info(),
-        ctx.accounts.source_user_ata.to_account_info(),
-        ctx.accounts.source_mint.to_account_info(),
-        ctx.accounts.source_vault.to_account_info(),
-        ctx.accounts.signer.to_account_info(),
-        source_amount_to_vault,
-        ctx.accounts.source_mint.decimals,
-    )?;
-
-    if result.owner_fee > 0 {
-        let mut owner_fee = result.owner_fee;
-        // Allow none to fall through
-        if let Some(host_fees_account) = &ctx.accounts.source_token_host_fees_account {
-            let host_fee = pool
-                .fees()
-                .host_fee(owner_fee)
-                .map_err(|_| error!(SwapError::FeeCalculationFailure))?;
-            if host_fee > 0 {
-                owner_fee = try_math!(owner_fee.try_sub(host_fee))?;
-                let host_fee = utils::add_inverse_transfer_fee(
-                    &ctx.accounts.source_mint.to_account_info(),
-                    to_u64!(host_fee)?,
-                )?;
-
-                swap_token::transfer_from_user(
-                    ctx.accounts.source_token_program.to_account_info(),
-                    ctx.accounts.source_user_ata.to_account_info(),
-                    ctx.accounts.source_mint.to_account_info(),
-                    host_fees_account.to_account_info(),
-                    ctx.accounts.signer.to_account_info(),
-                    host_fee,
-                    ctx.accounts.source_mint.decimals,
-                )?;
-            }
-        }
-        let owner_fee = utils::add_inverse_transfer_fee(
-            &ctx.accounts.source_mint.to_account_info(),
-            to_u64!(owner_fee)?,
-        )?;
-        swap_token::transfer_from_user(
-            ctx.accounts.source_token_program.to_account_info(),
-            ctx.accounts.source_user_ata.to_account_info(),
-            ctx.accounts.source_mint.to_account_info(),
-            ctx.accounts.source_token_fees_vault.to_account_info(),
-            ctx.accounts.signer.to_account_info(),
-            owner_fee,
-            ctx.accounts.source_mint.decimals,
-        )?;
-    }
-
-    swap_token::transfer_from_vault(
-        ctx.accounts.destination_token_program.to_account_info(),
-        ctx.accounts.pool.to_account_info(),
-        ctx.accounts.destination_vault.to_account_info(),
-        ctx.accounts.destination_mint.to_account_info(),
-        ctx.accounts.destination_user_ata.to_account_info(),
-        ctx.accounts.pool_authority.to_account_info(),
-        pool.bump_seed(),
-        destination_amount_from_vault,
-        ctx.accounts.destination_mint.decimals,
-    )?;
-
-    let total_fees = to_u64!(result.total_fees)?;
+    utils::transfer_swap(&ctx, &pool, &transfers)?;
 
+    let event = transfers.event();
     msg!(
-        "Swap outputs: token_in_amount={}, token_out_amount={}, total_fees={}",
-        source_amount_to_vault,
-        destination_amount_from_vault,
-        total_fees
+        "Swap outputs: token_in_amount={}, token_out_amount={}, total_fees={}, trade_fee={}, owner_fee={}, host_fee={}, protocol_fee={}",
+        event.token_in_amount,
+        event.token_out_amount,
+        event.total_fees,
+        event.trade_fee,
+        event.owner_fee,
+        event.host_fee,
+        event.protocol_fee,
     );
-    emitted!(event::Swap {
-        token_in_amount: source_amount_to_vault,
-        token_out_amount: destination_amount_from_vault,
-        total_fees,
-    });
+    emitted!(event);
 }
 
 #[derive(Accounts)]
@@ -184,6 +120,13 @@ pub struct Swap<'info> {
     )]
     pub pool: AccountLoader<'info, SwapPool>,
 
+    /// Program wide constraints the pool is validated against
+    #[account(
+        seeds = [seeds::GLOBAL_CONFIG],
+        bump,
+    )]
+    pub global_config: AccountLoader<'info, GlobalConfig>,
+
     /// CHECK: has_one constraint on the pool
     pub swap_curve: UncheckedAccount<'info>,
 
@@ -217,6 +160,12 @@ pub struct Swap<'info> {
     #[account(mut)]
     pub source_token_fees_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    /// Account to collect the protocol share of the owner fees into, none for pools whose
+    /// protocol fees vaults have not been initialized, which pay no protocol fees
+    /// CHECK: checked in the handler
+    #[account(mut)]
+    pub source_token_protocol_fees_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
     /// Signer's source token account
     // note - authority constraint repeated for clarity
     #[account(mut,
@@ -248,184 +197,273 @@ pub struct Swap<'info> {
     pub destination_token_program: Interface<'info, TokenInterface>,
 }
 
-mod utils {
-    use std::cell::Ref;
-
+pub(crate) mod utils {
     use super::*;
-    use crate::curve::fees::Fees;
+    use crate::{
+        curve::fees::Fees,
+        utils::{pool_transfers::SwapTransfers, transfer_fee::MintTransferFee},
+    };
+
+    pub fn validate_inputs(ctx: &Context<Swap>, pool: &SwapPool) -> Result<TradeDirection> {
+        let trade_direction = validate_swap_accounts(
+            pool,
+            &ctx.accounts.source_mint.key(),
+            &ctx.accounts.destination_mint.key(),
+            &ctx.accounts.source_vault.key(),
+            &ctx.accounts.destination_vault.key(),
+            &ctx.accounts.source_token_fees_vault.key(),
+            ctx.accounts
+                .source_token_protocol_fees_vault
+                .as_ref()
+                .map(|vault| vault.key())
+                .as_ref(),
+        )?;
+        // curve types can be disallowed after pools were created with them
+        ctx.accounts
+            .global_config
+            .load()?
+            .validate_curve(pool.curve_type())?;
+        Ok(trade_direction)
+    }
+
+    /// Accumulate the spot prices of the pool from the vault balances before the swap
+    pub fn update_price_accumulator(
+        pool: &mut SwapPool,
+        swap_curve: &SwapCurve,
+        trade_direction: TradeDirection,
+        source_vault_amount: u64,
+        destination_vault_amount: u64,
+    ) -> Result<()> {
+        let (pool_token_a_amount, pool_token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (source_vault_amount, destination_vault_amount),
+            TradeDirection::BtoA => (destination_vault_amount, source_vault_amount),
+        };
+        pool.price_accumulator.update(
+            swap_curve.calculator.as_ref(),
+            pool_token_a_amount,
+            pool_token_b_amount,
+        )
+    }
+
+    /// Accumulate the volatility of the pool from the vault balances before the swap, returning
+    /// the pool fees with the resulting dynamic trade fee
+    pub fn update_dynamic_fee(
+        pool: &mut SwapPool,
+        swap_curve: &SwapCurve,
+        trade_direction: TradeDirection,
+        source_vault_amount: u64,
+        destination_vault_amount: u64,
+    ) -> Result<Fees> {
+        let (pool_token_a_amount, pool_token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (source_vault_amount, destination_vault_amount),
+            TradeDirection::BtoA => (destination_vault_amount, source_vault_amount),
+        };
+        pool.dynamic_fee.update(
+            swap_curve.calculator.as_ref(),
+            pool_token_a_amount,
+            pool_token_b_amount,
+        )?;
+        pool.dynamic_fee.fees(&pool.fees)
+    }
 
-    pub fn validate_inputs(ctx: &Context<Swap>, pool: &Ref<SwapPool>) -> Result<TradeDirection> {
+    /// Validate the mints and vaults of a swap against the pool, returning the trade direction
+    pub fn validate_swap_accounts(
+        pool: &SwapPool,
+        source_mint: &Pubkey,
+        destination_mint: &Pubkey,
+        source_vault: &Pubkey,
+        destination_vault: &Pubkey,
+        source_token_fees_vault: &Pubkey,
+        source_token_protocol_fees_vault: Option<&Pubkey>,
+    ) -> Result<TradeDirection> {
         require_msg!(
             !pool.withdrawals_only(),
             SwapError::WithdrawalsOnlyMode,
             "The pool is in withdrawals only mode"
         );
-        let trade_direction = if ctx.accounts.source_mint.key() == pool.token_a_mint
-            && ctx.accounts.destination_mint.key() == pool.token_b_mint
-        {
-            TradeDirection::AtoB
-        } else if ctx.accounts.source_mint.key() == pool.token_b_mint
-            && ctx.accounts.destination_mint.key() == pool.token_a_mint
-        {
-            TradeDirection::BtoA
-        } else {
-            return err!(SwapError::IncorrectSwapAccount);
-        };
+        let trade_direction =
+            if *source_mint == pool.token_a_mint && *destination_mint == pool.token_b_mint {
+                TradeDirection::AtoB
+            } else if *source_mint == pool.token_b_mint && *destination_mint == pool.token_a_mint {
+                TradeDirection::BtoA
+            } else {
+                return err!(SwapError::IncorrectSwapAccount);
+            };
 
         match trade_direction {
             TradeDirection::AtoB => {
                 require_msg!(
-                    ctx.accounts.source_vault.key() == pool.token_a_vault,
+                    *source_vault == pool.token_a_vault,
                     SwapError::IncorrectSwapAccount,
                     &format!(
                         "IncorrectSwapAccount: source_vault.key ({}) != token_a_vault.key ({})",
-                        ctx.accounts.source_vault.key(),
+                        source_vault,
                         pool.token_a_vault.key()
                     )
                 );
                 require_msg!(
-                    ctx.accounts.destination_vault.key() == pool.token_b_vault,
+                    *destination_vault == pool.token_b_vault,
                     SwapError::IncorrectSwapAccount,
                     &format!(
                         "IncorrectSwapAccount: destination_vault.key ({}) != token_b_vault.key ({})",
-                        ctx.accounts.destination_vault.key(),
+                        destination_vault,
                         pool.token_b_vault.key()
                     )
                 );
                 require_msg!(
-                    ctx.accounts.source_token_fees_vault.key() == pool.token_a_fees_vault,
+                    *source_token_fees_vault == pool.token_a_fees_vault,
                     SwapError::IncorrectSwapAccount,
                     &format!(
                         "IncorrectSwapAccount: source_token_fees_vault.key ({}) != token_a_fees_vault.key ({})",
-                        ctx.accounts.source_token_fees_vault.key(),
+                        source_token_fees_vault,
                         pool.token_a_fees_vault.key()
                     )
                 );
+                require_msg!(
+                    source_token_protocol_fees_vault.copied()
+                        == pool.protocol_fees_vault(AorB::A),
+                    SwapError::IncorrectSwapAccount,
+                    &format!(
+                        "IncorrectSwapAccount: source_token_protocol_fees_vault.key ({:?}) != token_a_protocol_fees_vault.key ({:?})",
+                        source_token_protocol_fees_vault,
+                        pool.protocol_fees_vault(AorB::A)
+                    )
+                );
             }
             TradeDirection::BtoA => {
                 require_msg!(
-                    ctx.accounts.destination_vault.key() == pool.token_a_vault,
+                    *destination_vault == pool.token_a_vault,
                     SwapError::IncorrectSwapAccount,
                     &format!(
                         "IncorrectSwapAccount: destination_vault.key ({}) != token_a_vault.key ({})",
-                        ctx.accounts.source_vault.key(),
+                        destination_vault,
                         pool.token_a_vault.key()
                     )
                 );
                 require_msg!(
-                    ctx.accounts.source_vault.key() == pool.token_b_vault,
+                    *source_vault == pool.token_b_vault,
                     SwapError::IncorrectSwapAccount,
                     &format!(
                         "IncorrectSwapAccount: source_vault.key ({}) != token_b_vault.key ({})",
-                        ctx.accounts.source_vault.key(),
+                        source_vault,
                         pool.token_b_vault.key()
                     )
                 );
                 require_msg!(
-                    ctx.accounts.source_token_fees_vault.key() == pool.token_b_fees_vault,
+                    *source_token_fees_vault == pool.token_b_fees_vault,
                     SwapError::IncorrectSwapAccount,
                     &format!(
                         "IncorrectSwapAccount: source_token_fees_vault.key ({}) != token_b_fees_vault.key ({})",
-                        ctx.accounts.source_token_fees_vault.key(),
+                        source_token_fees_vault,
                         pool.token_b_fees_vault.key()
                     )
                 );
+                require_msg!(
+                    source_token_protocol_fees_vault.copied()
+                        == pool.protocol_fees_vault(AorB::B),
+                    SwapError::IncorrectSwapAccount,
+                    &format!(
+                        "IncorrectSwapAccount: source_token_protocol_fees_vault.key ({:?}) != token_b_protocol_fees_vault.key ({:?})",
+                        source_token_protocol_fees_vault,
+                        pool.protocol_fees_vault(AorB::B)
+                    )
+                );
             }
         };
 
         Ok(trade_direction)
     }
 
+    /// Transfer the source tokens from the user to the pool, fees and host fees accounts, and the
+    /// destination tokens from the pool to the user
+    pub fn transfer_swap(
+        ctx: &Context<Swap>,
+        pool: &SwapPool,
+        transfers: &SwapTransfers,
+    ) -> Result<()> {
+        swap_token::transfer_from_user(
+            ctx.accounts.source_token_program.to_account_info(),
+            ctx.accounts.source_user_ata.to_account_info(),
+            ctx.accounts.source_mint.to_account_info(),
+            ctx.accounts.source_vault.to_account_info(),
+            ctx.accounts.signer.to_account_info(),
+            ctx.remaining_accounts,
+            transfers.source_amount_to_vault,
+            ctx.accounts.source_mint.decimals,
+        )?;
+        // the fees are sent straight from the user, so the admin, the host and the protocol each
+        // receive their whole fee after transfer fees
+        let mut fee_transfers = vec![(
+            ctx.accounts.source_token_fees_vault.to_account_info(),
+            transfers.owner_fee_to_vault,
+        )];
+        if let Some(host_fees_account) = &ctx.accounts.source_token_host_fees_account {
+            fee_transfers.push((
+                host_fees_account.to_account_info(),
+                transfers.host_fee_to_account,
+            ));
+        }
+        if let Some(protocol_fees_vault) = &ctx.accounts.source_token_protocol_fees_vault {
+            fee_transfers.push((
+                protocol_fees_vault.to_account_info(),
+                transfers.protocol_fee_to_vault,
+            ));
+        }
+        for (fees_account, amount) in fee_transfers {
+            if amount > 0 {
+                swap_token::transfer_from_user(
+                    ctx.accounts.source_token_program.to_account_info(),
+                    ctx.accounts.source_user_ata.to_account_info(),
+                    ctx.accounts.source_mint.to_account_info(),
+                    fees_account,
+                    ctx.accounts.signer.to_account_info(),
+                    ctx.remaining_accounts,
+                    amount,
+                    ctx.accounts.source_mint.decimals,
+                )?;
+            }
+        }
+
+        swap_token::transfer_from_vault(
+            ctx.accounts.destination_token_program.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            ctx.accounts.destination_vault.to_account_info(),
+            ctx.accounts.destination_mint.to_account_info(),
+            ctx.accounts.destination_user_ata.to_account_info(),
+            ctx.accounts.pool_authority.to_account_info(),
+            ctx.remaining_accounts,
+            pool.bump_seed(),
+            transfers.destination_amount_from_vault,
+            ctx.accounts.destination_mint.decimals,
+        )
+    }
+
+    /// Transfer fees of a token mint as of the current epoch
+    pub fn mint_transfer_fee(mint_acc_info: &AccountInfo) -> Result<MintTransferFee> {
+        MintTransferFee::new(&mint_acc_info.data.borrow(), Clock::get()?.epoch)
+    }
+
     /// Subtract token mint transfer fees for actual amount received by the user post-transfer fees
     pub fn sub_transfer_fee(mint_acc_info: &AccountInfo, amount: u64) -> Result<u64> {
-        let mint_data = mint_acc_info.data.borrow();
-        let mint =
-            StateWithExtensions::<anchor_spl::token_2022::spl_token_2022::state::Mint>::unpack(
-                &mint_data,
-            )?;
-        let amount = if let Ok(transfer_fee_config) = mint.get_extension::<TransferFeeConfig>() {
-            let transfer_fee = transfer_fee_config
-                .calculate_epoch_fee(Clock::get()?.epoch, amount)
-                .ok_or_else(|| error!(SwapError::FeeCalculationFailure))?;
-            let amount_sub_fee = try_math!(amount.try_sub(transfer_fee))?;
-            msg!(
-                "Subtract token transfer fee: fee={}, amount={}, amount_sub_fee={}",
-                transfer_fee,
-                amount,
-                amount_sub_fee
-            );
-            amount_sub_fee
-        } else {
-            amount
-        };
-        Ok(amount)
+        mint_transfer_fee(mint_acc_info)?.sub_transfer_fee(amount)
     }
 
     /// Subtract token mint transfer fees for actual amount received by the pool post-transfer fees
     ///
-    /// There are potentially 3 input transfers:
-    /// 1. User -> Pool
-    /// 2. User -> Fees
-    /// 3. User -> Host Fees (optional)
-    ///
-    /// At low token amounts, the fees on each transfer rounding up can result in the user paying more than the amount_in, causing an unexpected `ExceededSlippage` error
+    /// See `transfer_fee::sub_input_transfer_fees`
     pub fn sub_input_transfer_fees(
         mint_acc_info: &AccountInfo,
         fees: &Fees,
         amount_in: u64,
         host_fee: bool,
+        global_config: Option<&GlobalConfig>,
     ) -> Result<u64> {
-        let mint_data = mint_acc_info.data.borrow();
-        let mint =
-            StateWithExtensions::<anchor_spl::token_2022::spl_token_2022::state::Mint>::unpack(
-                &mint_data,
-            )?;
-        let amount = if let Ok(transfer_fee_config) = mint.get_extension::<TransferFeeConfig>() {
-            let owner_and_host_fee = fees.owner_trading_fee(amount_in.into())?;
-            let epoch = Clock::get()?.epoch;
-            let (host_fee, host_transfer_fee) = if host_fee {
-                let host_fee = fees.host_fee(owner_and_host_fee)?;
-                (
-                    host_fee,
-                    transfer_fee_config
-                        .calculate_epoch_fee(epoch, to_u64!(host_fee)?)
-                        .ok_or_else(|| error!(SwapError::FeeCalculationFailure))?,
-                )
-            } else {
-                (0, 0)
-            };
-            let owner_fee = try_math!(owner_and_host_fee.try_sub(host_fee))?;
-            let owner_transfer_fee = transfer_fee_config
-                .calculate_epoch_fee(epoch, to_u64!(owner_fee)?)
-                .ok_or_else(|| error!(SwapError::FeeCalculationFailure))?;
-
-            let vault_amount_in = try_math!(amount_in.try_sub(to_u64!(owner_and_host_fee)?))?;
-            let vault_transfer_fee = transfer_fee_config
-                .calculate_epoch_fee(epoch, vault_amount_in)
-                .ok_or_else(|| error!(SwapError::FeeCalculationFailure))?;
-
-            let amount_sub_fees = try_math!(try_math!(try_math!(
-                amount_in.try_sub(vault_transfer_fee)
-            )?
-            .try_sub(owner_transfer_fee))?
-            .try_sub(host_transfer_fee))?;
-
-            msg!(
-                "Subtract input token transfer fee: vault_transfer_amount={}, vault_transfer_fee={}, owner_fee={}, owner_fee_transfer_fee={}, host_fee={}, host_fee_transfer_fee={} amount={}, input_amount_sub_transfer_fees={}",
-                vault_amount_in,
-                vault_transfer_fee,
-                owner_fee,
-                owner_transfer_fee,
-                host_fee,
-                host_transfer_fee,
-                amount_in,
-                amount_sub_fees
-            );
-            amount_sub_fees
-        } else {
-            amount_in
-        };
-        Ok(amount)
+        mint_transfer_fee(mint_acc_info)?.sub_input_transfer_fees(
+            fees,
+            amount_in,
+            host_fee,
+            global_config,
+        )
     }
 
     /// Add token mint transfer fees for actual amount sent pre-transfer fees
@@ -433,27 +471,7 @@ mod utils {
         mint_acc_info: &AccountInfo,
         post_fee_amount: u64,
     ) -> Result<u64> {
-        let mint_data = mint_acc_info.data.borrow();
-        let mint =
-            StateWithExtensions::<anchor_spl::token_2022::spl_token_2022::state::Mint>::unpack(
-                &mint_data,
-            )?;
-        let amount = if let Ok(transfer_fee_config) = mint.get_extension::<TransferFeeConfig>() {
-            let transfer_fee = transfer_fee_config
-                .calculate_inverse_epoch_fee(Clock::get()?.epoch, post_fee_amount)
-                .ok_or_else(|| error!(SwapError::FeeCalculationFailure))?;
-            let amount_add_fee = try_math!(post_fee_amount.try_add(transfer_fee))?;
-            msg!(
-                "Add token transfer fee: fee={}, amount={}, amount_add_fee={}",
-                transfer_fee,
-                post_fee_amount,
-                amount_add_fee
-            );
-            amount_add_fee
-        } else {
-            post_fee_amount
-        };
-        Ok(amount)
+        mint_transfer_fee(mint_acc_info)?.add_inverse_transfer_fee(post_fee_amount)
     }
 
     #[cfg(test)]
@@ -463,7 +481,8 @@ mod utils {
             token_2022::{
                 spl_token_2022,
                 spl_token_2022::extension::{
-                    transfer_fee::TransferFee, ExtensionType, StateWithExtensionsMut,
+                    transfer_fee::{TransferFee, TransferFeeConfig},
+                    ExtensionType, StateWithExtensionsMut,
                 },
             },
             token_interface::spl_token_2022::pod::OptionalNonZeroPubkey,
@@ -678,7 +697,7 @@ mod utils {
             );
 
             let amount =
-                sub_input_transfer_fees(&mint_info, &Fees::default(), 10_000, false).unwrap();
+                sub_input_transfer_fees(&mint_info, &Fees::default(), 10_000, false, None).unwrap();
 
             assert_eq!(amount, 10_000);
         }
@@ -705,7 +724,7 @@ mod utils {
             );
 
             let amount =
-                sub_input_transfer_fees(&mint_info, &Fees::default(), 10_000, false).unwrap();
+                sub_input_transfer_fees(&mint_info, &Fees::default(), 10_000, false, None).unwrap();
 
             // 1 transfer fee of 10 bps
             assert_eq!(amount, 9990);
@@ -738,7 +757,8 @@ mod utils {
                 ..Default::default()
             };
 
-            let amount = sub_input_transfer_fees(&mint_info, &fees, 10_000_000, false).unwrap();
+            let amount =
+                sub_input_transfer_fees(&mint_info, &fees, 10_000_000, false, None).unwrap();
 
             // Raw owner fee amount is 10_000 (10 bps of 10M)
             // Raw owner transfer fee is 10 (10 bps of 10_000)
@@ -781,7 +801,8 @@ mod utils {
             };
 
             let amount =
-                sub_input_transfer_fees(&mint_info, &fees, 100_000_000_000_000, true).unwrap();
+                sub_input_transfer_fees(&mint_info, &fees, 100_000_000_000_000, true, None)
+                    .unwrap();
 
             // Owner fee amount is 100_000_000_000 (10 bps of 100_000B)
             // Host fee 10_000_000 (10 bps of 100_000_000_000) taken from the owner fee which is now 99_990_000_000 (100_000_000_000 - 10_000_000)
@@ -823,7 +844,8 @@ mod utils {
                 ..Default::default()
             };
 
-            let amount = sub_input_transfer_fees(&mint_info, &fees, 100_000_000, true).unwrap();
+            let amount =
+                sub_input_transfer_fees(&mint_info, &fees, 100_000_000, true, None).unwrap();
 
             // Owner fee amount is 100_000 (10 bps of 100M)
             // Host fee 100 (10 bps of 100_000) taken from the owner fee which is now 99_900 (100_000 - 100)
@@ -835,6 +857,57 @@ mod utils {
             assert_eq!(amount, 99_899_999);
         }
 
+        #[test]
+        pub fn test_sub_input_transfer_fee_when_10bps_transfer_fees_and_owner_host_and_protocol_fees(
+        ) {
+            test_syscall_stubs();
+
+            let mut mint_data = mint_with_fee_data();
+            mint_with_transfer_fee(&mut mint_data, 10);
+
+            let key = Pubkey::new_unique();
+            let mut lamports = u64::MAX;
+            let token_program = spl_token_2022::id();
+            let mint_info = AccountInfo::new(
+                &key,
+                false,
+                false,
+                &mut lamports,
+                &mut mint_data,
+                &token_program,
+                false,
+                Epoch::default(),
+            );
+
+            let fees = Fees {
+                owner_trade_fee_numerator: 10,
+                owner_trade_fee_denominator: 10_000,
+                host_fee_numerator: 10,
+                host_fee_denominator: 10_000,
+                ..Default::default()
+            };
+            let global_config = GlobalConfig {
+                protocol_fee_numerator: 1,
+                protocol_fee_denominator: 3,
+                ..Default::default()
+            };
+
+            let amount =
+                sub_input_transfer_fees(&mint_info, &fees, 100_000_000, true, Some(&global_config))
+                    .unwrap();
+
+            // Owner fee amount is 100_000 (10 bps of 100M)
+            // Host fee 100 (10 bps of 100_000) taken from the owner fee which is now 99_900 (100_000 - 100)
+            // Protocol fee 33_300 (1/3 of 99_900) taken from the owner fee which is now 66_600
+            // Owner transfer fee is 67 (10 bps of 66_600 rounded up)
+            // Host transfer fee is 1 (10 bps of 100 rounded up)
+            // Protocol transfer fee is 34 (10 bps of 33_300 rounded up)
+            // Vault transfer amount is 99_900_000 (100M - 100_000)
+            // Vault transfer fee is 99_900 (10 bps of 99_900_000)
+            // 4 transfer fees equal to 100_002 total (99_900 + 67 + 1 + 34)
+            assert_eq!(amount, 99_899_998);
+        }
+
         #[test]
         pub fn test_sub_input_transfer_fee_when_10bps_transfer_fees_and_both_owner_and_host_protocol_fees_small(
         ) {
@@ -865,7 +938,8 @@ mod utils {
                 ..Default::default()
             };
 
-            let amount = sub_input_transfer_fees(&mint_info, &fees, 10_000_000, true).unwrap();
+            let amount =
+                sub_input_transfer_fees(&mint_info, &fees, 10_000_000, true, None).unwrap();
 
             // Owner fee amount is 10_000 (10 bps of 10M)
             // Host fee 10 (10 bps of 10_000) taken from the owner fee which is now 9_990 (10_000 - 10)
@@ -951,7 +1025,7 @@ mod utils {
                     ..Default::default()
                 };
 
-                let amount_sub_fees = sub_input_transfer_fees(&mint_info, &fees, amount, host_fees).unwrap();
+                let amount_sub_fees = sub_input_transfer_fees(&mint_info, &fees, amount, host_fees, None).unwrap();
 
                 let estimated_transfer_fees = amount - amount_sub_fees;
 
@@ -1038,7 +1112,7 @@ mod utils {
                     ..Default::default()
                 };
 
-                let amount_sub_fees = sub_input_transfer_fees(&mint_info, &fees, amount, host_fees).unwrap();
+                let amount_sub_fees = sub_input_transfer_fees(&mint_info, &fees, amount, host_fees, None).unwrap();
                 // Compare with subtracting all fees at once
                 let full_amount_sub_fees = sub_transfer_fee(&mint_info, amount).unwrap();
 
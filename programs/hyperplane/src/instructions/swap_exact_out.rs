@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    curve,
+    curve::base::SwapCurve,
+    emitted,
+    error::SwapError,
+    event, require_msg,
+    swap::utils::{self, validate_inputs},
+    utils::pool_transfers::SwapParams,
+    Swap,
+};
+
+pub fn handler_swap_exact_out<'info>(
+    ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+    amount_out: u64,
+    maximum_amount_in: u64,
+) -> Result<event::Swap> {
+    let mut pool = ctx.accounts.pool.load_mut()?;
+    let trade_direction = validate_inputs(&ctx, &pool)?;
+    let swap_curve = curve!(ctx.accounts.swap_curve, pool, ctx.remaining_accounts)
+        .with_pool_interest_bearing_mints(
+            &pool,
+            &[
+                &ctx.accounts.source_mint.to_account_info(),
+                &ctx.accounts.destination_mint.to_account_info(),
+            ],
+            ctx.remaining_accounts,
+        )?;
+    utils::update_price_accumulator(
+        &mut pool,
+        &swap_curve,
+        trade_direction,
+        ctx.accounts.source_vault.amount,
+        ctx.accounts.destination_vault.amount,
+    )?;
+    let fees = utils::update_dynamic_fee(
+        &mut pool,
+        &swap_curve,
+        trade_direction,
+        ctx.accounts.source_vault.amount,
+        ctx.accounts.destination_vault.amount,
+    )?;
+
+    msg!(
+        "Swap exact out inputs: trade_direction={:?}, amount_out={}, maximum_amount_in={}",
+        trade_direction,
+        amount_out,
+        maximum_amount_in
+    );
+    msg!(
+        "Swap pool inputs: swap_type={:?}, source_token_balance={}, destination_token_balance={}",
+        swap_curve.curve_type,
+        ctx.accounts.source_vault.amount,
+        ctx.accounts.destination_vault.amount,
+    );
+    let global_config = ctx.accounts.global_config.load()?;
+    let transfers = SwapParams {
+        swap_curve: &swap_curve,
+        fees: &fees,
+        trade_direction,
+        source_vault_amount: ctx.accounts.source_vault.amount,
+        destination_vault_amount: ctx.accounts.destination_vault.amount,
+        source_transfer_fee: utils::mint_transfer_fee(&ctx.accounts.source_mint.to_account_info())?,
+        destination_transfer_fee: utils::mint_transfer_fee(
+            &ctx.accounts.destination_mint.to_account_info(),
+        )?,
+        host_fee: ctx.accounts.source_token_host_fees_account.is_some(),
+        // pools without protocol fees vaults pay no protocol fees
+        protocol_fee: ctx
+            .accounts
+            .source_token_protocol_fees_vault
+            .is_some()
+            .then_some(&*global_config),
+    }
+    .swap_exact_out(amount_out)?;
+
+    let amount_in = transfers.source_amount()?;
+    require_msg!(
+        amount_in <= maximum_amount_in,
+        SwapError::ExceededSlippage,
+        &format!(
+            "ExceededSlippage: amount_in={} > maximum_amount_in={}",
+            amount_in, maximum_amount_in
+        )
+    );
+
+    utils::transfer_swap(&ctx, &pool, &transfers)?;
+
+    let event = transfers.event();
+    msg!(
+        "Swap outputs: token_in_amount={}, token_out_amount={}, total_fees={}, trade_fee={}, owner_fee={}, host_fee={}, protocol_fee={}",
+        event.token_in_amount,
+        event.token_out_amount,
+        event.total_fees,
+        event.trade_fee,
+        event.owner_fee,
+        event.host_fee,
+        event.protocol_fee,
+    );
+    emitted!(event);
+}
@@ -1,6 +1,9 @@
-use anchor_lang::solana_program::{
-    account_info::AccountInfo, entrypoint::ProgramResult, instruction::Instruction,
-    program_error::ProgramError, program_pack::Pack, pubkey::Pubkey, rent::Rent,
+use anchor_lang::{
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, instruction::Instruction,
+        program_error::ProgramError, program_pack::Pack, pubkey::Pubkey, rent::Rent,
+    },
+    Discriminator,
 };
 use anchor_spl::{
     token::spl_token,
@@ -12,15 +15,18 @@ use anchor_spl::{
 use solana_sdk::account::{create_account_for_test, Account as SolanaAccount, WritableAccount};
 
 use crate::{
-    constraints::{SwapConstraints, SWAP_CONSTRAINTS},
-    curve::{base::SwapCurve, fees::Fees},
+    curve::{
+        base::{CurveType, SwapCurve},
+        calculator::AorB,
+        fees::Fees,
+    },
     instructions::{
         model::CurveParameters,
         test::runner::{syscall_stubs::test_syscall_stubs, token},
     },
     ix,
     ix::Initialize,
-    state::SwapPool,
+    state::{GlobalConfig, SwapPool},
     utils::seeds,
     InitialSupply,
 };
@@ -42,6 +48,8 @@ pub struct SwapAccountInfo {
     pub transfer_fees: SwapTransferFees,
     pub pool: Pubkey,
     pub pool_account: SolanaAccount,
+    pub global_config_key: Pubkey,
+    pub global_config_account: SolanaAccount,
     pub swap_curve_key: Pubkey,
     pub swap_curve_account: SolanaAccount,
     pub swap_curve: SwapCurve,
@@ -52,6 +60,10 @@ pub struct SwapAccountInfo {
     pub token_a_fees_vault_account: SolanaAccount,
     pub token_b_fees_vault_key: Pubkey,
     pub token_b_fees_vault_account: SolanaAccount,
+    pub token_a_protocol_fees_vault_key: Pubkey,
+    pub token_a_protocol_fees_vault_account: SolanaAccount,
+    pub token_b_protocol_fees_vault_key: Pubkey,
+    pub token_b_protocol_fees_vault_account: SolanaAccount,
     pub admin_authority_token_a_ata_key: Pubkey,
     pub admin_authority_token_a_ata_account: SolanaAccount,
     pub admin_authority_token_b_ata_key: Pubkey,
@@ -141,6 +153,13 @@ impl SwapAccountInfo {
             token::get_token_account_space(token_a_program_id, &token_a_mint_account), // size needed because syscall not stubbed
             token_a_program_id, // this should be system but we no-op the system program calls
         );
+        let (token_a_protocol_fees_vault_key, _token_a_protocol_fees_vault_bump_seed) =
+            seeds::pda::token_a_protocol_fees_vault_pda(&pool, &token_a_mint_key);
+        let token_a_protocol_fees_vault_account = SolanaAccount::new(
+            u32::MAX as u64,
+            token::get_token_account_space(token_a_program_id, &token_a_mint_account), // size needed because syscall not stubbed
+            token_a_program_id, // this should be system but we no-op the system program calls
+        );
         let (admin_authority_token_a_ata_key, admin_authority_token_a_ata_account) =
             token::create_token_account(
                 token_a_program_id,
@@ -173,6 +192,13 @@ impl SwapAccountInfo {
             token::get_token_account_space(token_b_program_id, &token_b_mint_account), // size needed because syscall not stubbed
             token_b_program_id, // this should be system but we no-op the system program calls
         );
+        let (token_b_protocol_fees_vault_key, _token_b_protocol_fees_vault_bump_seed) =
+            seeds::pda::token_b_protocol_fees_vault_pda(&pool, &token_b_mint_key);
+        let token_b_protocol_fees_vault_account = SolanaAccount::new(
+            u32::MAX as u64,
+            token::get_token_account_space(token_b_program_id, &token_b_mint_account), // size needed because syscall not stubbed
+            token_b_program_id, // this should be system but we no-op the system program calls
+        );
         let (admin_authority_token_b_ata_key, admin_authority_token_b_ata_account) =
             token::create_token_account(
                 token_b_program_id,
@@ -192,6 +218,8 @@ impl SwapAccountInfo {
             transfer_fees,
             pool,
             pool_account,
+            global_config_key: seeds::pda::global_config_pda().0,
+            global_config_account: global_config_account(&permissive_global_config()),
             swap_curve_key,
             swap_curve_account,
             swap_curve: SwapCurve::new_from_params(curve_params.clone()).unwrap(),
@@ -202,6 +230,10 @@ impl SwapAccountInfo {
             token_a_fees_vault_account,
             token_b_fees_vault_key,
             token_b_fees_vault_account,
+            token_a_protocol_fees_vault_key,
+            token_a_protocol_fees_vault_account,
+            token_b_protocol_fees_vault_key,
+            token_b_protocol_fees_vault_account,
             admin_authority_token_a_ata_key,
             admin_authority_token_a_ata_account,
             admin_authority_token_b_ata_key,
@@ -239,6 +271,8 @@ impl SwapAccountInfo {
                 &self.pool_token_mint_key,
                 &self.token_a_fees_vault_key,
                 &self.token_b_fees_vault_key,
+                &self.token_a_protocol_fees_vault_key,
+                &self.token_b_protocol_fees_vault_key,
                 &self.admin_authority_token_a_ata_key,
                 &self.admin_authority_token_b_ata_key,
                 &self.admin_authority_pool_token_ata_key,
@@ -249,12 +283,14 @@ impl SwapAccountInfo {
                     fees: self.fees,
                     initial_supply: self.initial_supply.clone(),
                     curve_parameters: self.curve_params.clone().into(),
+                    pool_token_metadata: None,
                 },
             )
             .unwrap(),
             vec![
                 &mut SolanaAccount::default(),
                 &mut self.pool_account,
+                &mut self.global_config_account,
                 &mut self.swap_curve_account,
                 &mut SolanaAccount::default(),
                 &mut self.token_a_mint_account,
@@ -264,6 +300,8 @@ impl SwapAccountInfo {
                 &mut self.pool_token_mint_account,
                 &mut self.token_a_fees_vault_account,
                 &mut self.token_b_fees_vault_account,
+                &mut self.token_a_protocol_fees_vault_account,
+                &mut self.token_b_protocol_fees_vault_account,
                 &mut self.admin_authority_token_a_ata_account,
                 &mut self.admin_authority_token_b_ata_account,
                 &mut self.admin_authority_pool_token_ata_account,
@@ -345,6 +383,16 @@ impl SwapAccountInfo {
         }
     }
 
+    fn get_protocol_fees_vault_key(&self, account_key: &Pubkey) -> Pubkey {
+        if *account_key == self.token_a_vault_key {
+            self.token_a_protocol_fees_vault_key
+        } else if *account_key == self.token_b_vault_key {
+            self.token_b_protocol_fees_vault_key
+        } else {
+            panic!("Could not find matching swap token account");
+        }
+    }
+
     pub fn get_vault_account(&self, account_key: &Pubkey) -> &SolanaAccount {
         if account_key == &self.token_a_vault_key {
             &self.token_a_vault_account
@@ -354,6 +402,10 @@ impl SwapAccountInfo {
             &self.token_a_fees_vault_account
         } else if account_key == &self.token_b_fees_vault_key {
             &self.token_b_fees_vault_account
+        } else if account_key == &self.token_a_protocol_fees_vault_key {
+            &self.token_a_protocol_fees_vault_account
+        } else if account_key == &self.token_b_protocol_fees_vault_key {
+            &self.token_b_protocol_fees_vault_account
         } else {
             panic!("Could not find matching swap token account");
         }
@@ -368,6 +420,10 @@ impl SwapAccountInfo {
             self.token_a_fees_vault_account = account;
         } else if account_key == &self.token_b_fees_vault_key {
             self.token_b_fees_vault_account = account;
+        } else if account_key == &self.token_a_protocol_fees_vault_key {
+            self.token_a_protocol_fees_vault_account = account;
+        } else if account_key == &self.token_b_protocol_fees_vault_key {
+            self.token_b_protocol_fees_vault_account = account;
         } else {
             panic!("Could not find matching swap token account");
         }
@@ -415,6 +471,10 @@ impl SwapAccountInfo {
         let mut source_vault_account = self.get_vault_account(source_vault_key).clone();
         let mut destination_vault_account = self.get_vault_account(destination_vault_key).clone();
         let mut source_fees_vault_account = self.get_vault_account(source_fees_vault_key).clone();
+        let source_protocol_fees_vault_key = self.get_protocol_fees_vault_key(source_vault_key);
+        let mut source_protocol_fees_vault_account = self
+            .get_vault_account(&source_protocol_fees_vault_key)
+            .clone();
 
         let exe = &mut SolanaAccount::default();
         exe.set_executable(true);
@@ -432,6 +492,7 @@ impl SwapAccountInfo {
                 source_vault_key,
                 destination_vault_key,
                 source_fees_vault_key,
+                &source_protocol_fees_vault_key,
                 user_source_key,
                 user_destination_key,
                 None,
@@ -446,6 +507,7 @@ impl SwapAccountInfo {
             vec![
                 &mut SolanaAccount::default(),
                 &mut self.pool_account,
+                &mut self.global_config_account,
                 &mut self.swap_curve_account,
                 &mut SolanaAccount::default(),
                 &mut source_mint_account,
@@ -453,6 +515,7 @@ impl SwapAccountInfo {
                 &mut source_vault_account,
                 &mut destination_vault_account,
                 &mut source_fees_vault_account,
+                &mut source_protocol_fees_vault_account,
                 user_source_account,
                 user_destination_account,
                 &mut exe.clone(), // Optional front end host fees - passed as the program if not present
@@ -463,6 +526,116 @@ impl SwapAccountInfo {
 
         self.set_token_account(source_vault_key, source_vault_account);
         self.set_token_account(source_fees_vault_key, source_fees_vault_account);
+        self.set_token_account(
+            &source_protocol_fees_vault_key,
+            source_protocol_fees_vault_account,
+        );
+        self.set_token_account(destination_vault_key, destination_vault_account);
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap_exact_out(
+        &mut self,
+        user_key: &Pubkey,
+        user_source_key: &Pubkey,
+        user_source_account: &mut SolanaAccount,
+        source_vault_key: &Pubkey,
+        source_fees_vault_key: &Pubkey,
+        destination_vault_key: &Pubkey,
+        user_destination_key: &Pubkey,
+        user_destination_account: &mut SolanaAccount,
+        amount_out: u64,
+        maximum_amount_in: u64,
+    ) -> ProgramResult {
+        let user_transfer_key = Pubkey::new_unique();
+        let source_token_program_id = self.get_token_program_id(source_vault_key);
+        let destination_token_program_id = self.get_token_program_id(destination_vault_key);
+        // approve moving from user source account
+        do_process_instruction(
+            approve(
+                source_token_program_id,
+                user_source_key,
+                &user_transfer_key,
+                user_key,
+                &[],
+                maximum_amount_in,
+            )
+            .unwrap(),
+            vec![
+                user_source_account,
+                &mut SolanaAccount::default(),
+                &mut SolanaAccount::default(),
+            ],
+        )
+        .unwrap();
+
+        let (source_mint_key, mut source_mint_account) = self.get_token_mint(source_vault_key);
+        let (destination_mint_key, mut destination_mint_account) =
+            self.get_token_mint(destination_vault_key);
+        let mut source_vault_account = self.get_vault_account(source_vault_key).clone();
+        let mut destination_vault_account = self.get_vault_account(destination_vault_key).clone();
+        let mut source_fees_vault_account = self.get_vault_account(source_fees_vault_key).clone();
+        let source_protocol_fees_vault_key = self.get_protocol_fees_vault_key(source_vault_key);
+        let mut source_protocol_fees_vault_account = self
+            .get_vault_account(&source_protocol_fees_vault_key)
+            .clone();
+
+        let exe = &mut SolanaAccount::default();
+        exe.set_executable(true);
+
+        // perform the exact out swap
+        do_process_instruction(
+            ix::swap_exact_out(
+                &crate::id(),
+                &user_transfer_key,
+                &self.pool,
+                &self.swap_curve_key,
+                &self.pool_authority,
+                &source_mint_key,
+                &destination_mint_key,
+                source_vault_key,
+                destination_vault_key,
+                source_fees_vault_key,
+                &source_protocol_fees_vault_key,
+                user_source_key,
+                user_destination_key,
+                None,
+                source_token_program_id,
+                destination_token_program_id,
+                ix::SwapExactOut {
+                    amount_out,
+                    maximum_amount_in,
+                },
+            )
+            .unwrap(),
+            vec![
+                &mut SolanaAccount::default(),
+                &mut self.pool_account,
+                &mut self.global_config_account,
+                &mut self.swap_curve_account,
+                &mut SolanaAccount::default(),
+                &mut source_mint_account,
+                &mut destination_mint_account,
+                &mut source_vault_account,
+                &mut destination_vault_account,
+                &mut source_fees_vault_account,
+                &mut source_protocol_fees_vault_account,
+                user_source_account,
+                user_destination_account,
+                &mut exe.clone(), // Optional front end host fees - passed as the program if not present
+                &mut exe.clone(), // source_token_program
+                &mut exe.clone(), // destination_token_program
+            ],
+        )?;
+
+        self.set_token_account(source_vault_key, source_vault_account);
+        self.set_token_account(source_fees_vault_key, source_fees_vault_account);
+        self.set_token_account(
+            &source_protocol_fees_vault_key,
+            source_protocol_fees_vault_account,
+        );
         self.set_token_account(destination_vault_key, destination_vault_account);
 
         Ok(())
@@ -571,6 +744,265 @@ impl SwapAccountInfo {
         )
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn deposit_single_token_type_exact_amount_in(
+        &mut self,
+        depositor_key: &Pubkey,
+        source_vault_key: &Pubkey,
+        source_fees_vault_key: &Pubkey,
+        depositor_source_key: &Pubkey,
+        depositor_source_account: &mut SolanaAccount,
+        depositor_pool_key: &Pubkey,
+        depositor_pool_account: &mut SolanaAccount,
+        source_token_amount: u64,
+        minimum_pool_token_amount: u64,
+    ) -> ProgramResult {
+        let user_transfer_authority = Pubkey::new_unique();
+        let source_token_program_id = *self.get_token_program_id(source_vault_key);
+        do_process_instruction(
+            approve(
+                &source_token_program_id,
+                depositor_source_key,
+                &user_transfer_authority,
+                depositor_key,
+                &[],
+                source_token_amount,
+            )
+            .unwrap(),
+            vec![
+                depositor_source_account,
+                &mut SolanaAccount::default(),
+                &mut SolanaAccount::default(),
+            ],
+        )
+        .unwrap();
+
+        let (source_mint_key, mut source_mint_account) = self.get_token_mint(source_vault_key);
+        let mut source_fees_vault_account = self.get_vault_account(source_fees_vault_key).clone();
+        let pool_token_program_id = depositor_pool_account.owner;
+
+        let exe = &mut SolanaAccount::default();
+        exe.set_executable(true);
+
+        do_process_instruction(
+            ix::deposit_single_token_type_exact_amount_in(
+                &crate::id(),
+                &user_transfer_authority,
+                &self.pool,
+                &self.swap_curve_key,
+                &self.pool_authority,
+                &source_mint_key,
+                &self.token_a_vault_key,
+                &self.token_b_vault_key,
+                source_fees_vault_key,
+                &self.pool_token_mint_key,
+                depositor_source_key,
+                depositor_pool_key,
+                &pool_token_program_id,
+                &source_token_program_id,
+                ix::DepositSingleTokenTypeExactAmountIn {
+                    source_token_amount,
+                    minimum_pool_token_amount,
+                },
+            )
+            .unwrap(),
+            vec![
+                &mut SolanaAccount::default(),
+                &mut self.pool_account,
+                &mut self.swap_curve_account,
+                &mut SolanaAccount::default(),
+                &mut source_mint_account,
+                &mut self.token_a_vault_account,
+                &mut self.token_b_vault_account,
+                &mut source_fees_vault_account,
+                &mut self.pool_token_mint_account,
+                depositor_source_account,
+                depositor_pool_account,
+                &mut exe.clone(), // pool_token_program
+                &mut exe.clone(), // source_token_program
+            ],
+        )?;
+
+        self.set_token_account(source_fees_vault_key, source_fees_vault_account);
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn withdraw_single_token_type_exact_amount_out(
+        &mut self,
+        user_key: &Pubkey,
+        destination_vault_key: &Pubkey,
+        destination_fees_vault_key: &Pubkey,
+        user_pool_token_key: &Pubkey,
+        user_pool_token_account: &mut SolanaAccount,
+        user_destination_key: &Pubkey,
+        user_destination_account: &mut SolanaAccount,
+        destination_token_amount: u64,
+        maximum_pool_token_amount: u64,
+    ) -> ProgramResult {
+        let user_transfer_authority_key = self.approve_pool_tokens(
+            user_key,
+            user_pool_token_key,
+            user_pool_token_account,
+            maximum_pool_token_amount,
+        );
+        let (destination_mint_key, _) = self.get_token_mint(destination_vault_key);
+        let pool_token_program_id = user_pool_token_account.owner;
+        let destination_token_program_id = *self.get_token_program_id(destination_vault_key);
+
+        let instruction = ix::withdraw_single_token_type_exact_amount_out(
+            &crate::id(),
+            &user_transfer_authority_key,
+            &self.pool,
+            &self.swap_curve_key,
+            &self.pool_authority,
+            &destination_mint_key,
+            &self.token_a_vault_key,
+            &self.token_b_vault_key,
+            destination_fees_vault_key,
+            &self.pool_token_mint_key,
+            user_destination_key,
+            user_pool_token_key,
+            &pool_token_program_id,
+            &destination_token_program_id,
+            ix::WithdrawSingleTokenTypeExactAmountOut {
+                destination_token_amount,
+                maximum_pool_token_amount,
+            },
+        )
+        .unwrap();
+        self.process_withdraw_single_token_type(
+            instruction,
+            destination_vault_key,
+            destination_fees_vault_key,
+            user_pool_token_account,
+            user_destination_account,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn withdraw_one_token(
+        &mut self,
+        user_key: &Pubkey,
+        destination_vault_key: &Pubkey,
+        destination_fees_vault_key: &Pubkey,
+        user_pool_token_key: &Pubkey,
+        user_pool_token_account: &mut SolanaAccount,
+        user_destination_key: &Pubkey,
+        user_destination_account: &mut SolanaAccount,
+        pool_token_amount: u64,
+        minimum_token_amount: u64,
+        a_or_b: AorB,
+    ) -> ProgramResult {
+        let user_transfer_authority_key = self.approve_pool_tokens(
+            user_key,
+            user_pool_token_key,
+            user_pool_token_account,
+            pool_token_amount,
+        );
+        let (destination_mint_key, _) = self.get_token_mint(destination_vault_key);
+        let pool_token_program_id = user_pool_token_account.owner;
+        let destination_token_program_id = *self.get_token_program_id(destination_vault_key);
+
+        let instruction = ix::withdraw_one_token(
+            &crate::id(),
+            &user_transfer_authority_key,
+            &self.pool,
+            &self.swap_curve_key,
+            &self.pool_authority,
+            &destination_mint_key,
+            &self.token_a_vault_key,
+            &self.token_b_vault_key,
+            destination_fees_vault_key,
+            &self.pool_token_mint_key,
+            user_destination_key,
+            user_pool_token_key,
+            &pool_token_program_id,
+            &destination_token_program_id,
+            ix::WithdrawOneToken {
+                pool_token_amount,
+                minimum_token_amount,
+                a_or_b,
+            },
+        )
+        .unwrap();
+        self.process_withdraw_single_token_type(
+            instruction,
+            destination_vault_key,
+            destination_fees_vault_key,
+            user_pool_token_account,
+            user_destination_account,
+        )
+    }
+
+    fn approve_pool_tokens(
+        &self,
+        user_key: &Pubkey,
+        user_pool_token_key: &Pubkey,
+        user_pool_token_account: &mut SolanaAccount,
+        pool_token_amount: u64,
+    ) -> Pubkey {
+        let user_transfer_authority_key = Pubkey::new_unique();
+        do_process_instruction(
+            approve(
+                &user_pool_token_account.owner,
+                user_pool_token_key,
+                &user_transfer_authority_key,
+                user_key,
+                &[],
+                pool_token_amount,
+            )
+            .unwrap(),
+            vec![
+                user_pool_token_account,
+                &mut SolanaAccount::default(),
+                &mut SolanaAccount::default(),
+            ],
+        )
+        .unwrap();
+        user_transfer_authority_key
+    }
+
+    fn process_withdraw_single_token_type(
+        &mut self,
+        instruction: Instruction,
+        destination_vault_key: &Pubkey,
+        destination_fees_vault_key: &Pubkey,
+        user_pool_token_account: &mut SolanaAccount,
+        user_destination_account: &mut SolanaAccount,
+    ) -> ProgramResult {
+        let (_, mut destination_mint_account) = self.get_token_mint(destination_vault_key);
+        let mut destination_fees_vault_account =
+            self.get_vault_account(destination_fees_vault_key).clone();
+
+        let exe = &mut SolanaAccount::default();
+        exe.set_executable(true);
+
+        do_process_instruction(
+            instruction,
+            vec![
+                &mut SolanaAccount::default(),
+                &mut self.pool_account,
+                &mut self.swap_curve_account,
+                &mut SolanaAccount::default(),
+                &mut destination_mint_account,
+                &mut self.token_a_vault_account,
+                &mut self.token_b_vault_account,
+                &mut destination_fees_vault_account,
+                &mut self.pool_token_mint_account,
+                user_destination_account,
+                user_pool_token_account,
+                &mut exe.clone(), // pool_token_program
+                &mut exe.clone(), // destination_token_program
+            ],
+        )?;
+
+        self.set_token_account(destination_fees_vault_key, destination_fees_vault_account);
+
+        Ok(())
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn withdraw(
         &mut self,
@@ -663,10 +1095,34 @@ impl SwapAccountInfo {
     }
 }
 
-pub fn do_process_instruction_with_fee_constraints(
+/// Global config which allows every curve type, without minimum fees or blocked extensions
+pub fn permissive_global_config() -> GlobalConfig {
+    let mut global_config = GlobalConfig::default();
+    global_config
+        .set_valid_curve_types(&[
+            CurveType::ConstantProduct.into(),
+            CurveType::ConstantPrice.into(),
+            CurveType::Offset.into(),
+            CurveType::Stable.into(),
+            CurveType::OraclePrice.into(),
+            CurveType::Concentrated.into(),
+            CurveType::Weighted.into(),
+        ])
+        .unwrap();
+    global_config
+}
+
+/// Program owned account holding the serialized global config
+pub fn global_config_account(global_config: &GlobalConfig) -> SolanaAccount {
+    let mut account = SolanaAccount::new(u32::MAX as u64, GlobalConfig::LEN, &crate::id());
+    account.data[..8].copy_from_slice(&GlobalConfig::discriminator());
+    account.data[8..].copy_from_slice(bytemuck::bytes_of(global_config));
+    account
+}
+
+pub fn do_process_instruction(
     instruction: Instruction,
     accounts: Vec<&mut SolanaAccount>,
-    _swap_constraints: &Option<SwapConstraints>, // todo - elliot - compile time constraints
 ) -> ProgramResult {
     test_syscall_stubs();
 
@@ -729,10 +1185,3 @@ pub fn do_process_instruction_with_fee_constraints(
     }
     res
 }
-
-pub fn do_process_instruction(
-    instruction: Instruction,
-    accounts: Vec<&mut SolanaAccount>,
-) -> ProgramResult {
-    do_process_instruction_with_fee_constraints(instruction, accounts, &SWAP_CONSTRAINTS)
-}
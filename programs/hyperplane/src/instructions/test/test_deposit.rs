@@ -843,3 +843,261 @@ fn test_deposit(
         );
     }
 }
+
+#[test_case(spl_token::id(), spl_token::id(), spl_token::id(); "all-token")]
+#[test_case(spl_token::id(), spl_token_2022::id(), spl_token_2022::id(); "mixed-pool-token")]
+#[test_case(spl_token::id(), spl_token_2022::id(), spl_token::id(); "a-only-token-2022")]
+#[test_case(spl_token::id(), spl_token::id(), spl_token_2022::id(); "b-only-token-2022")]
+fn test_deposit_single_token_type_exact_amount_in(
+    pool_token_program_id: Pubkey,
+    token_a_program_id: Pubkey,
+    token_b_program_id: Pubkey,
+) {
+    let user_key = Pubkey::new_unique();
+    let depositor_key = Pubkey::new_unique();
+    let fees = Fees {
+        trade_fee_numerator: 1,
+        trade_fee_denominator: 100,
+        owner_trade_fee_numerator: 1,
+        owner_trade_fee_denominator: 100,
+        ..Default::default()
+    };
+
+    let token_a_amount = 1000;
+    let token_b_amount = 9000;
+    let curve_params = CurveParameters::ConstantProduct;
+
+    let mut accounts = SwapAccountInfo::new(
+        &user_key,
+        fees,
+        SwapTransferFees::default(),
+        curve_params,
+        InitialSupply::new(token_a_amount, token_b_amount),
+        &pool_token_program_id,
+        &token_a_program_id,
+        &token_b_program_id,
+    );
+    accounts.initialize_pool().unwrap();
+
+    let deposit_a = 300;
+    let deposit_b = 3000;
+    let token_a_vault_key = accounts.token_a_vault_key;
+    let token_b_vault_key = accounts.token_b_vault_key;
+    let token_a_fees_vault_key = accounts.token_a_fees_vault_key;
+    let token_b_fees_vault_key = accounts.token_b_fees_vault_key;
+
+    // wrong fees vault
+    {
+        let (token_a_key, mut token_a_account, _, _, pool_key, mut pool_account) =
+            accounts.setup_token_accounts(&user_key, &depositor_key, deposit_a, deposit_b, 0);
+        assert_eq!(
+            Err(SwapError::IncorrectSwapAccount.into()),
+            accounts.deposit_single_token_type_exact_amount_in(
+                &depositor_key,
+                &token_a_vault_key,
+                &token_b_fees_vault_key,
+                &token_a_key,
+                &mut token_a_account,
+                &pool_key,
+                &mut pool_account,
+                deposit_a,
+                0,
+            )
+        );
+    }
+
+    // invalid input: can't use swap pool tokens as source
+    {
+        let (_, _, _, _, pool_key, mut pool_account) =
+            accounts.setup_token_accounts(&user_key, &depositor_key, deposit_a, deposit_b, 0);
+        let mut swap_token_a_account = accounts.get_vault_account(&token_a_vault_key).clone();
+        let authority_key = accounts.pool_authority;
+        assert_eq!(
+            Err(ProgramError::Custom(
+                AnchorError::ConstraintTokenOwner.into()
+            )),
+            accounts.deposit_single_token_type_exact_amount_in(
+                &authority_key,
+                &token_a_vault_key,
+                &token_a_fees_vault_key,
+                &token_a_vault_key,
+                &mut swap_token_a_account,
+                &pool_key,
+                &mut pool_account,
+                deposit_a,
+                0,
+            )
+        );
+    }
+
+    // not enough token A
+    {
+        let (token_a_key, mut token_a_account, _, _, pool_key, mut pool_account) =
+            accounts.setup_token_accounts(&user_key, &depositor_key, deposit_a / 2, deposit_b, 0);
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            accounts.deposit_single_token_type_exact_amount_in(
+                &depositor_key,
+                &token_a_vault_key,
+                &token_a_fees_vault_key,
+                &token_a_key,
+                &mut token_a_account,
+                &pool_key,
+                &mut pool_account,
+                deposit_a,
+                0,
+            )
+        );
+    }
+
+    // fees are more than the deposit
+    {
+        let (token_a_key, mut token_a_account, _, _, pool_key, mut pool_account) =
+            accounts.setup_token_accounts(&user_key, &depositor_key, deposit_a, deposit_b, 0);
+        assert_eq!(
+            Err(SwapError::ZeroTradingTokens.into()),
+            accounts.deposit_single_token_type_exact_amount_in(
+                &depositor_key,
+                &token_a_vault_key,
+                &token_a_fees_vault_key,
+                &token_a_key,
+                &mut token_a_account,
+                &pool_key,
+                &mut pool_account,
+                1,
+                0,
+            )
+        );
+    }
+
+    // slippage exceeded
+    {
+        let (token_a_key, mut token_a_account, _, _, pool_key, mut pool_account) =
+            accounts.setup_token_accounts(&user_key, &depositor_key, deposit_a, deposit_b, 0);
+        assert_eq!(
+            Err(SwapError::ExceededSlippage.into()),
+            accounts.deposit_single_token_type_exact_amount_in(
+                &depositor_key,
+                &token_a_vault_key,
+                &token_a_fees_vault_key,
+                &token_a_key,
+                &mut token_a_account,
+                &pool_key,
+                &mut pool_account,
+                deposit_a,
+                139_298_030,
+            )
+        );
+    }
+
+    // correctly deposit token A
+    {
+        let (token_a_key, mut token_a_account, _, _, pool_key, mut pool_account) =
+            accounts.setup_token_accounts(&user_key, &depositor_key, deposit_a, deposit_b, 0);
+        // fees are charged on half of the deposit: 1 trade fee + 1 owner fee
+        // 1e9 * (sqrt(1 + 298 / 1000) - 1) = 139_298_029
+        accounts
+            .deposit_single_token_type_exact_amount_in(
+                &depositor_key,
+                &token_a_vault_key,
+                &token_a_fees_vault_key,
+                &token_a_key,
+                &mut token_a_account,
+                &pool_key,
+                &mut pool_account,
+                deposit_a,
+                139_298_029,
+            )
+            .unwrap();
+
+        let swap_token_a =
+            StateWithExtensions::<Account>::unpack(&accounts.token_a_vault_account.data).unwrap();
+        assert_eq!(swap_token_a.base.amount, token_a_amount + deposit_a - 1);
+        let token_a_fees =
+            StateWithExtensions::<Account>::unpack(&accounts.token_a_fees_vault_account.data)
+                .unwrap();
+        assert_eq!(token_a_fees.base.amount, 1);
+        let token_a = StateWithExtensions::<Account>::unpack(&token_a_account.data).unwrap();
+        assert_eq!(token_a.base.amount, 0);
+        let pool_account = StateWithExtensions::<Account>::unpack(&pool_account.data).unwrap();
+        assert_eq!(pool_account.base.amount, 139_298_029);
+        let pool_mint =
+            StateWithExtensions::<Mint>::unpack(&accounts.pool_token_mint_account.data).unwrap();
+        assert_eq!(
+            u128::from(pool_mint.base.supply),
+            INITIAL_SWAP_POOL_AMOUNT + 139_298_029
+        );
+    }
+
+    // correctly deposit token B
+    {
+        let (_, _, token_b_key, mut token_b_account, pool_key, mut pool_account) =
+            accounts.setup_token_accounts(&user_key, &depositor_key, deposit_a, deposit_b, 0);
+        // fees are charged on half of the deposit: 15 trade fee + 15 owner fee
+        // 1_139_298_029 * (sqrt(1 + 2970 / 9000) - 1) = 174_604_554
+        accounts
+            .deposit_single_token_type_exact_amount_in(
+                &depositor_key,
+                &token_b_vault_key,
+                &token_b_fees_vault_key,
+                &token_b_key,
+                &mut token_b_account,
+                &pool_key,
+                &mut pool_account,
+                deposit_b,
+                174_604_554,
+            )
+            .unwrap();
+
+        let swap_token_b =
+            StateWithExtensions::<Account>::unpack(&accounts.token_b_vault_account.data).unwrap();
+        assert_eq!(swap_token_b.base.amount, token_b_amount + deposit_b - 15);
+        let token_b_fees =
+            StateWithExtensions::<Account>::unpack(&accounts.token_b_fees_vault_account.data)
+                .unwrap();
+        assert_eq!(token_b_fees.base.amount, 15);
+        let token_b = StateWithExtensions::<Account>::unpack(&token_b_account.data).unwrap();
+        assert_eq!(token_b.base.amount, 0);
+        let pool_account = StateWithExtensions::<Account>::unpack(&pool_account.data).unwrap();
+        assert_eq!(pool_account.base.amount, 174_604_554);
+    }
+}
+
+#[test]
+fn test_deposit_single_token_type_exact_amount_in_offset_curve_unsupported() {
+    let user_key = Pubkey::new_unique();
+    let depositor_key = Pubkey::new_unique();
+
+    let mut accounts = SwapAccountInfo::new(
+        &user_key,
+        Fees::default(),
+        SwapTransferFees::default(),
+        CurveParameters::Offset {
+            token_b_offset: 1_000,
+        },
+        InitialSupply::new(1_000, 1_000),
+        &spl_token::id(),
+        &spl_token::id(),
+        &spl_token::id(),
+    );
+    accounts.initialize_pool().unwrap();
+
+    let token_a_vault_key = accounts.token_a_vault_key;
+    let token_a_fees_vault_key = accounts.token_a_fees_vault_key;
+    let (token_a_key, mut token_a_account, _, _, pool_key, mut pool_account) =
+        accounts.setup_token_accounts(&user_key, &depositor_key, 100, 0, 0);
+    assert_eq!(
+        Err(SwapError::UnsupportedCurveOperation.into()),
+        accounts.deposit_single_token_type_exact_amount_in(
+            &depositor_key,
+            &token_a_vault_key,
+            &token_a_fees_vault_key,
+            &token_a_key,
+            &mut token_a_account,
+            &pool_key,
+            &mut pool_account,
+            100,
+            0,
+        )
+    );
+}
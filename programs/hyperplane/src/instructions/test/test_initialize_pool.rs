@@ -16,13 +16,12 @@ use solana_sdk::account::{
 use test_case::test_case;
 
 use crate::{
-    constraints::SwapConstraints,
     curve::{base::CurveType, fees::Fees, stable::MAX_AMP},
     error::SwapError,
     instructions::test::runner::{
         processor::{
-            do_process_instruction, do_process_instruction_with_fee_constraints, SwapAccountInfo,
-            SwapTransferFees,
+            do_process_instruction, global_config_account, permissive_global_config,
+            SwapAccountInfo, SwapTransferFees,
         },
         token,
     },
@@ -393,6 +392,8 @@ fn test_initialize(
                     &accounts.pool_token_mint_key,
                     &accounts.token_a_fees_vault_key,
                     &accounts.token_b_fees_vault_key,
+                    &accounts.token_a_protocol_fees_vault_key,
+                    &accounts.token_b_protocol_fees_vault_key,
                     &accounts.admin_authority_token_a_ata_key,
                     &accounts.admin_authority_token_b_ata_key,
                     &accounts.admin_authority_pool_token_ata_key,
@@ -403,12 +404,14 @@ fn test_initialize(
                         fees: accounts.fees,
                         initial_supply: accounts.initial_supply.clone(),
                         curve_parameters: accounts.curve_params.clone().into(),
+                        pool_token_metadata: None,
                     },
                 )
                 .unwrap(),
                 vec![
                     &mut SolanaAccount::default(),
                     &mut accounts.pool_account,
+                    &mut accounts.global_config_account,
                     &mut accounts.swap_curve_account,
                     &mut SolanaAccount::default(),
                     &mut accounts.token_a_mint_account,
@@ -418,6 +421,8 @@ fn test_initialize(
                     &mut accounts.pool_token_mint_account,
                     &mut accounts.token_a_fees_vault_account,
                     &mut accounts.token_b_fees_vault_account,
+                    &mut accounts.token_a_protocol_fees_vault_account,
+                    &mut accounts.token_b_protocol_fees_vault_account,
                     &mut accounts.admin_authority_token_a_ata_account,
                     &mut accounts.admin_authority_token_b_ata_account,
                     &mut accounts.admin_authority_pool_token_ata_account,
@@ -626,185 +631,69 @@ fn test_initialize(
         assert_eq!(curve.token_b_factor, 1);
     }
 
-    // todo - elliot - compile-time constraints
-    // // wrong owner key in constraint
-    // {
-    //     let new_key = Pubkey::new_unique();
-    //     let trade_fee_numerator = 25;
-    //     let trade_fee_denominator = 10000;
-    //     let owner_trade_fee_numerator = 5;
-    //     let owner_trade_fee_denominator = 10000;
-    //     let host_fee_numerator = 20;
-    //     let host_fee_denominator = 100;
-    //     let fees = Fees {
-    //         trade_fee_numerator,
-    //         trade_fee_denominator,
-    //         owner_trade_fee_numerator,
-    //         owner_trade_fee_denominator,
-    //         owner_withdraw_fee_numerator,
-    //         owner_withdraw_fee_denominator,
-    //         host_fee_numerator,
-    //         host_fee_denominator,
-    //     };
-    //     let curve_params = CurveParameters::ConstantProduct;
-    //     let owner_key = &new_key.to_string();
-    //     let valid_curve_types = &[CurveType::ConstantProduct];
-    //     let constraints = Some(SwapConstraints {
-    //         owner_key,
-    //         valid_curve_types,
-    //         fees: &fees,
-    //     });
-    //     let mut accounts = SwapAccountInfo::new(
-    //         &user_key,
-    //         fees.clone(),
-    //         SwapTransferFees::default(),
-    //         curve_params.clone(),
-    //         token_a_amount,
-    //         token_b_amount,
-    //         &token_a_program_id,
-    //         &token_b_program_id,
-    //     );
-    //     let exe = &mut SolanaAccount::default();
-    //     exe.set_executable(true);
-    //     assert_eq!(
-    //         Err(SwapError::InvalidOwner.into()),
-    //         do_process_instruction_with_fee_constraints(
-    //             ix::initialize_pool(
-    //                 &crate::id(),
-    //                 &accounts.admin_authority,
-    //                 &accounts.pool,
-    //                 &accounts.swap_curve_key,
-    //                 &accounts.token_a_mint_key,
-    //                 &accounts.token_b_mint_key,
-    //                 &accounts.token_a_vault_key,
-    //                 &accounts.token_b_vault_key,
-    //                 &accounts.pool_authority,
-    //                 &accounts.pool_token_mint_key,
-    //                 &accounts.pool_token_fees_vault_key,
-    //                 &accounts.admin_authority_pool_token_ata_key,
-    //                 accounts.fees.clone(),
-    //                 accounts.curve_params.clone(),
-    //             )
-    //             .unwrap(),
-    //             vec![
-    //                 &mut SolanaAccount::default(),
-    //                 &mut accounts.pool_account,
-    //                 &mut accounts.swap_curve_account,
-    //                 &mut SolanaAccount::default(),
-    //                 &mut accounts.token_a_mint_account,
-    //                 &mut accounts.token_b_mint_account,
-    //                 &mut accounts.token_a_vault_account,
-    //                 &mut accounts.token_b_vault_account,
-    //                 &mut accounts.pool_token_mint_account,
-    //                 &mut accounts.pool_token_fees_vault_account,
-    //                 &mut accounts.admin_authority_pool_token_ata_account,
-    //                 &mut exe.clone(),
-    //                 &mut create_account_for_test(&Rent::default()),
-    //                 &mut exe.clone(),
-    //             ],
-    //             &constraints,
-    //         )
-    //     );
-    // }
-    //
-    // // wrong fee in constraint
-    // {
-    //     let trade_fee_numerator = 25;
-    //     let trade_fee_denominator = 10000;
-    //     let owner_trade_fee_numerator = 5;
-    //     let owner_trade_fee_denominator = 10000;
-    //     let host_fee_numerator = 20;
-    //     let host_fee_denominator = 100;
-    //     let fees = Fees {
-    //         trade_fee_numerator,
-    //         trade_fee_denominator,
-    //         owner_trade_fee_numerator,
-    //         owner_trade_fee_denominator,
-    //         owner_withdraw_fee_numerator,
-    //         owner_withdraw_fee_denominator,
-    //         host_fee_numerator,
-    //         host_fee_denominator,
-    //     };
-    //
-    //     let curve_params = CurveParameters::ConstantProduct;
-    //     let owner_key = &user_key.to_string();
-    //     let valid_curve_types = &[CurveType::ConstantProduct];
-    //     let constraints = Some(SwapConstraints {
-    //         owner_key,
-    //         valid_curve_types,
-    //         fees: &fees,
-    //         blocked_trading_token_extensions: &[],
-    //     });
-    //     let mut bad_fees = fees.clone();
-    //     bad_fees.trade_fee_numerator = trade_fee_numerator - 1;
-    //     let mut accounts = SwapAccountInfo::new(
-    //         &user_key,
-    //         bad_fees,
-    //         SwapTransferFees::default(),
-    //         curve_params,
-    //         InitialSupply::new(token_a_amount, token_b_amount),
-    //         &pool_token_program_id,
-    //         &token_a_program_id,
-    //         &token_b_program_id,
-    //     );
-    //     let exe = &mut SolanaAccount::default();
-    //     exe.set_executable(true);
-    //     assert_eq!(
-    //         Err(SwapError::InvalidFee.into()),
-    //         do_process_instruction_with_fee_constraints(
-    //             ix::initialize_pool(
-    //                 &crate::id(),
-    //                 &accounts.admin_authority,
-    //                 &accounts.pool,
-    //                 &accounts.swap_curve_key,
-    //                 &accounts.token_a_mint_key,
-    //                 &accounts.token_b_mint_key,
-    //                 &accounts.token_a_vault_key,
-    //                 &accounts.token_b_vault_key,
-    //                 &accounts.pool_authority,
-    //                 &accounts.pool_token_mint_key,
-    //                 &accounts.token_a_fees_vault_key,
-    //                 &accounts.token_b_fees_vault_key,
-    //                 &accounts.admin_authority_token_a_ata_key,
-    //                 &accounts.admin_authority_token_b_ata_key,
-    //                 &accounts.admin_authority_pool_token_ata_key,
-    //                 &accounts.pool_token_program_id,
-    //                 &accounts.token_a_program_id,
-    //                 &accounts.token_b_program_id,
-    //                 Initialize {
-    //                     fees: accounts.fees,
-    //                     initial_supply: accounts.initial_supply.clone(),
-    //                     curve_parameters: accounts.curve_params.clone().into(),
-    //                 },
-    //             )
-    //             .unwrap(),
-    //             vec![
-    //                 &mut SolanaAccount::default(),
-    //                 &mut accounts.pool_account,
-    //                 &mut accounts.swap_curve_account,
-    //                 &mut SolanaAccount::default(),
-    //                 &mut accounts.token_a_mint_account,
-    //                 &mut accounts.token_b_mint_account,
-    //                 &mut accounts.token_a_vault_account,
-    //                 &mut accounts.token_b_vault_account,
-    //                 &mut accounts.pool_token_mint_account,
-    //                 &mut accounts.token_a_fees_vault_account,
-    //                 &mut accounts.token_b_fees_vault_account,
-    //                 &mut accounts.admin_authority_token_a_ata_account,
-    //                 &mut accounts.admin_authority_token_b_ata_account,
-    //                 &mut accounts.admin_authority_pool_token_ata_account,
-    //                 &mut exe.clone(), // system_program
-    //                 &mut create_account_for_test(&Rent::default()),
-    //                 &mut exe.clone(), // pool_token_program
-    //                 &mut exe.clone(), // token_a_program
-    //                 &mut exe.clone(), // token_b_program
-    //             ],
-    //             &constraints,
-    //         )
-    //     );
-    // }
+    // fee below the global config minimum
+    {
+        let trade_fee_numerator = 25;
+        let trade_fee_denominator = 10000;
+        let owner_trade_fee_numerator = 5;
+        let owner_trade_fee_denominator = 10000;
+        let host_fee_numerator = 20;
+        let host_fee_denominator = 100;
+        let fees = Fees {
+            trade_fee_numerator,
+            trade_fee_denominator,
+            owner_trade_fee_numerator,
+            owner_trade_fee_denominator,
+            owner_withdraw_fee_numerator,
+            owner_withdraw_fee_denominator,
+            host_fee_numerator,
+            host_fee_denominator,
+        };
+        let mut global_config = permissive_global_config();
+        global_config.min_fees = fees;
+        let mut bad_fees = fees;
+        bad_fees.trade_fee_numerator = trade_fee_numerator - 1;
+        let mut accounts = SwapAccountInfo::new(
+            &user_key,
+            bad_fees,
+            SwapTransferFees::default(),
+            CurveParameters::ConstantProduct,
+            InitialSupply::new(token_a_amount, token_b_amount),
+            &pool_token_program_id,
+            &token_a_program_id,
+            &token_b_program_id,
+        );
+        accounts.global_config_account = global_config_account(&global_config);
+        assert_eq!(
+            Err(SwapError::InvalidFee.into()),
+            accounts.initialize_pool()
+        );
+    }
+
+    // curve type not allowed by the global config
+    {
+        let mut global_config = permissive_global_config();
+        global_config
+            .set_valid_curve_types(&[CurveType::ConstantPrice.into()])
+            .unwrap();
+        let mut accounts = SwapAccountInfo::new(
+            &user_key,
+            fees,
+            SwapTransferFees::default(),
+            CurveParameters::ConstantProduct,
+            InitialSupply::new(token_a_amount, token_b_amount),
+            &pool_token_program_id,
+            &token_a_program_id,
+            &token_b_program_id,
+        );
+        accounts.global_config_account = global_config_account(&global_config);
+        assert_eq!(
+            Err(SwapError::UnsupportedCurveType.into()),
+            accounts.initialize_pool()
+        );
+    }
 
-    // create valid swap with constraints
+    // create valid swap with global config constraints
     {
         let trade_fee_numerator = 25;
         let trade_fee_denominator = 10000;
@@ -822,78 +711,23 @@ fn test_initialize(
             host_fee_numerator,
             host_fee_denominator,
         };
-        let curve_params = CurveParameters::ConstantProduct;
-        let owner_key = &user_key.to_string();
-        let valid_curve_types = &[CurveType::ConstantProduct];
-        let constraints = Some(SwapConstraints {
-            owner_key,
-            valid_curve_types,
-            fees: &fees,
-            blocked_trading_token_extensions: &[],
-        });
+        let mut global_config = permissive_global_config();
+        global_config.min_fees = fees;
+        global_config
+            .set_valid_curve_types(&[CurveType::ConstantProduct.into()])
+            .unwrap();
         let mut accounts = SwapAccountInfo::new(
             &user_key,
             fees,
             SwapTransferFees::default(),
-            curve_params,
+            CurveParameters::ConstantProduct,
             InitialSupply::new(token_a_amount, token_b_amount),
             &pool_token_program_id,
             &token_a_program_id,
             &token_b_program_id,
         );
-        let exe = &mut SolanaAccount::default();
-        exe.set_executable(true);
-        do_process_instruction_with_fee_constraints(
-            crate::ix::initialize_pool(
-                &crate::id(),
-                &accounts.admin_authority,
-                &accounts.pool,
-                &accounts.swap_curve_key,
-                &accounts.token_a_mint_key,
-                &accounts.token_b_mint_key,
-                &accounts.token_a_vault_key,
-                &accounts.token_b_vault_key,
-                &accounts.pool_authority,
-                &accounts.pool_token_mint_key,
-                &accounts.token_a_fees_vault_key,
-                &accounts.token_b_fees_vault_key,
-                &accounts.admin_authority_token_a_ata_key,
-                &accounts.admin_authority_token_b_ata_key,
-                &accounts.admin_authority_pool_token_ata_key,
-                &accounts.pool_token_program_id,
-                &accounts.token_a_program_id,
-                &accounts.token_b_program_id,
-                Initialize {
-                    fees: accounts.fees,
-                    initial_supply: accounts.initial_supply.clone(),
-                    curve_parameters: accounts.curve_params.clone().into(),
-                },
-            )
-            .unwrap(),
-            vec![
-                &mut SolanaAccount::default(),
-                &mut accounts.pool_account,
-                &mut accounts.swap_curve_account,
-                &mut SolanaAccount::default(),
-                &mut accounts.token_a_mint_account,
-                &mut accounts.token_b_mint_account,
-                &mut accounts.token_a_vault_account,
-                &mut accounts.token_b_vault_account,
-                &mut accounts.pool_token_mint_account,
-                &mut accounts.token_a_fees_vault_account,
-                &mut accounts.token_b_fees_vault_account,
-                &mut accounts.admin_authority_token_a_ata_account,
-                &mut accounts.admin_authority_token_b_ata_account,
-                &mut accounts.admin_authority_pool_token_ata_account,
-                &mut exe.clone(), // system_program
-                &mut create_account_for_test(&Rent::default()),
-                &mut exe.clone(), // pool_token_program
-                &mut exe.clone(), // token_a_program
-                &mut exe.clone(), // token_b_program
-            ],
-            &constraints,
-        )
-        .unwrap();
+        accounts.global_config_account = global_config_account(&global_config);
+        accounts.initialize_pool().unwrap();
     }
 
     // create again
@@ -16,13 +16,12 @@ use solana_sdk::account::{create_account_for_test, Account as SolanaAccount, Wri
 use test_case::test_case;
 
 use crate::{
-    constraints::SwapConstraints,
     curve::{base::CurveType, fees::Fees},
     error::SwapError,
     instructions::test::runner::{
         processor::{
-            do_process_instruction, do_process_instruction_with_fee_constraints, SwapAccountInfo,
-            SwapTransferFees,
+            do_process_instruction, global_config_account, permissive_global_config,
+            SwapAccountInfo, SwapTransferFees,
         },
         token,
     },
@@ -233,14 +232,11 @@ fn test_valid_swap_with_fee_constraints(
 
     let curve_params = CurveParameters::ConstantProduct;
 
-    let owner_key_str = &owner_key.to_string();
-    let valid_curve_types = &[CurveType::ConstantProduct];
-    let constraints = Some(SwapConstraints {
-        owner_key: owner_key_str,
-        valid_curve_types,
-        fees: &fees,
-        blocked_trading_token_extensions: &[],
-    });
+    let mut global_config = permissive_global_config();
+    global_config.min_fees = fees;
+    global_config
+        .set_valid_curve_types(&[CurveType::ConstantProduct.into()])
+        .unwrap();
     let mut accounts = SwapAccountInfo::new(
         &owner_key,
         fees,
@@ -252,11 +248,13 @@ fn test_valid_swap_with_fee_constraints(
         &token_b_program_id,
     );
 
+    accounts.global_config_account = global_config_account(&global_config);
+
     let exe = &mut SolanaAccount::default();
     exe.set_executable(true);
 
     // initialize swap
-    do_process_instruction_with_fee_constraints(
+    do_process_instruction(
         ix::initialize_pool(
             &crate::id(),
             &accounts.admin_authority,
@@ -270,6 +268,8 @@ fn test_valid_swap_with_fee_constraints(
             &accounts.pool_token_mint_key,
             &accounts.token_a_fees_vault_key,
             &accounts.token_b_fees_vault_key,
+            &accounts.token_a_protocol_fees_vault_key,
+            &accounts.token_b_protocol_fees_vault_key,
             &accounts.admin_authority_token_a_ata_key,
             &accounts.admin_authority_token_b_ata_key,
             &accounts.admin_authority_pool_token_ata_key,
@@ -280,12 +280,14 @@ fn test_valid_swap_with_fee_constraints(
                 fees: accounts.fees,
                 initial_supply: accounts.initial_supply.clone(),
                 curve_parameters: accounts.curve_params.clone().into(),
+                pool_token_metadata: None,
             },
         )
         .unwrap(),
         vec![
             &mut SolanaAccount::default(),
             &mut accounts.pool_account,
+            &mut accounts.global_config_account,
             &mut accounts.swap_curve_account,
             &mut SolanaAccount::default(),
             &mut accounts.token_a_mint_account,
@@ -295,6 +297,8 @@ fn test_valid_swap_with_fee_constraints(
             &mut accounts.pool_token_mint_account,
             &mut accounts.token_a_fees_vault_account,
             &mut accounts.token_b_fees_vault_account,
+            &mut accounts.token_a_protocol_fees_vault_account,
+            &mut accounts.token_b_protocol_fees_vault_account,
             &mut accounts.admin_authority_token_a_ata_account,
             &mut accounts.admin_authority_token_b_ata_account,
             &mut accounts.admin_authority_pool_token_ata_account,
@@ -304,7 +308,6 @@ fn test_valid_swap_with_fee_constraints(
             &mut exe.clone(), // token_a_program
             &mut exe.clone(), // token_b_program
         ],
-        &constraints,
     )
     .unwrap();
 
@@ -341,7 +344,7 @@ fn test_valid_swap_with_fee_constraints(
     exe.set_executable(true);
 
     // perform the swap
-    do_process_instruction_with_fee_constraints(
+    do_process_instruction(
         ix::swap(
             &crate::id(),
             &authority_key,
@@ -353,6 +356,7 @@ fn test_valid_swap_with_fee_constraints(
             &accounts.token_a_vault_key,
             &accounts.token_b_vault_key,
             &accounts.token_a_fees_vault_key,
+            &accounts.token_a_protocol_fees_vault_key,
             &user_token_a_key,
             &user_token_b_key,
             Some(&host_fee_token_a_key),
@@ -367,6 +371,7 @@ fn test_valid_swap_with_fee_constraints(
         vec![
             &mut SolanaAccount::default(),
             &mut accounts.pool_account,
+            &mut accounts.global_config_account,
             &mut accounts.swap_curve_account,
             &mut SolanaAccount::default(),
             &mut accounts.token_a_mint_account,
@@ -374,13 +379,13 @@ fn test_valid_swap_with_fee_constraints(
             &mut accounts.token_a_vault_account,
             &mut accounts.token_b_vault_account,
             &mut accounts.token_a_fees_vault_account,
+            &mut accounts.token_a_protocol_fees_vault_account,
             &mut token_a_account,
             &mut token_b_account,
             &mut host_fee_a_account,
             &mut exe.clone(), // source_token_program
             &mut exe.clone(), // destination_token_program
         ],
-        &constraints,
     )
     .unwrap();
 
@@ -572,6 +577,7 @@ fn test_invalid_swap(
                     &accounts.token_a_vault_key,
                     &accounts.token_b_vault_key,
                     &accounts.token_a_fees_vault_key,
+                    &accounts.token_a_protocol_fees_vault_key,
                     &user_token_a_key,
                     &user_token_b_key,
                     None,
@@ -586,6 +592,7 @@ fn test_invalid_swap(
                 vec![
                     &mut SolanaAccount::default(),
                     &mut accounts.pool_account,
+                    &mut accounts.global_config_account,
                     &mut accounts.swap_curve_account,
                     &mut SolanaAccount::default(),
                     &mut accounts.token_a_mint_account,
@@ -593,6 +600,7 @@ fn test_invalid_swap(
                     &mut accounts.token_a_vault_account,
                     &mut accounts.token_b_vault_account,
                     &mut accounts.token_a_fees_vault_account,
+                    &mut accounts.token_a_protocol_fees_vault_account,
                     &mut token_a_account,
                     &mut token_b_account,
                     &mut exe.clone(), // Optional front end host fees - passed as the program if not present
@@ -657,7 +665,7 @@ fn test_invalid_swap(
         );
     }
 
-    // still correct: constraint specified, no host fee account
+    // still correct: global config min fees, no host fee account
     {
         let authority_key = accounts.pool_authority;
         let (
@@ -668,7 +676,6 @@ fn test_invalid_swap(
             _pool_key,
             _pool_account,
         ) = accounts.setup_token_accounts(&user_key, &authority_key, initial_a, initial_b, 0);
-        let owner_key = &swapper_key.to_string();
         let fees = Fees {
             trade_fee_numerator,
             trade_fee_denominator,
@@ -679,17 +686,14 @@ fn test_invalid_swap(
             host_fee_numerator,
             host_fee_denominator,
         };
-        let constraints = Some(SwapConstraints {
-            owner_key,
-            valid_curve_types: &[],
-            fees: &fees,
-            blocked_trading_token_extensions: &[],
-        });
+        let mut global_config = permissive_global_config();
+        global_config.min_fees = fees;
+        accounts.global_config_account = global_config_account(&global_config);
 
         let exe = &mut SolanaAccount::default();
         exe.set_executable(true);
 
-        do_process_instruction_with_fee_constraints(
+        do_process_instruction(
             ix::swap(
                 &crate::id(),
                 &accounts.pool_authority,
@@ -701,6 +705,7 @@ fn test_invalid_swap(
                 &accounts.token_a_vault_key,
                 &accounts.token_b_vault_key,
                 &accounts.token_a_fees_vault_key,
+                &accounts.token_a_protocol_fees_vault_key,
                 &token_a_key,
                 &token_b_key,
                 None,
@@ -715,6 +720,7 @@ fn test_invalid_swap(
             vec![
                 &mut SolanaAccount::default(),
                 &mut accounts.pool_account,
+                &mut accounts.global_config_account,
                 &mut accounts.swap_curve_account,
                 &mut SolanaAccount::default(),
                 &mut accounts.token_a_mint_account,
@@ -722,13 +728,13 @@ fn test_invalid_swap(
                 &mut accounts.token_a_vault_account,
                 &mut accounts.token_b_vault_account,
                 &mut accounts.token_a_fees_vault_account,
+                &mut accounts.token_a_protocol_fees_vault_account,
                 &mut token_a_account,
                 &mut token_b_account,
                 &mut exe.clone(), // Optional front end host fees - passed as the program if not present
                 &mut exe.clone(), // source_token_program
                 &mut exe.clone(), // destination_token_program
             ],
-            &constraints,
         )
         .unwrap();
     }
@@ -752,7 +758,6 @@ fn test_invalid_swap(
             _pool_key,
             _pool_account,
         ) = accounts.setup_token_accounts(&user_key, &authority_key, initial_a, initial_b, 0);
-        let owner_key = &swapper_key.to_string();
         let fees = Fees {
             trade_fee_numerator,
             trade_fee_denominator,
@@ -763,12 +768,9 @@ fn test_invalid_swap(
             host_fee_numerator,
             host_fee_denominator,
         };
-        let constraints = Some(SwapConstraints {
-            owner_key,
-            valid_curve_types: &[],
-            fees: &fees,
-            blocked_trading_token_extensions: &[],
-        });
+        let mut global_config = permissive_global_config();
+        global_config.min_fees = fees;
+        accounts.global_config_account = global_config_account(&global_config);
 
         let exe = &mut SolanaAccount::default();
         exe.set_executable(true);
@@ -777,7 +779,7 @@ fn test_invalid_swap(
             Err(ProgramError::Custom(
                 AnchorError::ConstraintTokenMint.into()
             )),
-            do_process_instruction_with_fee_constraints(
+            do_process_instruction(
                 ix::swap(
                     &crate::id(),
                     &accounts.pool_authority,
@@ -789,6 +791,7 @@ fn test_invalid_swap(
                     &accounts.token_a_vault_key,
                     &accounts.token_b_vault_key,
                     &accounts.token_a_fees_vault_key,
+                    &accounts.token_a_protocol_fees_vault_key,
                     &token_a_key,
                     &token_b_key,
                     Some(&bad_token_a_key),
@@ -803,6 +806,7 @@ fn test_invalid_swap(
                 vec![
                     &mut SolanaAccount::default(),
                     &mut accounts.pool_account,
+                    &mut accounts.global_config_account,
                     &mut accounts.swap_curve_account,
                     &mut SolanaAccount::default(),
                     &mut accounts.token_a_mint_account,
@@ -810,13 +814,13 @@ fn test_invalid_swap(
                     &mut accounts.token_a_vault_account,
                     &mut accounts.token_b_vault_account,
                     &mut accounts.token_a_fees_vault_account,
+                    &mut accounts.token_a_protocol_fees_vault_account,
                     &mut token_a_account,
                     &mut token_b_account,
                     &mut bad_token_a_account, // Optional front end host fees - passed as the program if not present
                     &mut exe.clone(),         // source_token_program
                     &mut exe.clone(),         // destination_token_program
                 ],
-                &constraints,
             ),
         );
     }
@@ -1042,6 +1046,54 @@ fn test_swap_curve_with_transfer_fees(
     );
 }
 
+#[test_case(spl_token::id(), spl_token::id(), spl_token::id(); "all-token")]
+#[test_case(spl_token::id(), spl_token_2022::id(), spl_token_2022::id(); "mixed-pool-token")]
+#[test_case(spl_token::id(), spl_token_2022::id(), spl_token::id(); "a-only-token-2022")]
+#[test_case(spl_token::id(), spl_token::id(), spl_token_2022::id(); "b-only-token-2022")]
+fn test_valid_swap_exact_out_curve_all_fees(
+    pool_token_program_id: Pubkey,
+    token_a_program_id: Pubkey,
+    token_b_program_id: Pubkey,
+) {
+    // All fees
+    let fees = Fees {
+        trade_fee_numerator: 1,
+        trade_fee_denominator: 10,
+        owner_trade_fee_numerator: 1,
+        owner_trade_fee_denominator: 30,
+        owner_withdraw_fee_numerator: 1,
+        owner_withdraw_fee_denominator: 30,
+        host_fee_numerator: 20,
+        host_fee_denominator: 100,
+    };
+
+    let token_a_amount = 10_000_000_000;
+    let token_b_amount = 50_000_000_000;
+
+    for curve_params in [
+        CurveParameters::ConstantProduct,
+        CurveParameters::ConstantPrice { token_b_price: 1 },
+        CurveParameters::Offset {
+            token_b_offset: 10_000_000_000,
+        },
+        CurveParameters::Stable {
+            amp: 100,
+            token_a_decimals: 6,
+            token_b_decimals: 6,
+        },
+    ] {
+        assert::check_valid_swap_exact_out_curve(
+            fees,
+            curve_params,
+            token_a_amount,
+            token_b_amount,
+            &pool_token_program_id,
+            &token_a_program_id,
+            &token_b_program_id,
+        );
+    }
+}
+
 mod assert {
     use super::*;
     use crate::curve::calculator::TradeDirection;
@@ -1247,4 +1299,181 @@ mod assert {
                 .unwrap();
         assert_eq!(token_b_fee_account.base.amount, second_fee);
     }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn check_valid_swap_exact_out_curve(
+        fees: Fees,
+        curve_params: CurveParameters,
+        token_a_amount: u64,
+        token_b_amount: u64,
+        pool_token_program_id: &Pubkey,
+        token_a_program_id: &Pubkey,
+        token_b_program_id: &Pubkey,
+    ) {
+        let user_key = Pubkey::new_unique();
+        let swapper_key = Pubkey::new_unique();
+
+        let mut accounts = SwapAccountInfo::new(
+            &user_key,
+            fees,
+            SwapTransferFees::default(),
+            curve_params,
+            InitialSupply::new(token_a_amount, token_b_amount),
+            pool_token_program_id,
+            token_a_program_id,
+            token_b_program_id,
+        );
+        let initial_a = token_a_amount / 5;
+        let initial_b = token_b_amount / 5;
+        accounts.initialize_pool().unwrap();
+
+        let token_a_vault_key = accounts.token_a_vault_key;
+        let token_b_vault_key = accounts.token_b_vault_key;
+        let token_a_fees_vault_key = accounts.token_a_fees_vault_key;
+        let token_b_fees_vault_key = accounts.token_b_fees_vault_key;
+
+        let (
+            token_a_key,
+            mut token_a_account,
+            token_b_key,
+            mut token_b_account,
+            _pool_key,
+            _pool_account,
+        ) = accounts.setup_token_accounts(&user_key, &swapper_key, initial_a, initial_b, 0);
+
+        // swap one way for an exact amount of token b
+        let a_to_b_amount_out = initial_b / 10;
+        let results = accounts
+            .swap_curve
+            .swap_exact_out(
+                a_to_b_amount_out.into(),
+                token_a_amount.into(),
+                token_b_amount.into(),
+                TradeDirection::AtoB,
+                &fees,
+            )
+            .unwrap();
+        let a_to_b_amount_in = u64::try_from(results.total_source_amount_swapped).unwrap();
+
+        // not enough input allowed
+        assert_eq!(
+            Err(SwapError::ExceededSlippage.into()),
+            accounts.swap_exact_out(
+                &swapper_key,
+                &token_a_key,
+                &mut token_a_account,
+                &token_a_vault_key,
+                &token_a_fees_vault_key,
+                &token_b_vault_key,
+                &token_b_key,
+                &mut token_b_account,
+                a_to_b_amount_out,
+                a_to_b_amount_in - 1,
+            )
+        );
+
+        accounts
+            .swap_exact_out(
+                &swapper_key,
+                &token_a_key,
+                &mut token_a_account,
+                &token_a_vault_key,
+                &token_a_fees_vault_key,
+                &token_b_vault_key,
+                &token_b_key,
+                &mut token_b_account,
+                a_to_b_amount_out,
+                a_to_b_amount_in,
+            )
+            .unwrap();
+
+        let swap_token_a =
+            StateWithExtensions::<Account>::unpack(&accounts.token_a_vault_account.data).unwrap();
+        let token_a_amount = swap_token_a.base.amount;
+        assert_eq!(
+            token_a_amount,
+            u64::try_from(results.new_pool_source_amount).unwrap()
+        );
+        let token_a = StateWithExtensions::<Account>::unpack(&token_a_account.data).unwrap();
+        assert_eq!(token_a.base.amount, initial_a - a_to_b_amount_in);
+
+        let swap_token_b =
+            StateWithExtensions::<Account>::unpack(&accounts.token_b_vault_account.data).unwrap();
+        let token_b_amount = swap_token_b.base.amount;
+        assert_eq!(
+            token_b_amount,
+            u64::try_from(results.new_pool_destination_amount).unwrap()
+        );
+        let token_b = StateWithExtensions::<Account>::unpack(&token_b_account.data).unwrap();
+        assert_eq!(token_b.base.amount, initial_b + a_to_b_amount_out);
+
+        let first_fee = u64::try_from(results.owner_fee).unwrap();
+        let token_a_fee_account =
+            StateWithExtensions::<Account>::unpack(&accounts.token_a_fees_vault_account.data)
+                .unwrap();
+        assert_eq!(token_a_fee_account.base.amount, first_fee);
+
+        // swap the other way for an exact amount of token a
+        let b_to_a_amount_out = initial_a / 10;
+        let results = accounts
+            .swap_curve
+            .swap_exact_out(
+                b_to_a_amount_out.into(),
+                token_b_amount.into(),
+                token_a_amount.into(),
+                TradeDirection::BtoA,
+                &fees,
+            )
+            .unwrap();
+        let b_to_a_amount_in = u64::try_from(results.total_source_amount_swapped).unwrap();
+
+        accounts
+            .swap_exact_out(
+                &swapper_key,
+                &token_b_key,
+                &mut token_b_account,
+                &token_b_vault_key,
+                &token_b_fees_vault_key,
+                &token_a_vault_key,
+                &token_a_key,
+                &mut token_a_account,
+                b_to_a_amount_out,
+                b_to_a_amount_in,
+            )
+            .unwrap();
+
+        let swap_token_a =
+            StateWithExtensions::<Account>::unpack(&accounts.token_a_vault_account.data).unwrap();
+        assert_eq!(
+            swap_token_a.base.amount,
+            u64::try_from(results.new_pool_destination_amount).unwrap()
+        );
+        let token_a = StateWithExtensions::<Account>::unpack(&token_a_account.data).unwrap();
+        assert_eq!(
+            token_a.base.amount,
+            initial_a - a_to_b_amount_in + b_to_a_amount_out
+        );
+
+        let swap_token_b =
+            StateWithExtensions::<Account>::unpack(&accounts.token_b_vault_account.data).unwrap();
+        assert_eq!(
+            swap_token_b.base.amount,
+            u64::try_from(results.new_pool_source_amount).unwrap()
+        );
+        let token_b = StateWithExtensions::<Account>::unpack(&token_b_account.data).unwrap();
+        assert_eq!(
+            token_b.base.amount,
+            initial_b + a_to_b_amount_out - b_to_a_amount_in
+        );
+
+        let second_fee = u64::try_from(results.owner_fee).unwrap();
+        let token_a_fee_account =
+            StateWithExtensions::<Account>::unpack(&accounts.token_a_fees_vault_account.data)
+                .unwrap();
+        assert_eq!(token_a_fee_account.base.amount, first_fee);
+        let token_b_fee_account =
+            StateWithExtensions::<Account>::unpack(&accounts.token_b_fees_vault_account.data)
+                .unwrap();
+        assert_eq!(token_b_fee_account.base.amount, second_fee);
+    }
 }
@@ -16,7 +16,11 @@ use solana_sdk::account::{Account as SolanaAccount, WritableAccount};
 use test_case::test_case;
 
 use crate::{
-    curve::{base::SwapCurve, calculator::RoundDirection, fees::Fees},
+    curve::{
+        base::SwapCurve,
+        calculator::{AorB, RoundDirection, INITIAL_SWAP_POOL_AMOUNT},
+        fees::Fees,
+    },
     error::SwapError,
     instructions::test::runner::{
         processor::{do_process_instruction, SwapAccountInfo, SwapTransferFees},
@@ -715,3 +719,334 @@ fn test_withdraw_constant_price_curve(
         )
         .unwrap();
 }
+
+#[test_case(spl_token::id(), spl_token::id(), spl_token::id(); "all-token")]
+#[test_case(spl_token::id(), spl_token_2022::id(), spl_token_2022::id(); "mixed-pool-token")]
+#[test_case(spl_token::id(), spl_token_2022::id(), spl_token::id(); "a-only-token-2022")]
+#[test_case(spl_token::id(), spl_token::id(), spl_token_2022::id(); "b-only-token-2022")]
+fn test_withdraw_single_token_type_exact_amount_out(
+    pool_token_program_id: Pubkey,
+    token_a_program_id: Pubkey,
+    token_b_program_id: Pubkey,
+) {
+    let user_key = Pubkey::new_unique();
+    let withdrawer_key = Pubkey::new_unique();
+    let fees = Fees {
+        trade_fee_numerator: 1,
+        trade_fee_denominator: 100,
+        owner_trade_fee_numerator: 1,
+        owner_trade_fee_denominator: 100,
+        owner_withdraw_fee_numerator: 1,
+        owner_withdraw_fee_denominator: 100,
+        ..Default::default()
+    };
+
+    let token_a_amount = 10_000;
+    let token_b_amount = 10_000;
+    let curve_params = CurveParameters::ConstantProduct;
+
+    let mut accounts = SwapAccountInfo::new(
+        &user_key,
+        fees,
+        SwapTransferFees::default(),
+        curve_params,
+        InitialSupply::new(token_a_amount, token_b_amount),
+        &pool_token_program_id,
+        &token_a_program_id,
+        &token_b_program_id,
+    );
+    accounts.initialize_pool().unwrap();
+
+    let initial_pool = INITIAL_SWAP_POOL_AMOUNT as u64;
+    let destination_amount = 1_000;
+    let token_a_vault_key = accounts.token_a_vault_key;
+    let token_a_fees_vault_key = accounts.token_a_fees_vault_key;
+    let token_b_fees_vault_key = accounts.token_b_fees_vault_key;
+
+    // wrong fees vault
+    {
+        let (token_a_key, mut token_a_account, _, _, pool_key, mut pool_account) =
+            accounts.setup_token_accounts(&user_key, &withdrawer_key, 0, 0, 0);
+        assert_eq!(
+            Err(SwapError::IncorrectFeeAccount.into()),
+            accounts.withdraw_single_token_type_exact_amount_out(
+                &withdrawer_key,
+                &token_a_vault_key,
+                &token_b_fees_vault_key,
+                &pool_key,
+                &mut pool_account,
+                &token_a_key,
+                &mut token_a_account,
+                destination_amount,
+                initial_pool,
+            )
+        );
+    }
+
+    // withdrawing more than the pool holds
+    {
+        let (token_a_key, mut token_a_account, _, _, pool_key, mut pool_account) =
+            accounts.setup_token_accounts(&user_key, &withdrawer_key, 0, 0, 0);
+        assert_eq!(
+            Err(SwapError::ZeroTradingTokens.into()),
+            accounts.withdraw_single_token_type_exact_amount_out(
+                &withdrawer_key,
+                &token_a_vault_key,
+                &token_a_fees_vault_key,
+                &pool_key,
+                &mut pool_account,
+                &token_a_key,
+                &mut token_a_account,
+                token_a_amount,
+                initial_pool,
+            )
+        );
+    }
+
+    // slippage exceeded: 1e9 * (1 - sqrt(1 - 1_020 / 10_000)) = 52_371_381 pool tokens
+    {
+        let (token_a_key, mut token_a_account, _, _, pool_key, mut pool_account) =
+            accounts.setup_token_accounts(&user_key, &withdrawer_key, 0, 0, 0);
+        assert_eq!(
+            Err(SwapError::ExceededSlippage.into()),
+            accounts.withdraw_single_token_type_exact_amount_out(
+                &withdrawer_key,
+                &token_a_vault_key,
+                &token_a_fees_vault_key,
+                &pool_key,
+                &mut pool_account,
+                &token_a_key,
+                &mut token_a_account,
+                destination_amount,
+                52_371_380,
+            )
+        );
+    }
+
+    // not enough pool tokens
+    {
+        let (token_a_key, mut token_a_account, _, _, pool_key, mut pool_account) =
+            accounts.setup_token_accounts(&user_key, &withdrawer_key, 0, 0, 0);
+        assert_eq!(
+            Err(SwapError::InsufficientPoolTokenFunds.into()),
+            accounts.withdraw_single_token_type_exact_amount_out(
+                &withdrawer_key,
+                &token_a_vault_key,
+                &token_a_fees_vault_key,
+                &pool_key,
+                &mut pool_account,
+                &token_a_key,
+                &mut token_a_account,
+                destination_amount,
+                initial_pool,
+            )
+        );
+    }
+
+    // correctly withdraw token A
+    {
+        let (token_a_key, mut token_a_account, _, _, pool_key, mut pool_account) =
+            accounts.setup_token_accounts(&user_key, &withdrawer_key, 0, 0, initial_pool);
+        // the pool token supply is 2e9, fees are 10 owner withdraw and 5 + 5
+        // trade fees charged on half of the withdrawal, so 1_020 leaves the pool curve
+        // 2_000_000_000 * (1 - sqrt(1 - 1_020 / 10_000)) = 104_742_762
+        accounts
+            .withdraw_single_token_type_exact_amount_out(
+                &withdrawer_key,
+                &token_a_vault_key,
+                &token_a_fees_vault_key,
+                &pool_key,
+                &mut pool_account,
+                &token_a_key,
+                &mut token_a_account,
+                destination_amount,
+                initial_pool,
+            )
+            .unwrap();
+
+        let swap_token_a =
+            StateWithExtensions::<Account>::unpack(&accounts.token_a_vault_account.data).unwrap();
+        assert_eq!(
+            swap_token_a.base.amount,
+            token_a_amount - destination_amount - 15
+        );
+        let token_a_fees =
+            StateWithExtensions::<Account>::unpack(&accounts.token_a_fees_vault_account.data)
+                .unwrap();
+        assert_eq!(token_a_fees.base.amount, 15);
+        let token_a = StateWithExtensions::<Account>::unpack(&token_a_account.data).unwrap();
+        assert_eq!(token_a.base.amount, destination_amount);
+        let pool_account = StateWithExtensions::<Account>::unpack(&pool_account.data).unwrap();
+        assert_eq!(pool_account.base.amount, initial_pool - 104_742_762);
+        let pool_mint =
+            StateWithExtensions::<Mint>::unpack(&accounts.pool_token_mint_account.data).unwrap();
+        assert_eq!(pool_mint.base.supply, 2 * initial_pool - 104_742_762);
+    }
+}
+
+#[test_case(spl_token::id(), spl_token::id(), spl_token::id(); "all-token")]
+#[test_case(spl_token::id(), spl_token_2022::id(), spl_token_2022::id(); "mixed-pool-token")]
+#[test_case(spl_token::id(), spl_token_2022::id(), spl_token::id(); "a-only-token-2022")]
+#[test_case(spl_token::id(), spl_token::id(), spl_token_2022::id(); "b-only-token-2022")]
+fn test_withdraw_one_token(
+    pool_token_program_id: Pubkey,
+    token_a_program_id: Pubkey,
+    token_b_program_id: Pubkey,
+) {
+    let user_key = Pubkey::new_unique();
+    let withdrawer_key = Pubkey::new_unique();
+    let fees = Fees {
+        trade_fee_numerator: 1,
+        trade_fee_denominator: 100,
+        owner_trade_fee_numerator: 1,
+        owner_trade_fee_denominator: 100,
+        owner_withdraw_fee_numerator: 1,
+        owner_withdraw_fee_denominator: 100,
+        ..Default::default()
+    };
+
+    let token_a_amount = 10_000;
+    let token_b_amount = 10_000;
+    let curve_params = CurveParameters::ConstantProduct;
+
+    let mut accounts = SwapAccountInfo::new(
+        &user_key,
+        fees,
+        SwapTransferFees::default(),
+        curve_params,
+        InitialSupply::new(token_a_amount, token_b_amount),
+        &pool_token_program_id,
+        &token_a_program_id,
+        &token_b_program_id,
+    );
+    accounts.initialize_pool().unwrap();
+
+    let initial_pool = INITIAL_SWAP_POOL_AMOUNT as u64;
+    let withdraw_amount = initial_pool / 10;
+    let token_b_vault_key = accounts.token_b_vault_key;
+    let token_a_fees_vault_key = accounts.token_a_fees_vault_key;
+    let token_b_fees_vault_key = accounts.token_b_fees_vault_key;
+
+    // wrong fees vault
+    {
+        let (_, _, token_b_key, mut token_b_account, pool_key, mut pool_account) =
+            accounts.setup_token_accounts(&user_key, &withdrawer_key, 0, 0, 0);
+        assert_eq!(
+            Err(SwapError::IncorrectFeeAccount.into()),
+            accounts.withdraw_one_token(
+                &withdrawer_key,
+                &token_b_vault_key,
+                &token_a_fees_vault_key,
+                &pool_key,
+                &mut pool_account,
+                &token_b_key,
+                &mut token_b_account,
+                withdraw_amount,
+                0,
+                AorB::B,
+            )
+        );
+    }
+
+    // requested token does not match the destination mint
+    {
+        let (_, _, token_b_key, mut token_b_account, pool_key, mut pool_account) =
+            accounts.setup_token_accounts(&user_key, &withdrawer_key, 0, 0, 0);
+        assert_eq!(
+            Err(SwapError::IncorrectTradingMint.into()),
+            accounts.withdraw_one_token(
+                &withdrawer_key,
+                &token_b_vault_key,
+                &token_b_fees_vault_key,
+                &pool_key,
+                &mut pool_account,
+                &token_b_key,
+                &mut token_b_account,
+                withdraw_amount,
+                0,
+                AorB::A,
+            )
+        );
+    }
+
+    // not enough pool tokens
+    {
+        let (_, _, token_b_key, mut token_b_account, pool_key, mut pool_account) =
+            accounts.setup_token_accounts(&user_key, &withdrawer_key, 0, 0, 0);
+        assert_eq!(
+            Err(SwapError::InsufficientPoolTokenFunds.into()),
+            accounts.withdraw_one_token(
+                &withdrawer_key,
+                &token_b_vault_key,
+                &token_b_fees_vault_key,
+                &pool_key,
+                &mut pool_account,
+                &token_b_key,
+                &mut token_b_account,
+                withdraw_amount,
+                0,
+                AorB::B,
+            )
+        );
+    }
+
+    // slippage exceeded: 10_000 * (1 - (1 - 1e8 / 2e9)^2) = 975, minus 4 + 4 trade fees
+    // and 9 owner withdraw fee leaves 958 for the user
+    {
+        let (_, _, token_b_key, mut token_b_account, pool_key, mut pool_account) =
+            accounts.setup_token_accounts(&user_key, &withdrawer_key, 0, 0, initial_pool);
+        assert_eq!(
+            Err(SwapError::ExceededSlippage.into()),
+            accounts.withdraw_one_token(
+                &withdrawer_key,
+                &token_b_vault_key,
+                &token_b_fees_vault_key,
+                &pool_key,
+                &mut pool_account,
+                &token_b_key,
+                &mut token_b_account,
+                withdraw_amount,
+                959,
+                AorB::B,
+            )
+        );
+    }
+
+    // correctly withdraw token B
+    {
+        let (_, _, token_b_key, mut token_b_account, pool_key, mut pool_account) =
+            accounts.setup_token_accounts(&user_key, &withdrawer_key, 0, 0, initial_pool);
+        // the pool token supply is 3e9 after the previous mints
+        // 10_000 * (1 - (1 - 1e8 / 3e9)^2) = 655, with 3 + 3 trade fees charged on half
+        // and 6 owner withdraw fee on the remaining 649
+        accounts
+            .withdraw_one_token(
+                &withdrawer_key,
+                &token_b_vault_key,
+                &token_b_fees_vault_key,
+                &pool_key,
+                &mut pool_account,
+                &token_b_key,
+                &mut token_b_account,
+                withdraw_amount,
+                643,
+                AorB::B,
+            )
+            .unwrap();
+
+        let swap_token_b =
+            StateWithExtensions::<Account>::unpack(&accounts.token_b_vault_account.data).unwrap();
+        assert_eq!(swap_token_b.base.amount, token_b_amount - 643 - 9);
+        let token_b_fees =
+            StateWithExtensions::<Account>::unpack(&accounts.token_b_fees_vault_account.data)
+                .unwrap();
+        assert_eq!(token_b_fees.base.amount, 9);
+        let token_b = StateWithExtensions::<Account>::unpack(&token_b_account.data).unwrap();
+        assert_eq!(token_b.base.amount, 643);
+        let pool_account = StateWithExtensions::<Account>::unpack(&pool_account.data).unwrap();
+        assert_eq!(pool_account.base.amount, initial_pool - withdraw_amount);
+        let pool_mint =
+            StateWithExtensions::<Mint>::unpack(&accounts.pool_token_mint_account.data).unwrap();
+        assert_eq!(pool_mint.base.supply, 3 * initial_pool - withdraw_amount);
+    }
+}
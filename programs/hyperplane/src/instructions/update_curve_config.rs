@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    curve::{base::CurveType, calculator::CurveCalculator},
+    emitted,
+    error::SwapError,
+    event,
+    state::{
+        ConstantPriceCurve, OffsetCurve, OraclePriceCurve, SwapPool, SwapState,
+        UpdateCurveConfigMode, UpdateCurveConfigValue,
+    },
+    VALUE_BYTE_ARRAY_LEN,
+};
+
+pub fn handler_update_curve_config(
+    ctx: Context<UpdateCurveConfig>,
+    mode: u16,
+    value: &[u8; VALUE_BYTE_ARRAY_LEN],
+) -> Result<event::UpdateCurveConfig> {
+    let pool = ctx.accounts.pool.load()?;
+    let swap_curve = &ctx.accounts.swap_curve;
+
+    let mode = UpdateCurveConfigMode::try_from(mode)
+        .map_err(|_| error!(ErrorCode::InstructionDidNotDeserialize))?;
+    let value = UpdateCurveConfigValue::from_u64_bytes(value)?;
+    let UpdateCurveConfigValue::U64(new_value) = value;
+
+    let previous_value = match (pool.curve_type(), mode) {
+        (CurveType::ConstantPrice, UpdateCurveConfigMode::TokenBPrice) => {
+            utils::update_curve(swap_curve, |curve: &mut ConstantPriceCurve| {
+                std::mem::replace(&mut curve.token_b_price, new_value)
+            })?
+        }
+        (CurveType::Offset, UpdateCurveConfigMode::TokenBOffset) => {
+            utils::update_curve(swap_curve, |curve: &mut OffsetCurve| {
+                std::mem::replace(&mut curve.token_b_offset, new_value)
+            })?
+        }
+        (CurveType::OraclePrice, UpdateCurveConfigMode::MaxPriceAgeSec) => {
+            utils::update_curve(swap_curve, |curve: &mut OraclePriceCurve| {
+                std::mem::replace(&mut curve.max_price_age_sec, new_value)
+            })?
+        }
+        (CurveType::OraclePrice, UpdateCurveConfigMode::MaxPriceConfidenceBps) => {
+            utils::update_curve(swap_curve, |curve: &mut OraclePriceCurve| {
+                std::mem::replace(&mut curve.max_price_confidence_bps, new_value)
+            })?
+        }
+        (curve_type, mode) => {
+            msg!(
+                "UnsupportedCurveOperation: mode {:?} cannot be updated on curve type {:?}",
+                mode,
+                curve_type
+            );
+            return err!(SwapError::UnsupportedCurveOperation);
+        }
+    };
+    msg!(
+        "Updated curve config {:?}: {} -> {}",
+        mode,
+        previous_value,
+        new_value
+    );
+
+    emitted!(event::UpdateCurveConfig {
+        mode,
+        previous_value: UpdateCurveConfigValue::U64(previous_value),
+        value: UpdateCurveConfigValue::U64(new_value),
+    });
+}
+
+#[derive(Accounts)]
+pub struct UpdateCurveConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        has_one = admin,
+        has_one = swap_curve,
+    )]
+    pub pool: AccountLoader<'info, SwapPool>,
+
+    /// CHECK: has_one constraint on the pool, owner and discriminator checked in the handler
+    #[account(mut)]
+    pub swap_curve: UncheckedAccount<'info>,
+}
+
+mod utils {
+    use anchor_lang::Discriminator;
+
+    use super::*;
+    use crate::utils::instructions::deserialize;
+
+    /// Apply `update` to the curve account, validating the updated curve before
+    /// rewriting the account. Returns the value returned by `update`
+    pub fn update_curve<T, F>(swap_curve: &AccountInfo, update: F) -> Result<u64>
+    where
+        T: CurveCalculator + AccountDeserialize + Discriminator,
+        F: FnOnce(&mut T) -> u64,
+    {
+        let mut curve = deserialize::<T>(swap_curve)?;
+        let previous_value = update(&mut curve);
+        curve.validate()?;
+        curve.try_dyn_serialize(swap_curve.try_borrow_mut_data()?)?;
+        Ok(previous_value)
+    }
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    dynamic_fee::DynamicFeeParameters,
+    emitted, event,
+    state::{DynamicFee, GlobalConfig, SwapPool},
+    utils::seeds,
+};
+
+pub fn handler_update_dynamic_fee(
+    ctx: Context<UpdateDynamicFee>,
+    parameters: Option<DynamicFeeParameters>,
+) -> Result<event::UpdateDynamicFee> {
+    let pool = &mut ctx.accounts.pool.load_mut()?;
+    let previous_parameters = pool.dynamic_fee.parameters();
+
+    let dynamic_fee = match parameters {
+        Some(parameters) => {
+            parameters.validate()?;
+            let dynamic_fee = DynamicFee::new(&parameters);
+            // the pool fees with the minimum dynamic trade fee must satisfy the program constraints
+            let fees = dynamic_fee.fees(&pool.fees)?;
+            let global_config = ctx.accounts.global_config.load()?;
+            global_config.validate_fees(&fees)?;
+            fees.validate()?;
+            dynamic_fee
+        }
+        None => DynamicFee::default(),
+    };
+
+    msg!(
+        "Updating dynamic fee: previous_parameters={:?}, parameters={:?}",
+        previous_parameters,
+        parameters
+    );
+    pool.dynamic_fee = dynamic_fee;
+
+    emitted!(event::UpdateDynamicFee {
+        previous_parameters,
+        parameters,
+    });
+}
+
+#[derive(Accounts)]
+pub struct UpdateDynamicFee<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut,
+        has_one = admin,
+    )]
+    pub pool: AccountLoader<'info, SwapPool>,
+
+    /// Program wide constraints the minimum dynamic trade fee is validated against
+    #[account(
+        seeds = [seeds::GLOBAL_CONFIG],
+        bump,
+    )]
+    pub global_config: AccountLoader<'info, GlobalConfig>,
+}
@@ -0,0 +1,184 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    curve::fees::validate_fraction,
+    emitted, event, set_config,
+    state::{GlobalConfig, UpdateGlobalConfigMode, UpdateGlobalConfigValue},
+    utils::seeds,
+    VALUE_BYTE_ARRAY_LEN,
+};
+
+pub fn handler_update_global_config(
+    ctx: Context<UpdateGlobalConfig>,
+    mode: u16,
+    value: &[u8; VALUE_BYTE_ARRAY_LEN],
+) -> Result<event::UpdateGlobalConfig> {
+    let global_config = &mut ctx.accounts.global_config.load_mut()?;
+
+    let mode = UpdateGlobalConfigMode::try_from(mode)
+        .map_err(|_| error!(ErrorCode::InstructionDidNotDeserialize))?;
+
+    let (previous_value, value) = match mode {
+        UpdateGlobalConfigMode::Owner => {
+            let previous_value = UpdateGlobalConfigValue::Pubkey(global_config.owner);
+            let value = UpdateGlobalConfigValue::from_pubkey_bytes(value)?;
+            if let UpdateGlobalConfigValue::Pubkey(owner) = value {
+                set_config!(global_config, owner, owner);
+            }
+            (previous_value, value)
+        }
+        UpdateGlobalConfigMode::ProtocolAuthority => {
+            let previous_value = UpdateGlobalConfigValue::Pubkey(global_config.protocol_authority);
+            let value = UpdateGlobalConfigValue::from_pubkey_bytes(value)?;
+            if let UpdateGlobalConfigValue::Pubkey(protocol_authority) = value {
+                set_config!(global_config, protocol_authority, protocol_authority);
+            }
+            (previous_value, value)
+        }
+        UpdateGlobalConfigMode::MinTradeFee
+        | UpdateGlobalConfigMode::MinOwnerTradeFee
+        | UpdateGlobalConfigMode::MinOwnerWithdrawFee
+        | UpdateGlobalConfigMode::MinHostFee => {
+            let value = UpdateGlobalConfigValue::from_fee_bytes(value)?;
+            let previous_value = utils::update_min_fee(global_config, mode, &value)?;
+            (previous_value, value)
+        }
+        UpdateGlobalConfigMode::ValidCurveTypes => {
+            let previous_value =
+                UpdateGlobalConfigValue::CurveTypes(global_config.valid_curve_types().collect());
+            let value = UpdateGlobalConfigValue::from_curve_types_bytes(value)?;
+            if let UpdateGlobalConfigValue::CurveTypes(curve_types) = &value {
+                msg!(
+                    "Setting global config valid_curve_types -> {:?}",
+                    curve_types
+                );
+                global_config.set_valid_curve_types(curve_types)?;
+            }
+            (previous_value, value)
+        }
+        UpdateGlobalConfigMode::BlockedTokenExtensions => {
+            let previous_value = UpdateGlobalConfigValue::TokenExtensions(
+                global_config.blocked_token_extensions().collect(),
+            );
+            let value = UpdateGlobalConfigValue::from_token_extensions_bytes(value)?;
+            if let UpdateGlobalConfigValue::TokenExtensions(extensions) = &value {
+                msg!(
+                    "Setting global config blocked_token_extensions -> {:?}",
+                    extensions
+                );
+                global_config.set_blocked_token_extensions(extensions)?;
+            }
+            (previous_value, value)
+        }
+        UpdateGlobalConfigMode::AllowedTokenExtensions => {
+            let previous_value = UpdateGlobalConfigValue::TokenExtensions(
+                global_config.allowed_token_extensions().collect(),
+            );
+            let value = UpdateGlobalConfigValue::from_token_extensions_bytes(value)?;
+            if let UpdateGlobalConfigValue::TokenExtensions(extensions) = &value {
+                msg!(
+                    "Setting global config allowed_token_extensions -> {:?}",
+                    extensions
+                );
+                global_config.set_allowed_token_extensions(extensions)?;
+            }
+            (previous_value, value)
+        }
+        UpdateGlobalConfigMode::ProtocolFee => {
+            let previous_value = UpdateGlobalConfigValue::Fee {
+                numerator: global_config.protocol_fee_numerator,
+                denominator: global_config.protocol_fee_denominator,
+            };
+            let value = UpdateGlobalConfigValue::from_fee_bytes(value)?;
+            if let UpdateGlobalConfigValue::Fee {
+                numerator,
+                denominator,
+            } = value
+            {
+                validate_fraction(numerator, denominator)?;
+                set_config!(global_config, protocol_fee_numerator, numerator);
+                set_config!(global_config, protocol_fee_denominator, denominator);
+            }
+            (previous_value, value)
+        }
+    };
+
+    emitted!(event::UpdateGlobalConfig {
+        mode,
+        previous_value: previous_value.clone(),
+        value: value.clone()
+    });
+}
+
+#[derive(Accounts)]
+pub struct UpdateGlobalConfig<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut,
+        seeds = [seeds::GLOBAL_CONFIG],
+        bump,
+        has_one = owner,
+    )]
+    pub global_config: AccountLoader<'info, GlobalConfig>,
+}
+
+mod utils {
+    use super::*;
+
+    /// Set the minimum fee fraction selected by `mode`. Returns the previous fee value
+    pub fn update_min_fee(
+        global_config: &mut GlobalConfig,
+        mode: UpdateGlobalConfigMode,
+        value: &UpdateGlobalConfigValue,
+    ) -> Result<UpdateGlobalConfigValue> {
+        let (numerator, denominator) = match *value {
+            UpdateGlobalConfigValue::Fee {
+                numerator,
+                denominator,
+            } => (numerator, denominator),
+            _ => {
+                return err!(ErrorCode::InstructionDidNotDeserialize);
+            }
+        };
+
+        let mut min_fees = global_config.min_fees;
+        let (fee_numerator, fee_denominator) = match mode {
+            UpdateGlobalConfigMode::MinTradeFee => (
+                &mut min_fees.trade_fee_numerator,
+                &mut min_fees.trade_fee_denominator,
+            ),
+            UpdateGlobalConfigMode::MinOwnerTradeFee => (
+                &mut min_fees.owner_trade_fee_numerator,
+                &mut min_fees.owner_trade_fee_denominator,
+            ),
+            UpdateGlobalConfigMode::MinOwnerWithdrawFee => (
+                &mut min_fees.owner_withdraw_fee_numerator,
+                &mut min_fees.owner_withdraw_fee_denominator,
+            ),
+            UpdateGlobalConfigMode::MinHostFee => (
+                &mut min_fees.host_fee_numerator,
+                &mut min_fees.host_fee_denominator,
+            ),
+            UpdateGlobalConfigMode::Owner
+            | UpdateGlobalConfigMode::ValidCurveTypes
+            | UpdateGlobalConfigMode::BlockedTokenExtensions
+            | UpdateGlobalConfigMode::AllowedTokenExtensions
+            | UpdateGlobalConfigMode::ProtocolAuthority
+            | UpdateGlobalConfigMode::ProtocolFee => {
+                return err!(ErrorCode::InstructionDidNotDeserialize);
+            }
+        };
+        let previous_value = UpdateGlobalConfigValue::Fee {
+            numerator: *fee_numerator,
+            denominator: *fee_denominator,
+        };
+        *fee_numerator = numerator;
+        *fee_denominator = denominator;
+        min_fees.validate()?;
+
+        let new_min_fees = &min_fees;
+        set_config!(global_config, min_fees, &new_min_fees);
+
+        Ok(previous_value)
+    }
+}
@@ -2,10 +2,13 @@ use anchor_lang::prelude::*;
 
 use crate::{
     emitted, event, set_config,
-    state::{SwapPool, UpdatePoolConfigMode, UpdatePoolConfigValue},
+    state::{
+        DynamicFee, GlobalConfig, SwapPool, SwapState, UpdatePoolConfigMode, UpdatePoolConfigValue,
+    },
+    utils::seeds,
 };
 
-pub const VALUE_BYTE_ARRAY_LEN: usize = 32;
+pub use crate::state::VALUE_BYTE_ARRAY_LEN;
 
 pub fn handler_update_pool_config(
     ctx: Context<UpdatePoolConfig>,
@@ -17,17 +20,28 @@ pub fn handler_update_pool_config(
     let mode = UpdatePoolConfigMode::try_from(mode)
         .map_err(|_| error!(ErrorCode::InstructionDidNotDeserialize))?;
 
-    let value = match mode {
+    let (previous_value, value) = match mode {
         UpdatePoolConfigMode::WithdrawalsOnly => {
+            let previous_value = UpdatePoolConfigValue::Bool(pool.withdrawals_only());
             let value = UpdatePoolConfigValue::from_bool_bytes(value)?;
-            let packed_value = value.to_u64();
+            let packed_value = u64::from(value == UpdatePoolConfigValue::Bool(true));
             set_config!(pool, withdrawals_only, packed_value);
-            value
+            (previous_value, value)
+        }
+        UpdatePoolConfigMode::TradeFee
+        | UpdatePoolConfigMode::OwnerTradeFee
+        | UpdatePoolConfigMode::OwnerWithdrawFee
+        | UpdatePoolConfigMode::HostFee => {
+            let value = UpdatePoolConfigValue::from_fee_bytes(value)?;
+            let global_config = ctx.accounts.global_config.load()?;
+            let previous_value = utils::update_fee(pool, &global_config, mode, &value)?;
+            (previous_value, value)
         }
     };
 
     emitted!(event::UpdatePoolConfig {
         mode,
+        previous_value: previous_value.clone(),
         value: value.clone()
     });
 }
@@ -41,9 +55,79 @@ pub struct UpdatePoolConfig<'info> {
         has_one = admin,
     )]
     pub pool: AccountLoader<'info, SwapPool>,
+
+    /// Program wide constraints the updated fees are validated against
+    #[account(
+        seeds = [seeds::GLOBAL_CONFIG],
+        bump,
+    )]
+    pub global_config: AccountLoader<'info, GlobalConfig>,
 }
 
 mod utils {
+    use super::*;
+
+    /// Set the fee fraction selected by `mode`, validating the resulting fees
+    /// against the program constraints. Returns the previous fee value
+    pub fn update_fee(
+        pool: &mut SwapPool,
+        global_config: &GlobalConfig,
+        mode: UpdatePoolConfigMode,
+        value: &UpdatePoolConfigValue,
+    ) -> Result<UpdatePoolConfigValue> {
+        let (numerator, denominator) = match *value {
+            UpdatePoolConfigValue::Fee {
+                numerator,
+                denominator,
+            } => (numerator, denominator),
+            UpdatePoolConfigValue::Bool(_) => {
+                return err!(ErrorCode::InstructionDidNotDeserialize);
+            }
+        };
+
+        let mut fees = pool.fees;
+        let (fee_numerator, fee_denominator) = match mode {
+            UpdatePoolConfigMode::TradeFee => (
+                &mut fees.trade_fee_numerator,
+                &mut fees.trade_fee_denominator,
+            ),
+            UpdatePoolConfigMode::OwnerTradeFee => (
+                &mut fees.owner_trade_fee_numerator,
+                &mut fees.owner_trade_fee_denominator,
+            ),
+            UpdatePoolConfigMode::OwnerWithdrawFee => (
+                &mut fees.owner_withdraw_fee_numerator,
+                &mut fees.owner_withdraw_fee_denominator,
+            ),
+            UpdatePoolConfigMode::HostFee => {
+                (&mut fees.host_fee_numerator, &mut fees.host_fee_denominator)
+            }
+            UpdatePoolConfigMode::WithdrawalsOnly => {
+                return err!(ErrorCode::InstructionDidNotDeserialize);
+            }
+        };
+        let previous_value = UpdatePoolConfigValue::Fee {
+            numerator: *fee_numerator,
+            denominator: *fee_denominator,
+        };
+        *fee_numerator = numerator;
+        *fee_denominator = denominator;
+
+        global_config.validate_fees(&fees)?;
+        fees.validate()?;
+        // the fees charged with an enabled dynamic fee, at its minimum trade fee, must also
+        // satisfy the program constraints
+        if let Some(parameters) = pool.dynamic_fee.parameters() {
+            let dynamic_fees = DynamicFee::new(&parameters).fees(&fees)?;
+            global_config.validate_fees(&dynamic_fees)?;
+            dynamic_fees.validate()?;
+        }
+
+        let new_fees = &fees;
+        set_config!(pool, fees, &new_fees);
+
+        Ok(previous_value)
+    }
 
     #[macro_export]
     macro_rules! set_config {
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    emitted,
+    error::SwapError,
+    event,
+    state::{StableCurve, SwapPool},
+};
+
+pub fn handler_update_rate(
+    ctx: Context<UpdateRate>,
+    token_b_rate: u64,
+) -> Result<event::UpdateRate> {
+    let now = u64::try_from(Clock::get()?.unix_timestamp)
+        .map_err(|_| error!(SwapError::ConversionFailure))?;
+    let swap_curve = &mut ctx.accounts.swap_curve;
+    let previous_rate = swap_curve.token_b_rate;
+
+    msg!(
+        "Updating rate: previous_rate={}, token_b_rate={}, now={}",
+        previous_rate,
+        token_b_rate,
+        now
+    );
+    swap_curve.update_rate(&ctx.accounts.rate_updater.key(), token_b_rate, now)?;
+
+    emitted!(event::UpdateRate {
+        previous_rate,
+        token_b_rate,
+        rate_updated_ts: now,
+    });
+}
+
+#[derive(Accounts)]
+pub struct UpdateRate<'info> {
+    /// Rate updater authority of the swap curve, checked in the handler
+    pub rate_updater: Signer<'info>,
+
+    #[account(has_one = swap_curve)]
+    pub pool: AccountLoader<'info, SwapPool>,
+
+    #[account(mut)]
+    pub swap_curve: Account<'info, StableCurve>,
+}
@@ -6,27 +6,23 @@ use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 use crate::{
     curve,
-    curve::{
-        base::SwapCurve,
-        calculator::{AorB, RoundDirection},
-    },
+    curve::base::SwapCurve,
     emitted,
     error::SwapError,
     event, require_msg,
     state::{SwapPool, SwapState},
-    to_u64, try_math,
-    utils::{math::TryMath, pool_token, swap_token},
+    swap::utils as swap_utils,
+    utils::{pool_token, pool_transfers::LiquidityParams, swap_token},
     withdraw::utils::validate_inputs,
 };
 
-// todo - elliot token2022 transfer fees
-pub fn handler_withdraw(
-    ctx: Context<Withdraw>,
+pub fn handler_withdraw<'info>(
+    ctx: Context<'_, '_, '_, 'info, Withdraw<'info>>,
     pool_token_amount: u64,
     minimum_token_a_amount: u64,
     minimum_token_b_amount: u64,
 ) -> Result<event::Withdraw> {
-    let pool = ctx.accounts.pool.load()?;
+    let mut pool = ctx.accounts.pool.load_mut()?;
     validate_inputs(&ctx, &pool)?;
     msg!(
         "Withdraw inputs: minimum_token_a_amount={}, minimum_token_b_amount={}, pool_token_amount={}",
@@ -35,9 +31,19 @@ pub fn handler_withdraw(
         pool_token_amount,
     );
 
-    let swap_curve = curve!(ctx.accounts.swap_curve, pool);
-    let calculator = &swap_curve.calculator;
-
+    let swap_curve = curve!(ctx.accounts.swap_curve, pool).with_pool_interest_bearing_mints(
+        &pool,
+        &[
+            &ctx.accounts.token_a_mint.to_account_info(),
+            &ctx.accounts.token_b_mint.to_account_info(),
+        ],
+        ctx.remaining_accounts,
+    )?;
+    pool.price_accumulator.update_best_effort(
+        swap_curve.calculator.as_ref(),
+        ctx.accounts.token_a_vault.amount,
+        ctx.accounts.token_b_vault.amount,
+    );
     msg!(
         "Swap pool inputs: swap_type={:?}, token_a_balance={}, token_b_balance={}, pool_token_supply={}",
         swap_curve.curve_type,
@@ -45,45 +51,50 @@ pub fn handler_withdraw(
         ctx.accounts.token_b_vault.amount,
         ctx.accounts.pool_token_mint.supply,
     );
-    require_msg!(
-        pool_token_amount > 0,
-        SwapError::ZeroTradingTokens,
-        "ZeroTradingTokens: pool_token_amount=0"
-    );
-
-    let results = calculator
-        .pool_tokens_to_trading_tokens(
-            u128::from(pool_token_amount),
-            u128::from(ctx.accounts.pool_token_mint.supply),
-            u128::from(ctx.accounts.token_a_vault.amount),
-            u128::from(ctx.accounts.token_b_vault.amount),
-            RoundDirection::Floor,
-        )
-        .map_err(|_| error!(SwapError::ZeroTradingTokens))?;
-
-    let (token_a_after_fee, token_a_fees) = utils::sub_withdraw_fee(
-        &pool,
-        ctx.accounts.token_a_vault.amount,
-        results.token_a_amount,
-        minimum_token_a_amount,
-        AorB::A,
-    )?;
-    let (token_b_after_fee, token_b_fees) = utils::sub_withdraw_fee(
-        &pool,
-        ctx.accounts.token_b_vault.amount,
-        results.token_b_amount,
-        minimum_token_b_amount,
-        AorB::B,
-    )?;
+    let transfers = LiquidityParams {
+        calculator: swap_curve.calculator.as_ref(),
+        fees: pool.fees(),
+        token_a_vault_amount: ctx.accounts.token_a_vault.amount,
+        token_b_vault_amount: ctx.accounts.token_b_vault.amount,
+        pool_token_supply: ctx.accounts.pool_token_mint.supply,
+        token_a_transfer_fee: swap_utils::mint_transfer_fee(
+            &ctx.accounts.token_a_mint.to_account_info(),
+        )?,
+        token_b_transfer_fee: swap_utils::mint_transfer_fee(
+            &ctx.accounts.token_b_mint.to_account_info(),
+        )?,
+    }
+    .withdraw(pool_token_amount)?;
+    let token_a_after_fee = transfers.token_a_amount;
+    let token_b_after_fee = transfers.token_b_amount;
+    let token_a_fees = transfers.token_a_fees;
+    let token_b_fees = transfers.token_b_fees;
 
     msg!(
-        "Withdraw outputs: token_a_to_receive={}, token_b_to_receive={}, pool_tokens_to_burn={}",
+        "Withdraw outputs: token_a_from_vault={}, token_b_from_vault={}, token_a_to_receive={}, token_b_to_receive={}, pool_tokens_to_burn={}",
         token_a_after_fee,
         token_b_after_fee,
+        transfers.token_a_amount_received,
+        transfers.token_b_amount_received,
         pool_token_amount,
     );
+    require_msg!(
+        transfers.token_a_amount_received >= minimum_token_a_amount,
+        SwapError::ExceededSlippage,
+        &format!(
+            "ExceededSlippage: token=A amount_received={} < minimum_withdraw_amount={}",
+            transfers.token_a_amount_received, minimum_token_a_amount
+        )
+    );
+    require_msg!(
+        transfers.token_b_amount_received >= minimum_token_b_amount,
+        SwapError::ExceededSlippage,
+        &format!(
+            "ExceededSlippage: token=B amount_received={} < minimum_withdraw_amount={}",
+            transfers.token_b_amount_received, minimum_token_b_amount
+        )
+    );
 
-    let pool_token_amount = to_u64!(pool_token_amount)?;
     pool_token::burn(
         ctx.accounts.pool_token_mint.to_account_info(),
         ctx.accounts.pool_token_user_ata.to_account_info(),
@@ -100,6 +111,7 @@ pub fn handler_withdraw(
             ctx.accounts.token_a_mint.to_account_info(),
             ctx.accounts.token_a_user_ata.to_account_info(),
             ctx.accounts.pool_authority.to_account_info(),
+            ctx.remaining_accounts,
             pool.bump_seed(),
             token_a_after_fee,
             ctx.accounts.token_a_mint.decimals,
@@ -113,6 +125,7 @@ pub fn handler_withdraw(
             ctx.accounts.token_b_mint.to_account_info(),
             ctx.accounts.token_b_user_ata.to_account_info(),
             ctx.accounts.pool_authority.to_account_info(),
+            ctx.remaining_accounts,
             pool.bump_seed(),
             token_b_after_fee,
             ctx.accounts.token_b_mint.decimals,
@@ -126,6 +139,7 @@ pub fn handler_withdraw(
             ctx.accounts.token_a_mint.to_account_info(),
             ctx.accounts.token_a_fees_vault.to_account_info(),
             ctx.accounts.pool_authority.to_account_info(),
+            ctx.remaining_accounts,
             pool.bump_seed(),
             token_a_fees,
             ctx.accounts.token_a_mint.decimals,
@@ -139,6 +153,7 @@ pub fn handler_withdraw(
             ctx.accounts.token_b_mint.to_account_info(),
             ctx.accounts.token_b_fees_vault.to_account_info(),
             ctx.accounts.pool_authority.to_account_info(),
+            ctx.remaining_accounts,
             pool.bump_seed(),
             token_b_fees,
             ctx.accounts.token_b_mint.decimals,
@@ -146,11 +161,11 @@ pub fn handler_withdraw(
     }
 
     emitted!(event::Withdraw {
-        token_a_amount: token_a_after_fee,
-        token_b_amount: token_b_after_fee,
+        token_a_amount: transfers.token_a_amount_received,
+        token_b_amount: transfers.token_b_amount_received,
         pool_token_amount,
-        token_a_fees,
-        token_b_fees,
+        token_a_fees: transfers.token_a_fees_received,
+        token_b_fees: transfers.token_b_fees_received,
     });
 }
 
@@ -244,12 +259,9 @@ pub struct Withdraw<'info> {
 }
 
 mod utils {
-    use std::cell::Ref;
-
     use super::*;
-    use crate::curve::calculator::AorB;
 
-    pub fn validate_inputs(ctx: &Context<Withdraw>, pool: &Ref<SwapPool>) -> Result<()> {
+    pub fn validate_inputs(ctx: &Context<Withdraw>, pool: &SwapPool) -> Result<()> {
         require_msg!(
             pool.token_a_vault != ctx.accounts.token_a_user_ata.key(),
             SwapError::IncorrectSwapAccount,
@@ -270,44 +282,4 @@ mod utils {
         );
         Ok(())
     }
-
-    pub fn sub_withdraw_fee(
-        pool: &Ref<SwapPool>,
-        pool_balance: u64,
-        withdraw_amount: u128,
-        minimum_withdraw_amount: u64,
-        a_or_b: AorB,
-    ) -> Result<(u64, u64)> {
-        let withdraw_amount = std::cmp::min(u128::from(pool_balance), withdraw_amount);
-
-        let token_withdraw_fee = pool
-            .fees()
-            .owner_withdraw_fee(withdraw_amount)
-            .map_err(|_| error!(SwapError::FeeCalculationFailure))?;
-        let amount_after_fee = try_math!(withdraw_amount.try_sub(token_withdraw_fee))?;
-
-        let amount_after_fee = to_u64!(amount_after_fee)?;
-        let withdraw_fee = to_u64!(token_withdraw_fee)?;
-
-        msg!(
-            "Token {:?} withdrawal fee: fee={}, amount_after_fee={}",
-            a_or_b,
-            withdraw_fee,
-            amount_after_fee
-        );
-        require_msg!(
-            amount_after_fee >= minimum_withdraw_amount,
-            SwapError::ExceededSlippage,
-            &format!(
-                "ExceededSlippage: token={:?} amount_after_fee={} < minimum_withdraw_amount={}",
-                a_or_b, amount_after_fee, minimum_withdraw_amount
-            )
-        );
-        require!(
-            amount_after_fee > 0 || pool_balance == 0,
-            SwapError::ZeroTradingTokens
-        );
-
-        Ok((amount_after_fee, withdraw_fee))
-    }
 }
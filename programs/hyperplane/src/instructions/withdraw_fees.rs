@@ -15,8 +15,8 @@ use crate::{
     withdraw_fees::utils::validate_inputs,
 };
 
-pub fn handler_withdraw_fees(
-    ctx: Context<WithdrawFees>,
+pub fn handler_withdraw_fees<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawFees<'info>>,
     requested_withdraw_amount: u64,
 ) -> Result<event::WithdrawFees> {
     let pool = ctx.accounts.pool.load()?;
@@ -43,6 +43,7 @@ pub fn handler_withdraw_fees(
         ctx.accounts.fees_mint.to_account_info(),
         ctx.accounts.admin_fees_ata.to_account_info(),
         ctx.accounts.pool_authority.to_account_info(),
+        ctx.remaining_accounts,
         pool.bump_seed(),
         withdraw_amount,
         ctx.accounts.fees_mint.decimals,
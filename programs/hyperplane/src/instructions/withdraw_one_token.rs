@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    curve,
+    curve::{
+        base::SwapCurve,
+        calculator::{AorB, TradeDirection},
+    },
+    emitted,
+    error::SwapError,
+    event, require_msg,
+    state::SwapState,
+    swap::utils as swap_utils,
+    to_u64,
+    withdraw_single_token_type_exact_amount_out::utils::{transfer_withdrawal, validate_inputs},
+    WithdrawSingleTokenType,
+};
+
+pub fn handler_withdraw_one_token<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawSingleTokenType<'info>>,
+    pool_token_amount: u64,
+    minimum_token_amount: u64,
+    a_or_b: AorB,
+) -> Result<event::WithdrawSingleTokenType> {
+    let mut pool = ctx.accounts.pool.load_mut()?;
+    let trade_direction = validate_inputs(&ctx, &pool)?;
+    let expected_trade_direction = match a_or_b {
+        AorB::A => TradeDirection::AtoB,
+        AorB::B => TradeDirection::BtoA,
+    };
+    require_msg!(
+        trade_direction == expected_trade_direction,
+        SwapError::IncorrectTradingMint,
+        &format!(
+            "IncorrectTradingMint: destination_mint.key ({}) is not the pool token {:?} mint",
+            ctx.accounts.destination_mint.key(),
+            a_or_b
+        )
+    );
+    let swap_curve = curve!(ctx.accounts.swap_curve, pool, ctx.remaining_accounts)
+        .with_pool_interest_bearing_mints(
+            &pool,
+            &[&ctx.accounts.destination_mint.to_account_info()],
+            ctx.remaining_accounts,
+        )?;
+    pool.price_accumulator.update(
+        swap_curve.calculator.as_ref(),
+        ctx.accounts.token_a_vault.amount,
+        ctx.accounts.token_b_vault.amount,
+    )?;
+    pool.dynamic_fee.update(
+        swap_curve.calculator.as_ref(),
+        ctx.accounts.token_a_vault.amount,
+        ctx.accounts.token_b_vault.amount,
+    )?;
+    let fees = pool.dynamic_fee.fees(&pool.fees)?;
+
+    msg!(
+        "Withdraw one token inputs: a_or_b={:?}, pool_token_amount={}, minimum_token_amount={}",
+        a_or_b,
+        pool_token_amount,
+        minimum_token_amount,
+    );
+    msg!(
+        "Swap pool inputs: swap_type={:?}, token_a_balance={}, token_b_balance={}, pool_token_supply={}",
+        swap_curve.curve_type,
+        ctx.accounts.token_a_vault.amount,
+        ctx.accounts.token_b_vault.amount,
+        ctx.accounts.pool_token_mint.supply,
+    );
+    require_msg!(
+        pool_token_amount > 0,
+        SwapError::ZeroTradingTokens,
+        "ZeroTradingTokens: pool_token_amount=0"
+    );
+    require_msg!(
+        pool_token_amount <= ctx.accounts.pool_token_user_ata.amount,
+        SwapError::InsufficientPoolTokenFunds,
+        &format!(
+            "InsufficientPoolTokenFunds: pool_token_amount={} > pool_token_user_ata.amount={}",
+            pool_token_amount, ctx.accounts.pool_token_user_ata.amount
+        )
+    );
+
+    let result = swap_curve
+        .withdraw_one_token(
+            u128::from(pool_token_amount),
+            u128::from(ctx.accounts.token_a_vault.amount),
+            u128::from(ctx.accounts.token_b_vault.amount),
+            u128::from(ctx.accounts.pool_token_mint.supply),
+            trade_direction,
+            &fees,
+        )
+        .map_err(|_| error!(SwapError::ZeroTradingTokens))?;
+
+    let destination_amount_from_vault = to_u64!(result.destination_amount)?;
+    let destination_amount_post_transfer_fees = swap_utils::sub_transfer_fee(
+        &ctx.accounts.destination_mint.to_account_info(),
+        destination_amount_from_vault,
+    )?;
+    let owner_fee = to_u64!(result.owner_fee)?;
+
+    msg!(
+        "Withdraw one token result: destination_amount_from_vault={}, destination_amount_post_transfer_fees={}, trade_fee={}, owner_fee={}",
+        destination_amount_from_vault,
+        destination_amount_post_transfer_fees,
+        result.trade_fee,
+        owner_fee,
+    );
+    require_msg!(
+        destination_amount_post_transfer_fees >= minimum_token_amount,
+        SwapError::ExceededSlippage,
+        &format!(
+            "ExceededSlippage: amount_received={} < minimum_token_amount={}",
+            destination_amount_post_transfer_fees, minimum_token_amount
+        )
+    );
+    require_msg!(
+        destination_amount_from_vault > 0,
+        SwapError::ZeroTradingTokens,
+        "Amount of tokens being withdrawn is 0"
+    );
+
+    transfer_withdrawal(
+        &ctx,
+        &pool,
+        trade_direction,
+        pool_token_amount,
+        destination_amount_from_vault,
+        owner_fee,
+    )?;
+
+    let total_fees = to_u64!(result.total_fees)?;
+
+    msg!(
+        "Withdraw one token outputs: token_amount={}, pool_token_amount={}, total_fees={}",
+        destination_amount_from_vault,
+        pool_token_amount,
+        total_fees,
+    );
+    emitted!(event::WithdrawSingleTokenType {
+        token_amount: destination_amount_from_vault,
+        pool_token_amount,
+        total_fees,
+    });
+}
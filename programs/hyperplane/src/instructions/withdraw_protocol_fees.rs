@@ -0,0 +1,135 @@
+use std::cmp;
+
+use anchor_lang::{
+    accounts::{interface::Interface, interface_account::InterfaceAccount},
+    prelude::*,
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    emitted,
+    error::SwapError,
+    event, require_msg,
+    state::{GlobalConfig, SwapPool, SwapState},
+    utils::{seeds, swap_token},
+    withdraw_protocol_fees::utils::validate_inputs,
+};
+
+/// Withdraw the protocol share of the owner trading fees of a pool to the protocol authority
+pub fn handler_withdraw_protocol_fees<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawProtocolFees<'info>>,
+    requested_withdraw_amount: u64,
+) -> Result<event::WithdrawProtocolFees> {
+    let pool = ctx.accounts.pool.load()?;
+    validate_inputs(&ctx, &pool)?;
+
+    require_msg!(
+        requested_withdraw_amount > 0,
+        SwapError::ZeroTradingTokens,
+        "Cannot withdraw zero protocol fees"
+    );
+
+    let withdraw_amount = cmp::min(
+        requested_withdraw_amount,
+        ctx.accounts.protocol_fees_vault.amount,
+    );
+
+    msg!(
+        "Withdrawing from protocol fees vault: withdraw_amount={}, requested_withdraw_amount={}",
+        withdraw_amount,
+        requested_withdraw_amount,
+    );
+
+    swap_token::transfer_from_vault(
+        ctx.accounts.fees_token_program.to_account_info(),
+        ctx.accounts.pool.to_account_info(),
+        ctx.accounts.protocol_fees_vault.to_account_info(),
+        ctx.accounts.fees_mint.to_account_info(),
+        ctx.accounts.protocol_authority_fees_ata.to_account_info(),
+        ctx.accounts.pool_authority.to_account_info(),
+        ctx.remaining_accounts,
+        pool.bump_seed(),
+        withdraw_amount,
+        ctx.accounts.fees_mint.decimals,
+    )?;
+
+    emitted!(event::WithdrawProtocolFees { withdraw_amount });
+}
+
+#[derive(Accounts)]
+pub struct WithdrawProtocolFees<'info> {
+    #[account(mut)]
+    pub protocol_authority: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::GLOBAL_CONFIG],
+        bump,
+        has_one = protocol_authority @ SwapError::InvalidProtocolAuthority,
+    )]
+    pub global_config: AccountLoader<'info, GlobalConfig>,
+
+    #[account(mut,
+        has_one = pool_authority @ SwapError::InvalidProgramAddress,
+    )]
+    pub pool: AccountLoader<'info, SwapPool>,
+
+    /// CHECK: has_one constraint on the pool
+    pub pool_authority: AccountInfo<'info>,
+
+    /// CHECK: checked in the handler
+    #[account(
+        token::token_program = fees_token_program,
+    )]
+    pub fees_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Protocol fee vault to withdraw from
+    /// CHECK: checked in the handler
+    #[account(mut,
+        constraint = protocol_fees_vault.amount > 0 @ SwapError::ZeroTradingTokens,
+        token::token_program = fees_token_program,
+    )]
+    pub protocol_fees_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Protocol authority's token account to withdraw fees to
+    #[account(mut,
+        token::mint = fees_mint,
+        token::authority = protocol_authority,
+        token::token_program = fees_token_program,
+    )]
+    pub protocol_authority_fees_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token program for the fee token mint
+    pub fees_token_program: Interface<'info, TokenInterface>,
+}
+
+mod utils {
+    use std::cell::Ref;
+
+    use super::*;
+    use crate::curve::calculator::AorB;
+
+    pub fn validate_inputs(
+        ctx: &Context<WithdrawProtocolFees>,
+        pool: &Ref<SwapPool>,
+    ) -> Result<()> {
+        let (pool_protocol_fees_vault, a_or_b) =
+            if ctx.accounts.fees_mint.key() == pool.token_a_mint {
+                (pool.token_a_protocol_fees_vault.key(), AorB::A)
+            } else if ctx.accounts.fees_mint.key() == pool.token_b_mint {
+                (pool.token_b_protocol_fees_vault.key(), AorB::B)
+            } else {
+                return err!(SwapError::IncorrectTradingMint);
+            };
+        require_msg!(
+            pool_protocol_fees_vault == ctx.accounts.protocol_fees_vault.key(),
+            SwapError::IncorrectFeeAccount,
+            &format!(
+                "IncorrectFeeAccount: pool_protocol_fees_vault.key ({}) != protocol_fees_vault.key ({}), a_or_b={:?}",
+                pool_protocol_fees_vault,
+                ctx.accounts.protocol_fees_vault.key(),
+                a_or_b,
+            )
+        );
+        Ok(())
+    }
+}
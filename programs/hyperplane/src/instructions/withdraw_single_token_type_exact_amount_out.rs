@@ -0,0 +1,293 @@
+use anchor_lang::{
+    accounts::{interface::Interface, interface_account::InterfaceAccount},
+    prelude::*,
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{
+    curve,
+    curve::base::SwapCurve,
+    emitted,
+    error::SwapError,
+    event, require_msg,
+    state::{SwapPool, SwapState},
+    swap::utils as swap_utils,
+    to_u64,
+    withdraw_single_token_type_exact_amount_out::utils::{transfer_withdrawal, validate_inputs},
+};
+
+pub fn handler_withdraw_single_token_type_exact_amount_out<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawSingleTokenType<'info>>,
+    destination_token_amount: u64,
+    maximum_pool_token_amount: u64,
+) -> Result<event::WithdrawSingleTokenType> {
+    let mut pool = ctx.accounts.pool.load_mut()?;
+    let trade_direction = validate_inputs(&ctx, &pool)?;
+    let swap_curve = curve!(ctx.accounts.swap_curve, pool, ctx.remaining_accounts)
+        .with_pool_interest_bearing_mints(
+            &pool,
+            &[&ctx.accounts.destination_mint.to_account_info()],
+            ctx.remaining_accounts,
+        )?;
+    pool.price_accumulator.update(
+        swap_curve.calculator.as_ref(),
+        ctx.accounts.token_a_vault.amount,
+        ctx.accounts.token_b_vault.amount,
+    )?;
+    pool.dynamic_fee.update(
+        swap_curve.calculator.as_ref(),
+        ctx.accounts.token_a_vault.amount,
+        ctx.accounts.token_b_vault.amount,
+    )?;
+    let fees = pool.dynamic_fee.fees(&pool.fees)?;
+
+    // Take transfer fees into account for the actual amount the user must receive
+    let destination_amount_from_vault = swap_utils::add_inverse_transfer_fee(
+        &ctx.accounts.destination_mint.to_account_info(),
+        destination_token_amount,
+    )?;
+
+    msg!(
+        "Withdraw single token type exact out inputs: trade_direction={:?}, destination_token_amount={}, destination_amount_from_vault={}, maximum_pool_token_amount={}",
+        trade_direction,
+        destination_token_amount,
+        destination_amount_from_vault,
+        maximum_pool_token_amount,
+    );
+    msg!(
+        "Swap pool inputs: swap_type={:?}, token_a_balance={}, token_b_balance={}, pool_token_supply={}",
+        swap_curve.curve_type,
+        ctx.accounts.token_a_vault.amount,
+        ctx.accounts.token_b_vault.amount,
+        ctx.accounts.pool_token_mint.supply,
+    );
+    require_msg!(
+        destination_token_amount > 0,
+        SwapError::ZeroTradingTokens,
+        "ZeroTradingTokens: destination_token_amount=0"
+    );
+
+    let result = swap_curve
+        .withdraw_single_token_type_exact_out(
+            u128::from(destination_amount_from_vault),
+            u128::from(ctx.accounts.token_a_vault.amount),
+            u128::from(ctx.accounts.token_b_vault.amount),
+            u128::from(ctx.accounts.pool_token_mint.supply),
+            trade_direction,
+            &fees,
+        )
+        .map_err(|_| error!(SwapError::ZeroTradingTokens))?;
+
+    let pool_token_amount = to_u64!(result.pool_token_amount)?;
+    let owner_fee = to_u64!(result.owner_fee)?;
+
+    msg!(
+        "Withdraw single token type exact out result: pool_token_amount={}, trade_fee={}, owner_fee={}",
+        pool_token_amount,
+        result.trade_fee,
+        owner_fee,
+    );
+    require_msg!(
+        pool_token_amount <= maximum_pool_token_amount,
+        SwapError::ExceededSlippage,
+        &format!(
+            "ExceededSlippage: pool_token_amount={} > maximum_pool_token_amount={}",
+            pool_token_amount, maximum_pool_token_amount
+        )
+    );
+    require_msg!(
+        pool_token_amount <= ctx.accounts.pool_token_user_ata.amount,
+        SwapError::InsufficientPoolTokenFunds,
+        &format!(
+            "InsufficientPoolTokenFunds: pool_token_amount={} > pool_token_user_ata.amount={}",
+            pool_token_amount, ctx.accounts.pool_token_user_ata.amount
+        )
+    );
+
+    transfer_withdrawal(
+        &ctx,
+        &pool,
+        trade_direction,
+        pool_token_amount,
+        destination_amount_from_vault,
+        owner_fee,
+    )?;
+
+    let total_fees = to_u64!(result.total_fees)?;
+
+    msg!(
+        "Withdraw single token type exact out outputs: token_amount={}, pool_token_amount={}, total_fees={}",
+        destination_amount_from_vault,
+        pool_token_amount,
+        total_fees,
+    );
+    emitted!(event::WithdrawSingleTokenType {
+        token_amount: destination_amount_from_vault,
+        pool_token_amount,
+        total_fees,
+    });
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSingleTokenType<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(mut,
+        has_one = swap_curve,
+        has_one = pool_authority @ SwapError::InvalidProgramAddress,
+        has_one = token_a_vault @ SwapError::IncorrectSwapAccount,
+        has_one = token_b_vault @ SwapError::IncorrectSwapAccount,
+        has_one = pool_token_mint @ SwapError::IncorrectPoolMint,
+    )]
+    pub pool: AccountLoader<'info, SwapPool>,
+
+    /// CHECK: has_one constraint on the pool
+    pub swap_curve: UncheckedAccount<'info>,
+
+    /// CHECK: has_one constraint on the pool
+    pub pool_authority: AccountInfo<'info>,
+
+    /// CHECK: checked in the handler
+    pub destination_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: has_one constraint on the pool
+    #[account(mut)]
+    pub token_a_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: has_one constraint on the pool
+    #[account(mut)]
+    pub token_b_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Account to collect fees into
+    /// CHECK: checked in the handler
+    #[account(mut)]
+    pub destination_token_fees_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: has_one constraint on the pool
+    #[account(mut)]
+    pub pool_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Signer's destination token account
+    #[account(mut,
+        token::mint = destination_mint,
+        token::token_program = destination_token_program,
+    )]
+    pub destination_user_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Signer's pool token account
+    #[account(mut,
+        token::mint = pool_token_mint,
+        token::authority = destination_user_ata.owner,
+        token::token_program = pool_token_program,
+    )]
+    pub pool_token_user_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token program for the pool token mint
+    pub pool_token_program: Interface<'info, TokenInterface>,
+    /// Token program for the destination mint
+    pub destination_token_program: Interface<'info, TokenInterface>,
+}
+
+pub(crate) mod utils {
+    use super::*;
+    use crate::{
+        curve::calculator::TradeDirection,
+        utils::{pool_token, swap_token},
+    };
+
+    pub fn validate_inputs(
+        ctx: &Context<WithdrawSingleTokenType>,
+        pool: &SwapPool,
+    ) -> Result<TradeDirection> {
+        let (trade_direction, destination_vault, destination_token_fees_vault) =
+            if ctx.accounts.destination_mint.key() == pool.token_a_mint {
+                (
+                    TradeDirection::AtoB,
+                    pool.token_a_vault,
+                    pool.token_a_fees_vault,
+                )
+            } else if ctx.accounts.destination_mint.key() == pool.token_b_mint {
+                (
+                    TradeDirection::BtoA,
+                    pool.token_b_vault,
+                    pool.token_b_fees_vault,
+                )
+            } else {
+                return err!(SwapError::IncorrectSwapAccount);
+            };
+
+        require_msg!(
+            ctx.accounts.destination_token_fees_vault.key() == destination_token_fees_vault,
+            SwapError::IncorrectFeeAccount,
+            &format!(
+                "IncorrectFeeAccount: destination_token_fees_vault.key ({}) != fees_vault.key ({})",
+                ctx.accounts.destination_token_fees_vault.key(),
+                destination_token_fees_vault
+            )
+        );
+        require_msg!(
+            ctx.accounts.destination_user_ata.key() != destination_vault,
+            SwapError::IncorrectSwapAccount,
+            &format!(
+                "IncorrectSwapAccount: destination_user_ata.key ({}) == destination_vault.key ({})",
+                ctx.accounts.destination_user_ata.key(),
+                destination_vault
+            )
+        );
+
+        Ok(trade_direction)
+    }
+
+    /// Burn the user's pool tokens, then transfer the withdrawn tokens to the
+    /// user and the owner fees to the fees vault
+    pub fn transfer_withdrawal<'info>(
+        ctx: &Context<'_, '_, '_, 'info, WithdrawSingleTokenType<'info>>,
+        pool: &SwapPool,
+        trade_direction: TradeDirection,
+        pool_token_amount: u64,
+        destination_amount_from_vault: u64,
+        owner_fee: u64,
+    ) -> Result<()> {
+        pool_token::burn(
+            ctx.accounts.pool_token_mint.to_account_info(),
+            ctx.accounts.pool_token_user_ata.to_account_info(),
+            ctx.accounts.signer.to_account_info(),
+            ctx.accounts.pool_token_program.to_account_info(),
+            pool_token_amount,
+        )?;
+
+        let destination_vault = match trade_direction {
+            TradeDirection::AtoB => &ctx.accounts.token_a_vault,
+            TradeDirection::BtoA => &ctx.accounts.token_b_vault,
+        };
+        swap_token::transfer_from_vault(
+            ctx.accounts.destination_token_program.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            destination_vault.to_account_info(),
+            ctx.accounts.destination_mint.to_account_info(),
+            ctx.accounts.destination_user_ata.to_account_info(),
+            ctx.accounts.pool_authority.to_account_info(),
+            ctx.remaining_accounts,
+            pool.bump_seed(),
+            destination_amount_from_vault,
+            ctx.accounts.destination_mint.decimals,
+        )?;
+
+        if owner_fee > 0 {
+            swap_token::transfer_from_vault(
+                ctx.accounts.destination_token_program.to_account_info(),
+                ctx.accounts.pool.to_account_info(),
+                destination_vault.to_account_info(),
+                ctx.accounts.destination_mint.to_account_info(),
+                ctx.accounts.destination_token_fees_vault.to_account_info(),
+                ctx.accounts.pool_authority.to_account_info(),
+                ctx.remaining_accounts,
+                pool.bump_seed(),
+                owner_fee,
+                ctx.accounts.destination_mint.decimals,
+            )?;
+        }
+        Ok(())
+    }
+}
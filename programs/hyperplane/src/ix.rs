@@ -2,24 +2,72 @@
 
 #![allow(clippy::too_many_arguments)]
 
+use std::future::Future;
+
 use anchor_lang::{
     prelude::{Rent, System},
     solana_program::{
-        instruction::Instruction, program_error::ProgramError, pubkey::Pubkey, sysvar::SysvarId,
+        instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::SysvarId,
     },
     Id, InstructionData, ToAccountMetas,
 };
 #[cfg(feature = "fuzz")]
 use arbitrary::Arbitrary;
 use derive_more::Constructor;
+use spl_tlv_account_resolution::state::{
+    AccountDataResult, AccountFetchError, ExtraAccountMetaList,
+};
+use spl_transfer_hook_interface::{
+    get_extra_account_metas_address,
+    instruction::{execute, ExecuteInstruction},
+};
 
 use crate::{
-    curve::fees::Fees,
-    instructions::CurveUserParameters,
-    state::{UpdatePoolConfigMode, UpdatePoolConfigValue},
+    curve::{calculator::AorB, fees::Fees, rate_provider::RateProvider},
+    dynamic_fee::DynamicFeeParameters,
+    instructions::{CurveUserParameters, PoolTokenMetadata},
+    state::{
+        UpdateCurveConfigMode, UpdateCurveConfigValue, UpdateGlobalConfigMode,
+        UpdateGlobalConfigValue, UpdatePoolConfigMode, UpdatePoolConfigValue,
+    },
+    utils::{seeds, token_extensions},
     InitialSupply,
 };
 
+/// InitializeGlobalConfig instruction data
+#[derive(Clone, Debug, PartialEq, Constructor)]
+pub struct InitializeGlobalConfig {
+    /// owner and protocol authority of the global config
+    pub owner: Pubkey,
+    /// minimum fees of every pool
+    pub min_fees: Fees,
+    /// curve types pools can be created with and swapped through
+    pub valid_curve_types: Vec<u64>,
+    /// Token-2022 extensions not allowed on trading token mints
+    pub blocked_token_extensions: Vec<u16>,
+}
+
+/// UpdateGlobalConfig instruction data
+#[derive(Clone, Debug, PartialEq, Constructor)]
+pub struct UpdateGlobalConfig {
+    /// Update mode
+    pub mode: UpdateGlobalConfigMode,
+    /// Value to set
+    pub value: UpdateGlobalConfigValue,
+}
+
+impl From<UpdateGlobalConfig> for crate::instruction::UpdateGlobalConfig {
+    fn from(value: UpdateGlobalConfig) -> Self {
+        crate::instruction::UpdateGlobalConfig {
+            mode: value.mode as u16,
+            value: value.value.to_bytes(),
+        }
+    }
+}
+
 /// Initialize instruction data
 #[derive(Debug, PartialEq, Constructor)]
 pub struct Initialize {
@@ -30,6 +78,8 @@ pub struct Initialize {
     pub curve_parameters: CurveUserParameters,
     /// initial supply of token A and B
     pub initial_supply: InitialSupply,
+    /// token metadata of a token-2022 pool token mint, derived from the trading mints if `None`
+    pub pool_token_metadata: Option<PoolTokenMetadata>,
 }
 
 /// Swap instruction data
@@ -42,6 +92,65 @@ pub struct Swap {
     pub minimum_amount_out: u64,
 }
 
+/// SwapExactOut instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Constructor)]
+pub struct SwapExactOut {
+    /// DESTINATION amount to receive, input from SOURCE is based on the exchange rate
+    pub amount_out: u64,
+    /// Maximum amount of SOURCE token to input, prevents excessive slippage
+    pub maximum_amount_in: u64,
+}
+
+/// RouteSwap instruction data
+#[derive(Clone, Debug, PartialEq, Constructor)]
+pub struct RouteSwap {
+    /// SOURCE amount to transfer into the first pool of the route
+    pub amount_in: u64,
+    /// Minimum amount of DESTINATION token to output from the last pool, prevents excessive slippage
+    pub minimum_amount_out: u64,
+}
+
+/// Accounts of a single hop of a RouteSwap, passed as remaining accounts
+#[derive(Clone, Debug, PartialEq, Constructor)]
+pub struct RouteSwapHop {
+    pub pool: Pubkey,
+    pub swap_curve: Pubkey,
+    pub pool_authority: Pubkey,
+    pub source_vault: Pubkey,
+    pub destination_vault: Pubkey,
+    pub source_token_fees_vault: Pubkey,
+    pub source_token_protocol_fees_vault: Pubkey,
+    pub destination_mint: Pubkey,
+    pub destination_token_program: Pubkey,
+}
+
+/// The protocol fees vault as stored on the pool, none for pools whose protocol fees vaults
+/// have not been initialized
+fn protocol_fees_vault(vault: &Pubkey) -> Option<Pubkey> {
+    (*vault != Pubkey::default()).then_some(*vault)
+}
+
+impl RouteSwapHop {
+    /// Account metas in the order expected by the route_swap instruction
+    pub fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new_readonly(self.swap_curve, false),
+            AccountMeta::new_readonly(self.pool_authority, false),
+            AccountMeta::new(self.source_vault, false),
+            AccountMeta::new(self.destination_vault, false),
+            AccountMeta::new(self.source_token_fees_vault, false),
+            match protocol_fees_vault(&self.source_token_protocol_fees_vault) {
+                Some(vault) => AccountMeta::new(vault, false),
+                None => AccountMeta::new_readonly(crate::ID, false),
+            },
+            AccountMeta::new_readonly(self.destination_mint, false),
+            AccountMeta::new_readonly(self.destination_token_program, false),
+        ]
+    }
+}
+
 /// Deposit instruction data
 #[cfg_attr(feature = "fuzz", derive(Arbitrary))]
 #[derive(Clone, Debug, PartialEq, Constructor)]
@@ -55,6 +164,16 @@ pub struct Deposit {
     pub maximum_token_b_amount: u64,
 }
 
+/// DepositSingleTokenTypeExactAmountIn instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Constructor)]
+pub struct DepositSingleTokenTypeExactAmountIn {
+    /// Token A or B amount to deposit
+    pub source_token_amount: u64,
+    /// Minimum pool token amount to receive, prevents excessive slippage
+    pub minimum_pool_token_amount: u64,
+}
+
 /// Withdraw instruction data
 #[cfg_attr(feature = "fuzz", derive(Arbitrary))]
 #[derive(Clone, Debug, PartialEq, Constructor)]
@@ -68,6 +187,29 @@ pub struct Withdraw {
     pub minimum_token_b_amount: u64,
 }
 
+/// WithdrawSingleTokenTypeExactAmountOut instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Constructor)]
+pub struct WithdrawSingleTokenTypeExactAmountOut {
+    /// Amount of token A or B to receive
+    pub destination_token_amount: u64,
+    /// Maximum amount of pool tokens to burn, prevents excessive slippage
+    pub maximum_pool_token_amount: u64,
+}
+
+/// WithdrawOneToken instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Constructor)]
+pub struct WithdrawOneToken {
+    /// Amount of pool tokens to burn. User receives an output of token a
+    /// or b based on the value of the pool tokens that are returned.
+    pub pool_token_amount: u64,
+    /// Minimum amount of token A or B to receive, prevents excessive slippage
+    pub minimum_token_amount: u64,
+    /// The token to receive
+    pub a_or_b: AorB,
+}
+
 /// WithdrawFees instruction data
 #[derive(Clone, Debug, PartialEq, Constructor)]
 pub struct WithdrawFees {
@@ -75,6 +217,105 @@ pub struct WithdrawFees {
     pub requested_token_amount: u64,
 }
 
+/// WithdrawProtocolFees instruction data
+#[derive(Clone, Debug, PartialEq, Constructor)]
+pub struct WithdrawProtocolFees {
+    /// Amount of trading tokens to withdraw
+    pub requested_token_amount: u64,
+}
+
+/// InitializeMultiStablePool instruction data
+#[derive(Clone, Debug, PartialEq, Constructor)]
+pub struct InitializeMultiStablePool {
+    /// amplifier constant of the stable curve
+    pub amp: u64,
+    /// all swap fees
+    pub fees: Fees,
+    /// initial supply of each token, in pool order
+    pub initial_supplies: Vec<u64>,
+}
+
+/// Accounts of a token of an InitializeMultiStablePool, passed as remaining accounts
+#[derive(Clone, Debug, PartialEq, Constructor)]
+pub struct InitMultiStableToken {
+    pub mint: Pubkey,
+    pub admin_ata: Pubkey,
+    pub token_program: Pubkey,
+}
+
+impl InitMultiStableToken {
+    /// Account metas in the order expected by the initialize_multi_stable_pool instruction
+    pub fn to_account_metas(&self, program_id: &Pubkey, pool: &Pubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(self.mint, false),
+            AccountMeta::new(
+                seeds::pda::token_vault_pda_program_id(program_id, pool, &self.mint).0,
+                false,
+            ),
+            AccountMeta::new(
+                seeds::pda::token_fees_vault_pda_program_id(program_id, pool, &self.mint).0,
+                false,
+            ),
+            AccountMeta::new(
+                seeds::pda::token_protocol_fees_vault_pda_program_id(program_id, pool, &self.mint)
+                    .0,
+                false,
+            ),
+            AccountMeta::new(self.admin_ata, false),
+            AccountMeta::new_readonly(self.token_program, false),
+        ]
+    }
+}
+
+/// MultiStableDeposit instruction data
+#[derive(Clone, Debug, PartialEq, Constructor)]
+pub struct MultiStableDeposit {
+    /// Amount of each token to deposit, in pool order
+    pub token_amounts: Vec<u64>,
+    /// Minimum pool token amount to receive, prevents excessive slippage
+    pub minimum_pool_token_amount: u64,
+}
+
+/// MultiStableWithdraw instruction data
+#[derive(Clone, Debug, PartialEq, Constructor)]
+pub struct MultiStableWithdraw {
+    /// Amount of pool tokens to burn, the token amounts are proportional to the pool balances
+    pub pool_token_amount: u64,
+    /// Minimum amount of each token to receive, in pool order, prevents excessive slippage
+    pub minimum_token_amounts: Vec<u64>,
+}
+
+/// MultiStableWithdrawImbalanced instruction data
+#[derive(Clone, Debug, PartialEq, Constructor)]
+pub struct MultiStableWithdrawImbalanced {
+    /// Amount of each token to receive, in pool order
+    pub token_amounts: Vec<u64>,
+    /// Maximum pool token amount to burn, prevents excessive slippage
+    pub maximum_pool_token_amount: u64,
+}
+
+/// Accounts of a token of a multi-token stable pool deposit or withdrawal, passed as remaining
+/// accounts
+#[derive(Clone, Debug, PartialEq, Constructor)]
+pub struct MultiStableLiquidityToken {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub user_ata: Pubkey,
+    pub token_program: Pubkey,
+}
+
+impl MultiStableLiquidityToken {
+    /// Account metas in the order expected by the multi-token stable pool liquidity instructions
+    pub fn to_account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(self.mint, false),
+            AccountMeta::new(self.vault, false),
+            AccountMeta::new(self.user_ata, false),
+            AccountMeta::new_readonly(self.token_program, false),
+        ]
+    }
+}
+
 /// UpdatePoolConfig instruction data
 #[derive(Clone, Debug, PartialEq, Constructor)]
 pub struct UpdatePoolConfig {
@@ -93,6 +334,83 @@ impl From<UpdatePoolConfig> for crate::instruction::UpdatePoolConfig {
     }
 }
 
+/// UpdateCurveConfig instruction data
+#[derive(Clone, Debug, PartialEq, Constructor)]
+pub struct UpdateCurveConfig {
+    /// Update mode
+    pub mode: UpdateCurveConfigMode,
+    /// Value to set
+    pub value: UpdateCurveConfigValue,
+}
+
+impl From<UpdateCurveConfig> for crate::instruction::UpdateCurveConfig {
+    fn from(value: UpdateCurveConfig) -> Self {
+        crate::instruction::UpdateCurveConfig {
+            mode: value.mode as u16,
+            value: value.value.to_bytes(),
+        }
+    }
+}
+
+/// Creates an 'initialize global config' instruction.
+pub fn initialize_global_config(
+    program_id: &Pubkey,
+    upgrade_authority: &Pubkey,
+    InitializeGlobalConfig {
+        owner,
+        min_fees,
+        valid_curve_types,
+        blocked_token_extensions,
+    }: InitializeGlobalConfig,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::InitializeGlobalConfig {
+        owner,
+        min_fees,
+        valid_curve_types,
+        blocked_token_extensions,
+    }
+    .data();
+
+    let accounts = super::accounts::InitializeGlobalConfig {
+        upgrade_authority: *upgrade_authority,
+        global_config: seeds::pda::global_config_pda_program_id(program_id).0,
+        program_data: seeds::pda::program_data_pda_program_id(program_id).0,
+        system_program: System::id(),
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'update global config' instruction.
+pub fn update_global_config(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    UpdateGlobalConfig { mode, value }: UpdateGlobalConfig,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::UpdateGlobalConfig {
+        mode: mode as u16,
+        value: value.to_bytes(),
+    }
+    .data();
+
+    let accounts = super::accounts::UpdateGlobalConfig {
+        owner: *owner,
+        global_config: seeds::pda::global_config_pda_program_id(program_id).0,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
 /// Creates an 'initialize' instruction.
 pub fn initialize_pool(
     program_id: &Pubkey,
@@ -107,6 +425,8 @@ pub fn initialize_pool(
     pool_token_mint: &Pubkey,
     token_a_fees_vault: &Pubkey,
     token_b_fees_vault: &Pubkey,
+    token_a_protocol_fees_vault: &Pubkey,
+    token_b_protocol_fees_vault: &Pubkey,
     admin_token_a_ata: &Pubkey,
     admin_token_b_ata: &Pubkey,
     admin_pool_token_ata: &Pubkey,
@@ -121,19 +441,28 @@ pub fn initialize_pool(
                 initial_supply_a,
                 initial_supply_b,
             },
+        pool_token_metadata,
     }: Initialize,
 ) -> Result<Instruction, ProgramError> {
+    // Oracle priced curves read their initial price from the price feed
+    let price_feed = match curve_parameters {
+        CurveUserParameters::OraclePrice { price_feed, .. } => Some(price_feed),
+        _ => None,
+    };
+
     let data = super::instruction::InitializePool {
         initial_supply_a,
         initial_supply_b,
         fees,
         curve_parameters,
+        pool_token_metadata,
     }
     .data();
 
-    let accounts = super::accounts::InitializePool {
+    let mut accounts = super::accounts::InitializePool {
         admin: *admin,
         pool: *pool,
+        global_config: seeds::pda::global_config_pda_program_id(program_id).0,
         swap_curve: *swap_curve,
         pool_authority: *pool_authority,
         token_a_mint: *token_a_mint,
@@ -143,6 +472,8 @@ pub fn initialize_pool(
         pool_token_mint: *pool_token_mint,
         token_a_fees_vault: *token_a_fees_vault,
         token_b_fees_vault: *token_b_fees_vault,
+        token_a_protocol_fees_vault: *token_a_protocol_fees_vault,
+        token_b_protocol_fees_vault: *token_b_protocol_fees_vault,
         admin_token_a_ata: *admin_token_a_ata,
         admin_token_b_ata: *admin_token_b_ata,
         admin_pool_token_ata: *admin_pool_token_ata,
@@ -153,6 +484,9 @@ pub fn initialize_pool(
         token_b_token_program: *token_b_program_id,
     }
     .to_account_metas(None);
+    if let Some(price_feed) = price_feed {
+        accounts.push(AccountMeta::new_readonly(price_feed, false));
+    }
 
     Ok(Instruction {
         program_id: *program_id,
@@ -218,6 +552,57 @@ pub fn deposit(
     })
 }
 
+/// Creates a 'deposit_single_token_type_exact_amount_in' instruction.
+pub fn deposit_single_token_type_exact_amount_in(
+    program_id: &Pubkey,
+    user_transfer_authority_pubkey: &Pubkey,
+    pool: &Pubkey,
+    swap_curve: &Pubkey,
+    pool_authority: &Pubkey,
+    source_mint: &Pubkey,
+    token_a_vault: &Pubkey,
+    token_b_vault: &Pubkey,
+    source_token_fees_vault: &Pubkey,
+    pool_token_mint: &Pubkey,
+    user_source_ata: &Pubkey,
+    user_pool_token_ata: &Pubkey,
+    pool_token_program: &Pubkey,
+    source_token_program: &Pubkey,
+    DepositSingleTokenTypeExactAmountIn {
+        source_token_amount,
+        minimum_pool_token_amount,
+    }: DepositSingleTokenTypeExactAmountIn,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::DepositSingleTokenTypeExactAmountIn {
+        source_token_amount,
+        minimum_pool_token_amount,
+    }
+    .data();
+
+    let accounts = super::accounts::DepositSingleTokenType {
+        signer: *user_transfer_authority_pubkey,
+        pool: *pool,
+        swap_curve: *swap_curve,
+        pool_authority: *pool_authority,
+        source_mint: *source_mint,
+        token_a_vault: *token_a_vault,
+        token_b_vault: *token_b_vault,
+        source_token_fees_vault: *source_token_fees_vault,
+        pool_token_mint: *pool_token_mint,
+        source_user_ata: *user_source_ata,
+        pool_token_user_ata: *user_pool_token_ata,
+        pool_token_program: *pool_token_program,
+        source_token_program: *source_token_program,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
 /// Creates a 'withdraw' instruction.
 pub fn withdraw(
     program_id: &Pubkey,
@@ -291,6 +676,7 @@ pub fn swap(
     source_vault: &Pubkey,
     destination_vault: &Pubkey,
     source_token_fees_vault: &Pubkey,
+    source_token_protocol_fees_vault: &Pubkey,
     source_user_ata: &Pubkey,
     destination_user_ata: &Pubkey,
     source_token_host_fees: Option<&Pubkey>,
@@ -310,6 +696,7 @@ pub fn swap(
     let accounts = super::accounts::Swap {
         signer: *user_transfer_authority,
         pool: *pool,
+        global_config: seeds::pda::global_config_pda_program_id(program_id).0,
         swap_curve: *swap_curve,
         pool_authority: *pool_authority,
         source_mint: *source_mint,
@@ -317,6 +704,7 @@ pub fn swap(
         source_vault: *source_vault,
         destination_vault: *destination_vault,
         source_token_fees_vault: *source_token_fees_vault,
+        source_token_protocol_fees_vault: protocol_fees_vault(source_token_protocol_fees_vault),
         source_user_ata: *source_user_ata,
         destination_user_ata: *destination_user_ata,
         source_token_host_fees_account: source_token_host_fees.copied(),
@@ -332,35 +720,40 @@ pub fn swap(
     })
 }
 
-/// Creates a 'withdraw_fees' instruction.
-pub fn withdraw_fees(
+/// Creates a 'route_swap' instruction.
+pub fn route_swap(
     program_id: &Pubkey,
-    admin: &Pubkey,
-    pool: &Pubkey,
-    pool_authority: &Pubkey,
-    fees_mint: &Pubkey,
-    fees_vault: &Pubkey,
-    admin_fees_ata: &Pubkey,
-    fees_token_program: &Pubkey,
-    WithdrawFees {
-        requested_token_amount: requested_pool_token_amount,
-    }: WithdrawFees,
+    user_transfer_authority: &Pubkey,
+    source_mint: &Pubkey,
+    destination_mint: &Pubkey,
+    source_user_ata: &Pubkey,
+    destination_user_ata: &Pubkey,
+    source_token_program_id: &Pubkey,
+    destination_token_program_id: &Pubkey,
+    hops: &[RouteSwapHop],
+    RouteSwap {
+        amount_in,
+        minimum_amount_out,
+    }: RouteSwap,
 ) -> Result<Instruction, ProgramError> {
-    let data = super::instruction::WithdrawFees {
-        requested_pool_token_amount,
+    let data = super::instruction::RouteSwap {
+        amount_in,
+        minimum_amount_out,
     }
     .data();
 
-    let accounts = super::accounts::WithdrawFees {
-        admin: *admin,
-        pool: *pool,
-        pool_authority: *pool_authority,
-        fees_mint: *fees_mint,
-        fees_vault: *fees_vault,
-        admin_fees_ata: *admin_fees_ata,
-        fees_token_program: *fees_token_program,
+    let mut accounts = super::accounts::RouteSwap {
+        signer: *user_transfer_authority,
+        global_config: seeds::pda::global_config_pda_program_id(program_id).0,
+        source_mint: *source_mint,
+        destination_mint: *destination_mint,
+        source_user_ata: *source_user_ata,
+        destination_user_ata: *destination_user_ata,
+        source_token_program: *source_token_program_id,
+        destination_token_program: *destination_token_program_id,
     }
     .to_account_metas(None);
+    accounts.extend(hops.iter().flat_map(RouteSwapHop::to_account_metas));
 
     Ok(Instruction {
         program_id: *program_id,
@@ -369,22 +762,52 @@ pub fn withdraw_fees(
     })
 }
 
-/// Creates an 'update pool config' instruction.
-pub fn update_pool_config(
+/// Creates a 'swap_exact_out' instruction.
+pub fn swap_exact_out(
     program_id: &Pubkey,
-    admin: &Pubkey,
+    user_transfer_authority: &Pubkey,
     pool: &Pubkey,
-    UpdatePoolConfig { mode, value }: UpdatePoolConfig,
+    swap_curve: &Pubkey,
+    pool_authority: &Pubkey,
+    source_mint: &Pubkey,
+    destination_mint: &Pubkey,
+    source_vault: &Pubkey,
+    destination_vault: &Pubkey,
+    source_token_fees_vault: &Pubkey,
+    source_token_protocol_fees_vault: &Pubkey,
+    source_user_ata: &Pubkey,
+    destination_user_ata: &Pubkey,
+    source_token_host_fees: Option<&Pubkey>,
+    source_token_program_id: &Pubkey,
+    destination_token_program_id: &Pubkey,
+    SwapExactOut {
+        amount_out,
+        maximum_amount_in,
+    }: SwapExactOut,
 ) -> Result<Instruction, ProgramError> {
-    let data = super::instruction::UpdatePoolConfig {
-        mode: mode as u16,
-        value: value.to_bytes(),
+    let data = super::instruction::SwapExactOut {
+        amount_out,
+        maximum_amount_in,
     }
     .data();
 
-    let accounts = super::accounts::UpdatePoolConfig {
-        admin: *admin,
+    let accounts = super::accounts::Swap {
+        signer: *user_transfer_authority,
         pool: *pool,
+        global_config: seeds::pda::global_config_pda_program_id(program_id).0,
+        swap_curve: *swap_curve,
+        pool_authority: *pool_authority,
+        source_mint: *source_mint,
+        destination_mint: *destination_mint,
+        source_vault: *source_vault,
+        destination_vault: *destination_vault,
+        source_token_fees_vault: *source_token_fees_vault,
+        source_token_protocol_fees_vault: protocol_fees_vault(source_token_protocol_fees_vault),
+        source_user_ata: *source_user_ata,
+        destination_user_ata: *destination_user_ata,
+        source_token_host_fees_account: source_token_host_fees.copied(),
+        source_token_program: *source_token_program_id,
+        destination_token_program: *destination_token_program_id,
     }
     .to_account_metas(None);
 
@@ -394,3 +817,902 @@ pub fn update_pool_config(
         data,
     })
 }
+
+/// Creates a 'withdraw_single_token_type_exact_amount_out' instruction.
+pub fn withdraw_single_token_type_exact_amount_out(
+    program_id: &Pubkey,
+    user_transfer_authority_pubkey: &Pubkey,
+    pool: &Pubkey,
+    swap_curve: &Pubkey,
+    pool_authority: &Pubkey,
+    destination_mint: &Pubkey,
+    token_a_vault: &Pubkey,
+    token_b_vault: &Pubkey,
+    destination_token_fees_vault: &Pubkey,
+    pool_token_mint: &Pubkey,
+    user_destination_ata: &Pubkey,
+    user_pool_token_ata: &Pubkey,
+    pool_token_program: &Pubkey,
+    destination_token_program: &Pubkey,
+    WithdrawSingleTokenTypeExactAmountOut {
+        destination_token_amount,
+        maximum_pool_token_amount,
+    }: WithdrawSingleTokenTypeExactAmountOut,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::WithdrawSingleTokenTypeExactAmountOut {
+        destination_token_amount,
+        maximum_pool_token_amount,
+    }
+    .data();
+
+    let accounts = super::accounts::WithdrawSingleTokenType {
+        signer: *user_transfer_authority_pubkey,
+        pool: *pool,
+        swap_curve: *swap_curve,
+        pool_authority: *pool_authority,
+        destination_mint: *destination_mint,
+        token_a_vault: *token_a_vault,
+        token_b_vault: *token_b_vault,
+        destination_token_fees_vault: *destination_token_fees_vault,
+        pool_token_mint: *pool_token_mint,
+        destination_user_ata: *user_destination_ata,
+        pool_token_user_ata: *user_pool_token_ata,
+        pool_token_program: *pool_token_program,
+        destination_token_program: *destination_token_program,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'withdraw_one_token' instruction.
+pub fn withdraw_one_token(
+    program_id: &Pubkey,
+    user_transfer_authority_pubkey: &Pubkey,
+    pool: &Pubkey,
+    swap_curve: &Pubkey,
+    pool_authority: &Pubkey,
+    destination_mint: &Pubkey,
+    token_a_vault: &Pubkey,
+    token_b_vault: &Pubkey,
+    destination_token_fees_vault: &Pubkey,
+    pool_token_mint: &Pubkey,
+    user_destination_ata: &Pubkey,
+    user_pool_token_ata: &Pubkey,
+    pool_token_program: &Pubkey,
+    destination_token_program: &Pubkey,
+    WithdrawOneToken {
+        pool_token_amount,
+        minimum_token_amount,
+        a_or_b,
+    }: WithdrawOneToken,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::WithdrawOneToken {
+        pool_token_amount,
+        minimum_token_amount,
+        a_or_b,
+    }
+    .data();
+
+    let accounts = super::accounts::WithdrawSingleTokenType {
+        signer: *user_transfer_authority_pubkey,
+        pool: *pool,
+        swap_curve: *swap_curve,
+        pool_authority: *pool_authority,
+        destination_mint: *destination_mint,
+        token_a_vault: *token_a_vault,
+        token_b_vault: *token_b_vault,
+        destination_token_fees_vault: *destination_token_fees_vault,
+        pool_token_mint: *pool_token_mint,
+        destination_user_ata: *user_destination_ata,
+        pool_token_user_ata: *user_pool_token_ata,
+        pool_token_program: *pool_token_program,
+        destination_token_program: *destination_token_program,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'withdraw_fees' instruction.
+pub fn withdraw_fees(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    pool: &Pubkey,
+    pool_authority: &Pubkey,
+    fees_mint: &Pubkey,
+    fees_vault: &Pubkey,
+    admin_fees_ata: &Pubkey,
+    fees_token_program: &Pubkey,
+    WithdrawFees {
+        requested_token_amount: requested_pool_token_amount,
+    }: WithdrawFees,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::WithdrawFees {
+        requested_pool_token_amount,
+    }
+    .data();
+
+    let accounts = super::accounts::WithdrawFees {
+        admin: *admin,
+        pool: *pool,
+        pool_authority: *pool_authority,
+        fees_mint: *fees_mint,
+        fees_vault: *fees_vault,
+        admin_fees_ata: *admin_fees_ata,
+        fees_token_program: *fees_token_program,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'initialize_protocol_fees_vaults' instruction.
+pub fn initialize_protocol_fees_vaults(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    pool: &Pubkey,
+    pool_authority: &Pubkey,
+    token_a_mint: &Pubkey,
+    token_b_mint: &Pubkey,
+    token_a_token_program: &Pubkey,
+    token_b_token_program: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::InitializeProtocolFeesVaults {}.data();
+
+    let accounts = super::accounts::InitializeProtocolFeesVaults {
+        payer: *payer,
+        global_config: seeds::pda::global_config_pda_program_id(program_id).0,
+        pool: *pool,
+        pool_authority: *pool_authority,
+        token_a_mint: *token_a_mint,
+        token_b_mint: *token_b_mint,
+        token_a_protocol_fees_vault: seeds::pda::token_a_protocol_fees_vault_pda_program_id(
+            program_id,
+            pool,
+            token_a_mint,
+        )
+        .0,
+        token_b_protocol_fees_vault: seeds::pda::token_b_protocol_fees_vault_pda_program_id(
+            program_id,
+            pool,
+            token_b_mint,
+        )
+        .0,
+        system_program: System::id(),
+        token_a_token_program: *token_a_token_program,
+        token_b_token_program: *token_b_token_program,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'withdraw_protocol_fees' instruction.
+pub fn withdraw_protocol_fees(
+    program_id: &Pubkey,
+    protocol_authority: &Pubkey,
+    pool: &Pubkey,
+    pool_authority: &Pubkey,
+    fees_mint: &Pubkey,
+    protocol_fees_vault: &Pubkey,
+    protocol_authority_fees_ata: &Pubkey,
+    fees_token_program: &Pubkey,
+    WithdrawProtocolFees {
+        requested_token_amount: requested_withdraw_amount,
+    }: WithdrawProtocolFees,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::WithdrawProtocolFees {
+        requested_withdraw_amount,
+    }
+    .data();
+
+    let accounts = super::accounts::WithdrawProtocolFees {
+        protocol_authority: *protocol_authority,
+        global_config: seeds::pda::global_config_pda_program_id(program_id).0,
+        pool: *pool,
+        pool_authority: *pool_authority,
+        fees_mint: *fees_mint,
+        protocol_fees_vault: *protocol_fees_vault,
+        protocol_authority_fees_ata: *protocol_authority_fees_ata,
+        fees_token_program: *fees_token_program,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'update pool config' instruction.
+pub fn update_pool_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    pool: &Pubkey,
+    UpdatePoolConfig { mode, value }: UpdatePoolConfig,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::UpdatePoolConfig {
+        mode: mode as u16,
+        value: value.to_bytes(),
+    }
+    .data();
+
+    let accounts = super::accounts::UpdatePoolConfig {
+        admin: *admin,
+        pool: *pool,
+        global_config: seeds::pda::global_config_pda_program_id(program_id).0,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'update curve config' instruction.
+pub fn update_curve_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    pool: &Pubkey,
+    swap_curve: &Pubkey,
+    UpdateCurveConfig { mode, value }: UpdateCurveConfig,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::UpdateCurveConfig {
+        mode: mode as u16,
+        value: value.to_bytes(),
+    }
+    .data();
+
+    let accounts = super::accounts::UpdateCurveConfig {
+        admin: *admin,
+        pool: *pool,
+        swap_curve: *swap_curve,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'propose new admin' instruction.
+pub fn propose_new_admin(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    pool: &Pubkey,
+    new_admin: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::ProposeNewAdmin {
+        new_admin: *new_admin,
+    }
+    .data();
+
+    let accounts = super::accounts::ProposeNewAdmin {
+        admin: *admin,
+        pool: *pool,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'accept admin' instruction.
+pub fn accept_admin(
+    program_id: &Pubkey,
+    pending_admin: &Pubkey,
+    pool: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::AcceptAdmin {}.data();
+
+    let accounts = super::accounts::AcceptAdmin {
+        pending_admin: *pending_admin,
+        pool: *pool,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'ramp amp' instruction.
+pub fn ramp_amp(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    pool: &Pubkey,
+    swap_curve: &Pubkey,
+    target_amp: u64,
+    ramp_end_ts: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::RampAmp {
+        target_amp,
+        ramp_end_ts,
+    }
+    .data();
+
+    let accounts = super::accounts::RampAmp {
+        admin: *admin,
+        pool: *pool,
+        swap_curve: *swap_curve,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'stop ramp amp' instruction.
+pub fn stop_ramp_amp(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    pool: &Pubkey,
+    swap_curve: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::StopRampAmp {}.data();
+
+    let accounts = super::accounts::StopRampAmp {
+        admin: *admin,
+        pool: *pool,
+        swap_curve: *swap_curve,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_rate_provider' instruction.
+pub fn set_rate_provider(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    pool: &Pubkey,
+    swap_curve: &Pubkey,
+    rate_provider: RateProvider,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::SetRateProvider { rate_provider }.data();
+
+    let mut accounts = super::accounts::SetRateProvider {
+        admin: *admin,
+        pool: *pool,
+        swap_curve: *swap_curve,
+    }
+    .to_account_metas(None);
+    if let RateProvider::StakePool { stake_pool } = rate_provider {
+        accounts.push(AccountMeta::new_readonly(stake_pool, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'update_rate' instruction.
+pub fn update_rate(
+    program_id: &Pubkey,
+    rate_updater: &Pubkey,
+    pool: &Pubkey,
+    swap_curve: &Pubkey,
+    token_b_rate: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::UpdateRate { token_b_rate }.data();
+
+    let accounts = super::accounts::UpdateRate {
+        rate_updater: *rate_updater,
+        pool: *pool,
+        swap_curve: *swap_curve,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'update_dynamic_fee' instruction.
+pub fn update_dynamic_fee(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    pool: &Pubkey,
+    parameters: Option<DynamicFeeParameters>,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::UpdateDynamicFee { parameters }.data();
+
+    let accounts = super::accounts::UpdateDynamicFee {
+        admin: *admin,
+        pool: *pool,
+        global_config: seeds::pda::global_config_pda_program_id(program_id).0,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'close_pool' instruction.
+pub fn close_pool(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    pool: &Pubkey,
+    swap_curve: &Pubkey,
+    pool_authority: &Pubkey,
+    token_a_mint: &Pubkey,
+    token_b_mint: &Pubkey,
+    token_a_vault: &Pubkey,
+    token_b_vault: &Pubkey,
+    pool_token_mint: &Pubkey,
+    token_a_fees_vault: &Pubkey,
+    token_b_fees_vault: &Pubkey,
+    token_a_protocol_fees_vault: &Pubkey,
+    token_b_protocol_fees_vault: &Pubkey,
+    admin_token_a_ata: &Pubkey,
+    admin_token_b_ata: &Pubkey,
+    token_a_token_program: &Pubkey,
+    token_b_token_program: &Pubkey,
+    pool_token_program: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::ClosePool {}.data();
+
+    let accounts = super::accounts::ClosePool {
+        admin: *admin,
+        pool: *pool,
+        swap_curve: *swap_curve,
+        pool_authority: *pool_authority,
+        token_a_mint: *token_a_mint,
+        token_b_mint: *token_b_mint,
+        token_a_vault: *token_a_vault,
+        token_b_vault: *token_b_vault,
+        pool_token_mint: *pool_token_mint,
+        token_a_fees_vault: *token_a_fees_vault,
+        token_b_fees_vault: *token_b_fees_vault,
+        token_a_protocol_fees_vault: protocol_fees_vault(token_a_protocol_fees_vault),
+        token_b_protocol_fees_vault: protocol_fees_vault(token_b_protocol_fees_vault),
+        admin_token_a_ata: *admin_token_a_ata,
+        admin_token_b_ata: *admin_token_b_ata,
+        token_a_token_program: *token_a_token_program,
+        token_b_token_program: *token_b_token_program,
+        pool_token_program: *pool_token_program,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'initialize_multi_stable_pool' instruction.
+pub fn initialize_multi_stable_pool(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    pool: &Pubkey,
+    admin_pool_token_ata: &Pubkey,
+    pool_token_program_id: &Pubkey,
+    tokens: &[InitMultiStableToken],
+    InitializeMultiStablePool {
+        amp,
+        fees,
+        initial_supplies,
+    }: InitializeMultiStablePool,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::InitializeMultiStablePool {
+        amp,
+        fees,
+        initial_supplies,
+    }
+    .data();
+
+    let mut accounts = super::accounts::InitializeMultiStablePool {
+        admin: *admin,
+        pool: *pool,
+        global_config: seeds::pda::global_config_pda_program_id(program_id).0,
+        pool_authority: seeds::pda::pool_authority_pda_program_id(program_id, pool).0,
+        pool_token_mint: seeds::pda::pool_token_mint_pda_program_id(program_id, pool).0,
+        admin_pool_token_ata: *admin_pool_token_ata,
+        system_program: System::id(),
+        rent: Rent::id(),
+        pool_token_program: *pool_token_program_id,
+    }
+    .to_account_metas(None);
+    accounts.extend(
+        tokens
+            .iter()
+            .flat_map(|token| token.to_account_metas(program_id, pool)),
+    );
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'multi_stable_swap' instruction.
+///
+/// `vaults` are the vaults of every token of the pool, in pool order
+pub fn multi_stable_swap(
+    program_id: &Pubkey,
+    user_transfer_authority: &Pubkey,
+    pool: &Pubkey,
+    pool_authority: &Pubkey,
+    source_mint: &Pubkey,
+    destination_mint: &Pubkey,
+    source_vault: &Pubkey,
+    destination_vault: &Pubkey,
+    source_token_fees_vault: &Pubkey,
+    source_token_protocol_fees_vault: &Pubkey,
+    source_user_ata: &Pubkey,
+    destination_user_ata: &Pubkey,
+    source_token_program_id: &Pubkey,
+    destination_token_program_id: &Pubkey,
+    vaults: &[Pubkey],
+    Swap {
+        amount_in,
+        minimum_amount_out,
+    }: Swap,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::MultiStableSwap {
+        amount_in,
+        minimum_amount_out,
+    }
+    .data();
+
+    let mut accounts = super::accounts::MultiStableSwap {
+        signer: *user_transfer_authority,
+        pool: *pool,
+        global_config: seeds::pda::global_config_pda_program_id(program_id).0,
+        pool_authority: *pool_authority,
+        source_mint: *source_mint,
+        destination_mint: *destination_mint,
+        source_vault: *source_vault,
+        destination_vault: *destination_vault,
+        source_token_fees_vault: *source_token_fees_vault,
+        source_token_protocol_fees_vault: *source_token_protocol_fees_vault,
+        source_user_ata: *source_user_ata,
+        destination_user_ata: *destination_user_ata,
+        source_token_program: *source_token_program_id,
+        destination_token_program: *destination_token_program_id,
+    }
+    .to_account_metas(None);
+    accounts.extend(
+        vaults
+            .iter()
+            .map(|vault| AccountMeta::new_readonly(*vault, false)),
+    );
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+fn multi_stable_liquidity_accounts(
+    user_transfer_authority: &Pubkey,
+    pool: &Pubkey,
+    pool_authority: &Pubkey,
+    pool_token_mint: &Pubkey,
+    user_pool_token_ata: &Pubkey,
+    pool_token_program: &Pubkey,
+    tokens: &[MultiStableLiquidityToken],
+) -> Vec<AccountMeta> {
+    let mut accounts = super::accounts::MultiStableLiquidity {
+        signer: *user_transfer_authority,
+        pool: *pool,
+        pool_authority: *pool_authority,
+        pool_token_mint: *pool_token_mint,
+        pool_token_user_ata: *user_pool_token_ata,
+        pool_token_program: *pool_token_program,
+    }
+    .to_account_metas(None);
+    accounts.extend(
+        tokens
+            .iter()
+            .flat_map(MultiStableLiquidityToken::to_account_metas),
+    );
+    accounts
+}
+
+/// Creates a 'multi_stable_deposit' instruction.
+pub fn multi_stable_deposit(
+    program_id: &Pubkey,
+    user_transfer_authority: &Pubkey,
+    pool: &Pubkey,
+    pool_authority: &Pubkey,
+    pool_token_mint: &Pubkey,
+    user_pool_token_ata: &Pubkey,
+    pool_token_program: &Pubkey,
+    tokens: &[MultiStableLiquidityToken],
+    MultiStableDeposit {
+        token_amounts,
+        minimum_pool_token_amount,
+    }: MultiStableDeposit,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::MultiStableDeposit {
+        token_amounts,
+        minimum_pool_token_amount,
+    }
+    .data();
+
+    let accounts = multi_stable_liquidity_accounts(
+        user_transfer_authority,
+        pool,
+        pool_authority,
+        pool_token_mint,
+        user_pool_token_ata,
+        pool_token_program,
+        tokens,
+    );
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'multi_stable_withdraw' instruction.
+pub fn multi_stable_withdraw(
+    program_id: &Pubkey,
+    user_transfer_authority: &Pubkey,
+    pool: &Pubkey,
+    pool_authority: &Pubkey,
+    pool_token_mint: &Pubkey,
+    user_pool_token_ata: &Pubkey,
+    pool_token_program: &Pubkey,
+    tokens: &[MultiStableLiquidityToken],
+    MultiStableWithdraw {
+        pool_token_amount,
+        minimum_token_amounts,
+    }: MultiStableWithdraw,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::MultiStableWithdraw {
+        pool_token_amount,
+        minimum_token_amounts,
+    }
+    .data();
+
+    let accounts = multi_stable_liquidity_accounts(
+        user_transfer_authority,
+        pool,
+        pool_authority,
+        pool_token_mint,
+        user_pool_token_ata,
+        pool_token_program,
+        tokens,
+    );
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'multi_stable_withdraw_imbalanced' instruction.
+pub fn multi_stable_withdraw_imbalanced(
+    program_id: &Pubkey,
+    user_transfer_authority: &Pubkey,
+    pool: &Pubkey,
+    pool_authority: &Pubkey,
+    pool_token_mint: &Pubkey,
+    user_pool_token_ata: &Pubkey,
+    pool_token_program: &Pubkey,
+    tokens: &[MultiStableLiquidityToken],
+    MultiStableWithdrawImbalanced {
+        token_amounts,
+        maximum_pool_token_amount,
+    }: MultiStableWithdrawImbalanced,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::MultiStableWithdrawImbalanced {
+        token_amounts,
+        maximum_pool_token_amount,
+    }
+    .data();
+
+    let accounts = multi_stable_liquidity_accounts(
+        user_transfer_authority,
+        pool,
+        pool_authority,
+        pool_token_mint,
+        user_pool_token_ata,
+        pool_token_program,
+        tokens,
+    );
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'multi_stable_withdraw_fees' instruction.
+pub fn multi_stable_withdraw_fees(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    pool: &Pubkey,
+    pool_authority: &Pubkey,
+    fees_mint: &Pubkey,
+    fees_vault: &Pubkey,
+    admin_fees_ata: &Pubkey,
+    fees_token_program: &Pubkey,
+    WithdrawFees {
+        requested_token_amount: requested_withdraw_amount,
+    }: WithdrawFees,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::MultiStableWithdrawFees {
+        requested_withdraw_amount,
+    }
+    .data();
+
+    let accounts = super::accounts::MultiStableWithdrawFees {
+        admin: *admin,
+        pool: *pool,
+        pool_authority: *pool_authority,
+        fees_mint: *fees_mint,
+        fees_vault: *fees_vault,
+        admin_fees_ata: *admin_fees_ata,
+        fees_token_program: *fees_token_program,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'multi_stable_withdraw_protocol_fees' instruction.
+pub fn multi_stable_withdraw_protocol_fees(
+    program_id: &Pubkey,
+    protocol_authority: &Pubkey,
+    pool: &Pubkey,
+    pool_authority: &Pubkey,
+    fees_mint: &Pubkey,
+    protocol_fees_vault: &Pubkey,
+    protocol_authority_fees_ata: &Pubkey,
+    fees_token_program: &Pubkey,
+    WithdrawProtocolFees {
+        requested_token_amount: requested_withdraw_amount,
+    }: WithdrawProtocolFees,
+) -> Result<Instruction, ProgramError> {
+    let data = super::instruction::MultiStableWithdrawProtocolFees {
+        requested_withdraw_amount,
+    }
+    .data();
+
+    let accounts = super::accounts::MultiStableWithdrawProtocolFees {
+        protocol_authority: *protocol_authority,
+        global_config: seeds::pda::global_config_pda_program_id(program_id).0,
+        pool: *pool,
+        pool_authority: *pool_authority,
+        fees_mint: *fees_mint,
+        protocol_fees_vault: *protocol_fees_vault,
+        protocol_authority_fees_ata: *protocol_authority_fees_ata,
+        fees_token_program: *fees_token_program,
+    }
+    .to_account_metas(None);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Resolve the accounts required by the transfer hook of `mint` for a transfer, to be passed to
+/// the pool instructions as remaining accounts: the extra accounts listed by the hook, the hook
+/// program and its extra account metas account. Empty if the mint has no transfer hook.
+///
+/// `fetch_account_data_fn` returns the data of an account, `None` if it does not exist. The
+/// `amount` only matters to hooks deriving extra accounts from the instruction data.
+pub async fn transfer_hook_accounts<F, Fut>(
+    fetch_account_data_fn: F,
+    source: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Result<Vec<AccountMeta>, AccountFetchError>
+where
+    F: Fn(Pubkey) -> Fut,
+    Fut: Future<Output = AccountDataResult>,
+{
+    let mint_data = fetch_account_data_fn(*mint)
+        .await?
+        .ok_or(ProgramError::InvalidAccountData)?;
+    let program_id =
+        match token_extensions::transfer_hook_program_id(&mint_data).map_err(ProgramError::from)? {
+            Some(program_id) => program_id,
+            None => return Ok(vec![]),
+        };
+    // resolved against the `Execute` instruction of the hook, as the token program does
+    let extra_account_metas = get_extra_account_metas_address(mint, &program_id);
+    let mut execute_ix = execute(
+        &program_id,
+        source,
+        mint,
+        destination,
+        authority,
+        &extra_account_metas,
+        amount,
+    );
+    let execute_accounts_len = execute_ix.accounts.len();
+    let extra_account_metas_data = fetch_account_data_fn(extra_account_metas)
+        .await?
+        .ok_or(ProgramError::InvalidAccountData)?;
+    ExtraAccountMetaList::add_to_instruction::<ExecuteInstruction, _, _>(
+        &mut execute_ix,
+        fetch_account_data_fn,
+        &extra_account_metas_data,
+    )
+    .await?;
+
+    let mut accounts = execute_ix.accounts.split_off(execute_accounts_len);
+    accounts.push(AccountMeta::new_readonly(extra_account_metas, false));
+    accounts.push(AccountMeta::new_readonly(program_id, false));
+    Ok(accounts)
+}
+
+/// Merge accounts into a list of remaining accounts, once per account and writable if any of the
+/// occurrences is writable, e.g. the transfer hook accounts of both trading tokens
+pub fn merge_remaining_accounts(
+    remaining_accounts: &mut Vec<AccountMeta>,
+    accounts: &[AccountMeta],
+) {
+    for account in accounts {
+        match remaining_accounts
+            .iter_mut()
+            .find(|existing| existing.pubkey == account.pubkey)
+        {
+            Some(existing) => {
+                existing.is_writable |= account.is_writable;
+                existing.is_signer |= account.is_signer;
+            }
+            None => remaining_accounts.push(account.clone()),
+        }
+    }
+}
@@ -5,51 +5,103 @@
 
 pub mod constraints;
 pub mod curve;
+pub mod dynamic_fee;
 pub mod error;
 pub mod event;
+#[cfg(feature = "program")]
 pub mod instructions;
+#[cfg(feature = "program")]
 pub mod ix;
 pub mod state;
+pub mod twap;
 pub mod utils;
 
 // Export current sdk types for downstream users building with a different sdk version
 pub use anchor_lang;
 use anchor_lang::prelude::*;
-use curve::fees::Fees;
+pub use anchor_spl;
+#[cfg(feature = "program")]
+use curve::{calculator::AorB, fees::Fees, rate_provider::RateProvider};
+#[cfg(feature = "program")]
+use dynamic_fee::DynamicFeeParameters;
+#[cfg(feature = "program")]
 pub use instructions::*;
 
 declare_id!("RaRe29KoKtQcZLoJfeZzzke1KnuVgFV6Lt6Hnjw2QTh");
 
+#[cfg(feature = "program")]
 #[program]
 pub mod hyperplane {
     use super::*;
     use crate::event;
 
-    pub fn initialize_pool(
-        ctx: Context<InitializePool>,
+    pub fn initialize_global_config(
+        ctx: Context<InitializeGlobalConfig>,
+        owner: Pubkey,
+        min_fees: Fees,
+        valid_curve_types: Vec<u64>,
+        blocked_token_extensions: Vec<u16>,
+    ) -> Result<()> {
+        instructions::initialize_global_config::handler_initialize_global_config(
+            ctx,
+            owner,
+            min_fees,
+            valid_curve_types,
+            blocked_token_extensions,
+        )
+    }
+
+    pub fn update_global_config(
+        ctx: Context<UpdateGlobalConfig>,
+        mode: u16,
+        value: [u8; VALUE_BYTE_ARRAY_LEN],
+    ) -> Result<event::UpdateGlobalConfig> {
+        instructions::update_global_config::handler_update_global_config(ctx, mode, &value)
+    }
+
+    pub fn initialize_pool<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitializePool<'info>>,
         curve_parameters: CurveUserParameters,
         fees: Fees,
         initial_supply_a: u64,
         initial_supply_b: u64,
+        pool_token_metadata: Option<PoolTokenMetadata>,
     ) -> Result<()> {
         instructions::initialize_pool::handler_initialize_pool(
             ctx,
             curve_parameters,
             fees,
             initialize_pool::InitialSupply::new(initial_supply_a, initial_supply_b),
+            pool_token_metadata,
         )
     }
 
-    pub fn swap(
-        ctx: Context<Swap>,
+    pub fn swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
         amount_in: u64,
         minimum_amount_out: u64,
     ) -> Result<event::Swap> {
         instructions::swap::handler_swap(ctx, amount_in, minimum_amount_out)
     }
 
-    pub fn deposit(
-        ctx: Context<Deposit>,
+    pub fn swap_exact_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+        amount_out: u64,
+        maximum_amount_in: u64,
+    ) -> Result<event::Swap> {
+        instructions::swap_exact_out::handler_swap_exact_out(ctx, amount_out, maximum_amount_in)
+    }
+
+    pub fn route_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, RouteSwap<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<Vec<event::Swap>> {
+        instructions::route_swap::handler_route_swap(ctx, amount_in, minimum_amount_out)
+    }
+
+    pub fn deposit<'info>(
+        ctx: Context<'_, '_, '_, 'info, Deposit<'info>>,
         pool_token_amount: u64,
         maximum_token_a_amount: u64,
         maximum_token_b_amount: u64,
@@ -62,8 +114,20 @@ pub mod hyperplane {
         )
     }
 
-    pub fn withdraw(
-        ctx: Context<Withdraw>,
+    pub fn deposit_single_token_type_exact_amount_in<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositSingleTokenType<'info>>,
+        source_token_amount: u64,
+        minimum_pool_token_amount: u64,
+    ) -> Result<event::DepositSingleTokenType> {
+        instructions::deposit_single_token_type_exact_amount_in::handler_deposit_single_token_type_exact_amount_in(
+            ctx,
+            source_token_amount,
+            minimum_pool_token_amount,
+        )
+    }
+
+    pub fn withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, Withdraw<'info>>,
         pool_token_amount: u64,
         minimum_token_a_amount: u64,
         minimum_token_b_amount: u64,
@@ -76,13 +140,137 @@ pub mod hyperplane {
         )
     }
 
-    pub fn withdraw_fees(
-        ctx: Context<WithdrawFees>,
+    pub fn withdraw_single_token_type_exact_amount_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawSingleTokenType<'info>>,
+        destination_token_amount: u64,
+        maximum_pool_token_amount: u64,
+    ) -> Result<event::WithdrawSingleTokenType> {
+        instructions::withdraw_single_token_type_exact_amount_out::handler_withdraw_single_token_type_exact_amount_out(
+            ctx,
+            destination_token_amount,
+            maximum_pool_token_amount,
+        )
+    }
+
+    pub fn withdraw_one_token<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawSingleTokenType<'info>>,
+        pool_token_amount: u64,
+        minimum_token_amount: u64,
+        a_or_b: AorB,
+    ) -> Result<event::WithdrawSingleTokenType> {
+        instructions::withdraw_one_token::handler_withdraw_one_token(
+            ctx,
+            pool_token_amount,
+            minimum_token_amount,
+            a_or_b,
+        )
+    }
+
+    pub fn withdraw_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawFees<'info>>,
         requested_pool_token_amount: u64,
     ) -> Result<event::WithdrawFees> {
         instructions::withdraw_fees::handler_withdraw_fees(ctx, requested_pool_token_amount)
     }
 
+    pub fn withdraw_protocol_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawProtocolFees<'info>>,
+        requested_withdraw_amount: u64,
+    ) -> Result<event::WithdrawProtocolFees> {
+        instructions::withdraw_protocol_fees::handler_withdraw_protocol_fees(
+            ctx,
+            requested_withdraw_amount,
+        )
+    }
+
+    pub fn initialize_protocol_fees_vaults(
+        ctx: Context<InitializeProtocolFeesVaults>,
+    ) -> Result<event::InitializeProtocolFeesVaults> {
+        instructions::initialize_protocol_fees_vaults::handler_initialize_protocol_fees_vaults(ctx)
+    }
+
+    pub fn initialize_multi_stable_pool<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitializeMultiStablePool<'info>>,
+        amp: u64,
+        fees: Fees,
+        initial_supplies: Vec<u64>,
+    ) -> Result<()> {
+        instructions::initialize_multi_stable_pool::handler_initialize_multi_stable_pool(
+            ctx,
+            amp,
+            fees,
+            initial_supplies,
+        )
+    }
+
+    pub fn multi_stable_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, MultiStableSwap<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<event::Swap> {
+        instructions::multi_stable_swap::handler_multi_stable_swap(
+            ctx,
+            amount_in,
+            minimum_amount_out,
+        )
+    }
+
+    pub fn multi_stable_deposit<'info>(
+        ctx: Context<'_, '_, '_, 'info, MultiStableLiquidity<'info>>,
+        token_amounts: Vec<u64>,
+        minimum_pool_token_amount: u64,
+    ) -> Result<event::MultiStableDeposit> {
+        instructions::multi_stable_deposit::handler_multi_stable_deposit(
+            ctx,
+            token_amounts,
+            minimum_pool_token_amount,
+        )
+    }
+
+    pub fn multi_stable_withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, MultiStableLiquidity<'info>>,
+        pool_token_amount: u64,
+        minimum_token_amounts: Vec<u64>,
+    ) -> Result<event::MultiStableWithdraw> {
+        instructions::multi_stable_withdraw::handler_multi_stable_withdraw(
+            ctx,
+            pool_token_amount,
+            minimum_token_amounts,
+        )
+    }
+
+    pub fn multi_stable_withdraw_imbalanced<'info>(
+        ctx: Context<'_, '_, '_, 'info, MultiStableLiquidity<'info>>,
+        token_amounts: Vec<u64>,
+        maximum_pool_token_amount: u64,
+    ) -> Result<event::MultiStableWithdraw> {
+        instructions::multi_stable_withdraw_imbalanced::handler_multi_stable_withdraw_imbalanced(
+            ctx,
+            token_amounts,
+            maximum_pool_token_amount,
+        )
+    }
+
+    pub fn multi_stable_withdraw_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, MultiStableWithdrawFees<'info>>,
+        requested_withdraw_amount: u64,
+    ) -> Result<event::WithdrawFees> {
+        instructions::multi_stable_withdraw_fees::handler_multi_stable_withdraw_fees(
+            ctx,
+            requested_withdraw_amount,
+        )
+    }
+
+    pub fn multi_stable_withdraw_protocol_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, MultiStableWithdrawProtocolFees<'info>>,
+        requested_withdraw_amount: u64,
+    ) -> Result<event::WithdrawProtocolFees> {
+        instructions::multi_stable_withdraw_protocol_fees::handler_multi_stable_withdraw_protocol_fees(
+            ctx,
+            requested_withdraw_amount,
+        )
+    }
+
     pub fn update_pool_config(
         ctx: Context<UpdatePoolConfig>,
         mode: u16,
@@ -90,4 +278,59 @@ pub mod hyperplane {
     ) -> Result<event::UpdatePoolConfig> {
         instructions::update_pool_config::handler_update_pool_config(ctx, mode, &value)
     }
+
+    pub fn update_curve_config(
+        ctx: Context<UpdateCurveConfig>,
+        mode: u16,
+        value: [u8; VALUE_BYTE_ARRAY_LEN],
+    ) -> Result<event::UpdateCurveConfig> {
+        instructions::update_curve_config::handler_update_curve_config(ctx, mode, &value)
+    }
+
+    pub fn propose_new_admin(
+        ctx: Context<ProposeNewAdmin>,
+        new_admin: Pubkey,
+    ) -> Result<event::ProposeNewAdmin> {
+        instructions::propose_new_admin::handler_propose_new_admin(ctx, new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<event::AcceptAdmin> {
+        instructions::accept_admin::handler_accept_admin(ctx)
+    }
+
+    pub fn ramp_amp(
+        ctx: Context<RampAmp>,
+        target_amp: u64,
+        ramp_end_ts: u64,
+    ) -> Result<event::RampAmp> {
+        instructions::ramp_amp::handler_ramp_amp(ctx, target_amp, ramp_end_ts)
+    }
+
+    pub fn stop_ramp_amp(ctx: Context<StopRampAmp>) -> Result<event::StopRampAmp> {
+        instructions::stop_ramp_amp::handler_stop_ramp_amp(ctx)
+    }
+
+    pub fn set_rate_provider(
+        ctx: Context<SetRateProvider>,
+        rate_provider: RateProvider,
+    ) -> Result<event::SetRateProvider> {
+        instructions::set_rate_provider::handler_set_rate_provider(ctx, rate_provider)
+    }
+
+    pub fn update_rate(ctx: Context<UpdateRate>, token_b_rate: u64) -> Result<event::UpdateRate> {
+        instructions::update_rate::handler_update_rate(ctx, token_b_rate)
+    }
+
+    pub fn update_dynamic_fee(
+        ctx: Context<UpdateDynamicFee>,
+        parameters: Option<DynamicFeeParameters>,
+    ) -> Result<event::UpdateDynamicFee> {
+        instructions::update_dynamic_fee::handler_update_dynamic_fee(ctx, parameters)
+    }
+
+    pub fn close_pool<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClosePool<'info>>,
+    ) -> Result<event::ClosePool> {
+        instructions::close_pool::handler_close_pool(ctx)
+    }
 }
@@ -1,7 +1,5 @@
-use std::ops::Deref;
-
 use anchor_lang::{
-    account,
+    account, err, error,
     prelude::{borsh, ProgramError, Pubkey},
     zero_copy, AnchorDeserialize, AnchorSerialize, Result,
 };
@@ -10,13 +8,20 @@ use num_enum::TryFromPrimitive;
 use strum::EnumString;
 
 use crate::{
-    curve::{base::CurveType, fees::Fees},
+    curve::{
+        base::CurveType,
+        calculator::{AorB, RoundDirection},
+        fees::{calculate_fee, Fees},
+        multi_stable::MultiStableCurve,
+        rate_provider::RateProviderType,
+    },
+    error::SwapError,
     try_math,
-    utils::math::decimals_to_factor,
-    VALUE_BYTE_ARRAY_LEN,
+    utils::{math::decimals_to_factor, token_extensions::MintExtension},
 };
 
 const DISCRIMINATOR_SIZE: usize = 8;
+pub const VALUE_BYTE_ARRAY_LEN: usize = 32;
 
 /// Trait representing access to program state
 #[enum_dispatch]
@@ -50,12 +55,12 @@ pub trait SwapState {
 #[zero_copy]
 #[derive(PartialEq)]
 pub struct SwapPoolPadding {
-    pub inner: [u64; 504],
+    pub inner: [u64; 474],
 }
 
 impl Default for SwapPoolPadding {
     fn default() -> Self {
-        SwapPoolPadding { inner: [0; 504] }
+        SwapPoolPadding { inner: [0; 474] }
     }
 }
 
@@ -65,6 +70,50 @@ impl std::fmt::Debug for SwapPoolPadding {
     }
 }
 
+/// Uniswap v2 style cumulative spot prices of a pool, see `crate::twap`
+#[zero_copy]
+#[derive(Debug, Default, PartialEq)]
+pub struct PriceAccumulator {
+    /// Unix timestamp of the last update
+    pub last_update_ts: u64,
+    /// Sum of the spot prices of token A in token B, each multiplied by the seconds
+    /// it was in effect. A u128 fixed point number stored as little endian words
+    pub price_a_cumulative: [u64; 2],
+    /// Sum of the spot prices of token B in token A, each multiplied by the seconds
+    /// it was in effect. A u128 fixed point number stored as little endian words
+    pub price_b_cumulative: [u64; 2],
+}
+
+/// Volatility-based trade fee of a pool, see `crate::dynamic_fee`
+#[zero_copy]
+#[derive(Debug, Default, PartialEq)]
+pub struct DynamicFee {
+    /// The trade fee is dynamic when non-zero, otherwise the static `Fees` trade fee applies
+    pub enabled: u64,
+    /// Trade fee without volatility, over `DYNAMIC_FEE_DENOMINATOR`
+    pub min_fee: u64,
+    /// Trade fee at `max_volatility_bps` of volatility, over `DYNAMIC_FEE_DENOMINATOR`
+    pub max_fee: u64,
+    /// Swaps less than this many seconds apart keep accumulating volatility against the same
+    /// reference price
+    pub filter_period_sec: u64,
+    /// Seconds without swaps after which the accumulated volatility is forgotten
+    pub decay_period_sec: u64,
+    /// Share of the volatility accumulator carried over into the volatility reference, in bps
+    pub reduction_factor_bps: u64,
+    /// Volatility at which the trade fee reaches `max_fee`, in bps of price movement
+    pub max_volatility_bps: u64,
+    /// Volatility of the last swap, in bps of price movement
+    pub volatility_accumulator_bps: u64,
+    /// Volatility carried over from before the current reference price, in bps of price movement
+    pub volatility_reference_bps: u64,
+    /// Spot price of token A in token B the volatility is measured against. A u128 fixed point
+    /// number stored as little endian words
+    pub price_reference: [u64; 2],
+    /// Unix timestamp of the last update
+    pub last_update_ts: u64,
+}
+
 #[account(zero_copy)]
 #[derive(Default, Debug, PartialEq)]
 pub struct SwapPool {
@@ -111,12 +160,54 @@ pub struct SwapPool {
     pub token_a_program: Pubkey,
     pub token_b_program: Pubkey,
 
+    /// Admin proposed by the current admin, becomes the pool admin once it accepts
+    pub pending_admin: Pubkey,
+
+    /// Cumulative spot prices, updated before every swap, deposit and withdrawal
+    pub price_accumulator: PriceAccumulator,
+
+    /// Trading token mints with the token 2022 interest-bearing extension, set at init
+    /// - `INTEREST_BEARING_TOKEN_A` and `INTEREST_BEARING_TOKEN_B` bit flags
+    pub interest_bearing_mints: u64,
+
+    /// Trading token account to receive the protocol share of the token A owner fees
+    pub token_a_protocol_fees_vault: Pubkey,
+    /// Trading token account to receive the protocol share of the token B owner fees
+    pub token_b_protocol_fees_vault: Pubkey,
+
+    /// Volatility-based trade fee, updated before every swap
+    pub dynamic_fee: DynamicFee,
+
     pub _padding: SwapPoolPadding,
 }
 
+/// `SwapPool::interest_bearing_mints` flag of an interest-bearing token A mint
+pub const INTEREST_BEARING_TOKEN_A: u64 = 1;
+/// `SwapPool::interest_bearing_mints` flag of an interest-bearing token B mint
+pub const INTEREST_BEARING_TOKEN_B: u64 = 2;
+
 impl SwapPool {
     // note: also hardcoded in /js/src/util/const.ts
     pub const LEN: usize = DISCRIMINATOR_SIZE + 4504; // 8 + 4504 = 4512
+
+    /// Whether the token A or B mint of the pool is interest-bearing
+    pub fn is_interest_bearing(&self, a_or_b: AorB) -> bool {
+        let flag = match a_or_b {
+            AorB::A => INTEREST_BEARING_TOKEN_A,
+            AorB::B => INTEREST_BEARING_TOKEN_B,
+        };
+        self.interest_bearing_mints & flag != 0
+    }
+
+    /// The protocol fees vault of the token A or B mint of the pool, `None` for pools created
+    /// before protocol fees until `initialize_protocol_fees_vaults` creates their vaults
+    pub fn protocol_fees_vault(&self, a_or_b: AorB) -> Option<Pubkey> {
+        let vault = match a_or_b {
+            AorB::A => self.token_a_protocol_fees_vault,
+            AorB::B => self.token_b_protocol_fees_vault,
+        };
+        (vault != Pubkey::default()).then_some(vault)
+    }
 }
 
 impl SwapState for SwapPool {
@@ -161,6 +252,213 @@ impl SwapState for SwapPool {
     }
 }
 
+/// Trading token of a multi-token stable pool
+#[zero_copy]
+#[derive(Debug, Default, PartialEq)]
+pub struct MultiStableToken {
+    /// Mint of the token
+    pub mint: Pubkey,
+    /// Token account holding the pool liquidity of the token
+    pub vault: Pubkey,
+    /// Token account to receive the owner trading fees of the token
+    pub fees_vault: Pubkey,
+    /// Token account to receive the protocol share of the owner trading fees of the token
+    pub protocol_fees_vault: Pubkey,
+    /// Token program of the mint, either the normal token program or token2022
+    pub token_program: Pubkey,
+    /// Amount to multiply the token by to scale it to the largest number of decimals in the pool
+    pub factor: u64,
+    /// Decimals of the mint
+    pub decimals: u64,
+}
+
+/// Stableswap pool of 3 to `multi_stable::MAX_TOKENS` tokens, swapping between any pair of them
+#[account(zero_copy)]
+#[derive(Default, Debug, PartialEq)]
+pub struct MultiStablePool {
+    /// Pool admin - account which initialised the pool
+    pub admin: Pubkey,
+    /// Pool authority PDA - holds authority of the vaults
+    pub pool_authority: Pubkey,
+    /// Bump seed used in pool authority program address
+    pub pool_authority_bump_seed: u64,
+
+    /// Pool tokens are issued when trading tokens are deposited
+    pub pool_token_mint: Pubkey,
+    /// Token program of the pool token mint
+    pub pool_token_program: Pubkey,
+
+    /// All fee information
+    pub fees: Fees,
+
+    /// Amplifier constant
+    pub amp: u64,
+
+    /// Number of tokens in use at the start of `tokens`
+    pub n_tokens: u64,
+
+    /// The pool is in withdraw mode, and will only allow withdrawals
+    pub withdrawals_only: u64,
+
+    /// Trading tokens of the pool, `multi_stable::MAX_TOKENS` of which the first `n_tokens` are used
+    pub tokens: [MultiStableToken; 8],
+
+    pub _padding: [u64; 32],
+}
+
+impl MultiStablePool {
+    pub const LEN: usize = DISCRIMINATOR_SIZE + 1888; // 8 + 1888 = 1896
+
+    /// Trading tokens in use
+    pub fn tokens(&self) -> &[MultiStableToken] {
+        let n_tokens = usize::try_from(self.n_tokens)
+            .unwrap()
+            .min(self.tokens.len());
+        &self.tokens[..n_tokens]
+    }
+
+    /// Index of the trading token with `mint`
+    pub fn token_index(&self, mint: &Pubkey) -> Result<usize> {
+        self.tokens()
+            .iter()
+            .position(|token| token.mint == *mint)
+            .ok_or_else(|| error!(SwapError::IncorrectTradingMint))
+    }
+
+    /// Stableswap curve of the pool tokens
+    pub fn curve(&self) -> MultiStableCurve {
+        MultiStableCurve {
+            amp: self.amp,
+            factors: self.tokens().iter().map(|token| token.factor).collect(),
+        }
+    }
+
+    pub fn bump_seed(&self) -> u8 {
+        u8::try_from(self.pool_authority_bump_seed).unwrap()
+    }
+
+    pub fn withdrawals_only(&self) -> bool {
+        self.withdrawals_only != 0
+    }
+}
+
+/// Maximum number of curve types the global config can allow
+pub const MAX_VALID_CURVE_TYPES: usize = 32;
+/// Maximum number of token 2022 extensions the global config can block
+pub const MAX_BLOCKED_TOKEN_EXTENSIONS: usize = 16;
+/// Maximum number of opt-in token 2022 extensions the global config can allow
+pub const MAX_ALLOWED_TOKEN_EXTENSIONS: usize = 16;
+
+/// Program wide constraints, used in multihost environments where the program
+/// may be used by multiple frontends, to ensure that proper fees are being
+/// assessed. Held in a single PDA owned by the program.
+#[account(zero_copy)]
+#[derive(Default, Debug, PartialEq)]
+pub struct GlobalConfig {
+    /// Owner of the program - can update the global config
+    pub owner: Pubkey,
+    /// Minimum fees pools must charge, except the host fee which pools must match exactly when set
+    pub min_fees: Fees,
+    /// Curve types pools can be created with and swapped on, zero entries are unused
+    pub valid_curve_types: [u8; MAX_VALID_CURVE_TYPES],
+    /// Token 2022 extensions trading token mints cannot have, zero (uninitialized) entries are unused
+    pub blocked_token_extensions: [u16; MAX_BLOCKED_TOKEN_EXTENSIONS],
+    /// Protocol treasury authority - can withdraw the protocol fees of every pool
+    pub protocol_authority: Pubkey,
+    /// Portion of the owner trading fees (after the host fee) sent to the protocol fees vaults
+    pub protocol_fee_numerator: u64,
+    pub protocol_fee_denominator: u64,
+    /// Opt-in token 2022 extensions trading token mints can have, zero (uninitialized) entries are unused
+    pub allowed_token_extensions: [u16; MAX_ALLOWED_TOKEN_EXTENSIONS],
+    pub _padding: [u64; 22],
+}
+
+impl GlobalConfig {
+    pub const LEN: usize = DISCRIMINATOR_SIZE + 416; // 8 + 416 = 424
+
+    pub fn valid_curve_types(&self) -> impl Iterator<Item = u64> + '_ {
+        self.valid_curve_types
+            .iter()
+            .take_while(|curve_type| **curve_type != 0)
+            .map(|curve_type| u64::from(*curve_type))
+    }
+
+    pub fn blocked_token_extensions(&self) -> impl Iterator<Item = u16> + '_ {
+        self.blocked_token_extensions
+            .iter()
+            .take_while(|extension| **extension != 0)
+            .copied()
+    }
+
+    pub fn allowed_token_extensions(&self) -> impl Iterator<Item = u16> + '_ {
+        self.allowed_token_extensions
+            .iter()
+            .take_while(|extension| **extension != 0)
+            .copied()
+    }
+
+    /// Calculate the protocol share of the owner trading fee
+    pub fn protocol_fee(&self, owner_fee: u128) -> Result<u128> {
+        calculate_fee(
+            owner_fee,
+            u128::from(self.protocol_fee_numerator),
+            u128::from(self.protocol_fee_denominator),
+            RoundDirection::Floor,
+        )
+    }
+
+    /// Replace the valid curve types, each must be a known `CurveType`
+    pub fn set_valid_curve_types(&mut self, curve_types: &[u64]) -> Result<()> {
+        if curve_types.len() > MAX_VALID_CURVE_TYPES {
+            return err!(SwapError::InvalidGlobalConfig);
+        }
+        let mut valid_curve_types = [0; MAX_VALID_CURVE_TYPES];
+        for (slot, curve_type) in valid_curve_types.iter_mut().zip(curve_types) {
+            let curve_type = CurveType::try_from(*curve_type)
+                .map_err(|_| error!(SwapError::UnsupportedCurveType))?;
+            *slot = u8::try_from(u64::from(curve_type))
+                .map_err(|_| error!(SwapError::UnsupportedCurveType))?;
+        }
+        self.valid_curve_types = valid_curve_types;
+        Ok(())
+    }
+
+    /// Replace the blocked token extensions, each must be a known `MintExtension`
+    pub fn set_blocked_token_extensions(&mut self, extensions: &[u16]) -> Result<()> {
+        if extensions.len() > MAX_BLOCKED_TOKEN_EXTENSIONS {
+            return err!(SwapError::InvalidGlobalConfig);
+        }
+        let mut blocked_token_extensions = [0; MAX_BLOCKED_TOKEN_EXTENSIONS];
+        for (slot, extension) in blocked_token_extensions.iter_mut().zip(extensions) {
+            if MintExtension::try_from(*extension).is_err() {
+                return err!(SwapError::InvalidTokenExtension);
+            }
+            *slot = *extension;
+        }
+        self.blocked_token_extensions = blocked_token_extensions;
+        Ok(())
+    }
+
+    /// Replace the allowed token extensions, each must be an opt-in `MintExtension`
+    pub fn set_allowed_token_extensions(&mut self, extensions: &[u16]) -> Result<()> {
+        if extensions.len() > MAX_ALLOWED_TOKEN_EXTENSIONS {
+            return err!(SwapError::InvalidGlobalConfig);
+        }
+        let mut allowed_token_extensions = [0; MAX_ALLOWED_TOKEN_EXTENSIONS];
+        for (slot, extension) in allowed_token_extensions.iter_mut().zip(extensions) {
+            let opt_in = MintExtension::try_from(*extension)
+                .map(|ext| ext.is_opt_in())
+                .unwrap_or(false);
+            if !opt_in {
+                return err!(SwapError::InvalidTokenExtension);
+            }
+            *slot = *extension;
+        }
+        self.allowed_token_extensions = allowed_token_extensions;
+        Ok(())
+    }
+}
+
 #[derive(
     Debug,
     TryFromPrimitive,
@@ -173,33 +471,142 @@ impl SwapState for SwapPool {
     AnchorDeserialize,
 )]
 #[repr(u16)]
-pub enum UpdatePoolConfigMode {
-    WithdrawalsOnly = 0,
+pub enum UpdateGlobalConfigMode {
+    /// `GlobalConfig::owner`
+    Owner = 0,
+    /// `GlobalConfig::min_fees` trade fee
+    MinTradeFee = 1,
+    /// `GlobalConfig::min_fees` owner trade fee
+    MinOwnerTradeFee = 2,
+    /// `GlobalConfig::min_fees` owner withdraw fee
+    MinOwnerWithdrawFee = 3,
+    /// `GlobalConfig::min_fees` host fee, which pools must match exactly
+    MinHostFee = 4,
+    /// `GlobalConfig::valid_curve_types`
+    ValidCurveTypes = 5,
+    /// `GlobalConfig::blocked_token_extensions`
+    BlockedTokenExtensions = 6,
+    /// `GlobalConfig::protocol_authority`
+    ProtocolAuthority = 7,
+    /// `GlobalConfig::protocol_fee_numerator` and `GlobalConfig::protocol_fee_denominator`
+    ProtocolFee = 8,
+    /// `GlobalConfig::allowed_token_extensions`
+    AllowedTokenExtensions = 9,
 }
 
 #[derive(PartialEq, Eq, Clone, Debug, AnchorSerialize, AnchorDeserialize)]
-pub enum UpdatePoolConfigValue {
-    Bool(bool),
+pub enum UpdateGlobalConfigValue {
+    Pubkey(Pubkey),
+    Fee { numerator: u64, denominator: u64 },
+    CurveTypes(Vec<u64>),
+    TokenExtensions(Vec<u16>),
 }
 
-impl Deref for UpdatePoolConfigValue {
-    type Target = bool;
-
-    fn deref(&self) -> &Self::Target {
+impl UpdateGlobalConfigValue {
+    pub fn to_bytes(&self) -> [u8; VALUE_BYTE_ARRAY_LEN] {
+        let mut val = [0; VALUE_BYTE_ARRAY_LEN];
         match self {
-            UpdatePoolConfigValue::Bool(v) => v,
+            UpdateGlobalConfigValue::Pubkey(v) => {
+                val.copy_from_slice(v.as_ref());
+                val
+            }
+            UpdateGlobalConfigValue::Fee {
+                numerator,
+                denominator,
+            } => {
+                val[..8].copy_from_slice(&numerator.to_le_bytes());
+                val[8..16].copy_from_slice(&denominator.to_le_bytes());
+                val
+            }
+            UpdateGlobalConfigValue::CurveTypes(curve_types) => {
+                for (b, curve_type) in val.iter_mut().zip(curve_types) {
+                    *b = *curve_type as u8;
+                }
+                val
+            }
+            UpdateGlobalConfigValue::TokenExtensions(extensions) => {
+                for (b, extension) in val.chunks_exact_mut(2).zip(extensions) {
+                    b.copy_from_slice(&extension.to_le_bytes());
+                }
+                val
+            }
         }
     }
-}
 
-impl UpdatePoolConfigValue {
-    pub fn to_u64(&self) -> u64 {
-        match self {
-            UpdatePoolConfigValue::Bool(v) => *v as u64,
+    pub fn from_pubkey_bytes(val: &[u8]) -> Result<Self> {
+        let key: [u8; 32] = val
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        Ok(UpdateGlobalConfigValue::Pubkey(Pubkey::new_from_array(key)))
+    }
+
+    /// Fee values are packed as a little-endian u64 numerator followed by a
+    /// little-endian u64 denominator, the remaining bytes must be zero
+    pub fn from_fee_bytes(val: &[u8]) -> Result<Self> {
+        if val.len() < 16 || val[16..].iter().any(|b| *b != 0) {
+            return Err(ProgramError::InvalidInstructionData.into());
+        }
+        let numerator = u64::from_le_bytes(val[..8].try_into().unwrap());
+        let denominator = u64::from_le_bytes(val[8..16].try_into().unwrap());
+        Ok(UpdateGlobalConfigValue::Fee {
+            numerator,
+            denominator,
+        })
+    }
+
+    /// Curve types are packed as one byte each, terminated by zero bytes
+    pub fn from_curve_types_bytes(val: &[u8]) -> Result<Self> {
+        let len = val.iter().take_while(|b| **b != 0).count();
+        if val[len..].iter().any(|b| *b != 0) {
+            return Err(ProgramError::InvalidInstructionData.into());
+        }
+        Ok(UpdateGlobalConfigValue::CurveTypes(
+            val[..len].iter().map(|b| u64::from(*b)).collect(),
+        ))
+    }
+
+    /// Token extensions are packed as little-endian u16s, terminated by zero values
+    pub fn from_token_extensions_bytes(val: &[u8]) -> Result<Self> {
+        let extensions: Vec<u16> = val
+            .chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .collect();
+        let len = extensions.iter().take_while(|e| **e != 0).count();
+        if extensions[len..].iter().any(|e| *e != 0) {
+            return Err(ProgramError::InvalidInstructionData.into());
         }
+        Ok(UpdateGlobalConfigValue::TokenExtensions(
+            extensions[..len].to_vec(),
+        ))
     }
 }
 
+#[derive(
+    Debug,
+    TryFromPrimitive,
+    EnumString,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    AnchorSerialize,
+    AnchorDeserialize,
+)]
+#[repr(u16)]
+pub enum UpdatePoolConfigMode {
+    WithdrawalsOnly = 0,
+    TradeFee = 1,
+    OwnerTradeFee = 2,
+    OwnerWithdrawFee = 3,
+    HostFee = 4,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub enum UpdatePoolConfigValue {
+    Bool(bool),
+    Fee { numerator: u64, denominator: u64 },
+}
+
 impl UpdatePoolConfigValue {
     pub fn to_bytes(&self) -> [u8; VALUE_BYTE_ARRAY_LEN] {
         let mut val = [0; VALUE_BYTE_ARRAY_LEN];
@@ -208,6 +615,14 @@ impl UpdatePoolConfigValue {
                 val[0] = *v as u8;
                 val
             }
+            UpdatePoolConfigValue::Fee {
+                numerator,
+                denominator,
+            } => {
+                val[..8].copy_from_slice(&numerator.to_le_bytes());
+                val[8..16].copy_from_slice(&denominator.to_le_bytes());
+                val
+            }
         }
     }
 
@@ -218,6 +633,70 @@ impl UpdatePoolConfigValue {
             _ => Err(ProgramError::InvalidInstructionData.into()),
         }
     }
+
+    /// Fee values are packed as a little-endian u64 numerator followed by a
+    /// little-endian u64 denominator, the remaining bytes must be zero
+    pub fn from_fee_bytes(val: &[u8]) -> Result<Self> {
+        if val.len() < 16 || val[16..].iter().any(|b| *b != 0) {
+            return Err(ProgramError::InvalidInstructionData.into());
+        }
+        let numerator = u64::from_le_bytes(val[..8].try_into().unwrap());
+        let denominator = u64::from_le_bytes(val[8..16].try_into().unwrap());
+        Ok(UpdatePoolConfigValue::Fee {
+            numerator,
+            denominator,
+        })
+    }
+}
+
+#[derive(
+    Debug,
+    TryFromPrimitive,
+    EnumString,
+    PartialEq,
+    Eq,
+    Clone,
+    Copy,
+    AnchorSerialize,
+    AnchorDeserialize,
+)]
+#[repr(u16)]
+pub enum UpdateCurveConfigMode {
+    /// `ConstantPriceCurve::token_b_price`
+    TokenBPrice = 0,
+    /// `OffsetCurve::token_b_offset`
+    TokenBOffset = 1,
+    /// `OraclePriceCurve::max_price_age_sec`
+    MaxPriceAgeSec = 2,
+    /// `OraclePriceCurve::max_price_confidence_bps`
+    MaxPriceConfidenceBps = 3,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug, AnchorSerialize, AnchorDeserialize)]
+pub enum UpdateCurveConfigValue {
+    U64(u64),
+}
+
+impl UpdateCurveConfigValue {
+    pub fn to_bytes(&self) -> [u8; VALUE_BYTE_ARRAY_LEN] {
+        let mut val = [0; VALUE_BYTE_ARRAY_LEN];
+        match self {
+            UpdateCurveConfigValue::U64(v) => {
+                val[..8].copy_from_slice(&v.to_le_bytes());
+                val
+            }
+        }
+    }
+
+    /// u64 values are packed as a little-endian u64, the remaining bytes must be zero
+    pub fn from_u64_bytes(val: &[u8]) -> Result<Self> {
+        if val.len() < 8 || val[8..].iter().any(|b| *b != 0) {
+            return Err(ProgramError::InvalidInstructionData.into());
+        }
+        Ok(UpdateCurveConfigValue::U64(u64::from_le_bytes(
+            val[..8].try_into().unwrap(),
+        )))
+    }
 }
 
 pub struct Curve {}
@@ -247,16 +726,78 @@ pub struct OffsetCurve {
     pub _padding: [u64; 15],
 }
 
+#[account]
+#[derive(Debug, PartialEq, Default)]
+pub struct OraclePriceCurve {
+    /// Price feed account that token B is priced from, in amount of token A per token B
+    pub price_feed: Pubkey,
+    /// Program that must own the price feed account
+    pub price_feed_program: Pubkey,
+    /// Maximum age of the price feed's publish time before the price is considered stale
+    pub max_price_age_sec: u64,
+    /// Maximum confidence interval of the price, in basis points of the price
+    pub max_price_confidence_bps: u64,
+    /// Amount of token A required to get 1 token B as a Q64.64 number, read from the price feed
+    /// when the curve is loaded
+    pub token_b_price_x64: u128,
+    pub _padding: [u64; 4],
+}
+
 #[account]
 #[derive(Debug, Default, PartialEq)]
 pub struct StableCurve {
-    /// Amplifier constant
+    /// Amplifier constant - the target amplifier while a ramp is in progress
     pub amp: u64,
     /// Amount of token A required to get 1 token B
     pub token_a_factor: u64,
     /// Amount of token B required to get 1 token A
     pub token_b_factor: u64,
-    pub _padding: [u64; 13],
+    /// Amplifier constant at the start of the current ramp
+    pub initial_amp: u64,
+    /// Amplifier constant at the end of the current ramp
+    pub target_amp: u64,
+    /// Unix timestamp at which the current ramp started
+    pub ramp_start_ts: u64,
+    /// Unix timestamp at which the current ramp ends, 0 if the amp has never been ramped
+    pub ramp_end_ts: u64,
+    /// `RateProviderType` of the token B rate
+    pub rate_provider_type: u64,
+    /// Stake pool account the rate is read from, or the authority allowed to update the rate
+    pub rate_provider: Pubkey,
+    /// Amount of token A per token B over `RATE_PRECISION`, read from the stake pool when the
+    /// curve is loaded or set by the rate updater
+    pub token_b_rate: u64,
+    /// Unix timestamp of the last rate update by the rate updater
+    pub rate_updated_ts: u64,
+    /// Maximum age of a rate set by the rate updater before it is considered stale
+    pub max_rate_age_sec: u64,
+    /// Maximum change of a rate set by the rate updater per `max_rate_age_sec` elapsed since the
+    /// previous update, in basis points of the previous rate
+    pub max_rate_change_bps: u64,
+}
+
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct ConcentratedCurve {
+    /// Lower bound of the price range, in amount of token A per token B as a Q64.64 number
+    pub price_lower_x64: u128,
+    /// Upper bound of the price range, in amount of token A per token B as a Q64.64 number
+    pub price_upper_x64: u128,
+    /// Square root of the lower price as a Q64.64 number, rounded down
+    pub sqrt_price_lower_x64: u128,
+    /// Square root of the upper price as a Q64.64 number, rounded up
+    pub sqrt_price_upper_x64: u128,
+    pub _padding: [u64; 8],
+}
+
+#[account]
+#[derive(Debug, Default, PartialEq)]
+pub struct WeightedCurve {
+    /// Weight of token A in basis points
+    pub token_a_weight_bps: u64,
+    /// Weight of token B in basis points, 10_000 - token_a_weight_bps
+    pub token_b_weight_bps: u64,
+    pub _padding: [u64; 14],
 }
 
 impl StableCurve {
@@ -265,13 +806,24 @@ impl StableCurve {
             amp,
             token_a_factor: try_math!(decimals_to_factor(token_a_decimals, token_b_decimals))?,
             token_b_factor: try_math!(decimals_to_factor(token_b_decimals, token_a_decimals))?,
-            _padding: [0; 13],
+            initial_amp: 0,
+            target_amp: 0,
+            ramp_start_ts: 0,
+            ramp_end_ts: 0,
+            rate_provider_type: RateProviderType::None.into(),
+            rate_provider: Pubkey::default(),
+            token_b_rate: 0,
+            rate_updated_ts: 0,
+            max_rate_age_sec: 0,
+            max_rate_change_bps: 0,
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use anchor_spl::token_2022::spl_token_2022::extension::ExtensionType;
+
     use super::*;
 
     #[test]
@@ -279,4 +831,197 @@ mod tests {
         let x = std::mem::size_of::<SwapPool>();
         assert_eq!(x, SwapPool::LEN - DISCRIMINATOR_SIZE);
     }
+
+    #[test]
+    fn test_multi_stable_pool_state_size() {
+        let x = std::mem::size_of::<MultiStablePool>();
+        assert_eq!(x, MultiStablePool::LEN - DISCRIMINATOR_SIZE);
+        assert_eq!(
+            MultiStablePool::default().tokens.len(),
+            crate::curve::multi_stable::MAX_TOKENS
+        );
+    }
+
+    #[test]
+    fn test_global_config_state_size() {
+        let x = std::mem::size_of::<GlobalConfig>();
+        assert_eq!(x, GlobalConfig::LEN - DISCRIMINATOR_SIZE);
+    }
+
+    #[test]
+    fn test_global_config_set_valid_curve_types() {
+        let mut global_config = GlobalConfig::default();
+        global_config
+            .set_valid_curve_types(&[CurveType::ConstantProduct.into(), CurveType::Stable.into()])
+            .unwrap();
+        assert_eq!(
+            global_config.valid_curve_types().collect::<Vec<_>>(),
+            vec![
+                u64::from(CurveType::ConstantProduct),
+                u64::from(CurveType::Stable)
+            ]
+        );
+
+        assert_eq!(
+            global_config.set_valid_curve_types(&[0]),
+            Err(SwapError::UnsupportedCurveType.into())
+        );
+        assert_eq!(
+            global_config.set_valid_curve_types(&[1; MAX_VALID_CURVE_TYPES + 1]),
+            Err(SwapError::InvalidGlobalConfig.into())
+        );
+    }
+
+    #[test]
+    fn test_global_config_set_blocked_token_extensions() {
+        let mut global_config = GlobalConfig::default();
+        global_config
+            .set_blocked_token_extensions(&[ExtensionType::TransferFeeConfig.into()])
+            .unwrap();
+        assert_eq!(
+            global_config.blocked_token_extensions().collect::<Vec<_>>(),
+            vec![u16::from(ExtensionType::TransferFeeConfig)]
+        );
+
+        global_config
+            .set_blocked_token_extensions(&[MintExtension::TransferHook.into()])
+            .unwrap();
+        assert_eq!(
+            global_config.blocked_token_extensions().collect::<Vec<_>>(),
+            vec![u16::from(MintExtension::TransferHook)]
+        );
+
+        assert_eq!(
+            global_config.set_blocked_token_extensions(&[ExtensionType::Uninitialized.into()]),
+            Err(SwapError::InvalidTokenExtension.into())
+        );
+        // account extensions are never found on mints
+        assert_eq!(
+            global_config.set_blocked_token_extensions(&[ExtensionType::ImmutableOwner.into()]),
+            Err(SwapError::InvalidTokenExtension.into())
+        );
+        assert_eq!(
+            global_config.set_blocked_token_extensions(&[u16::MAX]),
+            Err(SwapError::InvalidTokenExtension.into())
+        );
+    }
+
+    #[test]
+    fn test_global_config_set_allowed_token_extensions() {
+        let mut global_config = GlobalConfig::default();
+        assert_eq!(global_config.allowed_token_extensions().count(), 0);
+
+        global_config
+            .set_allowed_token_extensions(&[MintExtension::TransferHook.into()])
+            .unwrap();
+        assert_eq!(
+            global_config.allowed_token_extensions().collect::<Vec<_>>(),
+            vec![u16::from(MintExtension::TransferHook)]
+        );
+
+        global_config.set_allowed_token_extensions(&[]).unwrap();
+        assert_eq!(global_config.allowed_token_extensions().count(), 0);
+
+        // extensions supported by default and unsupported extensions cannot be opted into
+        assert_eq!(
+            global_config.set_allowed_token_extensions(&[ExtensionType::TransferFeeConfig.into()]),
+            Err(SwapError::InvalidTokenExtension.into())
+        );
+        assert_eq!(
+            global_config.set_allowed_token_extensions(&[ExtensionType::PermanentDelegate.into()]),
+            Err(SwapError::InvalidTokenExtension.into())
+        );
+        assert_eq!(
+            global_config.set_allowed_token_extensions(&[u16::MAX]),
+            Err(SwapError::InvalidTokenExtension.into())
+        );
+        assert_eq!(
+            global_config.set_allowed_token_extensions(
+                &[MintExtension::TransferHook.into(); MAX_ALLOWED_TOKEN_EXTENSIONS + 1]
+            ),
+            Err(SwapError::InvalidGlobalConfig.into())
+        );
+    }
+
+    #[test]
+    fn test_global_config_protocol_fee() {
+        let mut global_config = GlobalConfig::default();
+        assert_eq!(global_config.protocol_fee(1_000).unwrap(), 0);
+
+        global_config.protocol_fee_numerator = 1;
+        global_config.protocol_fee_denominator = 3;
+        assert_eq!(global_config.protocol_fee(1_000).unwrap(), 333);
+        // rounds down so dust stays with the pool admin
+        assert_eq!(global_config.protocol_fee(2).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_update_global_config_value_bytes_roundtrip() {
+        let value = UpdateGlobalConfigValue::Pubkey(Pubkey::new_unique());
+        assert_eq!(
+            UpdateGlobalConfigValue::from_pubkey_bytes(&value.to_bytes()).unwrap(),
+            value
+        );
+
+        let value = UpdateGlobalConfigValue::CurveTypes(vec![1, 4]);
+        assert_eq!(
+            UpdateGlobalConfigValue::from_curve_types_bytes(&value.to_bytes()).unwrap(),
+            value
+        );
+
+        let value = UpdateGlobalConfigValue::TokenExtensions(vec![1, 14]);
+        assert_eq!(
+            UpdateGlobalConfigValue::from_token_extensions_bytes(&value.to_bytes()).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn test_update_global_config_value_list_bytes_gap() {
+        let mut bytes = UpdateGlobalConfigValue::CurveTypes(vec![1]).to_bytes();
+        bytes[2] = 1;
+        assert!(UpdateGlobalConfigValue::from_curve_types_bytes(&bytes).is_err());
+
+        let mut bytes = UpdateGlobalConfigValue::TokenExtensions(vec![1]).to_bytes();
+        bytes[4] = 1;
+        assert!(UpdateGlobalConfigValue::from_token_extensions_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_update_pool_config_value_fee_bytes_roundtrip() {
+        let value = UpdatePoolConfigValue::Fee {
+            numerator: 25,
+            denominator: 10_000,
+        };
+        let bytes = value.to_bytes();
+        assert_eq!(
+            UpdatePoolConfigValue::from_fee_bytes(&bytes).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn test_update_pool_config_value_fee_bytes_trailing_data() {
+        let mut bytes = UpdatePoolConfigValue::Fee {
+            numerator: 25,
+            denominator: 10_000,
+        }
+        .to_bytes();
+        bytes[16] = 1;
+        assert!(UpdatePoolConfigValue::from_fee_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_update_curve_config_value_u64_bytes_roundtrip() {
+        let value = UpdateCurveConfigValue::U64(1_251_258);
+        let bytes = value.to_bytes();
+        assert_eq!(
+            UpdateCurveConfigValue::from_u64_bytes(&bytes).unwrap(),
+            value
+        );
+
+        let mut bytes = bytes;
+        bytes[8] = 1;
+        assert!(UpdateCurveConfigValue::from_u64_bytes(&bytes).is_err());
+    }
 }
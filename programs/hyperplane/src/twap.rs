@@ -0,0 +1,328 @@
+//! Uniswap v2 style time weighted average prices (TWAP)
+//!
+//! Before every swap, deposit and withdrawal the spot prices of the pool, multiplied by the
+//! seconds since the previous update, are added to the pool's price accumulators. The time
+//! weighted average price between two observations of a pool is the difference between their
+//! cumulative prices divided by the time between them.
+//!
+//! The accumulators wrap on overflow, only the difference between two observations is meaningful.
+
+use anchor_lang::{
+    error,
+    prelude::{msg, Clock, SolanaSysvar},
+    Result,
+};
+use spl_math::precise_number::PreciseNumber;
+
+use crate::{
+    curve::calculator::{CurveCalculator, TradeDirection},
+    error::SwapError,
+    require_msg,
+    state::PriceAccumulator,
+    utils::math::{TryMathRef, TryNew},
+};
+
+/// Cumulative prices are fixed point numbers with 12 decimals
+pub const PRICE_SCALE: u128 = 1_000_000_000_000;
+
+impl PriceAccumulator {
+    /// Accumulators of a pool created at `now`
+    pub fn new(now: u64) -> Self {
+        Self {
+            last_update_ts: now,
+            ..Default::default()
+        }
+    }
+
+    /// Sum of the spot prices of token A in token B multiplied by the seconds they were in effect,
+    /// scaled by `PRICE_SCALE`
+    pub fn price_a_cumulative(&self) -> u128 {
+        from_words(self.price_a_cumulative)
+    }
+
+    /// Sum of the spot prices of token B in token A multiplied by the seconds they were in effect,
+    /// scaled by `PRICE_SCALE`
+    pub fn price_b_cumulative(&self) -> u128 {
+        from_words(self.price_b_cumulative)
+    }
+
+    /// Accumulate the spot prices of the pool balances before they change, as of the on-chain clock
+    pub fn update(
+        &mut self,
+        calculator: &dyn CurveCalculator,
+        pool_token_a_amount: u64,
+        pool_token_b_amount: u64,
+    ) -> Result<()> {
+        let now = u64::try_from(Clock::get()?.unix_timestamp)
+            .map_err(|_| error!(SwapError::ConversionFailure))?;
+        self.update_at(calculator, pool_token_a_amount, pool_token_b_amount, now)
+    }
+
+    /// Accumulate the spot prices of the pool balances like `update`, skipping the update when the
+    /// spot prices cannot be computed, so that proportional deposits and withdrawals, which do not
+    /// depend on the price, never fail because of the accumulators
+    pub fn update_best_effort(
+        &mut self,
+        calculator: &dyn CurveCalculator,
+        pool_token_a_amount: u64,
+        pool_token_b_amount: u64,
+    ) {
+        if let Err(e) = self.update(calculator, pool_token_a_amount, pool_token_b_amount) {
+            msg!("Skipping price accumulator update: {}", e);
+        }
+    }
+
+    /// Accumulate the spot prices of the pool balances like `update_at`, skipping the update when
+    /// the spot prices cannot be computed
+    pub fn update_best_effort_at(
+        &mut self,
+        calculator: &dyn CurveCalculator,
+        pool_token_a_amount: u64,
+        pool_token_b_amount: u64,
+        now: u64,
+    ) {
+        if let Err(e) = self.update_at(calculator, pool_token_a_amount, pool_token_b_amount, now) {
+            msg!("Skipping price accumulator update: {}", e);
+        }
+    }
+
+    /// Accumulate the spot prices of the pool balances before they change, as of `now`
+    ///
+    /// Nothing is accumulated while either side of the pool is empty, since it has no price.
+    pub fn update_at(
+        &mut self,
+        calculator: &dyn CurveCalculator,
+        pool_token_a_amount: u64,
+        pool_token_b_amount: u64,
+        now: u64,
+    ) -> Result<()> {
+        let elapsed = u128::from(now.saturating_sub(self.last_update_ts));
+        // pools created before the accumulators existed start from their first update
+        if self.last_update_ts > 0
+            && elapsed > 0
+            && pool_token_a_amount > 0
+            && pool_token_b_amount > 0
+        {
+            let pool_token_a_amount = u128::from(pool_token_a_amount);
+            let pool_token_b_amount = u128::from(pool_token_b_amount);
+            let price_a = to_fixed_point(calculator.spot_price(
+                pool_token_a_amount,
+                pool_token_b_amount,
+                TradeDirection::AtoB,
+            )?)?;
+            let price_b = to_fixed_point(calculator.spot_price(
+                pool_token_a_amount,
+                pool_token_b_amount,
+                TradeDirection::BtoA,
+            )?)?;
+
+            self.price_a_cumulative = to_words(
+                self.price_a_cumulative()
+                    .wrapping_add(price_a.wrapping_mul(elapsed)),
+            );
+            self.price_b_cumulative = to_words(
+                self.price_b_cumulative()
+                    .wrapping_add(price_b.wrapping_mul(elapsed)),
+            );
+        }
+        self.last_update_ts = self.last_update_ts.max(now);
+        Ok(())
+    }
+}
+
+/// Time weighted average prices between two observations of a pool
+#[derive(Clone, Debug, PartialEq)]
+pub struct Twap {
+    /// Average price of token A in token B
+    pub price_a: PreciseNumber,
+    /// Average price of token B in token A
+    pub price_b: PreciseNumber,
+}
+
+/// Compute the time weighted average prices between an earlier and a later observation of the
+/// price accumulators of the same pool.
+///
+/// The accumulators are only written when the pool is used, to observe them at the current time
+/// call `PriceAccumulator::update_at` on a copy of the pool's accumulators with the current
+/// vault balances.
+pub fn get_twap(start: &PriceAccumulator, end: &PriceAccumulator) -> Result<Twap> {
+    require_msg!(
+        end.last_update_ts > start.last_update_ts,
+        SwapError::CalculationFailure,
+        &format!(
+            "TWAP end observation at {} must be after the start observation at {}",
+            end.last_update_ts, start.last_update_ts
+        )
+    );
+    let elapsed = PreciseNumber::try_new(
+        u128::from(end.last_update_ts - start.last_update_ts) * PRICE_SCALE,
+    )?;
+
+    Ok(Twap {
+        price_a: PreciseNumber::try_new(
+            end.price_a_cumulative()
+                .wrapping_sub(start.price_a_cumulative()),
+        )?
+        .try_div(&elapsed)?,
+        price_b: PreciseNumber::try_new(
+            end.price_b_cumulative()
+                .wrapping_sub(start.price_b_cumulative()),
+        )?
+        .try_div(&elapsed)?,
+    })
+}
+
+pub(crate) fn to_fixed_point(price: PreciseNumber) -> Result<u128> {
+    price
+        .try_mul(&PreciseNumber::try_new(PRICE_SCALE)?)?
+        .try_floor()?
+        .try_to_imprecise()
+}
+
+pub(crate) fn from_words(words: [u64; 2]) -> u128 {
+    u128::from(words[0]) | (u128::from(words[1]) << 64)
+}
+
+pub(crate) fn to_words(value: u128) -> [u64; 2] {
+    [value as u64, (value >> 64) as u64]
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::Pubkey;
+    use spl_math::uint::U256;
+
+    use super::*;
+    use crate::state::{ConstantPriceCurve, ConstantProductCurve, OraclePriceCurve, StableCurve};
+
+    fn precise(numerator: u128, denominator: u128) -> PreciseNumber {
+        PreciseNumber::new(numerator)
+            .unwrap()
+            .checked_div(&PreciseNumber::new(denominator).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn words_roundtrip() {
+        for value in [0, 1, u64::MAX as u128, u64::MAX as u128 + 1, u128::MAX] {
+            assert_eq!(from_words(to_words(value)), value);
+        }
+    }
+
+    #[test]
+    fn accumulates_spot_prices_over_time() {
+        let curve = ConstantProductCurve::default();
+        let mut accumulator = PriceAccumulator::new(1_000);
+
+        // 2 B per A for 100 seconds
+        accumulator.update_at(&curve, 1_000, 2_000, 1_100).unwrap();
+        assert_eq!(accumulator.last_update_ts, 1_100);
+        assert_eq!(accumulator.price_a_cumulative(), 200 * PRICE_SCALE);
+        assert_eq!(accumulator.price_b_cumulative(), 50 * PRICE_SCALE);
+        let start = accumulator;
+
+        // 4 B per A for 300 seconds
+        accumulator.update_at(&curve, 1_000, 4_000, 1_400).unwrap();
+        assert_eq!(accumulator.price_a_cumulative(), 1_400 * PRICE_SCALE);
+        assert_eq!(accumulator.price_b_cumulative(), 125 * PRICE_SCALE);
+
+        let twap = get_twap(&start, &accumulator).unwrap();
+        assert_eq!(twap.price_a, PreciseNumber::new(4).unwrap());
+        assert_eq!(twap.price_b, precise(1, 4));
+
+        let twap = get_twap(&PriceAccumulator::new(1_000), &accumulator).unwrap();
+        assert_eq!(twap.price_a, precise(7, 2));
+        assert_eq!(twap.price_b, precise(5, 16));
+    }
+
+    #[test]
+    fn skips_empty_pools_and_repeated_timestamps() {
+        let curve = ConstantProductCurve::default();
+        let mut accumulator = PriceAccumulator::new(1_000);
+
+        accumulator.update_at(&curve, 0, 2_000, 1_100).unwrap();
+        assert_eq!(accumulator, PriceAccumulator::new(1_100));
+
+        accumulator.update_at(&curve, 1_000, 2_000, 1_100).unwrap();
+        assert_eq!(accumulator, PriceAccumulator::new(1_100));
+
+        // the clock going backwards does not move the last update back
+        accumulator.update_at(&curve, 1_000, 2_000, 1_050).unwrap();
+        assert_eq!(accumulator, PriceAccumulator::new(1_100));
+    }
+
+    #[test]
+    fn pools_without_a_start_time_start_on_first_update() {
+        let curve = ConstantProductCurve::default();
+        let mut accumulator = PriceAccumulator::default();
+
+        accumulator.update_at(&curve, 1_000, 2_000, 1_000).unwrap();
+        assert_eq!(accumulator, PriceAccumulator::new(1_000));
+    }
+
+    #[test]
+    fn best_effort_update_skips_unpriced_pools() {
+        // the price of an oracle curve is only known once loaded from its price feed
+        let curve = OraclePriceCurve::new(Pubkey::new_unique(), Pubkey::new_unique(), 60, 100);
+        let mut accumulator = PriceAccumulator::new(1_000);
+
+        assert!(accumulator.update_at(&curve, 1_000, 2_000, 1_100).is_err());
+        accumulator.update_best_effort_at(&curve, 1_000, 2_000, 1_100);
+        assert_eq!(accumulator, PriceAccumulator::new(1_000));
+
+        // priced pools are accumulated as usual
+        let curve = ConstantProductCurve::default();
+        accumulator.update_best_effort_at(&curve, 1_000, 2_000, 1_100);
+        assert_eq!(accumulator.last_update_ts, 1_100);
+        assert_eq!(accumulator.price_a_cumulative(), 200 * PRICE_SCALE);
+    }
+
+    #[test]
+    fn accumulators_wrap_on_overflow() {
+        let curve = ConstantPriceCurve {
+            token_b_price: 3,
+            ..Default::default()
+        };
+        let mut start = PriceAccumulator::new(1_000);
+        start.price_b_cumulative = to_words(u128::MAX - PRICE_SCALE);
+        let mut end = start;
+
+        end.update_at(&curve, 1, 1, 1_010).unwrap();
+        assert!(end.price_b_cumulative() < start.price_b_cumulative());
+
+        let twap = get_twap(&start, &end).unwrap();
+        assert_eq!(twap.price_b, PreciseNumber::new(3).unwrap());
+        assert!(twap
+            .price_a
+            .almost_eq(&precise(1, 3), U256::from(PRICE_SCALE / 1_000_000)));
+    }
+
+    #[test]
+    fn stable_prices_near_balance_are_near_par() {
+        let curve = StableCurve {
+            amp: 100,
+            token_a_factor: 1,
+            token_b_factor: 1,
+            ..Default::default()
+        };
+        let mut start = PriceAccumulator::new(1_000);
+        start
+            .update_at(&curve, 1_000_000, 1_000_000, 1_000)
+            .unwrap();
+        let mut end = start;
+        end.update_at(&curve, 1_000_000, 1_000_000, 2_000).unwrap();
+
+        let twap = get_twap(&start, &end).unwrap();
+        assert_eq!(twap.price_a, PreciseNumber::new(1).unwrap());
+        assert_eq!(twap.price_b, PreciseNumber::new(1).unwrap());
+    }
+
+    #[test]
+    fn twap_requires_later_end_observation() {
+        let accumulator = PriceAccumulator::new(1_000);
+        assert_eq!(
+            get_twap(&accumulator, &accumulator).unwrap_err(),
+            error!(SwapError::CalculationFailure)
+        );
+    }
+}
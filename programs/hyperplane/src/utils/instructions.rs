@@ -3,7 +3,7 @@ use std::cell::Ref;
 use anchor_lang::{
     __private::bytemuck,
     prelude::{error::ErrorCode as AnchorError, *},
-    AccountDeserialize, Discriminator, Key,
+    system_program, AccountDeserialize, Discriminator, Key,
 };
 
 pub fn deserialize<T: AccountDeserialize + Discriminator>(account: &AccountInfo<'_>) -> Result<T> {
@@ -61,3 +61,21 @@ pub fn zero_copy_deserialize<'info, T: bytemuck::AnyBitPattern + Discriminator>(
 
     Ok(Ref::map(data, |data| bytemuck::from_bytes(&data[8..])))
 }
+
+/// Close a program owned account which is not deserialized by anchor, returning its lamports to `destination`
+pub fn close_program_account<'info>(
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or_else(|| error!(crate::error::SwapError::CalculationFailure))?;
+    **account.try_borrow_mut_lamports()? = 0;
+
+    account.assign(&system_program::ID);
+    account.realloc(0, false)?;
+
+    Ok(())
+}
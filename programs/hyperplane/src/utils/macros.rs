@@ -1,7 +1,11 @@
-/// macro to deserialize a curve account depending on the curve type
+/// macro to deserialize a curve account depending on the curve type, loading the price of
+/// oracle priced curves and the rate of stable curves from the remaining accounts
+///
+/// Without remaining accounts the price and rate are not loaded, for operations that do not depend
+/// on them like proportional withdrawals, so a stale or missing price feed cannot lock LPs out.
 #[macro_export]
 macro_rules! curve {
-    ($swap_curve_info: expr, $pool: expr) => {
+    (@load $swap_curve_info: expr, $pool: expr, $remaining_accounts: expr) => {
         match $pool.curve_type() {
             $crate::curve::base::CurveType::ConstantProduct => {
                 let calculator = $crate::utils::instructions::deserialize::<
@@ -31,9 +35,42 @@ macro_rules! curve {
                 }
             }
             $crate::curve::base::CurveType::Stable => {
-                let calculator = $crate::utils::instructions::deserialize::<
+                let mut calculator = $crate::utils::instructions::deserialize::<
                     $crate::state::StableCurve,
                 >(&$swap_curve_info)?;
+                if let Some(remaining_accounts) = $remaining_accounts {
+                    calculator.load_rate(remaining_accounts)?;
+                }
+                SwapCurve {
+                    calculator: std::sync::Arc::new(calculator),
+                    curve_type: $pool.curve_type(),
+                }
+            }
+            $crate::curve::base::CurveType::OraclePrice => {
+                let mut calculator = $crate::utils::instructions::deserialize::<
+                    $crate::state::OraclePriceCurve,
+                >(&$swap_curve_info)?;
+                if let Some(remaining_accounts) = $remaining_accounts {
+                    calculator.load_price(remaining_accounts)?;
+                }
+                SwapCurve {
+                    calculator: std::sync::Arc::new(calculator),
+                    curve_type: $pool.curve_type(),
+                }
+            }
+            $crate::curve::base::CurveType::Concentrated => {
+                let calculator = $crate::utils::instructions::deserialize::<
+                    $crate::state::ConcentratedCurve,
+                >(&$swap_curve_info)?;
+                SwapCurve {
+                    calculator: std::sync::Arc::new(calculator),
+                    curve_type: $pool.curve_type(),
+                }
+            }
+            $crate::curve::base::CurveType::Weighted => {
+                let calculator = $crate::utils::instructions::deserialize::<
+                    $crate::state::WeightedCurve,
+                >(&$swap_curve_info)?;
                 SwapCurve {
                     calculator: std::sync::Arc::new(calculator),
                     curve_type: $pool.curve_type(),
@@ -41,14 +78,24 @@ macro_rules! curve {
             }
         }
     };
+    ($swap_curve_info: expr, $pool: expr) => {
+        $crate::curve!(
+            @load $swap_curve_info,
+            $pool,
+            None::<&[$crate::anchor_lang::prelude::AccountInfo]>
+        )
+    };
+    ($swap_curve_info: expr, $pool: expr, $remaining_accounts: expr) => {
+        $crate::curve!(@load $swap_curve_info, $pool, Some($remaining_accounts))
+    };
 }
 
 #[macro_export]
 macro_rules! require_msg {
     ($invariant:expr, $error:expr $(,)?, $message: expr) => {
         if !($invariant) {
-            ::anchor_lang::prelude::msg!($message);
-            return Err(anchor_lang::error!($error));
+            $crate::anchor_lang::prelude::msg!($message);
+            return Err($crate::anchor_lang::error!($error));
         }
     };
 }
@@ -64,7 +111,7 @@ macro_rules! dbg_msg {
         #[cfg(not(target_arch = "bpf"))]
         println!("[{}:{}]", file!(), line!())
         #[cfg(target_arch = "bpf")]
-        ::anchor_lang::prelude::msg!("[{}:{}]", file!(), line!())
+        $crate::anchor_lang::prelude::msg!("[{}:{}]", file!(), line!())
     };
     ($val:expr $(,)?) => {
         // Use of `match` here is intentional because it affects the lifetimes
@@ -75,7 +122,7 @@ macro_rules! dbg_msg {
                 println!("[{}:{}] {} = {:#?}",
                     file!(), line!(), stringify!($val), &tmp);
                 #[cfg(target_arch = "bpf")]
-                ::anchor_lang::prelude::msg!("[{}:{}] {} = {:#?}",
+                $crate::anchor_lang::prelude::msg!("[{}:{}] {} = {:#?}",
                     file!(), line!(), stringify!($val), &tmp);
                 tmp
             }
@@ -90,7 +137,7 @@ macro_rules! dbg_msg {
 #[macro_export]
 macro_rules! emitted {
     ($event: expr) => {
-        ::anchor_lang::prelude::emit!($event.clone());
+        $crate::anchor_lang::prelude::emit!($event.clone());
         #[allow(clippy::redundant_clone)]
         return Ok($event);
     };
@@ -101,8 +148,12 @@ macro_rules! emitted {
 macro_rules! to_u64 {
     ($val: expr) => {
         u64::try_from($val).map_err(|_| {
-            ::anchor_lang::prelude::msg!("Unable to convert {} to u64: {}", stringify!($val), $val);
-            ::anchor_lang::error!(SwapError::ConversionFailure)
+            $crate::anchor_lang::prelude::msg!(
+                "Unable to convert {} to u64: {}",
+                stringify!($val),
+                $val
+            );
+            $crate::anchor_lang::error!(SwapError::ConversionFailure)
         })
     };
 }
@@ -112,8 +163,8 @@ macro_rules! to_u64 {
 macro_rules! try_math {
     ($val: expr) => {
         $val.map_err(|_| {
-            ::anchor_lang::prelude::msg!("[{}:{}] {}", file!(), line!(), stringify!($val));
-            ::anchor_lang::error!($crate::error::SwapError::CalculationFailure)
+            $crate::anchor_lang::prelude::msg!("[{}:{}] {}", file!(), line!(), stringify!($val));
+            $crate::anchor_lang::error!($crate::error::SwapError::CalculationFailure)
         })
     };
 }
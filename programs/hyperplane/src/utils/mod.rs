@@ -1,7 +1,14 @@
+#[cfg(feature = "program")]
 pub mod instructions;
 pub mod macros;
 pub mod math;
+#[cfg(feature = "program")]
 pub mod pool_pda;
+#[cfg(feature = "program")]
 pub mod pool_token;
+pub mod pool_transfers;
 pub mod seeds;
+#[cfg(feature = "program")]
 pub mod swap_token;
+pub mod token_extensions;
+pub mod transfer_fee;
@@ -1,9 +1,180 @@
 use anchor_lang::{
-    prelude::{AccountInfo, CpiContext},
+    prelude::{AccountInfo, CpiContext, Rent, SolanaSysvar},
+    solana_program::program::{invoke, invoke_signed},
     Result,
 };
+use anchor_spl::{
+    token::{Mint, TokenAccount},
+    token_2022::spl_token_2022,
+};
+use spl_token_2022_latest::extension::{metadata_pointer, ExtensionType};
+use spl_token_metadata_interface::state::TokenMetadata;
+
+use crate::{instructions::PoolTokenMetadata, utils::seeds};
+
+/// Decimals of the pool token mint
+pub const POOL_TOKEN_DECIMALS: u8 = 6;
+
+/// Create the pool token mint, minted by the pool authority
+///
+/// Token-2022 mints are closable by the pool authority, so `close_pool` can reclaim their rent,
+/// and are created with `metadata`, stored in the mint itself behind a metadata pointer. The pool
+/// authority is the metadata update authority. Spl token mints cannot be closed.
+#[allow(clippy::too_many_arguments)]
+pub fn create_mint<'info>(
+    token_program: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    admin: &AccountInfo<'info>,
+    pool: &AccountInfo<'info>,
+    pool_token_mint: &AccountInfo<'info>,
+    pool_token_mint_bump: u8,
+    pool_authority: &AccountInfo<'info>,
+    pool_authority_bump: u8,
+    metadata: Option<PoolTokenMetadata>,
+) -> Result<()> {
+    let mint_seeds = [
+        seeds::POOL_TOKEN_MINT,
+        pool.key.as_ref(),
+        &[pool_token_mint_bump],
+    ];
+    let is_token_2022 = *token_program.key == spl_token_2022::id();
+    let (space, lamports) = match &metadata {
+        Some(PoolTokenMetadata { name, symbol, uri }) => {
+            // the token metadata entry is allocated by the token program, the mint is funded for it
+            let metadata_len = TokenMetadata {
+                mint: *pool_token_mint.key,
+                name: name.clone(),
+                symbol: symbol.clone(),
+                uri: uri.clone(),
+                ..Default::default()
+            }
+            .tlv_size_of()?;
+            let space = mint_len(&[
+                ExtensionType::MintCloseAuthority,
+                ExtensionType::MetadataPointer,
+            ])?;
+            (space, Rent::get()?.minimum_balance(space + metadata_len))
+        }
+        None if is_token_2022 => {
+            let space = mint_len(&[ExtensionType::MintCloseAuthority])?;
+            (space, Rent::get()?.minimum_balance(space))
+        }
+        None => (Mint::LEN, Rent::get()?.minimum_balance(Mint::LEN)),
+    };
+    anchor_lang::system_program::create_account(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            anchor_lang::system_program::CreateAccount {
+                from: admin.clone(),
+                to: pool_token_mint.clone(),
+            },
+            &[&mint_seeds[..]],
+        ),
+        lamports,
+        space as u64,
+        token_program.key,
+    )?;
 
-use crate::utils::seeds;
+    if is_token_2022 {
+        invoke(
+            &spl_token_2022::instruction::initialize_mint_close_authority(
+                token_program.key,
+                pool_token_mint.key,
+                Some(pool_authority.key),
+            )?,
+            &[pool_token_mint.clone(), token_program.clone()],
+        )?;
+    }
+    if metadata.is_some() {
+        // the metadata is stored in the mint itself, with no authority to point it elsewhere
+        invoke(
+            &metadata_pointer::instruction::initialize(
+                token_program.key,
+                pool_token_mint.key,
+                None,
+                Some(*pool_token_mint.key),
+            )?,
+            &[pool_token_mint.clone(), token_program.clone()],
+        )?;
+    }
+
+    anchor_spl::token_2022::initialize_mint2(
+        CpiContext::new(
+            token_program.clone(),
+            anchor_spl::token_2022::InitializeMint2 {
+                mint: pool_token_mint.clone(),
+            },
+        ),
+        POOL_TOKEN_DECIMALS,
+        pool_authority.key,
+        None,
+    )?;
+
+    if let Some(PoolTokenMetadata { name, symbol, uri }) = metadata {
+        let authority_seeds = [
+            seeds::POOL_AUTHORITY,
+            pool.key.as_ref(),
+            &[pool_authority_bump],
+        ];
+        invoke_signed(
+            &spl_token_metadata_interface::instruction::initialize(
+                token_program.key,
+                pool_token_mint.key,
+                pool_authority.key,
+                pool_token_mint.key,
+                pool_authority.key,
+                name,
+                symbol,
+                uri,
+            ),
+            &[
+                pool_token_mint.clone(),
+                pool_authority.clone(),
+                token_program.clone(),
+            ],
+            &[&authority_seeds[..]],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Length of a token-2022 mint with the given extensions
+fn mint_len(extension_types: &[ExtensionType]) -> Result<usize> {
+    Ok(ExtensionType::try_calculate_account_len::<
+        spl_token_2022_latest::state::Mint,
+    >(extension_types)?)
+}
+
+/// Create a token account of `owner` for the pool token mint
+pub fn create_token_account<'info>(
+    token_program: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    owner: &AccountInfo<'info>,
+    pool_token_mint: &AccountInfo<'info>,
+    token_account: &AccountInfo<'info>,
+) -> Result<()> {
+    anchor_lang::system_program::create_account(
+        CpiContext::new(
+            system_program.clone(),
+            anchor_lang::system_program::CreateAccount {
+                from: owner.clone(),
+                to: token_account.clone(),
+            },
+        ),
+        Rent::get()?.minimum_balance(TokenAccount::LEN),
+        TokenAccount::LEN as u64,
+        token_program.key,
+    )?;
+    anchor_spl::token_2022::initialize_account3(CpiContext::new(
+        token_program.clone(),
+        anchor_spl::token_2022::InitializeAccount3 {
+            account: token_account.clone(),
+            mint: pool_token_mint.clone(),
+            authority: owner.clone(),
+        },
+    ))
+}
 
 /// Issue an spl_token or spl_token_2022 `Mint` instruction.
 pub fn mint<'info>(
@@ -59,3 +230,34 @@ pub fn burn<'info>(
 
     Ok(())
 }
+
+/// Close an empty token-2022 pool token mint, returning its rent to `destination`
+///
+/// The pool authority must be the mint close authority, as set by `create_mint`
+pub fn close_mint<'info>(
+    token_program: AccountInfo<'info>,
+    pool: AccountInfo<'info>,
+    pool_token_mint: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    pool_authority: AccountInfo<'info>,
+    pool_authority_bump: u8,
+) -> Result<()> {
+    let inner_seeds = [
+        seeds::POOL_AUTHORITY,
+        pool.key.as_ref(),
+        &[pool_authority_bump],
+    ];
+    let signer_seeds = &[&inner_seeds[..]];
+
+    anchor_spl::token_2022::close_account(CpiContext::new_with_signer(
+        token_program,
+        anchor_spl::token_2022::CloseAccount {
+            account: pool_token_mint,
+            destination,
+            authority: pool_authority,
+        },
+        signer_seeds,
+    ))?;
+
+    Ok(())
+}
@@ -0,0 +1,411 @@
+//! Token transfers of the swap, deposit and withdraw instructions, independent of the accounts
+//! and clock they are read from
+//!
+//! Shared by the instruction handlers and off-chain quotes, so quotes transfer exactly what the
+//! instructions do.
+
+use anchor_lang::{error, prelude::msg, Result};
+
+use crate::{
+    curve::{
+        base::{SwapCurve, SwapResult},
+        calculator::{AorB, CurveCalculator, RoundDirection, TradeDirection},
+        fees::Fees,
+    },
+    error::SwapError,
+    event, require_msg,
+    state::GlobalConfig,
+    to_u64, try_math,
+    utils::{math::TryMath, transfer_fee::MintTransferFee},
+};
+
+/// Pool state a swap is calculated from
+pub struct SwapParams<'a> {
+    pub swap_curve: &'a SwapCurve,
+    /// Fees of the swap, including the dynamic trade fee of the pool if enabled
+    pub fees: &'a Fees,
+    pub trade_direction: TradeDirection,
+    pub source_vault_amount: u64,
+    pub destination_vault_amount: u64,
+    pub source_transfer_fee: MintTransferFee,
+    pub destination_transfer_fee: MintTransferFee,
+    /// Whether the swap pays a host fee out of the owner fee
+    pub host_fee: bool,
+    /// Global config for the protocol share of the owner fee, `None` for pools without a protocol
+    /// fees vault for the source token, which pay no protocol fees
+    pub protocol_fee: Option<&'a GlobalConfig>,
+}
+
+/// Token transfers of a swap, the amounts transferred include transfer fees
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SwapTransfers {
+    /// Amount transferred from the user to the source vault
+    pub source_amount_to_vault: u64,
+    /// Amount transferred from the user to the source token fees vault
+    pub owner_fee_to_vault: u64,
+    /// Amount transferred from the user to the host fees account
+    pub host_fee_to_account: u64,
+    /// Amount transferred from the user to the source token protocol fees vault
+    pub protocol_fee_to_vault: u64,
+    /// Amount transferred from the destination vault to the user
+    pub destination_amount_from_vault: u64,
+    /// Amount credited to the user's destination token account (excludes: transfer fees)
+    pub destination_amount: u64,
+    /// Total fees paid in source tokens (includes: owner, trading, host + protocol fees)
+    pub total_fees: u64,
+    /// Trading fee kept in the pool for liquidity providers
+    pub trade_fee: u64,
+    /// Owner fee (excludes: host + protocol fees, transfer fees)
+    pub owner_fee: u64,
+    /// Portion of the owner fee paid to the host (excludes: transfer fees)
+    pub host_fee: u64,
+    /// Portion of the owner fee paid to the protocol (excludes: transfer fees)
+    pub protocol_fee: u64,
+    /// Effective trade fee fraction of the swap
+    pub trade_fee_numerator: u64,
+    pub trade_fee_denominator: u64,
+}
+
+impl SwapTransfers {
+    /// Total amount debited from the user (includes: all fees + transfer fees)
+    pub fn source_amount(&self) -> Result<u64> {
+        try_math!(self
+            .source_amount_to_vault
+            .try_add(self.owner_fee_to_vault)?
+            .try_add(self.host_fee_to_account)?
+            .try_add(self.protocol_fee_to_vault))
+    }
+
+    /// The swap event of the transfers
+    pub fn event(&self) -> event::Swap {
+        event::Swap {
+            token_in_amount: self.source_amount_to_vault,
+            token_out_amount: self.destination_amount_from_vault,
+            total_fees: self.total_fees,
+            trade_fee: self.trade_fee,
+            owner_fee: self.owner_fee,
+            host_fee: self.host_fee,
+            protocol_fee: self.protocol_fee,
+            trade_fee_numerator: self.trade_fee_numerator,
+            trade_fee_denominator: self.trade_fee_denominator,
+        }
+    }
+}
+
+impl SwapParams<'_> {
+    /// Swap `amount_in` of the source token, including the fees and the transfer fees of each
+    /// input transfer
+    ///
+    /// See `transfer_fee::sub_input_transfer_fees` for the input transfer fees
+    pub fn swap(&self, amount_in: u64) -> Result<SwapTransfers> {
+        let actual_amount_in = self.source_transfer_fee.sub_input_transfer_fees(
+            self.fees,
+            amount_in,
+            self.host_fee,
+            self.protocol_fee,
+        )?;
+        let result = self
+            .swap_curve
+            .swap(
+                u128::from(actual_amount_in),
+                u128::from(self.source_vault_amount),
+                u128::from(self.destination_vault_amount),
+                self.trade_direction,
+                self.fees,
+            )
+            .map_err(|_| error!(SwapError::ZeroTradingTokens))?;
+        msg!(
+            "Swap result: amount_in={}, actual_amount_in={}, total_source_amount_swapped={}, source_amount_swapped={}",
+            amount_in,
+            actual_amount_in,
+            result.total_source_amount_swapped,
+            result.source_amount_swapped,
+        );
+
+        let destination_amount_from_vault = to_u64!(result.destination_amount_swapped)?;
+        self.transfers(&result, destination_amount_from_vault)
+    }
+
+    /// Swap for `amount_out` of the destination token received by the user, post-transfer fees
+    pub fn swap_exact_out(&self, amount_out: u64) -> Result<SwapTransfers> {
+        let destination_amount_from_vault = self
+            .destination_transfer_fee
+            .add_inverse_transfer_fee(amount_out)?;
+        let result = self
+            .swap_curve
+            .swap_exact_out(
+                u128::from(destination_amount_from_vault),
+                u128::from(self.source_vault_amount),
+                u128::from(self.destination_vault_amount),
+                self.trade_direction,
+                self.fees,
+            )
+            .map_err(|_| error!(SwapError::ZeroTradingTokens))?;
+        msg!(
+            "Swap exact out result: amount_out={}, destination_amount_from_vault={}, total_source_amount_swapped={}, source_amount_swapped={}",
+            amount_out,
+            destination_amount_from_vault,
+            result.total_source_amount_swapped,
+            result.source_amount_swapped,
+        );
+
+        self.transfers(&result, destination_amount_from_vault)
+    }
+
+    /// Split the owner fee into the host and protocol fees, and add the source mint transfer fees
+    /// to each of the input transfers so the vaults and fee accounts receive the curve amounts
+    fn transfers(
+        &self,
+        result: &SwapResult,
+        destination_amount_from_vault: u64,
+    ) -> Result<SwapTransfers> {
+        let mut owner_fee = result.owner_fee;
+        let host_fee = if self.host_fee {
+            self.fees
+                .host_fee(owner_fee)
+                .map_err(|_| error!(SwapError::FeeCalculationFailure))?
+        } else {
+            0
+        };
+        owner_fee = try_math!(owner_fee.try_sub(host_fee))?;
+        let protocol_fee = match self.protocol_fee {
+            Some(global_config) => global_config
+                .protocol_fee(owner_fee)
+                .map_err(|_| error!(SwapError::FeeCalculationFailure))?,
+            None => 0,
+        };
+        owner_fee = try_math!(owner_fee.try_sub(protocol_fee))?;
+
+        let owner_fee = to_u64!(owner_fee)?;
+        let host_fee = to_u64!(host_fee)?;
+        let protocol_fee = to_u64!(protocol_fee)?;
+        let transfers = SwapTransfers {
+            source_amount_to_vault: self
+                .source_transfer_fee
+                .add_inverse_transfer_fee(to_u64!(result.source_amount_to_vault)?)?,
+            owner_fee_to_vault: self
+                .source_transfer_fee
+                .add_inverse_transfer_fee(owner_fee)?,
+            host_fee_to_account: self
+                .source_transfer_fee
+                .add_inverse_transfer_fee(host_fee)?,
+            protocol_fee_to_vault: self
+                .source_transfer_fee
+                .add_inverse_transfer_fee(protocol_fee)?,
+            destination_amount_from_vault,
+            destination_amount: self
+                .destination_transfer_fee
+                .sub_transfer_fee(destination_amount_from_vault)?,
+            total_fees: to_u64!(result.total_fees)?,
+            trade_fee: to_u64!(result.trade_fee)?,
+            owner_fee,
+            host_fee,
+            protocol_fee,
+            trade_fee_numerator: self.fees.trade_fee_numerator,
+            trade_fee_denominator: self.fees.trade_fee_denominator,
+        };
+        msg!(
+            "Swap transfers: source_amount_to_vault={}, owner_fee_to_vault={}, host_fee_to_account={}, protocol_fee_to_vault={}, destination_amount_from_vault={}, destination_amount_post_transfer_fees={}",
+            transfers.source_amount_to_vault,
+            transfers.owner_fee_to_vault,
+            transfers.host_fee_to_account,
+            transfers.protocol_fee_to_vault,
+            transfers.destination_amount_from_vault,
+            transfers.destination_amount,
+        );
+        Ok(transfers)
+    }
+}
+
+/// Pool state deposits and withdrawals of both trading tokens are calculated from
+pub struct LiquidityParams<'a> {
+    pub calculator: &'a dyn CurveCalculator,
+    /// Fees of the pool, for the owner withdraw fee
+    pub fees: &'a Fees,
+    pub token_a_vault_amount: u64,
+    pub token_b_vault_amount: u64,
+    pub pool_token_supply: u64,
+    pub token_a_transfer_fee: MintTransferFee,
+    pub token_b_transfer_fee: MintTransferFee,
+}
+
+/// Token transfers of a deposit
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DepositTransfers {
+    /// Amount transferred from the user to the token A vault (includes: transfer fees)
+    pub token_a_amount: u64,
+    /// Amount transferred from the user to the token B vault (includes: transfer fees)
+    pub token_b_amount: u64,
+    /// Amount credited to the token A vault (excludes: transfer fees)
+    pub token_a_amount_received: u64,
+    /// Amount credited to the token B vault (excludes: transfer fees)
+    pub token_b_amount_received: u64,
+    /// Amount of pool tokens minted to the user
+    pub pool_token_amount: u64,
+}
+
+/// Token transfers of a withdrawal
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WithdrawTransfers {
+    /// Amount transferred from the token A vault to the user
+    pub token_a_amount: u64,
+    /// Amount transferred from the token B vault to the user
+    pub token_b_amount: u64,
+    /// Amount credited to the user's token A account (excludes: transfer fees)
+    pub token_a_amount_received: u64,
+    /// Amount credited to the user's token B account (excludes: transfer fees)
+    pub token_b_amount_received: u64,
+    /// Amount transferred from the token A vault to the token A fees vault
+    pub token_a_fees: u64,
+    /// Amount transferred from the token B vault to the token B fees vault
+    pub token_b_fees: u64,
+    /// Amount credited to the token A fees vault (excludes: transfer fees)
+    pub token_a_fees_received: u64,
+    /// Amount credited to the token B fees vault (excludes: transfer fees)
+    pub token_b_fees_received: u64,
+}
+
+impl LiquidityParams<'_> {
+    /// Deposit both trading tokens in proportion to the pool for `pool_token_amount` pool
+    /// tokens, the first deposit into an empty pool mints the new pool supply instead
+    pub fn deposit(&self, pool_token_amount: u64) -> Result<DepositTransfers> {
+        require_msg!(
+            self.calculator.allows_deposits(),
+            SwapError::UnsupportedCurveOperation,
+            "The curve does not allow deposits"
+        );
+
+        let current_pool_mint_supply = u128::from(self.pool_token_supply);
+        let (pool_token_amount, pool_mint_supply) = if current_pool_mint_supply > 0 {
+            (u128::from(pool_token_amount), current_pool_mint_supply)
+        } else {
+            (
+                self.calculator.new_pool_supply(),
+                self.calculator.new_pool_supply(),
+            )
+        };
+
+        let results = self
+            .calculator
+            .pool_tokens_to_trading_tokens(
+                pool_token_amount,
+                pool_mint_supply,
+                u128::from(self.token_a_vault_amount),
+                u128::from(self.token_b_vault_amount),
+                RoundDirection::Ceiling,
+            )
+            .map_err(|_| error!(SwapError::ZeroTradingTokens))?;
+        let token_a_amount = to_u64!(results.token_a_amount)?;
+        let token_b_amount = to_u64!(results.token_b_amount)?;
+        require_msg!(
+            token_a_amount > 0 && token_b_amount > 0,
+            SwapError::ZeroTradingTokens,
+            &format!(
+                "ZeroTradingTokens: pool tokens being minted are worth less than 1 token A or B: token_a_amount={}, token_b_amount={}",
+                token_a_amount, token_b_amount
+            )
+        );
+
+        // Add transfer fees to each of the input transfers, so the vaults receive the curve amounts
+        let token_a_amount_to_vault = self
+            .token_a_transfer_fee
+            .add_inverse_transfer_fee(token_a_amount)?;
+        let token_b_amount_to_vault = self
+            .token_b_transfer_fee
+            .add_inverse_transfer_fee(token_b_amount)?;
+
+        Ok(DepositTransfers {
+            token_a_amount: token_a_amount_to_vault,
+            token_b_amount: token_b_amount_to_vault,
+            token_a_amount_received: self
+                .token_a_transfer_fee
+                .sub_transfer_fee(token_a_amount_to_vault)?,
+            token_b_amount_received: self
+                .token_b_transfer_fee
+                .sub_transfer_fee(token_b_amount_to_vault)?,
+            pool_token_amount: to_u64!(pool_token_amount)?,
+        })
+    }
+
+    /// Withdraw both trading tokens in proportion to the pool for burning `pool_token_amount`
+    /// pool tokens, less the owner withdraw fee
+    pub fn withdraw(&self, pool_token_amount: u64) -> Result<WithdrawTransfers> {
+        require_msg!(
+            pool_token_amount > 0,
+            SwapError::ZeroTradingTokens,
+            "ZeroTradingTokens: pool_token_amount=0"
+        );
+        let results = self
+            .calculator
+            .pool_tokens_to_trading_tokens(
+                u128::from(pool_token_amount),
+                u128::from(self.pool_token_supply),
+                u128::from(self.token_a_vault_amount),
+                u128::from(self.token_b_vault_amount),
+                RoundDirection::Floor,
+            )
+            .map_err(|_| error!(SwapError::ZeroTradingTokens))?;
+
+        let (token_a_amount, token_a_amount_received, token_a_fees) = self.sub_withdraw_fee(
+            &self.token_a_transfer_fee,
+            self.token_a_vault_amount,
+            results.token_a_amount,
+            AorB::A,
+        )?;
+        let (token_b_amount, token_b_amount_received, token_b_fees) = self.sub_withdraw_fee(
+            &self.token_b_transfer_fee,
+            self.token_b_vault_amount,
+            results.token_b_amount,
+            AorB::B,
+        )?;
+
+        Ok(WithdrawTransfers {
+            token_a_amount,
+            token_b_amount,
+            token_a_amount_received,
+            token_b_amount_received,
+            token_a_fees,
+            token_b_fees,
+            token_a_fees_received: self.token_a_transfer_fee.sub_transfer_fee(token_a_fees)?,
+            token_b_fees_received: self.token_b_transfer_fee.sub_transfer_fee(token_b_fees)?,
+        })
+    }
+
+    /// Subtract the owner withdraw fee from the amount withdrawn from the vault
+    ///
+    /// Returns the amount transferred from the vault to the user, the amount received by the user
+    /// post-transfer fees and the owner withdraw fee
+    fn sub_withdraw_fee(
+        &self,
+        transfer_fee: &MintTransferFee,
+        pool_balance: u64,
+        withdraw_amount: u128,
+        a_or_b: AorB,
+    ) -> Result<(u64, u64, u64)> {
+        let withdraw_amount = std::cmp::min(u128::from(pool_balance), withdraw_amount);
+        let withdraw_fee = self
+            .fees
+            .owner_withdraw_fee(withdraw_amount)
+            .map_err(|_| error!(SwapError::FeeCalculationFailure))?;
+        let amount_after_fee = to_u64!(try_math!(withdraw_amount.try_sub(withdraw_fee))?)?;
+        let withdraw_fee = to_u64!(withdraw_fee)?;
+        let amount_received = transfer_fee.sub_transfer_fee(amount_after_fee)?;
+
+        msg!(
+            "Token {:?} withdrawal fee: fee={}, amount_after_fee={}, amount_received={}",
+            a_or_b,
+            withdraw_fee,
+            amount_after_fee,
+            amount_received
+        );
+        require_msg!(
+            amount_received > 0 || pool_balance == 0,
+            SwapError::ZeroTradingTokens,
+            &format!(
+                "ZeroTradingTokens: token={:?} withdrawal is worth less than 1 token after fees",
+                a_or_b
+            )
+        );
+        Ok((amount_after_fee, amount_received, withdraw_fee))
+    }
+}
@@ -5,9 +5,15 @@ pub const TOKEN_A_VAULT: &[u8] = b"pvault_a";
 pub const TOKEN_B_VAULT: &[u8] = b"pvault_b";
 pub const TOKEN_A_FEES_VAULT: &[u8] = b"fvault_a";
 pub const TOKEN_B_FEES_VAULT: &[u8] = b"fvault_b";
+pub const TOKEN_A_PROTOCOL_FEES_VAULT: &[u8] = b"pfvault_a";
+pub const TOKEN_B_PROTOCOL_FEES_VAULT: &[u8] = b"pfvault_b";
+pub const GLOBAL_CONFIG: &[u8] = b"global_config";
+pub const TOKEN_VAULT: &[u8] = b"pvault";
+pub const TOKEN_FEES_VAULT: &[u8] = b"fvault";
+pub const TOKEN_PROTOCOL_FEES_VAULT: &[u8] = b"pfvault";
 
 pub mod pda {
-    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::{prelude::Pubkey, solana_program::bpf_loader_upgradeable};
 
     use super::*;
     use crate::ID;
@@ -20,6 +26,21 @@ pub mod pda {
         pub pool_token_mint: Pubkey,
         pub token_a_fees_vault: Pubkey,
         pub token_b_fees_vault: Pubkey,
+        pub token_a_protocol_fees_vault: Pubkey,
+        pub token_b_protocol_fees_vault: Pubkey,
+    }
+
+    pub fn global_config_pda() -> (Pubkey, u8) {
+        global_config_pda_program_id(&ID)
+    }
+
+    pub fn global_config_pda_program_id(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[GLOBAL_CONFIG], program_id)
+    }
+
+    /// Program data account of the program, holding its upgrade authority
+    pub fn program_data_pda_program_id(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID)
     }
 
     pub fn pool_authority_pda(pool: &Pubkey) -> (Pubkey, u8) {
@@ -90,6 +111,93 @@ pub mod pda {
         )
     }
 
+    pub fn token_a_protocol_fees_vault_pda(pool: &Pubkey, token_a_mint: &Pubkey) -> (Pubkey, u8) {
+        token_a_protocol_fees_vault_pda_program_id(&ID, pool, token_a_mint)
+    }
+
+    pub fn token_a_protocol_fees_vault_pda_program_id(
+        program_id: &Pubkey,
+        pool: &Pubkey,
+        token_a_mint: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                TOKEN_A_PROTOCOL_FEES_VAULT,
+                pool.as_ref(),
+                token_a_mint.as_ref(),
+            ],
+            program_id,
+        )
+    }
+
+    pub fn token_b_protocol_fees_vault_pda(pool: &Pubkey, token_b_mint: &Pubkey) -> (Pubkey, u8) {
+        token_b_protocol_fees_vault_pda_program_id(&ID, pool, token_b_mint)
+    }
+
+    pub fn token_b_protocol_fees_vault_pda_program_id(
+        program_id: &Pubkey,
+        pool: &Pubkey,
+        token_b_mint: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                TOKEN_B_PROTOCOL_FEES_VAULT,
+                pool.as_ref(),
+                token_b_mint.as_ref(),
+            ],
+            program_id,
+        )
+    }
+
+    /// Vault of any token of a multi-token pool
+    pub fn token_vault_pda(pool: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        token_vault_pda_program_id(&ID, pool, mint)
+    }
+
+    pub fn token_vault_pda_program_id(
+        program_id: &Pubkey,
+        pool: &Pubkey,
+        mint: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[TOKEN_VAULT, pool.as_ref(), mint.as_ref()], program_id)
+    }
+
+    /// Fees vault of any token of a multi-token pool
+    pub fn token_fees_vault_pda(pool: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        token_fees_vault_pda_program_id(&ID, pool, mint)
+    }
+
+    pub fn token_fees_vault_pda_program_id(
+        program_id: &Pubkey,
+        pool: &Pubkey,
+        mint: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[TOKEN_FEES_VAULT, pool.as_ref(), mint.as_ref()],
+            program_id,
+        )
+    }
+
+    /// Protocol fees vault of any token of a multi-token pool
+    pub fn token_protocol_fees_vault_pda(pool: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        token_protocol_fees_vault_pda_program_id(&ID, pool, mint)
+    }
+
+    pub fn token_protocol_fees_vault_pda_program_id(
+        program_id: &Pubkey,
+        pool: &Pubkey,
+        mint: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[TOKEN_PROTOCOL_FEES_VAULT, pool.as_ref(), mint.as_ref()],
+            program_id,
+        )
+    }
+
+    pub fn pool_token_mint_pda_program_id(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[POOL_TOKEN_MINT, pool.as_ref()], program_id)
+    }
+
     pub fn init_pool_pdas(
         pool: &Pubkey,
         token_a_mint: &Pubkey,
@@ -122,6 +230,11 @@ pub mod pda {
         let (token_b_fees_vault, _token_b_fees_vault_bump_seed) =
             token_b_fees_vault_pda_program_id(program_id, pool, token_b_mint);
 
+        let (token_a_protocol_fees_vault, _token_a_protocol_fees_vault_bump_seed) =
+            token_a_protocol_fees_vault_pda_program_id(program_id, pool, token_a_mint);
+        let (token_b_protocol_fees_vault, _token_b_protocol_fees_vault_bump_seed) =
+            token_b_protocol_fees_vault_pda_program_id(program_id, pool, token_b_mint);
+
         InitPoolPdas {
             curve,
             authority,
@@ -130,6 +243,8 @@ pub mod pda {
             pool_token_mint,
             token_a_fees_vault,
             token_b_fees_vault,
+            token_a_protocol_fees_vault,
+            token_b_protocol_fees_vault,
         }
     }
 }
@@ -1,8 +1,27 @@
-use anchor_lang::prelude::{AccountInfo, CpiContext, Result};
+use anchor_lang::{
+    error,
+    prelude::{msg, AccountInfo, AccountMeta, CpiContext, Pubkey, Result},
+    solana_program::{
+        instruction::Instruction,
+        program::{invoke, invoke_signed},
+    },
+};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee, BaseStateWithExtensions, StateWithExtensions},
+};
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_transfer_hook_interface::{
+    get_extra_account_metas_address, instruction::ExecuteInstruction,
+};
 
-use crate::utils::seeds;
+use crate::{
+    error::SwapError,
+    utils::{seeds, token_extensions},
+};
 
-/// Issue an spl_token or spl_token_2022 `TransferChecked` instruction.
+/// Issue an spl_token or spl_token_2022 `TransferChecked` instruction out of a pool vault,
+/// signed by the pool authority
 #[allow(clippy::too_many_arguments)]
 pub fn transfer_from_vault<'info>(
     token_program: AccountInfo<'info>,
@@ -11,6 +30,7 @@ pub fn transfer_from_vault<'info>(
     mint: AccountInfo<'info>,
     destination: AccountInfo<'info>,
     authority: AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
     pool_authority_bump: u8,
     amount: u64,
     decimals: u8,
@@ -22,25 +42,20 @@ pub fn transfer_from_vault<'info>(
     ];
     let signer_seeds = &[&inner_seeds[..]];
 
-    anchor_spl::token_2022::transfer_checked(
-        CpiContext::new_with_signer(
-            token_program,
-            anchor_spl::token_2022::TransferChecked {
-                from: source,
-                mint,
-                to: destination,
-                authority,
-            },
-            signer_seeds,
-        ),
+    transfer_checked(
+        token_program,
+        source,
+        mint,
+        destination,
+        authority,
+        remaining_accounts,
         amount,
         decimals,
-    )?;
-
-    Ok(())
+        signer_seeds,
+    )
 }
 
-/// Issue an spl_token or spl_token_2022 `TransferChecked` instruction.
+/// Issue an spl_token or spl_token_2022 `TransferChecked` instruction signed by the user
 #[allow(clippy::too_many_arguments)]
 pub fn transfer_from_user<'info>(
     token_program: AccountInfo<'info>,
@@ -48,22 +63,192 @@ pub fn transfer_from_user<'info>(
     mint: AccountInfo<'info>,
     destination: AccountInfo<'info>,
     authority: AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
     amount: u64,
     decimals: u8,
 ) -> Result<()> {
-    anchor_spl::token_2022::transfer_checked(
-        CpiContext::new(
-            token_program,
-            anchor_spl::token_2022::TransferChecked {
-                from: source,
-                mint,
-                to: destination,
-                authority,
-            },
-        ),
+    transfer_checked(
+        token_program,
+        source,
+        mint,
+        destination,
+        authority,
+        remaining_accounts,
+        amount,
+        decimals,
+        &[],
+    )
+}
+
+/// `anchor_spl::token_2022::transfer_checked` only passes the accounts of the instruction itself,
+/// the transfer hook accounts are appended here so the token program can invoke the hook with them
+///
+/// When the mint has a transfer hook, the hook program, its extra account metas account and the
+/// extra accounts it lists are looked up by key in `remaining_accounts`.
+#[allow(clippy::too_many_arguments)]
+fn transfer_checked<'info>(
+    token_program: AccountInfo<'info>,
+    source: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let mut ix = spl_token_2022::instruction::transfer_checked(
+        token_program.key,
+        source.key,
+        mint.key,
+        destination.key,
+        authority.key,
+        &[],
         amount,
         decimals,
     )?;
+    let mut account_infos = vec![source, mint.clone(), destination, authority];
+    if *token_program.key == spl_token_2022::ID {
+        add_transfer_hook_accounts(
+            &mut ix,
+            &mut account_infos,
+            &mint,
+            remaining_accounts,
+            amount,
+        )?;
+    }
+    account_infos.push(token_program);
+    invoke_signed(&ix, &account_infos, signer_seeds)?;
+
+    Ok(())
+}
+
+/// Resolve the extra accounts of the mint's transfer hook from its `ExtraAccountMetaList` and
+/// append them, followed by the list and the hook program, to the `TransferChecked` instruction
+///
+/// The extra accounts are resolved against the `Execute` instruction of the hook, as the token
+/// program does when invoking it, since seeds may refer to its accounts, data and program id.
+fn add_transfer_hook_accounts<'info>(
+    ix: &mut Instruction,
+    account_infos: &mut Vec<AccountInfo<'info>>,
+    mint: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    let program_id = match token_extensions::transfer_hook_program_id(&mint.try_borrow_data()?)? {
+        Some(program_id) => program_id,
+        None => return Ok(()),
+    };
+    let extra_account_metas_address = get_extra_account_metas_address(mint.key, &program_id);
+    let find_account = |key: &Pubkey| {
+        let account = remaining_accounts.iter().find(|account| account.key == key);
+        if account.is_none() {
+            msg!(
+                "MissingTransferHookAccounts: mint={} hook_program={} is missing account {}",
+                mint.key,
+                program_id,
+                key
+            );
+        }
+        account.ok_or(error!(SwapError::MissingTransferHookAccounts))
+    };
+    let program = find_account(&program_id)?;
+    let extra_account_metas = find_account(&extra_account_metas_address)?;
+
+    let mut execute_ix = spl_transfer_hook_interface::instruction::execute(
+        &program_id,
+        account_infos[0].key,
+        account_infos[1].key,
+        account_infos[2].key,
+        account_infos[3].key,
+        &extra_account_metas_address,
+        amount,
+    );
+    let mut execute_account_infos = account_infos.clone();
+    execute_account_infos.push(extra_account_metas.clone());
+    ExtraAccountMetaList::add_to_cpi_instruction::<ExecuteInstruction>(
+        &mut execute_ix,
+        &mut execute_account_infos,
+        &extra_account_metas.try_borrow_data()?,
+        remaining_accounts,
+    )
+    .map_err(|e| {
+        msg!(
+            "MissingTransferHookAccounts: mint={} hook_program={} extra accounts could not be resolved: {:?}",
+            mint.key,
+            program_id,
+            e
+        );
+        error!(SwapError::MissingTransferHookAccounts)
+    })?;
+
+    // the token program looks up the hook accounts by key, after the transfer accounts
+    let execute_accounts_len = account_infos.len() + 1;
+    ix.accounts
+        .extend_from_slice(&execute_ix.accounts[execute_accounts_len..]);
+    account_infos.extend_from_slice(&execute_account_infos[execute_accounts_len..]);
+    ix.accounts.push(AccountMeta::new_readonly(
+        extra_account_metas_address,
+        false,
+    ));
+    account_infos.push(extra_account_metas.clone());
+    ix.accounts
+        .push(AccountMeta::new_readonly(program_id, false));
+    account_infos.push(program.clone());
 
     Ok(())
 }
+
+/// Issue an spl_token or spl_token_2022 `CloseAccount` instruction for a pool owned vault.
+///
+/// Transfer fees withheld in a token 2022 vault block it from being closed, so they are first
+/// harvested to the mint, where they remain claimable by the mint's withdraw withheld authority.
+pub fn close_vault<'info>(
+    token_program: AccountInfo<'info>,
+    pool: AccountInfo<'info>,
+    vault: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    pool_authority_bump: u8,
+) -> Result<()> {
+    if *token_program.key == spl_token_2022::ID && withheld_transfer_fees(&vault)? > 0 {
+        invoke(
+            &transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+                token_program.key,
+                mint.key,
+                &[vault.key],
+            )?,
+            &[mint, vault.clone(), token_program.clone()],
+        )?;
+    }
+
+    let inner_seeds = [
+        seeds::POOL_AUTHORITY,
+        pool.key.as_ref(),
+        &[pool_authority_bump],
+    ];
+    let signer_seeds = &[&inner_seeds[..]];
+
+    anchor_spl::token_2022::close_account(CpiContext::new_with_signer(
+        token_program,
+        anchor_spl::token_2022::CloseAccount {
+            account: vault,
+            destination,
+            authority,
+        },
+        signer_seeds,
+    ))?;
+
+    Ok(())
+}
+
+/// Transfer fees withheld in a token 2022 account, zero if the mint has no transfer fee extension
+fn withheld_transfer_fees(token_account: &AccountInfo) -> Result<u64> {
+    let data = token_account.data.borrow();
+    let account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+    Ok(account
+        .get_extension::<transfer_fee::TransferFeeAmount>()
+        .map(|amount| u64::from(amount.withheld_amount))
+        .unwrap_or_default())
+}
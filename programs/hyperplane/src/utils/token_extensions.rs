@@ -0,0 +1,321 @@
+//! Token-2022 mint extensions, read straight from the TLV data of the mint
+//!
+//! The spl-token-2022 version the program builds against predates some of the mint extensions
+//! found on chain (e.g. transfer hooks and metadata), and fails to parse any mint that has them.
+//! The mint TLV entries are walked here without requiring every extension type to be known.
+
+use anchor_lang::{
+    err, error,
+    prelude::{borsh::BorshDeserialize, Pubkey},
+    Result,
+};
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{BaseStateWithExtensions, Extension, StateWithExtensions},
+    pod::pod_from_bytes,
+    state::Mint,
+};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use spl_token_metadata_interface::state::TokenMetadata;
+
+use crate::error::SwapError;
+
+/// Token-2022 mint extension types, numbered as the token-2022 program `ExtensionType`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
+#[repr(u16)]
+pub enum MintExtension {
+    TransferFeeConfig = 1,
+    MintCloseAuthority = 3,
+    ConfidentialTransferMint = 4,
+    DefaultAccountState = 6,
+    NonTransferable = 9,
+    InterestBearingConfig = 10,
+    PermanentDelegate = 12,
+    TransferHook = 14,
+    ConfidentialTransferFeeConfig = 16,
+    MetadataPointer = 18,
+    TokenMetadata = 19,
+}
+
+impl MintExtension {
+    /// Whether pools can trade a mint with this extension without the global config opting in
+    ///
+    /// - Transfer fees are accounted for on every transfer in and out of the pool
+    /// - Interest-bearing amounts are scaled to UI amounts by the curves pegged to a price
+    /// - Close authority and metadata have no effect on transfers
+    ///
+    /// Confidential transfers bypass the vault balances, a default frozen state blocks the vaults,
+    /// non-transferable tokens cannot be swapped and a permanent delegate can drain the vaults.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            MintExtension::TransferFeeConfig
+            | MintExtension::MintCloseAuthority
+            | MintExtension::InterestBearingConfig
+            | MintExtension::MetadataPointer
+            | MintExtension::TokenMetadata => true,
+            MintExtension::ConfidentialTransferMint
+            | MintExtension::DefaultAccountState
+            | MintExtension::NonTransferable
+            | MintExtension::PermanentDelegate
+            | MintExtension::TransferHook
+            | MintExtension::ConfidentialTransferFeeConfig => false,
+        }
+    }
+
+    /// Whether pools can trade a mint with this extension once it is allowed by the global config
+    ///
+    /// Transfer hooks are forwarded the extra accounts passed to the instruction, but the hook
+    /// program runs arbitrary code on every vault transfer so each deployment has to opt in.
+    pub fn is_opt_in(&self) -> bool {
+        matches!(self, MintExtension::TransferHook)
+    }
+}
+
+const TLV_TYPE_LEN: usize = 2;
+const TLV_LENGTH_LEN: usize = 2;
+
+/// Iterate the `(extension type, value)` TLV entries of a mint, empty for spl-token mints
+fn mint_tlv_entries(tlv_data: &[u8]) -> Result<Vec<(u16, &[u8])>> {
+    let mut entries = vec![];
+    let mut start = 0;
+    while start < tlv_data.len() {
+        let length_start = start.saturating_add(TLV_TYPE_LEN);
+        let value_start = length_start.saturating_add(TLV_LENGTH_LEN);
+        if tlv_data.len() < value_start {
+            return err!(SwapError::InvalidTokenExtension);
+        }
+        let extension_type = u16::from_le_bytes([tlv_data[start], tlv_data[start + 1]]);
+        // nothing is written after an uninitialized entry
+        if extension_type == 0 {
+            break;
+        }
+        let length = u16::from_le_bytes([tlv_data[length_start], tlv_data[length_start + 1]]);
+        let value_end = value_start.saturating_add(usize::from(length));
+        if tlv_data.len() < value_end {
+            return err!(SwapError::InvalidTokenExtension);
+        }
+        entries.push((extension_type, &tlv_data[value_start..value_end]));
+        start = value_end;
+    }
+    Ok(entries)
+}
+
+/// The extension types of a mint, including types unknown to the program
+pub fn mint_extension_types(mint_data: &[u8]) -> Result<Vec<u16>> {
+    let mint = StateWithExtensions::<Mint>::unpack(mint_data)?;
+    Ok(mint_tlv_entries(mint.get_tlv_data())?
+        .into_iter()
+        .map(|(extension_type, _)| extension_type)
+        .collect())
+}
+
+/// Read an extension of a mint, `None` if the mint does not have it
+pub fn mint_extension<V: Extension>(mint_data: &[u8]) -> Result<Option<V>> {
+    let mint = StateWithExtensions::<Mint>::unpack(mint_data)?;
+    let extension_type = u16::from(V::TYPE);
+    match mint_tlv_entries(mint.get_tlv_data())?
+        .into_iter()
+        .find(|(t, _)| *t == extension_type)
+    {
+        Some((_, value)) => Ok(Some(*pod_from_bytes::<V>(value)?)),
+        None => Ok(None),
+    }
+}
+
+/// The transfer hook program of a mint, `None` if the mint has no transfer hook or it is unset
+///
+/// The extension value is the hook authority followed by the hook program id, all zeros when unset.
+pub fn transfer_hook_program_id(mint_data: &[u8]) -> Result<Option<Pubkey>> {
+    let mint = StateWithExtensions::<Mint>::unpack(mint_data)?;
+    let extension_type = u16::from(MintExtension::TransferHook);
+    match mint_tlv_entries(mint.get_tlv_data())?
+        .into_iter()
+        .find(|(t, _)| *t == extension_type)
+    {
+        Some((_, value)) => {
+            let program_id = value.get(32..64).ok_or(SwapError::InvalidTokenExtension)?;
+            let program_id = Pubkey::try_from(program_id).unwrap();
+            Ok((program_id != Pubkey::default()).then_some(program_id))
+        }
+        None => Ok(None),
+    }
+}
+
+/// The token metadata stored in a mint, `None` if the mint does not have the extension
+pub fn token_metadata(mint_data: &[u8]) -> Result<Option<TokenMetadata>> {
+    let mint = StateWithExtensions::<Mint>::unpack(mint_data)?;
+    let extension_type = u16::from(MintExtension::TokenMetadata);
+    match mint_tlv_entries(mint.get_tlv_data())?
+        .into_iter()
+        .find(|(t, _)| *t == extension_type)
+    {
+        Some((_, value)) => Ok(Some(
+            TokenMetadata::try_from_slice(value)
+                .map_err(|_| error!(SwapError::InvalidTokenExtension))?,
+        )),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{prelude::borsh::BorshSerialize, solana_program::program_pack::Pack};
+    use anchor_spl::token_2022::spl_token_2022::extension::{
+        transfer_fee::TransferFeeConfig, ExtensionType,
+    };
+
+    use super::*;
+
+    /// A mint account with the given raw TLV entries after the base mint and account type
+    fn mint_data(entries: &[(u16, Vec<u8>)]) -> Vec<u8> {
+        let mut data = vec![0; Mint::LEN];
+        Mint::pack(
+            Mint {
+                decimals: 6,
+                is_initialized: true,
+                ..Default::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        // padding up to the base account length, then the mint account type
+        data.resize(165, 0);
+        data.push(1);
+        for (extension_type, value) in entries {
+            data.extend_from_slice(&extension_type.to_le_bytes());
+            data.extend_from_slice(&(value.len() as u16).to_le_bytes());
+            data.extend_from_slice(value);
+        }
+        data
+    }
+
+    #[test]
+    fn test_mint_extension_types_includes_unknown_types() {
+        let data = mint_data(&[
+            (MintExtension::TransferHook.into(), vec![0; 64]),
+            (MintExtension::TransferFeeConfig.into(), vec![0; 108]),
+            (999, vec![1, 2, 3]),
+        ]);
+        assert_eq!(
+            mint_extension_types(&data).unwrap(),
+            vec![
+                u16::from(MintExtension::TransferHook),
+                u16::from(MintExtension::TransferFeeConfig),
+                999
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mint_extension_after_unknown_type() {
+        let data = mint_data(&[
+            (MintExtension::TokenMetadata.into(), vec![7; 40]),
+            (MintExtension::TransferFeeConfig.into(), vec![0; 108]),
+        ]);
+        assert_eq!(
+            mint_extension::<TransferFeeConfig>(&data).unwrap(),
+            Some(TransferFeeConfig::default())
+        );
+
+        let data = mint_data(&[(MintExtension::TokenMetadata.into(), vec![7; 40])]);
+        assert_eq!(mint_extension::<TransferFeeConfig>(&data).unwrap(), None);
+    }
+
+    #[test]
+    fn test_mint_without_extensions() {
+        let mut data = vec![0; Mint::LEN];
+        Mint::pack(
+            Mint {
+                is_initialized: true,
+                ..Default::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        assert!(mint_extension_types(&data).unwrap().is_empty());
+        assert_eq!(mint_extension::<TransferFeeConfig>(&data).unwrap(), None);
+    }
+
+    #[test]
+    fn test_transfer_hook_program_id() {
+        let program_id = Pubkey::new_unique();
+        let mut value = Pubkey::new_unique().to_bytes().to_vec();
+        value.extend_from_slice(program_id.as_ref());
+        let data = mint_data(&[
+            (MintExtension::TransferFeeConfig.into(), vec![0; 108]),
+            (MintExtension::TransferHook.into(), value),
+        ]);
+        assert_eq!(transfer_hook_program_id(&data).unwrap(), Some(program_id));
+
+        let data = mint_data(&[(MintExtension::TransferHook.into(), vec![0; 64])]);
+        assert_eq!(transfer_hook_program_id(&data).unwrap(), None);
+
+        let data = mint_data(&[(MintExtension::TransferFeeConfig.into(), vec![0; 108])]);
+        assert_eq!(transfer_hook_program_id(&data).unwrap(), None);
+
+        let data = mint_data(&[(MintExtension::TransferHook.into(), vec![0; 32])]);
+        assert_eq!(
+            transfer_hook_program_id(&data).err(),
+            Some(SwapError::InvalidTokenExtension.into())
+        );
+    }
+
+    #[test]
+    fn test_token_metadata() {
+        let metadata = TokenMetadata {
+            mint: Pubkey::new_unique(),
+            name: "USD Coin".to_string(),
+            symbol: "USDC".to_string(),
+            ..Default::default()
+        };
+        let data = mint_data(&[
+            (MintExtension::MetadataPointer.into(), vec![0; 64]),
+            (
+                MintExtension::TokenMetadata.into(),
+                metadata.try_to_vec().unwrap(),
+            ),
+        ]);
+        assert_eq!(token_metadata(&data).unwrap(), Some(metadata));
+
+        let data = mint_data(&[(MintExtension::MetadataPointer.into(), vec![0; 64])]);
+        assert_eq!(token_metadata(&data).unwrap(), None);
+
+        let data = mint_data(&[(MintExtension::TokenMetadata.into(), vec![7; 40])]);
+        assert_eq!(
+            token_metadata(&data).err(),
+            Some(SwapError::InvalidTokenExtension.into())
+        );
+    }
+
+    #[test]
+    fn test_malformed_tlv_data() {
+        let mut data = mint_data(&[(MintExtension::TransferHook.into(), vec![0; 64])]);
+        data.truncate(data.len() - 1);
+        assert_eq!(
+            mint_extension_types(&data).err(),
+            Some(SwapError::InvalidTokenExtension.into())
+        );
+    }
+
+    #[test]
+    fn test_mint_extension_numbering_matches_token_2022() {
+        for extension_type in [
+            ExtensionType::TransferFeeConfig,
+            ExtensionType::MintCloseAuthority,
+            ExtensionType::ConfidentialTransferMint,
+            ExtensionType::DefaultAccountState,
+            ExtensionType::NonTransferable,
+            ExtensionType::InterestBearingConfig,
+            ExtensionType::PermanentDelegate,
+        ] {
+            assert!(MintExtension::try_from(u16::from(extension_type)).is_ok());
+        }
+        for extension_type in [
+            ExtensionType::Uninitialized,
+            ExtensionType::TransferFeeAmount,
+            ExtensionType::ImmutableOwner,
+            ExtensionType::CpiGuard,
+        ] {
+            assert!(MintExtension::try_from(u16::from(extension_type)).is_err());
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Token-2022 transfer fee calculations, independent of the accounts and clock they are read from
+
+use anchor_lang::{error, prelude::msg, Result};
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+
+use crate::{
+    curve::fees::Fees,
+    error::SwapError,
+    state::GlobalConfig,
+    to_u64, try_math,
+    utils::{math::TryMath, token_extensions},
+};
+
+/// Read the transfer fee config of a mint, `None` if the mint has no transfer fee extension
+pub fn transfer_fee_config(mint_data: &[u8]) -> Result<Option<TransferFeeConfig>> {
+    token_extensions::mint_extension::<TransferFeeConfig>(mint_data)
+}
+
+/// Transfer fees of a mint as of an epoch, `None` config for mints without transfer fees
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MintTransferFee {
+    pub config: Option<TransferFeeConfig>,
+    pub epoch: u64,
+}
+
+impl MintTransferFee {
+    /// Read the transfer fee config of a mint, charged as of `epoch`
+    pub fn new(mint_data: &[u8], epoch: u64) -> Result<Self> {
+        Ok(Self {
+            config: transfer_fee_config(mint_data)?,
+            epoch,
+        })
+    }
+
+    /// See `sub_transfer_fee`
+    pub fn sub_transfer_fee(&self, amount: u64) -> Result<u64> {
+        match &self.config {
+            Some(config) => sub_transfer_fee(config, self.epoch, amount),
+            None => Ok(amount),
+        }
+    }
+
+    /// See `add_inverse_transfer_fee`
+    pub fn add_inverse_transfer_fee(&self, post_fee_amount: u64) -> Result<u64> {
+        match &self.config {
+            Some(config) => add_inverse_transfer_fee(config, self.epoch, post_fee_amount),
+            None => Ok(post_fee_amount),
+        }
+    }
+
+    /// See `sub_input_transfer_fees`
+    pub fn sub_input_transfer_fees(
+        &self,
+        fees: &Fees,
+        amount_in: u64,
+        host_fee: bool,
+        global_config: Option<&GlobalConfig>,
+    ) -> Result<u64> {
+        match &self.config {
+            Some(config) => sub_input_transfer_fees(
+                config,
+                self.epoch,
+                fees,
+                amount_in,
+                host_fee,
+                global_config,
+            ),
+            None => Ok(amount_in),
+        }
+    }
+}
+
+/// Subtract the transfer fee for the amount received post-transfer fees
+pub fn sub_transfer_fee(
+    transfer_fee_config: &TransferFeeConfig,
+    epoch: u64,
+    amount: u64,
+) -> Result<u64> {
+    let transfer_fee = transfer_fee_config
+        .calculate_epoch_fee(epoch, amount)
+        .ok_or_else(|| error!(SwapError::FeeCalculationFailure))?;
+    let amount_sub_fee = try_math!(amount.try_sub(transfer_fee))?;
+    msg!(
+        "Subtract token transfer fee: fee={}, amount={}, amount_sub_fee={}",
+        transfer_fee,
+        amount,
+        amount_sub_fee
+    );
+    Ok(amount_sub_fee)
+}
+
+/// Add the transfer fee for the amount to send pre-transfer fees
+pub fn add_inverse_transfer_fee(
+    transfer_fee_config: &TransferFeeConfig,
+    epoch: u64,
+    post_fee_amount: u64,
+) -> Result<u64> {
+    let transfer_fee = transfer_fee_config
+        .calculate_inverse_epoch_fee(epoch, post_fee_amount)
+        .ok_or_else(|| error!(SwapError::FeeCalculationFailure))?;
+    let amount_add_fee = try_math!(post_fee_amount.try_add(transfer_fee))?;
+    msg!(
+        "Add token transfer fee: fee={}, amount={}, amount_add_fee={}",
+        transfer_fee,
+        post_fee_amount,
+        amount_add_fee
+    );
+    Ok(amount_add_fee)
+}
+
+/// Subtract the transfer fees of each of the input transfers of a swap, for the actual amount
+/// received by the pool post-transfer fees
+///
+/// There are potentially 4 input transfers:
+/// 1. User -> Pool
+/// 2. User -> Fees
+/// 3. User -> Host Fees (optional)
+/// 4. User -> Protocol Fees (optional), the `global_config` protocol share of the owner fee after
+///    the host fee, for pools with a protocol fees vault
+///
+/// At low token amounts, the fees on each transfer rounding up can result in the user paying more than the amount_in, causing an unexpected `ExceededSlippage` error
+pub fn sub_input_transfer_fees(
+    transfer_fee_config: &TransferFeeConfig,
+    epoch: u64,
+    fees: &Fees,
+    amount_in: u64,
+    host_fee: bool,
+    global_config: Option<&GlobalConfig>,
+) -> Result<u64> {
+    let owner_and_host_fee = fees.owner_trading_fee(amount_in.into())?;
+    let (host_fee, host_transfer_fee) = if host_fee {
+        let host_fee = fees.host_fee(owner_and_host_fee)?;
+        (
+            host_fee,
+            transfer_fee_config
+                .calculate_epoch_fee(epoch, to_u64!(host_fee)?)
+                .ok_or_else(|| error!(SwapError::FeeCalculationFailure))?,
+        )
+    } else {
+        (0, 0)
+    };
+    let owner_and_protocol_fee = try_math!(owner_and_host_fee.try_sub(host_fee))?;
+    let (protocol_fee, protocol_transfer_fee) = match global_config {
+        Some(global_config) => {
+            let protocol_fee = global_config.protocol_fee(owner_and_protocol_fee)?;
+            (
+                protocol_fee,
+                transfer_fee_config
+                    .calculate_epoch_fee(epoch, to_u64!(protocol_fee)?)
+                    .ok_or_else(|| error!(SwapError::FeeCalculationFailure))?,
+            )
+        }
+        None => (0, 0),
+    };
+    let owner_fee = try_math!(owner_and_protocol_fee.try_sub(protocol_fee))?;
+    let owner_transfer_fee = transfer_fee_config
+        .calculate_epoch_fee(epoch, to_u64!(owner_fee)?)
+        .ok_or_else(|| error!(SwapError::FeeCalculationFailure))?;
+
+    let vault_amount_in = try_math!(amount_in.try_sub(to_u64!(owner_and_host_fee)?))?;
+    let vault_transfer_fee = transfer_fee_config
+        .calculate_epoch_fee(epoch, vault_amount_in)
+        .ok_or_else(|| error!(SwapError::FeeCalculationFailure))?;
+
+    let amount_sub_fees = try_math!(try_math!(try_math!(try_math!(
+        amount_in.try_sub(vault_transfer_fee)
+    )?
+    .try_sub(owner_transfer_fee))?
+    .try_sub(host_transfer_fee))?
+    .try_sub(protocol_transfer_fee))?;
+
+    msg!(
+        "Subtract input token transfer fee: vault_transfer_amount={}, vault_transfer_fee={}, owner_fee={}, owner_fee_transfer_fee={}, host_fee={}, host_fee_transfer_fee={}, protocol_fee={}, protocol_fee_transfer_fee={}, amount={}, input_amount_sub_transfer_fees={}",
+        vault_amount_in,
+        vault_transfer_fee,
+        owner_fee,
+        owner_transfer_fee,
+        host_fee,
+        host_transfer_fee,
+        protocol_fee,
+        protocol_transfer_fee,
+        amount_in,
+        amount_sub_fees
+    );
+    Ok(amount_sub_fees)
+}
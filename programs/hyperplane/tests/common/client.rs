@@ -1,21 +1,68 @@
 #![allow(clippy::too_many_arguments)]
 
+use anchor_lang::AnchorDeserialize;
 use hyperplane::{
-    curve::calculator::{AorB, TradeDirection},
-    ix::{Deposit, Initialize, Swap, UpdatePoolConfig, Withdraw, WithdrawFees},
+    curve::{
+        calculator::{AorB, TradeDirection},
+        rate_provider::RateProvider,
+    },
+    dynamic_fee::DynamicFeeParameters,
+    event, ix,
+    ix::{
+        Deposit, DepositSingleTokenTypeExactAmountIn, Initialize, InitializeGlobalConfig,
+        RouteSwap, Swap, SwapExactOut, UpdateCurveConfig, UpdateGlobalConfig, UpdatePoolConfig,
+        Withdraw, WithdrawFees, WithdrawOneToken, WithdrawProtocolFees,
+        WithdrawSingleTokenTypeExactAmountOut,
+    },
     state::SwapPool,
 };
 use solana_program_test::BanksClientError;
-use solana_sdk::{instruction::Instruction, system_instruction};
+use solana_sdk::{
+    instruction::Instruction, pubkey::Pubkey, signature::Keypair, signer::Signer,
+    system_instruction, transaction::Transaction,
+};
 
 use super::types::{PoolUserAccounts, SwapPoolAccounts, TestContext};
 use crate::send_tx;
 
+pub async fn initialize_global_config(
+    ctx: &mut TestContext,
+    upgrade_authority: &Keypair,
+    initialize_global_config: InitializeGlobalConfig,
+) -> Result<(), BanksClientError> {
+    send_tx!(
+        ctx,
+        [instructions::initialize_global_config(
+            &upgrade_authority.pubkey(),
+            initialize_global_config
+        )],
+        upgrade_authority
+    )
+}
+
+pub async fn update_global_config(
+    ctx: &mut TestContext,
+    owner: &Keypair,
+    update_global_config: UpdateGlobalConfig,
+) -> Result<(), BanksClientError> {
+    send_tx!(
+        ctx,
+        [instructions::update_global_config(
+            &owner.pubkey(),
+            update_global_config
+        )],
+        owner
+    )
+}
+
 pub async fn initialize_pool(
     ctx: &mut TestContext,
     pool: &SwapPoolAccounts,
     initialize: Initialize,
 ) -> Result<(), BanksClientError> {
+    let ix =
+        with_transfer_hook_accounts(ctx, pool, instructions::initialize_pool(pool, initialize))
+            .await;
     send_tx!(
         ctx,
         [
@@ -26,7 +73,7 @@ pub async fn initialize_pool(
                 SwapPool::LEN as u64,
                 &hyperplane::id(),
             ),
-            instructions::initialize_pool(pool, initialize)
+            ix
         ],
         pool.pool.as_ref(),
         pool.admin.admin.as_ref(),
@@ -40,11 +87,30 @@ pub async fn deposit(
     user: &PoolUserAccounts,
     deposit: Deposit,
 ) -> Result<(), BanksClientError> {
-    send_tx!(
+    let ix =
+        with_transfer_hook_accounts(ctx, pool, instructions::deposit(pool, user, deposit)).await;
+    send_tx!(ctx, [ix], user.user.as_ref())
+}
+
+pub async fn deposit_single_token_type_exact_amount_in(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    user: &PoolUserAccounts,
+    trade_direction: TradeDirection,
+    deposit: DepositSingleTokenTypeExactAmountIn,
+) -> Result<(), BanksClientError> {
+    let ix = with_transfer_hook_accounts(
         ctx,
-        [instructions::deposit(pool, user, deposit)],
-        user.user.as_ref()
+        pool,
+        instructions::deposit_single_token_type_exact_amount_in(
+            pool,
+            user,
+            trade_direction,
+            deposit,
+        ),
     )
+    .await;
+    send_tx!(ctx, [ix], user.user.as_ref())
 }
 
 pub async fn swap_with_host_fees(
@@ -55,17 +121,13 @@ pub async fn swap_with_host_fees(
     trade_direction: TradeDirection,
     swap: Swap,
 ) -> Result<(), BanksClientError> {
-    send_tx!(
+    let ix = with_transfer_hook_accounts(
         ctx,
-        [instructions::swap(
-            pool,
-            user,
-            host_fees,
-            trade_direction,
-            swap
-        )],
-        user.user.as_ref()
+        pool,
+        instructions::swap(pool, user, host_fees, trade_direction, swap),
     )
+    .await;
+    send_tx!(ctx, [ix], user.user.as_ref())
 }
 
 pub async fn swap(
@@ -78,17 +140,131 @@ pub async fn swap(
     swap_with_host_fees(ctx, pool, user, None, trade_direction, swap).await
 }
 
+/// Swap, returning the swap event returned by the program
+pub async fn swap_with_event(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    user: &PoolUserAccounts,
+    trade_direction: TradeDirection,
+    swap: Swap,
+) -> Result<event::Swap, BanksClientError> {
+    let ix = with_transfer_hook_accounts(
+        ctx,
+        pool,
+        instructions::swap(pool, user, None, trade_direction, swap),
+    )
+    .await;
+    let hash = ctx.context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.context.payer.pubkey()),
+        &[&ctx.context.payer, user.user.as_ref()],
+        hash,
+    );
+    let result = ctx
+        .context
+        .banks_client
+        .process_transaction_with_metadata(transaction)
+        .await?;
+    result.result.map_err(BanksClientError::TransactionError)?;
+    let return_data = result.metadata.unwrap().return_data.unwrap();
+    Ok(event::Swap::try_from_slice(&return_data.data).unwrap())
+}
+
+pub async fn swap_exact_out_with_host_fees(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    user: &PoolUserAccounts,
+    host_fees: Option<&PoolUserAccounts>,
+    trade_direction: TradeDirection,
+    swap: SwapExactOut,
+) -> Result<(), BanksClientError> {
+    let ix = with_transfer_hook_accounts(
+        ctx,
+        pool,
+        instructions::swap_exact_out(pool, user, host_fees, trade_direction, swap),
+    )
+    .await;
+    send_tx!(ctx, [ix], user.user.as_ref())
+}
+
+/// Swap through a route of pools, each hop trading in the given direction
+pub async fn route_swap(
+    ctx: &mut TestContext,
+    user: &Keypair,
+    source_user_ata: &Pubkey,
+    destination_user_ata: &Pubkey,
+    route: &[(&SwapPoolAccounts, TradeDirection)],
+    route_swap: RouteSwap,
+) -> Result<(), BanksClientError> {
+    send_tx!(
+        ctx,
+        [instructions::route_swap(
+            &user.pubkey(),
+            source_user_ata,
+            destination_user_ata,
+            route,
+            route_swap
+        )],
+        user
+    )
+}
+
+pub async fn swap_exact_out(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    user: &PoolUserAccounts,
+    trade_direction: TradeDirection,
+    swap: SwapExactOut,
+) -> Result<(), BanksClientError> {
+    swap_exact_out_with_host_fees(ctx, pool, user, None, trade_direction, swap).await
+}
+
 pub async fn withdraw(
     ctx: &mut TestContext,
     pool: &SwapPoolAccounts,
     user: &PoolUserAccounts,
     withdraw: Withdraw,
 ) -> Result<(), BanksClientError> {
-    send_tx!(
+    let ix =
+        with_transfer_hook_accounts(ctx, pool, instructions::withdraw(pool, user, withdraw)).await;
+    send_tx!(ctx, [ix], user.user.as_ref())
+}
+
+pub async fn withdraw_single_token_type_exact_amount_out(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    user: &PoolUserAccounts,
+    trade_direction: TradeDirection,
+    withdraw: WithdrawSingleTokenTypeExactAmountOut,
+) -> Result<(), BanksClientError> {
+    let ix = with_transfer_hook_accounts(
         ctx,
-        [instructions::withdraw(pool, user, withdraw)],
-        user.user.as_ref()
+        pool,
+        instructions::withdraw_single_token_type_exact_amount_out(
+            pool,
+            user,
+            trade_direction,
+            withdraw,
+        ),
     )
+    .await;
+    send_tx!(ctx, [ix], user.user.as_ref())
+}
+
+pub async fn withdraw_one_token(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    user: &PoolUserAccounts,
+    withdraw: WithdrawOneToken,
+) -> Result<(), BanksClientError> {
+    let ix = with_transfer_hook_accounts(
+        ctx,
+        pool,
+        instructions::withdraw_one_token(pool, user, withdraw),
+    )
+    .await;
+    send_tx!(ctx, [ix], user.user.as_ref())
 }
 
 pub async fn withdraw_fees(
@@ -96,11 +272,51 @@ pub async fn withdraw_fees(
     pool: &SwapPoolAccounts,
     a_or_b: AorB,
     withdraw_fees: WithdrawFees,
+) -> Result<(), BanksClientError> {
+    let ix = with_transfer_hook_accounts(
+        ctx,
+        pool,
+        instructions::withdraw_fees(pool, a_or_b, withdraw_fees),
+    )
+    .await;
+    send_tx!(ctx, [ix], pool.admin.admin.as_ref())
+}
+
+pub async fn withdraw_protocol_fees(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    protocol_authority: &Keypair,
+    protocol_authority_fees_ata: &Pubkey,
+    a_or_b: AorB,
+    withdraw_protocol_fees: WithdrawProtocolFees,
+) -> Result<(), BanksClientError> {
+    let ix = with_transfer_hook_accounts(
+        ctx,
+        pool,
+        instructions::withdraw_protocol_fees(
+            pool,
+            &protocol_authority.pubkey(),
+            protocol_authority_fees_ata,
+            a_or_b,
+            withdraw_protocol_fees,
+        ),
+    )
+    .await;
+    send_tx!(ctx, [ix], protocol_authority)
+}
+
+pub async fn initialize_protocol_fees_vaults(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    payer: &Keypair,
 ) -> Result<(), BanksClientError> {
     send_tx!(
         ctx,
-        [instructions::withdraw_fees(pool, a_or_b, withdraw_fees)],
-        pool.admin.admin.as_ref()
+        [instructions::initialize_protocol_fees_vaults(
+            pool,
+            &payer.pubkey()
+        )],
+        payer
     )
 }
 
@@ -116,12 +332,180 @@ pub async fn update_pool_config(
     )
 }
 
+pub async fn update_curve_config(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    update_curve_config: UpdateCurveConfig,
+) -> Result<(), BanksClientError> {
+    send_tx!(
+        ctx,
+        [instructions::update_curve_config(pool, update_curve_config)],
+        pool.admin.admin.as_ref()
+    )
+}
+
+pub async fn propose_new_admin(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    new_admin: &Pubkey,
+) -> Result<(), BanksClientError> {
+    send_tx!(
+        ctx,
+        [instructions::propose_new_admin(pool, new_admin)],
+        pool.admin.admin.as_ref()
+    )
+}
+
+pub async fn accept_admin(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    pending_admin: &Keypair,
+) -> Result<(), BanksClientError> {
+    send_tx!(
+        ctx,
+        [instructions::accept_admin(pool, &pending_admin.pubkey())],
+        pending_admin
+    )
+}
+
+pub async fn ramp_amp(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    target_amp: u64,
+    ramp_end_ts: u64,
+) -> Result<(), BanksClientError> {
+    send_tx!(
+        ctx,
+        [instructions::ramp_amp(pool, target_amp, ramp_end_ts)],
+        pool.admin.admin.as_ref()
+    )
+}
+
+pub async fn stop_ramp_amp(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+) -> Result<(), BanksClientError> {
+    send_tx!(
+        ctx,
+        [instructions::stop_ramp_amp(pool)],
+        pool.admin.admin.as_ref()
+    )
+}
+
+pub async fn set_rate_provider(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    rate_provider: RateProvider,
+) -> Result<(), BanksClientError> {
+    send_tx!(
+        ctx,
+        [instructions::set_rate_provider(pool, rate_provider)],
+        pool.admin.admin.as_ref()
+    )
+}
+
+pub async fn update_rate(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    rate_updater: &Keypair,
+    token_b_rate: u64,
+) -> Result<(), BanksClientError> {
+    send_tx!(
+        ctx,
+        [instructions::update_rate(
+            pool,
+            &rate_updater.pubkey(),
+            token_b_rate
+        )],
+        rate_updater
+    )
+}
+
+pub async fn update_dynamic_fee(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    parameters: Option<DynamicFeeParameters>,
+) -> Result<(), BanksClientError> {
+    send_tx!(
+        ctx,
+        [instructions::update_dynamic_fee(pool, parameters)],
+        pool.admin.admin.as_ref()
+    )
+}
+
+pub async fn close_pool(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+) -> Result<(), BanksClientError> {
+    let ix = with_transfer_hook_accounts(ctx, pool, instructions::close_pool(pool)).await;
+    send_tx!(ctx, [ix], pool.admin.admin.as_ref())
+}
+
+/// Append the accounts of the test transfer hook for the trading tokens of the pool using it
+async fn with_transfer_hook_accounts(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    mut ix: Instruction,
+) -> Instruction {
+    let mut remaining_accounts = vec![];
+    for mint in [&pool.token_a_mint, &pool.token_b_mint] {
+        let banks_client = ctx.context.banks_client.clone();
+        // the extra accounts of the test hook only depend on the mint, not on the transfer
+        let transfer_hook_accounts = ix::transfer_hook_accounts(
+            |address| {
+                let mut banks_client = banks_client.clone();
+                async move {
+                    Ok(banks_client
+                        .get_account(address)
+                        .await
+                        .ok()
+                        .flatten()
+                        .map(|account| account.data))
+                }
+            },
+            &Pubkey::default(),
+            mint,
+            &Pubkey::default(),
+            &Pubkey::default(),
+            0,
+        )
+        .await
+        // not a mint, e.g. in the security tests swapping out the pool accounts
+        .unwrap_or_default();
+        ix::merge_remaining_accounts(&mut remaining_accounts, &transfer_hook_accounts);
+    }
+    ix.accounts.extend(remaining_accounts);
+    ix
+}
+
 pub(crate) mod instructions {
-    use hyperplane::{ix, ix::Deposit};
+    use hyperplane::{
+        ix,
+        ix::{Deposit, RouteSwapHop},
+    };
     use solana_sdk::signer::Signer;
 
     use super::*;
 
+    pub fn initialize_global_config(
+        upgrade_authority: &Pubkey,
+        initialize_global_config: InitializeGlobalConfig,
+    ) -> Instruction {
+        ix::initialize_global_config(
+            &hyperplane::id(),
+            upgrade_authority,
+            initialize_global_config,
+        )
+        .unwrap()
+    }
+
+    pub fn update_global_config(
+        owner: &Pubkey,
+        update_global_config: UpdateGlobalConfig,
+    ) -> Instruction {
+        ix::update_global_config(&hyperplane::id(), owner, update_global_config).unwrap()
+    }
+
     pub fn initialize_pool(pool: &SwapPoolAccounts, initialize: Initialize) -> Instruction {
         ix::initialize_pool(
             &hyperplane::id(),
@@ -136,6 +520,8 @@ pub(crate) mod instructions {
             &pool.pool_token_mint,
             &pool.token_a_fees_vault,
             &pool.token_b_fees_vault,
+            &pool.token_a_protocol_fees_vault,
+            &pool.token_b_protocol_fees_vault,
             &pool.admin.token_a_ata,
             &pool.admin.token_b_ata,
             &pool.admin.pool_token_ata.pubkey(),
@@ -174,6 +560,47 @@ pub(crate) mod instructions {
         .unwrap()
     }
 
+    pub fn deposit_single_token_type_exact_amount_in(
+        pool: &SwapPoolAccounts,
+        user: &PoolUserAccounts,
+        trade_direction: TradeDirection,
+        deposit: DepositSingleTokenTypeExactAmountIn,
+    ) -> Instruction {
+        let (source_mint, source_token_program, source_fees_vault, user_source_ata) =
+            match trade_direction {
+                TradeDirection::AtoB => (
+                    &pool.token_a_mint,
+                    &pool.token_a_token_program,
+                    &pool.token_a_fees_vault,
+                    &user.token_a_ata,
+                ),
+                TradeDirection::BtoA => (
+                    &pool.token_b_mint,
+                    &pool.token_b_token_program,
+                    &pool.token_b_fees_vault,
+                    &user.token_b_ata,
+                ),
+            };
+        ix::deposit_single_token_type_exact_amount_in(
+            &hyperplane::id(),
+            &user.pubkey(),
+            &pool.pubkey(),
+            &pool.curve,
+            &pool.authority,
+            source_mint,
+            &pool.token_a_vault,
+            &pool.token_b_vault,
+            source_fees_vault,
+            &pool.pool_token_mint,
+            user_source_ata,
+            &user.pool_token_ata,
+            &pool.pool_token_program,
+            source_token_program,
+            deposit,
+        )
+        .unwrap()
+    }
+
     pub fn swap(
         pool: &SwapPoolAccounts,
         user: &PoolUserAccounts,
@@ -187,6 +614,7 @@ pub(crate) mod instructions {
                 source_token_program,
                 source_vault,
                 source_fees_vault,
+                source_protocol_fees_vault,
                 user_source_ata,
                 host_fees_source_ata,
             ),
@@ -200,6 +628,7 @@ pub(crate) mod instructions {
                         &pool.token_a_token_program,
                         &pool.token_a_vault,
                         &pool.token_a_fees_vault,
+                        &pool.token_a_protocol_fees_vault,
                         &user.token_a_ata,
                         host_fees_source_ata,
                     ),
@@ -219,6 +648,7 @@ pub(crate) mod instructions {
                         &pool.token_b_token_program,
                         &pool.token_b_vault,
                         &pool.token_b_fees_vault,
+                        &pool.token_b_protocol_fees_vault,
                         &user.token_b_ata,
                         host_fees_source_ata,
                     ),
@@ -242,6 +672,159 @@ pub(crate) mod instructions {
             source_vault,
             destination_vault,
             source_fees_vault,
+            source_protocol_fees_vault,
+            user_source_ata,
+            user_destination_ata,
+            host_fees_source_ata,
+            source_token_program,
+            destination_token_program,
+            swap,
+        )
+        .unwrap()
+    }
+
+    pub fn route_swap(
+        user: &Pubkey,
+        source_user_ata: &Pubkey,
+        destination_user_ata: &Pubkey,
+        route: &[(&SwapPoolAccounts, TradeDirection)],
+        route_swap: RouteSwap,
+    ) -> Instruction {
+        let hops: Vec<RouteSwapHop> = route
+            .iter()
+            .map(|(pool, trade_direction)| {
+                let (
+                    source_vault,
+                    destination_vault,
+                    source_token_fees_vault,
+                    source_token_protocol_fees_vault,
+                    destination_mint,
+                    destination_token_program,
+                ) = match trade_direction {
+                    TradeDirection::AtoB => (
+                        pool.token_a_vault,
+                        pool.token_b_vault,
+                        pool.token_a_fees_vault,
+                        pool.token_a_protocol_fees_vault,
+                        pool.token_b_mint,
+                        pool.token_b_token_program,
+                    ),
+                    TradeDirection::BtoA => (
+                        pool.token_b_vault,
+                        pool.token_a_vault,
+                        pool.token_b_fees_vault,
+                        pool.token_b_protocol_fees_vault,
+                        pool.token_a_mint,
+                        pool.token_a_token_program,
+                    ),
+                };
+                RouteSwapHop::new(
+                    pool.pubkey(),
+                    pool.curve,
+                    pool.authority,
+                    source_vault,
+                    destination_vault,
+                    source_token_fees_vault,
+                    source_token_protocol_fees_vault,
+                    destination_mint,
+                    destination_token_program,
+                )
+            })
+            .collect();
+        let (first_pool, first_trade_direction) = route.first().unwrap();
+        let (source_mint, source_token_program) = match first_trade_direction {
+            TradeDirection::AtoB => (first_pool.token_a_mint, first_pool.token_a_token_program),
+            TradeDirection::BtoA => (first_pool.token_b_mint, first_pool.token_b_token_program),
+        };
+        let last_hop = hops.last().unwrap();
+
+        ix::route_swap(
+            &hyperplane::id(),
+            user,
+            &source_mint,
+            &last_hop.destination_mint,
+            source_user_ata,
+            destination_user_ata,
+            &source_token_program,
+            &last_hop.destination_token_program,
+            &hops,
+            route_swap,
+        )
+        .unwrap()
+    }
+
+    pub fn swap_exact_out(
+        pool: &SwapPoolAccounts,
+        user: &PoolUserAccounts,
+        host_fees: Option<&PoolUserAccounts>,
+        trade_direction: TradeDirection,
+        swap: SwapExactOut,
+    ) -> Instruction {
+        let (
+            (
+                source_mint,
+                source_token_program,
+                source_vault,
+                source_fees_vault,
+                source_protocol_fees_vault,
+                user_source_ata,
+                host_fees_source_ata,
+            ),
+            (destination_mint, destination_token_program, destination_vault, user_destination_ata),
+        ) = match trade_direction {
+            TradeDirection::AtoB => {
+                let host_fees_source_ata = host_fees.map(|host_fees| &host_fees.token_a_ata);
+                (
+                    (
+                        &pool.token_a_mint,
+                        &pool.token_a_token_program,
+                        &pool.token_a_vault,
+                        &pool.token_a_fees_vault,
+                        &pool.token_a_protocol_fees_vault,
+                        &user.token_a_ata,
+                        host_fees_source_ata,
+                    ),
+                    (
+                        &pool.token_b_mint,
+                        &pool.token_b_token_program,
+                        &pool.token_b_vault,
+                        &user.token_b_ata,
+                    ),
+                )
+            }
+            TradeDirection::BtoA => {
+                let host_fees_source_ata = host_fees.map(|host_fees| &host_fees.token_b_ata);
+                (
+                    (
+                        &pool.token_b_mint,
+                        &pool.token_b_token_program,
+                        &pool.token_b_vault,
+                        &pool.token_b_fees_vault,
+                        &pool.token_b_protocol_fees_vault,
+                        &user.token_b_ata,
+                        host_fees_source_ata,
+                    ),
+                    (
+                        &pool.token_a_mint,
+                        &pool.token_a_token_program,
+                        &pool.token_a_vault,
+                        &user.token_a_ata,
+                    ),
+                )
+            }
+        };
+        ix::swap_exact_out(
+            &hyperplane::id(),
+            &user.pubkey(),
+            &pool.pubkey(),
+            &pool.curve,
+            &pool.authority,
+            source_mint,
+            destination_mint,
+            source_vault,
+            destination_vault,
+            source_fees_vault,
+            source_protocol_fees_vault,
             user_source_ata,
             user_destination_ata,
             host_fees_source_ata,
@@ -281,6 +864,95 @@ pub(crate) mod instructions {
         .unwrap()
     }
 
+    pub fn withdraw_single_token_type_exact_amount_out(
+        pool: &SwapPoolAccounts,
+        user: &PoolUserAccounts,
+        trade_direction: TradeDirection,
+        withdraw: WithdrawSingleTokenTypeExactAmountOut,
+    ) -> Instruction {
+        let (
+            destination_mint,
+            destination_token_program,
+            destination_fees_vault,
+            user_destination_ata,
+        ) = match trade_direction {
+            TradeDirection::AtoB => (
+                &pool.token_a_mint,
+                &pool.token_a_token_program,
+                &pool.token_a_fees_vault,
+                &user.token_a_ata,
+            ),
+            TradeDirection::BtoA => (
+                &pool.token_b_mint,
+                &pool.token_b_token_program,
+                &pool.token_b_fees_vault,
+                &user.token_b_ata,
+            ),
+        };
+        ix::withdraw_single_token_type_exact_amount_out(
+            &hyperplane::id(),
+            &user.pubkey(),
+            &pool.pubkey(),
+            &pool.curve,
+            &pool.authority,
+            destination_mint,
+            &pool.token_a_vault,
+            &pool.token_b_vault,
+            destination_fees_vault,
+            &pool.pool_token_mint,
+            user_destination_ata,
+            &user.pool_token_ata,
+            &pool.pool_token_program,
+            destination_token_program,
+            withdraw,
+        )
+        .unwrap()
+    }
+
+    pub fn withdraw_one_token(
+        pool: &SwapPoolAccounts,
+        user: &PoolUserAccounts,
+        withdraw: WithdrawOneToken,
+    ) -> Instruction {
+        let (
+            destination_mint,
+            destination_token_program,
+            destination_fees_vault,
+            user_destination_ata,
+        ) = match withdraw.a_or_b {
+            AorB::A => (
+                &pool.token_a_mint,
+                &pool.token_a_token_program,
+                &pool.token_a_fees_vault,
+                &user.token_a_ata,
+            ),
+            AorB::B => (
+                &pool.token_b_mint,
+                &pool.token_b_token_program,
+                &pool.token_b_fees_vault,
+                &user.token_b_ata,
+            ),
+        };
+        ix::withdraw_one_token(
+            &hyperplane::id(),
+            &user.pubkey(),
+            &pool.pubkey(),
+            &pool.curve,
+            &pool.authority,
+            destination_mint,
+            &pool.token_a_vault,
+            &pool.token_b_vault,
+            destination_fees_vault,
+            &pool.pool_token_mint,
+            user_destination_ata,
+            &user.pool_token_ata,
+            &pool.pool_token_program,
+            destination_token_program,
+            withdraw,
+        )
+        .unwrap()
+    }
+
     pub fn withdraw_fees(
         pool: &SwapPoolAccounts,
         a_or_b: AorB,
@@ -315,6 +987,54 @@ pub(crate) mod instructions {
         .unwrap()
     }
 
+    pub fn withdraw_protocol_fees(
+        pool: &SwapPoolAccounts,
+        protocol_authority: &Pubkey,
+        protocol_authority_fees_ata: &Pubkey,
+        a_or_b: AorB,
+        withdraw_protocol_fees: WithdrawProtocolFees,
+    ) -> Instruction {
+        let (fees_mint, protocol_fees_vault, fees_token_program) = match a_or_b {
+            AorB::A => (
+                &pool.token_a_mint,
+                &pool.token_a_protocol_fees_vault,
+                &pool.token_a_token_program,
+            ),
+            AorB::B => (
+                &pool.token_b_mint,
+                &pool.token_b_protocol_fees_vault,
+                &pool.token_b_token_program,
+            ),
+        };
+
+        ix::withdraw_protocol_fees(
+            &hyperplane::id(),
+            protocol_authority,
+            &pool.pubkey(),
+            &pool.authority,
+            fees_mint,
+            protocol_fees_vault,
+            protocol_authority_fees_ata,
+            fees_token_program,
+            withdraw_protocol_fees,
+        )
+        .unwrap()
+    }
+
+    pub fn initialize_protocol_fees_vaults(pool: &SwapPoolAccounts, payer: &Pubkey) -> Instruction {
+        ix::initialize_protocol_fees_vaults(
+            &hyperplane::id(),
+            payer,
+            &pool.pubkey(),
+            &pool.authority,
+            &pool.token_a_mint,
+            &pool.token_b_mint,
+            &pool.token_a_token_program,
+            &pool.token_b_token_program,
+        )
+        .unwrap()
+    }
+
     pub fn update_pool_config(
         pool: &SwapPoolAccounts,
         update_pool_config: UpdatePoolConfig,
@@ -327,4 +1047,118 @@ pub(crate) mod instructions {
         )
         .unwrap()
     }
+
+    pub fn propose_new_admin(pool: &SwapPoolAccounts, new_admin: &Pubkey) -> Instruction {
+        ix::propose_new_admin(
+            &hyperplane::id(),
+            &pool.admin.pubkey(),
+            &pool.pubkey(),
+            new_admin,
+        )
+        .unwrap()
+    }
+
+    pub fn accept_admin(pool: &SwapPoolAccounts, pending_admin: &Pubkey) -> Instruction {
+        ix::accept_admin(&hyperplane::id(), pending_admin, &pool.pubkey()).unwrap()
+    }
+
+    pub fn update_curve_config(
+        pool: &SwapPoolAccounts,
+        update_curve_config: UpdateCurveConfig,
+    ) -> Instruction {
+        ix::update_curve_config(
+            &hyperplane::id(),
+            &pool.admin.pubkey(),
+            &pool.pubkey(),
+            &pool.curve,
+            update_curve_config,
+        )
+        .unwrap()
+    }
+
+    pub fn ramp_amp(pool: &SwapPoolAccounts, target_amp: u64, ramp_end_ts: u64) -> Instruction {
+        ix::ramp_amp(
+            &hyperplane::id(),
+            &pool.admin.pubkey(),
+            &pool.pubkey(),
+            &pool.curve,
+            target_amp,
+            ramp_end_ts,
+        )
+        .unwrap()
+    }
+
+    pub fn stop_ramp_amp(pool: &SwapPoolAccounts) -> Instruction {
+        ix::stop_ramp_amp(
+            &hyperplane::id(),
+            &pool.admin.pubkey(),
+            &pool.pubkey(),
+            &pool.curve,
+        )
+        .unwrap()
+    }
+
+    pub fn set_rate_provider(pool: &SwapPoolAccounts, rate_provider: RateProvider) -> Instruction {
+        ix::set_rate_provider(
+            &hyperplane::id(),
+            &pool.admin.pubkey(),
+            &pool.pubkey(),
+            &pool.curve,
+            rate_provider,
+        )
+        .unwrap()
+    }
+
+    pub fn update_rate(
+        pool: &SwapPoolAccounts,
+        rate_updater: &Pubkey,
+        token_b_rate: u64,
+    ) -> Instruction {
+        ix::update_rate(
+            &hyperplane::id(),
+            rate_updater,
+            &pool.pubkey(),
+            &pool.curve,
+            token_b_rate,
+        )
+        .unwrap()
+    }
+
+    pub fn update_dynamic_fee(
+        pool: &SwapPoolAccounts,
+        parameters: Option<DynamicFeeParameters>,
+    ) -> Instruction {
+        ix::update_dynamic_fee(
+            &hyperplane::id(),
+            &pool.admin.pubkey(),
+            &pool.pubkey(),
+            parameters,
+        )
+        .unwrap()
+    }
+
+    pub fn close_pool(pool: &SwapPoolAccounts) -> Instruction {
+        ix::close_pool(
+            &hyperplane::id(),
+            &pool.admin.pubkey(),
+            &pool.pubkey(),
+            &pool.curve,
+            &pool.authority,
+            &pool.token_a_mint,
+            &pool.token_b_mint,
+            &pool.token_a_vault,
+            &pool.token_b_vault,
+            &pool.pool_token_mint,
+            &pool.token_a_fees_vault,
+            &pool.token_b_fees_vault,
+            &pool.token_a_protocol_fees_vault,
+            &pool.token_b_protocol_fees_vault,
+            &pool.admin.token_a_ata,
+            &pool.admin.token_b_ata,
+            &pool.token_a_token_program,
+            &pool.token_b_token_program,
+            &pool.pool_token_program,
+        )
+        .unwrap()
+    }
 }
@@ -1,12 +1,17 @@
 use hyperplane::{curve::fees::Fees, ix::Initialize, CurveUserParameters, InitialSupply};
-use solana_sdk::native_token::sol_to_lamports;
+use solana_sdk::{native_token::sol_to_lamports, pubkey::Pubkey};
 
 use crate::common::{
     client, setup, token_operations,
     types::{SwapPairSpec, SwapPoolAccounts, TestContext},
 };
 
-pub enum ProgramDependency {}
+pub enum ProgramDependency {
+    /// The test transfer hook program, along with a token-2022 version executing transfer hooks
+    TransferHook,
+    /// A token-2022 version supporting the metadata pointer and token metadata extensions
+    TokenMetadata,
+}
 
 pub async fn new_pool(
     ctx: &mut TestContext,
@@ -15,6 +20,39 @@ pub async fn new_pool(
     trading_tokens: SwapPairSpec,
     curve_parameters: CurveUserParameters,
 ) -> SwapPoolAccounts {
+    let initial_supply = initial_supply_with_transfer_fees(initial_supply, trading_tokens);
+    let pool = setup::new_pool_accs(ctx, trading_tokens, &initial_supply).await;
+
+    initialize_pool(ctx, pool, fees, initial_supply, curve_parameters).await
+}
+
+/// New pool of existing mints, e.g. to route swaps through several pools sharing a mint
+pub async fn new_pool_for_mints(
+    ctx: &mut TestContext,
+    fees: Fees,
+    initial_supply: InitialSupply,
+    trading_tokens: SwapPairSpec,
+    token_a_mint: &Pubkey,
+    token_b_mint: &Pubkey,
+    curve_parameters: CurveUserParameters,
+) -> SwapPoolAccounts {
+    let initial_supply = initial_supply_with_transfer_fees(initial_supply, trading_tokens);
+    let pool = setup::new_pool_accs_for_mints(
+        ctx,
+        trading_tokens,
+        token_a_mint,
+        token_b_mint,
+        &initial_supply,
+    )
+    .await;
+
+    initialize_pool(ctx, pool, fees, initial_supply, curve_parameters).await
+}
+
+fn initial_supply_with_transfer_fees(
+    initial_supply: InitialSupply,
+    trading_tokens: SwapPairSpec,
+) -> InitialSupply {
     let initial_supply_a = token_operations::amount_with_transfer_fees(
         initial_supply.initial_supply_a,
         trading_tokens.a.transfer_fee_bps,
@@ -23,10 +61,16 @@ pub async fn new_pool(
         initial_supply.initial_supply_b,
         trading_tokens.b.transfer_fee_bps,
     );
-    let initial_supply = InitialSupply::new(initial_supply_a, initial_supply_b);
-
-    let pool = setup::new_pool_accs(ctx, trading_tokens, &initial_supply).await;
+    InitialSupply::new(initial_supply_a, initial_supply_b)
+}
 
+async fn initialize_pool(
+    ctx: &mut TestContext,
+    pool: SwapPoolAccounts,
+    fees: Fees,
+    initial_supply: InitialSupply,
+    curve_parameters: CurveUserParameters,
+) -> SwapPoolAccounts {
     client::initialize_pool(
         ctx,
         &pool,
@@ -34,6 +78,7 @@ pub async fn new_pool(
             fees,
             initial_supply,
             curve_parameters,
+            pool_token_metadata: None,
         },
     )
     .await
@@ -8,5 +8,6 @@ pub mod runner;
 pub mod setup;
 pub mod state;
 pub mod token_operations;
+pub mod transfer_hook;
 pub mod types;
 pub mod utils;
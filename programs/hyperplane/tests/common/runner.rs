@@ -1,24 +1,116 @@
+use anchor_spl::token_2022::spl_token_2022;
+use hyperplane::{
+    curve::{base::CurveType, fees::Fees},
+    ix::InitializeGlobalConfig,
+    utils::seeds,
+};
 use solana_program_test::ProgramTest;
+use solana_sdk::{
+    account::Account,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    pubkey::Pubkey,
+    signer::Signer,
+    system_program,
+};
 
 use super::types::TestContext;
-use crate::common::fixtures::ProgramDependency;
+use crate::common::{
+    client,
+    fixtures::{ProgramDependency, Sol},
+    setup::kp,
+    transfer_hook,
+};
 
 pub fn program(dependencies: &[ProgramDependency]) -> ProgramTest {
-    let program_test = ProgramTest::new(
+    let mut program_test = ProgramTest::new(
         "hyperplane",
         hyperplane::ID,
         solana_program_test::processor!(hyperplane::entry),
     );
 
-    dependencies
-        .iter()
-        .for_each(|_dep| unimplemented!("No dependency supported yet."));
+    // the token-2022 program bundled with program-test predates transfer hooks and token metadata
+    if !dependencies.is_empty() {
+        program_test.add_program(
+            "spl_token_2022",
+            spl_token_2022::id(),
+            solana_program_test::processor!(spl_token_2022_latest::processor::Processor::process),
+        );
+    }
+    dependencies.iter().for_each(|dep| match dep {
+        ProgramDependency::TransferHook => {
+            program_test.add_program(
+                "transfer_hook",
+                transfer_hook::id(),
+                solana_program_test::processor!(transfer_hook::process),
+            );
+        }
+        ProgramDependency::TokenMetadata => {}
+    });
     program_test
 }
 
 pub async fn start(test: ProgramTest) -> TestContext {
-    let mut context = test.start_with_context().await;
+    let mut ctx = start_without_global_config(test).await;
+    // permissive global config so tests can create any kind of pool
+    let owner = ctx.global_config_owner.clone();
+    client::initialize_global_config(
+        &mut ctx,
+        &owner,
+        InitializeGlobalConfig::new(owner.pubkey(), Fees::default(), all_curve_types(), vec![]),
+    )
+    .await
+    .unwrap();
+
+    ctx
+}
+
+/// Start the program with the global config owner as its upgrade authority, before the global
+/// config is initialized
+pub async fn start_without_global_config(mut test: ProgramTest) -> TestContext {
+    let global_config_owner = kp();
+    test.add_account(
+        global_config_owner.pubkey(),
+        Account::new(Sol::one(), 0, &system_program::id()),
+    );
+    test.add_account(
+        seeds::pda::program_data_pda_program_id(&hyperplane::id()).0,
+        program_data_account(&global_config_owner.pubkey()),
+    );
+
+    let context = test.start_with_context().await;
     let rent = context.banks_client.get_rent().await.unwrap();
 
-    TestContext { context, rent }
+    TestContext {
+        context,
+        rent,
+        global_config_owner,
+    }
+}
+
+/// Program data account of an upgradeable program with the given upgrade authority
+fn program_data_account(upgrade_authority: &Pubkey) -> Account {
+    let data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+        slot: 0,
+        upgrade_authority_address: Some(*upgrade_authority),
+    })
+    .unwrap();
+    Account {
+        lamports: Sol::one(),
+        data,
+        owner: bpf_loader_upgradeable::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub fn all_curve_types() -> Vec<u64> {
+    vec![
+        CurveType::ConstantProduct.into(),
+        CurveType::ConstantPrice.into(),
+        CurveType::Offset.into(),
+        CurveType::Stable.into(),
+        CurveType::OraclePrice.into(),
+        CurveType::Concentrated.into(),
+        CurveType::Weighted.into(),
+    ]
 }
@@ -3,7 +3,7 @@ use std::sync::Arc;
 use anchor_lang::Id;
 use anchor_spl::token::Token;
 use hyperplane::{ix::Deposit, utils::seeds, InitialSupply};
-use solana_sdk::{signature::Keypair, signer::Signer, system_instruction};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, system_instruction};
 
 use super::{fixtures::Sol, token_operations, types::TestContext};
 use crate::{
@@ -135,8 +135,6 @@ pub async fn new_pool_accs(
     trading_tokens: SwapPairSpec,
     initial_supply: &InitialSupply,
 ) -> SwapPoolAccounts {
-    let admin = new_keypair(ctx, Sol::from(100.0)).await;
-
     let token_a_mint = kp();
     let token_b_mint = kp();
     token_operations::create_mint(ctx, &token_a_mint, trading_tokens.a)
@@ -146,6 +144,26 @@ pub async fn new_pool_accs(
         .await
         .unwrap();
 
+    new_pool_accs_for_mints(
+        ctx,
+        trading_tokens,
+        &token_a_mint.pubkey(),
+        &token_b_mint.pubkey(),
+        initial_supply,
+    )
+    .await
+}
+
+/// Pool accounts for a new pool of existing mints, the mints must have been created by `new_pool_accs`
+pub async fn new_pool_accs_for_mints(
+    ctx: &mut TestContext,
+    trading_tokens: SwapPairSpec,
+    token_a_mint: &Pubkey,
+    token_b_mint: &Pubkey,
+    initial_supply: &InitialSupply,
+) -> SwapPoolAccounts {
+    let admin = new_keypair(ctx, Sol::from(100.0)).await;
+
     let pool = kp();
 
     let seeds::pda::InitPoolPdas {
@@ -156,17 +174,15 @@ pub async fn new_pool_accs(
         pool_token_mint,
         token_a_fees_vault,
         token_b_fees_vault,
-    } = seeds::pda::init_pool_pdas(
-        &pool.pubkey(),
-        &token_a_mint.pubkey(),
-        &token_b_mint.pubkey(),
-    );
+        token_a_protocol_fees_vault,
+        token_b_protocol_fees_vault,
+    } = seeds::pda::init_pool_pdas(&pool.pubkey(), token_a_mint, token_b_mint);
 
     let token_a_admin_ata = token_operations::create_and_mint_to_token_account(
         ctx,
         &trading_tokens.a.token_program,
         &admin.pubkey(),
-        &token_a_mint.pubkey(),
+        token_a_mint,
         initial_supply.initial_supply_a,
     )
     .await;
@@ -174,7 +190,7 @@ pub async fn new_pool_accs(
         ctx,
         &trading_tokens.b.token_program,
         &admin.pubkey(),
-        &token_b_mint.pubkey(),
+        token_b_mint,
         initial_supply.initial_supply_b,
     )
     .await;
@@ -192,13 +208,15 @@ pub async fn new_pool_accs(
         pool,
         curve,
         authority,
-        token_a_mint: token_a_mint.pubkey(),
-        token_b_mint: token_b_mint.pubkey(),
+        token_a_mint: *token_a_mint,
+        token_b_mint: *token_b_mint,
         pool_token_mint,
         token_a_vault,
         token_b_vault,
         token_a_fees_vault,
         token_b_fees_vault,
+        token_a_protocol_fees_vault,
+        token_b_protocol_fees_vault,
         pool_token_program: Token::id(),
         token_a_token_program: trading_tokens.a.token_program,
         token_b_token_program: trading_tokens.b.token_program,
@@ -1,5 +1,11 @@
 use anchor_lang::{prelude::Pubkey, AccountDeserialize, Discriminator};
-use hyperplane::state::{ConstantProductCurve, StableCurve, SwapPool};
+use hyperplane::{
+    state::{
+        ConcentratedCurve, ConstantPriceCurve, ConstantProductCurve, GlobalConfig, OffsetCurve,
+        OraclePriceCurve, StableCurve, SwapPool, WeightedCurve,
+    },
+    utils::seeds,
+};
 use solana_sdk::account::Account;
 
 use crate::common::types::{SwapPoolAccounts, TestContext, TestError};
@@ -8,6 +14,10 @@ pub async fn get_pool(ctx: &mut TestContext, pool: &SwapPoolAccounts) -> SwapPoo
     get::<SwapPool>(ctx, pool.pubkey()).await
 }
 
+pub async fn get_global_config(ctx: &mut TestContext) -> GlobalConfig {
+    get::<GlobalConfig>(ctx, seeds::pda::global_config_pda().0).await
+}
+
 pub async fn get_constant_product_curve(
     ctx: &mut TestContext,
     pool: &SwapPoolAccounts,
@@ -15,10 +25,39 @@ pub async fn get_constant_product_curve(
     get::<ConstantProductCurve>(ctx, pool.curve).await
 }
 
+pub async fn get_constant_price_curve(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+) -> ConstantPriceCurve {
+    get::<ConstantPriceCurve>(ctx, pool.curve).await
+}
+
+pub async fn get_offset_curve(ctx: &mut TestContext, pool: &SwapPoolAccounts) -> OffsetCurve {
+    get::<OffsetCurve>(ctx, pool.curve).await
+}
+
+pub async fn get_oracle_price_curve(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+) -> OraclePriceCurve {
+    get::<OraclePriceCurve>(ctx, pool.curve).await
+}
+
 pub async fn get_stable_curve(ctx: &mut TestContext, pool: &SwapPoolAccounts) -> StableCurve {
     get::<StableCurve>(ctx, pool.curve).await
 }
 
+pub async fn get_concentrated_curve(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+) -> ConcentratedCurve {
+    get::<ConcentratedCurve>(ctx, pool.curve).await
+}
+
+pub async fn get_weighted_curve(ctx: &mut TestContext, pool: &SwapPoolAccounts) -> WeightedCurve {
+    get::<WeightedCurve>(ctx, pool.curve).await
+}
+
 pub async fn get<T: AccountDeserialize + Discriminator>(
     ctx: &mut TestContext,
     address: Pubkey,
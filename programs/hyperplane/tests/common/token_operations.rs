@@ -12,14 +12,20 @@ use anchor_spl::{
     token_interface::spl_token_2022::pod::{PodU16, PodU64},
 };
 use arrayref::array_ref;
+use hyperplane::utils::token_extensions;
 use solana_program_test::BanksClientError;
 use solana_sdk::{
     program_error::ProgramError, program_pack::Pack, signer::Signer, system_instruction,
     transport::TransportError,
 };
+use spl_token_2022_latest::{
+    extension::ExtensionType as ExtensionTypeWithHooks,
+    state::{Account as AccountWithHooks, Mint as MintWithHooks},
+};
 
 use super::{
     setup::{kp, KP},
+    transfer_hook,
     types::TestContext,
 };
 use crate::{common::types::TokenSpec, send_tx};
@@ -42,7 +48,11 @@ pub async fn create_token_account_kp(
     owner: &Pubkey,
 ) -> Result<Pubkey, BanksClientError> {
     let space = if token_program == &spl_token_2022::id() {
-        ExtensionType::get_account_len::<Account>(&[ExtensionType::TransferFeeAmount])
+        let mut extensions = vec![ExtensionTypeWithHooks::TransferFeeAmount];
+        if has_transfer_hook(ctx, mint).await {
+            extensions.push(ExtensionTypeWithHooks::TransferHookAccount);
+        }
+        ExtensionTypeWithHooks::try_calculate_account_len::<AccountWithHooks>(&extensions).unwrap()
     } else {
         Account::LEN
     };
@@ -70,6 +80,19 @@ pub async fn create_token_account_kp(
     Ok(account.pubkey())
 }
 
+async fn has_transfer_hook(ctx: &mut TestContext, mint: &Pubkey) -> bool {
+    let mint = ctx
+        .context
+        .banks_client
+        .get_account(*mint)
+        .await
+        .unwrap()
+        .unwrap();
+    token_extensions::transfer_hook_program_id(&mint.data)
+        .unwrap()
+        .is_some()
+}
+
 pub async fn create_mint(
     ctx: &mut TestContext,
     mint: &KP,
@@ -77,10 +100,17 @@ pub async fn create_mint(
         token_program,
         decimals,
         transfer_fee_bps,
+        transfer_hook,
     }: TokenSpec,
 ) -> Result<(), TransportError> {
     let is_transfer_fee = token_program == spl_token_2022::id() && transfer_fee_bps > 0;
-    let space = if is_transfer_fee {
+    let space = if transfer_hook {
+        let mut extensions = vec![ExtensionTypeWithHooks::TransferHook];
+        if is_transfer_fee {
+            extensions.push(ExtensionTypeWithHooks::TransferFeeConfig);
+        }
+        ExtensionTypeWithHooks::try_calculate_account_len::<MintWithHooks>(&extensions).unwrap()
+    } else if is_transfer_fee {
         ExtensionType::get_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])
     } else if transfer_fee_bps > 0 {
         panic!(
@@ -90,6 +120,12 @@ pub async fn create_mint(
     } else {
         Mint::LEN
     };
+    if transfer_hook && token_program != spl_token_2022::id() {
+        panic!(
+            "Transfer hook not supported for token program (only token-2022): {}",
+            token_program
+        )
+    }
     let mut ix = vec![system_instruction::create_account(
         &ctx.context.payer.pubkey(),
         &mint.pubkey(),
@@ -111,6 +147,17 @@ pub async fn create_mint(
             .unwrap(),
         );
     }
+    if transfer_hook {
+        ix.push(
+            spl_token_2022_latest::extension::transfer_hook::instruction::initialize(
+                &token_program,
+                &mint.pubkey(),
+                None,
+                Some(transfer_hook::id()),
+            )
+            .unwrap(),
+        );
+    }
 
     ix.push(
         spl_token_2022::instruction::initialize_mint(
@@ -123,6 +170,11 @@ pub async fn create_mint(
         .unwrap(),
     );
     send_tx!(ctx, ix, mint.as_ref()).unwrap();
+    if transfer_hook {
+        transfer_hook::initialize_mint(ctx, &mint.pubkey())
+            .await
+            .unwrap();
+    }
     Ok(())
 }
 
@@ -0,0 +1,142 @@
+//! Minimal transfer hook program counting the transfers of its mints
+//!
+//! The count is kept in an extra account of the hook, a PDA of the mint, so a transfer only
+//! succeeds if the extra accounts of the hook were resolved and passed along.
+
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    account_info::AccountInfo, entrypoint::ProgramResult, instruction::AccountMeta,
+    program::invoke_signed, program_error::ProgramError, pubkey::Pubkey, rent::Rent,
+    signer::Signer, system_instruction, sysvar::Sysvar,
+};
+use spl_tlv_account_resolution::{
+    account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList,
+};
+use spl_transfer_hook_interface::{
+    collect_extra_account_metas_signer_seeds, get_extra_account_metas_address,
+    get_extra_account_metas_address_and_bump_seed,
+    instruction::{
+        initialize_extra_account_meta_list, ExecuteInstruction, TransferHookInstruction,
+    },
+};
+
+use super::types::TestContext;
+use crate::send_tx;
+
+solana_sdk::declare_id!("GtGw5toSCJnV5rtEmzLiZCvrC5T3breoyQqLDo8ynX5B");
+
+const COUNTER_SEED: &[u8] = b"counter";
+const COUNTER_LEN: usize = 8;
+
+pub fn counter_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[COUNTER_SEED, mint.as_ref()], &id()).0
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
+    match TransferHookInstruction::unpack(input)? {
+        TransferHookInstruction::Execute { amount } => {
+            // 0. source, 1. mint, 2. destination, 3. authority, 4. extra account metas, 5. counter
+            let [_source, _mint, _destination, _authority, extra_account_metas, counter, ..] =
+                accounts
+            else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            ExtraAccountMetaList::check_account_infos::<ExecuteInstruction>(
+                accounts,
+                &TransferHookInstruction::Execute { amount }.pack(),
+                program_id,
+                &extra_account_metas.try_borrow_data()?,
+            )?;
+            let mut counter = counter.try_borrow_mut_data()?;
+            let transfers = u64::from_le_bytes(counter[..COUNTER_LEN].try_into().unwrap());
+            counter[..COUNTER_LEN].copy_from_slice(&(transfers + 1).to_le_bytes());
+            Ok(())
+        }
+        TransferHookInstruction::InitializeExtraAccountMetaList {
+            extra_account_metas: metas,
+        } => {
+            // 0. extra account metas, 1. mint, 2. mint authority paying for the accounts,
+            // 3. system program, 4. counter
+            let [extra_account_metas, mint, authority, system_program, counter] = accounts else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            let rent = Rent::get()?;
+
+            let (_, bump) = get_extra_account_metas_address_and_bump_seed(mint.key, program_id);
+            let bump = [bump];
+            let list_len = ExtraAccountMetaList::size_of(metas.len())?;
+            invoke_signed(
+                &system_instruction::create_account(
+                    authority.key,
+                    extra_account_metas.key,
+                    rent.minimum_balance(list_len),
+                    list_len as u64,
+                    program_id,
+                ),
+                &[
+                    authority.clone(),
+                    extra_account_metas.clone(),
+                    system_program.clone(),
+                ],
+                &[&collect_extra_account_metas_signer_seeds(mint.key, &bump)],
+            )?;
+            ExtraAccountMetaList::init::<ExecuteInstruction>(
+                &mut extra_account_metas.try_borrow_mut_data()?,
+                &metas,
+            )?;
+
+            let (_, bump) =
+                Pubkey::find_program_address(&[COUNTER_SEED, mint.key.as_ref()], program_id);
+            invoke_signed(
+                &system_instruction::create_account(
+                    authority.key,
+                    counter.key,
+                    rent.minimum_balance(COUNTER_LEN),
+                    COUNTER_LEN as u64,
+                    program_id,
+                ),
+                &[authority.clone(), counter.clone(), system_program.clone()],
+                &[&[COUNTER_SEED, mint.key.as_ref(), &[bump]]],
+            )
+        }
+    }
+}
+
+/// Create the extra account metas list and the transfer counter of a mint using the hook
+pub async fn initialize_mint(ctx: &mut TestContext, mint: &Pubkey) -> Result<(), BanksClientError> {
+    let counter = ExtraAccountMeta::new_with_seeds(
+        &[
+            Seed::Literal {
+                bytes: COUNTER_SEED.to_vec(),
+            },
+            Seed::AccountKey { index: 1 },
+        ],
+        false,
+        true,
+    )
+    .unwrap();
+    let mut ix = initialize_extra_account_meta_list(
+        &id(),
+        &get_extra_account_metas_address(mint, &id()),
+        mint,
+        &ctx.context.payer.pubkey(),
+        &[counter],
+    );
+    // the mint authority pays for the accounts
+    ix.accounts[2].is_writable = true;
+    ix.accounts
+        .push(AccountMeta::new(counter_address(mint), false));
+    send_tx!(ctx, [ix],)
+}
+
+/// Number of transfers of a mint using the hook
+pub async fn transfers(ctx: &mut TestContext, mint: &Pubkey) -> u64 {
+    let counter = ctx
+        .context
+        .banks_client
+        .get_account(counter_address(mint))
+        .await
+        .unwrap()
+        .unwrap();
+    u64::from_le_bytes(counter.data[..COUNTER_LEN].try_into().unwrap())
+}
@@ -12,6 +12,8 @@ use thiserror::Error;
 pub struct TestContext {
     pub context: ProgramTestContext,
     pub rent: Rent,
+    /// Owner of the global config initialized when the test starts
+    pub global_config_owner: Arc<Keypair>,
 }
 
 #[derive(PartialEq, Eq, Error, Debug)]
@@ -41,6 +43,8 @@ pub struct SwapPoolAccounts {
     pub token_b_vault: Pubkey,
     pub token_a_fees_vault: Pubkey,
     pub token_b_fees_vault: Pubkey,
+    pub token_a_protocol_fees_vault: Pubkey,
+    pub token_b_protocol_fees_vault: Pubkey,
     pub token_a_token_program: Pubkey,
     pub token_b_token_program: Pubkey,
     pub pool_token_program: Pubkey,
@@ -57,6 +61,8 @@ pub struct TokenSpec {
     pub decimals: u8,
     pub transfer_fee_bps: u16,
     pub token_program: Pubkey,
+    /// Use the test transfer hook program, requires `ProgramDependency::TransferHook`
+    pub transfer_hook: bool,
 }
 
 impl Default for TokenSpec {
@@ -74,6 +80,7 @@ impl TokenSpec {
             decimals,
             transfer_fee_bps,
             token_program,
+            transfer_hook: false,
         }
     }
     pub fn spl_token(decimals: u8) -> Self {
@@ -82,6 +89,12 @@ impl TokenSpec {
     pub fn transfer_fees(bps: u16) -> Self {
         Self::new(6, bps, spl_token_2022::id())
     }
+    pub fn transfer_hook() -> Self {
+        Self {
+            transfer_hook: true,
+            ..Self::new(6, 0, spl_token_2022::id())
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Constructor)]
@@ -1,8 +1,19 @@
-use anchor_lang::prelude::Pubkey;
-use hyperplane::curve::{calculator::RoundDirection, math::pool_tokens_to_trading_tokens};
+use anchor_lang::{
+    prelude::{Clock, Pubkey},
+    Discriminator,
+};
+use hyperplane::{
+    curve::{
+        calculator::RoundDirection,
+        math::pool_tokens_to_trading_tokens,
+        oracle_price::PriceFeed,
+        rate_provider::{StakePoolRate, SPL_STAKE_POOL_PROGRAM_ID},
+    },
+    state::SwapPool,
+};
 use solana_sdk::account::AccountSharedData;
 
-use crate::common::types::TestContext;
+use crate::common::types::{SwapPoolAccounts, TestContext};
 
 pub async fn clone_account(ctx: &mut TestContext, previous_address: &Pubkey, new_address: &Pubkey) {
     let account_to_clone = ctx
@@ -21,6 +32,53 @@ pub async fn clone_account(ctx: &mut TestContext, previous_address: &Pubkey, new
     ctx.context.set_account(new_address, &cloned_account);
 }
 
+pub async fn set_unix_timestamp(ctx: &mut TestContext, unix_timestamp: i64) {
+    let mut clock: Clock = ctx.context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    ctx.context.set_sysvar(&clock);
+}
+
+/// Overwrite the pool account, e.g. to recreate the state of pools created by older program versions
+pub async fn set_pool(ctx: &mut TestContext, pool: &SwapPoolAccounts, swap_pool: &SwapPool) {
+    let account = ctx
+        .context
+        .banks_client
+        .get_account(pool.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    let mut data = SwapPool::discriminator().to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(swap_pool));
+    let mut updated_account = AccountSharedData::new(account.lamports, data.len(), &account.owner);
+    updated_account.set_data_from_slice(&data);
+    ctx.context.set_account(&pool.pubkey(), &updated_account);
+}
+
+pub fn set_price_feed(
+    ctx: &mut TestContext,
+    price_feed: &Pubkey,
+    price_feed_program: &Pubkey,
+    price: PriceFeed,
+) {
+    let mut account = AccountSharedData::new(
+        ctx.rent.minimum_balance(PriceFeed::LEN),
+        PriceFeed::LEN,
+        price_feed_program,
+    );
+    account.set_data_from_slice(&price.to_bytes());
+    ctx.context.set_account(price_feed, &account);
+}
+
+pub fn set_stake_pool(ctx: &mut TestContext, stake_pool: &Pubkey, rate: StakePoolRate) {
+    let mut account = AccountSharedData::new(
+        ctx.rent.minimum_balance(StakePoolRate::LEN),
+        StakePoolRate::LEN,
+        &SPL_STAKE_POOL_PROGRAM_ID,
+    );
+    account.set_data_from_slice(&rate.to_bytes());
+    ctx.context.set_account(stake_pool, &account);
+}
+
 pub async fn clone_account_with_new_owner(
     test_context: &mut TestContext,
     previous_address: &Pubkey,
@@ -0,0 +1,199 @@
+mod common;
+
+use anchor_lang::prelude::{ErrorCode, Pubkey};
+use common::{client, runner};
+use hyperplane::{
+    curve::{
+        calculator::{AorB, TradeDirection},
+        fees::Fees,
+    },
+    ix::{Swap, UpdatePoolConfig, WithdrawFees},
+    state::{SwapState, UpdatePoolConfigMode, UpdatePoolConfigValue},
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+use solana_sdk::signature::Signer;
+
+use crate::common::{
+    fixtures,
+    fixtures::Sol,
+    setup,
+    setup::new_keypair,
+    state, token_operations,
+    token_operations::create_token_account,
+    types::{PoolAdminAccounts, SwapPairSpec},
+};
+
+#[tokio::test]
+pub async fn test_successful_admin_transfer() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees {
+            host_fee_numerator: 1,
+            host_fee_denominator: 100,
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            owner_trade_fee_numerator: 1,
+            owner_trade_fee_denominator: 100,
+            owner_withdraw_fee_numerator: 1,
+            owner_withdraw_fee_denominator: 100,
+        },
+        InitialSupply::new(100, 100),
+        SwapPairSpec::default(),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    let new_admin = new_keypair(&mut ctx, Sol::one()).await;
+    client::propose_new_admin(&mut ctx, &pool, &new_admin.pubkey())
+        .await
+        .unwrap();
+
+    // the admin does not change until the proposal is accepted
+    let pool_state = state::get_pool(&mut ctx, &pool).await;
+    assert_eq!(pool_state.admin, pool.admin.pubkey());
+    assert_eq!(pool_state.pending_admin, new_admin.pubkey());
+
+    client::accept_admin(&mut ctx, &pool, &new_admin)
+        .await
+        .unwrap();
+
+    let pool_state = state::get_pool(&mut ctx, &pool).await;
+    assert_eq!(pool_state.admin, new_admin.pubkey());
+    assert_eq!(pool_state.pending_admin, Pubkey::default());
+
+    // the previous admin can no longer update the pool
+    assert_eq!(
+        client::update_pool_config(
+            &mut ctx,
+            &pool,
+            UpdatePoolConfig::new(
+                UpdatePoolConfigMode::WithdrawalsOnly,
+                UpdatePoolConfigValue::Bool(true),
+            ),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        anchor_error!(ErrorCode::ConstraintHasOne)
+    );
+
+    let new_admin_token_a_ata = create_token_account(
+        &mut ctx,
+        &pool.token_a_token_program,
+        &pool.token_a_mint,
+        &new_admin.pubkey(),
+    )
+    .await
+    .unwrap();
+    let new_admin_token_b_ata = create_token_account(
+        &mut ctx,
+        &pool.token_b_token_program,
+        &pool.token_b_mint,
+        &new_admin.pubkey(),
+    )
+    .await
+    .unwrap();
+    let mut new_admin_pool = pool.clone();
+    new_admin_pool.admin = PoolAdminAccounts {
+        admin: new_admin.clone(),
+        token_a_ata: new_admin_token_a_ata,
+        token_b_ata: new_admin_token_b_ata,
+        pool_token_ata: pool.admin.pool_token_ata.clone(),
+    };
+
+    // the new admin can update the pool
+    client::update_pool_config(
+        &mut ctx,
+        &new_admin_pool,
+        UpdatePoolConfig::new(
+            UpdatePoolConfigMode::WithdrawalsOnly,
+            UpdatePoolConfigValue::Bool(true),
+        ),
+    )
+    .await
+    .unwrap();
+    let pool_state = state::get_pool(&mut ctx, &pool).await;
+    assert!(pool_state.withdrawals_only());
+    client::update_pool_config(
+        &mut ctx,
+        &new_admin_pool,
+        UpdatePoolConfig::new(
+            UpdatePoolConfigMode::WithdrawalsOnly,
+            UpdatePoolConfigValue::Bool(false),
+        ),
+    )
+    .await
+    .unwrap();
+
+    // the new admin can withdraw fees
+    let user = setup::new_pool_user(&mut ctx, &pool, (50, 0)).await;
+    client::swap(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        Swap::new(50, 47),
+    )
+    .await
+    .unwrap();
+
+    let fees_from_swap = token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await;
+    assert_eq!(
+        client::withdraw_fees(&mut ctx, &pool, AorB::A, WithdrawFees::new(fees_from_swap))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        anchor_error!(ErrorCode::ConstraintHasOne)
+    );
+    client::withdraw_fees(
+        &mut ctx,
+        &new_admin_pool,
+        AorB::A,
+        WithdrawFees::new(fees_from_swap),
+    )
+    .await
+    .unwrap();
+
+    let fee_vault_balance = token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await;
+    assert_eq!(fee_vault_balance, 0);
+    let new_admin_balance = token_operations::balance(&mut ctx, &new_admin_token_a_ata).await;
+    assert_eq!(new_admin_balance, fees_from_swap);
+}
+
+#[tokio::test]
+pub async fn test_admin_can_replace_pending_admin() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(100, 100),
+        SwapPairSpec::default(),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    let first_admin = new_keypair(&mut ctx, Sol::one()).await;
+    let second_admin = new_keypair(&mut ctx, Sol::one()).await;
+    client::propose_new_admin(&mut ctx, &pool, &first_admin.pubkey())
+        .await
+        .unwrap();
+    client::propose_new_admin(&mut ctx, &pool, &second_admin.pubkey())
+        .await
+        .unwrap();
+
+    let pool_state = state::get_pool(&mut ctx, &pool).await;
+    assert_eq!(pool_state.pending_admin, second_admin.pubkey());
+
+    client::accept_admin(&mut ctx, &pool, &second_admin)
+        .await
+        .unwrap();
+
+    let pool_state = state::get_pool(&mut ctx, &pool).await;
+    assert_eq!(pool_state.admin, second_admin.pubkey());
+}
@@ -0,0 +1,270 @@
+mod common;
+
+use anchor_lang::{prelude::Pubkey, Id};
+use anchor_spl::token_2022::{
+    spl_token_2022::extension::mint_close_authority::MintCloseAuthority, Token2022,
+};
+use common::{client, runner};
+use hyperplane::{
+    curve::{calculator::INITIAL_SWAP_POOL_AMOUNT, fees::Fees},
+    error::SwapError,
+    ix::{Initialize, Withdraw},
+    utils::token_extensions,
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+
+use crate::common::{
+    fixtures,
+    fixtures::ProgramDependency,
+    setup, token_operations,
+    types::{SwapPairSpec, SwapPoolAccounts, TestContext, TokenSpec},
+};
+
+async fn account_exists(ctx: &mut TestContext, address: &Pubkey) -> bool {
+    ctx.context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .is_some()
+}
+
+async fn lamports(ctx: &mut TestContext, address: &Pubkey) -> u64 {
+    ctx.context
+        .banks_client
+        .get_balance(*address)
+        .await
+        .unwrap()
+}
+
+async fn assert_pool_closed(ctx: &mut TestContext, pool: &SwapPoolAccounts) {
+    for address in [
+        pool.pubkey(),
+        pool.curve,
+        pool.token_a_vault,
+        pool.token_b_vault,
+        pool.token_a_fees_vault,
+        pool.token_b_fees_vault,
+        pool.token_a_protocol_fees_vault,
+        pool.token_b_protocol_fees_vault,
+    ] {
+        assert!(!account_exists(ctx, &address).await);
+    }
+}
+
+#[tokio::test]
+pub async fn test_close_pool_sweeps_fees_and_returns_rent() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees {
+            owner_withdraw_fee_numerator: 1,
+            owner_withdraw_fee_denominator: 100,
+            ..Default::default()
+        },
+        InitialSupply::new(100, 100),
+        SwapPairSpec::default(),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    client::withdraw(
+        &mut ctx,
+        &pool,
+        &pool.admin.clone().into(),
+        Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64, 99, 99),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await,
+        1
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.token_b_fees_vault).await,
+        1
+    );
+
+    let admin_lamports_before = lamports(&mut ctx, &pool.admin.pubkey()).await;
+    client::close_pool(&mut ctx, &pool).await.unwrap();
+
+    assert_pool_closed(&mut ctx, &pool).await;
+    // spl token mints cannot be closed
+    assert!(account_exists(&mut ctx, &pool.pool_token_mint).await);
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.admin.token_a_ata).await,
+        100
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.admin.token_b_ata).await,
+        100
+    );
+    assert!(lamports(&mut ctx, &pool.admin.pubkey()).await > admin_lamports_before);
+}
+
+#[tokio::test]
+pub async fn test_close_pool_with_token_2022_withheld_transfer_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(100, 100),
+        SwapPairSpec::new(TokenSpec::transfer_fees(100), TokenSpec::transfer_fees(100)),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    client::withdraw(
+        &mut ctx,
+        &pool,
+        &pool.admin.clone().into(),
+        Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64, 0, 0),
+    )
+    .await
+    .unwrap();
+
+    client::close_pool(&mut ctx, &pool).await.unwrap();
+
+    assert_pool_closed(&mut ctx, &pool).await;
+}
+
+#[tokio::test]
+pub async fn test_close_pool_closes_token_2022_pool_token_mint() {
+    let program = runner::program(&[ProgramDependency::TokenMetadata]);
+    let mut ctx = runner::start(program).await;
+
+    let initial_supply = InitialSupply::new(100, 100);
+    let mut pool = setup::new_pool_accs(&mut ctx, SwapPairSpec::default(), &initial_supply).await;
+    pool.pool_token_program = Token2022::id();
+    client::initialize_pool(
+        &mut ctx,
+        &pool,
+        Initialize {
+            fees: Fees::default(),
+            initial_supply,
+            curve_parameters: CurveUserParameters::ConstantProduct,
+            pool_token_metadata: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let pool_token_mint = ctx
+        .context
+        .banks_client
+        .get_account(pool.pool_token_mint)
+        .await
+        .unwrap()
+        .unwrap();
+    let mint_close_authority =
+        token_extensions::mint_extension::<MintCloseAuthority>(&pool_token_mint.data)
+            .unwrap()
+            .unwrap();
+    assert_eq!(
+        Option::<Pubkey>::from(mint_close_authority.close_authority),
+        Some(pool.authority)
+    );
+
+    client::withdraw(
+        &mut ctx,
+        &pool,
+        &pool.admin.clone().into(),
+        Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64, 0, 0),
+    )
+    .await
+    .unwrap();
+
+    let admin_lamports_before = lamports(&mut ctx, &pool.admin.pubkey()).await;
+    client::close_pool(&mut ctx, &pool).await.unwrap();
+
+    assert_pool_closed(&mut ctx, &pool).await;
+    assert!(!account_exists(&mut ctx, &pool.pool_token_mint).await);
+    assert!(
+        lamports(&mut ctx, &pool.admin.pubkey()).await
+            >= admin_lamports_before + pool_token_mint.lamports
+    );
+}
+
+#[tokio::test]
+pub async fn test_close_pool_fails_with_outstanding_pool_tokens() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(100, 100),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    client::withdraw(
+        &mut ctx,
+        &pool,
+        &pool.admin.clone().into(),
+        Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64 / 2, 0, 0),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        client::close_pool(&mut ctx, &pool)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        hyperplane_error!(SwapError::PoolNotEmpty)
+    );
+}
+
+#[tokio::test]
+pub async fn test_close_pool_sweeps_leftover_vault_balances() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(100, 100),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    client::withdraw(
+        &mut ctx,
+        &pool,
+        &pool.admin.clone().into(),
+        Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64, 0, 0),
+    )
+    .await
+    .unwrap();
+
+    // tokens sent directly to the vault after the pool was drained
+    token_operations::mint_to(
+        &mut ctx,
+        &pool.token_b_token_program,
+        &pool.token_b_mint,
+        &pool.token_b_vault,
+        1,
+    )
+    .await
+    .unwrap();
+
+    client::close_pool(&mut ctx, &pool).await.unwrap();
+
+    assert_pool_closed(&mut ctx, &pool).await;
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.admin.token_a_ata).await,
+        100
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.admin.token_b_ata).await,
+        101
+    );
+}
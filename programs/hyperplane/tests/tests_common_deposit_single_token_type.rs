@@ -0,0 +1,198 @@
+mod common;
+
+use common::{client, runner};
+use hyperplane::{
+    curve::{
+        calculator::{TradeDirection, INITIAL_SWAP_POOL_AMOUNT},
+        fees::Fees,
+    },
+    error::SwapError,
+    ix::{DepositSingleTokenTypeExactAmountIn, UpdatePoolConfig},
+    state::{SwapState, UpdatePoolConfigMode, UpdatePoolConfigValue},
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+
+use crate::common::{
+    fixtures, setup, setup::default_supply, state, token_operations, types::SwapPairSpec,
+};
+
+#[tokio::test]
+pub async fn test_deposit_single_token_type_fails_with_withdrawal_only_mode() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        default_supply(),
+        SwapPairSpec::default(),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    client::update_pool_config(
+        &mut ctx,
+        &pool,
+        UpdatePoolConfig::new(
+            UpdatePoolConfigMode::WithdrawalsOnly,
+            UpdatePoolConfigValue::Bool(true),
+        ),
+    )
+    .await
+    .unwrap();
+    let pool_state = state::get_pool(&mut ctx, &pool).await;
+    assert!(pool_state.withdrawals_only());
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 1_000)).await;
+    assert_eq!(
+        client::deposit_single_token_type_exact_amount_in(
+            &mut ctx,
+            &pool,
+            &user,
+            TradeDirection::AtoB,
+            DepositSingleTokenTypeExactAmountIn::new(1_000, 1),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::WithdrawalsOnlyMode)
+    );
+
+    // unset withdrawals_only mode
+    client::update_pool_config(
+        &mut ctx,
+        &pool,
+        UpdatePoolConfig::new(
+            UpdatePoolConfigMode::WithdrawalsOnly,
+            UpdatePoolConfigValue::Bool(false),
+        ),
+    )
+    .await
+    .unwrap();
+    client::deposit_single_token_type_exact_amount_in(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        DepositSingleTokenTypeExactAmountIn::new(1_000, 1),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+pub async fn test_deposit_single_token_type_fails_with_exceeded_slippage() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(1_000, 1_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (3_000, 0)).await;
+    // depositing 3x the balance of a constant product pool doubles the pool token supply
+    assert_eq!(
+        client::deposit_single_token_type_exact_amount_in(
+            &mut ctx,
+            &pool,
+            &user,
+            TradeDirection::AtoB,
+            DepositSingleTokenTypeExactAmountIn::new(3_000, INITIAL_SWAP_POOL_AMOUNT as u64 + 1),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::ExceededSlippage)
+    );
+
+    client::deposit_single_token_type_exact_amount_in(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        DepositSingleTokenTypeExactAmountIn::new(3_000, INITIAL_SWAP_POOL_AMOUNT as u64),
+    )
+    .await
+    .unwrap();
+
+    let user_pool_token_balance = token_operations::balance(&mut ctx, &user.pool_token_ata).await;
+    assert_eq!(user_pool_token_balance, INITIAL_SWAP_POOL_AMOUNT as u64);
+    let token_a_vault_balance = token_operations::balance(&mut ctx, &pool.token_a_vault).await;
+    assert_eq!(token_a_vault_balance, 4_000);
+}
+
+#[tokio::test]
+pub async fn test_successful_deposit_single_token_type_with_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees {
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            owner_trade_fee_numerator: 1,
+            owner_trade_fee_denominator: 100,
+            ..Default::default()
+        },
+        InitialSupply::new(1_000, 9_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (300, 3_000)).await;
+
+    // fees are charged on half of the deposit: 1 trade fee + 1 owner fee
+    client::deposit_single_token_type_exact_amount_in(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        DepositSingleTokenTypeExactAmountIn::new(300, 139_298_029),
+    )
+    .await
+    .unwrap();
+
+    // fees are charged on half of the deposit: 15 trade fee + 15 owner fee
+    client::deposit_single_token_type_exact_amount_in(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::BtoA,
+        DepositSingleTokenTypeExactAmountIn::new(3_000, 174_604_554),
+    )
+    .await
+    .unwrap();
+
+    let user_pool_token_balance = token_operations::balance(&mut ctx, &user.pool_token_ata).await;
+    assert_eq!(user_pool_token_balance, 139_298_029 + 174_604_554);
+    let pool_token_supply = token_operations::supply(&mut ctx, &pool.pool_token_mint).await;
+    assert_eq!(
+        pool_token_supply,
+        INITIAL_SWAP_POOL_AMOUNT as u64 + 139_298_029 + 174_604_554
+    );
+
+    let user_token_a_balance = token_operations::balance(&mut ctx, &user.token_a_ata).await;
+    assert_eq!(user_token_a_balance, 0);
+    let user_token_b_balance = token_operations::balance(&mut ctx, &user.token_b_ata).await;
+    assert_eq!(user_token_b_balance, 0);
+
+    let token_a_vault_balance = token_operations::balance(&mut ctx, &pool.token_a_vault).await;
+    assert_eq!(token_a_vault_balance, 1_299);
+    let token_b_vault_balance = token_operations::balance(&mut ctx, &pool.token_b_vault).await;
+    assert_eq!(token_b_vault_balance, 11_985);
+
+    let token_a_fee_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await;
+    assert_eq!(token_a_fee_vault_balance, 1);
+    let token_b_fee_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_b_fees_vault).await;
+    assert_eq!(token_b_fee_vault_balance, 15);
+}
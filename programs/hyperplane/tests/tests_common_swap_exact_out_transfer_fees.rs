@@ -0,0 +1,489 @@
+// useful for d.p. clarity in tests
+#![allow(clippy::inconsistent_digit_grouping)]
+
+mod common;
+
+use common::{client, runner};
+use hyperplane::{
+    curve::{calculator::TradeDirection, fees::Fees},
+    ix::SwapExactOut,
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+
+use crate::common::{
+    fixtures, setup, token_operations,
+    types::{SwapPairSpec, TokenSpec},
+};
+
+#[tokio::test]
+pub async fn test_swap_exact_out_a_to_b_with_a_transfer_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees {
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            owner_trade_fee_numerator: 0, // no owner trade fee
+            owner_trade_fee_denominator: 0,
+            ..Default::default()
+        },
+        InitialSupply::new(100, 100),
+        SwapPairSpec::new(TokenSpec::transfer_fees(10), TokenSpec::default()),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (50, 0)).await;
+
+    client::swap_exact_out(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        SwapExactOut {
+            amount_out: 47,
+            maximum_amount_in: 50,
+        },
+    )
+    .await
+    .unwrap();
+
+    let vault_a_balance = token_operations::balance(&mut ctx, &pool.token_a_vault).await;
+    assert_eq!(vault_a_balance, 149);
+    let vault_b_balance = token_operations::balance(&mut ctx, &pool.token_b_vault).await;
+    assert_eq!(vault_b_balance, 53);
+
+    // 0% owner fees - nothing paid into fee vault
+    let token_a_fees_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await;
+    assert_eq!(token_a_fees_vault_balance, 0);
+    let token_b_fees_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_b_fees_vault).await;
+    assert_eq!(token_b_fees_vault_balance, 0);
+
+    let user_a_balance = token_operations::balance(&mut ctx, &user.token_a_ata).await;
+    let user_b_balance = token_operations::balance(&mut ctx, &user.token_b_ata).await;
+    assert_eq!(user_a_balance, 0);
+    assert_eq!(user_b_balance, 47);
+}
+
+#[tokio::test]
+pub async fn test_swap_exact_out_b_to_a_with_b_transfer_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees {
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            owner_trade_fee_numerator: 0, // no owner trade fee
+            owner_trade_fee_denominator: 0,
+            ..Default::default()
+        },
+        InitialSupply::new(100, 100),
+        SwapPairSpec::new(TokenSpec::default(), TokenSpec::transfer_fees(10)),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (0, 50)).await;
+
+    client::swap_exact_out(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::BtoA,
+        SwapExactOut {
+            amount_out: 47,
+            maximum_amount_in: 50,
+        },
+    )
+    .await
+    .unwrap();
+
+    let vault_a_balance = token_operations::balance(&mut ctx, &pool.token_a_vault).await;
+    assert_eq!(vault_a_balance, 53);
+    let vault_b_balance = token_operations::balance(&mut ctx, &pool.token_b_vault).await;
+    assert_eq!(vault_b_balance, 149);
+
+    // 0% owner fees - nothing paid into fee vault
+    let token_a_fees_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await;
+    assert_eq!(token_a_fees_vault_balance, 0);
+    let token_b_fees_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_b_fees_vault).await;
+    assert_eq!(token_b_fees_vault_balance, 0);
+
+    let user_a_balance = token_operations::balance(&mut ctx, &user.token_a_ata).await;
+    let user_b_balance = token_operations::balance(&mut ctx, &user.token_b_ata).await;
+    assert_eq!(user_a_balance, 47);
+    assert_eq!(user_b_balance, 0);
+}
+
+#[tokio::test]
+pub async fn test_swap_exact_out_a_to_b_with_b_transfer_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees {
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            owner_trade_fee_numerator: 0, // no owner trade fee
+            owner_trade_fee_denominator: 0,
+            ..Default::default()
+        },
+        InitialSupply::new(100, 100),
+        SwapPairSpec::new(TokenSpec::default(), TokenSpec::transfer_fees(10)),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (50, 0)).await;
+
+    client::swap_exact_out(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        SwapExactOut {
+            amount_out: 47,
+            maximum_amount_in: 50,
+        },
+    )
+    .await
+    .unwrap();
+
+    let vault_a_balance = token_operations::balance(&mut ctx, &pool.token_a_vault).await;
+    assert_eq!(vault_a_balance, 150);
+    let vault_b_balance = token_operations::balance(&mut ctx, &pool.token_b_vault).await;
+    assert_eq!(vault_b_balance, 52);
+
+    // 0% owner fees - nothing paid into fee vault
+    let token_a_fees_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await;
+    assert_eq!(token_a_fees_vault_balance, 0);
+    let token_b_fees_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_b_fees_vault).await;
+    assert_eq!(token_b_fees_vault_balance, 0);
+
+    let user_a_balance = token_operations::balance(&mut ctx, &user.token_a_ata).await;
+    let user_b_balance = token_operations::balance(&mut ctx, &user.token_b_ata).await;
+    assert_eq!(user_a_balance, 0);
+    assert_eq!(user_b_balance, 47);
+}
+
+#[tokio::test]
+pub async fn test_swap_exact_out_b_to_a_with_a_transfer_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees {
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            owner_trade_fee_numerator: 0, // no owner trade fee
+            owner_trade_fee_denominator: 0,
+            ..Default::default()
+        },
+        InitialSupply::new(100, 100),
+        SwapPairSpec::new(TokenSpec::transfer_fees(10), TokenSpec::default()),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (0, 50)).await;
+
+    client::swap_exact_out(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::BtoA,
+        SwapExactOut {
+            amount_out: 47,
+            maximum_amount_in: 50,
+        },
+    )
+    .await
+    .unwrap();
+
+    let vault_a_balance = token_operations::balance(&mut ctx, &pool.token_a_vault).await;
+    assert_eq!(vault_a_balance, 52);
+    let vault_b_balance = token_operations::balance(&mut ctx, &pool.token_b_vault).await;
+    assert_eq!(vault_b_balance, 150);
+
+    // 0% owner fees - nothing paid into fee vault
+    let token_a_fees_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await;
+    assert_eq!(token_a_fees_vault_balance, 0);
+    let token_b_fees_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_b_fees_vault).await;
+    assert_eq!(token_b_fees_vault_balance, 0);
+
+    let user_a_balance = token_operations::balance(&mut ctx, &user.token_a_ata).await;
+    let user_b_balance = token_operations::balance(&mut ctx, &user.token_b_ata).await;
+    assert_eq!(user_a_balance, 47);
+    assert_eq!(user_b_balance, 0);
+}
+
+#[tokio::test]
+pub async fn test_swap_exact_out_a_to_b_with_a_transfer_fees_and_owner_and_host_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees {
+            host_fee_numerator: 1,
+            host_fee_denominator: 100,
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            owner_trade_fee_numerator: 1,
+            owner_trade_fee_denominator: 100,
+            ..Default::default()
+        },
+        InitialSupply::new(1_000_000_000000, 1_000_000_000000),
+        SwapPairSpec::new(TokenSpec::transfer_fees(10), TokenSpec::default()),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (49_051_985090, 0)).await;
+    let host_fees = setup::new_pool_user(&mut ctx, &pool, (0, 0)).await;
+
+    client::swap_exact_out_with_host_fees(
+        &mut ctx,
+        &pool,
+        &user,
+        Some(&host_fees),
+        TradeDirection::AtoB,
+        SwapExactOut {
+            amount_out: 48_000_000000,
+            maximum_amount_in: 49_051_985090,
+        },
+    )
+    .await
+    .unwrap();
+
+    let vault_a_balance = token_operations::balance(&mut ctx, &pool.token_a_vault).await;
+    assert_eq!(vault_a_balance, 1_048_512_903772);
+    let vault_b_balance = token_operations::balance(&mut ctx, &pool.token_b_vault).await;
+    assert_eq!(vault_b_balance, 952_000_000000);
+
+    // fees paid into fee vault
+    let token_a_fees_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await;
+    assert_eq!(token_a_fees_vault_balance, 485_129038);
+    let token_b_fees_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_b_fees_vault).await;
+    assert_eq!(token_b_fees_vault_balance, 0);
+
+    // host fees paid host fee account
+    let host_token_a_fees_balance =
+        token_operations::balance(&mut ctx, &host_fees.token_a_ata).await;
+    assert_eq!(host_token_a_fees_balance, 4_900293);
+
+    // user receives exactly the amount out after transfer fees
+    let user_a_balance = token_operations::balance(&mut ctx, &user.token_a_ata).await;
+    let user_b_balance = token_operations::balance(&mut ctx, &user.token_b_ata).await;
+    assert_eq!(user_a_balance, 0);
+    assert_eq!(user_b_balance, 48_000_000000);
+}
+
+#[tokio::test]
+pub async fn test_swap_exact_out_b_to_a_with_a_transfer_fees_and_owner_and_host_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees {
+            host_fee_numerator: 1,
+            host_fee_denominator: 100,
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            owner_trade_fee_numerator: 1,
+            owner_trade_fee_denominator: 100,
+            ..Default::default()
+        },
+        InitialSupply::new(1_000_000_000000, 1_000_000_000000),
+        SwapPairSpec::new(TokenSpec::transfer_fees(10), TokenSpec::default()),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (0, 49_052_008594)).await;
+    let host_fees = setup::new_pool_user(&mut ctx, &pool, (0, 0)).await;
+
+    client::swap_exact_out_with_host_fees(
+        &mut ctx,
+        &pool,
+        &user,
+        Some(&host_fees),
+        TradeDirection::BtoA,
+        SwapExactOut {
+            amount_out: 48_000_000000,
+            maximum_amount_in: 49_052_008594,
+        },
+    )
+    .await
+    .unwrap();
+
+    let vault_a_balance = token_operations::balance(&mut ctx, &pool.token_a_vault).await;
+    assert_eq!(vault_a_balance, 951_951_951951);
+    let vault_b_balance = token_operations::balance(&mut ctx, &pool.token_b_vault).await;
+    assert_eq!(vault_b_balance, 1_048_561_488509);
+
+    // fees paid into fee vault
+    let token_a_fees_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await;
+    assert_eq!(token_a_fees_vault_balance, 0);
+    let token_b_fees_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_b_fees_vault).await;
+    assert_eq!(token_b_fees_vault_balance, 485_614885);
+
+    // host fees paid host fee account
+    let host_token_b_fees_balance =
+        token_operations::balance(&mut ctx, &host_fees.token_b_ata).await;
+    assert_eq!(host_token_b_fees_balance, 4_905200);
+
+    // user receives exactly the amount out after transfer fees
+    let user_a_balance = token_operations::balance(&mut ctx, &user.token_a_ata).await;
+    let user_b_balance = token_operations::balance(&mut ctx, &user.token_b_ata).await;
+    assert_eq!(user_a_balance, 48_000_000000);
+    assert_eq!(user_b_balance, 0);
+}
+
+#[tokio::test]
+pub async fn test_swap_exact_out_a_to_b_with_a_and_b_transfer_fees_and_owner_and_host_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees {
+            host_fee_numerator: 1,
+            host_fee_denominator: 100,
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            owner_trade_fee_numerator: 1,
+            owner_trade_fee_denominator: 100,
+            ..Default::default()
+        },
+        InitialSupply::new(1_000_000_000000, 1_000_000_000000),
+        SwapPairSpec::new(TokenSpec::transfer_fees(10), TokenSpec::transfer_fees(10)),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (49_101_109705, 0)).await;
+    let host_fees = setup::new_pool_user(&mut ctx, &pool, (0, 0)).await;
+
+    client::swap_exact_out_with_host_fees(
+        &mut ctx,
+        &pool,
+        &user,
+        Some(&host_fees),
+        TradeDirection::AtoB,
+        SwapExactOut {
+            amount_out: 48_000_000000,
+            maximum_amount_in: 49_101_109705,
+        },
+    )
+    .await
+    .unwrap();
+
+    let vault_a_balance = token_operations::balance(&mut ctx, &pool.token_a_vault).await;
+    assert_eq!(vault_a_balance, 1_048_561_488509);
+    let vault_b_balance = token_operations::balance(&mut ctx, &pool.token_b_vault).await;
+    assert_eq!(vault_b_balance, 951_951_951951);
+
+    // fees paid into fee vault
+    let token_a_fees_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await;
+    assert_eq!(token_a_fees_vault_balance, 485_614885);
+    let token_b_fees_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_b_fees_vault).await;
+    assert_eq!(token_b_fees_vault_balance, 0);
+
+    // host fees paid host fee account
+    let host_token_a_fees_balance =
+        token_operations::balance(&mut ctx, &host_fees.token_a_ata).await;
+    assert_eq!(host_token_a_fees_balance, 4_905200);
+
+    // user receives exactly the amount out after transfer fees
+    let user_a_balance = token_operations::balance(&mut ctx, &user.token_a_ata).await;
+    let user_b_balance = token_operations::balance(&mut ctx, &user.token_b_ata).await;
+    assert_eq!(user_a_balance, 0);
+    assert_eq!(user_b_balance, 48_000_000000);
+}
+
+#[tokio::test]
+pub async fn test_swap_exact_out_b_to_a_with_a_and_b_transfer_fees_and_owner_and_host_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees {
+            host_fee_numerator: 1,
+            host_fee_denominator: 100,
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            owner_trade_fee_numerator: 1,
+            owner_trade_fee_denominator: 100,
+            ..Default::default()
+        },
+        InitialSupply::new(1_000_000_000000, 1_000_000_000000),
+        SwapPairSpec::new(TokenSpec::transfer_fees(10), TokenSpec::transfer_fees(10)),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (0, 49_101_109705)).await;
+    let host_fees = setup::new_pool_user(&mut ctx, &pool, (0, 0)).await;
+
+    client::swap_exact_out_with_host_fees(
+        &mut ctx,
+        &pool,
+        &user,
+        Some(&host_fees),
+        TradeDirection::BtoA,
+        SwapExactOut {
+            amount_out: 48_000_000000,
+            maximum_amount_in: 49_101_109705,
+        },
+    )
+    .await
+    .unwrap();
+
+    let vault_a_balance = token_operations::balance(&mut ctx, &pool.token_a_vault).await;
+    assert_eq!(vault_a_balance, 951_951_951951);
+    let vault_b_balance = token_operations::balance(&mut ctx, &pool.token_b_vault).await;
+    assert_eq!(vault_b_balance, 1_048_561_488509);
+
+    // fees paid into fee vault
+    let token_a_fees_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await;
+    assert_eq!(token_a_fees_vault_balance, 0);
+    let token_b_fees_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_b_fees_vault).await;
+    assert_eq!(token_b_fees_vault_balance, 485_614885);
+
+    // host fees paid host fee account
+    let host_token_b_fees_balance =
+        token_operations::balance(&mut ctx, &host_fees.token_b_ata).await;
+    assert_eq!(host_token_b_fees_balance, 4_905200);
+
+    // user receives exactly the amount out after transfer fees
+    let user_a_balance = token_operations::balance(&mut ctx, &user.token_a_ata).await;
+    let user_b_balance = token_operations::balance(&mut ctx, &user.token_b_ata).await;
+    assert_eq!(user_a_balance, 48_000_000000);
+    assert_eq!(user_b_balance, 0);
+}
@@ -0,0 +1,190 @@
+mod common;
+
+use common::{client, runner};
+use hyperplane::{
+    curve::{
+        calculator::{AorB, TradeDirection, INITIAL_SWAP_POOL_AMOUNT},
+        fees::Fees,
+    },
+    error::SwapError,
+    ix::{WithdrawOneToken, WithdrawSingleTokenTypeExactAmountOut},
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+use solana_sdk::signer::Signer;
+
+use crate::common::{fixtures, token_operations, types::SwapPairSpec};
+
+fn fees() -> Fees {
+    Fees {
+        trade_fee_numerator: 1,
+        trade_fee_denominator: 100,
+        owner_trade_fee_numerator: 1,
+        owner_trade_fee_denominator: 100,
+        owner_withdraw_fee_numerator: 1,
+        owner_withdraw_fee_denominator: 100,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+pub async fn test_withdraw_single_token_type_exact_amount_out_fails_with_exceeded_slippage() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        fees(),
+        InitialSupply::new(10_000, 10_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    // 1e9 * (1 - sqrt(1 - 1_020 / 10_000)) = 52_371_381 pool tokens
+    assert_eq!(
+        client::withdraw_single_token_type_exact_amount_out(
+            &mut ctx,
+            &pool,
+            &pool.admin.clone().into(),
+            TradeDirection::AtoB,
+            WithdrawSingleTokenTypeExactAmountOut::new(1_000, 52_371_380),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::ExceededSlippage)
+    );
+}
+
+#[tokio::test]
+pub async fn test_successful_withdraw_single_token_type_exact_amount_out_with_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        fees(),
+        InitialSupply::new(10_000, 10_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    // 10 owner withdraw fee, plus 5 trade fee + 5 owner trade fee charged on half of the withdrawal
+    client::withdraw_single_token_type_exact_amount_out(
+        &mut ctx,
+        &pool,
+        &pool.admin.clone().into(),
+        TradeDirection::AtoB,
+        WithdrawSingleTokenTypeExactAmountOut::new(1_000, 52_371_381),
+    )
+    .await
+    .unwrap();
+
+    let pool_token_supply = token_operations::supply(&mut ctx, &pool.pool_token_mint).await;
+    assert_eq!(
+        pool_token_supply,
+        INITIAL_SWAP_POOL_AMOUNT as u64 - 52_371_381
+    );
+    let admin_pool_token_balance =
+        token_operations::balance(&mut ctx, &pool.admin.pool_token_ata.pubkey()).await;
+    assert_eq!(
+        admin_pool_token_balance,
+        INITIAL_SWAP_POOL_AMOUNT as u64 - 52_371_381
+    );
+
+    let admin_token_a_balance = token_operations::balance(&mut ctx, &pool.admin.token_a_ata).await;
+    assert_eq!(admin_token_a_balance, 1_000);
+    let admin_token_b_balance = token_operations::balance(&mut ctx, &pool.admin.token_b_ata).await;
+    assert_eq!(admin_token_b_balance, 0);
+
+    let token_a_vault_balance = token_operations::balance(&mut ctx, &pool.token_a_vault).await;
+    assert_eq!(token_a_vault_balance, 8_985);
+    let token_b_vault_balance = token_operations::balance(&mut ctx, &pool.token_b_vault).await;
+    assert_eq!(token_b_vault_balance, 10_000);
+
+    let token_a_fee_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await;
+    assert_eq!(token_a_fee_vault_balance, 15);
+    let token_b_fee_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_b_fees_vault).await;
+    assert_eq!(token_b_fee_vault_balance, 0);
+}
+
+#[tokio::test]
+pub async fn test_withdraw_one_token_fails_with_exceeded_slippage() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        fees(),
+        InitialSupply::new(10_000, 10_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    // 10_000 * (1 - (1 - 1e8 / 1e9)^2) = 1_900, minus 9 + 9 trade fees and 18 owner withdraw fee
+    assert_eq!(
+        client::withdraw_one_token(
+            &mut ctx,
+            &pool,
+            &pool.admin.clone().into(),
+            WithdrawOneToken::new(100_000_000, 1_865, AorB::B),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::ExceededSlippage)
+    );
+}
+
+#[tokio::test]
+pub async fn test_successful_withdraw_one_token_with_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        fees(),
+        InitialSupply::new(10_000, 10_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    // 10_000 * (1 - (1 - 1e8 / 1e9)^2) = 1_900, minus 9 + 9 trade fees and 18 owner withdraw fee
+    client::withdraw_one_token(
+        &mut ctx,
+        &pool,
+        &pool.admin.clone().into(),
+        WithdrawOneToken::new(100_000_000, 1_864, AorB::B),
+    )
+    .await
+    .unwrap();
+
+    let pool_token_supply = token_operations::supply(&mut ctx, &pool.pool_token_mint).await;
+    assert_eq!(pool_token_supply, 900_000_000);
+    let admin_pool_token_balance =
+        token_operations::balance(&mut ctx, &pool.admin.pool_token_ata.pubkey()).await;
+    assert_eq!(admin_pool_token_balance, 900_000_000);
+
+    let admin_token_a_balance = token_operations::balance(&mut ctx, &pool.admin.token_a_ata).await;
+    assert_eq!(admin_token_a_balance, 0);
+    let admin_token_b_balance = token_operations::balance(&mut ctx, &pool.admin.token_b_ata).await;
+    assert_eq!(admin_token_b_balance, 1_864);
+
+    let token_a_vault_balance = token_operations::balance(&mut ctx, &pool.token_a_vault).await;
+    assert_eq!(token_a_vault_balance, 10_000);
+    let token_b_vault_balance = token_operations::balance(&mut ctx, &pool.token_b_vault).await;
+    assert_eq!(token_b_vault_balance, 8_109);
+
+    let token_a_fee_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await;
+    assert_eq!(token_a_fee_vault_balance, 0);
+    let token_b_fee_vault_balance =
+        token_operations::balance(&mut ctx, &pool.token_b_fees_vault).await;
+    assert_eq!(token_b_fee_vault_balance, 27);
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use common::{client, runner};
+use hyperplane::{
+    curve::{base::CurveType, calculator::TradeDirection, fees::Fees},
+    error::SwapError,
+    ix::{Initialize, Swap},
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+
+use crate::common::{fixtures, setup, state, token_operations, types::SwapPairSpec};
+
+/// Price range of 1 to 4 token A per token B, as Q64.64 numbers
+const PRICE_LOWER_X64: u128 = 1 << 64;
+const PRICE_UPPER_X64: u128 = 4 << 64;
+
+#[tokio::test]
+pub async fn test_success_init_concentrated_pool() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(3_000, 1_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::Concentrated {
+            price_lower_x64: PRICE_LOWER_X64,
+            price_upper_x64: PRICE_UPPER_X64,
+        },
+    )
+    .await;
+
+    let pool_state = state::get_pool(&mut ctx, &pool).await;
+    assert_eq!(pool_state.curve_type, CurveType::Concentrated as u64);
+
+    let curve = state::get_concentrated_curve(&mut ctx, &pool).await;
+    assert_eq!(curve.price_lower_x64, PRICE_LOWER_X64);
+    assert_eq!(curve.price_upper_x64, PRICE_UPPER_X64);
+    assert_eq!(curve.sqrt_price_lower_x64, 1 << 64);
+    assert_eq!(curve.sqrt_price_upper_x64, 2 << 64);
+}
+
+#[tokio::test]
+pub async fn test_swap_within_range() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(3_000, 1_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::Concentrated {
+            price_lower_x64: PRICE_LOWER_X64,
+            price_upper_x64: PRICE_UPPER_X64,
+        },
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_500, 0)).await;
+
+    // liquidity of 6_000, (3_000 + 6_000 + 1_500) * (1_000 + 3_000 - 571) >= 6_000^2
+    client::swap(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        Swap::new(1_500, 571),
+    )
+    .await
+    .unwrap();
+
+    let vault_a_balance = token_operations::balance(&mut ctx, &pool.token_a_vault).await;
+    assert_eq!(vault_a_balance, 4_500);
+    let vault_b_balance = token_operations::balance(&mut ctx, &pool.token_b_vault).await;
+    assert_eq!(vault_b_balance, 429);
+    let user_b_balance = token_operations::balance(&mut ctx, &user.token_b_ata).await;
+    assert_eq!(user_b_balance, 571);
+}
+
+#[tokio::test]
+pub async fn test_swap_fails_out_of_range() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(3_000, 1_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::Concentrated {
+            price_lower_x64: PRICE_LOWER_X64,
+            price_upper_x64: PRICE_UPPER_X64,
+        },
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (3_100, 0)).await;
+
+    // 3_000 token A moves the price to the upper bound, with no token B left
+    assert_eq!(
+        client::swap(
+            &mut ctx,
+            &pool,
+            &user,
+            TradeDirection::AtoB,
+            Swap::new(3_100, 0),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::PriceOutOfRange)
+    );
+}
+
+#[tokio::test]
+pub async fn test_initialize_pool_fails_with_invalid_price_range() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let initial_supply = InitialSupply::new(3_000, 1_000);
+    let pool = setup::new_pool_accs(&mut ctx, SwapPairSpec::default(), &initial_supply).await;
+    assert_eq!(
+        client::initialize_pool(
+            &mut ctx,
+            &pool,
+            Initialize {
+                fees: Fees::default(),
+                initial_supply,
+                curve_parameters: CurveUserParameters::Concentrated {
+                    price_lower_x64: PRICE_UPPER_X64,
+                    price_upper_x64: PRICE_LOWER_X64,
+                },
+                pool_token_metadata: None,
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::InvalidCurve)
+    );
+}
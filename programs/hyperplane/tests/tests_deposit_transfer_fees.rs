@@ -0,0 +1,171 @@
+mod common;
+
+use common::{client, runner};
+use hyperplane::{
+    curve::fees::Fees, error::SwapError, ix::Deposit, CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+
+use crate::common::{
+    fixtures, setup, token_operations,
+    token_operations::amount_with_transfer_fees,
+    types::{PoolUserAccounts, SwapPairSpec, SwapPoolAccounts, TestContext, TokenSpec},
+};
+
+// 10% of the initial pool token supply, worth 100_000 of each token
+const POOL_TOKEN_AMOUNT: u64 = 100_000_000;
+const TOKEN_AMOUNT: u64 = 100_000;
+
+async fn new_pool(ctx: &mut TestContext, trading_tokens: SwapPairSpec) -> SwapPoolAccounts {
+    fixtures::new_pool(
+        ctx,
+        Fees::default(),
+        InitialSupply::new(1_000_000, 1_000_000),
+        trading_tokens,
+        CurveUserParameters::ConstantProduct,
+    )
+    .await
+}
+
+async fn assert_balances(ctx: &mut TestContext, pool: &SwapPoolAccounts, user: &PoolUserAccounts) {
+    // the vaults receive the curve amounts, the user pays the transfer fees
+    let token_a_vault_balance = token_operations::balance(ctx, &pool.token_a_vault).await;
+    assert_eq!(token_a_vault_balance, 1_000_000 + TOKEN_AMOUNT);
+    let token_b_vault_balance = token_operations::balance(ctx, &pool.token_b_vault).await;
+    assert_eq!(token_b_vault_balance, 1_000_000 + TOKEN_AMOUNT);
+
+    let user_a_balance = token_operations::balance(ctx, &user.token_a_ata).await;
+    assert_eq!(user_a_balance, 0);
+    let user_b_balance = token_operations::balance(ctx, &user.token_b_ata).await;
+    assert_eq!(user_b_balance, 0);
+    let user_pool_token_balance = token_operations::balance(ctx, &user.pool_token_ata).await;
+    assert_eq!(user_pool_token_balance, POOL_TOKEN_AMOUNT);
+}
+
+#[tokio::test]
+pub async fn test_deposit_with_a_transfer_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = new_pool(
+        &mut ctx,
+        SwapPairSpec::new(TokenSpec::transfer_fees(100), TokenSpec::default()),
+    )
+    .await;
+
+    let token_a_amount = amount_with_transfer_fees(TOKEN_AMOUNT, 100);
+    let user = setup::new_pool_user(&mut ctx, &pool, (token_a_amount, TOKEN_AMOUNT)).await;
+    client::deposit(
+        &mut ctx,
+        &pool,
+        &user,
+        Deposit::new(POOL_TOKEN_AMOUNT, token_a_amount, TOKEN_AMOUNT),
+    )
+    .await
+    .unwrap();
+
+    assert_balances(&mut ctx, &pool, &user).await;
+}
+
+#[tokio::test]
+pub async fn test_deposit_with_b_transfer_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = new_pool(
+        &mut ctx,
+        SwapPairSpec::new(TokenSpec::default(), TokenSpec::transfer_fees(100)),
+    )
+    .await;
+
+    let token_b_amount = amount_with_transfer_fees(TOKEN_AMOUNT, 100);
+    let user = setup::new_pool_user(&mut ctx, &pool, (TOKEN_AMOUNT, token_b_amount)).await;
+    client::deposit(
+        &mut ctx,
+        &pool,
+        &user,
+        Deposit::new(POOL_TOKEN_AMOUNT, TOKEN_AMOUNT, token_b_amount),
+    )
+    .await
+    .unwrap();
+
+    assert_balances(&mut ctx, &pool, &user).await;
+}
+
+#[tokio::test]
+pub async fn test_deposit_with_a_and_b_transfer_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = new_pool(
+        &mut ctx,
+        SwapPairSpec::new(TokenSpec::transfer_fees(100), TokenSpec::transfer_fees(50)),
+    )
+    .await;
+
+    let token_a_amount = amount_with_transfer_fees(TOKEN_AMOUNT, 100);
+    let token_b_amount = amount_with_transfer_fees(TOKEN_AMOUNT, 50);
+    let user = setup::new_pool_user(&mut ctx, &pool, (token_a_amount, token_b_amount)).await;
+    client::deposit(
+        &mut ctx,
+        &pool,
+        &user,
+        Deposit::new(POOL_TOKEN_AMOUNT, token_a_amount, token_b_amount),
+    )
+    .await
+    .unwrap();
+
+    assert_balances(&mut ctx, &pool, &user).await;
+}
+
+#[tokio::test]
+pub async fn test_deposit_slippage_is_checked_against_amount_paid_including_transfer_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = new_pool(
+        &mut ctx,
+        SwapPairSpec::new(TokenSpec::transfer_fees(100), TokenSpec::transfer_fees(100)),
+    )
+    .await;
+
+    let token_amount = amount_with_transfer_fees(TOKEN_AMOUNT, 100);
+    let user = setup::new_pool_user(&mut ctx, &pool, (token_amount, token_amount)).await;
+
+    // the curve amounts are not enough to cover the transfer fees
+    assert_eq!(
+        client::deposit(
+            &mut ctx,
+            &pool,
+            &user,
+            Deposit::new(POOL_TOKEN_AMOUNT, TOKEN_AMOUNT, token_amount),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::ExceededSlippage)
+    );
+    assert_eq!(
+        client::deposit(
+            &mut ctx,
+            &pool,
+            &user,
+            Deposit::new(POOL_TOKEN_AMOUNT, token_amount, token_amount - 1),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::ExceededSlippage)
+    );
+
+    client::deposit(
+        &mut ctx,
+        &pool,
+        &user,
+        Deposit::new(POOL_TOKEN_AMOUNT, token_amount, token_amount),
+    )
+    .await
+    .unwrap();
+
+    assert_balances(&mut ctx, &pool, &user).await;
+}
@@ -0,0 +1,422 @@
+mod common;
+
+use anchor_lang::prelude::{Clock, Pubkey};
+use common::{client, runner};
+use hyperplane::{
+    curve::{
+        calculator::{AorB, TradeDirection},
+        fees::Fees,
+    },
+    dynamic_fee::{DynamicFeeParameters, DYNAMIC_FEE_DENOMINATOR},
+    error::SwapError,
+    ix::{
+        DepositSingleTokenTypeExactAmountIn, Swap, UpdateGlobalConfig, UpdatePoolConfig,
+        WithdrawOneToken, WithdrawSingleTokenTypeExactAmountOut,
+    },
+    state::{
+        UpdateGlobalConfigMode, UpdateGlobalConfigValue, UpdatePoolConfigMode,
+        UpdatePoolConfigValue,
+    },
+    CurveUserParameters, InitialSupply,
+};
+use hyperplane_quote::{PoolAccounts, PoolQuoter};
+use solana_program_test::tokio;
+
+use crate::common::{
+    fixtures, setup, state, token_operations,
+    types::{PoolUserAccounts, SwapPairSpec, SwapPoolAccounts, TestContext},
+    utils::set_unix_timestamp,
+};
+
+fn fees() -> Fees {
+    Fees {
+        trade_fee_numerator: 25,
+        trade_fee_denominator: 10_000,
+        owner_trade_fee_numerator: 5,
+        owner_trade_fee_denominator: 10_000,
+        ..Default::default()
+    }
+}
+
+fn parameters() -> DynamicFeeParameters {
+    DynamicFeeParameters {
+        min_fee: 1_000,
+        max_fee: 11_000,
+        filter_period_sec: 30,
+        decay_period_sec: 600,
+        reduction_factor_bps: 5_000,
+        max_volatility_bps: 1_000,
+    }
+}
+
+async fn account_data(ctx: &mut TestContext, address: &Pubkey) -> Vec<u8> {
+    ctx.context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data
+}
+
+async fn quoter(ctx: &mut TestContext, pool: &SwapPoolAccounts) -> PoolQuoter {
+    let swap_pool = state::get_pool(ctx, pool).await;
+    let global_config = state::get_global_config(ctx).await;
+    let swap_curve = account_data(ctx, &pool.curve).await;
+    let token_a_mint = account_data(ctx, &pool.token_a_mint).await;
+    let token_b_mint = account_data(ctx, &pool.token_b_mint).await;
+    let token_a_vault_amount = token_operations::balance(ctx, &pool.token_a_vault).await;
+    let token_b_vault_amount = token_operations::balance(ctx, &pool.token_b_vault).await;
+    let pool_token_supply = token_operations::supply(ctx, &pool.pool_token_mint).await;
+    let clock: Clock = ctx.context.banks_client.get_sysvar().await.unwrap();
+    PoolQuoter::new(
+        PoolAccounts {
+            pool: &swap_pool,
+            global_config: &global_config,
+            swap_curve: &swap_curve,
+            price_feed: None,
+            rate_provider: None,
+            token_a_mint: &token_a_mint,
+            token_b_mint: &token_b_mint,
+            token_a_vault_amount,
+            token_b_vault_amount,
+            pool_token_supply,
+        },
+        &clock,
+    )
+    .unwrap()
+}
+
+/// Vault balances and pool token supply of the pool
+async fn pool_amounts(ctx: &mut TestContext, pool: &SwapPoolAccounts) -> (u128, u128, u128) {
+    (
+        token_operations::balance(ctx, &pool.token_a_vault)
+            .await
+            .into(),
+        token_operations::balance(ctx, &pool.token_b_vault)
+            .await
+            .into(),
+        token_operations::supply(ctx, &pool.pool_token_mint)
+            .await
+            .into(),
+    )
+}
+
+/// Swap `amount_in` of token A, checking the quote matches the swap, and return the quoted
+/// trade fee numerator
+async fn swap_a_to_b(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    user: &PoolUserAccounts,
+    amount_in: u64,
+) -> u64 {
+    let quote = quoter(ctx, pool)
+        .await
+        .swap(TradeDirection::AtoB, amount_in, false)
+        .unwrap();
+    let token_b_before = token_operations::balance(ctx, &user.token_b_ata).await;
+    client::swap(
+        ctx,
+        pool,
+        user,
+        TradeDirection::AtoB,
+        Swap::new(amount_in, 0),
+    )
+    .await
+    .unwrap();
+    let token_b_after = token_operations::balance(ctx, &user.token_b_ata).await;
+    assert_eq!(token_b_after - token_b_before, quote.destination_amount);
+    assert_eq!(quote.trade_fee_denominator, DYNAMIC_FEE_DENOMINATOR);
+    quote.trade_fee_numerator
+}
+
+#[tokio::test]
+pub async fn test_dynamic_fee_scales_with_volatility() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    set_unix_timestamp(&mut ctx, 1_000).await;
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        fees(),
+        InitialSupply::new(1_000_000_000, 1_000_000_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+    client::update_dynamic_fee(&mut ctx, &pool, Some(parameters()))
+        .await
+        .unwrap();
+    assert_eq!(
+        state::get_pool(&mut ctx, &pool)
+            .await
+            .dynamic_fee
+            .parameters(),
+        Some(parameters())
+    );
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (200_000_000, 0)).await;
+
+    // the first swap sets the reference price and pays the minimum fee
+    assert_eq!(swap_a_to_b(&mut ctx, &pool, &user, 50_000_000).await, 1_000);
+
+    // the first swap moved the price by over 10%, so the next swap pays the maximum fee
+    set_unix_timestamp(&mut ctx, 1_010).await;
+    assert_eq!(
+        swap_a_to_b(&mut ctx, &pool, &user, 50_000_000).await,
+        11_000
+    );
+    let dynamic_fee = state::get_pool(&mut ctx, &pool).await.dynamic_fee;
+    assert_eq!(dynamic_fee.volatility_accumulator_bps, 1_000);
+    assert_eq!(dynamic_fee.last_update_ts, 1_010);
+
+    // after the decay period the volatility is forgotten
+    set_unix_timestamp(&mut ctx, 1_010 + 600).await;
+    assert_eq!(swap_a_to_b(&mut ctx, &pool, &user, 50_000_000).await, 1_000);
+}
+
+#[tokio::test]
+pub async fn test_single_token_deposits_and_withdrawals_charge_dynamic_fee() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    set_unix_timestamp(&mut ctx, 1_000).await;
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        fees(),
+        InitialSupply::new(1_000_000_000, 1_000_000_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+    client::update_dynamic_fee(&mut ctx, &pool, Some(parameters()))
+        .await
+        .unwrap();
+    let user = setup::new_pool_user(&mut ctx, &pool, (200_000_000, 0)).await;
+    assert_eq!(swap_a_to_b(&mut ctx, &pool, &user, 50_000_000).await, 1_000);
+
+    // the swap moved the price by over 10%, so single sided deposits and withdrawals, which
+    // also move the price, pay the maximum fee
+    set_unix_timestamp(&mut ctx, 1_010).await;
+    let max_fees = Fees {
+        trade_fee_numerator: parameters().max_fee,
+        trade_fee_denominator: DYNAMIC_FEE_DENOMINATOR,
+        ..fees()
+    };
+
+    let pool_quoter = quoter(&mut ctx, &pool).await;
+    let (token_a_vault_amount, token_b_vault_amount, pool_token_supply) =
+        pool_amounts(&mut ctx, &pool).await;
+    let expected = pool_quoter
+        .swap_curve()
+        .deposit_single_token_type(
+            10_000_000,
+            token_a_vault_amount,
+            token_b_vault_amount,
+            pool_token_supply,
+            TradeDirection::AtoB,
+            &max_fees,
+        )
+        .unwrap();
+    let static_fee_expected = pool_quoter
+        .swap_curve()
+        .deposit_single_token_type(
+            10_000_000,
+            token_a_vault_amount,
+            token_b_vault_amount,
+            pool_token_supply,
+            TradeDirection::AtoB,
+            &fees(),
+        )
+        .unwrap();
+    assert!(expected.pool_token_amount < static_fee_expected.pool_token_amount);
+    client::deposit_single_token_type_exact_amount_in(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        DepositSingleTokenTypeExactAmountIn::new(10_000_000, 0),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        u128::from(token_operations::balance(&mut ctx, &user.pool_token_ata).await),
+        expected.pool_token_amount
+    );
+    let dynamic_fee = state::get_pool(&mut ctx, &pool).await.dynamic_fee;
+    assert_eq!(
+        dynamic_fee.trade_fee_numerator().unwrap(),
+        parameters().max_fee
+    );
+    assert_eq!(dynamic_fee.last_update_ts, 1_010);
+
+    let pool_quoter = quoter(&mut ctx, &pool).await;
+    let (token_a_vault_amount, token_b_vault_amount, pool_token_supply) =
+        pool_amounts(&mut ctx, &pool).await;
+    let expected = pool_quoter
+        .swap_curve()
+        .withdraw_one_token(
+            1_000_000,
+            token_a_vault_amount,
+            token_b_vault_amount,
+            pool_token_supply,
+            TradeDirection::BtoA,
+            &max_fees,
+        )
+        .unwrap();
+    let token_b_before = token_operations::balance(&mut ctx, &user.token_b_ata).await;
+    client::withdraw_one_token(
+        &mut ctx,
+        &pool,
+        &user,
+        WithdrawOneToken::new(1_000_000, 0, AorB::B),
+    )
+    .await
+    .unwrap();
+    let token_b_after = token_operations::balance(&mut ctx, &user.token_b_ata).await;
+    assert_eq!(
+        u128::from(token_b_after - token_b_before),
+        expected.destination_amount
+    );
+
+    let pool_quoter = quoter(&mut ctx, &pool).await;
+    let (token_a_vault_amount, token_b_vault_amount, pool_token_supply) =
+        pool_amounts(&mut ctx, &pool).await;
+    let expected = pool_quoter
+        .swap_curve()
+        .withdraw_single_token_type_exact_out(
+            1_000_000,
+            token_a_vault_amount,
+            token_b_vault_amount,
+            pool_token_supply,
+            TradeDirection::BtoA,
+            &max_fees,
+        )
+        .unwrap();
+    let pool_tokens_before = token_operations::balance(&mut ctx, &user.pool_token_ata).await;
+    client::withdraw_single_token_type_exact_amount_out(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::BtoA,
+        WithdrawSingleTokenTypeExactAmountOut::new(1_000_000, u64::MAX),
+    )
+    .await
+    .unwrap();
+    let pool_tokens_after = token_operations::balance(&mut ctx, &user.pool_token_ata).await;
+    assert_eq!(
+        u128::from(pool_tokens_before - pool_tokens_after),
+        expected.pool_token_amount
+    );
+}
+
+#[tokio::test]
+pub async fn test_update_dynamic_fee() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        fees(),
+        InitialSupply::new(1_000_000, 1_000_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    assert_eq!(
+        client::update_dynamic_fee(
+            &mut ctx,
+            &pool,
+            Some(DynamicFeeParameters {
+                min_fee: 12_000,
+                ..parameters()
+            })
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::InvalidFee)
+    );
+
+    client::update_dynamic_fee(&mut ctx, &pool, Some(parameters()))
+        .await
+        .unwrap();
+    assert!(state::get_pool(&mut ctx, &pool)
+        .await
+        .dynamic_fee
+        .is_enabled());
+
+    // disabling restores the static trade fee
+    client::update_dynamic_fee(&mut ctx, &pool, None)
+        .await
+        .unwrap();
+    let swap_pool = state::get_pool(&mut ctx, &pool).await;
+    assert!(!swap_pool.dynamic_fee.is_enabled());
+    let quoter = quoter(&mut ctx, &pool).await;
+    assert_eq!(quoter.fees(), &fees());
+}
+
+#[tokio::test]
+pub async fn test_update_pool_fees_validates_dynamic_fee() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        fees(),
+        InitialSupply::new(1_000_000, 1_000_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+    client::update_dynamic_fee(&mut ctx, &pool, Some(parameters()))
+        .await
+        .unwrap();
+
+    // raise the program minimum trade fee above the minimum dynamic trade fee of 0.1%
+    let owner = ctx.global_config_owner.clone();
+    client::update_global_config(
+        &mut ctx,
+        &owner,
+        UpdateGlobalConfig::new(
+            UpdateGlobalConfigMode::MinTradeFee,
+            UpdateGlobalConfigValue::Fee {
+                numerator: 2,
+                denominator: 1_000,
+            },
+        ),
+    )
+    .await
+    .unwrap();
+
+    // the static trade fee satisfies the new minimum but the dynamic one charged does not
+    let update_trade_fee = UpdatePoolConfig::new(
+        UpdatePoolConfigMode::TradeFee,
+        UpdatePoolConfigValue::Fee {
+            numerator: 3,
+            denominator: 1_000,
+        },
+    );
+    assert_eq!(
+        client::update_pool_config(&mut ctx, &pool, update_trade_fee.clone())
+            .await
+            .unwrap_err()
+            .unwrap(),
+        hyperplane_error!(SwapError::InvalidFee)
+    );
+
+    client::update_dynamic_fee(&mut ctx, &pool, None)
+        .await
+        .unwrap();
+    client::update_pool_config(&mut ctx, &pool, update_trade_fee)
+        .await
+        .unwrap();
+    assert_eq!(
+        state::get_pool(&mut ctx, &pool)
+            .await
+            .fees
+            .trade_fee_numerator,
+        3
+    );
+}
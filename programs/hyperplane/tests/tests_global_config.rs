@@ -0,0 +1,460 @@
+mod common;
+
+use anchor_spl::token_2022::spl_token_2022::extension::ExtensionType;
+use common::{client, runner};
+use hyperplane::{
+    curve::{base::CurveType, calculator::TradeDirection, fees::Fees},
+    error::SwapError,
+    ix::{Initialize, Swap, UpdateGlobalConfig, UpdatePoolConfig},
+    state::{
+        UpdateGlobalConfigMode, UpdateGlobalConfigValue, UpdatePoolConfigMode,
+        UpdatePoolConfigValue,
+    },
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+use solana_sdk::signer::Signer;
+
+use crate::common::{
+    fixtures,
+    fixtures::Sol,
+    setup,
+    setup::{default_supply, new_keypair},
+    state,
+    types::{SwapPairSpec, TokenSpec},
+};
+
+#[tokio::test]
+pub async fn test_initialize_global_config() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let global_config = state::get_global_config(&mut ctx).await;
+    assert_eq!(global_config.owner, ctx.global_config_owner.pubkey());
+    assert_eq!(global_config.min_fees, Fees::default());
+    assert_eq!(
+        global_config.valid_curve_types().collect::<Vec<_>>(),
+        runner::all_curve_types()
+    );
+    assert_eq!(global_config.blocked_token_extensions().count(), 0);
+}
+
+#[tokio::test]
+pub async fn test_update_global_config() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+    let owner = ctx.global_config_owner.clone();
+
+    for (mode, numerator, denominator) in [
+        (UpdateGlobalConfigMode::MinTradeFee, 1, 1_000),
+        (UpdateGlobalConfigMode::MinOwnerTradeFee, 2, 1_000),
+        (UpdateGlobalConfigMode::MinOwnerWithdrawFee, 3, 1_000),
+        (UpdateGlobalConfigMode::MinHostFee, 4, 1_000),
+    ] {
+        client::update_global_config(
+            &mut ctx,
+            &owner,
+            UpdateGlobalConfig::new(
+                mode,
+                UpdateGlobalConfigValue::Fee {
+                    numerator,
+                    denominator,
+                },
+            ),
+        )
+        .await
+        .unwrap();
+    }
+    client::update_global_config(
+        &mut ctx,
+        &owner,
+        UpdateGlobalConfig::new(
+            UpdateGlobalConfigMode::ValidCurveTypes,
+            UpdateGlobalConfigValue::CurveTypes(vec![
+                CurveType::ConstantProduct.into(),
+                CurveType::Stable.into(),
+            ]),
+        ),
+    )
+    .await
+    .unwrap();
+    client::update_global_config(
+        &mut ctx,
+        &owner,
+        UpdateGlobalConfig::new(
+            UpdateGlobalConfigMode::BlockedTokenExtensions,
+            UpdateGlobalConfigValue::TokenExtensions(vec![
+                ExtensionType::TransferFeeConfig.into(),
+                ExtensionType::PermanentDelegate.into(),
+            ]),
+        ),
+    )
+    .await
+    .unwrap();
+
+    let global_config = state::get_global_config(&mut ctx).await;
+    assert_eq!(
+        global_config.min_fees,
+        Fees {
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 1_000,
+            owner_trade_fee_numerator: 2,
+            owner_trade_fee_denominator: 1_000,
+            owner_withdraw_fee_numerator: 3,
+            owner_withdraw_fee_denominator: 1_000,
+            host_fee_numerator: 4,
+            host_fee_denominator: 1_000,
+        }
+    );
+    assert_eq!(
+        global_config.valid_curve_types().collect::<Vec<_>>(),
+        vec![
+            u64::from(CurveType::ConstantProduct),
+            u64::from(CurveType::Stable)
+        ]
+    );
+    assert_eq!(
+        global_config.blocked_token_extensions().collect::<Vec<_>>(),
+        vec![
+            u16::from(ExtensionType::TransferFeeConfig),
+            u16::from(ExtensionType::PermanentDelegate)
+        ]
+    );
+
+    // new owner takes over the global config
+    let new_owner = new_keypair(&mut ctx, Sol::one()).await;
+    client::update_global_config(
+        &mut ctx,
+        &owner,
+        UpdateGlobalConfig::new(
+            UpdateGlobalConfigMode::Owner,
+            UpdateGlobalConfigValue::Pubkey(new_owner.pubkey()),
+        ),
+    )
+    .await
+    .unwrap();
+    client::update_global_config(
+        &mut ctx,
+        &new_owner,
+        UpdateGlobalConfig::new(
+            UpdateGlobalConfigMode::ValidCurveTypes,
+            UpdateGlobalConfigValue::CurveTypes(runner::all_curve_types()),
+        ),
+    )
+    .await
+    .unwrap();
+
+    let global_config = state::get_global_config(&mut ctx).await;
+    assert_eq!(global_config.owner, new_owner.pubkey());
+    assert_eq!(
+        global_config.valid_curve_types().collect::<Vec<_>>(),
+        runner::all_curve_types()
+    );
+}
+
+#[tokio::test]
+pub async fn test_global_config_min_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+    let owner = ctx.global_config_owner.clone();
+
+    client::update_global_config(
+        &mut ctx,
+        &owner,
+        UpdateGlobalConfig::new(
+            UpdateGlobalConfigMode::MinTradeFee,
+            UpdateGlobalConfigValue::Fee {
+                numerator: 1,
+                denominator: 100,
+            },
+        ),
+    )
+    .await
+    .unwrap();
+
+    // pool fees below the minimum
+    {
+        let initial_supply = default_supply();
+        let pool = setup::new_pool_accs(&mut ctx, SwapPairSpec::default(), &initial_supply).await;
+        assert_eq!(
+            client::initialize_pool(
+                &mut ctx,
+                &pool,
+                Initialize {
+                    fees: Fees::default(),
+                    initial_supply,
+                    curve_parameters: CurveUserParameters::ConstantProduct,
+                    pool_token_metadata: None,
+                },
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            hyperplane_error!(SwapError::InvalidFee, 1)
+        );
+    }
+
+    // the same fee fraction with a different denominator meets the minimum
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees {
+            trade_fee_numerator: 10,
+            trade_fee_denominator: 1_000,
+            ..Default::default()
+        },
+        default_supply(),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    // pool fees cannot be updated below the minimum
+    assert_eq!(
+        client::update_pool_config(
+            &mut ctx,
+            &pool,
+            UpdatePoolConfig::new(
+                UpdatePoolConfigMode::TradeFee,
+                UpdatePoolConfigValue::Fee {
+                    numerator: 9,
+                    denominator: 1_000,
+                },
+            ),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::InvalidFee)
+    );
+    client::update_pool_config(
+        &mut ctx,
+        &pool,
+        UpdatePoolConfig::new(
+            UpdatePoolConfigMode::TradeFee,
+            UpdatePoolConfigValue::Fee {
+                numerator: 2,
+                denominator: 100,
+            },
+        ),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+pub async fn test_global_config_host_fee() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+    let owner = ctx.global_config_owner.clone();
+
+    client::update_global_config(
+        &mut ctx,
+        &owner,
+        UpdateGlobalConfig::new(
+            UpdateGlobalConfigMode::MinHostFee,
+            UpdateGlobalConfigValue::Fee {
+                numerator: 20,
+                denominator: 100,
+            },
+        ),
+    )
+    .await
+    .unwrap();
+
+    // the host fee must match exactly, neither a lower nor a higher one is accepted
+    for (host_fee_numerator, host_fee_denominator) in [(19, 100), (21, 100), (2, 10)] {
+        let initial_supply = default_supply();
+        let pool = setup::new_pool_accs(&mut ctx, SwapPairSpec::default(), &initial_supply).await;
+        assert_eq!(
+            client::initialize_pool(
+                &mut ctx,
+                &pool,
+                Initialize {
+                    fees: Fees {
+                        host_fee_numerator,
+                        host_fee_denominator,
+                        ..Default::default()
+                    },
+                    initial_supply,
+                    curve_parameters: CurveUserParameters::ConstantProduct,
+                    pool_token_metadata: None,
+                },
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            hyperplane_error!(SwapError::InvalidFee, 1)
+        );
+    }
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees {
+            host_fee_numerator: 20,
+            host_fee_denominator: 100,
+            ..Default::default()
+        },
+        default_supply(),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    // nor can pools update their host fee away from it
+    assert_eq!(
+        client::update_pool_config(
+            &mut ctx,
+            &pool,
+            UpdatePoolConfig::new(
+                UpdatePoolConfigMode::HostFee,
+                UpdatePoolConfigValue::Fee {
+                    numerator: 30,
+                    denominator: 100,
+                },
+            ),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::InvalidFee)
+    );
+}
+
+#[tokio::test]
+pub async fn test_global_config_valid_curve_types() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+    let owner = ctx.global_config_owner.clone();
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(1_000_000, 1_000_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+    let user = setup::new_pool_user(&mut ctx, &pool, (100, 0)).await;
+
+    client::update_global_config(
+        &mut ctx,
+        &owner,
+        UpdateGlobalConfig::new(
+            UpdateGlobalConfigMode::ValidCurveTypes,
+            UpdateGlobalConfigValue::CurveTypes(vec![CurveType::Stable.into()]),
+        ),
+    )
+    .await
+    .unwrap();
+
+    // new pools of a disallowed curve type
+    {
+        let initial_supply = default_supply();
+        let new_pool =
+            setup::new_pool_accs(&mut ctx, SwapPairSpec::default(), &initial_supply).await;
+        assert_eq!(
+            client::initialize_pool(
+                &mut ctx,
+                &new_pool,
+                Initialize {
+                    fees: Fees::default(),
+                    initial_supply,
+                    curve_parameters: CurveUserParameters::ConstantProduct,
+                    pool_token_metadata: None,
+                },
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            hyperplane_error!(SwapError::UnsupportedCurveType, 1)
+        );
+    }
+
+    // swaps through existing pools of a disallowed curve type
+    assert_eq!(
+        client::swap(
+            &mut ctx,
+            &pool,
+            &user,
+            TradeDirection::AtoB,
+            Swap::new(100, 0),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::UnsupportedCurveType)
+    );
+
+    client::update_global_config(
+        &mut ctx,
+        &owner,
+        UpdateGlobalConfig::new(
+            UpdateGlobalConfigMode::ValidCurveTypes,
+            UpdateGlobalConfigValue::CurveTypes(runner::all_curve_types()),
+        ),
+    )
+    .await
+    .unwrap();
+
+    client::swap(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        Swap::new(100, 0),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+pub async fn test_global_config_blocked_token_extensions() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+    let owner = ctx.global_config_owner.clone();
+
+    client::update_global_config(
+        &mut ctx,
+        &owner,
+        UpdateGlobalConfig::new(
+            UpdateGlobalConfigMode::BlockedTokenExtensions,
+            UpdateGlobalConfigValue::TokenExtensions(vec![ExtensionType::TransferFeeConfig.into()]),
+        ),
+    )
+    .await
+    .unwrap();
+
+    let initial_supply = default_supply();
+    let pool = setup::new_pool_accs(
+        &mut ctx,
+        SwapPairSpec::new(TokenSpec::transfer_fees(100), TokenSpec::default()),
+        &initial_supply,
+    )
+    .await;
+    assert_eq!(
+        client::initialize_pool(
+            &mut ctx,
+            &pool,
+            Initialize {
+                fees: Fees::default(),
+                initial_supply,
+                curve_parameters: CurveUserParameters::ConstantProduct,
+                pool_token_metadata: None,
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::InvalidTokenExtension, 1)
+    );
+
+    // mints without the blocked extension are still allowed
+    fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        default_supply(),
+        SwapPairSpec::new(TokenSpec::transfer_fees(0), TokenSpec::default()),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+}
@@ -95,6 +95,7 @@ pub async fn test_initialize_pool_with_same_token_a_and_b() {
                 fees: Fees::default(),
                 initial_supply,
                 curve_parameters: CurveUserParameters::Stable { amp: 100 },
+                pool_token_metadata: None,
             },
         )
         .await
@@ -130,6 +131,7 @@ pub async fn test_initialize_pool_with_same_token_b_and_a() {
                 fees: Fees::default(),
                 initial_supply,
                 curve_parameters: CurveUserParameters::Stable { amp: 100 },
+                pool_token_metadata: None,
             },
         )
         .await
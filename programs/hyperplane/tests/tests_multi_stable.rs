@@ -0,0 +1,591 @@
+mod common;
+
+use std::sync::Arc;
+
+use anchor_spl::token::spl_token;
+use common::{client, runner, setup, token_operations};
+use hyperplane::{
+    curve::fees::Fees,
+    error::SwapError,
+    ix,
+    ix::{
+        InitMultiStableToken, InitializeMultiStablePool, MultiStableDeposit,
+        MultiStableLiquidityToken, MultiStableWithdraw, MultiStableWithdrawImbalanced, Swap,
+        UpdateGlobalConfig, WithdrawFees, WithdrawProtocolFees,
+    },
+    state::{MultiStablePool, UpdateGlobalConfigMode, UpdateGlobalConfigValue},
+    utils::seeds,
+};
+use solana_program_test::tokio::{self};
+use solana_sdk::{
+    pubkey::Pubkey, signature::Keypair, signer::Signer, system_instruction,
+    transaction::TransactionError,
+};
+
+use crate::common::{
+    fixtures::Sol,
+    state,
+    types::{TestContext, TokenSpec},
+};
+
+const AMP: u64 = 100;
+const DECIMALS: [u8; 3] = [6, 6, 9];
+const INITIAL_SUPPLIES: [u64; 3] = [1_000_000_000, 1_000_000_000, 1_000_000_000_000];
+
+fn fees() -> Fees {
+    Fees {
+        trade_fee_numerator: 1,
+        trade_fee_denominator: 1_000,
+        owner_trade_fee_numerator: 1,
+        owner_trade_fee_denominator: 2_000,
+        ..Default::default()
+    }
+}
+
+struct MultiStablePoolAccounts {
+    admin: Arc<Keypair>,
+    pool: Arc<Keypair>,
+    authority: Pubkey,
+    pool_token_mint: Pubkey,
+    admin_pool_token_ata: Pubkey,
+    mints: Vec<Pubkey>,
+    vaults: Vec<Pubkey>,
+    fees_vaults: Vec<Pubkey>,
+    protocol_fees_vaults: Vec<Pubkey>,
+}
+
+struct MultiStableUser {
+    user: Arc<Keypair>,
+    atas: Vec<Pubkey>,
+    pool_token_ata: Pubkey,
+}
+
+async fn new_multi_stable_pool(
+    ctx: &mut TestContext,
+    initial_supplies: &[u64],
+) -> Result<MultiStablePoolAccounts, TransactionError> {
+    let admin = setup::new_keypair(ctx, Sol::from(100.0)).await;
+    let pool = setup::kp();
+    let admin_pool_token_ata = setup::kp();
+
+    let mut tokens = vec![];
+    for (decimals, supply) in DECIMALS.iter().zip(initial_supplies.iter()) {
+        let mint = setup::kp();
+        token_operations::create_mint(ctx, &mint, TokenSpec::new(*decimals, 0, spl_token::id()))
+            .await
+            .unwrap();
+        let admin_ata = token_operations::create_and_mint_to_token_account(
+            ctx,
+            &spl_token::id(),
+            &admin.pubkey(),
+            &mint.pubkey(),
+            *supply,
+        )
+        .await;
+        tokens.push(InitMultiStableToken::new(
+            mint.pubkey(),
+            admin_ata,
+            spl_token::id(),
+        ));
+    }
+
+    let init_ix = ix::initialize_multi_stable_pool(
+        &hyperplane::id(),
+        &admin.pubkey(),
+        &pool.pubkey(),
+        &admin_pool_token_ata.pubkey(),
+        &spl_token::id(),
+        &tokens,
+        InitializeMultiStablePool::new(AMP, fees(), initial_supplies.to_vec()),
+    )
+    .unwrap();
+    crate::send_tx!(
+        ctx,
+        [
+            system_instruction::create_account(
+                &ctx.context.payer.pubkey(),
+                &pool.pubkey(),
+                ctx.rent.minimum_balance(MultiStablePool::LEN),
+                MultiStablePool::LEN as u64,
+                &hyperplane::id(),
+            ),
+            init_ix
+        ],
+        pool.as_ref(),
+        admin.as_ref(),
+        admin_pool_token_ata.as_ref()
+    )
+    .map_err(|e| e.unwrap())?;
+
+    let mints: Vec<Pubkey> = tokens.iter().map(|token| token.mint).collect();
+    Ok(MultiStablePoolAccounts {
+        admin,
+        authority: seeds::pda::pool_authority_pda(&pool.pubkey()).0,
+        pool_token_mint: seeds::pda::pool_token_mint_pda_program_id(
+            &hyperplane::id(),
+            &pool.pubkey(),
+        )
+        .0,
+        admin_pool_token_ata: admin_pool_token_ata.pubkey(),
+        vaults: mints
+            .iter()
+            .map(|mint| seeds::pda::token_vault_pda(&pool.pubkey(), mint).0)
+            .collect(),
+        fees_vaults: mints
+            .iter()
+            .map(|mint| seeds::pda::token_fees_vault_pda(&pool.pubkey(), mint).0)
+            .collect(),
+        protocol_fees_vaults: mints
+            .iter()
+            .map(|mint| seeds::pda::token_protocol_fees_vault_pda(&pool.pubkey(), mint).0)
+            .collect(),
+        mints,
+        pool,
+    })
+}
+
+async fn new_user(
+    ctx: &mut TestContext,
+    pool: &MultiStablePoolAccounts,
+    balances: &[u64],
+) -> MultiStableUser {
+    let user = setup::new_keypair(ctx, Sol::one()).await;
+    let mut atas = vec![];
+    for (mint, balance) in pool.mints.iter().zip(balances.iter()) {
+        atas.push(
+            token_operations::create_and_mint_to_token_account(
+                ctx,
+                &spl_token::id(),
+                &user.pubkey(),
+                mint,
+                *balance,
+            )
+            .await,
+        );
+    }
+    let pool_token_ata = token_operations::create_token_account(
+        ctx,
+        &spl_token::id(),
+        &pool.pool_token_mint,
+        &user.pubkey(),
+    )
+    .await
+    .unwrap();
+    MultiStableUser {
+        user,
+        atas,
+        pool_token_ata,
+    }
+}
+
+fn liquidity_tokens(
+    pool: &MultiStablePoolAccounts,
+    user: &MultiStableUser,
+) -> Vec<MultiStableLiquidityToken> {
+    pool.mints
+        .iter()
+        .zip(pool.vaults.iter())
+        .zip(user.atas.iter())
+        .map(|((mint, vault), ata)| {
+            MultiStableLiquidityToken::new(*mint, *vault, *ata, spl_token::id())
+        })
+        .collect()
+}
+
+async fn swap(
+    ctx: &mut TestContext,
+    pool: &MultiStablePoolAccounts,
+    user: &MultiStableUser,
+    i: usize,
+    j: usize,
+    swap: Swap,
+) -> Result<(), TransactionError> {
+    let ix = ix::multi_stable_swap(
+        &hyperplane::id(),
+        &user.user.pubkey(),
+        &pool.pool.pubkey(),
+        &pool.authority,
+        &pool.mints[i],
+        &pool.mints[j],
+        &pool.vaults[i],
+        &pool.vaults[j],
+        &pool.fees_vaults[i],
+        &pool.protocol_fees_vaults[i],
+        &user.atas[i],
+        &user.atas[j],
+        &spl_token::id(),
+        &spl_token::id(),
+        &pool.vaults,
+        swap,
+    )
+    .unwrap();
+    crate::send_tx!(ctx, [ix], user.user.as_ref()).map_err(|e| e.unwrap())
+}
+
+async fn balances(ctx: &mut TestContext, accounts: &[Pubkey]) -> Vec<u64> {
+    let mut balances = vec![];
+    for account in accounts {
+        balances.push(token_operations::balance(ctx, account).await);
+    }
+    balances
+}
+
+#[tokio::test]
+pub async fn test_initialize_multi_stable_pool() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = new_multi_stable_pool(&mut ctx, &INITIAL_SUPPLIES)
+        .await
+        .unwrap();
+
+    let pool_state = state::get::<MultiStablePool>(&mut ctx, pool.pool.pubkey()).await;
+    assert_eq!(pool_state.admin, pool.admin.pubkey());
+    assert_eq!(pool_state.n_tokens, 3);
+    assert_eq!(pool_state.amp, AMP);
+    assert_eq!(
+        pool_state
+            .tokens()
+            .iter()
+            .map(|token| token.factor)
+            .collect::<Vec<_>>(),
+        vec![1_000, 1_000, 1]
+    );
+    assert_eq!(
+        balances(&mut ctx, &pool.vaults).await,
+        INITIAL_SUPPLIES.to_vec()
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.admin_pool_token_ata).await,
+        1_000_000_000
+    );
+}
+
+#[tokio::test]
+pub async fn test_initialize_multi_stable_pool_fails_with_empty_supply() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    assert_eq!(
+        new_multi_stable_pool(&mut ctx, &[1_000_000_000, 0, 1_000_000_000_000])
+            .await
+            .err()
+            .unwrap(),
+        hyperplane_error!(SwapError::EmptySupply, 1)
+    );
+}
+
+#[tokio::test]
+pub async fn test_multi_stable_swap() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = new_multi_stable_pool(&mut ctx, &INITIAL_SUPPLIES)
+        .await
+        .unwrap();
+    let user = new_user(&mut ctx, &pool, &[0, 1_000_000, 0]).await;
+
+    // swap token 1 (6 decimals) for token 2 (9 decimals) in a balanced pool
+    swap(
+        &mut ctx,
+        &pool,
+        &user,
+        1,
+        2,
+        Swap::new(1_000_000, 998_000_000),
+    )
+    .await
+    .unwrap();
+
+    let user_balances = balances(&mut ctx, &user.atas).await;
+    assert_eq!(user_balances[0], 0);
+    assert_eq!(user_balances[1], 0);
+    assert!(user_balances[2] >= 998_000_000 && user_balances[2] < 1_000_000_000);
+
+    // owner fee of 1/2000 to the fees vault, the trade fee stays in the vault
+    assert_eq!(balances(&mut ctx, &pool.fees_vaults).await, vec![0, 500, 0]);
+    assert_eq!(
+        balances(&mut ctx, &pool.protocol_fees_vaults).await,
+        vec![0, 0, 0]
+    );
+    assert_eq!(
+        balances(&mut ctx, &pool.vaults).await,
+        vec![
+            INITIAL_SUPPLIES[0],
+            INITIAL_SUPPLIES[1] + 1_000_000 - 500,
+            INITIAL_SUPPLIES[2] - user_balances[2],
+        ]
+    );
+
+    // the admin withdraws the owner fee
+    let admin_ata = token_operations::create_token_account(
+        &mut ctx,
+        &spl_token::id(),
+        &pool.mints[1],
+        &pool.admin.pubkey(),
+    )
+    .await
+    .unwrap();
+    let withdraw_fees_ix = ix::multi_stable_withdraw_fees(
+        &hyperplane::id(),
+        &pool.admin.pubkey(),
+        &pool.pool.pubkey(),
+        &pool.authority,
+        &pool.mints[1],
+        &pool.fees_vaults[1],
+        &admin_ata,
+        &spl_token::id(),
+        WithdrawFees::new(u64::MAX),
+    )
+    .unwrap();
+    crate::send_tx!(ctx, [withdraw_fees_ix], pool.admin.as_ref()).unwrap();
+    assert_eq!(token_operations::balance(&mut ctx, &admin_ata).await, 500);
+}
+
+#[tokio::test]
+pub async fn test_multi_stable_swap_splits_owner_fee_with_protocol() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let owner = ctx.global_config_owner.clone();
+    client::update_global_config(
+        &mut ctx,
+        &owner,
+        UpdateGlobalConfig::new(
+            UpdateGlobalConfigMode::ProtocolFee,
+            UpdateGlobalConfigValue::Fee {
+                numerator: 1,
+                denominator: 2,
+            },
+        ),
+    )
+    .await
+    .unwrap();
+    let pool = new_multi_stable_pool(&mut ctx, &INITIAL_SUPPLIES)
+        .await
+        .unwrap();
+    let user = new_user(&mut ctx, &pool, &[0, 1_000_000, 0]).await;
+
+    swap(
+        &mut ctx,
+        &pool,
+        &user,
+        1,
+        2,
+        Swap::new(1_000_000, 998_000_000),
+    )
+    .await
+    .unwrap();
+
+    // owner fee of 500, half of which goes to the protocol
+    assert_eq!(balances(&mut ctx, &pool.fees_vaults).await, vec![0, 250, 0]);
+    assert_eq!(
+        balances(&mut ctx, &pool.protocol_fees_vaults).await,
+        vec![0, 250, 0]
+    );
+    assert_eq!(
+        balances(&mut ctx, &pool.vaults).await[1],
+        INITIAL_SUPPLIES[1] + 1_000_000 - 500
+    );
+
+    // the protocol authority withdraws the protocol fee, the pool admin cannot
+    let protocol_authority_ata = token_operations::create_token_account(
+        &mut ctx,
+        &spl_token::id(),
+        &pool.mints[1],
+        &owner.pubkey(),
+    )
+    .await
+    .unwrap();
+    let withdraw_protocol_fees_ix = |protocol_authority: &Pubkey| {
+        ix::multi_stable_withdraw_protocol_fees(
+            &hyperplane::id(),
+            protocol_authority,
+            &pool.pool.pubkey(),
+            &pool.authority,
+            &pool.mints[1],
+            &pool.protocol_fees_vaults[1],
+            &protocol_authority_ata,
+            &spl_token::id(),
+            WithdrawProtocolFees::new(u64::MAX),
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        crate::send_tx!(
+            ctx,
+            [withdraw_protocol_fees_ix(&pool.admin.pubkey())],
+            pool.admin.as_ref()
+        )
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::InvalidProtocolAuthority)
+    );
+    crate::send_tx!(
+        ctx,
+        [withdraw_protocol_fees_ix(&owner.pubkey())],
+        owner.as_ref()
+    )
+    .unwrap();
+    assert_eq!(
+        token_operations::balance(&mut ctx, &protocol_authority_ata).await,
+        250
+    );
+    assert_eq!(
+        balances(&mut ctx, &pool.protocol_fees_vaults).await,
+        vec![0, 0, 0]
+    );
+}
+
+#[tokio::test]
+pub async fn test_multi_stable_swap_fails_with_exceeded_slippage() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = new_multi_stable_pool(&mut ctx, &INITIAL_SUPPLIES)
+        .await
+        .unwrap();
+    let user = new_user(&mut ctx, &pool, &[1_000_000, 0, 0]).await;
+
+    assert_eq!(
+        swap(
+            &mut ctx,
+            &pool,
+            &user,
+            0,
+            1,
+            Swap::new(1_000_000, 1_000_000)
+        )
+        .await
+        .unwrap_err(),
+        hyperplane_error!(SwapError::ExceededSlippage)
+    );
+}
+
+#[tokio::test]
+pub async fn test_multi_stable_deposit_and_withdraw() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = new_multi_stable_pool(&mut ctx, &INITIAL_SUPPLIES)
+        .await
+        .unwrap();
+    let deposit_amounts = vec![100_000_000, 100_000_000, 100_000_000_000];
+    let user = new_user(&mut ctx, &pool, &deposit_amounts).await;
+    let tokens = liquidity_tokens(&pool, &user);
+
+    // a balanced deposit pays no imbalance fee
+    let deposit_ix = ix::multi_stable_deposit(
+        &hyperplane::id(),
+        &user.user.pubkey(),
+        &pool.pool.pubkey(),
+        &pool.authority,
+        &pool.pool_token_mint,
+        &user.pool_token_ata,
+        &spl_token::id(),
+        &tokens,
+        MultiStableDeposit::new(deposit_amounts.clone(), 100_000_000),
+    )
+    .unwrap();
+    crate::send_tx!(ctx, [deposit_ix], user.user.as_ref()).unwrap();
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.pool_token_ata).await,
+        100_000_000
+    );
+    assert_eq!(balances(&mut ctx, &user.atas).await, vec![0, 0, 0]);
+
+    let withdraw_ix = ix::multi_stable_withdraw(
+        &hyperplane::id(),
+        &user.user.pubkey(),
+        &pool.pool.pubkey(),
+        &pool.authority,
+        &pool.pool_token_mint,
+        &user.pool_token_ata,
+        &spl_token::id(),
+        &tokens,
+        MultiStableWithdraw::new(100_000_000, deposit_amounts.clone()),
+    )
+    .unwrap();
+    crate::send_tx!(ctx, [withdraw_ix], user.user.as_ref()).unwrap();
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.pool_token_ata).await,
+        0
+    );
+    assert_eq!(balances(&mut ctx, &user.atas).await, deposit_amounts);
+    assert_eq!(
+        balances(&mut ctx, &pool.vaults).await,
+        INITIAL_SUPPLIES.to_vec()
+    );
+}
+
+#[tokio::test]
+pub async fn test_multi_stable_withdraw_imbalanced() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = new_multi_stable_pool(&mut ctx, &INITIAL_SUPPLIES)
+        .await
+        .unwrap();
+    let user = new_user(
+        &mut ctx,
+        &pool,
+        &[100_000_000, 100_000_000, 100_000_000_000],
+    )
+    .await;
+    let tokens = liquidity_tokens(&pool, &user);
+
+    let deposit_ix = ix::multi_stable_deposit(
+        &hyperplane::id(),
+        &user.user.pubkey(),
+        &pool.pool.pubkey(),
+        &pool.authority,
+        &pool.pool_token_mint,
+        &user.pool_token_ata,
+        &spl_token::id(),
+        &tokens,
+        MultiStableDeposit::new(vec![100_000_000, 100_000_000, 100_000_000_000], 1),
+    )
+    .unwrap();
+    crate::send_tx!(ctx, [deposit_ix], user.user.as_ref()).unwrap();
+
+    // withdrawing a single token costs more pool tokens than the same value withdrawn in balance,
+    // 1/66 of the pool tokens
+    let withdraw_amounts = vec![0, 50_000_000, 0];
+    let too_few_pool_tokens_ix = ix::multi_stable_withdraw_imbalanced(
+        &hyperplane::id(),
+        &user.user.pubkey(),
+        &pool.pool.pubkey(),
+        &pool.authority,
+        &pool.pool_token_mint,
+        &user.pool_token_ata,
+        &spl_token::id(),
+        &tokens,
+        MultiStableWithdrawImbalanced::new(withdraw_amounts.clone(), 16_666_666),
+    )
+    .unwrap();
+    assert_eq!(
+        crate::send_tx!(ctx, [too_few_pool_tokens_ix], user.user.as_ref())
+            .unwrap_err()
+            .unwrap(),
+        hyperplane_error!(SwapError::ExceededSlippage)
+    );
+
+    let withdraw_ix = ix::multi_stable_withdraw_imbalanced(
+        &hyperplane::id(),
+        &user.user.pubkey(),
+        &pool.pool.pubkey(),
+        &pool.authority,
+        &pool.pool_token_mint,
+        &user.pool_token_ata,
+        &spl_token::id(),
+        &tokens,
+        MultiStableWithdrawImbalanced::new(withdraw_amounts.clone(), 16_700_000),
+    )
+    .unwrap();
+    crate::send_tx!(ctx, [withdraw_ix], user.user.as_ref()).unwrap();
+
+    assert_eq!(balances(&mut ctx, &user.atas).await, withdraw_amounts);
+    let pool_tokens_burnt =
+        100_000_000 - token_operations::balance(&mut ctx, &user.pool_token_ata).await;
+    assert!(pool_tokens_burnt > 16_666_666 && pool_tokens_burnt <= 16_700_000);
+}
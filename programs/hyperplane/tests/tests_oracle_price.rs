@@ -0,0 +1,411 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::{client, runner};
+use hyperplane::{
+    curve::{
+        calculator::{TradeDirection, INITIAL_SWAP_POOL_AMOUNT},
+        fees::Fees,
+        oracle_price::PriceFeed,
+    },
+    error::SwapError,
+    ix::{Initialize, Swap, Withdraw},
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::instruction::AccountMeta;
+
+use crate::{
+    common::{
+        setup, token_operations,
+        types::{PoolUserAccounts, SwapPairSpec, SwapPoolAccounts, TestContext},
+        utils::{set_price_feed, set_unix_timestamp},
+    },
+    send_tx,
+};
+
+const MAX_PRICE_AGE_SEC: u64 = 60;
+const MAX_PRICE_CONFIDENCE_BPS: u64 = 100;
+
+struct OraclePool {
+    pool: SwapPoolAccounts,
+    price_feed: Pubkey,
+    price_feed_program: Pubkey,
+}
+
+fn price(price: i64, conf: u64, publish_time: i64) -> PriceFeed {
+    PriceFeed {
+        price,
+        conf,
+        expo: 0,
+        publish_time,
+    }
+}
+
+async fn new_oracle_pool(ctx: &mut TestContext, initial_price: PriceFeed) -> OraclePool {
+    let price_feed = Pubkey::new_unique();
+    let price_feed_program = Pubkey::new_unique();
+    set_price_feed(ctx, &price_feed, &price_feed_program, initial_price);
+
+    let initial_supply = InitialSupply::new(1_000_000, 1_000_000);
+    let pool = setup::new_pool_accs(ctx, SwapPairSpec::default(), &initial_supply).await;
+    client::initialize_pool(
+        ctx,
+        &pool,
+        Initialize {
+            fees: Fees::default(),
+            initial_supply,
+            curve_parameters: CurveUserParameters::OraclePrice {
+                price_feed,
+                price_feed_program,
+                max_price_age_sec: MAX_PRICE_AGE_SEC,
+                max_price_confidence_bps: MAX_PRICE_CONFIDENCE_BPS,
+            },
+            pool_token_metadata: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    OraclePool {
+        pool,
+        price_feed,
+        price_feed_program,
+    }
+}
+
+async fn swap(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    user: &PoolUserAccounts,
+    price_feed: Option<&Pubkey>,
+    trade_direction: TradeDirection,
+    swap: Swap,
+) -> Result<(), BanksClientError> {
+    let mut swap_ix = client::instructions::swap(pool, user, None, trade_direction, swap);
+    if let Some(price_feed) = price_feed {
+        swap_ix
+            .accounts
+            .push(AccountMeta::new_readonly(*price_feed, false));
+    }
+    send_tx!(ctx, [swap_ix], user.user.as_ref())
+}
+
+#[tokio::test]
+pub async fn test_swap_at_oracle_price() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    set_unix_timestamp(&mut ctx, 1_000).await;
+    let OraclePool {
+        pool,
+        price_feed,
+        price_feed_program,
+    } = new_oracle_pool(&mut ctx, price(2, 0, 1_000)).await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 0)).await;
+    swap(
+        &mut ctx,
+        &pool,
+        &user,
+        Some(&price_feed),
+        TradeDirection::AtoB,
+        Swap::new(1_000, 500),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.token_b_ata).await,
+        500
+    );
+
+    // the next swap uses the updated price
+    set_unix_timestamp(&mut ctx, 1_010).await;
+    set_price_feed(
+        &mut ctx,
+        &price_feed,
+        &price_feed_program,
+        price(4, 0, 1_010),
+    );
+
+    swap(
+        &mut ctx,
+        &pool,
+        &user,
+        Some(&price_feed),
+        TradeDirection::BtoA,
+        Swap::new(500, 2_000),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.token_a_ata).await,
+        2_000
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.token_b_ata).await,
+        0
+    );
+}
+
+#[tokio::test]
+pub async fn test_swap_at_fractional_oracle_price() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    set_unix_timestamp(&mut ctx, 1_000).await;
+    // 0.25 token A per token B
+    let OraclePool {
+        pool,
+        price_feed,
+        price_feed_program,
+    } = new_oracle_pool(
+        &mut ctx,
+        PriceFeed {
+            expo: -2,
+            ..price(25, 0, 1_000)
+        },
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 0)).await;
+    swap(
+        &mut ctx,
+        &pool,
+        &user,
+        Some(&price_feed),
+        TradeDirection::AtoB,
+        Swap::new(1_000, 4_000),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.token_a_ata).await,
+        0
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.token_b_ata).await,
+        4_000
+    );
+
+    // 2.5005 token A per token B
+    set_price_feed(
+        &mut ctx,
+        &price_feed,
+        &price_feed_program,
+        PriceFeed {
+            expo: -4,
+            ..price(25_005, 0, 1_000)
+        },
+    );
+    swap(
+        &mut ctx,
+        &pool,
+        &user,
+        Some(&price_feed),
+        TradeDirection::BtoA,
+        Swap::new(1_000, 2_500),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.token_a_ata).await,
+        2_500
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.token_b_ata).await,
+        3_000
+    );
+}
+
+#[tokio::test]
+pub async fn test_swap_fails_with_stale_price() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    set_unix_timestamp(&mut ctx, 1_000).await;
+    let OraclePool {
+        pool, price_feed, ..
+    } = new_oracle_pool(&mut ctx, price(2, 0, 1_000)).await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 0)).await;
+    set_unix_timestamp(&mut ctx, 1_000 + MAX_PRICE_AGE_SEC as i64 + 1).await;
+    assert_eq!(
+        swap(
+            &mut ctx,
+            &pool,
+            &user,
+            Some(&price_feed),
+            TradeDirection::AtoB,
+            Swap::new(1_000, 500),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::StalePrice)
+    );
+}
+
+#[tokio::test]
+pub async fn test_withdraw_with_stale_price() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    set_unix_timestamp(&mut ctx, 1_000).await;
+    let OraclePool { pool, .. } = new_oracle_pool(&mut ctx, price(2, 0, 1_000)).await;
+
+    // proportional withdrawals do not depend on the price, so neither a stale nor a missing price
+    // feed locks LPs out of the pool
+    set_unix_timestamp(&mut ctx, 1_000 + MAX_PRICE_AGE_SEC as i64 + 1).await;
+    let admin: PoolUserAccounts = pool.admin.clone().into();
+    let token_a_balance = token_operations::balance(&mut ctx, &admin.token_a_ata).await;
+    let token_b_balance = token_operations::balance(&mut ctx, &admin.token_b_ata).await;
+    client::withdraw(
+        &mut ctx,
+        &pool,
+        &admin,
+        Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64 / 2, 1, 1),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_operations::balance(&mut ctx, &admin.token_a_ata).await,
+        token_a_balance + 500_000
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &admin.token_b_ata).await,
+        token_b_balance + 500_000
+    );
+}
+
+#[tokio::test]
+pub async fn test_swap_fails_with_wide_price_confidence() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    set_unix_timestamp(&mut ctx, 1_000).await;
+    let OraclePool {
+        pool,
+        price_feed,
+        price_feed_program,
+    } = new_oracle_pool(&mut ctx, price(100, 1, 1_000)).await;
+
+    // 2% confidence interval
+    set_price_feed(
+        &mut ctx,
+        &price_feed,
+        &price_feed_program,
+        price(100, 2, 1_000),
+    );
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 0)).await;
+    assert_eq!(
+        swap(
+            &mut ctx,
+            &pool,
+            &user,
+            Some(&price_feed),
+            TradeDirection::AtoB,
+            Swap::new(1_000, 10),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::PriceConfidenceTooWide)
+    );
+}
+
+#[tokio::test]
+pub async fn test_swap_fails_with_invalid_price_feed() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    set_unix_timestamp(&mut ctx, 1_000).await;
+    let OraclePool {
+        pool, price_feed, ..
+    } = new_oracle_pool(&mut ctx, price(2, 0, 1_000)).await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 0)).await;
+
+    // missing price feed
+    {
+        assert_eq!(
+            swap(
+                &mut ctx,
+                &pool,
+                &user,
+                None,
+                TradeDirection::AtoB,
+                Swap::new(1_000, 500),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            hyperplane_error!(SwapError::InvalidPriceFeed)
+        );
+    }
+
+    // price feed owned by the wrong program
+    {
+        set_price_feed(
+            &mut ctx,
+            &price_feed,
+            &Pubkey::new_unique(),
+            price(2, 0, 1_000),
+        );
+        assert_eq!(
+            swap(
+                &mut ctx,
+                &pool,
+                &user,
+                Some(&price_feed),
+                TradeDirection::AtoB,
+                Swap::new(1_000, 500),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            hyperplane_error!(SwapError::InvalidPriceFeed)
+        );
+    }
+}
+
+#[tokio::test]
+pub async fn test_initialize_oracle_pool_fails_with_stale_price() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    set_unix_timestamp(&mut ctx, 1_000).await;
+    let price_feed = Pubkey::new_unique();
+    let price_feed_program = Pubkey::new_unique();
+    set_price_feed(
+        &mut ctx,
+        &price_feed,
+        &price_feed_program,
+        price(2, 0, 1_000 - MAX_PRICE_AGE_SEC as i64 - 1),
+    );
+
+    let initial_supply = InitialSupply::new(1_000_000, 1_000_000);
+    let pool = setup::new_pool_accs(&mut ctx, SwapPairSpec::default(), &initial_supply).await;
+    assert_eq!(
+        client::initialize_pool(
+            &mut ctx,
+            &pool,
+            Initialize {
+                fees: Fees::default(),
+                initial_supply,
+                curve_parameters: CurveUserParameters::OraclePrice {
+                    price_feed,
+                    price_feed_program,
+                    max_price_age_sec: MAX_PRICE_AGE_SEC,
+                    max_price_confidence_bps: MAX_PRICE_CONFIDENCE_BPS,
+                },
+                pool_token_metadata: None,
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::StalePrice, 1)
+    );
+}
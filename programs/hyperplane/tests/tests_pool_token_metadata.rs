@@ -0,0 +1,146 @@
+mod common;
+
+use anchor_lang::Id;
+use anchor_spl::token_2022::Token2022;
+use common::{client, runner};
+use hyperplane::{
+    curve::{calculator::INITIAL_SWAP_POOL_AMOUNT, fees::Fees},
+    error::SwapError,
+    ix::{Deposit, Initialize},
+    utils::token_extensions,
+    CurveUserParameters, InitialSupply, PoolTokenMetadata,
+};
+use solana_program_test::tokio::{self};
+use solana_sdk::signer::Signer;
+
+use crate::common::{fixtures::ProgramDependency, setup, token_operations, types::SwapPairSpec};
+
+fn metadata() -> PoolTokenMetadata {
+    PoolTokenMetadata {
+        name: "HYP-USDC-USDT LP".to_string(),
+        symbol: "HYP-USDC-USDT".to_string(),
+        uri: "https://hyperplane.so/lp.json".to_string(),
+    }
+}
+
+#[tokio::test]
+pub async fn test_token_2022_pool_token_with_metadata() {
+    let program = runner::program(&[ProgramDependency::TokenMetadata]);
+    let mut ctx = runner::start(program).await;
+
+    let initial_supply = InitialSupply::new(100, 100);
+    let mut pool =
+        setup::new_pool_accs(&mut ctx, SwapPairSpec::spl_tokens(6, 6), &initial_supply).await;
+    pool.pool_token_program = Token2022::id();
+
+    client::initialize_pool(
+        &mut ctx,
+        &pool,
+        Initialize {
+            fees: Fees::default(),
+            initial_supply,
+            curve_parameters: CurveUserParameters::ConstantProduct,
+            pool_token_metadata: Some(metadata()),
+        },
+    )
+    .await
+    .unwrap();
+
+    let pool_token_mint = ctx
+        .context
+        .banks_client
+        .get_account(pool.pool_token_mint)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(pool_token_mint.owner, Token2022::id());
+    let token_metadata = token_extensions::token_metadata(&pool_token_mint.data)
+        .unwrap()
+        .unwrap();
+    assert_eq!(token_metadata.name, metadata().name);
+    assert_eq!(token_metadata.symbol, metadata().symbol);
+    assert_eq!(token_metadata.uri, metadata().uri);
+    assert_eq!(token_metadata.mint, pool.pool_token_mint);
+    assert_eq!(
+        Option::<_>::from(token_metadata.update_authority),
+        Some(pool.authority)
+    );
+
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.admin.pool_token_ata.pubkey()).await,
+        INITIAL_SWAP_POOL_AMOUNT as u64
+    );
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (50, 50)).await;
+    client::deposit(
+        &mut ctx,
+        &pool,
+        &user,
+        Deposit {
+            pool_token_amount: INITIAL_SWAP_POOL_AMOUNT as u64 / 2,
+            maximum_token_a_amount: 50,
+            maximum_token_b_amount: 50,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.pool_token_ata).await,
+        INITIAL_SWAP_POOL_AMOUNT as u64 / 2
+    );
+}
+
+#[tokio::test]
+pub async fn test_token_2022_pool_token_without_trading_token_metadata() {
+    let program = runner::program(&[ProgramDependency::TokenMetadata]);
+    let mut ctx = runner::start(program).await;
+
+    let initial_supply = InitialSupply::new(100, 100);
+    let mut pool =
+        setup::new_pool_accs(&mut ctx, SwapPairSpec::spl_tokens(6, 6), &initial_supply).await;
+    pool.pool_token_program = Token2022::id();
+
+    assert_eq!(
+        client::initialize_pool(
+            &mut ctx,
+            &pool,
+            Initialize {
+                fees: Fees::default(),
+                initial_supply,
+                curve_parameters: CurveUserParameters::ConstantProduct,
+                pool_token_metadata: None,
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::MissingPoolTokenMetadata, 1)
+    );
+}
+
+#[tokio::test]
+pub async fn test_spl_token_pool_token_with_metadata() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let initial_supply = InitialSupply::new(100, 100);
+    let pool =
+        setup::new_pool_accs(&mut ctx, SwapPairSpec::spl_tokens(6, 6), &initial_supply).await;
+
+    assert_eq!(
+        client::initialize_pool(
+            &mut ctx,
+            &pool,
+            Initialize {
+                fees: Fees::default(),
+                initial_supply,
+                curve_parameters: CurveUserParameters::ConstantProduct,
+                pool_token_metadata: Some(metadata()),
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::PoolTokenMetadataNotSupported, 1)
+    );
+}
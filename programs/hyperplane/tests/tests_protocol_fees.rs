@@ -0,0 +1,443 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::{client, runner};
+use hyperplane::{
+    curve::{
+        calculator::{AorB, TradeDirection, INITIAL_SWAP_POOL_AMOUNT},
+        fees::Fees,
+    },
+    error::SwapError,
+    ix::{Swap, UpdateGlobalConfig, Withdraw, WithdrawProtocolFees},
+    state::{UpdateGlobalConfigMode, UpdateGlobalConfigValue},
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+use solana_sdk::{account::AccountSharedData, signer::Signer};
+
+use crate::common::{
+    fixtures,
+    fixtures::Sol,
+    setup,
+    setup::new_keypair,
+    state, token_operations,
+    types::{SwapPairSpec, SwapPoolAccounts, TestContext, TokenSpec},
+    utils::set_pool,
+};
+
+async fn new_pool_with_protocol_fee(ctx: &mut TestContext) -> SwapPoolAccounts {
+    new_pool_with_protocol_fee_for_pair(ctx, SwapPairSpec::default()).await
+}
+
+async fn new_pool_with_protocol_fee_for_pair(
+    ctx: &mut TestContext,
+    swap_pair_spec: SwapPairSpec,
+) -> SwapPoolAccounts {
+    let owner = ctx.global_config_owner.clone();
+    client::update_global_config(
+        ctx,
+        &owner,
+        UpdateGlobalConfig::new(
+            UpdateGlobalConfigMode::ProtocolFee,
+            UpdateGlobalConfigValue::Fee {
+                numerator: 1,
+                denominator: 2,
+            },
+        ),
+    )
+    .await
+    .unwrap();
+
+    fixtures::new_pool(
+        ctx,
+        Fees {
+            owner_trade_fee_numerator: 10,
+            owner_trade_fee_denominator: 100,
+            ..Default::default()
+        },
+        InitialSupply::new(1_000_000, 1_000_000),
+        swap_pair_spec,
+        CurveUserParameters::ConstantProduct,
+    )
+    .await
+}
+
+#[tokio::test]
+pub async fn test_swap_splits_owner_fee_with_protocol() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+    let pool = new_pool_with_protocol_fee(&mut ctx).await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 0)).await;
+    client::swap(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        Swap::new(1_000, 0),
+    )
+    .await
+    .unwrap();
+
+    // owner fee of 100, half of which goes to the protocol
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await,
+        50
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.token_a_protocol_fees_vault).await,
+        50
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.token_b_protocol_fees_vault).await,
+        0
+    );
+}
+
+#[tokio::test]
+pub async fn test_swap_protocol_fee_with_transfer_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+    let pool = new_pool_with_protocol_fee_for_pair(
+        &mut ctx,
+        SwapPairSpec::new(TokenSpec::transfer_fees(100), TokenSpec::default()),
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (20_000, 0)).await;
+    let token_a_balance = token_operations::balance(&mut ctx, &user.token_a_ata).await;
+    let swap = client::swap_with_event(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        Swap::new(10_000, 0),
+    )
+    .await
+    .unwrap();
+
+    // the fees vaults receive the fees of the event net of the transfer fees
+    assert!(swap.protocol_fee > 0);
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.token_a_protocol_fees_vault).await,
+        swap.protocol_fee
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await,
+        swap.owner_fee
+    );
+    // the transfer fees of the protocol fee transfer are covered by the amount in
+    let debited = token_a_balance - token_operations::balance(&mut ctx, &user.token_a_ata).await;
+    assert!(debited <= 10_000, "debited {} > amount_in 10000", debited);
+}
+
+/// Recreate the state of a pool created before protocol fees, without protocol fees vaults
+async fn into_pool_without_protocol_fees_vaults(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+) -> SwapPoolAccounts {
+    let mut swap_pool = state::get_pool(ctx, pool).await;
+    swap_pool.token_a_protocol_fees_vault = Pubkey::default();
+    swap_pool.token_b_protocol_fees_vault = Pubkey::default();
+    set_pool(ctx, pool, &swap_pool).await;
+    for vault in [
+        &pool.token_a_protocol_fees_vault,
+        &pool.token_b_protocol_fees_vault,
+    ] {
+        ctx.context
+            .set_account(vault, &AccountSharedData::default());
+    }
+    SwapPoolAccounts {
+        token_a_protocol_fees_vault: Pubkey::default(),
+        token_b_protocol_fees_vault: Pubkey::default(),
+        ..pool.clone()
+    }
+}
+
+#[tokio::test]
+pub async fn test_pool_without_protocol_fees_vaults() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+    let pool = new_pool_with_protocol_fee(&mut ctx).await;
+    let legacy_pool = into_pool_without_protocol_fees_vaults(&mut ctx, &pool).await;
+
+    // without protocol fees vaults the whole owner fee goes to the admin
+    let user = setup::new_pool_user(&mut ctx, &pool, (2_000, 0)).await;
+    client::swap(
+        &mut ctx,
+        &legacy_pool,
+        &user,
+        TradeDirection::AtoB,
+        Swap::new(1_000, 0),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await,
+        100
+    );
+
+    client::initialize_protocol_fees_vaults(&mut ctx, &legacy_pool, &pool.admin.admin)
+        .await
+        .unwrap();
+    let swap_pool = state::get_pool(&mut ctx, &pool).await;
+    assert_eq!(
+        swap_pool.token_a_protocol_fees_vault,
+        pool.token_a_protocol_fees_vault
+    );
+    assert_eq!(
+        swap_pool.token_b_protocol_fees_vault,
+        pool.token_b_protocol_fees_vault
+    );
+
+    // swaps now split the owner fee with the protocol
+    client::swap(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        Swap::new(1_000, 0),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await,
+        150
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.token_a_protocol_fees_vault).await,
+        50
+    );
+
+    assert_eq!(
+        client::initialize_protocol_fees_vaults(&mut ctx, &pool, &pool.admin.admin)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        hyperplane_error!(SwapError::ProtocolFeesVaultsAlreadyInitialized)
+    );
+}
+
+#[tokio::test]
+pub async fn test_security_initialize_protocol_fees_vaults_wrong_payer() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+    let pool = new_pool_with_protocol_fee(&mut ctx).await;
+    let legacy_pool = into_pool_without_protocol_fees_vaults(&mut ctx, &pool).await;
+
+    let payer = new_keypair(&mut ctx, Sol::one()).await;
+    assert_eq!(
+        client::initialize_protocol_fees_vaults(&mut ctx, &legacy_pool, &payer)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        hyperplane_error!(SwapError::InvalidProtocolAuthority)
+    );
+}
+
+#[tokio::test]
+pub async fn test_close_pool_without_protocol_fees_vaults() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+    let pool = new_pool_with_protocol_fee(&mut ctx).await;
+    let legacy_pool = into_pool_without_protocol_fees_vaults(&mut ctx, &pool).await;
+
+    client::withdraw(
+        &mut ctx,
+        &legacy_pool,
+        &pool.admin.clone().into(),
+        Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64, 0, 0),
+    )
+    .await
+    .unwrap();
+    client::close_pool(&mut ctx, &legacy_pool).await.unwrap();
+}
+
+#[tokio::test]
+pub async fn test_withdraw_protocol_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+    let pool = new_pool_with_protocol_fee(&mut ctx).await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 0)).await;
+    client::swap(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        Swap::new(1_000, 0),
+    )
+    .await
+    .unwrap();
+
+    let protocol_authority = ctx.global_config_owner.clone();
+    let protocol_authority_ata = token_operations::create_token_account(
+        &mut ctx,
+        &pool.token_a_token_program,
+        &pool.token_a_mint,
+        &protocol_authority.pubkey(),
+    )
+    .await
+    .unwrap();
+
+    client::withdraw_protocol_fees(
+        &mut ctx,
+        &pool,
+        &protocol_authority,
+        &protocol_authority_ata,
+        AorB::A,
+        WithdrawProtocolFees::new(u64::MAX),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.token_a_protocol_fees_vault).await,
+        0
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &protocol_authority_ata).await,
+        50
+    );
+    // the admin share is untouched
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await,
+        50
+    );
+}
+
+#[tokio::test]
+pub async fn test_update_protocol_authority() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+    let pool = new_pool_with_protocol_fee(&mut ctx).await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 0)).await;
+    client::swap(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        Swap::new(1_000, 0),
+    )
+    .await
+    .unwrap();
+
+    let owner = ctx.global_config_owner.clone();
+    let new_protocol_authority = new_keypair(&mut ctx, Sol::one()).await;
+    client::update_global_config(
+        &mut ctx,
+        &owner,
+        UpdateGlobalConfig::new(
+            UpdateGlobalConfigMode::ProtocolAuthority,
+            UpdateGlobalConfigValue::Pubkey(new_protocol_authority.pubkey()),
+        ),
+    )
+    .await
+    .unwrap();
+
+    let global_config = state::get_global_config(&mut ctx).await;
+    assert_eq!(
+        global_config.protocol_authority,
+        new_protocol_authority.pubkey()
+    );
+    assert_eq!(global_config.owner, owner.pubkey());
+
+    let new_protocol_authority_ata = token_operations::create_token_account(
+        &mut ctx,
+        &pool.token_a_token_program,
+        &pool.token_a_mint,
+        &new_protocol_authority.pubkey(),
+    )
+    .await
+    .unwrap();
+    client::withdraw_protocol_fees(
+        &mut ctx,
+        &pool,
+        &new_protocol_authority,
+        &new_protocol_authority_ata,
+        AorB::A,
+        WithdrawProtocolFees::new(20),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.token_a_protocol_fees_vault).await,
+        30
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &new_protocol_authority_ata).await,
+        20
+    );
+}
+
+#[tokio::test]
+pub async fn test_security_withdraw_protocol_fees_wrong_authority() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+    let pool = new_pool_with_protocol_fee(&mut ctx).await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 0)).await;
+    client::swap(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        Swap::new(1_000, 0),
+    )
+    .await
+    .unwrap();
+
+    // the pool admin is not the protocol authority
+    assert_eq!(
+        client::withdraw_protocol_fees(
+            &mut ctx,
+            &pool,
+            &pool.admin.admin,
+            &pool.admin.token_a_ata,
+            AorB::A,
+            WithdrawProtocolFees::new(u64::MAX),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::InvalidProtocolAuthority)
+    );
+}
+
+#[tokio::test]
+pub async fn test_security_close_pool_with_protocol_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+    let pool = new_pool_with_protocol_fee(&mut ctx).await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 0)).await;
+    client::swap(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        Swap::new(1_000, 0),
+    )
+    .await
+    .unwrap();
+
+    // drain the pool liquidity, leaving only the protocol fees behind
+    client::withdraw(
+        &mut ctx,
+        &pool,
+        &pool.admin.clone().into(),
+        Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64, 0, 0),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        client::close_pool(&mut ctx, &pool)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        hyperplane_error!(SwapError::PoolNotEmpty)
+    );
+}
@@ -0,0 +1,440 @@
+mod common;
+
+use anchor_lang::prelude::{Clock, Pubkey};
+use common::{client, runner};
+use hyperplane::{
+    curve::{calculator::TradeDirection, fees::Fees},
+    ix::{Deposit, Swap, SwapExactOut, UpdateGlobalConfig, Withdraw},
+    state::{UpdateGlobalConfigMode, UpdateGlobalConfigValue},
+    CurveUserParameters, InitialSupply,
+};
+use hyperplane_quote::{PoolAccounts, PoolQuoter};
+use solana_program_test::tokio::{self};
+use test_case::test_case;
+
+use crate::common::{
+    fixtures, setup, state, token_operations,
+    types::{PoolUserAccounts, SwapPairSpec, SwapPoolAccounts, TestContext, TokenSpec},
+};
+
+async fn account_data(ctx: &mut TestContext, address: &Pubkey) -> Vec<u8> {
+    ctx.context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data
+}
+
+async fn quoter(ctx: &mut TestContext, pool: &SwapPoolAccounts) -> PoolQuoter {
+    let swap_pool = state::get_pool(ctx, pool).await;
+    let global_config = state::get_global_config(ctx).await;
+    let swap_curve = account_data(ctx, &pool.curve).await;
+    let token_a_mint = account_data(ctx, &pool.token_a_mint).await;
+    let token_b_mint = account_data(ctx, &pool.token_b_mint).await;
+    let token_a_vault_amount = token_operations::balance(ctx, &pool.token_a_vault).await;
+    let token_b_vault_amount = token_operations::balance(ctx, &pool.token_b_vault).await;
+    let pool_token_supply = token_operations::supply(ctx, &pool.pool_token_mint).await;
+    let clock: Clock = ctx.context.banks_client.get_sysvar().await.unwrap();
+    PoolQuoter::new(
+        PoolAccounts {
+            pool: &swap_pool,
+            global_config: &global_config,
+            swap_curve: &swap_curve,
+            price_feed: None,
+            rate_provider: None,
+            token_a_mint: &token_a_mint,
+            token_b_mint: &token_b_mint,
+            token_a_vault_amount,
+            token_b_vault_amount,
+            pool_token_supply,
+        },
+        &clock,
+    )
+    .unwrap()
+}
+
+async fn balances(ctx: &mut TestContext, accounts: &[Pubkey]) -> Vec<u64> {
+    let mut balances = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        balances.push(token_operations::balance(ctx, account).await);
+    }
+    balances
+}
+
+fn fees() -> Fees {
+    Fees {
+        trade_fee_numerator: 25,
+        trade_fee_denominator: 10_000,
+        owner_trade_fee_numerator: 5,
+        owner_trade_fee_denominator: 10_000,
+        owner_withdraw_fee_numerator: 1,
+        owner_withdraw_fee_denominator: 1_000,
+        host_fee_numerator: 20,
+        host_fee_denominator: 100,
+    }
+}
+
+#[test_case(CurveUserParameters::ConstantProduct, 0, 0, TradeDirection::AtoB, false; "constant product a to b")]
+#[test_case(CurveUserParameters::ConstantProduct, 0, 0, TradeDirection::BtoA, true; "constant product b to a with host fees")]
+#[test_case(CurveUserParameters::ConstantProduct, 100, 250, TradeDirection::AtoB, true; "constant product a to b with transfer fees and host fees")]
+#[test_case(CurveUserParameters::ConstantProduct, 100, 250, TradeDirection::BtoA, false; "constant product b to a with transfer fees")]
+#[test_case(CurveUserParameters::Stable { amp: 100 }, 100, 250, TradeDirection::AtoB, true; "stable a to b with transfer fees and host fees")]
+#[test_case(CurveUserParameters::Stable { amp: 100 }, 0, 250, TradeDirection::BtoA, false; "stable b to a with transfer fees")]
+#[tokio::test]
+pub async fn test_swap_quote_matches_swap(
+    curve_parameters: CurveUserParameters,
+    token_a_transfer_fee_bps: u16,
+    token_b_transfer_fee_bps: u16,
+    trade_direction: TradeDirection,
+    with_host_fee: bool,
+) {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+    let owner = ctx.global_config_owner.clone();
+    client::update_global_config(
+        &mut ctx,
+        &owner,
+        UpdateGlobalConfig::new(
+            UpdateGlobalConfigMode::ProtocolFee,
+            UpdateGlobalConfigValue::Fee {
+                numerator: 1,
+                denominator: 4,
+            },
+        ),
+    )
+    .await
+    .unwrap();
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        fees(),
+        InitialSupply::new(1_000_000_000, 1_000_000_000),
+        SwapPairSpec::new(
+            TokenSpec::transfer_fees(token_a_transfer_fee_bps),
+            TokenSpec::transfer_fees(token_b_transfer_fee_bps),
+        ),
+        curve_parameters,
+    )
+    .await;
+
+    let amount_in = 12_345_678;
+    let user = setup::new_pool_user(&mut ctx, &pool, (amount_in, amount_in)).await;
+    let host_fees = setup::new_pool_user(&mut ctx, &pool, (0, 0)).await;
+    let (
+        user_source,
+        user_destination,
+        source_vault,
+        destination_vault,
+        source_fees_vault,
+        host_source,
+        source_protocol_fees_vault,
+    ) = match trade_direction {
+        TradeDirection::AtoB => (
+            user.token_a_ata,
+            user.token_b_ata,
+            pool.token_a_vault,
+            pool.token_b_vault,
+            pool.token_a_fees_vault,
+            host_fees.token_a_ata,
+            pool.token_a_protocol_fees_vault,
+        ),
+        TradeDirection::BtoA => (
+            user.token_b_ata,
+            user.token_a_ata,
+            pool.token_b_vault,
+            pool.token_a_vault,
+            pool.token_b_fees_vault,
+            host_fees.token_b_ata,
+            pool.token_b_protocol_fees_vault,
+        ),
+    };
+    let accounts = [
+        user_source,
+        user_destination,
+        source_vault,
+        destination_vault,
+        source_fees_vault,
+        host_source,
+        source_protocol_fees_vault,
+    ];
+
+    let quote = quoter(&mut ctx, &pool)
+        .await
+        .swap(trade_direction, amount_in, with_host_fee)
+        .unwrap();
+
+    let before = balances(&mut ctx, &accounts).await;
+    client::swap_with_host_fees(
+        &mut ctx,
+        &pool,
+        &user,
+        with_host_fee.then_some(&host_fees),
+        trade_direction,
+        Swap::new(amount_in, quote.destination_amount),
+    )
+    .await
+    .unwrap();
+    let after = balances(&mut ctx, &accounts).await;
+
+    assert_eq!(before[0] - after[0], quote.source_amount);
+    assert_eq!(after[1] - before[1], quote.destination_amount);
+    assert_eq!(before[3] - after[3], quote.destination_amount_from_vault);
+    assert_eq!(
+        quote.source_amount,
+        quote.source_amount_to_vault + quote.owner_fee + quote.host_fee + quote.protocol_fee
+    );
+    assert_eq!(after[2] - before[2], quote.source_amount_received);
+    assert_eq!(after[4] - before[4], quote.owner_fee_received);
+    assert_eq!(after[5] - before[5], quote.host_fee_received);
+    assert_eq!(after[6] - before[6], quote.protocol_fee_received);
+    if token_a_transfer_fee_bps == 0 && token_b_transfer_fee_bps == 0 {
+        assert_eq!(quote.source_amount_received, quote.source_amount_to_vault);
+        assert_eq!(quote.owner_fee_received, quote.owner_fee);
+        assert_eq!(
+            quote.destination_amount,
+            quote.destination_amount_from_vault
+        );
+    }
+    assert!(quote.owner_fee > 0);
+    assert_eq!(quote.host_fee > 0, with_host_fee);
+
+    // the protocol takes its share of the owner fee left after the host fee
+    assert!(quote.protocol_fee > 0);
+    let global_config = state::get_global_config(&mut ctx).await;
+    assert_eq!(
+        u128::from(quote.protocol_fee_received),
+        global_config
+            .protocol_fee(u128::from(
+                quote.owner_fee_received + quote.protocol_fee_received
+            ))
+            .unwrap()
+    );
+}
+
+#[test_case(CurveUserParameters::ConstantProduct, 0, 0, TradeDirection::AtoB, false; "constant product a to b")]
+#[test_case(CurveUserParameters::ConstantProduct, 100, 250, TradeDirection::AtoB, true; "constant product a to b with transfer fees and host fees")]
+#[test_case(CurveUserParameters::ConstantProduct, 100, 250, TradeDirection::BtoA, false; "constant product b to a with transfer fees")]
+#[test_case(CurveUserParameters::Stable { amp: 100 }, 0, 250, TradeDirection::BtoA, true; "stable b to a with transfer fees and host fees")]
+#[tokio::test]
+pub async fn test_swap_exact_out_quote_matches_swap_exact_out(
+    curve_parameters: CurveUserParameters,
+    token_a_transfer_fee_bps: u16,
+    token_b_transfer_fee_bps: u16,
+    trade_direction: TradeDirection,
+    with_host_fee: bool,
+) {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+    let owner = ctx.global_config_owner.clone();
+    client::update_global_config(
+        &mut ctx,
+        &owner,
+        UpdateGlobalConfig::new(
+            UpdateGlobalConfigMode::ProtocolFee,
+            UpdateGlobalConfigValue::Fee {
+                numerator: 1,
+                denominator: 4,
+            },
+        ),
+    )
+    .await
+    .unwrap();
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        fees(),
+        InitialSupply::new(1_000_000_000, 1_000_000_000),
+        SwapPairSpec::new(
+            TokenSpec::transfer_fees(token_a_transfer_fee_bps),
+            TokenSpec::transfer_fees(token_b_transfer_fee_bps),
+        ),
+        curve_parameters,
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (12_345_678, 12_345_678)).await;
+    let host_fees = setup::new_pool_user(&mut ctx, &pool, (0, 0)).await;
+    let accounts = match trade_direction {
+        TradeDirection::AtoB => [
+            user.token_a_ata,
+            user.token_b_ata,
+            pool.token_a_vault,
+            pool.token_b_vault,
+            pool.token_a_fees_vault,
+            host_fees.token_a_ata,
+            pool.token_a_protocol_fees_vault,
+        ],
+        TradeDirection::BtoA => [
+            user.token_b_ata,
+            user.token_a_ata,
+            pool.token_b_vault,
+            pool.token_a_vault,
+            pool.token_b_fees_vault,
+            host_fees.token_b_ata,
+            pool.token_b_protocol_fees_vault,
+        ],
+    };
+
+    let amount_out = 1_234_567;
+    let quote = quoter(&mut ctx, &pool)
+        .await
+        .swap_exact_out(trade_direction, amount_out, with_host_fee)
+        .unwrap();
+
+    let before = balances(&mut ctx, &accounts).await;
+    client::swap_exact_out_with_host_fees(
+        &mut ctx,
+        &pool,
+        &user,
+        with_host_fee.then_some(&host_fees),
+        trade_direction,
+        SwapExactOut::new(amount_out, quote.source_amount),
+    )
+    .await
+    .unwrap();
+    let after = balances(&mut ctx, &accounts).await;
+
+    assert_eq!(before[0] - after[0], quote.source_amount);
+    assert_eq!(after[1] - before[1], quote.destination_amount);
+    assert!(quote.destination_amount >= amount_out);
+    assert_eq!(before[3] - after[3], quote.destination_amount_from_vault);
+    assert_eq!(after[2] - before[2], quote.source_amount_received);
+    assert_eq!(after[4] - before[4], quote.owner_fee_received);
+    assert_eq!(after[5] - before[5], quote.host_fee_received);
+    assert_eq!(after[6] - before[6], quote.protocol_fee_received);
+    assert!(quote.owner_fee > 0 && quote.protocol_fee > 0);
+    assert_eq!(quote.host_fee > 0, with_host_fee);
+}
+
+#[test_case(CurveUserParameters::ConstantProduct, 0, 0; "constant product")]
+#[test_case(CurveUserParameters::ConstantProduct, 100, 250; "constant product with transfer fees")]
+#[test_case(CurveUserParameters::Stable { amp: 100 }, 100, 0; "stable with transfer fees")]
+#[tokio::test]
+pub async fn test_deposit_quote_matches_deposit(
+    curve_parameters: CurveUserParameters,
+    token_a_transfer_fee_bps: u16,
+    token_b_transfer_fee_bps: u16,
+) {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        fees(),
+        InitialSupply::new(1_000_000_000, 3_000_000_000),
+        SwapPairSpec::new(
+            TokenSpec::transfer_fees(token_a_transfer_fee_bps),
+            TokenSpec::transfer_fees(token_b_transfer_fee_bps),
+        ),
+        curve_parameters,
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (100_000_000, 300_000_000)).await;
+    let accounts = [
+        user.token_a_ata,
+        user.token_b_ata,
+        user.pool_token_ata,
+        pool.token_a_vault,
+        pool.token_b_vault,
+    ];
+
+    let pool_token_amount = 1_234_567;
+    let quote = quoter(&mut ctx, &pool)
+        .await
+        .deposit(pool_token_amount)
+        .unwrap();
+
+    let before = balances(&mut ctx, &accounts).await;
+    client::deposit(
+        &mut ctx,
+        &pool,
+        &user,
+        Deposit::new(
+            pool_token_amount,
+            quote.token_a_amount,
+            quote.token_b_amount,
+        ),
+    )
+    .await
+    .unwrap();
+    let after = balances(&mut ctx, &accounts).await;
+
+    assert_eq!(before[0] - after[0], quote.token_a_amount);
+    assert_eq!(before[1] - after[1], quote.token_b_amount);
+    assert_eq!(after[2] - before[2], quote.pool_token_amount);
+    assert_eq!(after[3] - before[3], quote.token_a_amount_received);
+    assert_eq!(after[4] - before[4], quote.token_b_amount_received);
+}
+
+#[test_case(CurveUserParameters::ConstantProduct, 0, 0; "constant product")]
+#[test_case(CurveUserParameters::ConstantProduct, 100, 250; "constant product with transfer fees")]
+#[test_case(CurveUserParameters::Stable { amp: 100 }, 0, 250; "stable with transfer fees")]
+#[tokio::test]
+pub async fn test_withdraw_quote_matches_withdraw(
+    curve_parameters: CurveUserParameters,
+    token_a_transfer_fee_bps: u16,
+    token_b_transfer_fee_bps: u16,
+) {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        fees(),
+        InitialSupply::new(1_000_000_000, 3_000_000_000),
+        SwapPairSpec::new(
+            TokenSpec::transfer_fees(token_a_transfer_fee_bps),
+            TokenSpec::transfer_fees(token_b_transfer_fee_bps),
+        ),
+        curve_parameters,
+    )
+    .await;
+
+    let lp: PoolUserAccounts = pool.admin.clone().into();
+    let accounts = [
+        lp.token_a_ata,
+        lp.token_b_ata,
+        pool.token_a_vault,
+        pool.token_b_vault,
+        pool.token_a_fees_vault,
+        pool.token_b_fees_vault,
+    ];
+
+    let pool_token_amount = token_operations::balance(&mut ctx, &lp.pool_token_ata).await / 3;
+    let quote = quoter(&mut ctx, &pool)
+        .await
+        .withdraw(pool_token_amount)
+        .unwrap();
+
+    let before = balances(&mut ctx, &accounts).await;
+    client::withdraw(
+        &mut ctx,
+        &pool,
+        &lp,
+        Withdraw::new(
+            pool_token_amount,
+            quote.token_a_amount_received,
+            quote.token_b_amount_received,
+        ),
+    )
+    .await
+    .unwrap();
+    let after = balances(&mut ctx, &accounts).await;
+
+    assert_eq!(after[0] - before[0], quote.token_a_amount_received);
+    assert_eq!(after[1] - before[1], quote.token_b_amount_received);
+    assert_eq!(
+        before[2] - after[2],
+        quote.token_a_amount + quote.token_a_fees
+    );
+    assert_eq!(
+        before[3] - after[3],
+        quote.token_b_amount + quote.token_b_fees
+    );
+    assert_eq!(after[4] - before[4], quote.token_a_fees_received);
+    assert_eq!(after[5] - before[5], quote.token_b_fees_received);
+    assert!(quote.token_a_fees > 0 && quote.token_b_fees > 0);
+}
@@ -0,0 +1,278 @@
+mod common;
+
+use common::{client, runner};
+use hyperplane::{
+    curve::{calculator::TradeDirection, fees::Fees},
+    error::SwapError,
+    ix::{RouteSwap, Swap},
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+use solana_sdk::signer::Signer;
+
+use crate::common::{
+    fixtures, setup, token_operations,
+    types::{SwapPairSpec, SwapPoolAccounts, TestContext},
+};
+
+fn fees() -> Fees {
+    Fees {
+        trade_fee_numerator: 1,
+        trade_fee_denominator: 100,
+        owner_trade_fee_numerator: 1,
+        owner_trade_fee_denominator: 100,
+        ..Default::default()
+    }
+}
+
+/// Pools of A/B and C/B, so a route from A to C trades A to B then B to A
+async fn new_route_pools(ctx: &mut TestContext) -> (SwapPoolAccounts, SwapPoolAccounts) {
+    let a_b_pool = fixtures::new_pool(
+        ctx,
+        fees(),
+        InitialSupply::new(1_000_000, 2_000_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    let c_mint = setup::kp();
+    token_operations::create_mint(ctx, &c_mint, SwapPairSpec::default().a)
+        .await
+        .unwrap();
+    let c_b_pool = fixtures::new_pool_for_mints(
+        ctx,
+        fees(),
+        InitialSupply::new(3_000_000, 2_000_000),
+        SwapPairSpec::default(),
+        &c_mint.pubkey(),
+        &a_b_pool.token_b_mint,
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    (a_b_pool, c_b_pool)
+}
+
+#[tokio::test]
+pub async fn test_route_swap_matches_sequential_swaps() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    // swap through one pair of pools with a single route swap
+    let (a_b_pool, c_b_pool) = new_route_pools(&mut ctx).await;
+    let user = setup::new_pool_user(&mut ctx, &a_b_pool, (10_000, 0)).await;
+    let user_c_ata = token_operations::create_token_account(
+        &mut ctx,
+        &c_b_pool.token_a_token_program,
+        &c_b_pool.token_a_mint,
+        &user.pubkey(),
+    )
+    .await
+    .unwrap();
+
+    client::route_swap(
+        &mut ctx,
+        &user.user,
+        &user.token_a_ata,
+        &user_c_ata,
+        &[
+            (&a_b_pool, TradeDirection::AtoB),
+            (&c_b_pool, TradeDirection::BtoA),
+        ],
+        RouteSwap::new(10_000, 1),
+    )
+    .await
+    .unwrap();
+
+    // and an identical pair of pools with two swaps
+    let (seq_a_b_pool, seq_c_b_pool) = new_route_pools(&mut ctx).await;
+    let seq_user = setup::new_pool_user(&mut ctx, &seq_a_b_pool, (10_000, 0)).await;
+    client::swap(
+        &mut ctx,
+        &seq_a_b_pool,
+        &seq_user,
+        TradeDirection::AtoB,
+        Swap::new(10_000, 1),
+    )
+    .await
+    .unwrap();
+    let intermediate_amount = token_operations::balance(&mut ctx, &seq_user.token_b_ata).await;
+    let seq_c_user = setup::new_pool_user(&mut ctx, &seq_c_b_pool, (0, intermediate_amount)).await;
+    client::swap(
+        &mut ctx,
+        &seq_c_b_pool,
+        &seq_c_user,
+        TradeDirection::BtoA,
+        Swap::new(intermediate_amount, 1),
+    )
+    .await
+    .unwrap();
+
+    let amount_out = token_operations::balance(&mut ctx, &user_c_ata).await;
+    assert!(amount_out > 0);
+    assert_eq!(
+        amount_out,
+        token_operations::balance(&mut ctx, &seq_c_user.token_a_ata).await
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.token_a_ata).await,
+        0
+    );
+    // no intermediate tokens are left with the user
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.token_b_ata).await,
+        0
+    );
+
+    for (pool, seq_pool) in [(&a_b_pool, &seq_a_b_pool), (&c_b_pool, &seq_c_b_pool)] {
+        for (vault, seq_vault) in [
+            (pool.token_a_vault, seq_pool.token_a_vault),
+            (pool.token_b_vault, seq_pool.token_b_vault),
+            (pool.token_a_fees_vault, seq_pool.token_a_fees_vault),
+            (pool.token_b_fees_vault, seq_pool.token_b_fees_vault),
+        ] {
+            assert_eq!(
+                token_operations::balance(&mut ctx, &vault).await,
+                token_operations::balance(&mut ctx, &seq_vault).await
+            );
+        }
+    }
+}
+
+#[tokio::test]
+pub async fn test_route_swap_single_hop() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let (a_b_pool, _) = new_route_pools(&mut ctx).await;
+    let user = setup::new_pool_user(&mut ctx, &a_b_pool, (10_000, 0)).await;
+
+    client::route_swap(
+        &mut ctx,
+        &user.user,
+        &user.token_a_ata,
+        &user.token_b_ata,
+        &[(&a_b_pool, TradeDirection::AtoB)],
+        RouteSwap::new(10_000, 19_000),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.token_a_ata).await,
+        0
+    );
+    assert!(token_operations::balance(&mut ctx, &user.token_b_ata).await >= 19_000);
+}
+
+#[tokio::test]
+pub async fn test_route_swap_fails_with_exceeded_slippage() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let (a_b_pool, c_b_pool) = new_route_pools(&mut ctx).await;
+    let user = setup::new_pool_user(&mut ctx, &a_b_pool, (10_000, 0)).await;
+    let user_c_ata = token_operations::create_token_account(
+        &mut ctx,
+        &c_b_pool.token_a_token_program,
+        &c_b_pool.token_a_mint,
+        &user.pubkey(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        client::route_swap(
+            &mut ctx,
+            &user.user,
+            &user.token_a_ata,
+            &user_c_ata,
+            &[
+                (&a_b_pool, TradeDirection::AtoB),
+                (&c_b_pool, TradeDirection::BtoA),
+            ],
+            RouteSwap::new(10_000, 30_000),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::ExceededSlippage)
+    );
+}
+
+#[tokio::test]
+pub async fn test_route_swap_fails_with_invalid_route() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let (a_b_pool, c_b_pool) = new_route_pools(&mut ctx).await;
+    let user = setup::new_pool_user(&mut ctx, &a_b_pool, (10_000, 0)).await;
+
+    // the same pool twice
+    {
+        assert_eq!(
+            client::route_swap(
+                &mut ctx,
+                &user.user,
+                &user.token_a_ata,
+                &user.token_b_ata,
+                &[
+                    (&a_b_pool, TradeDirection::AtoB),
+                    (&c_b_pool, TradeDirection::BtoA),
+                    (&c_b_pool, TradeDirection::AtoB),
+                ],
+                RouteSwap::new(10_000, 1),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            hyperplane_error!(SwapError::InvalidRoute)
+        );
+    }
+
+    // hop which does not trade the output of the previous hop
+    {
+        assert_eq!(
+            client::route_swap(
+                &mut ctx,
+                &user.user,
+                &user.token_a_ata,
+                &user.token_b_ata,
+                &[
+                    (&a_b_pool, TradeDirection::AtoB),
+                    (&c_b_pool, TradeDirection::AtoB),
+                ],
+                RouteSwap::new(10_000, 1),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            hyperplane_error!(SwapError::IncorrectSwapAccount)
+        );
+    }
+
+    // too many hops
+    {
+        assert_eq!(
+            client::route_swap(
+                &mut ctx,
+                &user.user,
+                &user.token_a_ata,
+                &user.token_b_ata,
+                &[
+                    (&a_b_pool, TradeDirection::AtoB),
+                    (&c_b_pool, TradeDirection::BtoA),
+                    (&c_b_pool, TradeDirection::AtoB),
+                    (&a_b_pool, TradeDirection::BtoA),
+                    (&a_b_pool, TradeDirection::AtoB),
+                ],
+                RouteSwap::new(10_000, 1),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            hyperplane_error!(SwapError::InvalidRoute)
+        );
+    }
+}
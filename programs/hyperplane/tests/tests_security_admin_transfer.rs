@@ -0,0 +1,116 @@
+mod common;
+
+use anchor_lang::prelude::ErrorCode;
+use common::{client, runner};
+use hyperplane::{curve::fees::Fees, error::SwapError, CurveUserParameters};
+use solana_program_test::tokio::{self};
+use solana_sdk::signature::Signer;
+
+use crate::common::{
+    fixtures,
+    fixtures::Sol,
+    setup::{default_supply, new_keypair},
+    types::SwapPairSpec,
+};
+
+#[tokio::test]
+pub async fn test_security_propose_new_admin() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        default_supply(),
+        SwapPairSpec::default(),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    // wrong admin
+    {
+        let mut cloned_pool = pool.clone();
+        cloned_pool.admin.admin = new_keypair(&mut ctx, Sol::one()).await;
+        let new_admin = cloned_pool.admin.pubkey();
+
+        assert_eq!(
+            client::propose_new_admin(&mut ctx, &cloned_pool, &new_admin)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            anchor_error!(ErrorCode::ConstraintHasOne)
+        );
+    }
+}
+
+#[tokio::test]
+pub async fn test_security_accept_admin() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        default_supply(),
+        SwapPairSpec::default(),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    let new_admin = new_keypair(&mut ctx, Sol::one()).await;
+
+    // nothing proposed yet
+    {
+        assert_eq!(
+            client::accept_admin(&mut ctx, &pool, &new_admin)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            hyperplane_error!(SwapError::InvalidPendingAdmin)
+        );
+    }
+
+    client::propose_new_admin(&mut ctx, &pool, &new_admin.pubkey())
+        .await
+        .unwrap();
+
+    // wrong pending admin
+    {
+        let wrong_admin = new_keypair(&mut ctx, Sol::one()).await;
+
+        assert_eq!(
+            client::accept_admin(&mut ctx, &pool, &wrong_admin)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            hyperplane_error!(SwapError::InvalidPendingAdmin)
+        );
+    }
+
+    // current admin cannot accept on behalf of the pending admin
+    {
+        assert_eq!(
+            client::accept_admin(&mut ctx, &pool, &pool.admin.admin)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            hyperplane_error!(SwapError::InvalidPendingAdmin)
+        );
+    }
+
+    client::accept_admin(&mut ctx, &pool, &new_admin)
+        .await
+        .unwrap();
+
+    // pending admin is cleared once accepted
+    {
+        let newer_admin = new_keypair(&mut ctx, Sol::one()).await;
+        assert_eq!(
+            client::accept_admin(&mut ctx, &pool, &newer_admin)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            hyperplane_error!(SwapError::InvalidPendingAdmin)
+        );
+    }
+}
@@ -0,0 +1,170 @@
+mod common;
+
+use anchor_lang::{prelude::ErrorCode, Id};
+use anchor_spl::token_2022::Token2022;
+use common::{client, runner};
+use hyperplane::{
+    curve::{calculator::INITIAL_SWAP_POOL_AMOUNT, fees::Fees},
+    error::SwapError,
+    ix::Withdraw,
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+use solana_sdk::signature::Signer;
+
+use crate::common::{
+    fixtures,
+    fixtures::Sol,
+    setup::{kp, new_keypair},
+    token_operations::create_token_account,
+    types::SwapPairSpec,
+    utils,
+};
+
+#[tokio::test]
+pub async fn test_security_close_pool() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees {
+            owner_withdraw_fee_numerator: 1,
+            owner_withdraw_fee_denominator: 100,
+            ..Default::default()
+        },
+        InitialSupply::new(100, 100),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    client::withdraw(
+        &mut ctx,
+        &pool,
+        &pool.admin.clone().into(),
+        Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64, 0, 0),
+    )
+    .await
+    .unwrap();
+
+    // wrong admin
+    {
+        let mut cloned_pool = pool.clone();
+        cloned_pool.admin.admin = new_keypair(&mut ctx, Sol::one()).await;
+
+        assert_eq!(
+            client::close_pool(&mut ctx, &cloned_pool)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            anchor_error!(ErrorCode::ConstraintHasOne)
+        );
+    }
+
+    // wrong swap_curve
+    {
+        let mut cloned_pool = pool.clone();
+        cloned_pool.curve = kp().pubkey();
+
+        utils::clone_account(&mut ctx, &pool.curve, &cloned_pool.curve).await;
+
+        assert_eq!(
+            client::close_pool(&mut ctx, &cloned_pool)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            anchor_error!(ErrorCode::ConstraintHasOne)
+        );
+    }
+
+    // wrong pool_authority
+    {
+        let mut cloned_pool = pool.clone();
+        cloned_pool.authority = kp().pubkey();
+
+        assert_eq!(
+            client::close_pool(&mut ctx, &cloned_pool)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            hyperplane_error!(SwapError::InvalidProgramAddress)
+        );
+    }
+
+    // wrong token_a_vault
+    {
+        let mut cloned_pool = pool.clone();
+        cloned_pool.token_a_vault = kp().pubkey();
+
+        utils::clone_account(&mut ctx, &pool.token_a_vault, &cloned_pool.token_a_vault).await;
+
+        assert_eq!(
+            client::close_pool(&mut ctx, &cloned_pool)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            hyperplane_error!(SwapError::IncorrectSwapAccount)
+        );
+    }
+
+    // wrong token_b_fees_vault
+    {
+        let mut cloned_pool = pool.clone();
+        cloned_pool.token_b_fees_vault = kp().pubkey();
+
+        utils::clone_account(
+            &mut ctx,
+            &pool.token_b_fees_vault,
+            &cloned_pool.token_b_fees_vault,
+        )
+        .await;
+
+        assert_eq!(
+            client::close_pool(&mut ctx, &cloned_pool)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            hyperplane_error!(SwapError::IncorrectFeeAccount)
+        );
+    }
+
+    // wrong admin_token_a_ata authority
+    {
+        let mut cloned_pool = pool.clone();
+        let wrong_authority = kp();
+
+        cloned_pool.admin.token_a_ata = create_token_account(
+            &mut ctx,
+            &pool.token_a_token_program,
+            &pool.token_a_mint,
+            &wrong_authority.pubkey(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            client::close_pool(&mut ctx, &cloned_pool)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            anchor_error!(ErrorCode::ConstraintTokenOwner)
+        );
+    }
+
+    // wrong pool_token_program
+    {
+        let mut cloned_pool = pool.clone();
+        cloned_pool.pool_token_program = Token2022::id();
+
+        assert_eq!(
+            client::close_pool(&mut ctx, &cloned_pool)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            anchor_error!(ErrorCode::ConstraintMintTokenProgram)
+        );
+    }
+
+    client::close_pool(&mut ctx, &pool).await.unwrap();
+}
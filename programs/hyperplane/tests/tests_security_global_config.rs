@@ -0,0 +1,197 @@
+mod common;
+
+use anchor_lang::prelude::ErrorCode;
+use common::{client, runner};
+use hyperplane::{
+    curve::fees::Fees,
+    error::SwapError,
+    ix::{InitializeGlobalConfig, UpdateGlobalConfig},
+    state::{UpdateGlobalConfigMode, UpdateGlobalConfigValue},
+};
+use solana_program_test::tokio::{self};
+use solana_sdk::signer::Signer;
+
+use crate::common::{fixtures::Sol, setup::new_keypair, state};
+
+#[tokio::test]
+pub async fn test_security_initialize_global_config() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    // the global config is a single PDA, it cannot be re-initialized to take it over
+    let new_owner = new_keypair(&mut ctx, Sol::one()).await;
+    client::initialize_global_config(
+        &mut ctx,
+        &new_owner,
+        InitializeGlobalConfig::new(
+            new_owner.pubkey(),
+            Fees::default(),
+            runner::all_curve_types(),
+            vec![],
+        ),
+    )
+    .await
+    .unwrap_err();
+
+    let global_config = state::get_global_config(&mut ctx).await;
+    assert_eq!(global_config.owner, ctx.global_config_owner.pubkey());
+}
+
+#[tokio::test]
+pub async fn test_security_initialize_global_config_not_upgrade_authority() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start_without_global_config(program).await;
+
+    // only the upgrade authority can initialize the global config, so it cannot be front-run
+    let new_owner = new_keypair(&mut ctx, Sol::one()).await;
+    assert_eq!(
+        client::initialize_global_config(
+            &mut ctx,
+            &new_owner,
+            InitializeGlobalConfig::new(
+                new_owner.pubkey(),
+                Fees::default(),
+                runner::all_curve_types(),
+                vec![],
+            ),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::InvalidUpgradeAuthority)
+    );
+
+    // the upgrade authority hands the global config to an owner that does not sign
+    let upgrade_authority = ctx.global_config_owner.clone();
+    client::initialize_global_config(
+        &mut ctx,
+        &upgrade_authority,
+        InitializeGlobalConfig::new(
+            new_owner.pubkey(),
+            Fees::default(),
+            runner::all_curve_types(),
+            vec![],
+        ),
+    )
+    .await
+    .unwrap();
+    let global_config = state::get_global_config(&mut ctx).await;
+    assert_eq!(global_config.owner, new_owner.pubkey());
+    assert_eq!(global_config.protocol_authority, new_owner.pubkey());
+}
+
+#[tokio::test]
+pub async fn test_security_update_global_config() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+    let owner = ctx.global_config_owner.clone();
+
+    // wrong owner
+    {
+        let wrong_owner = new_keypair(&mut ctx, Sol::one()).await;
+
+        assert_eq!(
+            client::update_global_config(
+                &mut ctx,
+                &wrong_owner,
+                UpdateGlobalConfig::new(
+                    UpdateGlobalConfigMode::Owner,
+                    UpdateGlobalConfigValue::Pubkey(wrong_owner.pubkey()),
+                ),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            anchor_error!(ErrorCode::ConstraintHasOne)
+        );
+    }
+
+    // wrong owner updating min fees
+    {
+        let wrong_owner = new_keypair(&mut ctx, Sol::one()).await;
+
+        assert_eq!(
+            client::update_global_config(
+                &mut ctx,
+                &wrong_owner,
+                UpdateGlobalConfig::new(
+                    UpdateGlobalConfigMode::MinTradeFee,
+                    UpdateGlobalConfigValue::Fee {
+                        numerator: 0,
+                        denominator: 0,
+                    },
+                ),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            anchor_error!(ErrorCode::ConstraintHasOne)
+        );
+    }
+
+    // invalid min fee
+    {
+        assert_eq!(
+            client::update_global_config(
+                &mut ctx,
+                &owner,
+                UpdateGlobalConfig::new(
+                    UpdateGlobalConfigMode::MinOwnerTradeFee,
+                    UpdateGlobalConfigValue::Fee {
+                        numerator: 2,
+                        denominator: 1,
+                    },
+                ),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            hyperplane_error!(SwapError::InvalidFee)
+        );
+    }
+
+    // unknown curve type
+    {
+        assert_eq!(
+            client::update_global_config(
+                &mut ctx,
+                &owner,
+                UpdateGlobalConfig::new(
+                    UpdateGlobalConfigMode::ValidCurveTypes,
+                    UpdateGlobalConfigValue::CurveTypes(vec![1, 200]),
+                ),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            hyperplane_error!(SwapError::UnsupportedCurveType)
+        );
+    }
+
+    // unknown token extension
+    {
+        assert_eq!(
+            client::update_global_config(
+                &mut ctx,
+                &owner,
+                UpdateGlobalConfig::new(
+                    UpdateGlobalConfigMode::BlockedTokenExtensions,
+                    UpdateGlobalConfigValue::TokenExtensions(vec![u16::MAX]),
+                ),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            hyperplane_error!(SwapError::InvalidTokenExtension)
+        );
+    }
+
+    let global_config = state::get_global_config(&mut ctx).await;
+    assert_eq!(global_config.owner, owner.pubkey());
+    assert_eq!(global_config.min_fees, Fees::default());
+    assert_eq!(
+        global_config.valid_curve_types().collect::<Vec<_>>(),
+        runner::all_curve_types()
+    );
+    assert_eq!(global_config.blocked_token_extensions().count(), 0);
+}
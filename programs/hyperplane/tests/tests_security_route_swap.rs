@@ -0,0 +1,307 @@
+mod common;
+
+use anchor_lang::prelude::ErrorCode;
+use common::{client, runner};
+use hyperplane::{
+    curve::{calculator::TradeDirection, fees::Fees},
+    error::SwapError,
+    ix::{RouteSwap, UpdatePoolConfig},
+    state::{UpdatePoolConfigMode, UpdatePoolConfigValue},
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+use solana_sdk::signature::Signer;
+
+use crate::common::{
+    fixtures,
+    fixtures::Sol,
+    setup,
+    setup::{kp, new_keypair},
+    token_operations,
+    types::SwapPairSpec,
+    utils,
+};
+
+#[tokio::test]
+pub async fn test_security_route_swap() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let fees = Fees {
+        trade_fee_numerator: 1,
+        trade_fee_denominator: 100,
+        owner_trade_fee_numerator: 1,
+        owner_trade_fee_denominator: 100,
+        ..Default::default()
+    };
+    let a_b_pool = fixtures::new_pool(
+        &mut ctx,
+        fees,
+        InitialSupply::new(1_000, 1_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+    let c_mint = kp();
+    token_operations::create_mint(&mut ctx, &c_mint, SwapPairSpec::default().a)
+        .await
+        .unwrap();
+    let c_b_pool = fixtures::new_pool_for_mints(
+        &mut ctx,
+        fees,
+        InitialSupply::new(1_000, 1_000),
+        SwapPairSpec::default(),
+        &c_mint.pubkey(),
+        &a_b_pool.token_b_mint,
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &a_b_pool, (100, 0)).await;
+    let user_c_ata = token_operations::create_token_account(
+        &mut ctx,
+        &c_b_pool.token_a_token_program,
+        &c_b_pool.token_a_mint,
+        &user.pubkey(),
+    )
+    .await
+    .unwrap();
+    let route_swap = RouteSwap::new(100, 1);
+
+    // wrong signer
+    {
+        let wrong_user = new_keypair(&mut ctx, Sol::one()).await;
+
+        assert_eq!(
+            client::route_swap(
+                &mut ctx,
+                &wrong_user,
+                &user.token_a_ata,
+                &user_c_ata,
+                &[
+                    (&a_b_pool, TradeDirection::AtoB),
+                    (&c_b_pool, TradeDirection::BtoA),
+                ],
+                route_swap.clone(),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            anchor_error!(ErrorCode::ConstraintTokenOwner)
+        );
+    }
+
+    // wrong destination_user_ata mint
+    {
+        assert_eq!(
+            client::route_swap(
+                &mut ctx,
+                &user.user,
+                &user.token_a_ata,
+                &user.token_b_ata,
+                &[
+                    (&a_b_pool, TradeDirection::AtoB),
+                    (&c_b_pool, TradeDirection::BtoA),
+                ],
+                route_swap.clone(),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            anchor_error!(ErrorCode::ConstraintTokenMint)
+        );
+    }
+
+    // wrong swap_curve
+    {
+        let mut cloned_pool = c_b_pool.clone();
+        cloned_pool.curve = kp().pubkey();
+
+        utils::clone_account(&mut ctx, &c_b_pool.curve, &cloned_pool.curve).await;
+
+        assert_eq!(
+            client::route_swap(
+                &mut ctx,
+                &user.user,
+                &user.token_a_ata,
+                &user_c_ata,
+                &[
+                    (&a_b_pool, TradeDirection::AtoB),
+                    (&cloned_pool, TradeDirection::BtoA),
+                ],
+                route_swap.clone(),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            anchor_error!(ErrorCode::ConstraintHasOne)
+        );
+    }
+
+    // wrong pool_authority
+    {
+        let mut cloned_pool = c_b_pool.clone();
+        cloned_pool.authority = kp().pubkey();
+
+        assert_eq!(
+            client::route_swap(
+                &mut ctx,
+                &user.user,
+                &user.token_a_ata,
+                &user_c_ata,
+                &[
+                    (&a_b_pool, TradeDirection::AtoB),
+                    (&cloned_pool, TradeDirection::BtoA),
+                ],
+                route_swap.clone(),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            hyperplane_error!(SwapError::InvalidProgramAddress)
+        );
+    }
+
+    // wrong intermediate source vault
+    {
+        let mut cloned_pool = c_b_pool.clone();
+        cloned_pool.token_b_vault = kp().pubkey();
+
+        utils::clone_account(
+            &mut ctx,
+            &c_b_pool.token_b_vault,
+            &cloned_pool.token_b_vault,
+        )
+        .await;
+
+        assert_eq!(
+            client::route_swap(
+                &mut ctx,
+                &user.user,
+                &user.token_a_ata,
+                &user_c_ata,
+                &[
+                    (&a_b_pool, TradeDirection::AtoB),
+                    (&cloned_pool, TradeDirection::BtoA),
+                ],
+                route_swap.clone(),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            hyperplane_error!(SwapError::IncorrectSwapAccount)
+        );
+    }
+
+    // wrong destination vault
+    {
+        let mut cloned_pool = a_b_pool.clone();
+        cloned_pool.token_b_vault = c_b_pool.token_b_vault;
+
+        assert_eq!(
+            client::route_swap(
+                &mut ctx,
+                &user.user,
+                &user.token_a_ata,
+                &user_c_ata,
+                &[
+                    (&cloned_pool, TradeDirection::AtoB),
+                    (&c_b_pool, TradeDirection::BtoA),
+                ],
+                route_swap.clone(),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            hyperplane_error!(SwapError::IncorrectSwapAccount)
+        );
+    }
+
+    // wrong source fees vault
+    {
+        let mut cloned_pool = a_b_pool.clone();
+        cloned_pool.token_a_fees_vault = kp().pubkey();
+
+        utils::clone_account(
+            &mut ctx,
+            &a_b_pool.token_a_fees_vault,
+            &cloned_pool.token_a_fees_vault,
+        )
+        .await;
+
+        assert_eq!(
+            client::route_swap(
+                &mut ctx,
+                &user.user,
+                &user.token_a_ata,
+                &user_c_ata,
+                &[
+                    (&cloned_pool, TradeDirection::AtoB),
+                    (&c_b_pool, TradeDirection::BtoA),
+                ],
+                route_swap.clone(),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            hyperplane_error!(SwapError::IncorrectSwapAccount)
+        );
+    }
+
+    // pool in withdrawals only mode
+    {
+        client::update_pool_config(
+            &mut ctx,
+            &c_b_pool,
+            UpdatePoolConfig::new(
+                UpdatePoolConfigMode::WithdrawalsOnly,
+                UpdatePoolConfigValue::Bool(true),
+            ),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            client::route_swap(
+                &mut ctx,
+                &user.user,
+                &user.token_a_ata,
+                &user_c_ata,
+                &[
+                    (&a_b_pool, TradeDirection::AtoB),
+                    (&c_b_pool, TradeDirection::BtoA),
+                ],
+                route_swap.clone(),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            hyperplane_error!(SwapError::WithdrawalsOnlyMode)
+        );
+
+        client::update_pool_config(
+            &mut ctx,
+            &c_b_pool,
+            UpdatePoolConfig::new(
+                UpdatePoolConfigMode::WithdrawalsOnly,
+                UpdatePoolConfigValue::Bool(false),
+            ),
+        )
+        .await
+        .unwrap();
+    }
+
+    client::route_swap(
+        &mut ctx,
+        &user.user,
+        &user.token_a_ata,
+        &user_c_ata,
+        &[
+            (&a_b_pool, TradeDirection::AtoB),
+            (&c_b_pool, TradeDirection::BtoA),
+        ],
+        route_swap,
+    )
+    .await
+    .unwrap();
+}
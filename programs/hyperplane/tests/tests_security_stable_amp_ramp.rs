@@ -0,0 +1,157 @@
+mod common;
+
+use anchor_lang::prelude::ErrorCode;
+use common::{client, runner};
+use hyperplane::{
+    curve::{fees::Fees, stable::MIN_RAMP_DURATION},
+    error::SwapError,
+    CurveUserParameters,
+};
+use solana_program_test::tokio::{self};
+
+use crate::common::{
+    fixtures,
+    fixtures::Sol,
+    setup::{default_supply, new_keypair},
+    types::SwapPairSpec,
+    utils::set_unix_timestamp,
+};
+
+#[tokio::test]
+pub async fn test_security_ramp_amp() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        default_supply(),
+        SwapPairSpec::default(),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+    set_unix_timestamp(&mut ctx, 1_000).await;
+    let ramp_end_ts = 1_000 + MIN_RAMP_DURATION;
+
+    // wrong admin
+    {
+        let mut cloned_pool = pool.clone();
+        cloned_pool.admin.admin = new_keypair(&mut ctx, Sol::one()).await;
+
+        assert_eq!(
+            client::ramp_amp(&mut ctx, &cloned_pool, 200, ramp_end_ts)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            anchor_error!(ErrorCode::ConstraintHasOne)
+        );
+    }
+
+    // ramp shorter than the minimum duration
+    {
+        assert_eq!(
+            client::ramp_amp(&mut ctx, &pool, 200, ramp_end_ts - 1)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            hyperplane_error!(SwapError::InvalidAmpRamp)
+        );
+    }
+
+    // target amp more than 10x the current amp
+    {
+        assert_eq!(
+            client::ramp_amp(&mut ctx, &pool, 1_001, ramp_end_ts)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            hyperplane_error!(SwapError::InvalidAmpRamp)
+        );
+    }
+
+    // target amp less than 1/10th the current amp
+    {
+        assert_eq!(
+            client::ramp_amp(&mut ctx, &pool, 9, ramp_end_ts)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            hyperplane_error!(SwapError::InvalidAmpRamp)
+        );
+    }
+
+    // ramp starts less than the minimum duration after the previous ramp
+    {
+        client::ramp_amp(&mut ctx, &pool, 200, ramp_end_ts)
+            .await
+            .unwrap();
+        set_unix_timestamp(&mut ctx, (ramp_end_ts - 1) as i64).await;
+
+        assert_eq!(
+            client::ramp_amp(&mut ctx, &pool, 100, ramp_end_ts + MIN_RAMP_DURATION)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            hyperplane_error!(SwapError::InvalidAmpRamp)
+        );
+    }
+}
+
+#[tokio::test]
+pub async fn test_security_ramp_amp_fails_for_non_stable_curve() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        default_supply(),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    assert_eq!(
+        client::ramp_amp(&mut ctx, &pool, 200, 2 * MIN_RAMP_DURATION)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        anchor_error!(ErrorCode::AccountDiscriminatorMismatch)
+    );
+    assert_eq!(
+        client::stop_ramp_amp(&mut ctx, &pool)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        anchor_error!(ErrorCode::AccountDiscriminatorMismatch)
+    );
+}
+
+#[tokio::test]
+pub async fn test_security_stop_ramp_amp() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        default_supply(),
+        SwapPairSpec::default(),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    // wrong admin
+    {
+        let mut cloned_pool = pool.clone();
+        cloned_pool.admin.admin = new_keypair(&mut ctx, Sol::one()).await;
+
+        assert_eq!(
+            client::stop_ramp_amp(&mut ctx, &cloned_pool)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            anchor_error!(ErrorCode::ConstraintHasOne)
+        );
+    }
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use anchor_lang::prelude::ErrorCode;
+use common::{client, runner};
+use hyperplane::{
+    curve::fees::Fees,
+    ix::UpdateCurveConfig,
+    state::{UpdateCurveConfigMode, UpdateCurveConfigValue},
+    CurveUserParameters,
+};
+use solana_program_test::tokio::{self};
+
+use crate::common::{
+    fixtures,
+    fixtures::Sol,
+    setup::{default_supply, new_keypair},
+    types::SwapPairSpec,
+};
+
+#[tokio::test]
+pub async fn test_security_update_curve_config() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        default_supply(),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantPrice { token_b_price: 1 },
+    )
+    .await;
+
+    // wrong admin
+    {
+        let mut cloned_pool = pool.clone();
+        cloned_pool.admin.admin = new_keypair(&mut ctx, Sol::one()).await;
+
+        assert_eq!(
+            client::update_curve_config(
+                &mut ctx,
+                &cloned_pool,
+                UpdateCurveConfig::new(
+                    UpdateCurveConfigMode::TokenBPrice,
+                    UpdateCurveConfigValue::U64(2),
+                ),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            anchor_error!(ErrorCode::ConstraintHasOne)
+        );
+    }
+
+    // curve of another pool
+    {
+        let other_pool = fixtures::new_pool(
+            &mut ctx,
+            Fees::default(),
+            default_supply(),
+            SwapPairSpec::default(),
+            CurveUserParameters::ConstantPrice { token_b_price: 1 },
+        )
+        .await;
+        let mut cloned_pool = pool.clone();
+        cloned_pool.curve = other_pool.curve;
+
+        assert_eq!(
+            client::update_curve_config(
+                &mut ctx,
+                &cloned_pool,
+                UpdateCurveConfig::new(
+                    UpdateCurveConfigMode::TokenBPrice,
+                    UpdateCurveConfigValue::U64(2),
+                ),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            anchor_error!(ErrorCode::ConstraintHasOne)
+        );
+    }
+}
@@ -51,4 +51,28 @@ pub async fn test_security_update_swap_config() {
             anchor_error!(ErrorCode::ConstraintHasOne)
         );
     }
+
+    // wrong admin updating fees
+    {
+        let mut cloned_pool = pool.clone();
+        cloned_pool.admin.admin = new_keypair(&mut ctx, Sol::one()).await;
+
+        assert_eq!(
+            client::update_pool_config(
+                &mut ctx,
+                &cloned_pool,
+                UpdatePoolConfig::new(
+                    UpdatePoolConfigMode::TradeFee,
+                    UpdatePoolConfigValue::Fee {
+                        numerator: 1,
+                        denominator: 100,
+                    },
+                ),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            anchor_error!(ErrorCode::ConstraintHasOne)
+        );
+    }
 }
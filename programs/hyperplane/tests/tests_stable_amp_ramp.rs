@@ -0,0 +1,87 @@
+mod common;
+
+use common::{client, runner};
+use hyperplane::{
+    curve::{fees::Fees, stable::MIN_RAMP_DURATION},
+    CurveUserParameters,
+};
+use solana_program_test::tokio::{self};
+
+use crate::common::{
+    fixtures, setup::default_supply, state, types::SwapPairSpec, utils::set_unix_timestamp,
+};
+
+#[tokio::test]
+pub async fn test_successful_ramp_amp() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        default_supply(),
+        SwapPairSpec::default(),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    set_unix_timestamp(&mut ctx, 1_000).await;
+    let ramp_end_ts = 1_000 + 2 * MIN_RAMP_DURATION;
+    client::ramp_amp(&mut ctx, &pool, 200, ramp_end_ts)
+        .await
+        .unwrap();
+
+    let curve = state::get_stable_curve(&mut ctx, &pool).await;
+    assert_eq!(curve.amp, 200);
+    assert_eq!(curve.initial_amp, 100);
+    assert_eq!(curve.target_amp, 200);
+    assert_eq!(curve.ramp_start_ts, 1_000);
+    assert_eq!(curve.ramp_end_ts, ramp_end_ts);
+    assert_eq!(curve.current_amp_at(1_000).unwrap(), 100);
+    assert_eq!(
+        curve.current_amp_at(1_000 + MIN_RAMP_DURATION).unwrap(),
+        150
+    );
+    assert_eq!(curve.current_amp_at(ramp_end_ts).unwrap(), 200);
+}
+
+#[tokio::test]
+pub async fn test_successful_stop_ramp_amp() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        default_supply(),
+        SwapPairSpec::default(),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    set_unix_timestamp(&mut ctx, 1_000).await;
+    client::ramp_amp(&mut ctx, &pool, 50, 1_000 + 2 * MIN_RAMP_DURATION)
+        .await
+        .unwrap();
+
+    let stop_ts = 1_000 + MIN_RAMP_DURATION;
+    set_unix_timestamp(&mut ctx, stop_ts as i64).await;
+    client::stop_ramp_amp(&mut ctx, &pool).await.unwrap();
+
+    let curve = state::get_stable_curve(&mut ctx, &pool).await;
+    assert_eq!(curve.amp, 75);
+    assert_eq!(curve.initial_amp, 75);
+    assert_eq!(curve.target_amp, 75);
+    assert_eq!(curve.ramp_start_ts, stop_ts);
+    assert_eq!(curve.ramp_end_ts, stop_ts);
+
+    // a new ramp can start once the minimum ramp duration has passed since the stop
+    set_unix_timestamp(&mut ctx, (stop_ts + MIN_RAMP_DURATION) as i64).await;
+    client::ramp_amp(&mut ctx, &pool, 150, stop_ts + 3 * MIN_RAMP_DURATION)
+        .await
+        .unwrap();
+
+    let curve = state::get_stable_curve(&mut ctx, &pool).await;
+    assert_eq!(curve.initial_amp, 75);
+    assert_eq!(curve.target_amp, 150);
+}
@@ -0,0 +1,374 @@
+mod common;
+
+use anchor_lang::prelude::{Clock, Pubkey};
+use common::{client, runner};
+use hyperplane::{
+    curve::{
+        calculator::{TradeDirection, INITIAL_SWAP_POOL_AMOUNT},
+        fees::Fees,
+        rate_provider::{RateProvider, RateProviderType, StakePoolRate},
+    },
+    error::SwapError,
+    ix::{Swap, Withdraw},
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::{instruction::AccountMeta, signer::Signer};
+
+use crate::{
+    common::{
+        fixtures, setup, state, token_operations,
+        types::{PoolUserAccounts, SwapPairSpec, SwapPoolAccounts, TestContext},
+        utils::{set_stake_pool, set_unix_timestamp},
+    },
+    send_tx,
+};
+
+const MAX_RATE_AGE_SEC: u64 = 60;
+/// The rate may change by at most 10% per `MAX_RATE_AGE_SEC`
+const MAX_RATE_CHANGE_BPS: u64 = 1_000;
+/// 1 token B is worth 2 token A
+const TOKEN_B_RATE: u64 = 2_000_000_000_000;
+
+/// Stable pool of 2 token A per token B, balanced at a rate of 2
+async fn new_lst_pool(ctx: &mut TestContext) -> SwapPoolAccounts {
+    fixtures::new_pool(
+        ctx,
+        Fees::default(),
+        InitialSupply::new(2_000_000, 1_000_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await
+}
+
+async fn current_epoch(ctx: &mut TestContext) -> u64 {
+    let clock: Clock = ctx.context.banks_client.get_sysvar().await.unwrap();
+    clock.epoch
+}
+
+fn stake_pool(pool: &SwapPoolAccounts, epoch: u64) -> StakePoolRate {
+    StakePoolRate {
+        account_type: StakePoolRate::ACCOUNT_TYPE_STAKE_POOL,
+        pool_mint: pool.token_b_mint,
+        total_lamports: 2_000_000,
+        pool_token_supply: 1_000_000,
+        last_update_epoch: epoch,
+    }
+}
+
+async fn swap(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    user: &PoolUserAccounts,
+    rate_provider: Option<&Pubkey>,
+    trade_direction: TradeDirection,
+    swap: Swap,
+) -> Result<(), BanksClientError> {
+    let mut swap_ix = client::instructions::swap(pool, user, None, trade_direction, swap);
+    if let Some(rate_provider) = rate_provider {
+        swap_ix
+            .accounts
+            .push(AccountMeta::new_readonly(*rate_provider, false));
+    }
+    send_tx!(ctx, [swap_ix], user.user.as_ref())
+}
+
+#[tokio::test]
+pub async fn test_swap_at_authority_rate() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    set_unix_timestamp(&mut ctx, 1_000).await;
+    let pool = new_lst_pool(&mut ctx).await;
+    let rate_updater = setup::new_keypair(&mut ctx, 1_000_000_000).await;
+    client::set_rate_provider(
+        &mut ctx,
+        &pool,
+        RateProvider::Authority {
+            rate_updater: rate_updater.pubkey(),
+            token_b_rate: TOKEN_B_RATE,
+            max_rate_age_sec: MAX_RATE_AGE_SEC,
+            max_rate_change_bps: MAX_RATE_CHANGE_BPS,
+        },
+    )
+    .await
+    .unwrap();
+
+    let curve = state::get_stable_curve(&mut ctx, &pool).await;
+    assert_eq!(
+        curve.rate_provider_type,
+        u64::from(RateProviderType::Authority)
+    );
+    assert_eq!(curve.token_b_rate, TOKEN_B_RATE);
+    assert_eq!(curve.rate_updated_ts, 1_000);
+    assert_eq!(curve.max_rate_age_sec, MAX_RATE_AGE_SEC);
+    assert_eq!(curve.max_rate_change_bps, MAX_RATE_CHANGE_BPS);
+
+    // the pool is balanced at the rate, so 2 token A buy close to 1 token B
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 0)).await;
+    swap(
+        &mut ctx,
+        &pool,
+        &user,
+        None,
+        TradeDirection::AtoB,
+        Swap::new(1_000, 495),
+    )
+    .await
+    .unwrap();
+    let token_b_amount = token_operations::balance(&mut ctx, &user.token_b_ata).await;
+    assert!((495..=500).contains(&token_b_amount));
+
+    // the rate is stale
+    set_unix_timestamp(&mut ctx, 1_000 + MAX_RATE_AGE_SEC as i64 + 1).await;
+    assert_eq!(
+        swap(
+            &mut ctx,
+            &pool,
+            &user,
+            None,
+            TradeDirection::BtoA,
+            Swap::new(token_b_amount, 0),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::StaleRate)
+    );
+
+    // only the rate updater can update the rate
+    let not_rate_updater = setup::new_keypair(&mut ctx, 1_000_000_000).await;
+    assert_eq!(
+        client::update_rate(&mut ctx, &pool, &not_rate_updater, TOKEN_B_RATE)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        hyperplane_error!(SwapError::InvalidRateProvider)
+    );
+
+    // a fresh rate of 2.2 token A per token B
+    client::update_rate(&mut ctx, &pool, &rate_updater, TOKEN_B_RATE / 10 * 11)
+        .await
+        .unwrap();
+    swap(
+        &mut ctx,
+        &pool,
+        &user,
+        None,
+        TradeDirection::BtoA,
+        Swap::new(token_b_amount, 2 * token_b_amount),
+    )
+    .await
+    .unwrap();
+    assert!(token_operations::balance(&mut ctx, &user.token_a_ata).await >= 2 * token_b_amount);
+}
+
+#[tokio::test]
+pub async fn test_update_rate_rejects_large_rate_change() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    set_unix_timestamp(&mut ctx, 1_000).await;
+    let pool = new_lst_pool(&mut ctx).await;
+    let rate_updater = setup::new_keypair(&mut ctx, 1_000_000_000).await;
+    client::set_rate_provider(
+        &mut ctx,
+        &pool,
+        RateProvider::Authority {
+            rate_updater: rate_updater.pubkey(),
+            token_b_rate: TOKEN_B_RATE,
+            max_rate_age_sec: MAX_RATE_AGE_SEC,
+            max_rate_change_bps: 100,
+        },
+    )
+    .await
+    .unwrap();
+
+    // at most 1% of the previous rate per MAX_RATE_AGE_SEC since the previous update, up or down
+    set_unix_timestamp(&mut ctx, 1_000 + MAX_RATE_AGE_SEC as i64).await;
+    for token_b_rate in [TOKEN_B_RATE / 100 * 101 + 1, TOKEN_B_RATE / 100 * 99 - 1] {
+        assert_eq!(
+            client::update_rate(&mut ctx, &pool, &rate_updater, token_b_rate)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            hyperplane_error!(SwapError::RateChangeTooLarge)
+        );
+    }
+    assert_eq!(
+        state::get_stable_curve(&mut ctx, &pool).await.token_b_rate,
+        TOKEN_B_RATE
+    );
+
+    client::update_rate(&mut ctx, &pool, &rate_updater, TOKEN_B_RATE / 100 * 101)
+        .await
+        .unwrap();
+    assert_eq!(
+        state::get_stable_curve(&mut ctx, &pool).await.token_b_rate,
+        TOKEN_B_RATE / 100 * 101
+    );
+
+    // repeated updates cannot move the rate further without time passing
+    assert_eq!(
+        client::update_rate(&mut ctx, &pool, &rate_updater, TOKEN_B_RATE / 100 * 101 + 1)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        hyperplane_error!(SwapError::RateChangeTooLarge)
+    );
+}
+
+#[tokio::test]
+pub async fn test_withdraw_with_stale_rate() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    set_unix_timestamp(&mut ctx, 1_000).await;
+    let pool = new_lst_pool(&mut ctx).await;
+    let rate_updater = setup::new_keypair(&mut ctx, 1_000_000_000).await;
+    client::set_rate_provider(
+        &mut ctx,
+        &pool,
+        RateProvider::Authority {
+            rate_updater: rate_updater.pubkey(),
+            token_b_rate: TOKEN_B_RATE,
+            max_rate_age_sec: MAX_RATE_AGE_SEC,
+            max_rate_change_bps: MAX_RATE_CHANGE_BPS,
+        },
+    )
+    .await
+    .unwrap();
+
+    // proportional withdrawals do not depend on the rate, so a stale rate does not lock LPs out
+    set_unix_timestamp(&mut ctx, 1_000 + MAX_RATE_AGE_SEC as i64 + 1).await;
+    let admin: PoolUserAccounts = pool.admin.clone().into();
+    let token_a_balance = token_operations::balance(&mut ctx, &admin.token_a_ata).await;
+    let token_b_balance = token_operations::balance(&mut ctx, &admin.token_b_ata).await;
+    client::withdraw(
+        &mut ctx,
+        &pool,
+        &admin,
+        Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64 / 2, 1, 1),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_operations::balance(&mut ctx, &admin.token_a_ata).await,
+        token_a_balance + 1_000_000
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &admin.token_b_ata).await,
+        token_b_balance + 500_000
+    );
+}
+
+#[tokio::test]
+pub async fn test_swap_at_stake_pool_rate() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = new_lst_pool(&mut ctx).await;
+    let epoch = current_epoch(&mut ctx).await;
+    let stake_pool_key = Pubkey::new_unique();
+    set_stake_pool(&mut ctx, &stake_pool_key, stake_pool(&pool, epoch));
+    client::set_rate_provider(
+        &mut ctx,
+        &pool,
+        RateProvider::StakePool {
+            stake_pool: stake_pool_key,
+        },
+    )
+    .await
+    .unwrap();
+
+    let curve = state::get_stable_curve(&mut ctx, &pool).await;
+    assert_eq!(
+        curve.rate_provider_type,
+        u64::from(RateProviderType::StakePool)
+    );
+    assert_eq!(curve.rate_provider, stake_pool_key);
+    assert_eq!(curve.token_b_rate, TOKEN_B_RATE);
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 0)).await;
+
+    // the stake pool is missing
+    assert_eq!(
+        swap(
+            &mut ctx,
+            &pool,
+            &user,
+            None,
+            TradeDirection::AtoB,
+            Swap::new(1_000, 495),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::InvalidRateProvider)
+    );
+
+    swap(
+        &mut ctx,
+        &pool,
+        &user,
+        Some(&stake_pool_key),
+        TradeDirection::AtoB,
+        Swap::new(1_000, 495),
+    )
+    .await
+    .unwrap();
+    let token_b_amount = token_operations::balance(&mut ctx, &user.token_b_ata).await;
+    assert!((495..=500).contains(&token_b_amount));
+
+    // the stake pool has not been updated this epoch
+    let mut clock: Clock = ctx.context.banks_client.get_sysvar().await.unwrap();
+    clock.epoch = epoch + 1;
+    ctx.context.set_sysvar(&clock);
+    assert_eq!(
+        swap(
+            &mut ctx,
+            &pool,
+            &user,
+            Some(&stake_pool_key),
+            TradeDirection::BtoA,
+            Swap::new(token_b_amount, 0),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::StaleRate)
+    );
+}
+
+#[tokio::test]
+pub async fn test_set_stake_pool_of_other_mint_fails() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = new_lst_pool(&mut ctx).await;
+    let epoch = current_epoch(&mut ctx).await;
+    let stake_pool_key = Pubkey::new_unique();
+    set_stake_pool(
+        &mut ctx,
+        &stake_pool_key,
+        StakePoolRate {
+            pool_mint: pool.token_a_mint,
+            ..stake_pool(&pool, epoch)
+        },
+    );
+
+    assert_eq!(
+        client::set_rate_provider(
+            &mut ctx,
+            &pool,
+            RateProvider::StakePool {
+                stake_pool: stake_pool_key,
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::InvalidRateProvider)
+    );
+}
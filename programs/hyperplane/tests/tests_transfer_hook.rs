@@ -0,0 +1,290 @@
+mod common;
+
+use common::{client, runner};
+use hyperplane::{
+    curve::{
+        calculator::{AorB, TradeDirection, INITIAL_SWAP_POOL_AMOUNT},
+        fees::Fees,
+    },
+    error::SwapError,
+    ix::{Initialize, Swap, UpdateGlobalConfig, Withdraw, WithdrawFees},
+    state::{UpdateGlobalConfigMode, UpdateGlobalConfigValue},
+    utils::token_extensions::MintExtension,
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+
+use crate::common::{
+    fixtures::{self, ProgramDependency},
+    setup, token_operations, transfer_hook,
+    types::{SwapPairSpec, TestContext, TokenSpec},
+};
+
+fn owner_fees() -> Fees {
+    Fees {
+        trade_fee_numerator: 1,
+        trade_fee_denominator: 100,
+        owner_trade_fee_numerator: 1,
+        owner_trade_fee_denominator: 100,
+        ..Default::default()
+    }
+}
+
+/// Transfer hooks are opt-in, allow them in the global config
+async fn allow_transfer_hooks(ctx: &mut TestContext) {
+    let owner = ctx.global_config_owner.clone();
+    client::update_global_config(
+        ctx,
+        &owner,
+        UpdateGlobalConfig::new(
+            UpdateGlobalConfigMode::AllowedTokenExtensions,
+            UpdateGlobalConfigValue::TokenExtensions(vec![MintExtension::TransferHook.into()]),
+        ),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+pub async fn test_initialize_pool_with_transfer_hook_not_allowed_fails() {
+    let program = runner::program(&[ProgramDependency::TransferHook]);
+    let mut ctx = runner::start(program).await;
+
+    let initial_supply = InitialSupply::new(1_000_000, 1_000_000);
+    let pool = setup::new_pool_accs(
+        &mut ctx,
+        SwapPairSpec::new(TokenSpec::transfer_hook(), TokenSpec::default()),
+        &initial_supply,
+    )
+    .await;
+    assert_eq!(
+        client::initialize_pool(
+            &mut ctx,
+            &pool,
+            Initialize {
+                fees: Fees::default(),
+                initial_supply,
+                curve_parameters: CurveUserParameters::ConstantProduct,
+                pool_token_metadata: None,
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::InvalidTokenExtension, 1)
+    );
+}
+
+#[tokio::test]
+pub async fn test_swap_with_transfer_hook() {
+    let program = runner::program(&[ProgramDependency::TransferHook]);
+    let mut ctx = runner::start(program).await;
+    allow_transfer_hooks(&mut ctx).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(1_000_000, 1_000_000),
+        SwapPairSpec::new(TokenSpec::transfer_hook(), TokenSpec::default()),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+    // the initial supply deposit
+    assert_eq!(
+        transfer_hook::transfers(&mut ctx, &pool.token_a_mint).await,
+        1
+    );
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 1_000)).await;
+    client::swap(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        Swap::new(1_000, 990),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.token_a_ata).await,
+        0
+    );
+    assert_eq!(
+        transfer_hook::transfers(&mut ctx, &pool.token_a_mint).await,
+        2
+    );
+
+    client::swap(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::BtoA,
+        Swap::new(1_000, 990),
+    )
+    .await
+    .unwrap();
+    assert!(token_operations::balance(&mut ctx, &user.token_a_ata).await >= 990);
+    assert_eq!(
+        transfer_hook::transfers(&mut ctx, &pool.token_a_mint).await,
+        3
+    );
+}
+
+#[tokio::test]
+pub async fn test_deposit_and_withdraw_with_transfer_hooks() {
+    let program = runner::program(&[ProgramDependency::TransferHook]);
+    let mut ctx = runner::start(program).await;
+    allow_transfer_hooks(&mut ctx).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(1_000_000, 1_000_000),
+        SwapPairSpec::new(TokenSpec::transfer_hook(), TokenSpec::transfer_hook()),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    let user = setup::new_lp_user(&mut ctx, &pool, (1_000, 1_000)).await;
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.token_a_ata).await,
+        0
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.token_b_ata).await,
+        0
+    );
+    assert_eq!(
+        transfer_hook::transfers(&mut ctx, &pool.token_a_mint).await,
+        2
+    );
+    assert_eq!(
+        transfer_hook::transfers(&mut ctx, &pool.token_b_mint).await,
+        2
+    );
+
+    let pool_tokens = token_operations::balance(&mut ctx, &user.pool_token_ata).await;
+    client::withdraw(&mut ctx, &pool, &user, Withdraw::new(pool_tokens, 1, 1))
+        .await
+        .unwrap();
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.pool_token_ata).await,
+        0
+    );
+    assert!(token_operations::balance(&mut ctx, &user.token_a_ata).await > 0);
+    assert!(token_operations::balance(&mut ctx, &user.token_b_ata).await > 0);
+    assert_eq!(
+        transfer_hook::transfers(&mut ctx, &pool.token_a_mint).await,
+        3
+    );
+    assert_eq!(
+        transfer_hook::transfers(&mut ctx, &pool.token_b_mint).await,
+        3
+    );
+
+    client::withdraw(
+        &mut ctx,
+        &pool,
+        &pool.admin.clone().into(),
+        Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64, 1, 1),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        transfer_hook::transfers(&mut ctx, &pool.token_a_mint).await,
+        4
+    );
+    assert_eq!(
+        transfer_hook::transfers(&mut ctx, &pool.token_b_mint).await,
+        4
+    );
+}
+
+#[tokio::test]
+pub async fn test_withdraw_fees_with_transfer_hook() {
+    let program = runner::program(&[ProgramDependency::TransferHook]);
+    let mut ctx = runner::start(program).await;
+    allow_transfer_hooks(&mut ctx).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        owner_fees(),
+        InitialSupply::new(1_000_000, 1_000_000),
+        SwapPairSpec::new(TokenSpec::transfer_hook(), TokenSpec::default()),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 0)).await;
+    client::swap(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        Swap::new(1_000, 970),
+    )
+    .await
+    .unwrap();
+    // the initial supply deposit, then the swap and its owner fee
+    assert_eq!(
+        transfer_hook::transfers(&mut ctx, &pool.token_a_mint).await,
+        3
+    );
+
+    let owner_fees = token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await;
+    assert!(owner_fees > 0);
+    let admin_balance = token_operations::balance(&mut ctx, &pool.admin.token_a_ata).await;
+    client::withdraw_fees(&mut ctx, &pool, AorB::A, WithdrawFees::new(owner_fees))
+        .await
+        .unwrap();
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.token_a_fees_vault).await,
+        0
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &pool.admin.token_a_ata).await,
+        admin_balance + owner_fees
+    );
+    assert_eq!(
+        transfer_hook::transfers(&mut ctx, &pool.token_a_mint).await,
+        4
+    );
+}
+
+#[tokio::test]
+pub async fn test_swap_without_transfer_hook_accounts_fails() {
+    let program = runner::program(&[ProgramDependency::TransferHook]);
+    let mut ctx = runner::start(program).await;
+    allow_transfer_hooks(&mut ctx).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(1_000_000, 1_000_000),
+        SwapPairSpec::new(TokenSpec::transfer_hook(), TokenSpec::default()),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 0)).await;
+    assert_eq!(
+        send_tx!(
+            ctx,
+            [client::instructions::swap(
+                &pool,
+                &user,
+                None,
+                TradeDirection::AtoB,
+                Swap::new(1_000, 990)
+            )],
+            user.user.as_ref()
+        )
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::MissingTransferHookAccounts)
+    );
+    assert_eq!(
+        token_operations::balance(&mut ctx, &user.token_a_ata).await,
+        1_000
+    );
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::{client, runner};
+use hyperplane::{
+    curve::{
+        calculator::{CurveCalculator, TradeDirection},
+        fees::Fees,
+    },
+    ix::{Swap, Withdraw},
+    state::{ConstantProductCurve, PriceAccumulator},
+    twap::{get_twap, PRICE_SCALE},
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+
+use crate::common::{
+    fixtures, setup, state, token_operations, types::SwapPairSpec, types::SwapPoolAccounts,
+    types::TestContext, utils::set_unix_timestamp,
+};
+
+async fn vault_balances(ctx: &mut TestContext, pool: &SwapPoolAccounts) -> (u128, u128) {
+    (
+        token_operations::balance(ctx, &pool.token_a_vault).await as u128,
+        token_operations::balance(ctx, &pool.token_b_vault).await as u128,
+    )
+}
+
+#[tokio::test]
+pub async fn test_twap_accumulates_on_swap_deposit_and_withdraw() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    set_unix_timestamp(&mut ctx, 1_000).await;
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(1_000_000, 2_000_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+    assert_eq!(
+        state::get_pool(&mut ctx, &pool).await.price_accumulator,
+        PriceAccumulator::new(1_000)
+    );
+
+    // 2 B per A for 100 seconds before the swap
+    let user = setup::new_pool_user(&mut ctx, &pool, (10_000, 0)).await;
+    set_unix_timestamp(&mut ctx, 1_100).await;
+    client::swap(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        Swap::new(10_000, 0),
+    )
+    .await
+    .unwrap();
+
+    let after_swap = state::get_pool(&mut ctx, &pool).await.price_accumulator;
+    assert_eq!(after_swap.last_update_ts, 1_100);
+    assert_eq!(after_swap.price_a_cumulative(), 200 * PRICE_SCALE);
+    assert_eq!(after_swap.price_b_cumulative(), 50 * PRICE_SCALE);
+
+    // the deposit accumulates the post swap price
+    let (token_a_amount, token_b_amount) = vault_balances(&mut ctx, &pool).await;
+    set_unix_timestamp(&mut ctx, 1_400).await;
+    let lp = setup::new_lp_user(&mut ctx, &pool, (100_000, 200_000)).await;
+
+    let after_deposit = state::get_pool(&mut ctx, &pool).await.price_accumulator;
+    assert_eq!(after_deposit.last_update_ts, 1_400);
+    let twap = get_twap(&after_swap, &after_deposit).unwrap();
+    let curve = ConstantProductCurve::default();
+    assert_eq!(
+        twap.price_a,
+        curve
+            .spot_price(token_a_amount, token_b_amount, TradeDirection::AtoB)
+            .unwrap()
+    );
+    assert_eq!(
+        twap.price_b,
+        curve
+            .spot_price(token_a_amount, token_b_amount, TradeDirection::BtoA)
+            .unwrap()
+    );
+
+    // the withdrawal accumulates the post deposit price
+    let (token_a_amount, token_b_amount) = vault_balances(&mut ctx, &pool).await;
+    set_unix_timestamp(&mut ctx, 2_000).await;
+    let lp_pool_tokens = token_operations::balance(&mut ctx, &lp.pool_token_ata).await;
+    client::withdraw(&mut ctx, &pool, &lp, Withdraw::new(lp_pool_tokens, 0, 0))
+        .await
+        .unwrap();
+
+    let after_withdraw = state::get_pool(&mut ctx, &pool).await.price_accumulator;
+    assert_eq!(after_withdraw.last_update_ts, 2_000);
+    let twap = get_twap(&after_deposit, &after_withdraw).unwrap();
+    assert_eq!(
+        twap.price_a,
+        curve
+            .spot_price(token_a_amount, token_b_amount, TradeDirection::AtoB)
+            .unwrap()
+    );
+    assert_eq!(
+        twap.price_b,
+        curve
+            .spot_price(token_a_amount, token_b_amount, TradeDirection::BtoA)
+            .unwrap()
+    );
+
+    // the average over the whole period is weighted by the time each price was in effect
+    let twap = get_twap(&PriceAccumulator::new(1_000), &after_withdraw).unwrap();
+    assert!(twap.price_a.less_than(
+        &get_twap(&PriceAccumulator::new(1_000), &after_swap)
+            .unwrap()
+            .price_a
+    ));
+}
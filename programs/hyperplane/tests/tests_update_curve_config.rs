@@ -0,0 +1,153 @@
+mod common;
+
+use common::{client, runner};
+use hyperplane::{
+    curve::{calculator::TradeDirection, fees::Fees},
+    error::SwapError,
+    ix::{Swap, UpdateCurveConfig},
+    state::{UpdateCurveConfigMode, UpdateCurveConfigValue},
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+
+use crate::common::{
+    fixtures, setup, setup::default_supply, state, token_operations, types::SwapPairSpec,
+};
+
+#[tokio::test]
+pub async fn test_update_constant_price_curve_token_b_price() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(1_000_000, 1_000_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantPrice { token_b_price: 1 },
+    )
+    .await;
+
+    client::update_curve_config(
+        &mut ctx,
+        &pool,
+        UpdateCurveConfig::new(
+            UpdateCurveConfigMode::TokenBPrice,
+            UpdateCurveConfigValue::U64(2),
+        ),
+    )
+    .await
+    .unwrap();
+
+    let curve = state::get_constant_price_curve(&mut ctx, &pool).await;
+    assert_eq!(curve.token_b_price, 2);
+
+    // swaps are priced at the new peg
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 0)).await;
+    client::swap(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        Swap::new(1_000, 500),
+    )
+    .await
+    .unwrap();
+
+    let user_token_b_balance = token_operations::balance(&mut ctx, &user.token_b_ata).await;
+    assert_eq!(user_token_b_balance, 500);
+}
+
+#[tokio::test]
+pub async fn test_update_offset_curve_token_b_offset() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(1_000_000, 0),
+        SwapPairSpec::default(),
+        CurveUserParameters::Offset {
+            token_b_offset: 1_000_000,
+        },
+    )
+    .await;
+
+    client::update_curve_config(
+        &mut ctx,
+        &pool,
+        UpdateCurveConfig::new(
+            UpdateCurveConfigMode::TokenBOffset,
+            UpdateCurveConfigValue::U64(2_000_000),
+        ),
+    )
+    .await
+    .unwrap();
+
+    let curve = state::get_offset_curve(&mut ctx, &pool).await;
+    assert_eq!(curve.token_b_offset, 2_000_000);
+}
+
+#[tokio::test]
+pub async fn test_update_curve_config_fails_with_invalid_value() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        default_supply(),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantPrice { token_b_price: 1 },
+    )
+    .await;
+
+    assert_eq!(
+        client::update_curve_config(
+            &mut ctx,
+            &pool,
+            UpdateCurveConfig::new(
+                UpdateCurveConfigMode::TokenBPrice,
+                UpdateCurveConfigValue::U64(0),
+            ),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::InvalidCurve)
+    );
+
+    let curve = state::get_constant_price_curve(&mut ctx, &pool).await;
+    assert_eq!(curve.token_b_price, 1);
+}
+
+#[tokio::test]
+pub async fn test_update_curve_config_fails_with_mode_for_other_curve_type() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        default_supply(),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantPrice { token_b_price: 1 },
+    )
+    .await;
+
+    assert_eq!(
+        client::update_curve_config(
+            &mut ctx,
+            &pool,
+            UpdateCurveConfig::new(
+                UpdateCurveConfigMode::TokenBOffset,
+                UpdateCurveConfigValue::U64(1_000),
+            ),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::UnsupportedCurveOperation)
+    );
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use common::{client, runner};
+use hyperplane::{
+    curve::{calculator::TradeDirection, fees::Fees},
+    error::SwapError,
+    ix::{Swap, UpdatePoolConfig},
+    state::{UpdatePoolConfigMode, UpdatePoolConfigValue},
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+
+use crate::common::{
+    fixtures, setup, setup::default_supply, state, token_operations, types::SwapPairSpec,
+};
+
+#[tokio::test]
+pub async fn test_update_pool_config_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        default_supply(),
+        SwapPairSpec::default(),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    for (mode, numerator, denominator) in [
+        (UpdatePoolConfigMode::TradeFee, 1, 1_000),
+        (UpdatePoolConfigMode::OwnerTradeFee, 2, 1_000),
+        (UpdatePoolConfigMode::OwnerWithdrawFee, 3, 1_000),
+        (UpdatePoolConfigMode::HostFee, 4, 1_000),
+    ] {
+        client::update_pool_config(
+            &mut ctx,
+            &pool,
+            UpdatePoolConfig::new(
+                mode,
+                UpdatePoolConfigValue::Fee {
+                    numerator,
+                    denominator,
+                },
+            ),
+        )
+        .await
+        .unwrap();
+    }
+
+    let pool_state = state::get_pool(&mut ctx, &pool).await;
+    assert_eq!(
+        pool_state.fees,
+        Fees {
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 1_000,
+            owner_trade_fee_numerator: 2,
+            owner_trade_fee_denominator: 1_000,
+            owner_withdraw_fee_numerator: 3,
+            owner_withdraw_fee_denominator: 1_000,
+            host_fee_numerator: 4,
+            host_fee_denominator: 1_000,
+        }
+    );
+}
+
+#[tokio::test]
+pub async fn test_update_pool_config_fees_fails_with_invalid_fee() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        default_supply(),
+        SwapPairSpec::default(),
+        CurveUserParameters::Stable { amp: 100 },
+    )
+    .await;
+
+    for (numerator, denominator) in [(1, 1), (2, 1), (1, 0)] {
+        assert_eq!(
+            client::update_pool_config(
+                &mut ctx,
+                &pool,
+                UpdatePoolConfig::new(
+                    UpdatePoolConfigMode::TradeFee,
+                    UpdatePoolConfigValue::Fee {
+                        numerator,
+                        denominator,
+                    },
+                ),
+            )
+            .await
+            .unwrap_err()
+            .unwrap(),
+            hyperplane_error!(SwapError::InvalidFee)
+        );
+    }
+
+    let pool_state = state::get_pool(&mut ctx, &pool).await;
+    assert_eq!(pool_state.fees, Fees::default());
+}
+
+#[tokio::test]
+pub async fn test_swap_charges_updated_trade_fee() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(1_000_000, 1_000_000),
+        SwapPairSpec::default(),
+        CurveUserParameters::ConstantPrice { token_b_price: 1 },
+    )
+    .await;
+
+    client::update_pool_config(
+        &mut ctx,
+        &pool,
+        UpdatePoolConfig::new(
+            UpdatePoolConfigMode::TradeFee,
+            UpdatePoolConfigValue::Fee {
+                numerator: 1,
+                denominator: 100,
+            },
+        ),
+    )
+    .await
+    .unwrap();
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (1_000, 0)).await;
+    // 1% trade fee is taken from the input amount, the rest is swapped 1:1
+    client::swap(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        Swap::new(1_000, 990),
+    )
+    .await
+    .unwrap();
+
+    let user_token_b_balance = token_operations::balance(&mut ctx, &user.token_b_ata).await;
+    assert_eq!(user_token_b_balance, 990);
+}
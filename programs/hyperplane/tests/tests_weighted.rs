@@ -0,0 +1,103 @@
+mod common;
+
+use common::{client, runner};
+use hyperplane::{
+    curve::{base::CurveType, calculator::TradeDirection, fees::Fees},
+    error::SwapError,
+    ix::{Initialize, Swap},
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+
+use crate::common::{fixtures, setup, state, token_operations, types::SwapPairSpec};
+
+/// 80/20 pool, balanced by value at a price of 1 with 800 token A and 200 token B
+const TOKEN_A_WEIGHT_BPS: u64 = 8_000;
+
+#[tokio::test]
+pub async fn test_success_init_weighted_pool() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(800, 200),
+        SwapPairSpec::default(),
+        CurveUserParameters::Weighted {
+            token_a_weight_bps: TOKEN_A_WEIGHT_BPS,
+        },
+    )
+    .await;
+
+    let pool_state = state::get_pool(&mut ctx, &pool).await;
+    assert_eq!(pool_state.curve_type, CurveType::Weighted as u64);
+
+    let curve = state::get_weighted_curve(&mut ctx, &pool).await;
+    assert_eq!(curve.token_a_weight_bps, 8_000);
+    assert_eq!(curve.token_b_weight_bps, 2_000);
+}
+
+#[tokio::test]
+pub async fn test_swap_eighty_twenty() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(800, 200),
+        SwapPairSpec::default(),
+        CurveUserParameters::Weighted {
+            token_a_weight_bps: TOKEN_A_WEIGHT_BPS,
+        },
+    )
+    .await;
+
+    let user = setup::new_pool_user(&mut ctx, &pool, (100, 0)).await;
+
+    // 200 * (1 - (800 / 900)^(0.8 / 0.2)) = 75.14...
+    client::swap(
+        &mut ctx,
+        &pool,
+        &user,
+        TradeDirection::AtoB,
+        Swap::new(100, 75),
+    )
+    .await
+    .unwrap();
+
+    let vault_a_balance = token_operations::balance(&mut ctx, &pool.token_a_vault).await;
+    assert_eq!(vault_a_balance, 900);
+    let vault_b_balance = token_operations::balance(&mut ctx, &pool.token_b_vault).await;
+    assert_eq!(vault_b_balance, 125);
+    let user_b_balance = token_operations::balance(&mut ctx, &user.token_b_ata).await;
+    assert_eq!(user_b_balance, 75);
+}
+
+#[tokio::test]
+pub async fn test_initialize_pool_fails_with_invalid_weights() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let initial_supply = InitialSupply::new(800, 200);
+    let pool = setup::new_pool_accs(&mut ctx, SwapPairSpec::default(), &initial_supply).await;
+    assert_eq!(
+        client::initialize_pool(
+            &mut ctx,
+            &pool,
+            Initialize {
+                fees: Fees::default(),
+                initial_supply,
+                curve_parameters: CurveUserParameters::Weighted {
+                    token_a_weight_bps: 9_900,
+                },
+                pool_token_metadata: None,
+            },
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::InvalidCurve)
+    );
+}
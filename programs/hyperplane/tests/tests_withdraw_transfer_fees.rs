@@ -0,0 +1,212 @@
+mod common;
+
+use common::{client, runner};
+use hyperplane::{
+    curve::{calculator::INITIAL_SWAP_POOL_AMOUNT, fees::Fees},
+    error::SwapError,
+    ix::Withdraw,
+    CurveUserParameters, InitialSupply,
+};
+use solana_program_test::tokio::{self};
+use solana_sdk::signer::Signer;
+
+use crate::common::{
+    fixtures, token_operations,
+    types::{SwapPairSpec, SwapPoolAccounts, TestContext, TokenSpec},
+};
+
+fn withdraw_fees() -> Fees {
+    Fees {
+        owner_withdraw_fee_numerator: 1,
+        owner_withdraw_fee_denominator: 100,
+        ..Default::default()
+    }
+}
+
+async fn assert_balances(
+    ctx: &mut TestContext,
+    pool: &SwapPoolAccounts,
+    (admin_token_a, admin_token_b): (u64, u64),
+    (token_a_fees, token_b_fees): (u64, u64),
+) {
+    let pool_token_supply = token_operations::supply(ctx, &pool.pool_token_mint).await;
+    assert_eq!(pool_token_supply, 0);
+    let admin_pool_token_balance =
+        token_operations::balance(ctx, &pool.admin.pool_token_ata.pubkey()).await;
+    assert_eq!(admin_pool_token_balance, 0);
+
+    let admin_token_a_balance = token_operations::balance(ctx, &pool.admin.token_a_ata).await;
+    assert_eq!(admin_token_a_balance, admin_token_a);
+    let admin_token_b_balance = token_operations::balance(ctx, &pool.admin.token_b_ata).await;
+    assert_eq!(admin_token_b_balance, admin_token_b);
+
+    let token_a_vault_balance = token_operations::balance(ctx, &pool.token_a_vault).await;
+    assert_eq!(token_a_vault_balance, 0);
+    let token_b_vault_balance = token_operations::balance(ctx, &pool.token_b_vault).await;
+    assert_eq!(token_b_vault_balance, 0);
+
+    let token_a_fee_vault_balance = token_operations::balance(ctx, &pool.token_a_fees_vault).await;
+    assert_eq!(token_a_fee_vault_balance, token_a_fees);
+    let token_b_fee_vault_balance = token_operations::balance(ctx, &pool.token_b_fees_vault).await;
+    assert_eq!(token_b_fee_vault_balance, token_b_fees);
+}
+
+#[tokio::test]
+pub async fn test_withdraw_with_a_transfer_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        withdraw_fees(),
+        InitialSupply::new(1_000_000, 1_000_000),
+        SwapPairSpec::new(TokenSpec::transfer_fees(100), TokenSpec::default()),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    // 1_000_000 - 1% withdraw fee = 990_000 - 1% transfer fee = 980_100
+    client::withdraw(
+        &mut ctx,
+        &pool,
+        &pool.admin.clone().into(),
+        Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64, 980_100, 990_000),
+    )
+    .await
+    .unwrap();
+
+    // 10_000 withdraw fee - 1% transfer fee = 9_900
+    assert_balances(&mut ctx, &pool, (980_100, 990_000), (9_900, 10_000)).await;
+}
+
+#[tokio::test]
+pub async fn test_withdraw_with_b_transfer_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        withdraw_fees(),
+        InitialSupply::new(1_000_000, 1_000_000),
+        SwapPairSpec::new(TokenSpec::default(), TokenSpec::transfer_fees(100)),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    client::withdraw(
+        &mut ctx,
+        &pool,
+        &pool.admin.clone().into(),
+        Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64, 990_000, 980_100),
+    )
+    .await
+    .unwrap();
+
+    assert_balances(&mut ctx, &pool, (990_000, 980_100), (10_000, 9_900)).await;
+}
+
+#[tokio::test]
+pub async fn test_withdraw_with_a_and_b_transfer_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        withdraw_fees(),
+        InitialSupply::new(1_000_000, 1_000_000),
+        SwapPairSpec::new(TokenSpec::transfer_fees(100), TokenSpec::transfer_fees(50)),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    // 990_000 - 0.5% transfer fee = 985_050
+    client::withdraw(
+        &mut ctx,
+        &pool,
+        &pool.admin.clone().into(),
+        Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64, 980_100, 985_050),
+    )
+    .await
+    .unwrap();
+
+    // 10_000 withdraw fee - 0.5% transfer fee = 9_950
+    assert_balances(&mut ctx, &pool, (980_100, 985_050), (9_900, 9_950)).await;
+}
+
+#[tokio::test]
+pub async fn test_withdraw_with_a_and_b_transfer_fees_and_no_withdraw_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        Fees::default(),
+        InitialSupply::new(1_000_000, 1_000_000),
+        SwapPairSpec::new(TokenSpec::transfer_fees(100), TokenSpec::transfer_fees(50)),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    client::withdraw(
+        &mut ctx,
+        &pool,
+        &pool.admin.clone().into(),
+        Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64, 990_000, 995_000),
+    )
+    .await
+    .unwrap();
+
+    assert_balances(&mut ctx, &pool, (990_000, 995_000), (0, 0)).await;
+}
+
+#[tokio::test]
+pub async fn test_withdraw_slippage_is_checked_against_amount_received_post_transfer_fees() {
+    let program = runner::program(&[]);
+    let mut ctx = runner::start(program).await;
+
+    let pool = fixtures::new_pool(
+        &mut ctx,
+        withdraw_fees(),
+        InitialSupply::new(1_000_000, 1_000_000),
+        SwapPairSpec::new(TokenSpec::transfer_fees(100), TokenSpec::transfer_fees(100)),
+        CurveUserParameters::ConstantProduct,
+    )
+    .await;
+
+    // 990_000 leaves the vault but only 980_100 is received
+    assert_eq!(
+        client::withdraw(
+            &mut ctx,
+            &pool,
+            &pool.admin.clone().into(),
+            Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64, 990_000, 980_100),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::ExceededSlippage)
+    );
+    assert_eq!(
+        client::withdraw(
+            &mut ctx,
+            &pool,
+            &pool.admin.clone().into(),
+            Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64, 980_100, 980_101),
+        )
+        .await
+        .unwrap_err()
+        .unwrap(),
+        hyperplane_error!(SwapError::ExceededSlippage)
+    );
+
+    client::withdraw(
+        &mut ctx,
+        &pool,
+        &pool.admin.clone().into(),
+        Withdraw::new(INITIAL_SWAP_POOL_AMOUNT as u64, 980_100, 980_100),
+    )
+    .await
+    .unwrap();
+
+    assert_balances(&mut ctx, &pool, (980_100, 980_100), (9_900, 9_900)).await;
+}
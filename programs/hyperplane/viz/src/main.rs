@@ -1,7 +1,9 @@
 mod stable;
+mod weighted;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     stable::plot("stable.svg")?;
+    weighted::plot("weighted.svg")?;
 
     Ok(())
 }
@@ -0,0 +1,110 @@
+#![allow(clippy::arithmetic_side_effects)]
+
+use std::iter::Chain;
+
+use hyperplane::{
+    curve::calculator::{CurveCalculator, SwapWithoutFeesResult, TradeDirection},
+    state,
+};
+use plotters::prelude::*;
+
+pub fn plot(output_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let root = SVGBackend::new(output_path, (640, 640)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Weighted Curve", ("sans-serif", 30).into_font())
+        .margin(5)
+        .x_label_area_size(40)
+        .y_label_area_size(40)
+        .build_cartesian_2d(0_u128..30_000_u128, 0_u128..30_000_u128)?;
+
+    chart.configure_mesh().draw()?;
+
+    chart
+        .draw_series(series(5_000, RED))?
+        .label("50/50")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+    chart
+        .draw_series(series(8_000, GREEN))?
+        .label("80/20")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], GREEN));
+    chart
+        .draw_series(series(2_000, BLUE))?
+        .label("20/80")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+    chart
+        .draw_series(series(9_800, MAGENTA))?
+        .label("98/2")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], MAGENTA));
+
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+
+    root.present()?;
+
+    Ok(())
+}
+
+#[allow(clippy::type_complexity)]
+fn series<DB: DrawingBackend>(
+    token_a_weight_bps: u64,
+    colour: RGBColor,
+) -> Chain<LineSeries<DB, (u128, u128)>, LineSeries<DB, (u128, u128)>> {
+    let curve = state::WeightedCurve::new(token_a_weight_bps);
+
+    // Plot 2 series, one for buy x, one for sell x
+    // Each series starts with a pool of 10k x and 10k y
+    let ((mut sell_pool_x_amt, mut sell_pool_y_amt), (mut buy_pool_x_amt, mut buy_pool_y_amt)) =
+        ((10_000_u128, 10_000_u128), (10_000_u128, 10_000_u128));
+    // number of points to plot for each pool
+    // a.k.a. number of swaps to simulate in each direction
+    let plot_range = 1_000_u128;
+    // amount to x or y to swap each iteration
+    // stays constant
+    let swap_amt = 100;
+
+    // with skewed weights the pool can run out of the bought token before the
+    // end of the range, in which case the series stops there
+    let buy_x_points = (1..=plot_range).map_while(|_| {
+        let SwapWithoutFeesResult {
+            source_amount_swapped,
+            destination_amount_swapped,
+        } = curve
+            .swap_without_fees(
+                swap_amt,
+                buy_pool_y_amt,
+                buy_pool_x_amt,
+                TradeDirection::BtoA,
+            )
+            .ok()?;
+
+        buy_pool_x_amt -= destination_amount_swapped; // pool x shrinks
+        buy_pool_y_amt += source_amount_swapped; // pool y grows
+
+        Some((buy_pool_x_amt, buy_pool_y_amt))
+    });
+
+    let sell_x_points = (1..=plot_range).map_while(|_| {
+        let SwapWithoutFeesResult {
+            source_amount_swapped,
+            destination_amount_swapped,
+        } = curve
+            .swap_without_fees(
+                swap_amt,
+                sell_pool_x_amt,
+                sell_pool_y_amt,
+                TradeDirection::AtoB,
+            )
+            .ok()?;
+
+        sell_pool_x_amt += source_amount_swapped; // pool x grows
+        sell_pool_y_amt -= destination_amount_swapped; // pool y shrinks
+
+        Some((sell_pool_x_amt, sell_pool_y_amt))
+    });
+
+    LineSeries::new(buy_x_points, colour).chain(LineSeries::new(sell_x_points, colour))
+}